@@ -1,4 +1,11 @@
-use std::{ffi::OsStr, io, path::Path, str::FromStr, time::Instant};
+use std::{
+    ffi::OsStr,
+    io,
+    path::Path,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
 
 use anyhow::anyhow;
 use gix::{
@@ -68,6 +75,11 @@ pub struct Context<W> {
     /// If true, delta objects may refer to their base as reference, allowing it not to be included in the created back.
     /// Otherwise these have to be recompressed in order to make the pack self-contained.
     pub thin: bool,
+    /// If true, objects will be decoded and compressed as deltas against similar objects, which is slow but yields
+    /// the smallest packs. The `pack.window` and `pack.depth` configuration of the repository controls how much effort is spent.
+    ///
+    /// Otherwise, deltas are copied from existing packs and all other objects are stored as is.
+    pub delta_compression: bool,
     /// If set, don't use more than this amount of threads.
     /// Otherwise, usually use as many threads as there are logical cores.
     /// A value of 0 is interpreted as no-limit
@@ -100,6 +112,7 @@ pub fn create<W, P>(
         expansion,
         nondeterministic_thread_count,
         thin,
+        delta_compression,
         thread_limit,
         statistics,
         pack_cache_size_in_bytes,
@@ -113,6 +126,29 @@ where
     P::SubProgress: 'static,
 {
     let repo = gix::discover(repository_path)?.into_sync();
+    let mode = if delta_compression {
+        use gix::config::tree::{Key, Pack};
+        let repo = repo.to_thread_local();
+        let config = repo.config_snapshot();
+        let config = config.plumbing();
+        pack::data::output::entry::iter_from_counts::Mode::DeltaCompression {
+            window: config
+                .integer_by_key(Pack::WINDOW.logical_name().as_str())
+                .map(|value| Pack::WINDOW.try_into_u32(value))
+                .transpose()?
+                .unwrap_or(10),
+            depth: config
+                .integer_by_key(Pack::DEPTH.logical_name().as_str())
+                .map(|value| Pack::DEPTH.try_into_u32(value))
+                .transpose()?
+                .unwrap_or(50),
+        }
+    } else {
+        pack::data::output::entry::iter_from_counts::Mode::PackCopyAndBaseObjects
+    };
+    // Paths are only known if trees are traversed, and they only matter when computing deltas.
+    let delta_attribute_repo =
+        (delta_compression && expansion != ObjectExpansion::None).then(|| repo.to_thread_local());
     progress.init(Some(2), progress::steps());
     let tips = tips.into_iter();
     let make_cancellation_err = || anyhow!("Cancelled by user");
@@ -166,9 +202,22 @@ where
         }
     };
 
+    let roots = Arc::new(Mutex::new(Vec::new()));
+    let input: Box<dyn Iterator<Item = Result<ObjectId, input_iteration::Error>> + Send> =
+        if delta_attribute_repo.is_some() {
+            let roots = roots.clone();
+            Box::new(input.inspect(move |id| {
+                if let Ok(id) = id {
+                    roots.lock().expect("no panic while holding the lock").push(*id);
+                }
+            }))
+        } else {
+            input
+        };
+
     let mut stats = Statistics::default();
     let chunk_size = 1000; // What's a good value for this?
-    let counts = {
+    let mut counts = {
         let mut progress = progress.add_child("counting");
         progress.init(None, progress::count("objects"));
         let may_use_multiple_threads =
@@ -228,6 +277,10 @@ where
         counts.shrink_to_fit();
         counts
     };
+    if let Some(repo) = delta_attribute_repo {
+        let roots = std::mem::take(&mut *roots.lock().expect("no panic while holding the lock"));
+        repo.mark_objects_without_delta(roots, &mut counts, &interrupt::IS_INTERRUPTED)?;
+    }
 
     progress.inc();
     let num_objects = counts.len();
@@ -239,7 +292,7 @@ where
             progress,
             pack::data::output::entry::iter_from_counts::Options {
                 thread_limit,
-                mode: pack::data::output::entry::iter_from_counts::Mode::PackCopyAndBaseObjects,
+                mode,
                 allow_thin_pack: thin,
                 chunk_size,
                 version: Default::default(),
@@ -329,6 +382,7 @@ fn human_output(
                 missing_objects,
                 objects_copied_from_pack,
                 ref_delta_objects,
+                delta_compressed_objects,
             },
     }: Statistics,
     mut out: impl std::io::Write,
@@ -349,10 +403,11 @@ fn human_output(
     #[rustfmt::skip]
     writeln!(
         out,
-        "\t{:<width$} {}\n\t{:<width$} {}\n\t{:<width$} {}\n\t{:<width$} {}",
+        "\t{:<width$} {}\n\t{:<width$} {}\n\t{:<width$} {}\n\t{:<width$} {}\n\t{:<width$} {}",
         "decoded and recompressed", decoded_and_recompressed_objects,
         "pack-to-pack copies", objects_copied_from_pack,
         "ref-delta-objects", ref_delta_objects,
+        "delta-compressed objects", delta_compressed_objects,
         "missing objects", missing_objects,
         width = width
     )?;
@@ -86,6 +86,7 @@ where
         iteration_mode: ctx.iteration_mode.into(),
         index_version: pack::index::Version::default(),
         object_hash: ctx.object_hash,
+        write_reverse_index: false,
     };
     let out = ctx.out;
     let format = ctx.format;
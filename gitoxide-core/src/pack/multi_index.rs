@@ -29,7 +29,10 @@ pub fn create(
         &mut out,
         progress,
         should_interrupt,
-        gix::odb::pack::multi_index::write::Options { object_hash },
+        gix::odb::pack::multi_index::write::Options {
+            object_hash,
+            write_reverse_index: false,
+        },
     )?;
     out.into_inner()?.commit()?;
     Ok(())
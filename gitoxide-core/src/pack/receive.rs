@@ -375,6 +375,7 @@ fn receive_pack_blocking<W: io::Write>(
         index_version: pack::index::Version::V2,
         iteration_mode: pack::data::input::Mode::Verify,
         object_hash: ctx.object_hash,
+        write_reverse_index: false,
     };
     let outcome =
         pack::Bundle::write_to_directory(input, directory.take(), progress, &ctx.should_interrupt, None, options)
@@ -22,6 +22,7 @@ pub(crate) mod function {
 
     pub fn entries(
         repo: gix::Repository,
+        pathspecs: Vec<gix::bstr::BString>,
         out: impl std::io::Write,
         mut err: impl std::io::Write,
         Options {
@@ -32,6 +33,15 @@ pub(crate) mod function {
     ) -> anyhow::Result<()> {
         use crate::OutputFormat::*;
         let index = repo.index()?;
+        let mut pathspec = match repo.worktree() {
+            Some(worktree) => worktree.pathspec(pathspecs.iter())?,
+            None => repo.pathspec(
+                pathspecs.iter(),
+                false,
+                &index,
+                gix::worktree::cache::state::attributes::Source::IdMapping,
+            )?,
+        };
         let mut cache = attributes
             .map(|attrs| {
                 repo.attributes(
@@ -63,7 +73,7 @@ pub(crate) mod function {
         if let Json = format {
             out.write_all(b"[\n")?;
         }
-        let mut entries = index.entries().iter().peekable();
+        let mut entries = pathspec.index_entries_with_paths(&index).map(|(_, e)| e).peekable();
         while let Some(entry) = entries.next() {
             let attrs = cache
                 .as_mut()
@@ -28,6 +28,7 @@ pub use fetch::function::fetch;
 pub mod index;
 pub mod mailmap;
 pub mod odb;
+pub mod reference;
 pub mod remote;
 pub mod revision;
 pub mod tree;
@@ -166,7 +166,7 @@ pub fn statistics(
                 cancelled,
             ),
             thread_limit,
-            move |_| (repo.objects.clone().into_inner(), counter.clone()),
+            move |_| (repo.objects.clone().into_inner().into_inner(), counter.clone()),
             |ids, (handle, counter)| {
                 let ids = ids?;
                 if let Some(counter) = counter {
@@ -215,3 +215,180 @@ pub fn entries(repo: gix::Repository, format: OutputFormat, mut out: impl io::Wr
 
     Ok(())
 }
+
+pub mod repack {
+    use crate::OutputFormat;
+
+    pub const PROGRESS_RANGE: std::ops::RangeInclusive<u8> = 1..=3;
+
+    #[derive(Debug, Copy, Clone)]
+    pub struct Options {
+        pub format: OutputFormat,
+        pub cruft: bool,
+        pub drop_unreachable: bool,
+        pub expire: Option<std::time::SystemTime>,
+        pub delta_compression: bool,
+        pub write_bitmap: bool,
+        pub thread_limit: Option<usize>,
+    }
+}
+
+pub fn repack(
+    repo: gix::Repository,
+    progress: impl gix::Progress + 'static,
+    mut out: impl io::Write,
+    _err: impl io::Write,
+    should_interrupt: &std::sync::atomic::AtomicBool,
+    repack::Options {
+        format,
+        cruft,
+        drop_unreachable,
+        expire,
+        delta_compression,
+        write_bitmap,
+        thread_limit,
+    }: repack::Options,
+) -> anyhow::Result<()> {
+    use gix::maintenance::repack::Unreachable;
+
+    if format != OutputFormat::Human {
+        bail!("Only human output format is supported at the moment");
+    }
+
+    let mut options = repo.repack_options()?;
+    let configured_expire = match options.unreachable {
+        Unreachable::Drop => None,
+        Unreachable::Loose { expire } | Unreachable::Cruft { expire } => expire,
+    };
+    let expire = expire.or(configured_expire);
+    options.unreachable = if drop_unreachable {
+        Unreachable::Drop
+    } else if cruft || matches!(options.unreachable, Unreachable::Cruft { .. }) {
+        Unreachable::Cruft { expire }
+    } else {
+        Unreachable::Loose { expire }
+    };
+    options.delta_compression |= delta_compression;
+    options.write_bitmap |= write_bitmap;
+    options.thread_limit = thread_limit.or(options.thread_limit);
+
+    let outcome = repo.repack(progress, should_interrupt, options)?;
+    match outcome.pack.as_ref().and_then(|pack| pack.data_path.as_ref()) {
+        Some(path) => writeln!(out, "pack: {}", path.display())?,
+        None => writeln!(out, "No reachable objects to pack")?,
+    }
+    if let Some(path) = outcome.bitmap_path {
+        writeln!(out, "bitmap: {}", path.display())?;
+    }
+    if let Some(path) = outcome.cruft_pack.as_ref().and_then(|pack| pack.data_path.as_ref()) {
+        writeln!(out, "cruft pack: {}", path.display())?;
+    }
+    for path in outcome.removed_packs {
+        writeln!(out, "removed: {}", path.display())?;
+    }
+    writeln!(
+        out,
+        "loosened {} unreachable objects, removed {} loose objects",
+        outcome.loosened_objects, outcome.removed_loose_objects
+    )?;
+    Ok(())
+}
+
+pub mod write_commit_graph {
+    use crate::OutputFormat;
+
+    pub const PROGRESS_RANGE: std::ops::RangeInclusive<u8> = 1..=2;
+
+    #[derive(Debug, Copy, Clone)]
+    pub struct Options {
+        pub format: OutputFormat,
+        pub split: Option<gix::commitgraph::graph::write::Split>,
+        pub changed_paths: bool,
+    }
+}
+
+pub fn write_commit_graph(
+    repo: gix::Repository,
+    progress: impl gix::Progress,
+    mut out: impl io::Write,
+    _err: impl io::Write,
+    should_interrupt: &std::sync::atomic::AtomicBool,
+    write_commit_graph::Options {
+        format,
+        split,
+        changed_paths,
+    }: write_commit_graph::Options,
+) -> anyhow::Result<()> {
+    if format != OutputFormat::Human {
+        bail!("Only human output format is supported at the moment");
+    }
+
+    let mut options = repo.commit_graph_options()?;
+    options.split = split;
+    if changed_paths {
+        options.changed_paths = Some(Default::default());
+    }
+
+    let outcome = repo.write_commit_graph_for_references(progress, should_interrupt, options)?;
+    match outcome.path {
+        Some(path) => writeln!(
+            out,
+            "{}: {} commits, {} file(s) in total",
+            path.display(),
+            outcome.num_commits,
+            outcome.num_files
+        )?,
+        None => writeln!(out, "The commit-graph is up to date")?,
+    }
+    for path in outcome.removed_files {
+        writeln!(out, "removed: {}", path.display())?;
+    }
+    Ok(())
+}
+
+pub mod prune {
+    use crate::OutputFormat;
+
+    pub const PROGRESS_RANGE: std::ops::RangeInclusive<u8> = 1..=2;
+
+    #[derive(Debug, Copy, Clone)]
+    pub struct Options {
+        pub format: OutputFormat,
+        pub expire: Option<std::time::SystemTime>,
+        pub dry_run: bool,
+    }
+}
+
+pub fn prune(
+    repo: gix::Repository,
+    progress: impl gix::Progress,
+    mut out: impl io::Write,
+    mut err: impl io::Write,
+    should_interrupt: &std::sync::atomic::AtomicBool,
+    prune::Options {
+        format,
+        expire,
+        dry_run,
+    }: prune::Options,
+) -> anyhow::Result<()> {
+    if format != OutputFormat::Human {
+        bail!("Only human output format is supported at the moment");
+    }
+
+    let mut options = repo.prune_options()?;
+    options.expire = expire.or(options.expire);
+    options.dry_run = dry_run;
+
+    let outcome = repo.prune(progress, should_interrupt, options)?;
+    for id in &outcome.pruned_objects {
+        writeln!(out, "{id}")?;
+    }
+    writeln!(
+        err,
+        "{} {} unreachable and {} packed loose objects",
+        if dry_run { "Would remove" } else { "Removed" },
+        outcome.pruned_objects.len(),
+        outcome.packed_objects
+    )?;
+    Ok(())
+}
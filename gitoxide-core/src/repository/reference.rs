@@ -0,0 +1,39 @@
+use std::io;
+
+use anyhow::bail;
+
+use crate::OutputFormat;
+
+pub mod pack {
+    use crate::OutputFormat;
+
+    #[derive(Debug, Copy, Clone)]
+    pub struct Options {
+        pub format: OutputFormat,
+        pub all: bool,
+        pub prune: bool,
+    }
+}
+
+pub fn pack(
+    repo: gix::Repository,
+    mut out: impl io::Write,
+    mut err: impl io::Write,
+    pack::Options { format, all, prune }: pack::Options,
+) -> anyhow::Result<()> {
+    use gix::maintenance::pack_refs::{Filter, Options};
+
+    if format != OutputFormat::Human {
+        bail!("Only human output format is supported at the moment");
+    }
+
+    let outcome = repo.pack_refs(Options {
+        filter: if all { Filter::All } else { Filter::TagsAndPacked },
+        prune,
+    })?;
+    for name in &outcome.packed {
+        writeln!(out, "{}", name.as_bstr())?;
+    }
+    writeln!(err, "Packed {} references", outcome.packed.len())?;
+    Ok(())
+}
@@ -16,7 +16,8 @@ impl Outcome {
     /// Initialize this instance to collect outcomes for all names in `collection`, which represents all possible attributes
     /// or macros we may visit, and [`reset`][Self::reset()] it unconditionally.
     ///
-    /// This must be called after each time `collection` changes.
+    /// This must be called after each time `collection` changes. A [selection of attributes][Self::initialize_with_selection()]
+    /// is retained and matched against the names in `collection`.
     pub fn initialize(&mut self, collection: &MetadataCollection) {
         if self.matches_by_id.len() != collection.name_to_meta.len() {
             let global_num_attrs = collection.name_to_meta.len();
@@ -30,6 +31,9 @@ impl Outcome {
                 self.matches_by_id[order].macro_attributes = macro_attributes.clone()
             }
         }
+        for (name, id) in self.selected.iter_mut() {
+            *id = collection.name_to_meta.get(name.as_str()).map(|meta| meta.id);
+        }
         self.reset();
     }
 
@@ -51,10 +51,60 @@ pub fn decode(data: &[u8]) -> Result<(Vec, &[u8]), decode::Error> {
     ))
 }
 
+const RLW_RUNNING_BITS: u64 = 4 * 8;
+const RLW_LARGEST_RUNNING_COUNT: u64 = (1 << RLW_RUNNING_BITS) - 1;
+const RLW_LARGEST_LITERAL_COUNT: u64 = (1 << (64 - 1 - RLW_RUNNING_BITS)) - 1;
+
+mod encode {
+    use super::{Vec, RLW_LARGEST_LITERAL_COUNT, RLW_LARGEST_RUNNING_COUNT, RLW_RUNNING_BITS};
+
+    impl Vec {
+        /// Compress the uncompressed bitmap `words` with `num_bits` valid bits, where bit `n` is found at `words[n / 64] & (1 << (n % 64))`.
+        ///
+        /// Note that bits at or beyond `num_bits` are expected to be unset.
+        pub fn from_words(words: &[u64], num_bits: u32) -> Self {
+            let mut bits = std::vec::Vec::<u64>::new();
+            let mut rlw = 0;
+            let mut remaining = words;
+            while !remaining.is_empty() {
+                let run_bit = remaining[0] == u64::MAX;
+                let clean_word = if run_bit { u64::MAX } else { 0 };
+                let run_len = remaining
+                    .iter()
+                    .take(RLW_LARGEST_RUNNING_COUNT as usize)
+                    .take_while(|w| **w == clean_word)
+                    .count();
+                remaining = &remaining[run_len..];
+                let num_literals = remaining
+                    .iter()
+                    .take(RLW_LARGEST_LITERAL_COUNT as usize)
+                    .take_while(|w| **w != 0 && **w != u64::MAX)
+                    .count();
+
+                rlw = bits.len() as u64;
+                bits.push(run_bit as u64 | ((run_len as u64) << 1) | ((num_literals as u64) << (1 + RLW_RUNNING_BITS)));
+                bits.extend_from_slice(&remaining[..num_literals]);
+                remaining = &remaining[num_literals..];
+            }
+            Vec { num_bits, bits, rlw }
+        }
+
+        /// Write ourselves in the format understood by [`decode()`][super::decode()] to `out`.
+        pub fn write_to(&self, out: &mut dyn std::io::Write) -> std::io::Result<()> {
+            out.write_all(&self.num_bits.to_be_bytes())?;
+            out.write_all(&(self.bits.len() as u32).to_be_bytes())?;
+            for word in &self.bits {
+                out.write_all(&word.to_be_bytes())?;
+            }
+            out.write_all(&(self.rlw as u32).to_be_bytes())
+        }
+    }
+}
+
 mod access {
     use std::convert::{TryFrom, TryInto};
 
-    use super::Vec;
+    use super::{Vec, RLW_LARGEST_RUNNING_COUNT, RLW_RUNNING_BITS};
 
     impl Vec {
         /// Call `f(index)` for each bit that is true, given the index of the bit that identifies it uniquely within the bit array.
@@ -90,6 +140,28 @@ mod access {
             Some(())
         }
 
+        /// Decompress all bits into words of 64 bits each, where bit `n` is found at `words[n / 64] & (1 << (n % 64))`.
+        ///
+        /// The returned vector has enough words to hold [all bits][Self::num_bits()], and the
+        /// inverse operation is [`from_words()`][Self::from_words()].
+        pub fn to_words(&self) -> std::vec::Vec<u64> {
+            let mut words = std::vec::Vec::with_capacity((self.num_bits() + 63) / 64);
+            let mut iter = self.bits.iter();
+            while let Some(word) = iter.next() {
+                let run_word = if rlw_runbit_is_set(word) { u64::MAX } else { 0 };
+                words.extend(std::iter::repeat(run_word).take(rlw_running_len(word) as usize));
+                for _ in 0..rlw_literal_words(word) {
+                    words.push(
+                        *iter
+                            .next()
+                            .expect("BUG: ran out of words while going through uncompressed portion"),
+                    );
+                }
+            }
+            words.resize((self.num_bits() + 63) / 64, 0);
+            words
+        }
+
         /// The amount of bits we are currently holding.
         pub fn num_bits(&self) -> usize {
             self.num_bits.try_into().expect("we are not on 16 bit systems")
@@ -115,9 +187,6 @@ mod access {
     fn rlw_runbit_is_set(w: &u64) -> bool {
         w & 1 == 1
     }
-
-    const RLW_RUNNING_BITS: u64 = 4 * 8;
-    const RLW_LARGEST_RUNNING_COUNT: u64 = (1 << RLW_RUNNING_BITS) - 1;
 }
 
 /// A growable collection of u64 that are seen as stream of individual bits.
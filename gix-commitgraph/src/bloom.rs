@@ -0,0 +1,203 @@
+//! Changed-path Bloom filters as stored in the Bloom Filter Index (BIDX) and Bloom Filter Data (BDAT) chunks.
+//!
+//! Each commit may have a filter which contains all paths that changed compared to its first parent, along with all of
+//! their leading directories. A filter can only tell for sure if a path did *not* change, which allows to skip
+//! tree-diffs in path-limited history traversals.
+use bstr::{BStr, ByteSlice};
+
+/// The maximum amount of changed paths for which a filter is computed. Commits with more changes get a filter that
+/// matches any path.
+pub const MAX_CHANGED_PATHS: usize = 512;
+
+/// The parameters of all filters in a commit-graph file, as stored in the header of the BDAT chunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Settings {
+    /// The version of the murmur3 hash implementation, with 1 being what git uses by default, and 2 being a version that
+    /// handles bytes with the high bit set correctly.
+    pub hash_version: u32,
+    /// The amount of hashes, and thus bits, per path in the filter.
+    pub num_hashes: u32,
+    /// The amount of bits to allocate per path in the filter.
+    pub bits_per_entry: u32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            hash_version: 1,
+            num_hashes: 7,
+            bits_per_entry: 10,
+        }
+    }
+}
+
+impl Settings {
+    /// The size of the settings when stored at the beginning of the BDAT chunk.
+    pub(crate) const SIZE: usize = 3 * 4;
+
+    /// Decode settings from the header of the BDAT chunk, or return `None` if the hash version isn't supported.
+    pub(crate) fn from_bytes(data: &[u8]) -> Option<Self> {
+        let read = |ofs: usize| u32::from_be_bytes(data[ofs..][..4].try_into().expect("4 bytes"));
+        let settings = Settings {
+            hash_version: read(0),
+            num_hashes: read(4),
+            bits_per_entry: read(8),
+        };
+        (matches!(settings.hash_version, 1 | 2) && settings.num_hashes != 0).then_some(settings)
+    }
+
+    /// Serialize these settings as header of the BDAT chunk.
+    pub(crate) fn to_bytes(self) -> [u8; Self::SIZE] {
+        let mut out = [0; Self::SIZE];
+        out[..4].copy_from_slice(&self.hash_version.to_be_bytes());
+        out[4..8].copy_from_slice(&self.num_hashes.to_be_bytes());
+        out[8..].copy_from_slice(&self.bits_per_entry.to_be_bytes());
+        out
+    }
+}
+
+/// The hashes of a single path, used to query or fill a [`Filter`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Key {
+    hashes: Vec<u32>,
+}
+
+impl Key {
+    /// Compute the key for `path` with the hash function and the amount of hashes configured in `settings`.
+    pub fn new(path: &BStr, settings: &Settings) -> Self {
+        const SEED0: u32 = 0x293a_e76f;
+        const SEED1: u32 = 0x7e64_6e2c;
+        let signed = settings.hash_version == 1;
+        let hash0 = murmur3_seeded(SEED0, path, signed);
+        let hash1 = murmur3_seeded(SEED1, path, signed);
+        Key {
+            hashes: (0..settings.num_hashes)
+                .map(|i| hash0.wrapping_add(i.wrapping_mul(hash1)))
+                .collect(),
+        }
+    }
+
+    /// Compute the keys for `path` and all of its leading directories, which all have to be contained in a
+    /// filter for `path` to possibly have changed.
+    pub fn for_path_and_leading_directories(path: &BStr, settings: &Settings) -> Vec<Self> {
+        let mut out = vec![Key::new(path, settings)];
+        let mut path = path.as_bytes();
+        while let Some(pos) = path.rfind_byte(b'/') {
+            path = &path[..pos];
+            out.push(Key::new(path.as_bstr(), settings));
+        }
+        out
+    }
+}
+
+/// A Bloom filter of a single commit, borrowed from the commit-graph file it is stored in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Filter<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Filter<'a> {
+    pub(crate) fn from_bytes(data: &'a [u8]) -> Self {
+        Filter { data }
+    }
+
+    /// Return `false` if the path of `key` definitely didn't change, or `true` if it might have changed.
+    pub fn contains(&self, key: &Key) -> bool {
+        contains(self.data, key)
+    }
+
+    /// The raw bytes of the filter.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.data
+    }
+}
+
+/// Compute the data of a filter for the `changed_paths` of a commit compared to its first parent, using `settings`.
+///
+/// All leading directories of each path are added as well. If there are more than [`MAX_CHANGED_PATHS`], the returned
+/// filter matches any path.
+pub fn compute_filter<'a>(changed_paths: impl IntoIterator<Item = &'a BStr>, settings: &Settings) -> Vec<u8> {
+    let mut paths = Vec::new();
+    for path in changed_paths {
+        let mut path = path.as_bytes();
+        loop {
+            paths.push(path);
+            match path.rfind_byte(b'/') {
+                Some(pos) => path = &path[..pos],
+                None => break,
+            }
+        }
+    }
+    paths.sort_unstable();
+    paths.dedup();
+    if paths.len() > MAX_CHANGED_PATHS {
+        return vec![0xff];
+    }
+
+    let num_bytes = (paths.len() * settings.bits_per_entry as usize + 7) / 8;
+    let mut data = vec![0; num_bytes.max(1)];
+    let num_bits = data.len() as u64 * 8;
+    for path in paths {
+        for hash in Key::new(path.as_bstr(), settings).hashes {
+            let bit = u64::from(hash) % num_bits;
+            data[(bit / 8) as usize] |= 1 << (bit % 8);
+        }
+    }
+    data
+}
+
+fn contains(data: &[u8], key: &Key) -> bool {
+    let num_bits = data.len() as u64 * 8;
+    if num_bits == 0 {
+        return true;
+    }
+    key.hashes.iter().all(|hash| {
+        let bit = u64::from(*hash) % num_bits;
+        data[(bit / 8) as usize] & (1 << (bit % 8)) != 0
+    })
+}
+
+/// The 32 bit murmur3 hash as implemented by git, which interprets bytes as signed with `signed_bytes` like git does in
+/// version 1 of its implementation.
+fn murmur3_seeded(seed: u32, data: &[u8], signed_bytes: bool) -> u32 {
+    const C1: u32 = 0xcc9e_2d51;
+    const C2: u32 = 0x1b87_3593;
+    const R1: u32 = 15;
+    const R2: u32 = 13;
+    const M: u32 = 5;
+    const N: u32 = 0xe654_6b64;
+
+    let byte = |b: u8| if signed_bytes { b as i8 as u32 } else { u32::from(b) };
+    let mut hash = seed;
+    let mut blocks = data.chunks_exact(4);
+    for block in &mut blocks {
+        let mut k = byte(block[0]) | byte(block[1]) << 8 | byte(block[2]) << 16 | byte(block[3]) << 24;
+        k = k.wrapping_mul(C1);
+        k = k.rotate_left(R1);
+        k = k.wrapping_mul(C2);
+
+        hash ^= k;
+        hash = hash.rotate_left(R2).wrapping_mul(M).wrapping_add(N);
+    }
+
+    let tail = blocks.remainder();
+    if !tail.is_empty() {
+        let mut k = 0u32;
+        for (shift, b) in tail.iter().enumerate().rev() {
+            k ^= byte(*b) << (shift * 8);
+        }
+        k = k.wrapping_mul(C1);
+        k = k.rotate_left(R1);
+        k = k.wrapping_mul(C2);
+        hash ^= k;
+    }
+
+    hash ^= data.len() as u32;
+    hash ^= hash >> 16;
+    hash = hash.wrapping_mul(0x85eb_ca6b);
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(0xc2b2_ae35);
+    hash ^= hash >> 16;
+    hash
+}
@@ -4,7 +4,7 @@ use std::{
     path::Path,
 };
 
-use crate::file::{self, commit::Commit, File, COMMIT_DATA_ENTRY_SIZE_SANS_HASH};
+use crate::file::{self, commit::Commit, File, COMMIT_DATA_ENTRY_SIZE_SANS_HASH, GENERATION_DATA_OVERFLOW_MASK};
 
 /// Access
 impl File {
@@ -24,6 +24,17 @@ impl File {
         Commit::new(self, pos)
     }
 
+    /// Returns the settings used to compute the changed-path Bloom filters of the commits in this file, or `None` if
+    /// this file has no Bloom filters.
+    pub fn bloom_settings(&self) -> Option<crate::bloom::Settings> {
+        self.bloom_settings
+    }
+
+    /// Returns `true` if this file stores the corrected commit date of each commit, also known as generation number v2.
+    pub fn has_generation_data(&self) -> bool {
+        self.generation_data_offset.is_some()
+    }
+
     /// The kind of hash used in this File.
     ///
     /// Note that it is always conforming to the hash used in the owning repository.
@@ -122,6 +133,34 @@ impl File {
         &self.data[start..][..entry_size]
     }
 
+    /// Returns the corrected commit date of the commit at `pos` with `committer_timestamp`, if this file has a
+    /// Generation Data (GDA2) chunk.
+    pub(crate) fn corrected_commit_date(&self, pos: file::Position, committer_timestamp: u64) -> Option<u64> {
+        let start = self.generation_data_offset? + pos.0 as usize * 4;
+        let offset = u32::from_be_bytes(self.data[start..][..4].try_into().unwrap());
+        if offset & GENERATION_DATA_OVERFLOW_MASK == 0 {
+            return Some(committer_timestamp + u64::from(offset));
+        }
+        let overflow = &self.data[self.generation_data_overflow_range.clone()?];
+        let start = (offset & !GENERATION_DATA_OVERFLOW_MASK) as usize * 8;
+        let offset = u64::from_be_bytes(overflow.get(start..start + 8)?.try_into().unwrap());
+        Some(committer_timestamp + offset)
+    }
+
+    /// Returns the changed-path Bloom filter of the commit at `pos`, if this file has Bloom filters and the one of the
+    /// commit was computed.
+    pub(crate) fn bloom_filter(&self, pos: file::Position) -> Option<crate::bloom::Filter<'_>> {
+        let index = self.bloom_filter_index_offset?;
+        let read_end = |pos: usize| u32::from_be_bytes(self.data[index + pos * 4..][..4].try_into().unwrap()) as usize;
+        let pos = pos.0 as usize;
+        let start = if pos == 0 { 0 } else { read_end(pos - 1) };
+        let end = read_end(pos);
+        let data = &self.data[self.bloom_filter_data_range.clone()?];
+        // Empty filters weren't computed, and invalid offsets are treated the same.
+        let filter = data.get(start..end).filter(|filter| !filter.is_empty())?;
+        Some(crate::bloom::Filter::from_bytes(filter))
+    }
+
     /// Returns the byte slice for this file's entire Extra Edge List (EDGE) chunk.
     pub(crate) fn extra_edges_data(&self) -> Option<&[u8]> {
         Some(&self.data[self.extra_edges_list_range.clone()?])
@@ -5,7 +5,10 @@ use std::{
     slice::Chunks,
 };
 
+use bstr::BStr;
+
 use crate::{
+    bloom,
     file::{self, File, EXTENDED_EDGES_MASK, LAST_EXTENDED_EDGE_MASK, NO_PARENT},
     graph,
 };
@@ -71,6 +74,37 @@ impl<'a> Commit<'a> {
         self.generation
     }
 
+    /// Returns the corrected commit date of this commit, also known as generation number v2, if the owning file
+    /// [has generation data][File::has_generation_data()].
+    ///
+    /// It's the committer timestamp of this commit, or the largest corrected commit date of its parents plus one if that is greater.
+    pub fn corrected_commit_date(&self) -> Option<u64> {
+        self.file.corrected_commit_date(self.pos, self.commit_timestamp)
+    }
+
+    /// Returns the changed-path Bloom filter of this commit, if the owning file [has Bloom filters][File::bloom_settings()]
+    /// and the one of this commit was computed.
+    pub fn bloom_filter(&self) -> Option<bloom::Filter<'a>> {
+        self.file.bloom_filter(self.pos)
+    }
+
+    /// Returns `Some(false)` if `path` definitely didn't change compared to the first parent of this commit, or
+    /// `Some(true)` if it may have changed, or `None` if there is no [Bloom filter][Self::bloom_filter()] to tell.
+    ///
+    /// `path` is relative to the root of the repository, using slashes as separators, like `dir/file`.
+    ///
+    /// Note that it's more efficient to compute [keys][bloom::Key::for_path_and_leading_directories()] once and
+    /// query the filter directly when checking many commits.
+    pub fn maybe_changed_path(&self, path: &BStr) -> Option<bool> {
+        let filter = self.bloom_filter()?;
+        let settings = self.file.bloom_settings()?;
+        Some(
+            bloom::Key::for_path_and_leading_directories(path, &settings)
+                .iter()
+                .all(|key| filter.contains(key)),
+        )
+    }
+
     /// Returns an iterator over the parent positions for lookup in the owning [Graph][crate::Graph].
     pub fn iter_parents(&'a self) -> impl Iterator<Item = Result<graph::Position, Error>> + 'a {
         // I didn't find a combinator approach that a) was as strict as ParentIterator, b) supported
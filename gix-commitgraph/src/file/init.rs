@@ -7,8 +7,10 @@ use bstr::ByteSlice;
 use memmap2::Mmap;
 
 use crate::file::{
-    ChunkId, File, BASE_GRAPHS_LIST_CHUNK_ID, COMMIT_DATA_CHUNK_ID, COMMIT_DATA_ENTRY_SIZE_SANS_HASH,
-    EXTENDED_EDGES_LIST_CHUNK_ID, FAN_LEN, HEADER_LEN, OID_FAN_CHUNK_ID, OID_LOOKUP_CHUNK_ID, SIGNATURE,
+    ChunkId, File, BASE_GRAPHS_LIST_CHUNK_ID, BLOOM_FILTER_DATA_CHUNK_ID, BLOOM_FILTER_INDEX_CHUNK_ID,
+    COMMIT_DATA_CHUNK_ID, COMMIT_DATA_ENTRY_SIZE_SANS_HASH, EXTENDED_EDGES_LIST_CHUNK_ID, FAN_LEN,
+    GENERATION_DATA_CHUNK_ID, GENERATION_DATA_OVERFLOW_CHUNK_ID, HEADER_LEN, OID_FAN_CHUNK_ID, OID_LOOKUP_CHUNK_ID,
+    SIGNATURE,
 };
 
 /// The error used in [`File::at()`].
@@ -196,6 +198,63 @@ impl TryFrom<&Path> for File {
 
         let extra_edges_list_range = chunks.usize_offset_by_id(EXTENDED_EDGES_LIST_CHUNK_ID).ok();
 
+        let generation_data = chunks
+            .validated_usize_offset_by_id(GENERATION_DATA_CHUNK_ID, |chunk_range| {
+                let chunk_size = chunk_range.len();
+                if chunk_size % 4 != 0 {
+                    return Err(Error::InvalidChunkSize {
+                        id: GENERATION_DATA_CHUNK_ID,
+                        msg: format!("chunk size {chunk_size} is not a multiple of 4"),
+                    });
+                }
+                Ok((chunk_range.start, chunk_size / 4))
+            })
+            .ok()
+            .transpose()?;
+        let generation_data_overflow_range = chunks
+            .validated_usize_offset_by_id(GENERATION_DATA_OVERFLOW_CHUNK_ID, |chunk_range| {
+                let chunk_size = chunk_range.len();
+                if chunk_size % 8 != 0 {
+                    return Err(Error::InvalidChunkSize {
+                        id: GENERATION_DATA_OVERFLOW_CHUNK_ID,
+                        msg: format!("chunk size {chunk_size} is not a multiple of 8"),
+                    });
+                }
+                Ok(chunk_range)
+            })
+            .ok()
+            .transpose()?;
+
+        let bloom_filter_index = chunks
+            .validated_usize_offset_by_id(BLOOM_FILTER_INDEX_CHUNK_ID, |chunk_range| {
+                let chunk_size = chunk_range.len();
+                if chunk_size % 4 != 0 {
+                    return Err(Error::InvalidChunkSize {
+                        id: BLOOM_FILTER_INDEX_CHUNK_ID,
+                        msg: format!("chunk size {chunk_size} is not a multiple of 4"),
+                    });
+                }
+                Ok((chunk_range.start, chunk_size / 4))
+            })
+            .ok()
+            .transpose()?;
+        let bloom_filter_data = chunks
+            .validated_usize_offset_by_id(BLOOM_FILTER_DATA_CHUNK_ID, |chunk_range| {
+                let chunk_size = chunk_range.len();
+                if chunk_size < crate::bloom::Settings::SIZE {
+                    return Err(Error::InvalidChunkSize {
+                        id: BLOOM_FILTER_DATA_CHUNK_ID,
+                        msg: format!(
+                            "chunk size {chunk_size} is too small to hold the {} bytes header",
+                            crate::bloom::Settings::SIZE
+                        ),
+                    });
+                }
+                Ok(chunk_range)
+            })
+            .ok()
+            .transpose()?;
+
         let trailer = &data[chunks.highest_offset() as usize..];
         if trailer.len() != object_hash.len_in_bytes() {
             return Err(Error::Trailer(format!(
@@ -229,13 +288,55 @@ impl TryFrom<&Path> for File {
                 chunk2_commits: commit_data_count,
             });
         }
+        let generation_data_offset = match generation_data {
+            Some((offset, count)) => {
+                if count != fan[255] as usize {
+                    return Err(Error::CommitCountMismatch {
+                        chunk1_id: OID_FAN_CHUNK_ID,
+                        chunk1_commits: fan[255],
+                        chunk2_id: GENERATION_DATA_CHUNK_ID,
+                        chunk2_commits: count as u32,
+                    });
+                }
+                Some(offset)
+            }
+            None => None,
+        };
+        let (bloom_filter_index_offset, bloom_filter_data_range, bloom_settings) =
+            match (bloom_filter_index, bloom_filter_data) {
+                (Some((offset, count)), Some(range)) => {
+                    if count != fan[255] as usize {
+                        return Err(Error::CommitCountMismatch {
+                            chunk1_id: OID_FAN_CHUNK_ID,
+                            chunk1_commits: fan[255],
+                            chunk2_id: BLOOM_FILTER_INDEX_CHUNK_ID,
+                            chunk2_commits: count as u32,
+                        });
+                    }
+                    // Filters computed with unknown settings are ignored, just like git does.
+                    match crate::bloom::Settings::from_bytes(&data[range.clone()]) {
+                        Some(settings) => (
+                            Some(offset),
+                            Some(range.start + crate::bloom::Settings::SIZE..range.end),
+                            Some(settings),
+                        ),
+                        None => (None, None, None),
+                    }
+                }
+                _ => (None, None, None),
+            };
         Ok(File {
             base_graph_count,
             base_graphs_list_offset,
+            bloom_filter_data_range,
+            bloom_filter_index_offset,
+            bloom_settings,
             commit_data_offset,
             data,
             extra_edges_list_range,
             fan,
+            generation_data_offset,
+            generation_data_overflow_range,
             oid_lookup_offset,
             path: path.to_owned(),
             hash_len: object_hash.len_in_bytes(),
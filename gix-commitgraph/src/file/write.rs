@@ -0,0 +1,368 @@
+//! Write individual commit-graph files.
+use std::{convert::TryInto, io::Write};
+
+use gix_hash::ObjectId;
+
+use crate::{
+    bloom,
+    file::{
+        File, BASE_GRAPHS_LIST_CHUNK_ID, BLOOM_FILTER_DATA_CHUNK_ID, BLOOM_FILTER_INDEX_CHUNK_ID, COMMIT_DATA_CHUNK_ID,
+        EXTENDED_EDGES_LIST_CHUNK_ID, EXTENDED_EDGES_MASK, FAN_LEN, GENERATION_DATA_CHUNK_ID,
+        GENERATION_DATA_OVERFLOW_CHUNK_ID, GENERATION_DATA_OVERFLOW_MASK, HEADER_LEN, LAST_EXTENDED_EDGE_MASK,
+        NO_PARENT, OID_FAN_CHUNK_ID, OID_LOOKUP_CHUNK_ID, SIGNATURE,
+    },
+    Graph, GENERATION_NUMBER_MAX, MAX_COMMITS,
+};
+
+/// The information about a commit needed to write it into a commit-graph file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Commit {
+    /// The id of the commit.
+    pub id: ObjectId,
+    /// The id of the tree the commit points to.
+    pub tree: ObjectId,
+    /// The ids of all parents of the commit, in order.
+    pub parents: Vec<ObjectId>,
+    /// The committer timestamp in seconds since the unix epoch.
+    pub committer_timestamp: u64,
+    /// The data of the changed-path Bloom filter as [computed][bloom::compute_filter()] with the settings that are
+    /// [used for writing][Options::changed_paths], or `None` if it wasn't computed.
+    pub bloom_filter: Option<Vec<u8>>,
+}
+
+/// Configuration for [`File::write_to()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    /// The kind of hash to use for the file and its checksum.
+    pub object_hash: gix_hash::Kind,
+    /// If `true`, store the corrected commit date of each commit, also known as generation number v2, like git does
+    /// with `commitGraph.generationVersion=2`.
+    ///
+    /// It's not stored if any of the base graphs lacks them, as they can't be computed without them.
+    pub generation_data: bool,
+    /// If set, store the [Bloom filters][Commit::bloom_filter] of all commits along with these settings, which must be
+    /// the ones the filters were computed with.
+    pub changed_paths: Option<bloom::Settings>,
+}
+
+/// The outcome of [`File::write_to()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Outcome {
+    /// The checksum of the written file, which is also used in its name if it's part of a split commit-graph.
+    pub checksum: ObjectId,
+    /// The amount of commits in the written file.
+    pub num_commits: u32,
+    /// If `true`, the corrected commit dates were written as well.
+    pub generation_data: bool,
+}
+
+/// The error returned by [`File::write_to()`].
+#[derive(thiserror::Error, Debug)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Commit {id} has parent {parent_id} which is neither among the commits to write nor in a base graph")]
+    MissingParent { id: ObjectId, parent_id: ObjectId },
+    #[error(
+        "Commit-graph would contain {0} commits altogether, but only {} commits are allowed",
+        MAX_COMMITS
+    )]
+    TooManyCommits(u64),
+    #[error("The changed-path Bloom filters of all commits would need more than 4GB")]
+    BloomFilterDataTooLarge,
+    #[error("Commit-graph files can have at most 255 base graphs, got {0}")]
+    TooManyBaseGraphs(usize),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+const CORRECTED_COMMIT_DATE_OFFSET_MAX: u64 = 0x7fff_ffff;
+
+/// Writing
+impl File {
+    /// Write a commit-graph file with all `commits` to `out`, each of which must have all of its parents either
+    /// among `commits` or in `base`, the graph the written file will be layered on top of in a split commit-graph.
+    ///
+    /// Commits that are already contained in `base` are skipped.
+    pub fn write_to(
+        mut commits: Vec<Commit>,
+        base: Option<&Graph>,
+        Options {
+            object_hash,
+            generation_data,
+            changed_paths,
+        }: Options,
+        out: impl std::io::Write,
+    ) -> Result<Outcome, Error> {
+        commits.sort_by_key(|c| c.id);
+        commits.dedup_by(|a, b| a.id == b.id);
+        if let Some(base) = base {
+            commits.retain(|c| base.lookup(c.id).is_none());
+        }
+        let base_files = base.map_or(&[][..], Graph::files);
+        let num_base_graphs: u8 = base_files
+            .len()
+            .try_into()
+            .map_err(|_| Error::TooManyBaseGraphs(base_files.len()))?;
+        let num_base_commits = base.map_or(0, Graph::num_commits);
+        let num_total = u64::from(num_base_commits) + commits.len() as u64;
+        if num_total > u64::from(MAX_COMMITS) {
+            return Err(Error::TooManyCommits(num_total));
+        }
+        let generation_data = generation_data && base.map_or(true, Graph::has_generation_data);
+
+        let graph_position = |commit: &Commit, parent_id: &ObjectId| -> Result<Parent, Error> {
+            match commits.binary_search_by(|c| c.id.cmp(parent_id)) {
+                Ok(idx) => Ok(Parent::Local(idx)),
+                Err(_) => base
+                    .and_then(|base| base.lookup(parent_id))
+                    .map(|pos| Parent::Base(pos.0))
+                    .ok_or(Error::MissingParent {
+                        id: commit.id,
+                        parent_id: *parent_id,
+                    }),
+            }
+        };
+        let parents = commits
+            .iter()
+            .map(|c| c.parents.iter().map(|p| graph_position(c, p)).collect())
+            .collect::<Result<Vec<Vec<_>>, _>>()?;
+        let generations = compute_generations(&commits, &parents, base);
+
+        let num_extra_edges: usize = parents.iter().filter(|p| p.len() > 2).map(|p| p.len() - 1).sum();
+        let num_overflows = if generation_data {
+            commits
+                .iter()
+                .zip(&generations)
+                .filter(|(c, g)| g.corrected_commit_date - c.committer_timestamp > CORRECTED_COMMIT_DATE_OFFSET_MAX)
+                .count()
+        } else {
+            0
+        };
+
+        let bloom_filter_data_len: u64 = commits
+            .iter()
+            .map(|c| c.bloom_filter.as_ref().map_or(0, |filter| filter.len() as u64))
+            .sum();
+        if changed_paths.is_some() && bloom_filter_data_len > u64::from(u32::MAX) {
+            return Err(Error::BloomFilterDataTooLarge);
+        }
+
+        let hash_len = object_hash.len_in_bytes();
+        let mut cf = gix_chunk::file::Index::for_writing();
+        cf.plan_chunk(OID_FAN_CHUNK_ID, (FAN_LEN * 4) as u64);
+        cf.plan_chunk(OID_LOOKUP_CHUNK_ID, (commits.len() * hash_len) as u64);
+        cf.plan_chunk(
+            COMMIT_DATA_CHUNK_ID,
+            (commits.len() * (hash_len + super::COMMIT_DATA_ENTRY_SIZE_SANS_HASH)) as u64,
+        );
+        if generation_data {
+            cf.plan_chunk(GENERATION_DATA_CHUNK_ID, (commits.len() * 4) as u64);
+            if num_overflows > 0 {
+                cf.plan_chunk(GENERATION_DATA_OVERFLOW_CHUNK_ID, (num_overflows * 8) as u64);
+            }
+        }
+        if num_extra_edges > 0 {
+            cf.plan_chunk(EXTENDED_EDGES_LIST_CHUNK_ID, (num_extra_edges * 4) as u64);
+        }
+        if changed_paths.is_some() {
+            cf.plan_chunk(BLOOM_FILTER_INDEX_CHUNK_ID, (commits.len() * 4) as u64);
+            cf.plan_chunk(
+                BLOOM_FILTER_DATA_CHUNK_ID,
+                bloom::Settings::SIZE as u64 + bloom_filter_data_len,
+            );
+        }
+        if num_base_graphs > 0 {
+            cf.plan_chunk(BASE_GRAPHS_LIST_CHUNK_ID, (base_files.len() * hash_len) as u64);
+        }
+
+        let mut out = gix_features::hash::Write::new(out, object_hash);
+        out.write_all(SIGNATURE)?;
+        out.write_all(&[1 /* version */, object_hash as u8])?;
+        out.write_all(&[
+            cf.num_chunks().try_into().expect("BUG: wrote more than 256 chunks"),
+            num_base_graphs,
+        ])?;
+
+        let to_graph_position = |parent: &Parent| match *parent {
+            Parent::Local(idx) => num_base_commits + idx as u32,
+            Parent::Base(pos) => pos,
+        };
+        let mut chunk_write = cf.into_write(&mut out, HEADER_LEN)?;
+        while let Some(chunk_to_write) = chunk_write.next_chunk() {
+            match chunk_to_write {
+                OID_FAN_CHUNK_ID => {
+                    let mut fan = [0u32; FAN_LEN];
+                    for commit in &commits {
+                        fan[usize::from(commit.id.first_byte())] += 1;
+                    }
+                    let mut count = 0;
+                    for entries in fan {
+                        count += entries;
+                        chunk_write.write_all(&count.to_be_bytes())?;
+                    }
+                }
+                OID_LOOKUP_CHUNK_ID => {
+                    for commit in &commits {
+                        chunk_write.write_all(commit.id.as_slice())?;
+                    }
+                }
+                COMMIT_DATA_CHUNK_ID => {
+                    let mut extra_edge_index = 0;
+                    for ((commit, parents), generation) in commits.iter().zip(&parents).zip(&generations) {
+                        chunk_write.write_all(commit.tree.as_slice())?;
+                        let parent1 = parents.first().map_or(NO_PARENT, to_graph_position);
+                        let parent2 = match parents.len() {
+                            0 | 1 => NO_PARENT,
+                            2 => to_graph_position(&parents[1]),
+                            num_parents => {
+                                let idx = extra_edge_index;
+                                extra_edge_index += num_parents as u32 - 1;
+                                EXTENDED_EDGES_MASK | idx
+                            }
+                        };
+                        chunk_write.write_all(&parent1.to_be_bytes())?;
+                        chunk_write.write_all(&parent2.to_be_bytes())?;
+                        let timestamp = commit.committer_timestamp;
+                        let high = (generation.level << 2) | ((timestamp >> 32) & 0x3) as u32;
+                        chunk_write.write_all(&high.to_be_bytes())?;
+                        chunk_write.write_all(&(timestamp as u32).to_be_bytes())?;
+                    }
+                }
+                GENERATION_DATA_CHUNK_ID => {
+                    let mut overflow_index = 0;
+                    for (commit, generation) in commits.iter().zip(&generations) {
+                        let offset = generation.corrected_commit_date - commit.committer_timestamp;
+                        let offset = if offset > CORRECTED_COMMIT_DATE_OFFSET_MAX {
+                            overflow_index += 1;
+                            GENERATION_DATA_OVERFLOW_MASK | (overflow_index - 1)
+                        } else {
+                            offset as u32
+                        };
+                        chunk_write.write_all(&offset.to_be_bytes())?;
+                    }
+                }
+                GENERATION_DATA_OVERFLOW_CHUNK_ID => {
+                    for (commit, generation) in commits.iter().zip(&generations) {
+                        let offset = generation.corrected_commit_date - commit.committer_timestamp;
+                        if offset > CORRECTED_COMMIT_DATE_OFFSET_MAX {
+                            chunk_write.write_all(&offset.to_be_bytes())?;
+                        }
+                    }
+                }
+                EXTENDED_EDGES_LIST_CHUNK_ID => {
+                    for parents in parents.iter().filter(|p| p.len() > 2) {
+                        let (last, rest) = parents[1..].split_last().expect("more than two parents");
+                        for parent in rest {
+                            chunk_write.write_all(&to_graph_position(parent).to_be_bytes())?;
+                        }
+                        chunk_write.write_all(&(LAST_EXTENDED_EDGE_MASK | to_graph_position(last)).to_be_bytes())?;
+                    }
+                }
+                BLOOM_FILTER_INDEX_CHUNK_ID => {
+                    let mut end = 0u32;
+                    for commit in &commits {
+                        end += commit.bloom_filter.as_ref().map_or(0, |filter| filter.len() as u32);
+                        chunk_write.write_all(&end.to_be_bytes())?;
+                    }
+                }
+                BLOOM_FILTER_DATA_CHUNK_ID => {
+                    let settings = changed_paths.expect("only planned with settings");
+                    chunk_write.write_all(&settings.to_bytes())?;
+                    for filter in commits.iter().filter_map(|c| c.bloom_filter.as_ref()) {
+                        chunk_write.write_all(filter)?;
+                    }
+                }
+                BASE_GRAPHS_LIST_CHUNK_ID => {
+                    for file in base_files {
+                        chunk_write.write_all(file.checksum().as_bytes())?;
+                    }
+                }
+                unknown => unreachable!("BUG: forgot to implement chunk {:?}", std::str::from_utf8(&unknown)),
+            }
+        }
+
+        let checksum: ObjectId = out.hash.digest();
+        out.inner.write_all(checksum.as_slice())?;
+        Ok(Outcome {
+            checksum,
+            num_commits: commits.len() as u32,
+            generation_data,
+        })
+    }
+}
+
+/// The position of a parent of a commit to write.
+enum Parent {
+    /// The index into the commits to write.
+    Local(usize),
+    /// The graph position in the base graph.
+    Base(u32),
+}
+
+#[derive(Default, Clone, Copy)]
+struct Generation {
+    /// The topological level, or generation number v1.
+    level: u32,
+    /// The corrected commit date, or generation number v2.
+    corrected_commit_date: u64,
+}
+
+/// Compute the generation numbers of all `commits` with `parents`, without recursion to support long histories.
+fn compute_generations(commits: &[Commit], parents: &[Vec<Parent>], base: Option<&Graph>) -> Vec<Generation> {
+    let mut generations: Vec<Option<Generation>> = vec![None; commits.len()];
+    let mut stack = Vec::new();
+    for start in 0..commits.len() {
+        if generations[start].is_some() {
+            continue;
+        }
+        stack.push(start);
+        while let Some(&idx) = stack.last() {
+            if generations[idx].is_some() {
+                stack.pop();
+                continue;
+            }
+            let mut max_level = 0;
+            let mut max_corrected_commit_date = 0;
+            let mut parents_are_known = true;
+            for parent in &parents[idx] {
+                let parent_generation = match *parent {
+                    Parent::Local(parent_idx) => match generations[parent_idx] {
+                        Some(generation) => generation,
+                        None => {
+                            stack.push(parent_idx);
+                            parents_are_known = false;
+                            continue;
+                        }
+                    },
+                    Parent::Base(pos) => {
+                        let commit = base
+                            .expect("base is set for base parents")
+                            .commit_at(crate::graph::Position(pos));
+                        Generation {
+                            level: commit.generation(),
+                            corrected_commit_date: commit.corrected_commit_date().unwrap_or_default(),
+                        }
+                    }
+                };
+                max_level = max_level.max(parent_generation.level);
+                max_corrected_commit_date = max_corrected_commit_date.max(parent_generation.corrected_commit_date);
+            }
+            if !parents_are_known {
+                continue;
+            }
+            stack.pop();
+            let timestamp = commits[idx].committer_timestamp;
+            if timestamp != 0 && timestamp > max_corrected_commit_date {
+                max_corrected_commit_date = timestamp - 1;
+            }
+            generations[idx] = Some(Generation {
+                level: (max_level + 1).min(GENERATION_NUMBER_MAX),
+                corrected_commit_date: max_corrected_commit_date + 1,
+            });
+        }
+    }
+    generations
+        .into_iter()
+        .map(|generation| generation.expect("all generations are computed"))
+        .collect()
+}
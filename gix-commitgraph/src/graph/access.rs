@@ -44,6 +44,18 @@ impl Graph {
         Some(self.lookup_by_id(id.as_ref())?.graph_pos)
     }
 
+    /// Returns `true` if all files of this graph store corrected commit dates, also known as generation number v2.
+    ///
+    /// Like git, corrected commit dates should only be used if this is the case.
+    pub fn has_generation_data(&self) -> bool {
+        self.files.iter().all(File::has_generation_data)
+    }
+
+    /// Return all files this graph consists of, starting with the base graph.
+    pub fn files(&self) -> &[File] {
+        &self.files
+    }
+
     /// Returns the number of commits stored in this file.
     pub fn num_commits(&self) -> u32 {
         self.files.iter().map(|f| f.num_commits()).sum()
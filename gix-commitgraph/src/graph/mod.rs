@@ -2,9 +2,12 @@
 mod access;
 mod init;
 pub mod verify;
+pub mod write;
 
 use std::fmt;
 
+pub use init::Error;
+
 use crate::file::File;
 
 /// A complete commit graph.
@@ -0,0 +1,319 @@
+//! Write commit-graphs into the `objects/info` directory, either as single file or as chain of files.
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use gix_tempfile::{AutoRemove, ContainingDirectory};
+
+use crate::{
+    bloom,
+    file::{self, File},
+    graph, Graph,
+};
+
+/// The way new commits are added to a split commit-graph, that is a chain of commit-graph files in the
+/// `objects/info/commit-graphs` directory, each of which builds on the ones before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Split {
+    /// Write new commits into a new file on top of the chain, and merge it with the files at the top of the chain as long as
+    /// these have no more than `size_multiple` times the amount of commits of the new file, or the new file would have more than
+    /// `max_commits` commits.
+    ///
+    /// This is what git does by default, with a `size_multiple` of 2.
+    Merge {
+        /// The factor by which a file in the chain has to be larger than the new file to not be merged into it.
+        size_multiple: u32,
+        /// If set, merge files as long as the new file would have more than this amount of commits.
+        max_commits: Option<u32>,
+    },
+    /// Write new commits into a new file on top of the chain without merging any of the existing files,
+    /// like `--split=no-merge`.
+    NoMerge,
+    /// Replace the whole chain with a single file containing all commits, like `--split=replace`.
+    Replace,
+}
+
+impl Default for Split {
+    fn default() -> Self {
+        Split::Merge {
+            size_multiple: 2,
+            max_commits: None,
+        }
+    }
+}
+
+/// Configuration for [`Graph::write_to_info_dir()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    /// If set, write a split commit-graph using the given strategy, or write a single `objects/info/commit-graph` file otherwise.
+    pub split: Option<Split>,
+    /// If `true`, store the corrected commit date of each commit, also known as generation number v2.
+    pub generation_data: bool,
+    /// If set, store changed-path Bloom filters computed with these settings.
+    ///
+    /// Filters of commits that are already in the commit-graph are retained if they were computed with the same settings.
+    pub changed_paths: Option<bloom::Settings>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            split: None,
+            generation_data: true,
+            changed_paths: None,
+        }
+    }
+}
+
+/// The outcome of [`Graph::write_to_info_dir()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// The path to the written commit-graph file, or `None` if nothing had to be written.
+    pub path: Option<PathBuf>,
+    /// The amount of commits in the written commit-graph file.
+    pub num_commits: u32,
+    /// The amount of files the commit-graph consists of after writing.
+    pub num_files: usize,
+    /// The commit-graph files that were removed as their commits are now contained in the written file.
+    pub removed_files: Vec<PathBuf>,
+}
+
+/// The error returned by [`Graph::write_to_info_dir()`].
+#[derive(thiserror::Error, Debug)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not open the existing commit-graph")]
+    Open(#[from] graph::Error),
+    #[error("Could not read a commit from the existing commit-graph")]
+    ReadCommit(#[from] file::commit::Error),
+    #[error(transparent)]
+    WriteFile(#[from] file::write::Error),
+    #[error(transparent)]
+    Lock(#[from] gix_lock::acquire::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// Writing
+impl Graph {
+    /// Write all `commits` into the commit-graph in `info_dir`, typically `.git/objects/info`, along with all commits
+    /// already contained in it, using `object_hash` and `options`.
+    ///
+    /// Each commit must have all of its parents either among `commits` or in the existing commit-graph.
+    /// Thus it's sufficient to pass only commits that aren't yet contained in the commit-graph, stopping the traversal at the
+    /// first commit that is.
+    ///
+    /// Files that aren't needed anymore are removed right away, which is safe for readers who have them opened already
+    /// on all platforms that allow removing memory-mapped files.
+    pub fn write_to_info_dir(
+        info_dir: impl AsRef<Path>,
+        commits: impl IntoIterator<Item = file::write::Commit>,
+        object_hash: gix_hash::Kind,
+        Options {
+            split,
+            generation_data,
+            changed_paths,
+        }: Options,
+    ) -> Result<Outcome, Error> {
+        let info_dir = info_dir.as_ref();
+        let single_path = info_dir.join("commit-graph");
+        let chain_dir = info_dir.join("commit-graphs");
+        let chain_path = chain_dir.join("commit-graph-chain");
+
+        let single = single_path
+            .is_file()
+            .then(|| Graph::from_file(&single_path))
+            .transpose()?;
+        let chain = chain_path
+            .is_file()
+            .then(|| Graph::from_commit_graphs_dir(&chain_dir))
+            .transpose()?;
+        let mut commits: Vec<_> = commits.into_iter().collect();
+        let write_options = file::write::Options {
+            object_hash,
+            generation_data,
+            changed_paths,
+        };
+        let mut removed_files = Vec::new();
+
+        let split = match split {
+            Some(split) => split,
+            None => {
+                if commits.is_empty() && chain.is_none() {
+                    return Ok(Outcome {
+                        path: None,
+                        num_commits: 0,
+                        num_files: usize::from(single.is_some()),
+                        removed_files,
+                    });
+                }
+                for graph in single.iter().chain(chain.iter()) {
+                    commits.extend(graph.write_commits(changed_paths, 0..graph.files.len())?);
+                }
+                let mut lock = std::io::BufWriter::new(gix_lock::File::acquire_to_update_resource(
+                    &single_path,
+                    gix_lock::acquire::Fail::Immediately,
+                    None,
+                )?);
+                let outcome = File::write_to(commits, None, write_options, &mut lock)?;
+                lock.into_inner()
+                    .map_err(|err| err.into_error())?
+                    .commit()
+                    .map_err(|err| err.error)?;
+                if let Some(chain) = chain {
+                    removed_files.extend(chain.files.iter().map(|file| file.path().to_owned()));
+                    removed_files.push(chain_path);
+                }
+                remove_files(&removed_files)?;
+                return Ok(Outcome {
+                    path: Some(single_path),
+                    num_commits: outcome.num_commits,
+                    num_files: 1,
+                    removed_files,
+                });
+            }
+        };
+
+        let mut layers = Vec::new();
+        if let Some(chain) = chain {
+            commits.retain(|commit| chain.lookup(commit.id).is_none());
+            if let Some(single) = &single {
+                let carried_over = single.write_commits(changed_paths, 0..single.files.len())?;
+                commits.extend(carried_over.into_iter().filter(|c| chain.lookup(c.id).is_none()));
+            }
+            let num_new_commits = {
+                let mut ids: Vec<_> = commits.iter().map(|c| c.id).collect();
+                ids.sort();
+                ids.dedup();
+                ids.len()
+            };
+            let num_kept = num_layers_to_keep(&chain.files, num_new_commits, split);
+            if num_new_commits == 0 && num_kept == chain.files.len() && single.is_none() {
+                return Ok(Outcome {
+                    path: None,
+                    num_commits: 0,
+                    num_files: num_kept,
+                    removed_files,
+                });
+            }
+            commits.extend(chain.write_commits(changed_paths, num_kept..chain.files.len())?);
+            layers = chain.files;
+            for merged in layers.drain(num_kept..) {
+                removed_files.push(merged.path().to_owned());
+            }
+        } else if let Some(single) = &single {
+            commits.extend(single.write_commits(changed_paths, 0..single.files.len())?);
+        }
+        if commits.is_empty() && layers.is_empty() {
+            return Ok(Outcome {
+                path: None,
+                num_commits: 0,
+                num_files: 0,
+                removed_files,
+            });
+        }
+
+        std::fs::create_dir_all(&chain_dir)?;
+        let mut chain_lock =
+            gix_lock::File::acquire_to_update_resource(&chain_path, gix_lock::acquire::Fail::Immediately, None)?;
+        let base = if layers.is_empty() {
+            None
+        } else {
+            Some(Graph::new(layers)?)
+        };
+        let mut tempfile = std::io::BufWriter::new(gix_tempfile::new(
+            &chain_dir,
+            ContainingDirectory::Exists,
+            AutoRemove::Tempfile,
+        )?);
+        let outcome = File::write_to(commits, base.as_ref(), write_options, &mut tempfile)?;
+        let path = chain_dir.join(format!("graph-{}.graph", outcome.checksum));
+        tempfile
+            .into_inner()
+            .map_err(|err| err.into_error())?
+            .persist(&path)
+            .map_err(|err| err.error)?;
+
+        let base_files = base.as_ref().map_or(&[][..], |base| &base.files);
+        for file in base_files {
+            writeln!(chain_lock, "{}", file.checksum())?;
+        }
+        writeln!(chain_lock, "{}", outcome.checksum)?;
+        chain_lock.commit().map_err(|err| err.error)?;
+
+        if single.is_some() {
+            removed_files.push(single_path);
+        }
+        remove_files(&removed_files)?;
+        Ok(Outcome {
+            path: Some(path),
+            num_commits: outcome.num_commits,
+            num_files: base_files.len() + 1,
+            removed_files,
+        })
+    }
+
+    /// Obtain all commits of the files in `file_range` in a form suitable for writing them.
+    fn write_commits(
+        &self,
+        changed_paths: Option<bloom::Settings>,
+        file_range: std::ops::Range<usize>,
+    ) -> Result<Vec<file::write::Commit>, Error> {
+        let mut out = Vec::new();
+        for file in &self.files[file_range] {
+            let retain_bloom_filters = changed_paths.is_some() && file.bloom_settings() == changed_paths;
+            for commit in file.iter_commits() {
+                out.push(file::write::Commit {
+                    id: commit.id().into(),
+                    tree: commit.root_tree_id().into(),
+                    parents: commit
+                        .iter_parents()
+                        .map(|pos| pos.map(|pos| self.id_at(pos).to_owned()))
+                        .collect::<Result<_, _>>()?,
+                    committer_timestamp: commit.committer_timestamp(),
+                    bloom_filter: retain_bloom_filters
+                        .then(|| commit.bloom_filter().map(|filter| filter.as_bytes().to_vec()))
+                        .flatten(),
+                });
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// Return the amount of `layers` at the bottom of the chain that are kept as they are when adding `num_new_commits` with `split`.
+fn num_layers_to_keep(layers: &[File], num_new_commits: usize, split: Split) -> usize {
+    match split {
+        Split::NoMerge => layers.len(),
+        Split::Replace => 0,
+        Split::Merge {
+            size_multiple,
+            max_commits,
+        } => {
+            let mut num_commits = num_new_commits as u64;
+            let mut num_kept = layers.len();
+            while let Some(top) = num_kept.checked_sub(1).map(|idx| &layers[idx]) {
+                let merge = u64::from(top.num_commits()) <= u64::from(size_multiple) * num_commits
+                    || max_commits.map_or(false, |max| num_commits > u64::from(max));
+                if !merge {
+                    break;
+                }
+                num_commits += u64::from(top.num_commits());
+                num_kept -= 1;
+            }
+            num_kept
+        }
+    }
+}
+
+fn remove_files(paths: &[PathBuf]) -> std::io::Result<()> {
+    for path in paths {
+        match std::fs::remove_file(path) {
+            Ok(()) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(())
+}
@@ -1,4 +1,4 @@
-//! Read, verify, and traverse git commit graphs.
+//! Read, verify, traverse and write git commit graphs.
 //!
 //! A [commit graph][Graph] is an index of commits in the git commit history.
 //! The [Graph] stores commit data in a way that accelerates lookups considerably compared to
@@ -15,6 +15,7 @@
 #![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
 #![deny(missing_docs, rust_2018_idioms, unsafe_code)]
 
+pub mod bloom;
 pub mod file;
 pub mod graph;
 
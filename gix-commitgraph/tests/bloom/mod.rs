@@ -0,0 +1,104 @@
+use std::{path::Path, process::Command};
+
+use bstr::{BString, ByteSlice};
+use gix_commitgraph::{bloom, Graph};
+use gix_hash::ObjectId;
+
+use crate::make_readonly_repo;
+
+fn git(repo: &Path, args: &[&str]) -> crate::Result<Vec<u8>> {
+    let out = Command::new("git").args(args).current_dir(repo).output()?;
+    assert!(out.status.success(), "git {args:?} failed");
+    Ok(out.stdout)
+}
+
+fn rev_parse(repo: &Path, spec: &str) -> crate::Result<ObjectId> {
+    Ok(ObjectId::from_hex(git(repo, &["rev-parse", spec])?.trim())?)
+}
+
+/// The paths that changed in `commit` compared to its first parent, like git computes them for Bloom filters.
+fn changed_paths(
+    repo: &Path,
+    commit: &gix_commitgraph::file::Commit<'_>,
+    graph: &Graph,
+) -> crate::Result<Vec<BString>> {
+    let id = commit.id().to_string();
+    let out = match commit.parent1()? {
+        Some(parent) => {
+            let parent = graph.id_at(parent).to_string();
+            git(
+                repo,
+                &["diff-tree", "-r", "-z", "--name-only", "--no-renames", &parent, &id],
+            )?
+        }
+        None => git(
+            repo,
+            &["diff-tree", "-r", "-z", "--name-only", "--no-renames", "--root", &id],
+        )?,
+    };
+    Ok(out
+        .split_str(b"\0")
+        .filter(|path| !path.is_empty())
+        .skip(usize::from(commit.parent1()?.is_none()))
+        .map(Into::into)
+        .collect())
+}
+
+#[test]
+fn filters_are_computed_like_git() -> crate::Result {
+    let repo = make_readonly_repo("changed_paths.sh");
+    let graph = Graph::from_info_dir(repo.join(".git/objects/info"))?;
+    let file = &graph.files()[0];
+    let settings = file.bloom_settings().expect("written with changed paths");
+    assert_eq!(settings, bloom::Settings::default());
+
+    for commit in file.iter_commits() {
+        let paths = changed_paths(&repo, &commit, &graph)?;
+        assert_eq!(
+            bloom::compute_filter(paths.iter().map(|p| p.as_bstr()), &settings),
+            commit.bloom_filter().expect("all filters are computed").as_bytes(),
+            "{}",
+            commit.id()
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn maybe_changed_path() -> crate::Result {
+    let repo = make_readonly_repo("changed_paths.sh");
+    let graph = Graph::from_info_dir(repo.join(".git/objects/info"))?;
+    let commit = |spec: &str| -> crate::Result<_> {
+        Ok(graph
+            .commit_by_id(rev_parse(&repo, spec)?)
+            .expect("all commits are in the graph"))
+    };
+
+    let change_nested = commit("HEAD^2~3")?;
+    for path in ["dir/sub/nested", "dir/sub", "dir"] {
+        assert_eq!(change_nested.maybe_changed_path(path.into()), Some(true), "{path}");
+    }
+
+    let high_bits = commit("HEAD^2~2")?;
+    assert_eq!(high_bits.maybe_changed_path("ünïcödé/dïr/fïle".into()), Some(true));
+
+    let empty = commit("HEAD^2~1")?;
+    assert_eq!(
+        empty.maybe_changed_path("dir/sub/nested".into()),
+        Some(false),
+        "nothing changed, so the filter has no bits set"
+    );
+
+    let too_many_changes = commit("HEAD^2")?;
+    assert_eq!(
+        too_many_changes.bloom_filter().expect("present").as_bytes(),
+        [0xff],
+        "a filter with all bits set is used if there are too many changes"
+    );
+    assert_eq!(too_many_changes.maybe_changed_path("root".into()), Some(true));
+
+    let change_on_main = commit("HEAD^1")?;
+    assert_eq!(change_on_main.maybe_changed_path("other".into()), Some(true));
+    assert_eq!(change_on_main.maybe_changed_path("dir/sub/nested".into()), Some(false));
+    Ok(())
+}
@@ -9,9 +9,11 @@ use std::{
 
 use gix_commitgraph::{graph::Position as GraphPosition, Graph};
 
-type Result = std::result::Result<(), Box<dyn std::error::Error>>;
+type Result<T = ()> = std::result::Result<T, Box<dyn std::error::Error>>;
 
 mod access;
+mod bloom;
+mod write;
 
 pub fn check_common(cg: &Graph, expected: &HashMap<String, RefInfo, impl BuildHasher>) {
     assert_eq!(
@@ -0,0 +1,38 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config core.quotePath false
+
+function commit() {
+  local message=${1:?message}
+  git add -A
+  git commit -q --allow-empty -m "$message"
+}
+
+echo root > root
+mkdir -p dir/sub
+echo file > dir/file
+echo nested > dir/sub/nested
+commit initial
+
+echo changed > dir/sub/nested
+commit "change nested file"
+
+mkdir -p "ünïcödé/dïr"
+echo high-bits > "ünïcödé/dïr/fïle"
+commit "add paths with bytes that have the high bit set"
+
+commit empty
+
+git checkout -q -b side
+mkdir many
+for i in $(seq 600); do echo $i > many/$i; done
+commit "too many changes"
+
+git checkout -q main 2>/dev/null || git checkout -q master
+echo other > other
+commit "change on main"
+git merge -q --no-ff -m merge side
+
+git commit-graph write --no-progress --reachable --changed-paths
@@ -0,0 +1,10 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+
+GIT_COMMITTER_DATE="@3000000000 +0000" git commit -q --allow-empty -m "far in the future"
+GIT_COMMITTER_DATE="@0 +0000" git commit -q --allow-empty -m "at the epoch"
+GIT_COMMITTER_DATE="@1 +0000" git commit -q --allow-empty -m "right after the epoch"
+
+git -c commitGraph.generationVersion=2 commit-graph write --no-progress --reachable
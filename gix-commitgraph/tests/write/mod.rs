@@ -0,0 +1,249 @@
+use std::path::Path;
+
+use gix_commitgraph::{
+    file::{self, write::Commit},
+    graph::write::{Options, Split},
+    Graph,
+};
+use gix_hash::ObjectId;
+
+use crate::make_readonly_repo;
+
+fn commits_of(graph: &Graph, file: &file::File) -> Vec<Commit> {
+    file.iter_commits()
+        .map(|commit| Commit {
+            id: commit.id().into(),
+            tree: commit.root_tree_id().into(),
+            parents: commit
+                .iter_parents()
+                .map(|pos| graph.id_at(pos.expect("valid parent")).to_owned())
+                .collect(),
+            committer_timestamp: commit.committer_timestamp(),
+            bloom_filter: commit.bloom_filter().map(|filter| filter.as_bytes().to_vec()),
+        })
+        .collect()
+}
+
+fn rewrite(file: &file::File, graph: &Graph, base: Option<&Graph>) -> crate::Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    let outcome = file::File::write_to(
+        commits_of(graph, file),
+        base,
+        file::write::Options {
+            object_hash: file.object_hash(),
+            generation_data: file.has_generation_data(),
+            changed_paths: file.bloom_settings(),
+        },
+        &mut buf,
+    )?;
+    assert_eq!(outcome.checksum, file.checksum());
+    assert_eq!(outcome.num_commits, file.num_commits());
+    Ok(buf)
+}
+
+#[test]
+fn single_files_are_written_like_git() -> crate::Result {
+    for script in [
+        "changed_paths.sh",
+        "octopus_merges.sh",
+        "single_commit.sh",
+        "single_parent.sh",
+        "two_parents.sh",
+    ] {
+        let path = make_readonly_repo(script).join(".git/objects/info/commit-graph");
+        let graph = Graph::from_file(&path)?;
+        let file = &graph.files()[0];
+        assert_eq!(rewrite(file, &graph, None)?, std::fs::read(&path)?, "{script}");
+    }
+    Ok(())
+}
+
+#[test]
+fn corrected_commit_date_offsets_which_overflow_are_read_and_written_like_git() -> crate::Result {
+    let path = make_readonly_repo("generation_data_overflow.sh").join(".git/objects/info/commit-graph");
+    let graph = Graph::from_file(&path)?;
+    let file = &graph.files()[0];
+    let mut dates: Vec<_> = graph
+        .iter_commits()
+        .map(|commit| (commit.committer_timestamp(), commit.corrected_commit_date()))
+        .collect();
+    dates.sort();
+    assert_eq!(
+        dates,
+        [
+            (0, Some(3_000_000_001)),
+            (1, Some(3_000_000_002)),
+            (3_000_000_000, Some(3_000_000_000))
+        ],
+        "the offsets of the first two commits are too large and stored in the overflow chunk"
+    );
+    assert_eq!(rewrite(file, &graph, None)?, std::fs::read(&path)?);
+    Ok(())
+}
+
+#[test]
+fn split_chains_are_written_like_git() -> crate::Result {
+    let chain_dir = make_readonly_repo("split_chain.sh").join(".git/objects/info/commit-graphs");
+    let graph = Graph::from_commit_graphs_dir(&chain_dir)?;
+    assert_eq!(graph.files().len(), 3);
+    for (idx, file) in graph.files().iter().enumerate() {
+        let base = (idx > 0)
+            .then(|| {
+                Graph::new(
+                    graph.files()[..idx]
+                        .iter()
+                        .map(|file| file::File::at(file.path()))
+                        .collect::<Result<_, _>>()?,
+                )
+                .map_err(Box::<dyn std::error::Error>::from)
+            })
+            .transpose()?;
+        assert_eq!(
+            rewrite(file, &graph, base.as_ref())?,
+            std::fs::read(file.path())?,
+            "layer {idx}"
+        );
+    }
+    Ok(())
+}
+
+fn id(n: u32) -> ObjectId {
+    let mut bytes = [0u8; 20];
+    bytes[..4].copy_from_slice(&n.to_be_bytes());
+    bytes[19] = 1;
+    ObjectId::from(bytes)
+}
+
+/// A linear history of commits `start..end`, with each commit having the previous one as parent.
+fn linear_history(range: std::ops::Range<u32>) -> Vec<Commit> {
+    range
+        .map(|n| Commit {
+            id: id(n),
+            tree: id(u32::MAX),
+            parents: n.checked_sub(1).map(id).into_iter().collect(),
+            committer_timestamp: 1_000_000 + u64::from(n),
+            bloom_filter: None,
+        })
+        .collect()
+}
+
+fn verify(info_dir: &Path, expected_commits: u32) -> crate::Result<Graph> {
+    let graph = Graph::from_info_dir(info_dir)?;
+    assert_eq!(graph.num_commits(), expected_commits);
+    graph.verify_integrity(|_| Ok::<_, std::convert::Infallible>(()))?;
+    assert!(graph.has_generation_data());
+    Ok(graph)
+}
+
+#[test]
+fn split_strategies() -> crate::Result {
+    let dir = gix_testtools::tempfile::TempDir::new()?;
+    let info_dir = dir.path();
+    let hash = gix_hash::Kind::Sha1;
+    let split = |split| Options {
+        split: Some(split),
+        ..Default::default()
+    };
+
+    let outcome = Graph::write_to_info_dir(info_dir, linear_history(0..8), hash, split(Split::NoMerge))?;
+    assert_eq!((outcome.num_commits, outcome.num_files), (8, 1));
+    let outcome = Graph::write_to_info_dir(info_dir, linear_history(8..10), hash, split(Split::NoMerge))?;
+    assert_eq!((outcome.num_commits, outcome.num_files), (2, 2));
+    let outcome = Graph::write_to_info_dir(info_dir, linear_history(10..11), hash, split(Split::NoMerge))?;
+    assert_eq!((outcome.num_commits, outcome.num_files), (1, 3));
+    assert!(outcome.removed_files.is_empty());
+    let graph = verify(info_dir, 11)?;
+    assert_eq!(
+        graph.commit_by_id(id(10)).expect("present").generation(),
+        11,
+        "generations are computed across layers"
+    );
+
+    let outcome = Graph::write_to_info_dir(info_dir, None, hash, split(Split::default()))?;
+    assert_eq!(outcome.path, None, "nothing to do without new commits");
+
+    let outcome = Graph::write_to_info_dir(info_dir, linear_history(11..12), hash, split(Split::default()))?;
+    assert_eq!(
+        (outcome.num_commits, outcome.num_files, outcome.removed_files.len()),
+        (12, 1, 3),
+        "merging continues as long as layers aren't large enough"
+    );
+    let outcome = Graph::write_to_info_dir(info_dir, linear_history(12..13), hash, split(Split::default()))?;
+    assert_eq!(
+        (outcome.num_commits, outcome.num_files, outcome.removed_files.len()),
+        (1, 2, 0),
+        "large layers are kept"
+    );
+    let outcome = Graph::write_to_info_dir(info_dir, linear_history(13..16), hash, split(Split::default()))?;
+    assert_eq!(
+        (outcome.num_commits, outcome.num_files, outcome.removed_files.len()),
+        (4, 2, 1),
+        "the small top layer is merged into the new one"
+    );
+    verify(info_dir, 16)?;
+
+    let outcome = Graph::write_to_info_dir(info_dir, linear_history(16..17), hash, split(Split::Replace))?;
+    assert_eq!((outcome.num_commits, outcome.num_files), (17, 1));
+    verify(info_dir, 17)?;
+
+    let outcome = Graph::write_to_info_dir(info_dir, linear_history(17..18), hash, Options::default())?;
+    assert_eq!((outcome.num_commits, outcome.num_files), (18, 1));
+    assert_eq!(outcome.path, Some(info_dir.join("commit-graph")));
+    assert_eq!(outcome.removed_files.len(), 2, "the chain file and the only layer");
+    assert!(!info_dir.join("commit-graphs/commit-graph-chain").exists());
+    verify(info_dir, 18)?;
+
+    let outcome = Graph::write_to_info_dir(info_dir, linear_history(18..19), hash, split(Split::NoMerge))?;
+    assert_eq!(
+        (outcome.num_commits, outcome.num_files),
+        (19, 1),
+        "the single commit-graph file is merged into the chain"
+    );
+    assert!(!info_dir.join("commit-graph").exists());
+    verify(info_dir, 19)?;
+    Ok(())
+}
+
+#[test]
+fn missing_parents_are_an_error() -> crate::Result {
+    let mut commits = linear_history(0..3);
+    commits.remove(1);
+    let err = file::File::write_to(
+        commits,
+        None,
+        file::write::Options {
+            object_hash: gix_hash::Kind::Sha1,
+            generation_data: true,
+            changed_paths: None,
+        },
+        Vec::new(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, file::write::Error::MissingParent { .. }));
+    Ok(())
+}
+
+#[test]
+fn corrected_commit_dates_with_overflow() -> crate::Result {
+    let dir = gix_testtools::tempfile::TempDir::new()?;
+    let future = 1 << 33;
+    let mut commits = linear_history(0..3);
+    commits[0].committer_timestamp = future;
+    commits[2].parents.push(id(0));
+
+    Graph::write_to_info_dir(dir.path(), commits, gix_hash::Kind::Sha1, Options::default())?;
+    let graph = verify(dir.path(), 3)?;
+    let corrected_commit_date = |n| graph.commit_by_id(id(n)).expect("present").corrected_commit_date();
+    assert_eq!(corrected_commit_date(0), Some(future));
+    assert_eq!(
+        corrected_commit_date(1),
+        Some(future + 1),
+        "the offset to the commit time is too large and stored separately"
+    );
+    assert_eq!(corrected_commit_date(2), Some(future + 2));
+    assert_eq!(
+        graph.commit_by_id(id(1)).expect("present").committer_timestamp(),
+        1_000_001
+    );
+    Ok(())
+}
@@ -90,6 +90,42 @@ impl<'event> Body<'event> {
         })
     }
 
+    /// Returns all keys along with their value in order, with implicit values of keys without key separator `=` being `None`.
+    ///
+    /// Unlike [`values()`][Self::values()], this allows to reproduce a section faithfully.
+    #[must_use]
+    pub fn key_values_implicit(&self) -> Vec<(&Key<'event>, Option<Cow<'_, BStr>>)> {
+        let mut out = Vec::new();
+        let mut key = None;
+        let mut has_separator = false;
+        let mut concatenated_value = BString::default();
+
+        for event in &self.0 {
+            match event {
+                Event::SectionKey(event_key) => {
+                    key = Some(event_key);
+                    has_separator = false;
+                }
+                Event::KeyValueSeparator => has_separator = true,
+                Event::Value(v) => {
+                    if let Some(key) = key.take() {
+                        out.push((key, has_separator.then(|| normalize_bstr(v.as_ref()))));
+                    }
+                }
+                Event::ValueNotDone(v) => concatenated_value.push_str(v.as_ref()),
+                Event::ValueDone(v) => {
+                    concatenated_value.push_str(v.as_ref());
+                    let value = normalize_bstring(std::mem::take(&mut concatenated_value));
+                    if let Some(key) = key.take() {
+                        out.push((key, Some(value)));
+                    }
+                }
+                _ => (),
+            }
+        }
+        out
+    }
+
     /// Returns true if the section contains the provided key.
     #[must_use]
     pub fn contains_key(&self, key: impl AsRef<str>) -> bool {
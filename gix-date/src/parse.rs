@@ -104,7 +104,10 @@ mod relative {
     use crate::parse::Error;
 
     fn parse_inner(input: &str) -> Option<Duration> {
-        let mut split = input.split_whitespace();
+        // Like git, allow dots as separators as well, as in `2.weeks.ago`.
+        let mut split = input
+            .split(|c: char| c.is_whitespace() || c == '.')
+            .filter(|token| !token.is_empty());
         let multiplier = i64::from_str(split.next()?).ok()?;
         let period = split.next()?;
         if split.next()? != "ago" {
@@ -150,5 +153,10 @@ mod relative {
         fn two_weeks_ago() {
             assert_eq!(parse_inner("2 weeks ago"), Some(Duration::weeks(2)));
         }
+
+        #[test]
+        fn two_weeks_ago_with_dots() {
+            assert_eq!(parse_inner("2.weeks.ago"), Some(Duration::weeks(2)));
+        }
     }
 }
@@ -0,0 +1,522 @@
+//! Three-way merges of blobs, line by line, with conflict markers like the ones produced by `git merge-file`.
+use std::ops::Range;
+
+use gix_object::bstr::{BStr, ByteSlice};
+
+use crate::blob::{
+    intern::{Interner, Token},
+    sources::byte_lines_with_terminator,
+    Algorithm,
+};
+
+/// The default amount of characters of each conflict marker, as in `<<<<<<<`.
+pub const DEFAULT_MARKER_SIZE: usize = 7;
+
+/// The amount of bytes at the beginning of a buffer to search for null bytes when determining if it is binary.
+const BINARY_DETECTION_SIZE: usize = 8000;
+
+/// Determine how conflicting hunks are presented in the merged output.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ConflictStyle {
+    /// Show only the conflicting lines of *ours* and *theirs*, separated by `=======`, after removing lines that are the same on both sides.
+    ///
+    /// Conflicting hunks that are separated by only a few unconflicted lines are merged into one.
+    #[default]
+    Merge,
+    /// Like [`Merge`][ConflictStyle::Merge], but additionally show the lines of the common ancestor after a `|||||||` marker,
+    /// without removing lines that are the same on both sides.
+    Diff3,
+    /// Like [`Diff3`][ConflictStyle::Diff3], but lines at the beginning and at the end of a conflicting hunk which are the same
+    /// on both sides are moved out of the conflict.
+    ZealousDiff3,
+}
+
+/// Determine how conflicts are resolved automatically instead of marking them.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Favor {
+    /// Use the lines of *ours* in conflicting hunks.
+    Ours,
+    /// Use the lines of *theirs* in conflicting hunks.
+    Theirs,
+    /// Use the lines of *ours*, followed by the lines of *theirs* in conflicting hunks.
+    Union,
+}
+
+/// The way a blob is merged, typically derived from the `merge` attribute with [`Driver::from_attribute()`].
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Driver {
+    /// Perform a line-wise [text merge][text()], unless one of the inputs [looks binary][is_binary()].
+    #[default]
+    Text,
+    /// Perform a [binary merge][binary()], which keeps *ours* and reports a conflict.
+    Binary,
+    /// Perform a line-wise [text merge][text()], resolving all conflicts with [`Favor::Union`].
+    Union,
+}
+
+impl Driver {
+    /// Obtain the driver from the `state` of the `merge` attribute of the path to merge.
+    ///
+    /// Drivers that aren't built-in are not supported and fall back to [`Text`][Driver::Text], just like unconfigured
+    /// drivers do in `git`.
+    pub fn from_attribute(state: gix_attributes::StateRef<'_>) -> Self {
+        match state {
+            gix_attributes::StateRef::Set | gix_attributes::StateRef::Unspecified => Driver::Text,
+            gix_attributes::StateRef::Unset => Driver::Binary,
+            gix_attributes::StateRef::Value(value) => match value.as_bstr().as_bytes() {
+                b"binary" => Driver::Binary,
+                b"union" => Driver::Union,
+                _ => Driver::Text,
+            },
+        }
+    }
+}
+
+/// Options for use in [`blob()`] and [`text()`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// The algorithm to use when diffing the common ancestor with each side.
+    pub algorithm: Algorithm,
+    /// How to present conflicts.
+    pub conflict_style: ConflictStyle,
+    /// The amount of characters each conflict marker consists of.
+    pub marker_size: usize,
+    /// If set, conflicts are resolved automatically instead of being marked.
+    pub favor: Option<Favor>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            algorithm: Algorithm::Myers,
+            conflict_style: Default::default(),
+            marker_size: DEFAULT_MARKER_SIZE,
+            favor: None,
+        }
+    }
+}
+
+/// The labels to show next to the conflict markers, typically the names of the revisions or the paths of the merged files.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Labels<'a> {
+    /// The label of the common ancestor, shown after `|||||||`.
+    pub ancestor: Option<&'a BStr>,
+    /// The label of *ours*, shown after `<<<<<<<`.
+    pub current: Option<&'a BStr>,
+    /// The label of *theirs*, shown after `>>>>>>>`.
+    pub other: Option<&'a BStr>,
+}
+
+/// The result of a merge.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Resolution {
+    /// All changes were merged without conflict.
+    Complete,
+    /// At least one conflict was encountered, which is marked in the output, or *ours* was used if the merge was binary.
+    Conflict,
+}
+
+/// Return `true` if `data` looks like binary data, which is the case if there is a null byte in its first 8000 bytes, just like `git` does.
+pub fn is_binary(data: &[u8]) -> bool {
+    data[..data.len().min(BINARY_DETECTION_SIZE)].contains(&0)
+}
+
+/// Merge `ours` and `theirs` which have the common ancestor `base` using `driver`, write the result into `out` and return
+/// whether or not it is free of conflicts.
+///
+/// `labels` and `options` are used to configure [text merges][text()].
+pub fn blob(
+    out: &mut Vec<u8>,
+    base: &[u8],
+    ours: &[u8],
+    theirs: &[u8],
+    driver: Driver,
+    labels: Labels<'_>,
+    options: Options,
+) -> Resolution {
+    match driver {
+        Driver::Text if is_binary(base) || is_binary(ours) || is_binary(theirs) => {
+            binary(out, ours, theirs, options.favor)
+        }
+        Driver::Text => text(out, base, ours, theirs, labels, options),
+        Driver::Binary => binary(out, ours, theirs, options.favor),
+        Driver::Union => text(
+            out,
+            base,
+            ours,
+            theirs,
+            labels,
+            Options {
+                favor: Some(Favor::Union),
+                ..options
+            },
+        ),
+    }
+}
+
+/// Merge `ours` and `theirs` without looking at their content by writing `ours` into `out`, which is a [conflict][Resolution::Conflict]
+/// unless `favor` says otherwise.
+///
+/// If `favor` is [`Favor::Theirs`], `theirs` is written instead. [`Favor::Union`] cannot resolve binary conflicts.
+pub fn binary(out: &mut Vec<u8>, ours: &[u8], theirs: &[u8], favor: Option<Favor>) -> Resolution {
+    let (content, resolution) = match favor {
+        Some(Favor::Ours) => (ours, Resolution::Complete),
+        Some(Favor::Theirs) => (theirs, Resolution::Complete),
+        Some(Favor::Union) | None => (ours, Resolution::Conflict),
+    };
+    out.extend_from_slice(content);
+    resolution
+}
+
+/// Merge `ours` and `theirs` line by line, using their common ancestor `base`, write the result into `out` and return whether
+/// or not it is free of conflicts.
+///
+/// Changes to the same lines, or to adjacent lines, are conflicting unless both sides changed them in the same way.
+/// Conflicts are marked according to the [conflict style][Options::conflict_style], using `labels` next to the markers,
+/// unless they are resolved by [favoring][Options::favor] one side.
+pub fn text(
+    out: &mut Vec<u8>,
+    base: &[u8],
+    ours: &[u8],
+    theirs: &[u8],
+    labels: Labels<'_>,
+    options: Options,
+) -> Resolution {
+    let mut interner = Interner::new(base.len() / 16 + ours.len() / 16 + theirs.len() / 16);
+    let input = Input {
+        base: tokenize(&mut interner, base),
+        ours: tokenize(&mut interner, ours),
+        theirs: tokenize(&mut interner, theirs),
+        interner: &interner,
+    };
+    let ours_hunks = hunks(options.algorithm, &input.base, &input.ours, &interner);
+    let theirs_hunks = hunks(options.algorithm, &input.base, &input.theirs, &interner);
+
+    let mut chunks = input.chunks(&ours_hunks, &theirs_hunks);
+    match options.conflict_style {
+        ConflictStyle::Merge => {
+            chunks = input.refine_conflicts(chunks, options.algorithm);
+            simplify_conflicts(&mut chunks);
+        }
+        ConflictStyle::Diff3 => {}
+        ConflictStyle::ZealousDiff3 => chunks = input.trim_conflicts(chunks),
+    }
+
+    let mut resolution = Resolution::Complete;
+    for chunk in chunks {
+        match chunk.kind {
+            Kind::Unchanged | Kind::Ours => input.write(out, &input.ours, chunk.ours, false),
+            Kind::Theirs => input.write(out, &input.theirs, chunk.theirs, false),
+            Kind::Conflict => match options.favor {
+                Some(Favor::Ours) => input.write(out, &input.ours, chunk.ours, false),
+                Some(Favor::Theirs) => input.write(out, &input.theirs, chunk.theirs, false),
+                Some(Favor::Union) => {
+                    input.write(out, &input.ours, chunk.ours, true);
+                    input.write(out, &input.theirs, chunk.theirs, false);
+                }
+                None => {
+                    resolution = Resolution::Conflict;
+                    write_marker(out, b'<', options.marker_size, labels.current);
+                    input.write(out, &input.ours, chunk.ours, true);
+                    if options.conflict_style != ConflictStyle::Merge {
+                        write_marker(out, b'|', options.marker_size, labels.ancestor);
+                        input.write(out, &input.base, chunk.base, true);
+                    }
+                    write_marker(out, b'=', options.marker_size, None);
+                    input.write(out, &input.theirs, chunk.theirs, true);
+                    write_marker(out, b'>', options.marker_size, labels.other);
+                }
+            },
+        }
+    }
+    resolution
+}
+
+/// A change of the lines in `before`, which are replaced by the lines in `after`.
+#[derive(Debug, Clone)]
+struct Hunk {
+    before: Range<u32>,
+    after: Range<u32>,
+}
+
+impl Hunk {
+    /// The amount of lines this hunk adds, or removes if negative.
+    fn delta(&self) -> i64 {
+        self.after.len() as i64 - self.before.len() as i64
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Kind {
+    /// The lines are the same in *ours* and *theirs*.
+    Unchanged,
+    /// Only *ours* changed the lines.
+    Ours,
+    /// Only *theirs* changed the lines.
+    Theirs,
+    /// Both sides changed the lines differently.
+    Conflict,
+}
+
+/// A section of the merge result, with the corresponding lines in all inputs.
+#[derive(Debug, Clone)]
+struct Chunk {
+    base: Range<u32>,
+    ours: Range<u32>,
+    theirs: Range<u32>,
+    kind: Kind,
+}
+
+struct Input<'a> {
+    base: Vec<Token>,
+    ours: Vec<Token>,
+    theirs: Vec<Token>,
+    interner: &'a Interner<&'a [u8]>,
+}
+
+impl<'a> Input<'a> {
+    /// Combine the changes of both sides into chunks, where changes of both sides to the same or adjacent lines are conflicts,
+    /// unless they are equal.
+    fn chunks(&self, ours_hunks: &[Hunk], theirs_hunks: &[Hunk]) -> Vec<Chunk> {
+        let mut chunks = Vec::new();
+        let mut ours_hunks = ours_hunks.iter().peekable();
+        let mut theirs_hunks = theirs_hunks.iter().peekable();
+        let (mut base_pos, mut ours_delta, mut theirs_delta) = (0, 0, 0);
+        loop {
+            let ours_first = match (ours_hunks.peek(), theirs_hunks.peek()) {
+                (None, None) => break,
+                (Some(ours), Some(theirs)) => ours.before.start <= theirs.before.start,
+                (ours, _) => ours.is_some(),
+            };
+            let first = if ours_first {
+                ours_hunks.next()
+            } else {
+                theirs_hunks.next()
+            }
+            .expect("checked");
+            let (start, mut end) = (first.before.start, first.before.end);
+            let (mut ours_group_delta, mut theirs_group_delta) = (0, 0);
+            let (mut ours_changed, mut theirs_changed) = (ours_first, !ours_first);
+            *if ours_first {
+                &mut ours_group_delta
+            } else {
+                &mut theirs_group_delta
+            } += first.delta();
+            loop {
+                if let Some(hunk) = ours_hunks.next_if(|hunk| hunk.before.start <= end) {
+                    end = end.max(hunk.before.end);
+                    ours_group_delta += hunk.delta();
+                    ours_changed = true;
+                } else if let Some(hunk) = theirs_hunks.next_if(|hunk| hunk.before.start <= end) {
+                    end = end.max(hunk.before.end);
+                    theirs_group_delta += hunk.delta();
+                    theirs_changed = true;
+                } else {
+                    break;
+                }
+            }
+
+            if base_pos < start {
+                chunks.push(Chunk {
+                    base: base_pos..start,
+                    ours: shift(base_pos, ours_delta)..shift(start, ours_delta),
+                    theirs: shift(base_pos, theirs_delta)..shift(start, theirs_delta),
+                    kind: Kind::Unchanged,
+                });
+            }
+            let ours = shift(start, ours_delta)..shift(end, ours_delta + ours_group_delta);
+            let theirs = shift(start, theirs_delta)..shift(end, theirs_delta + theirs_group_delta);
+            let kind = match (ours_changed, theirs_changed) {
+                (true, false) => Kind::Ours,
+                (false, true) => Kind::Theirs,
+                _ if self.ours[as_usize(&ours)] == self.theirs[as_usize(&theirs)] => Kind::Unchanged,
+                _ => Kind::Conflict,
+            };
+            chunks.push(Chunk {
+                base: start..end,
+                ours,
+                theirs,
+                kind,
+            });
+            base_pos = end;
+            ours_delta += ours_group_delta;
+            theirs_delta += theirs_group_delta;
+        }
+        let base_end = self.base.len() as u32;
+        if base_pos < base_end {
+            chunks.push(Chunk {
+                base: base_pos..base_end,
+                ours: shift(base_pos, ours_delta)..shift(base_end, ours_delta),
+                theirs: shift(base_pos, theirs_delta)..shift(base_end, theirs_delta),
+                kind: Kind::Unchanged,
+            });
+        }
+        chunks
+    }
+
+    /// Split conflicts into the lines that are the same on both sides, and the lines that actually differ.
+    fn refine_conflicts(&self, chunks: Vec<Chunk>, algorithm: Algorithm) -> Vec<Chunk> {
+        let mut out = Vec::with_capacity(chunks.len());
+        for chunk in chunks {
+            if chunk.kind != Kind::Conflict || chunk.ours.is_empty() || chunk.theirs.is_empty() {
+                out.push(chunk);
+                continue;
+            }
+            let (ours_pos, theirs_pos) = (chunk.ours.start, chunk.theirs.start);
+            let (mut ours_end, mut theirs_end) = (ours_pos, theirs_pos);
+            for hunk in hunks(
+                algorithm,
+                &self.ours[as_usize(&chunk.ours)],
+                &self.theirs[as_usize(&chunk.theirs)],
+                self.interner,
+            ) {
+                let (ours, theirs) = (
+                    ours_pos + hunk.before.start..ours_pos + hunk.before.end,
+                    theirs_pos + hunk.after.start..theirs_pos + hunk.after.end,
+                );
+                if ours_end < ours.start {
+                    out.push(Chunk {
+                        base: chunk.base.start..chunk.base.start,
+                        ours: ours_end..ours.start,
+                        theirs: theirs_end..theirs.start,
+                        kind: Kind::Unchanged,
+                    });
+                }
+                ours_end = ours.end;
+                theirs_end = theirs.end;
+                out.push(Chunk {
+                    base: chunk.base.clone(),
+                    ours,
+                    theirs,
+                    kind: Kind::Conflict,
+                });
+            }
+            if ours_end < chunk.ours.end {
+                out.push(Chunk {
+                    base: chunk.base.end..chunk.base.end,
+                    ours: ours_end..chunk.ours.end,
+                    theirs: theirs_end..chunk.theirs.end,
+                    kind: Kind::Unchanged,
+                });
+            }
+        }
+        out
+    }
+
+    /// Move the lines at the beginning and the end of conflicts which are the same on both sides out of the conflict.
+    fn trim_conflicts(&self, chunks: Vec<Chunk>) -> Vec<Chunk> {
+        let mut out = Vec::with_capacity(chunks.len());
+        for mut chunk in chunks {
+            if chunk.kind != Kind::Conflict {
+                out.push(chunk);
+                continue;
+            }
+            let (ours, theirs) = (&self.ours[as_usize(&chunk.ours)], &self.theirs[as_usize(&chunk.theirs)]);
+            let prefix = ours.iter().zip(theirs).take_while(|(a, b)| a == b).count() as u32;
+            let max_suffix = chunk.ours.len().min(chunk.theirs.len()) as u32 - prefix;
+            let suffix = ours
+                .iter()
+                .rev()
+                .zip(theirs.iter().rev())
+                .take(max_suffix as usize)
+                .take_while(|(a, b)| a == b)
+                .count() as u32;
+            if prefix > 0 {
+                out.push(Chunk {
+                    base: chunk.base.start..chunk.base.start,
+                    ours: chunk.ours.start..chunk.ours.start + prefix,
+                    theirs: chunk.theirs.start..chunk.theirs.start + prefix,
+                    kind: Kind::Unchanged,
+                });
+            }
+            let suffix_chunk = (suffix > 0).then(|| Chunk {
+                base: chunk.base.end..chunk.base.end,
+                ours: chunk.ours.end - suffix..chunk.ours.end,
+                theirs: chunk.theirs.end - suffix..chunk.theirs.end,
+                kind: Kind::Unchanged,
+            });
+            chunk.ours = chunk.ours.start + prefix..chunk.ours.end - suffix;
+            chunk.theirs = chunk.theirs.start + prefix..chunk.theirs.end - suffix;
+            out.push(chunk);
+            out.extend(suffix_chunk);
+        }
+        out
+    }
+
+    /// Write the lines in `range` of `tokens` to `out`, and add a newline if the last line doesn't end with one and `add_newline` is set.
+    fn write(&self, out: &mut Vec<u8>, tokens: &[Token], range: Range<u32>, add_newline: bool) {
+        for token in &tokens[as_usize(&range)] {
+            out.extend_from_slice(self.interner[*token]);
+        }
+        if add_newline && !range.is_empty() && !out.ends_with(b"\n") {
+            out.push(b'\n');
+        }
+    }
+}
+
+/// Merge conflicts which are separated by at most 3 lines which are the same on both sides into one, as it's easier
+/// to resolve a single conflict than many small ones.
+fn simplify_conflicts(chunks: &mut Vec<Chunk>) {
+    const MAX_SEPARATING_LINES: usize = 3;
+    let mut idx = 0;
+    while idx < chunks.len() {
+        if chunks[idx].kind != Kind::Conflict {
+            idx += 1;
+            continue;
+        }
+        let mut next = idx + 1;
+        let mut separating_lines = 0;
+        while next < chunks.len() && chunks[next].kind == Kind::Unchanged {
+            separating_lines += chunks[next].ours.len();
+            next += 1;
+        }
+        if next == chunks.len() || chunks[next].kind != Kind::Conflict || separating_lines > MAX_SEPARATING_LINES {
+            idx = next;
+            continue;
+        }
+        let last = chunks.drain(idx + 1..=next).next_back().expect("at least one");
+        let chunk = &mut chunks[idx];
+        chunk.base = chunk.base.start.min(last.base.start)..chunk.base.end.max(last.base.end);
+        chunk.ours.end = last.ours.end;
+        chunk.theirs.end = last.theirs.end;
+    }
+}
+
+/// Write a conflict marker made of `marker_size` times `marker`, followed by `label` if set.
+fn write_marker(out: &mut Vec<u8>, marker: u8, marker_size: usize, label: Option<&BStr>) {
+    if !out.is_empty() && !out.ends_with(b"\n") {
+        out.push(b'\n');
+    }
+    out.extend(std::iter::repeat(marker).take(marker_size));
+    if let Some(label) = label {
+        out.push(b' ');
+        out.extend_from_slice(label);
+    }
+    out.push(b'\n');
+}
+
+fn tokenize<'a>(interner: &mut Interner<&'a [u8]>, data: &'a [u8]) -> Vec<Token> {
+    byte_lines_with_terminator(data)
+        .map(|line| interner.intern(line))
+        .collect()
+}
+
+fn hunks(algorithm: Algorithm, before: &[Token], after: &[Token], interner: &Interner<&[u8]>) -> Vec<Hunk> {
+    let mut hunks = Vec::new();
+    crate::blob::diff_with_tokens(
+        algorithm,
+        before,
+        after,
+        interner.num_tokens(),
+        |before: Range<u32>, after: Range<u32>| hunks.push(Hunk { before, after }),
+    );
+    hunks
+}
+
+fn shift(pos: u32, delta: i64) -> u32 {
+    (pos as i64 + delta) as u32
+}
+
+fn as_usize(range: &Range<u32>) -> Range<usize> {
+    range.start as usize..range.end as usize
+}
@@ -0,0 +1,159 @@
+use gix_diff::blob::merge::{self, ConflictStyle, Driver, Favor, Labels, Options, Resolution};
+use gix_object::bstr::{BStr, ByteSlice};
+
+#[test]
+fn text_matches_git_merge_file() -> crate::Result {
+    let dir = gix_testtools::scripted_fixture_read_only_standalone("make_blob_merge_baseline.sh")?;
+    let mut num_cases = 0;
+    for case in std::fs::read_dir(&dir)? {
+        let case = case?.path();
+        let base = std::fs::read(case.join("base"))?;
+        let ours = std::fs::read(case.join("ours"))?;
+        let theirs = std::fs::read(case.join("theirs"))?;
+        for (name, options) in [
+            ("merge", Options::default()),
+            (
+                "diff3",
+                Options {
+                    conflict_style: ConflictStyle::Diff3,
+                    ..Default::default()
+                },
+            ),
+            (
+                "zdiff3",
+                Options {
+                    conflict_style: ConflictStyle::ZealousDiff3,
+                    ..Default::default()
+                },
+            ),
+            (
+                "ours",
+                Options {
+                    favor: Some(Favor::Ours),
+                    ..Default::default()
+                },
+            ),
+            (
+                "theirs",
+                Options {
+                    favor: Some(Favor::Theirs),
+                    ..Default::default()
+                },
+            ),
+            (
+                "union",
+                Options {
+                    favor: Some(Favor::Union),
+                    ..Default::default()
+                },
+            ),
+            (
+                "marker-size-3",
+                Options {
+                    marker_size: 3,
+                    ..Default::default()
+                },
+            ),
+        ] {
+            let expected = std::fs::read(case.join(format!("{name}.baseline")))?;
+            let num_conflicts: usize = std::fs::read_to_string(case.join(format!("{name}.exit")))?
+                .trim()
+                .parse()?;
+            let mut out = Vec::new();
+            let resolution = merge::text(&mut out, &base, &ours, &theirs, labels(), options);
+            assert_eq!(
+                out.as_bstr(),
+                expected.as_bstr(),
+                "{case:?}: {name}: output should match `git merge-file`"
+            );
+            assert_eq!(
+                resolution,
+                if num_conflicts == 0 {
+                    Resolution::Complete
+                } else {
+                    Resolution::Conflict
+                },
+                "{case:?}: {name}"
+            );
+        }
+        num_cases += 1;
+    }
+    assert_eq!(num_cases, 11, "all cases were seen");
+    Ok(())
+}
+
+#[test]
+fn binary_inputs_take_ours_without_markers() {
+    let mut out = Vec::new();
+    let resolution = merge::blob(
+        &mut out,
+        b"base\0",
+        b"ours\0",
+        b"theirs\0",
+        Driver::Text,
+        labels(),
+        Default::default(),
+    );
+    assert_eq!(resolution, Resolution::Conflict);
+    assert_eq!(out.as_bstr(), "ours\0");
+
+    out.clear();
+    let resolution = merge::blob(
+        &mut out,
+        b"base\n",
+        b"ours\n",
+        b"theirs\n",
+        Driver::Binary,
+        labels(),
+        Options {
+            favor: Some(Favor::Theirs),
+            ..Default::default()
+        },
+    );
+    assert_eq!(
+        resolution,
+        Resolution::Complete,
+        "favoring a side resolves the conflict"
+    );
+    assert_eq!(out.as_bstr(), "theirs\n");
+}
+
+#[test]
+fn union_driver_resolves_conflicts() {
+    let mut out = Vec::new();
+    let resolution = merge::blob(
+        &mut out,
+        b"1\n2\n",
+        b"1\nours\n",
+        b"1\ntheirs\n",
+        Driver::Union,
+        labels(),
+        Default::default(),
+    );
+    assert_eq!(resolution, Resolution::Complete);
+    assert_eq!(out.as_bstr(), "1\nours\ntheirs\n");
+}
+
+#[test]
+fn driver_from_attribute() {
+    use gix_attributes::StateRef;
+    for (state, expected) in [
+        (StateRef::Set, Driver::Text),
+        (StateRef::Unspecified, Driver::Text),
+        (StateRef::Unset, Driver::Binary),
+        (StateRef::Value("binary".into()), Driver::Binary),
+        (StateRef::Value("union".into()), Driver::Union),
+        (StateRef::Value("text".into()), Driver::Text),
+        (StateRef::Value("custom".into()), Driver::Text),
+    ] {
+        assert_eq!(Driver::from_attribute(state), expected, "{state:?}");
+    }
+}
+
+fn labels() -> Labels<'static> {
+    Labels {
+        ancestor: Some(BStr::new("base")),
+        current: Some(BStr::new("ours")),
+        other: Some(BStr::new("theirs")),
+    }
+}
@@ -0,0 +1,105 @@
+#!/bin/bash
+set -eu -o pipefail
+
+function baseline() {
+  local name=${1:?}
+  shift
+  git merge-file -p -L ours -L base -L theirs "$@" ours base theirs > "$name.baseline" && echo 0 > "$name.exit" || echo $? > "$name.exit"
+}
+
+function case_with() {
+  local name=${1:?}
+  mkdir "$name"
+  (cd "$name"
+    cat > base
+    cp base ours
+    cp base theirs
+  )
+}
+
+function all_baselines() {
+  baseline merge
+  baseline diff3 --diff3
+  baseline zdiff3 --zdiff3
+  baseline ours --ours
+  baseline theirs --theirs
+  baseline union --union
+  baseline marker-size-3 --marker-size=3
+}
+
+case_with non-overlapping < <(seq 1 9)
+(cd non-overlapping
+  sed -i 's/^2$/two/' ours
+  sed -i 's/^8$/eight/' theirs
+  all_baselines
+)
+
+case_with same-change < <(seq 1 9)
+(cd same-change
+  sed -i 's/^5$/five/' ours
+  sed -i 's/^5$/five/' theirs
+  all_baselines
+)
+
+case_with conflict < <(seq 1 9)
+(cd conflict
+  sed -i 's/^5$/five/' ours
+  sed -i 's/^5$/FIVE/' theirs
+  all_baselines
+)
+
+case_with adjacent < <(seq 1 9)
+(cd adjacent
+  sed -i 's/^4$/four/' ours
+  sed -i 's/^5$/five/' theirs
+  all_baselines
+)
+
+case_with common-lines-in-conflict < <(seq 1 9)
+(cd common-lines-in-conflict
+  printf '1\n2\n3\nours-a\nsame-1\nsame-2\nours-b\n7\n8\n9\n' > ours
+  printf '1\n2\n3\ntheirs-a\nsame-1\nsame-2\ntheirs-b\n7\n8\n9\n' > theirs
+  all_baselines
+)
+
+case_with common-prefix-and-suffix < <(seq 1 9)
+(cd common-prefix-and-suffix
+  printf '1\n2\n3\nsame-1\nours\nsame-2\n7\n8\n9\n' > ours
+  printf '1\n2\n3\nsame-1\ntheirs\nsame-2\n7\n8\n9\n' > theirs
+  all_baselines
+)
+
+case_with insertions-at-same-place < <(seq 1 9)
+(cd insertions-at-same-place
+  sed -i 's/^5$/5\nours/' ours
+  sed -i 's/^5$/5\ntheirs/' theirs
+  all_baselines
+)
+
+case_with deletion-and-modification < <(seq 1 9)
+(cd deletion-and-modification
+  sed -i '/^5$/d' ours
+  sed -i 's/^5$/five/' theirs
+  all_baselines
+)
+
+case_with no-newline-at-end < <(printf '1\n2\n3')
+(cd no-newline-at-end
+  printf '1\n2\nours' > ours
+  printf '1\n2\ntheirs' > theirs
+  all_baselines
+)
+
+case_with conflicts-close-to-each-other < <(seq 1 12)
+(cd conflicts-close-to-each-other
+  sed -i 's/^3$/three/; s/^6$/six/' ours
+  sed -i 's/^3$/THREE/; s/^6$/SIX/' theirs
+  all_baselines
+)
+
+case_with conflicts-far-from-each-other < <(seq 1 12)
+(cd conflicts-far-from-each-other
+  sed -i 's/^2$/two/; s/^10$/ten/' ours
+  sed -i 's/^2$/TWO/; s/^10$/TEN/' theirs
+  all_baselines
+)
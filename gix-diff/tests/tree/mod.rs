@@ -32,7 +32,7 @@ mod changes {
                 .try_find(commit, buf)?
                 .ok_or_else(|| format!("start commit {commit:?} to be present"))?
                 .0
-                .decode()?
+                .decode(gix_hash::Kind::Sha1)?
                 .into_commit()
                 .expect("id is actually a commit")
                 .tree();
@@ -41,7 +41,7 @@ mod changes {
                 .try_find(tree_id, buf)?
                 .expect("main tree present")
                 .0
-                .try_into_tree_iter()
+                .try_into_tree_iter(gix_hash::Kind::Sha1)
                 .expect("id to be a tree"))
         }
 
@@ -63,8 +63,11 @@ mod changes {
                 gix_diff::tree::State::default(),
                 |oid, buf| {
                     use gix_odb::pack::FindExt;
-                    db.find(oid, buf)
-                        .map(|obj| obj.0.try_into_tree_iter().expect("only called for trees"))
+                    db.find(oid, buf).map(|obj| {
+                        obj.0
+                            .try_into_tree_iter(gix_hash::Kind::Sha1)
+                            .expect("only called for trees")
+                    })
                 },
                 &mut recorder,
             )?;
@@ -78,7 +81,7 @@ mod changes {
                     .try_find(commit_id, &mut buf)?
                     .ok_or_else(|| format!("start commit {commit_id:?} to be present"))?
                     .0
-                    .decode()?
+                    .decode(gix_hash::Kind::Sha1)?
                     .into_commit()
                     .expect("id is actually a commit");
 
@@ -91,17 +94,17 @@ mod changes {
                 .try_find(main_tree_id, &mut buf)?
                 .expect("main tree present")
                 .0
-                .try_into_tree_iter()
+                .try_into_tree_iter(gix_hash::Kind::Sha1)
                 .expect("id to be a tree");
             let mut buf2 = Vec::new();
             let previous_tree: Option<_> = {
                 parent_commit_id
                     .and_then(|id| db.try_find(id, &mut buf2).ok().flatten())
-                    .and_then(|(c, _l)| c.decode().ok())
+                    .and_then(|(c, _l)| c.decode(gix_hash::Kind::Sha1).ok())
                     .and_then(|c| c.into_commit())
                     .map(|c| c.tree())
                     .and_then(|tree| db.try_find(tree, &mut buf2).ok().flatten())
-                    .and_then(|(tree, _)| tree.try_into_tree_iter())
+                    .and_then(|(tree, _)| tree.try_into_tree_iter(gix_hash::Kind::Sha1))
             };
 
             let mut recorder = gix_diff::tree::Recorder::default();
@@ -110,8 +113,10 @@ mod changes {
                 &mut gix_diff::tree::State::default(),
                 |oid, buf| {
                     use gix_odb::pack::FindExt;
-                    db.find(oid, buf)
-                        .map(|(obj, _)| obj.try_into_tree_iter().expect("only called for trees"))
+                    db.find(oid, buf).map(|(obj, _)| {
+                        obj.try_into_tree_iter(gix_hash::Kind::Sha1)
+                            .expect("only called for trees")
+                    })
                 },
                 &mut recorder,
             )?;
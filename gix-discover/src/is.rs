@@ -68,10 +68,11 @@ pub(crate) fn git_with_metadata(
 
     {
         // Fast-path: avoid doing the complete search if HEAD is already not there.
-        // TODO(reftable): use a ref-store to lookup HEAD if ref-tables should be supported, or detect ref-tables beforehand.
         if !dot_git.join("HEAD").exists() {
             return Err(crate::is_git::Error::MissingHead);
         }
+        // Repositories using reftables keep an invalid `HEAD` file for compatibility with older tools only.
+        let uses_reftable = dot_git.join("reftable").is_dir();
         // We expect to be able to parse any ref-hash, so we shouldn't have to know the repos hash here.
         // With ref-table, the has is probably stored as part of the ref-db itself, so we can handle it from there.
         // In other words, it's important not to fail on detached heads here because we guessed the hash kind wrongly.
@@ -81,11 +82,13 @@ pub(crate) fn git_with_metadata(
             gix_ref::store::WriteReflog::Normal,
             object_hash_should_not_matter_here,
         );
-        let head = refs.find_loose("HEAD")?;
-        if head.name.as_bstr() != "HEAD" {
-            return Err(crate::is_git::Error::MisplacedHead {
-                name: head.name.into_inner(),
-            });
+        if !uses_reftable {
+            let head = refs.find_loose("HEAD")?;
+            if head.name.as_bstr() != "HEAD" {
+                return Err(crate::is_git::Error::MisplacedHead {
+                    name: head.name.into_inner(),
+                });
+            }
         }
     }
 
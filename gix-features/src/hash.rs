@@ -3,7 +3,13 @@
 //! With the `fast-sha1` feature, the `Sha1` hash type will use a more elaborate implementation utilizing hardware support
 //! in case it is available. Otherwise the `rustsha1` feature should be set. `fast-sha1` will take precedence.
 //! Otherwise, a minimal yet performant implementation is used instead for a decent trade-off between compile times and run-time performance.
-#[cfg(all(feature = "rustsha1", not(feature = "fast-sha1")))]
+//!
+//! With the `sha1dc` feature, which takes precedence over both, the `Sha1` hash detects collision attacks like
+//! [SHAttered](https://shattered.io), similar to what `git` does by default. Use [`Hasher::try_digest()`] to learn about them.
+//!
+//! Either feature also enables the [`Sha256`] hash, which is used in repositories with the `sha256` object format.
+//! Use [`hasher()`] to obtain a [`Hasher`] for any kind of hash.
+#[cfg(all(feature = "rustsha1", not(any(feature = "fast-sha1", feature = "sha1dc"))))]
 mod _impl {
     use super::Sha1Digest;
 
@@ -20,14 +26,18 @@ mod _impl {
         pub fn digest(self) -> Sha1Digest {
             self.0.digest().bytes()
         }
+        /// Finalize the hash and produce a digest, which never fails as this implementation doesn't detect collisions.
+        pub fn try_digest(self) -> Result<Sha1Digest, super::try_digest::Error> {
+            Ok(self.digest())
+        }
     }
 }
 
 /// A 20 bytes digest produced by a [`Sha1`] hash implementation.
-#[cfg(any(feature = "fast-sha1", feature = "rustsha1"))]
+#[cfg(any(feature = "rustsha1", feature = "fast-sha1", feature = "sha1dc"))]
 pub type Sha1Digest = [u8; 20];
 
-#[cfg(feature = "fast-sha1")]
+#[cfg(all(feature = "fast-sha1", not(feature = "sha1dc")))]
 mod _impl {
     use sha1::Digest;
 
@@ -46,12 +56,145 @@ mod _impl {
         pub fn digest(self) -> Sha1Digest {
             self.0.finalize().into()
         }
+        /// Finalize the hash and produce a digest, which never fails as this implementation doesn't detect collisions.
+        pub fn try_digest(self) -> Result<Sha1Digest, super::try_digest::Error> {
+            Ok(self.digest())
+        }
+    }
+}
+
+#[cfg(feature = "sha1dc")]
+mod _impl {
+    use super::Sha1Digest;
+
+    /// A implementation of the Sha1 hash with collision detection, which can be used once.
+    ///
+    /// Its state is boxed as it is much larger than the one of other implementations.
+    #[derive(Clone)]
+    pub struct Sha1(Box<sha1_checked::Sha1>);
+
+    impl Default for Sha1 {
+        fn default() -> Self {
+            // Detected collisions are reported, we don't want a different hash to be computed in their stead.
+            Sha1(Box::new(sha1_checked::Sha1::builder().safe_hash(false).build()))
+        }
+    }
+
+    impl Sha1 {
+        /// Digest the given `bytes`.
+        pub fn update(&mut self, bytes: &[u8]) {
+            sha1_checked::Digest::update(self.0.as_mut(), bytes)
+        }
+        /// Finalize the hash and produce a digest, even if a collision attack was detected.
+        pub fn digest(self) -> Sha1Digest {
+            (*self.0.try_finalize().hash()).into()
+        }
+        /// Finalize the hash and produce a digest, or fail if the hashed bytes were crafted to produce a collision.
+        pub fn try_digest(self) -> Result<Sha1Digest, super::try_digest::Error> {
+            match self.0.try_finalize() {
+                sha1_checked::CollisionResult::Ok(digest) => Ok(digest.into()),
+                sha1_checked::CollisionResult::Mitigated(digest) | sha1_checked::CollisionResult::Collision(digest) => {
+                    Err(super::try_digest::Error::CollisionAttack {
+                        digest: gix_hash::ObjectId::from(<[u8; 20]>::from(digest)),
+                    })
+                }
+            }
+        }
     }
 }
 
-#[cfg(any(feature = "rustsha1", feature = "fast-sha1"))]
+#[cfg(any(feature = "rustsha1", feature = "fast-sha1", feature = "sha1dc"))]
 pub use _impl::Sha1;
 
+///
+#[cfg(any(feature = "rustsha1", feature = "fast-sha1", feature = "sha1dc"))]
+pub mod try_digest {
+    /// The error returned by [`Hasher::try_digest()`][super::Hasher::try_digest()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Detected SHA1 collision attack with digest {digest}")]
+        CollisionAttack { digest: gix_hash::ObjectId },
+    }
+}
+
+/// A 32 bytes digest produced by a [`Sha256`] hash implementation.
+#[cfg(any(feature = "rustsha1", feature = "fast-sha1", feature = "sha1dc"))]
+pub type Sha256Digest = [u8; 32];
+
+#[cfg(any(feature = "rustsha1", feature = "fast-sha1", feature = "sha1dc"))]
+mod sha256 {
+    use sha2::Digest;
+
+    use super::Sha256Digest;
+
+    /// A implementation of the Sha256 hash, which can be used once.
+    #[derive(Default, Clone)]
+    pub struct Sha256(sha2::Sha256);
+
+    impl Sha256 {
+        /// Digest the given `bytes`.
+        pub fn update(&mut self, bytes: &[u8]) {
+            self.0.update(bytes)
+        }
+        /// Finalize the hash and produce a digest.
+        pub fn digest(self) -> Sha256Digest {
+            self.0.finalize().into()
+        }
+    }
+}
+#[cfg(any(feature = "rustsha1", feature = "fast-sha1", feature = "sha1dc"))]
+pub use sha256::Sha256;
+
+/// A hash implementation for any of the [kinds of hashes][gix_hash::Kind] we support, which can be used once.
+#[cfg(any(feature = "rustsha1", feature = "fast-sha1", feature = "sha1dc"))]
+#[derive(Clone)]
+pub enum Hasher {
+    /// The Sha1 hash.
+    Sha1(Sha1),
+    /// The Sha256 hash.
+    Sha256(Sha256),
+}
+
+#[cfg(any(feature = "rustsha1", feature = "fast-sha1", feature = "sha1dc"))]
+impl Hasher {
+    /// Digest the given `bytes`.
+    pub fn update(&mut self, bytes: &[u8]) {
+        match self {
+            Hasher::Sha1(h) => h.update(bytes),
+            Hasher::Sha256(h) => h.update(bytes),
+        }
+    }
+
+    /// Finalize the hash and produce the object id it represents.
+    ///
+    /// Collision attacks are not reported here, use [`try_digest()`][Self::try_digest()] for that.
+    pub fn digest(self) -> gix_hash::ObjectId {
+        match self {
+            Hasher::Sha1(h) => h.digest().into(),
+            Hasher::Sha256(h) => h.digest().into(),
+        }
+    }
+
+    /// Finalize the hash and produce the object id it represents, or fail if a collision attack was detected.
+    ///
+    /// Note that collisions can only be detected with the `sha1dc` feature enabled.
+    pub fn try_digest(self) -> Result<gix_hash::ObjectId, try_digest::Error> {
+        Ok(match self {
+            Hasher::Sha1(h) => h.try_digest()?.into(),
+            Hasher::Sha256(h) => h.digest().into(),
+        })
+    }
+
+    /// The kind of hash this instance produces.
+    pub fn kind(&self) -> gix_hash::Kind {
+        match self {
+            Hasher::Sha1(_) => gix_hash::Kind::Sha1,
+            Hasher::Sha256(_) => gix_hash::Kind::Sha256,
+        }
+    }
+}
+
 /// Compute a CRC32 hash from the given `bytes`, returning the CRC32 hash.
 ///
 /// When calling this function for the first time, `previous_value` should be `0`. Otherwise it
@@ -75,10 +218,11 @@ pub fn crc32(bytes: &[u8]) -> u32 {
 }
 
 /// Produce a hasher suitable for the given kind of hash.
-#[cfg(any(feature = "rustsha1", feature = "fast-sha1"))]
-pub fn hasher(kind: gix_hash::Kind) -> Sha1 {
+#[cfg(any(feature = "rustsha1", feature = "fast-sha1", feature = "sha1dc"))]
+pub fn hasher(kind: gix_hash::Kind) -> Hasher {
     match kind {
-        gix_hash::Kind::Sha1 => Sha1::default(),
+        gix_hash::Kind::Sha1 => Hasher::Sha1(Sha1::default()),
+        gix_hash::Kind::Sha256 => Hasher::Sha256(Sha256::default()),
     }
 }
 
@@ -93,7 +237,10 @@ pub fn hasher(kind: gix_hash::Kind) -> Sha1 {
 /// * Only available with the `gix-object` feature enabled due to usage of the [`gix_hash::Kind`] enum and the
 ///   [`gix_hash::ObjectId`] return value.
 /// * [Interrupts][crate::interrupt] are supported.
-#[cfg(all(feature = "progress", any(feature = "rustsha1", feature = "fast-sha1")))]
+#[cfg(all(
+    feature = "progress",
+    any(feature = "rustsha1", feature = "fast-sha1", feature = "sha1dc")
+))]
 pub fn bytes_of_file(
     path: impl AsRef<std::path::Path>,
     num_bytes_from_start: usize,
@@ -111,7 +258,10 @@ pub fn bytes_of_file(
 }
 
 /// Similar to [`bytes_of_file`], but operates on an already open file.
-#[cfg(all(feature = "progress", any(feature = "rustsha1", feature = "fast-sha1")))]
+#[cfg(all(
+    feature = "progress",
+    any(feature = "rustsha1", feature = "fast-sha1", feature = "sha1dc")
+))]
 pub fn bytes(
     mut read: impl std::io::Read,
     num_bytes_from_start: usize,
@@ -139,19 +289,19 @@ pub fn bytes(
         }
     }
 
-    let id = gix_hash::ObjectId::from(hasher.digest());
+    let id = hasher.digest();
     progress.show_throughput(start);
     Ok(id)
 }
 
-#[cfg(any(feature = "rustsha1", feature = "fast-sha1"))]
+#[cfg(any(feature = "rustsha1", feature = "fast-sha1", feature = "sha1dc"))]
 mod write {
-    use crate::hash::Sha1;
+    use crate::hash::Hasher;
 
     /// A utility to automatically generate a hash while writing into an inner writer.
     pub struct Write<T> {
         /// The hash implementation.
-        pub hash: Sha1,
+        pub hash: Hasher,
         /// The inner writer.
         pub inner: T,
     }
@@ -177,14 +327,12 @@ mod write {
     {
         /// Create a new hash writer which hashes all bytes written to `inner` with a hash of `kind`.
         pub fn new(inner: T, object_hash: gix_hash::Kind) -> Self {
-            match object_hash {
-                gix_hash::Kind::Sha1 => Write {
-                    inner,
-                    hash: Sha1::default(),
-                },
+            Write {
+                inner,
+                hash: crate::hash::hasher(object_hash),
             }
         }
     }
 }
-#[cfg(any(feature = "rustsha1", feature = "fast-sha1"))]
+#[cfg(any(feature = "rustsha1", feature = "fast-sha1", feature = "sha1dc"))]
 pub use write::Write;
@@ -1,13 +1,86 @@
 use gix_features::hash::Sha1;
 
-#[cfg(not(feature = "fast-sha1"))]
+#[cfg(not(any(feature = "fast-sha1", feature = "sha1dc")))]
 #[test]
 fn size_of_sha1() {
     assert_eq!(std::mem::size_of::<Sha1>(), 96)
 }
 
-#[cfg(feature = "fast-sha1")]
+#[cfg(all(feature = "fast-sha1", not(feature = "sha1dc")))]
 #[test]
 fn size_of_sha1() {
     assert_eq!(std::mem::size_of::<Sha1>(), 104)
 }
+
+#[cfg(feature = "sha1dc")]
+#[test]
+fn size_of_sha1() {
+    assert_eq!(
+        std::mem::size_of::<Sha1>(),
+        8,
+        "the large collision detection state is boxed"
+    )
+}
+
+#[test]
+fn hasher_produces_ids_of_the_given_kind() {
+    for (kind, expected) in [
+        (gix_hash::Kind::Sha1, "da39a3ee5e6b4b0d3255bfef95601890afd80709"),
+        (
+            gix_hash::Kind::Sha256,
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+        ),
+    ] {
+        let hasher = gix_features::hash::hasher(kind);
+        assert_eq!(hasher.kind(), kind);
+        assert_eq!(hasher.digest().to_string(), expected, "the hash of no input");
+    }
+}
+
+mod try_digest {
+    fn shattered_prefix() -> Vec<u8> {
+        std::fs::read(
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("tests")
+                .join("fixtures")
+                .join("shattered-1.prefix"),
+        )
+        .expect("fixture is present")
+    }
+
+    #[test]
+    fn ordinary_input_is_hashed_like_with_digest() {
+        let mut hasher = gix_features::hash::hasher(gix_hash::Kind::Sha1);
+        hasher.update(b"hello world");
+        let expected = hasher.clone().digest();
+        assert_eq!(hasher.try_digest().expect("no collision"), expected);
+    }
+
+    #[cfg(feature = "sha1dc")]
+    #[test]
+    fn collision_attacks_are_detected() {
+        let mut hasher = gix_features::hash::hasher(gix_hash::Kind::Sha1);
+        hasher.update(&shattered_prefix());
+        let unchecked = hasher.clone().digest();
+        assert_eq!(
+            unchecked.to_string(),
+            "f92d74e3874587aaf443d1db961d4e26dde13e9c",
+            "the unchecked digest is the colliding one"
+        );
+        match hasher.try_digest() {
+            Err(gix_features::hash::try_digest::Error::CollisionAttack { digest }) => assert_eq!(digest, unchecked),
+            Ok(id) => unreachable!("the collision should have been detected, got {}", id),
+        }
+    }
+
+    #[cfg(not(feature = "sha1dc"))]
+    #[test]
+    fn collision_attacks_are_not_detected_without_sha1dc() {
+        let mut hasher = gix_features::hash::hasher(gix_hash::Kind::Sha1);
+        hasher.update(&shattered_prefix());
+        assert_eq!(
+            hasher.try_digest().expect("undetected").to_string(),
+            "f92d74e3874587aaf443d1db961d4e26dde13e9c"
+        );
+    }
+}
@@ -6,7 +6,7 @@ use std::{
     ops::Deref,
 };
 
-use crate::{borrowed::oid, Kind, SIZE_OF_SHA1_DIGEST};
+use crate::{borrowed::oid, Kind, SIZE_OF_SHA1_DIGEST, SIZE_OF_SHA256_DIGEST};
 
 /// An owned hash identifying objects, most commonly Sha1
 #[derive(PartialEq, Eq, Ord, PartialOrd, Clone, Copy)]
@@ -14,6 +14,8 @@ use crate::{borrowed::oid, Kind, SIZE_OF_SHA1_DIGEST};
 pub enum ObjectId {
     /// A SHA 1 hash digest
     Sha1([u8; SIZE_OF_SHA1_DIGEST]),
+    /// A SHA 256 hash digest
+    Sha256([u8; SIZE_OF_SHA256_DIGEST]),
 }
 
 // False positive: https://github.com/rust-lang/rust-clippy/issues/2627
@@ -47,20 +49,20 @@ pub mod decode {
 
     /// Hash decoding
     impl ObjectId {
-        /// Create an instance from a `buffer` of 40 bytes encoded with hexadecimal notation.
+        /// Create an instance from a `buffer` of 40 or 64 bytes encoded with hexadecimal notation.
         ///
         /// Such a buffer can be obtained using [`oid::write_hex_to(buffer)`][super::oid::write_hex_to()]
         pub fn from_hex(buffer: &[u8]) -> Result<ObjectId, Error> {
             use hex::FromHex;
+            let map_err = |err| match err {
+                hex::FromHexError::InvalidHexCharacter { c, index } => Error::Invalid { c, index },
+                hex::FromHexError::OddLength | hex::FromHexError::InvalidStringLength => {
+                    unreachable!("BUG: This is already checked")
+                }
+            };
             match buffer.len() {
-                40 => Ok(ObjectId::Sha1(<[u8; 20]>::from_hex(buffer).map_err(
-                    |err| match err {
-                        hex::FromHexError::InvalidHexCharacter { c, index } => Error::Invalid { c, index },
-                        hex::FromHexError::OddLength | hex::FromHexError::InvalidStringLength => {
-                            unreachable!("BUG: This is already checked")
-                        }
-                    },
-                )?)),
+                40 => Ok(ObjectId::Sha1(<[u8; 20]>::from_hex(buffer).map_err(map_err)?)),
+                64 => Ok(ObjectId::Sha256(<[u8; 32]>::from_hex(buffer).map_err(map_err)?)),
                 len => Err(Error::InvalidHexEncodingLength(len)),
             }
         }
@@ -82,6 +84,7 @@ impl ObjectId {
     pub fn kind(&self) -> crate::Kind {
         match self {
             ObjectId::Sha1(_) => crate::Kind::Sha1,
+            ObjectId::Sha256(_) => crate::Kind::Sha256,
         }
     }
     /// Return the raw byte slice representing this hash.
@@ -89,6 +92,7 @@ impl ObjectId {
     pub fn as_slice(&self) -> &[u8] {
         match self {
             Self::Sha1(b) => b.as_ref(),
+            Self::Sha256(b) => b.as_ref(),
         }
     }
     /// Return the raw mutable byte slice representing this hash.
@@ -96,6 +100,7 @@ impl ObjectId {
     pub fn as_mut_slice(&mut self) -> &mut [u8] {
         match self {
             Self::Sha1(b) => b.as_mut(),
+            Self::Sha256(b) => b.as_mut(),
         }
     }
 
@@ -106,6 +111,9 @@ impl ObjectId {
             Kind::Sha1 => {
                 ObjectId::Sha1(*b"\xe6\x9d\xe2\x9b\xb2\xd1\xd6\x43\x4b\x8b\x29\xae\x77\x5a\xd8\xc2\xe4\x8c\x53\x91")
             }
+            Kind::Sha256 => ObjectId::Sha256(
+                *b"\x47\x3a\x0f\x4c\x3b\xe8\xa9\x36\x81\xa2\x67\xe3\xb1\xe9\xa7\xdc\xda\x11\x85\x43\x6f\xe1\x41\xf7\x74\x91\x20\xa3\x03\x72\x18\x13",
+            ),
         }
     }
 
@@ -116,6 +124,9 @@ impl ObjectId {
             Kind::Sha1 => {
                 ObjectId::Sha1(*b"\x4b\x82\x5d\xc6\x42\xcb\x6e\xb9\xa0\x60\xe5\x4b\xf8\xd6\x92\x88\xfb\xee\x49\x04")
             }
+            Kind::Sha256 => ObjectId::Sha256(
+                *b"\x6e\xf1\x9b\x41\x22\x5c\x53\x69\xf1\xc1\x04\xd4\x5d\x8d\x85\xef\xa9\xb0\x57\xb5\x3b\x14\xb4\xb9\xb9\x39\xdd\x74\xde\xcc\x53\x21",
+            ),
         }
     }
 
@@ -124,6 +135,7 @@ impl ObjectId {
     pub fn is_null(&self) -> bool {
         match self {
             ObjectId::Sha1(digest) => &digest[..] == oid::null_sha1().as_bytes(),
+            ObjectId::Sha256(digest) => &digest[..] == oid::null_sha256().as_bytes(),
         }
     }
 
@@ -138,6 +150,7 @@ impl ObjectId {
     pub const fn null(kind: crate::Kind) -> ObjectId {
         match kind {
             crate::Kind::Sha1 => Self::null_sha1(),
+            crate::Kind::Sha256 => Self::null_sha256(),
         }
     }
 }
@@ -167,10 +180,30 @@ impl ObjectId {
     }
 }
 
+/// Sha256 hash specific methods
+impl ObjectId {
+    /// Instantiate an Digest from a slice 32 borrowed bytes of a Sha256 digest.
+    ///
+    /// Panics of the slice doesn't have a length of 32.
+    #[inline]
+    pub(crate) fn from_32_bytes(b: &[u8]) -> ObjectId {
+        let mut id = [0; SIZE_OF_SHA256_DIGEST];
+        id.copy_from_slice(b);
+        ObjectId::Sha256(id)
+    }
+
+    /// Returns an Digest representing a Sha256 with whose memory is zeroed.
+    #[inline]
+    pub(crate) const fn null_sha256() -> ObjectId {
+        ObjectId::Sha256([0u8; 32])
+    }
+}
+
 impl std::fmt::Debug for ObjectId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ObjectId::Sha1(_hash) => f.write_str("Sha1(")?,
+            ObjectId::Sha256(_hash) => f.write_str("Sha256(")?,
         }
         for b in self.as_bytes() {
             write!(f, "{b:02x}")?;
@@ -185,10 +218,17 @@ impl From<[u8; SIZE_OF_SHA1_DIGEST]> for ObjectId {
     }
 }
 
+impl From<[u8; SIZE_OF_SHA256_DIGEST]> for ObjectId {
+    fn from(v: [u8; 32]) -> Self {
+        Self::Sha256(v)
+    }
+}
+
 impl From<&[u8]> for ObjectId {
     fn from(v: &[u8]) -> Self {
         match v.len() {
             20 => Self::Sha1(v.try_into().expect("prior length validation")),
+            32 => Self::Sha256(v.try_into().expect("prior length validation")),
             other => panic!("BUG: unsupported hash len: {other}"),
         }
     }
@@ -198,6 +238,7 @@ impl From<&crate::oid> for ObjectId {
     fn from(v: &oid) -> Self {
         match v.kind() {
             crate::Kind::Sha1 => ObjectId::from_20_bytes(v.as_bytes()),
+            crate::Kind::Sha256 => ObjectId::from_32_bytes(v.as_bytes()),
         }
     }
 }
@@ -5,8 +5,6 @@ use filetime::FileTime;
 
 use crate::{entry, extension, Entry, PathStorage, State, Version};
 
-// TODO: integrate this somehow, somewhere, depending on later usage.
-#[allow(dead_code)]
 mod sparse;
 
 /// General information and entries
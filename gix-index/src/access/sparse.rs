@@ -1,59 +1,242 @@
-/// Configuration related to sparse indexes.
-#[derive(Debug, Default, Clone, Copy)]
-pub struct Options {
-    /// If true, certain entries in the index will be excluded / skipped for certain operations,
-    /// based on the ignore patterns in the `.git/info/sparse-checkout` file. These entries will
-    /// carry the [`SKIP_WORKTREE`][crate::entry::Flags::SKIP_WORKTREE] flag.
+use bstr::{BStr, ByteSlice};
+
+use crate::{
+    entry::{Flags, Mode, Stat},
+    extension, sparse, Entry, PathStorage, State,
+};
+
+/// Sparse checkout and sparse indices
+impl State {
+    /// Set the [`SKIP_WORKTREE`][Flags::SKIP_WORKTREE] flag on all file entries which are excluded by `patterns`, and
+    /// clear it on all file entries that are included, matching paths with the given `case`.
+    /// Return the amount of entries that are excluded from the worktree afterwards.
     ///
-    /// This typically is the value of `core.sparseCheckout` in the git configuration.
-    pub sparse_checkout: bool,
+    /// Conflicting entries are always included, and [sparse directory entries][Mode::DIR] are always excluded.
+    /// Expand them with [`expand_sparse_directories()`][State::expand_sparse_directories()] beforehand if `patterns` may
+    /// include some of their content.
+    pub fn apply_sparse_patterns(&mut self, patterns: &sparse::Patterns, case: gix_glob::pattern::Case) -> usize {
+        let mut num_skipped = 0;
+        for (entry, path) in self.entries_mut_with_paths() {
+            let skip = if entry.mode.is_sparse() {
+                true
+            } else {
+                entry.stage() == 0 && !patterns.is_included(path, case)
+            };
+            if skip {
+                entry.flags.insert(Flags::SKIP_WORKTREE | Flags::EXTENDED);
+                num_skipped += 1;
+            } else {
+                entry.flags.remove(Flags::SKIP_WORKTREE);
+                if !entry.flags.contains(Flags::INTENT_TO_ADD) {
+                    entry.flags.remove(Flags::EXTENDED);
+                }
+            }
+        }
+        num_skipped
+    }
 
-    /// Interpret the `.git/info/sparse-checkout` file using _cone mode_.
+    /// Replace all [sparse directory entries][Mode::DIR] with the entries of the tree they point to, obtained with `find`,
+    /// and mark them with the [`SKIP_WORKTREE`][Flags::SKIP_WORKTREE] flag. Return the amount of expanded directories.
     ///
-    /// If true, _cone mode_ is active and entire directories will be included in the checkout, as well as files in the root
-    /// of the repository.
-    /// If false, non-cone mode is active and entries to _include_ will be matched with patterns like those found in `.gitignore` files.
+    /// The [`tree` extension][State::tree()] is updated to also describe the content of the expanded directories,
+    /// so that they can be [collapsed][State::collapse_sparse_directories()] again later.
     ///
-    /// This typically is the value of `core.sparseCheckoutCone` in the git configuration.
-    pub directory_patterns_only: bool,
+    /// Afterwards, this index is not sparse anymore.
+    pub fn expand_sparse_directories<Find>(
+        &mut self,
+        mut find: Find,
+    ) -> Result<usize, gix_traverse::tree::breadthfirst::Error>
+    where
+        Find: for<'a> FnMut(&gix_hash::oid, &'a mut Vec<u8>) -> Option<gix_object::TreeRefIter<'a>>,
+    {
+        let mut num_expanded = 0;
+        let mut invalidate_tree = false;
+        if self.entries.iter().any(|e| e.mode.is_sparse()) {
+            let mut entries = Vec::with_capacity(self.entries.len());
+            let mut path_backing = PathStorage::with_capacity(self.path_backing.len());
+            for entry in &self.entries {
+                let dir = entry.path_in(&self.path_backing);
+                if !entry.mode.is_sparse() {
+                    push_entry(&mut entries, &mut path_backing, entry.clone(), [dir]);
+                    continue;
+                }
+                let tree = State::from_tree(&entry.id, &mut find)?;
+                if let Some(root) = self.tree.as_mut() {
+                    let components: Vec<_> = dir.split_str("/").filter(|c| !c.is_empty()).collect();
+                    let name = components.last().copied().unwrap_or_default();
+                    let node = tree_extension_node(&entry.id, name, &mut find)?;
+                    if !replace_tree_extension_node(root, &components, node) {
+                        invalidate_tree = true;
+                    }
+                }
+                for tree_entry in tree.entries() {
+                    let mut tree_entry = tree_entry.clone();
+                    tree_entry.flags.insert(Flags::SKIP_WORKTREE | Flags::EXTENDED);
+                    let path = tree_entry.path(&tree);
+                    push_entry(&mut entries, &mut path_backing, tree_entry, [dir, path]);
+                }
+                num_expanded += 1;
+            }
+            self.entries = entries;
+            self.path_backing = path_backing;
+            self.sort_entries();
+        }
+        if invalidate_tree {
+            self.tree = None;
+        }
+        self.is_sparse = false;
+        Ok(num_expanded)
+    }
 
-    /// If true, will attempt to write a sparse index file which only works in cone mode.
-    ///
-    /// A sparse index has [`DIR` entries][crate::entry::Mode::DIR] that represent entire directories to be skipped
-    /// during checkout and other operations due to the added presence of
-    /// the [`SKIP_WORKTREE`][crate::entry::Flags::SKIP_WORKTREE] flag.
+    /// Replace all entries of directories which are entirely excluded by `cone` with a single
+    /// [sparse directory entry][Mode::DIR], and return the amount of directories that were collapsed.
     ///
-    /// This is typically the value of `index.sparse` in the git configuration.
-    pub write_sparse_index: bool,
+    /// Only directories whose entries all carry the [`SKIP_WORKTREE`][Flags::SKIP_WORKTREE] flag without conflicts
+    /// and for which the [`tree` extension][State::tree()] has a valid entry can be collapsed, as the tree id of
+    /// the directory is taken from there.
+    pub fn collapse_sparse_directories(&mut self, cone: &sparse::Cone) -> usize {
+        let mut num_collapsed = 0;
+        let mut entries = Vec::with_capacity(self.entries.len());
+        let mut path_backing = PathStorage::with_capacity(self.path_backing.len());
+        let mut idx = 0;
+        while idx < self.entries.len() {
+            let entry = &self.entries[idx];
+            let path = entry.path(self);
+            let collapsed = (entry.flags.contains(Flags::SKIP_WORKTREE) && !entry.mode.is_sparse())
+                .then(|| self.collapsible_directory(cone, path, idx))
+                .flatten();
+            match collapsed {
+                Some((dir, id, num_entries)) => {
+                    let sparse_entry = Entry {
+                        stat: Stat::default(),
+                        id,
+                        flags: Flags::SKIP_WORKTREE | Flags::EXTENDED,
+                        mode: Mode::DIR,
+                        path: 0..0,
+                    };
+                    push_entry(&mut entries, &mut path_backing, sparse_entry, [dir, b"/".as_bstr()]);
+                    num_collapsed += 1;
+                    idx += num_entries;
+                }
+                None => {
+                    push_entry(&mut entries, &mut path_backing, entry.clone(), [path]);
+                    idx += 1;
+                }
+            }
+        }
+        if num_collapsed > 0 {
+            self.entries = entries;
+            self.path_backing = path_backing;
+            self.is_sparse = true;
+        }
+        num_collapsed
+    }
+
+    /// Return the leading directory of the entry at `path` and `idx` that can be collapsed, along with its tree id
+    /// and the amount of entries it contains, which start at `idx`.
+    fn collapsible_directory<'a>(
+        &self,
+        cone: &sparse::Cone,
+        path: &'a BStr,
+        idx: usize,
+    ) -> Option<(&'a BStr, gix_hash::ObjectId, usize)> {
+        let mut tree = self.tree.as_ref()?;
+        let mut start = 0;
+        while let Some(end) = path[start..].find_byte(b'/').map(|pos| start + pos) {
+            let dir = path[..end].as_bstr();
+            tree = tree.children.iter().find(|t| t.name.as_slice() == path[start..end])?;
+            start = end + 1;
+            if !cone.is_excluded_directory(dir) {
+                continue;
+            }
+            // Entries of the directory must start at `idx`, which is the case if our entry is the first of them.
+            let is_first = idx == 0 || !self.entries[idx - 1].path(self).starts_with(&path[..start]);
+            if !is_first {
+                continue;
+            }
+            let num_entries = self.entries[idx..]
+                .iter()
+                .take_while(|e| e.path(self).starts_with(&path[..start]))
+                .count();
+            let is_collapsible = tree.num_entries == Some(num_entries as u32)
+                && self.entries[idx..idx + num_entries]
+                    .iter()
+                    .all(|e| e.stage() == 0 && e.flags.contains(Flags::SKIP_WORKTREE) && !e.mode.is_sparse());
+            if is_collapsible {
+                return Some((dir, tree.id, num_entries));
+            }
+        }
+        None
+    }
 }
 
-impl Options {
-    /// Derive a valid mode from all parameters that affect the 'sparseness' of the index.
-    ///
-    /// Some combinations of them degenerate to one particular mode.
-    pub fn sparse_mode(&self) -> Mode {
-        match (
-            self.sparse_checkout,
-            self.directory_patterns_only,
-            self.write_sparse_index,
-        ) {
-            (true, true, true) => Mode::IncludeDirectoriesStoreIncludedEntriesAndExcludedDirs,
-            (true, true, false) => Mode::IncludeDirectoriesStoreAllEntriesSkipUnmatched,
-            (true, false, _) => Mode::IncludeByIgnorePatternStoreAllEntriesSkipUnmatched,
-            (false, _, _) => Mode::Disabled,
+/// Create a valid `tree` extension node named `name` for the tree with `id`, with all sub-trees obtained by `find`.
+fn tree_extension_node<Find>(
+    id: &gix_hash::oid,
+    name: &[u8],
+    find: &mut Find,
+) -> Result<extension::Tree, gix_traverse::tree::breadthfirst::Error>
+where
+    Find: for<'a> FnMut(&gix_hash::oid, &'a mut Vec<u8>) -> Option<gix_object::TreeRefIter<'a>>,
+{
+    let mut buf = Vec::new();
+    let entries = find(id, &mut buf)
+        .ok_or(gix_traverse::tree::breadthfirst::Error::NotFound { oid: id.to_owned() })?
+        .entries()?;
+    let mut num_entries = 0;
+    let mut children = Vec::new();
+    for entry in entries {
+        if entry.mode == gix_object::tree::EntryMode::Tree {
+            let child = tree_extension_node(entry.oid, entry.filename, find)?;
+            num_entries += child.num_entries.unwrap_or_default();
+            children.push(child);
+        } else {
+            num_entries += 1;
         }
     }
+    children.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(extension::Tree {
+        name: name.into(),
+        id: id.to_owned(),
+        num_entries: Some(num_entries),
+        children,
+    })
 }
 
-/// Describes the configuration how a sparse index should be written, or if one should be written at all.
-#[derive(Debug)]
-pub enum Mode {
-    /// index with DIR entries for exclusion and included entries, directory-only include patterns in `.git/info/sparse-checkout` file.
-    IncludeDirectoriesStoreIncludedEntriesAndExcludedDirs,
-    /// index with all file entries and skip worktree flags for exclusion, directory-only include patterns in `.git/info/sparse-checkout` file.
-    IncludeDirectoriesStoreAllEntriesSkipUnmatched,
-    /// index with all file entries and skip-worktree flags for exclusion, `ignore` patterns to include entries in `.git/info/sparse-checkout` file.
-    IncludeByIgnorePatternStoreAllEntriesSkipUnmatched,
-    /// index with all entries, non is excluded, `.git/info/sparse-checkout` file is not considered, a regular index.
-    Disabled,
+/// Replace the node at the path made of `components` below `tree` with `node`, and adjust the entry counts of all of its
+/// parents accordingly. Return `false` if the node to replace didn't exist.
+fn replace_tree_extension_node(tree: &mut extension::Tree, components: &[&[u8]], node: extension::Tree) -> bool {
+    let child = match components
+        .split_first()
+        .and_then(|(name, _)| tree.children.iter_mut().find(|c| c.name.as_slice() == *name))
+    {
+        Some(child) => child,
+        None => return false,
+    };
+    let components = &components[1..];
+    let previous_num_entries = child.num_entries;
+    if components.is_empty() {
+        *child = node;
+    } else if !replace_tree_extension_node(child, components, node) {
+        return false;
+    }
+    tree.num_entries = match (tree.num_entries, previous_num_entries, child.num_entries) {
+        (Some(num), Some(previous), Some(current)) => Some(num - previous + current),
+        _ => None,
+    };
+    true
+}
+
+fn push_entry<'a>(
+    entries: &mut Vec<Entry>,
+    path_backing: &mut PathStorage,
+    mut entry: Entry,
+    path_components: impl IntoIterator<Item = &'a BStr>,
+) {
+    let start = path_backing.len();
+    for component in path_components {
+        path_backing.extend_from_slice(component);
+    }
+    entry.path = start..path_backing.len();
+    entries.push(entry);
 }
@@ -26,8 +26,8 @@ impl File {
         let version = self.state.write_to(&mut hasher, options)?;
 
         let hash = hasher.hash.digest();
-        out.write_all(&hash)?;
-        Ok((version, gix_hash::ObjectId::from(hash)))
+        out.write_all(hash.as_slice())?;
+        Ok((version, hash))
     }
 
     /// Write ourselves to the path we were read from after acquiring a lock, using `options`.
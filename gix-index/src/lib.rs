@@ -30,6 +30,9 @@ pub mod decode;
 ///
 pub mod verify;
 
+///
+pub mod sparse;
+
 ///
 pub mod write;
 
@@ -194,7 +197,7 @@ pub(crate) mod util {
 
 #[test]
 fn size_of_entry() {
-    assert_eq!(std::mem::size_of::<crate::Entry>(), 80);
+    assert_eq!(std::mem::size_of::<crate::Entry>(), 96);
 
     // the reason we have our own time is half the size.
     assert_eq!(std::mem::size_of::<crate::entry::stat::Time>(), 8);
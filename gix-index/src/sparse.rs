@@ -0,0 +1,240 @@
+use std::{
+    collections::BTreeSet,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use bstr::{BStr, BString, ByteSlice};
+
+/// Configuration related to sparse indexes.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Options {
+    /// If true, certain entries in the index will be excluded / skipped for certain operations,
+    /// based on the ignore patterns in the `.git/info/sparse-checkout` file. These entries will
+    /// carry the [`SKIP_WORKTREE`][crate::entry::Flags::SKIP_WORKTREE] flag.
+    ///
+    /// This typically is the value of `core.sparseCheckout` in the git configuration.
+    pub sparse_checkout: bool,
+
+    /// Interpret the `.git/info/sparse-checkout` file using _cone mode_.
+    ///
+    /// If true, _cone mode_ is active and entire directories will be included in the checkout, as well as files in the root
+    /// of the repository.
+    /// If false, non-cone mode is active and entries to _include_ will be matched with patterns like those found in `.gitignore` files.
+    ///
+    /// This typically is the value of `core.sparseCheckoutCone` in the git configuration.
+    pub directory_patterns_only: bool,
+
+    /// If true, will attempt to write a sparse index file which only works in cone mode.
+    ///
+    /// A sparse index has [`DIR` entries][crate::entry::Mode::DIR] that represent entire directories to be skipped
+    /// during checkout and other operations due to the added presence of
+    /// the [`SKIP_WORKTREE`][crate::entry::Flags::SKIP_WORKTREE] flag.
+    ///
+    /// This is typically the value of `index.sparse` in the git configuration.
+    pub write_sparse_index: bool,
+}
+
+impl Options {
+    /// Derive a valid mode from all parameters that affect the 'sparseness' of the index.
+    ///
+    /// Some combinations of them degenerate to one particular mode.
+    pub fn sparse_mode(&self) -> Mode {
+        match (
+            self.sparse_checkout,
+            self.directory_patterns_only,
+            self.write_sparse_index,
+        ) {
+            (true, true, true) => Mode::IncludeDirectoriesStoreIncludedEntriesAndExcludedDirs,
+            (true, true, false) => Mode::IncludeDirectoriesStoreAllEntriesSkipUnmatched,
+            (true, false, _) => Mode::IncludeByIgnorePatternStoreAllEntriesSkipUnmatched,
+            (false, _, _) => Mode::Disabled,
+        }
+    }
+}
+
+/// Describes the configuration how a sparse index should be written, or if one should be written at all.
+#[derive(Debug)]
+pub enum Mode {
+    /// index with DIR entries for exclusion and included entries, directory-only include patterns in `.git/info/sparse-checkout` file.
+    IncludeDirectoriesStoreIncludedEntriesAndExcludedDirs,
+    /// index with all file entries and skip worktree flags for exclusion, directory-only include patterns in `.git/info/sparse-checkout` file.
+    IncludeDirectoriesStoreAllEntriesSkipUnmatched,
+    /// index with all file entries and skip-worktree flags for exclusion, `ignore` patterns to include entries in `.git/info/sparse-checkout` file.
+    IncludeByIgnorePatternStoreAllEntriesSkipUnmatched,
+    /// index with all entries, non is excluded, `.git/info/sparse-checkout` file is not considered, a regular index.
+    Disabled,
+}
+
+/// The patterns of a `.git/info/sparse-checkout` file, deciding which entries are part of the worktree.
+#[derive(Debug, Clone)]
+pub enum Patterns {
+    /// Patterns in _cone mode_, which only include entire directories and the files in their leading directories.
+    Cone(Cone),
+    /// Patterns in _non-cone mode_, which are matched like `.gitignore` patterns, with matching entries being included.
+    NonCone(gix_glob::search::pattern::List<gix_ignore::search::Ignore>),
+}
+
+/// The directories described by patterns in _cone mode_.
+///
+/// All directories are relative to the repository root and use slashes as separators, without leading or trailing slash.
+/// Files in the root of the repository are always included.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Cone {
+    /// Directories which are included with all of their content, recursively.
+    pub recursive: BTreeSet<BString>,
+    /// Directories of which only the files directly contained in them are included, which includes all leading
+    /// directories of [`recursive`][Cone::recursive] ones.
+    pub parents: BTreeSet<BString>,
+}
+
+/// Instantiation
+impl Patterns {
+    /// Parse `bytes` as the content of a `.git/info/sparse-checkout` file, in _cone mode_ if `cone_mode` is true.
+    ///
+    /// Note that just like `git`, we fall back to _non-cone mode_ if the patterns can't be interpreted in _cone mode_.
+    pub fn from_bytes(bytes: &[u8], cone_mode: bool) -> Self {
+        cone_mode
+            .then(|| Cone::from_bytes(bytes))
+            .flatten()
+            .map(Patterns::Cone)
+            .unwrap_or_else(|| {
+                Patterns::NonCone(gix_glob::search::pattern::List::from_bytes(
+                    bytes,
+                    PathBuf::from("info/sparse-checkout"),
+                    None,
+                ))
+            })
+    }
+
+    /// Read the sparse-checkout patterns from `path`, typically `.git/info/sparse-checkout`, using `buf` as buffer,
+    /// or return `None` if the file doesn't exist.
+    ///
+    /// See [`from_bytes()`][Patterns::from_bytes()] for details on `cone_mode`.
+    pub fn from_file(path: impl AsRef<Path>, cone_mode: bool, buf: &mut Vec<u8>) -> std::io::Result<Option<Self>> {
+        buf.clear();
+        match std::fs::File::open(path.as_ref()) {
+            Ok(mut file) => {
+                file.read_to_end(buf)?;
+                Ok(Some(Self::from_bytes(buf, cone_mode)))
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// Matching
+impl Patterns {
+    /// Return true if the file at the repository-relative `path` is included in the worktree, matching with the given `case`.
+    pub fn is_included(&self, path: &BStr, case: gix_glob::pattern::Case) -> bool {
+        match self {
+            Patterns::Cone(cone) => cone.is_included(path),
+            Patterns::NonCone(list) => {
+                let basename_pos = path.rfind_byte(b'/').map(|pos| pos + 1);
+                if let Some(m) =
+                    gix_ignore::search::pattern_matching_relative_path(list, path, basename_pos, Some(false), case)
+                {
+                    return !m.pattern.is_negative();
+                }
+                let mut dir = path;
+                while let Some(pos) = dir.rfind_byte(b'/') {
+                    dir = dir[..pos].as_bstr();
+                    let basename_pos = dir.rfind_byte(b'/').map(|pos| pos + 1);
+                    if let Some(m) =
+                        gix_ignore::search::pattern_matching_relative_path(list, dir, basename_pos, Some(true), case)
+                    {
+                        return !m.pattern.is_negative();
+                    }
+                }
+                false
+            }
+        }
+    }
+}
+
+impl Cone {
+    /// Parse `bytes` as patterns in _cone mode_, or return `None` if at least one of the patterns isn't valid in _cone mode_.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut out = Cone::default();
+        let bytes = bytes.strip_prefix(b"\xef\xbb\xbf").unwrap_or(bytes);
+        for line in bytes.lines() {
+            let line = line.trim_end();
+            if line.is_empty() || line.starts_with(b"#") {
+                continue;
+            }
+            match line {
+                b"/*" | b"!/*/" => {}
+                _ => {
+                    if let Some(negative) = line.strip_prefix(b"!") {
+                        let dir = negative.strip_prefix(b"/")?.strip_suffix(b"/*/")?;
+                        out.parents.insert(unescape_directory(dir)?);
+                    } else {
+                        let dir = line.strip_prefix(b"/")?.strip_suffix(b"/")?;
+                        out.recursive.insert(unescape_directory(dir)?);
+                    }
+                }
+            }
+        }
+
+        for dir in &out.parents {
+            out.recursive.remove(dir);
+        }
+        let mut leading_dirs = Vec::new();
+        for dir in out.recursive.iter().chain(out.parents.iter()) {
+            let mut dir = dir.as_bstr();
+            while let Some(pos) = dir.rfind_byte(b'/') {
+                dir = dir[..pos].as_bstr();
+                leading_dirs.push(dir.to_owned());
+            }
+        }
+        out.parents.extend(leading_dirs);
+        Some(out)
+    }
+
+    /// Return true if the file at the repository-relative `path` is included in the worktree.
+    pub fn is_included(&self, path: &BStr) -> bool {
+        match path.rfind_byte(b'/') {
+            None => true,
+            Some(pos) => {
+                let dir = path[..pos].as_bstr();
+                self.parents.contains(dir) || self.is_in_recursive_directory(dir)
+            }
+        }
+    }
+
+    /// Return true if the directory at the repository-relative `dir` (without trailing slash) and all of its content
+    /// are excluded from the worktree.
+    pub fn is_excluded_directory(&self, dir: &BStr) -> bool {
+        !self.parents.contains(dir) && !self.is_in_recursive_directory(dir)
+    }
+
+    fn is_in_recursive_directory(&self, mut dir: &BStr) -> bool {
+        loop {
+            if self.recursive.contains(dir) {
+                return true;
+            }
+            match dir.rfind_byte(b'/') {
+                Some(pos) => dir = dir[..pos].as_bstr(),
+                None => return false,
+            }
+        }
+    }
+}
+
+/// Remove escapes from `dir`, or return `None` if it contains unescaped wildcards or is empty.
+fn unescape_directory(dir: &[u8]) -> Option<BString> {
+    if dir.is_empty() {
+        return None;
+    }
+    let mut out = BString::default();
+    let mut bytes = dir.iter();
+    while let Some(b) = bytes.next() {
+        match b {
+            b'\\' => out.push(*bytes.next()?),
+            b'*' | b'?' | b'[' => return None,
+            _ => out.push(*b),
+        }
+    }
+    Some(out)
+}
@@ -6,6 +6,7 @@ mod access;
 mod entry;
 mod file;
 mod init;
+mod sparse;
 
 pub fn hex_to_id(hex: &str) -> ObjectId {
     ObjectId::from_hex(hex.as_bytes()).expect("40 bytes hex")
@@ -24,7 +25,7 @@ pub fn loose_file_path(name: &str) -> PathBuf {
 
 #[test]
 fn size_of_entry() {
-    assert_eq!(std::mem::size_of::<gix_index::Entry>(), 80);
+    assert_eq!(std::mem::size_of::<gix_index::Entry>(), 96);
 
     // the reason we have our own time is half the size.
     assert_eq!(std::mem::size_of::<gix_index::entry::stat::Time>(), 8);
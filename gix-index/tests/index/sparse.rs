@@ -0,0 +1,102 @@
+use gix::prelude::FindExt;
+use gix_glob::pattern::Case;
+use gix_index::{entry::Flags, sparse};
+use gix_testtools::scripted_fixture_read_only_standalone;
+
+use crate::index::Fixture;
+
+fn patterns(fixture: &str, cone_mode: bool) -> crate::Result<sparse::Patterns> {
+    let repo_dir = scripted_fixture_read_only_standalone(format!("make_index/{fixture}.sh"))?;
+    let path = repo_dir.join(".git").join("info").join("sparse-checkout");
+    Ok(sparse::Patterns::from_file(path, cone_mode, &mut Vec::new())?.expect("file exists"))
+}
+
+fn paths_and_flags(state: &gix_index::State) -> Vec<(bstr::BString, gix_index::entry::Mode, Flags)> {
+    state
+        .entries()
+        .iter()
+        .map(|e| (e.path(state).to_owned(), e.mode, e.flags))
+        .collect()
+}
+
+#[test]
+fn cone_patterns_parse_into_recursive_and_parent_directories() -> crate::Result {
+    let cone = match patterns("v3_skip_worktree", true)? {
+        sparse::Patterns::Cone(cone) => cone,
+        sparse::Patterns::NonCone(_) => unreachable!("valid cone patterns"),
+    };
+    assert_eq!(cone.recursive.into_iter().collect::<Vec<_>>(), ["c1/c2"]);
+    assert_eq!(cone.parents.into_iter().collect::<Vec<_>>(), ["c1"]);
+    Ok(())
+}
+
+#[test]
+fn invalid_cone_patterns_fall_back_to_non_cone_mode() {
+    assert!(matches!(
+        sparse::Patterns::from_bytes(b"/*\n!/*/\n*.txt\n", true),
+        sparse::Patterns::NonCone(_)
+    ));
+    assert!(matches!(
+        sparse::Patterns::from_bytes(b"/*\n!/*/\n/dir/\n", true),
+        sparse::Patterns::Cone(_)
+    ));
+}
+
+#[test]
+fn apply_patterns_in_cone_mode_reproduces_skip_worktree_flags_set_by_git() -> crate::Result {
+    let mut file = Fixture::Generated("v3_skip_worktree").open();
+    let expected = paths_and_flags(&file);
+    for entry in file.entries_mut() {
+        entry.flags = Flags::empty();
+    }
+
+    let num_skipped = file.apply_sparse_patterns(&patterns("v3_skip_worktree", true)?, Case::Sensitive);
+    assert_eq!(num_skipped, 7);
+    assert_eq!(paths_and_flags(&file), expected);
+    Ok(())
+}
+
+#[test]
+fn apply_patterns_in_non_cone_mode_reproduces_skip_worktree_flags_set_by_git() -> crate::Result {
+    let mut file = Fixture::Generated("v3_sparse_index_non_cone").open();
+    let expected = paths_and_flags(&file);
+    for entry in file.entries_mut() {
+        entry.flags = Flags::empty();
+    }
+
+    let num_skipped = file.apply_sparse_patterns(&patterns("v3_sparse_index_non_cone", false)?, Case::Sensitive);
+    assert_eq!(num_skipped, 11);
+    assert_eq!(paths_and_flags(&file), expected);
+    Ok(())
+}
+
+#[test]
+fn expand_and_collapse_sparse_directories_roundtrip() -> crate::Result {
+    let repo_dir = scripted_fixture_read_only_standalone("make_index/v3_sparse_index.sh")?;
+    let repo = gix::open(&repo_dir)?;
+    let mut file = Fixture::Generated("v3_sparse_index").open();
+    let sparse_entries = paths_and_flags(&file);
+    assert!(file.is_sparse());
+
+    let num_expanded = file.expand_sparse_directories(|oid, buf| repo.objects.find_tree_iter(oid, buf).ok())?;
+    assert_eq!(num_expanded, 2, "c1/c3 and d");
+    assert!(!file.is_sparse());
+    file.verify_entries()?;
+    file.verify_extensions(true, |oid, buf| repo.objects.find_tree_iter(oid, buf).ok())?;
+    assert_eq!(
+        paths_and_flags(&file),
+        paths_and_flags(&Fixture::Generated("v3_skip_worktree").open()),
+        "expansion yields what git writes without a sparse index"
+    );
+
+    let cone = match patterns("v3_sparse_index", true)? {
+        sparse::Patterns::Cone(cone) => cone,
+        sparse::Patterns::NonCone(_) => unreachable!("valid cone patterns"),
+    };
+    let num_collapsed = file.collapse_sparse_directories(&cone);
+    assert_eq!(num_collapsed, 2);
+    assert!(file.is_sparse());
+    file.verify_entries()?;
+    assert_eq!(paths_and_flags(&file), sparse_entries);
+    Ok(())
+}
@@ -10,11 +10,13 @@ impl<'a> Data<'a> {
     /// Decodes the data in the backing slice into a [`ObjectRef`], allowing to access all of its data
     /// conveniently. The cost of parsing an object is negligible.
     ///
+    /// All ids in trees are expected to be of kind `object_hash`.
+    ///
     /// **Note** that [mutable, decoded objects][crate::Object] can be created from [`Data`]
     /// using [`crate::ObjectRef::into_owned()`].
-    pub fn decode(&self) -> Result<ObjectRef<'a>, crate::decode::Error> {
+    pub fn decode(&self, object_hash: gix_hash::Kind) -> Result<ObjectRef<'a>, crate::decode::Error> {
         Ok(match self.kind {
-            Kind::Tree => ObjectRef::Tree(TreeRef::from_bytes(self.data)?),
+            Kind::Tree => ObjectRef::Tree(TreeRef::from_bytes(self.data, object_hash)?),
             Kind::Blob => ObjectRef::Blob(BlobRef { data: self.data }),
             Kind::Commit => ObjectRef::Commit(CommitRef::from_bytes(self.data)?),
             Kind::Tag => ObjectRef::Tag(TagRef::from_bytes(self.data)?),
@@ -22,10 +24,10 @@ impl<'a> Data<'a> {
     }
 
     /// Returns this object as tree iterator to parse entries one at a time to avoid allocations, or
-    /// `None` if this is not a tree object.
-    pub fn try_into_tree_iter(self) -> Option<TreeRefIter<'a>> {
+    /// `None` if this is not a tree object. The ids of all entries are expected to be of kind `object_hash`.
+    pub fn try_into_tree_iter(self, object_hash: gix_hash::Kind) -> Option<TreeRefIter<'a>> {
         match self.kind {
-            Kind::Tree => Some(TreeRefIter::from_bytes(self.data)),
+            Kind::Tree => Some(TreeRefIter::from_bytes(self.data, object_hash)),
             _ => None,
         }
     }
@@ -222,6 +222,8 @@ pub struct TreeRef<'a> {
 pub struct TreeRefIter<'a> {
     /// The directories and files contained in this tree.
     data: &'a [u8],
+    /// The kind of hash used by the ids of all entries.
+    object_hash: gix_hash::Kind,
 }
 
 /// A mutable Tree, containing other trees, blobs or commits.
@@ -384,5 +386,5 @@ pub fn compute_hash(hash_kind: gix_hash::Kind, object_kind: Kind, data: &[u8]) -
     hasher.update(&header);
     hasher.update(data);
 
-    hasher.digest().into()
+    hasher.digest()
 }
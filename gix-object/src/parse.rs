@@ -1,7 +1,7 @@
 use bstr::{BStr, BString, ByteVec};
 use nom::{
     bytes::complete::{is_not, tag, take_until, take_while_m_n},
-    combinator::{peek, recognize},
+    combinator::{peek, recognize, verify},
     error::{context, ContextError, ParseError},
     multi::many1_count,
     sequence::{preceded, terminated, tuple},
@@ -66,10 +66,13 @@ fn is_hex_digit_lc(b: u8) -> bool {
 }
 
 pub fn hex_hash<'a, E: ParseError<&'a [u8]>>(i: &'a [u8]) -> IResult<&'a [u8], &'a BStr, E> {
-    take_while_m_n(
-        gix_hash::Kind::shortest().len_in_hex(),
-        gix_hash::Kind::longest().len_in_hex(),
-        is_hex_digit_lc,
+    verify(
+        take_while_m_n(
+            gix_hash::Kind::shortest().len_in_hex(),
+            gix_hash::Kind::longest().len_in_hex(),
+            is_hex_digit_lc,
+        ),
+        |hex: &[u8]| matches!(gix_hash::Kind::from_hex_len(hex.len()), Some(kind) if kind.len_in_hex() == hex.len()),
     )(i)
     .map(|(i, hex)| (i, hex.as_bstr()))
 }
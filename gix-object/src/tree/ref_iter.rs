@@ -5,16 +5,18 @@ use nom::error::ParseError;
 use crate::{tree, tree::EntryRef, TreeRef, TreeRefIter};
 
 impl<'a> TreeRefIter<'a> {
-    /// Instantiate an iterator from the given tree data.
-    pub fn from_bytes(data: &'a [u8]) -> TreeRefIter<'a> {
-        TreeRefIter { data }
+    /// Instantiate an iterator from the given tree data, with the ids of all entries being of kind `object_hash`.
+    pub fn from_bytes(data: &'a [u8], object_hash: gix_hash::Kind) -> TreeRefIter<'a> {
+        TreeRefIter { data, object_hash }
     }
 }
 
 impl<'a> TreeRef<'a> {
-    /// Deserialize a Tree from `data`.
-    pub fn from_bytes(data: &'a [u8]) -> Result<TreeRef<'a>, crate::decode::Error> {
-        decode::tree(data).map(|(_, t)| t).map_err(crate::decode::Error::from)
+    /// Deserialize a Tree from `data`, with the ids of all entries being of kind `object_hash`.
+    pub fn from_bytes(data: &'a [u8], object_hash: gix_hash::Kind) -> Result<TreeRef<'a>, crate::decode::Error> {
+        decode::tree(data, object_hash)
+            .map(|(_, t)| t)
+            .map_err(crate::decode::Error::from)
     }
 
     /// Create an instance of the empty tree.
@@ -39,7 +41,7 @@ impl<'a> Iterator for TreeRefIter<'a> {
         if self.data.is_empty() {
             return None;
         }
-        match decode::fast_entry(self.data) {
+        match decode::fast_entry(self.data, self.object_hash) {
             Some((data_left, entry)) => {
                 self.data = data_left;
                 Some(Ok(entry))
@@ -109,7 +111,7 @@ mod decode {
 
     const NULL: &[u8] = b"\0";
 
-    pub fn fast_entry(i: &[u8]) -> Option<(&[u8], EntryRef<'_>)> {
+    pub fn fast_entry(i: &[u8], object_hash: gix_hash::Kind) -> Option<(&[u8], EntryRef<'_>)> {
         let mut mode = 0u32;
         let mut spacer_pos = 1;
         for b in i.iter().take_while(|b| **b != b' ') {
@@ -123,40 +125,46 @@ mod decode {
         let mode = tree::EntryMode::try_from(mode).ok()?;
         let (filename, i) = i.split_at(i.find_byte(0)?);
         let i = &i[1..];
-        const HASH_LEN_FIXME: usize = 20; // TODO: know actual /desired length or we may overshoot
+        let hash_len = object_hash.len_in_bytes();
         let (oid, i) = match i.len() {
-            len if len < HASH_LEN_FIXME => return None,
-            _ => i.split_at(20),
+            len if len < hash_len => return None,
+            _ => i.split_at(hash_len),
         };
         Some((
             i,
             EntryRef {
                 mode,
                 filename: filename.as_bstr(),
-                oid: gix_hash::oid::try_from_bytes(oid).expect("we counted exactly 20 bytes"),
+                oid: gix_hash::oid::try_from_bytes(oid).expect("we counted exactly as many bytes as the hash has"),
             },
         ))
     }
 
-    pub fn entry<'a, E: ParseError<&'a [u8]>>(i: &'a [u8]) -> IResult<&[u8], EntryRef<'_>, E> {
+    pub fn entry<'a, E: ParseError<&'a [u8]>>(
+        i: &'a [u8],
+        object_hash: gix_hash::Kind,
+    ) -> IResult<&'a [u8], EntryRef<'a>, E> {
         let (i, mode) = terminated(take_while_m_n(5, 6, is_digit), tag(SPACE))(i)?;
         let mode = tree::EntryMode::try_from(mode)
             .map_err(|invalid| nom::Err::Error(E::from_error_kind(invalid, nom::error::ErrorKind::MapRes)))?;
         let (i, filename) = terminated(take_while1(|b| b != NULL[0]), tag(NULL))(i)?;
-        let (i, oid) = take(20u8)(i)?; // TODO: make this compatible with other hash lengths
+        let (i, oid) = take(object_hash.len_in_bytes())(i)?;
 
         Ok((
             i,
             EntryRef {
                 mode,
                 filename: filename.as_bstr(),
-                oid: gix_hash::oid::try_from_bytes(oid).expect("we counted exactly 20 bytes"),
+                oid: gix_hash::oid::try_from_bytes(oid).expect("we counted exactly as many bytes as the hash has"),
             },
         ))
     }
 
-    pub fn tree<'a, E: ParseError<&'a [u8]>>(i: &'a [u8]) -> IResult<&'a [u8], TreeRef<'a>, E> {
-        let (i, entries) = all_consuming(many0(entry))(i)?;
+    pub fn tree<'a, E: ParseError<&'a [u8]>>(
+        i: &'a [u8],
+        object_hash: gix_hash::Kind,
+    ) -> IResult<&'a [u8], TreeRef<'a>, E> {
+        let (i, entries) = all_consuming(many0(|i| entry(i, object_hash)))(i)?;
         Ok((i, TreeRef { entries }))
     }
 }
@@ -52,8 +52,8 @@ fn fixture_name(kind: &str, path: &str) -> Vec<u8> {
 fn size_in_memory() {
     let actual = std::mem::size_of::<gix_object::Object>();
     assert!(
-        actual <= 264,
-        "{actual} <= 264: Prevent unexpected growth of what should be lightweight objects"
+        actual <= 288,
+        "{actual} <= 288: Prevent unexpected growth of what should be lightweight objects"
     )
 }
 
@@ -5,13 +5,17 @@ mod iter {
 
     #[test]
     fn empty() {
-        assert_eq!(TreeRefIter::from_bytes(&[]).count(), 0, "empty trees are definitely ok");
+        assert_eq!(
+            TreeRefIter::from_bytes(&[], gix_hash::Kind::Sha1).count(),
+            0,
+            "empty trees are definitely ok"
+        );
     }
 
     #[test]
     fn error_handling() {
         let data = fixture_name("tree", "everything.tree");
-        let iter = TreeRefIter::from_bytes(&data[..data.len() / 2]);
+        let iter = TreeRefIter::from_bytes(&data[..data.len() / 2], gix_hash::Kind::Sha1);
         let entries = iter.collect::<Vec<_>>();
         assert!(
             entries.last().expect("at least one token").is_err(),
@@ -22,7 +26,8 @@ mod iter {
     #[test]
     fn everything() -> crate::Result {
         assert_eq!(
-            TreeRefIter::from_bytes(&fixture_name("tree", "everything.tree")).collect::<Result<Vec<_>, _>>()?,
+            TreeRefIter::from_bytes(&fixture_name("tree", "everything.tree"), gix_hash::Kind::Sha1)
+                .collect::<Result<Vec<_>, _>>()?,
             vec![
                 EntryRef {
                     mode: tree::EntryMode::BlobExecutable,
@@ -55,6 +60,51 @@ mod iter {
     }
 }
 
+mod sha256 {
+    use gix_object::{tree, TreeRef, TreeRefIter, WriteTo};
+
+    #[test]
+    fn entries_are_decoded_with_the_given_hash_kind() -> crate::Result {
+        let id = |hex: &str| gix_hash::ObjectId::from_hex(hex.repeat(64).as_bytes()).expect("valid hex");
+        let tree = gix_object::Tree {
+            entries: vec![
+                tree::Entry {
+                    mode: tree::EntryMode::Blob,
+                    filename: "a".into(),
+                    oid: id("1"),
+                },
+                tree::Entry {
+                    mode: tree::EntryMode::Tree,
+                    filename: "dir".into(),
+                    oid: id("2"),
+                },
+                tree::Entry {
+                    mode: tree::EntryMode::BlobExecutable,
+                    filename: "z".into(),
+                    oid: id("f"),
+                },
+            ],
+        };
+        let mut buf = Vec::new();
+        tree.write_to(&mut buf)?;
+
+        let entries = TreeRefIter::from_bytes(&buf, gix_hash::Kind::Sha256).collect::<Result<Vec<_>, _>>()?;
+        assert!(entries.iter().all(|e| e.oid.kind() == gix_hash::Kind::Sha256));
+        assert_eq!(gix_object::Tree::from(TreeRef { entries }), tree);
+        assert_eq!(
+            gix_object::Tree::from(TreeRef::from_bytes(&buf, gix_hash::Kind::Sha256)?),
+            tree
+        );
+
+        assert!(
+            TreeRef::from_bytes(&buf, gix_hash::Kind::Sha1).is_err(),
+            "the hash kind isn't guessed"
+        );
+        assert!(TreeRefIter::from_bytes(&buf, gix_hash::Kind::Sha1).any(|e| e.is_err()));
+        Ok(())
+    }
+}
+
 mod from_bytes {
     use gix_object::{bstr::ByteSlice, tree, tree::EntryRef, TreeRef};
 
@@ -63,7 +113,7 @@ mod from_bytes {
     #[test]
     fn empty() -> crate::Result {
         assert_eq!(
-            TreeRef::from_bytes(&[])?,
+            TreeRef::from_bytes(&[], gix_hash::Kind::Sha1)?,
             TreeRef { entries: vec![] },
             "empty trees are valid despite usually rare in the wild"
         );
@@ -73,7 +123,7 @@ mod from_bytes {
     #[test]
     fn everything() -> crate::Result {
         assert_eq!(
-            TreeRef::from_bytes(&fixture_name("tree", "everything.tree"))?,
+            TreeRef::from_bytes(&fixture_name("tree", "everything.tree"), gix_hash::Kind::Sha1)?,
             TreeRef {
                 entries: vec![
                     EntryRef {
@@ -110,7 +160,7 @@ mod from_bytes {
     #[test]
     fn maybe_special() -> crate::Result {
         assert_eq!(
-            TreeRef::from_bytes(&fixture_name("tree", "maybe-special.tree"))?
+            TreeRef::from_bytes(&fixture_name("tree", "maybe-special.tree"), gix_hash::Kind::Sha1)?
                 .entries
                 .len(),
             160
@@ -121,7 +171,7 @@ mod from_bytes {
     #[test]
     fn definitely_special() -> crate::Result {
         assert_eq!(
-            TreeRef::from_bytes(&fixture_name("tree", "definitely-special.tree"))?
+            TreeRef::from_bytes(&fixture_name("tree", "definitely-special.tree"), gix_hash::Kind::Sha1)?
                 .entries
                 .len(),
             19
@@ -138,7 +188,7 @@ mod entries {
         let root = gix_testtools::scripted_fixture_read_only("make_trees.sh")?;
         let input = std::fs::read(root.join("tree.baseline"))?;
 
-        let mut tree = TreeRef::from_bytes(&input)?;
+        let mut tree = TreeRef::from_bytes(&input, gix_hash::Kind::Sha1)?;
         let expected = tree.entries.clone();
 
         tree.entries.sort();
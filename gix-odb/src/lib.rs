@@ -7,6 +7,7 @@
 //! * loose object reading and writing
 //! * access to packed objects
 //! * multiple loose objects and pack locations as gathered from `alternates` files.
+//! * keeping new objects in [memory][memory::Proxy] on top of another object database.
 //! ## Feature Flags
 #![cfg_attr(
     feature = "document-features",
@@ -49,6 +50,9 @@ pub struct Cache<S> {
 ///
 pub mod cache;
 
+///
+pub mod memory;
+
 ///
 /// It can optionally compress the content, similarly to what would happen when using a [`loose::Store`][crate::loose::Store].
 ///
@@ -130,6 +134,8 @@ pub struct Store {
     use_multi_pack_index: bool,
     /// The hash kind to use for some operations
     object_hash: gix_hash::Kind,
+    /// The way pack data files are accessed.
+    pack_access: gix_pack::data::Access,
 }
 
 /// Create a new cached handle to the object store with support for additional options.
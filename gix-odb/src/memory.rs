@@ -0,0 +1,338 @@
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    ops::{Deref, DerefMut},
+    rc::Rc,
+    sync::Arc,
+};
+
+use gix_hash::ObjectId;
+
+use crate::{store, Cache};
+
+/// The storage for objects kept in memory, keyed by their id, along with their kind and data.
+pub type Storage = BTreeMap<ObjectId, (gix_object::Kind, Vec<u8>)>;
+
+/// An object database which keeps newly written objects in memory and serves them on top of those in the `inner` database,
+/// which is useful for dry-runs or speculative writes, like performing merges whose results may be discarded.
+///
+/// Objects in memory can be [discarded][Proxy::take_object_memory()] or [written to a single pack][Proxy::persist_as_pack()].
+/// If object memory is disabled, all writes pass through to the `inner` database.
+///
+/// Note that cloning an instance also clones all objects in memory, which aren't shared.
+#[derive(Clone)]
+pub struct Proxy<T> {
+    inner: T,
+    memory: Option<RefCell<Storage>>,
+    object_hash: gix_hash::Kind,
+}
+
+/// Lifecycle
+impl<T> Proxy<T> {
+    /// Create a new instance which keeps all written objects in memory, hashing them with `object_hash`, and which
+    /// looks up objects in `inner` if they are not in memory.
+    pub fn new(inner: T, object_hash: gix_hash::Kind) -> Self {
+        Proxy {
+            inner,
+            memory: Some(Default::default()),
+            object_hash,
+        }
+    }
+
+    /// Turn ourselves into a proxy which writes objects to the `inner` database, discarding all objects held in memory.
+    pub fn with_write_passthrough(mut self) -> Self {
+        self.memory = None;
+        self
+    }
+
+    /// Dissolve this instance, discard all objects in memory, and return the inner implementation.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+/// Object memory
+impl<T> Proxy<T> {
+    /// Keep all future writes in memory, unless this was already enabled.
+    pub fn enable_object_memory(&mut self) -> &mut Self {
+        if self.memory.is_none() {
+            self.memory = Some(Default::default());
+        }
+        self
+    }
+
+    /// Return `true` if writes are kept in memory, or `false` if they pass through to the inner database.
+    pub fn has_object_memory(&self) -> bool {
+        self.memory.is_some()
+    }
+
+    /// Take all objects held in memory and disable object memory, so future writes pass through to the inner database.
+    ///
+    /// Returns `None` if object memory wasn't enabled. To discard all objects, drop the returned storage.
+    pub fn take_object_memory(&mut self) -> Option<Storage> {
+        self.memory.take().map(RefCell::into_inner)
+    }
+
+    /// Set the object memory to `storage` to serve its objects and keep all future writes in it,
+    /// and return the previous storage if object memory was enabled.
+    pub fn set_object_memory(&mut self, storage: Storage) -> Option<Storage> {
+        self.memory.replace(RefCell::new(storage)).map(RefCell::into_inner)
+    }
+
+    /// Return the amount of objects currently held in memory.
+    pub fn num_objects_in_memory(&self) -> usize {
+        self.memory.as_ref().map_or(0, |m| m.borrow().len())
+    }
+}
+
+impl Proxy<Cache<store::Handle<Rc<crate::Store>>>> {
+    /// Convert to an instance that can be shared across threads, keeping all objects in memory.
+    pub fn into_arc(self) -> std::io::Result<Proxy<Cache<store::Handle<Arc<crate::Store>>>>> {
+        Ok(Proxy {
+            inner: self.inner.into_arc()?,
+            memory: self.memory,
+            object_hash: self.object_hash,
+        })
+    }
+}
+
+impl Proxy<Cache<store::Handle<Arc<crate::Store>>>> {
+    /// No op, as we are already an instance that can be shared across threads.
+    pub fn into_arc(self) -> std::io::Result<Proxy<Cache<store::Handle<Arc<crate::Store>>>>> {
+        Ok(self)
+    }
+}
+
+impl<S> From<store::Handle<S>> for Proxy<Cache<store::Handle<S>>>
+where
+    S: Deref<Target = crate::Store> + Clone,
+{
+    /// Create a proxy which passes writes through to the store `handle`.
+    fn from(handle: store::Handle<S>) -> Self {
+        let object_hash = handle.store_ref().object_hash();
+        Proxy::new(Cache::from(handle), object_hash).with_write_passthrough()
+    }
+}
+
+impl<S> From<Cache<store::Handle<S>>> for Proxy<Cache<store::Handle<S>>>
+where
+    S: Deref<Target = crate::Store> + Clone,
+{
+    /// Create a proxy which passes writes through to the `cache`.
+    fn from(cache: Cache<store::Handle<S>>) -> Self {
+        let object_hash = cache.store_ref().object_hash();
+        Proxy::new(cache, object_hash).with_write_passthrough()
+    }
+}
+
+impl<T> Deref for Proxy<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<T> DerefMut for Proxy<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
+
+///
+pub mod persist {
+    /// The error returned by [`Proxy::persist_as_pack()`][super::Proxy::persist_as_pack()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not create a pack entry from an object in memory")]
+        CreateEntry(#[from] gix_pack::data::output::entry::Error),
+        #[error("Could not encode pack data")]
+        WritePackData(#[from] gix_pack::data::output::bytes::Error<gix_pack::data::output::entry::Error>),
+        #[error("Could not write the pack along with its index")]
+        WriteBundle(#[from] gix_pack::bundle::write::Error),
+    }
+}
+
+impl<T> Proxy<T> {
+    /// Write all objects held in memory into a single new pack along with its index in `directory`, typically the `pack`
+    /// directory of the inner database, and clear the object memory on success.
+    ///
+    /// Returns `None` if there was no object to write.
+    pub fn persist_as_pack(
+        &self,
+        directory: impl AsRef<std::path::Path>,
+        progress: impl gix_features::progress::Progress,
+        should_interrupt: &std::sync::atomic::AtomicBool,
+    ) -> Result<Option<gix_pack::bundle::write::Outcome>, persist::Error> {
+        use gix_pack::data::output;
+
+        let memory = match self.memory.as_ref() {
+            Some(memory) if !memory.borrow().is_empty() => memory,
+            _ => return Ok(None),
+        };
+        let entries = memory
+            .borrow()
+            .iter()
+            .map(|(id, (kind, data))| {
+                output::Entry::from_data(
+                    &output::Count::from_data(*id, None),
+                    &gix_object::Data::new(*kind, data),
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut pack = Vec::new();
+        let num_entries = entries.len() as u32;
+        for written in output::bytes::FromEntriesIter::new(
+            std::iter::once(Ok::<_, output::entry::Error>(entries)),
+            &mut pack,
+            num_entries,
+            gix_pack::data::Version::default(),
+            self.object_hash,
+        ) {
+            written?;
+        }
+
+        let outcome = gix_pack::Bundle::write_to_directory(
+            pack.as_slice(),
+            Some(directory),
+            progress,
+            should_interrupt,
+            None,
+            gix_pack::bundle::write::Options {
+                thread_limit: None,
+                iteration_mode: gix_pack::data::input::Mode::Verify,
+                index_version: Default::default(),
+                object_hash: self.object_hash,
+                write_reverse_index: false,
+            },
+        )?;
+        memory.borrow_mut().clear();
+        Ok(Some(outcome))
+    }
+}
+
+mod impls {
+    use std::io::Read;
+
+    use gix_hash::{oid, ObjectId};
+    use gix_object::{Data, Kind};
+
+    use super::Proxy;
+    use crate::find::Header;
+
+    impl<T> crate::Write for Proxy<T>
+    where
+        T: crate::Write,
+    {
+        type Error = T::Error;
+
+        fn write_stream(&self, kind: Kind, size: u64, mut from: impl Read) -> Result<ObjectId, Self::Error> {
+            let memory = match self.memory.as_ref() {
+                Some(memory) => memory,
+                None => return self.inner.write_stream(kind, size, from),
+            };
+            let mut buf = Vec::with_capacity(size as usize);
+            from.read_to_end(&mut buf)?;
+
+            let mut hasher = gix_features::hash::hasher(self.object_hash);
+            hasher.update(&gix_object::encode::loose_header(kind, buf.len()));
+            hasher.update(&buf);
+            let id = hasher
+                .try_digest()
+                .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+            memory.borrow_mut().insert(id, (kind, buf));
+            Ok(id)
+        }
+    }
+
+    impl<T> crate::Find for Proxy<T>
+    where
+        T: crate::Find,
+    {
+        type Error = T::Error;
+
+        fn contains(&self, id: impl AsRef<oid>) -> bool {
+            self.memory
+                .as_ref()
+                .map_or(false, |memory| memory.borrow().contains_key(id.as_ref()))
+                || self.inner.contains(id)
+        }
+
+        fn try_find<'a>(&self, id: impl AsRef<oid>, buffer: &'a mut Vec<u8>) -> Result<Option<Data<'a>>, Self::Error> {
+            if let Some(kind) = self.find_in_memory(id.as_ref(), buffer) {
+                return Ok(Some(Data::new(kind, buffer)));
+            }
+            self.inner.try_find(id, buffer)
+        }
+    }
+
+    impl<T> crate::Header for Proxy<T>
+    where
+        T: crate::Header,
+    {
+        type Error = T::Error;
+
+        fn try_header(&self, id: impl AsRef<oid>) -> Result<Option<Header>, Self::Error> {
+            if let Some(memory) = self.memory.as_ref() {
+                if let Some((kind, data)) = memory.borrow().get(id.as_ref()) {
+                    return Ok(Some(Header::Loose {
+                        kind: *kind,
+                        size: data.len() as u64,
+                    }));
+                }
+            }
+            self.inner.try_header(id)
+        }
+    }
+
+    impl<T> gix_pack::Find for Proxy<T>
+    where
+        T: gix_pack::Find,
+    {
+        type Error = T::Error;
+
+        fn contains(&self, id: impl AsRef<oid>) -> bool {
+            self.memory
+                .as_ref()
+                .map_or(false, |memory| memory.borrow().contains_key(id.as_ref()))
+                || self.inner.contains(id)
+        }
+
+        fn try_find_cached<'a>(
+            &self,
+            id: impl AsRef<oid>,
+            buffer: &'a mut Vec<u8>,
+            pack_cache: &mut impl gix_pack::cache::DecodeEntry,
+        ) -> Result<Option<(Data<'a>, Option<gix_pack::data::entry::Location>)>, Self::Error> {
+            if let Some(kind) = self.find_in_memory(id.as_ref(), buffer) {
+                return Ok(Some((Data::new(kind, buffer), None)));
+            }
+            self.inner.try_find_cached(id, buffer, pack_cache)
+        }
+
+        fn location_by_oid(&self, id: impl AsRef<oid>, buf: &mut Vec<u8>) -> Option<gix_pack::data::entry::Location> {
+            self.inner.location_by_oid(id, buf)
+        }
+
+        fn pack_offsets_and_oid(&self, pack_id: u32) -> Option<Vec<(u64, ObjectId)>> {
+            self.inner.pack_offsets_and_oid(pack_id)
+        }
+
+        fn entry_by_location(&self, location: &gix_pack::data::entry::Location) -> Option<gix_pack::find::Entry> {
+            self.inner.entry_by_location(location)
+        }
+    }
+
+    impl<T> Proxy<T> {
+        /// Copy the data of the object with `id` into `buffer` and return its kind, if it is held in memory.
+        fn find_in_memory(&self, id: &oid, buffer: &mut Vec<u8>) -> Option<Kind> {
+            let memory = self.memory.as_ref()?.borrow();
+            let (kind, data) = memory.get(id)?;
+            buffer.clear();
+            buffer.extend_from_slice(data);
+            Some(*kind)
+        }
+    }
+}
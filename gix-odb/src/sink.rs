@@ -57,6 +57,8 @@ impl crate::traits::Write for Sink {
             c.reset();
         }
 
-        Ok(hasher.digest().into())
+        hasher
+            .try_digest()
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
     }
 }
@@ -12,6 +12,11 @@ impl Store {
         self.object_hash
     }
 
+    /// The way pack data files are accessed.
+    pub fn pack_access(&self) -> gix_pack::data::Access {
+        self.pack_access
+    }
+
     /// Whether or not we are allowed to use multi-pack indices
     pub fn use_multi_pack_index(&self) -> bool {
         self.use_multi_pack_index
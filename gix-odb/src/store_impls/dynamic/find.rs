@@ -142,15 +142,16 @@ where
                                 }
                             },
                         };
-                        let entry = pack.entry(pack_offset);
+                        let entry = pack.entry(pack_offset).map_err(gix_pack::data::decode::Error::from)?;
                         let header_size = entry.header_size();
                         let res = match pack.decode_entry(
                             entry,
                             buffer,
                             |id, _out| {
-                                index_file.pack_offset_by_id(id).map(|pack_offset| {
-                                    gix_pack::data::decode::entry::ResolvedBase::InPack(pack.entry(pack_offset))
-                                })
+                                index_file
+                                    .pack_offset_by_id(id)
+                                    .and_then(|pack_offset| pack.entry(pack_offset).ok())
+                                    .map(gix_pack::data::decode::entry::ResolvedBase::InPack)
                             },
                             pack_cache,
                         ) {
@@ -226,7 +227,7 @@ where
                                 let pack = possibly_pack
                                     .as_ref()
                                     .expect("pack to still be available like just now");
-                                let entry = pack.entry(pack_offset);
+                                let entry = pack.entry(pack_offset).map_err(gix_pack::data::decode::Error::from)?;
                                 let header_size = entry.header_size();
                                 pack.decode_entry(
                                     entry,
@@ -234,11 +235,8 @@ where
                                     |id, out| {
                                         index_file
                                             .pack_offset_by_id(id)
-                                            .map(|pack_offset| {
-                                                gix_pack::data::decode::entry::ResolvedBase::InPack(
-                                                    pack.entry(pack_offset),
-                                                )
-                                            })
+                                            .and_then(|pack_offset| pack.entry(pack_offset).ok())
+                                            .map(gix_pack::data::decode::entry::ResolvedBase::InPack)
                                             .or_else(|| {
                                                 (id == base_id).then(|| {
                                                     out.resize(buf.len(), 0);
@@ -399,7 +397,7 @@ where
                                 }
                             },
                         };
-                        let entry = pack.entry(pack_offset);
+                        let entry = pack.entry(pack_offset).ok()?;
 
                         buf.resize(entry.decompressed_size.try_into().expect("representable size"), 0);
                         assert_eq!(pack.id, pack_id.to_intrinsic_pack_id(), "both ids must always match");
@@ -480,8 +478,9 @@ where
                         };
                         return pack
                             .entry_slice(location.entry_range(location.pack_offset))
+                            .ok()?
                             .map(|data| gix_pack::find::Entry {
-                                data: data.to_owned(),
+                                data: data.into_owned(),
                                 version: pack.version(),
                             });
                     }
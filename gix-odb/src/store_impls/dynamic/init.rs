@@ -19,6 +19,11 @@ pub struct Options {
     /// The current directory of the process at the time of instantiation.
     /// If unset, it will be retrieved using `std::env::current_dir()`.
     pub current_dir: Option<std::path::PathBuf>,
+    /// How to access pack data files, which are memory-mapped by default.
+    ///
+    /// Use [`Access::Read`][gix_pack::data::Access::Read] to have IO errors reported as such instead of crashing the
+    /// process, for instance if packs are stored on a network filesystem, or to handle packs too large to be mapped.
+    pub pack_access: gix_pack::data::Access,
 }
 
 impl Default for Options {
@@ -28,6 +33,7 @@ impl Default for Options {
             object_hash: Default::default(),
             use_multi_pack_index: true,
             current_dir: None,
+            pack_access: Default::default(),
         }
     }
 }
@@ -77,6 +83,7 @@ impl Store {
             object_hash,
             use_multi_pack_index,
             current_dir,
+            pack_access,
         }: Options,
     ) -> std::io::Result<Self> {
         let objects_dir = objects_dir.into();
@@ -118,6 +125,7 @@ impl Store {
             index: ArcSwap::new(Arc::new(SlotMapIndex::default())),
             use_multi_pack_index,
             object_hash,
+            pack_access,
             num_handles_stable: Default::default(),
             num_handles_unstable: Default::default(),
             num_disk_state_consolidation: Default::default(),
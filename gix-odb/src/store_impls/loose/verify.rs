@@ -84,7 +84,7 @@ impl Store {
                     expected: id,
                 });
             }
-            object.decode().map_err(|err| integrity::Error::ObjectDecode {
+            object.decode(id.kind()).map_err(|err| integrity::Error::ObjectDecode {
                 source: err,
                 kind: object.kind,
                 id,
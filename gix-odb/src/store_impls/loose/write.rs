@@ -24,6 +24,8 @@ pub enum Error {
         source: tempfile::PersistError,
         target: PathBuf,
     },
+    #[error("The object was crafted to cause a hash collision and was not written")]
+    CollisionAttack(#[from] hash::try_digest::Error),
 }
 
 impl crate::traits::Write for Store {
@@ -124,7 +126,7 @@ impl Store {
         &self,
         hash::Write { hash, inner: file }: hash::Write<CompressedTempfile>,
     ) -> Result<gix_hash::ObjectId, Error> {
-        let id = gix_hash::ObjectId::from(hash.digest());
+        let id = hash.try_digest()?;
         let object_path = loose::hash_path(&id, self.path.clone());
         let object_dir = object_path
             .parent()
@@ -228,7 +228,7 @@ mod ext {
                     .ok_or_else(|| find::existing_object::Error::NotFound {
                         oid: id.as_ref().to_owned(),
                     })
-                    .and_then(|o| o.decode().map_err(find::existing_object::Error::Decode))
+                    .and_then(|o| o.decode(id.kind()).map_err(find::existing_object::Error::Decode))
                     .and_then(|o| match o {
                         $object_variant(o) => return Ok(o),
                         _other => Err(find::existing_object::Error::ObjectKind {
@@ -240,7 +240,7 @@ mod ext {
     }
 
     macro_rules! make_iter_lookup {
-        ($method:ident, $object_kind:path, $object_type:ty, $into_iter:tt) => {
+        ($method:ident, $object_kind:path, $object_type:ty, $into_iter:tt $(, $object_hash_of:path)?) => {
             /// Like [`find(…)`][Self::find()], but flattens the `Result<Option<_>>` into a single `Result` making a non-existing object an error
             /// while returning the desired iterator type.
             fn $method<'a>(
@@ -255,7 +255,7 @@ mod ext {
                         oid: id.as_ref().to_owned(),
                     })
                     .and_then(|o| {
-                        o.$into_iter()
+                        o.$into_iter($($object_hash_of(id))?)
                             .ok_or_else(|| find::existing_iter::Error::ObjectKind {
                                 expected: $object_kind,
                             })
@@ -299,7 +299,13 @@ mod ext {
         make_obj_lookup!(find_tag, ObjectRef::Tag, Kind::Tag, TagRef<'a>);
         make_obj_lookup!(find_blob, ObjectRef::Blob, Kind::Blob, BlobRef<'a>);
         make_iter_lookup!(find_commit_iter, Kind::Commit, CommitRefIter<'a>, try_into_commit_iter);
-        make_iter_lookup!(find_tree_iter, Kind::Tree, TreeRefIter<'a>, try_into_tree_iter);
+        make_iter_lookup!(
+            find_tree_iter,
+            Kind::Tree,
+            TreeRefIter<'a>,
+            try_into_tree_iter,
+            gix_hash::oid::kind
+        );
         make_iter_lookup!(find_tag_iter, Kind::Tag, TagRefIter<'a>, try_into_tag_iter);
     }
 
@@ -0,0 +1,142 @@
+use std::sync::atomic::AtomicBool;
+
+use gix_features::progress;
+use gix_odb::{memory, Find, FindExt, Header, Write};
+
+use crate::{
+    hex_to_id,
+    store::loose::{locate_oid, object_ids},
+};
+
+fn empty_db() -> crate::Result<(gix_odb::Handle, gix_testtools::tempfile::TempDir)> {
+    let dir = gix_testtools::tempfile::tempdir()?;
+    std::fs::create_dir(dir.path().join("pack"))?;
+    Ok((gix_odb::at(dir.path())?, dir))
+}
+
+fn write_all_objects<W>(odb: &W) -> crate::Result
+where
+    W: Write,
+    W::Error: 'static,
+{
+    let mut buf = Vec::new();
+    for id in object_ids() {
+        let obj = locate_oid(id, &mut buf);
+        assert_eq!(odb.write_buf(obj.kind, obj.data)?, id);
+    }
+    Ok(())
+}
+
+#[test]
+fn writes_are_kept_in_memory_and_served_on_top_of_the_inner_database() -> crate::Result {
+    let (db, dir) = empty_db()?;
+    let odb = memory::Proxy::new(db, gix_hash::Kind::Sha1);
+    assert!(odb.has_object_memory());
+
+    write_all_objects(&odb)?;
+    assert_eq!(odb.num_objects_in_memory(), object_ids().len());
+    assert_eq!(
+        std::fs::read_dir(dir.path())?.count(),
+        1,
+        "nothing but the pack directory exists, no object was written to disk"
+    );
+
+    let mut buf = Vec::new();
+    for id in object_ids() {
+        assert!(Find::contains(&odb, id));
+        assert!(!Find::contains(&*odb, id), "the inner database doesn't know the object");
+        let obj = odb.find(id, &mut buf)?;
+        let expected_kind = obj.kind;
+        let expected_size = obj.data.len() as u64;
+        assert_eq!(
+            odb.try_header(id)?.map(|h| (h.kind(), h.size())),
+            Some((expected_kind, expected_size))
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn objects_of_the_inner_database_can_be_found() -> crate::Result {
+    let odb = memory::Proxy::new(gix_odb::at(crate::fixture_path("objects"))?, gix_hash::Kind::Sha1);
+    let mut buf = Vec::new();
+    let id = hex_to_id("501b297447a8255d3533c6858bb692575cdefaa0");
+    assert!(Find::contains(&odb, id));
+    assert!(odb.try_find(id, &mut buf)?.is_some());
+    assert!(odb.try_header(id)?.is_some());
+    assert_eq!(odb.num_objects_in_memory(), 0);
+    Ok(())
+}
+
+#[test]
+fn without_object_memory_writes_pass_through() -> crate::Result {
+    let (db, _dir) = empty_db()?;
+    let mut odb = memory::Proxy::from(db);
+    assert!(!odb.has_object_memory());
+
+    write_all_objects(&odb)?;
+    assert_eq!(odb.num_objects_in_memory(), 0);
+    for id in object_ids() {
+        assert!(Find::contains(&*odb, id), "the objects are in the inner database");
+    }
+
+    odb.enable_object_memory();
+    let id = odb.write_buf(gix_object::Kind::Blob, b"in memory")?;
+    assert!(!Find::contains(&*odb, id));
+    assert!(Find::contains(&odb, id));
+    Ok(())
+}
+
+#[test]
+fn object_memory_can_be_discarded() -> crate::Result {
+    let (db, _dir) = empty_db()?;
+    let mut odb = memory::Proxy::new(db, gix_hash::Kind::Sha1);
+    let id = odb.write_buf(gix_object::Kind::Blob, b"speculative")?;
+
+    let storage = odb.take_object_memory().expect("memory was enabled");
+    assert_eq!(storage.len(), 1);
+    assert!(!Find::contains(&odb, id), "the object is gone with the memory");
+    assert!(!odb.has_object_memory());
+
+    assert!(
+        odb.set_object_memory(storage).is_none(),
+        "there was no memory to return"
+    );
+    assert!(Find::contains(&odb, id), "memory can be restored as well");
+    Ok(())
+}
+
+#[test]
+fn object_memory_can_be_persisted_as_single_pack() -> crate::Result {
+    let (db, dir) = empty_db()?;
+    let odb = memory::Proxy::new(db, gix_hash::Kind::Sha1);
+    let should_interrupt = AtomicBool::new(false);
+    assert!(
+        odb.persist_as_pack(dir.path().join("pack"), progress::Discard, &should_interrupt)?
+            .is_none(),
+        "nothing to do without objects"
+    );
+
+    write_all_objects(&odb)?;
+    let outcome = odb
+        .persist_as_pack(dir.path().join("pack"), progress::Discard, &should_interrupt)?
+        .expect("objects were written");
+    assert_eq!(outcome.index.num_objects as usize, object_ids().len());
+    assert_eq!(odb.num_objects_in_memory(), 0, "memory is cleared once persisted");
+    assert!(odb.has_object_memory(), "but it is still used for future writes");
+
+    let mut buf = Vec::new();
+    for id in object_ids() {
+        let expected = locate_oid(id, &mut Vec::new()).data.to_owned();
+        assert_eq!(
+            odb.find(id, &mut buf)?.data,
+            expected,
+            "the inner database picks up the new pack"
+        );
+        assert!(
+            gix_odb::at(dir.path())?.contains(id),
+            "the pack is visible to new handles as well"
+        );
+    }
+    Ok(())
+}
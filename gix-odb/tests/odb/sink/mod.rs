@@ -7,7 +7,7 @@ fn write() -> Result<(), Box<dyn std::error::Error>> {
     let mut buf = Vec::new();
     for oid in object_ids() {
         let obj = locate_oid(oid, &mut buf);
-        let actual = gix_odb::sink(gix_hash::Kind::Sha1).write(&obj.decode()?)?;
+        let actual = gix_odb::sink(gix_hash::Kind::Sha1).write(&obj.decode(gix_hash::Kind::Sha1)?)?;
         assert_eq!(actual, oid);
     }
     Ok(())
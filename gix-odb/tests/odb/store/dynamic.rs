@@ -32,6 +32,7 @@ fn db_with_all_object_sources() -> crate::Result<(gix_odb::Handle, tempfile::Tem
         &std::sync::atomic::AtomicBool::default(),
         gix_odb::pack::multi_index::write::Options {
             object_hash: gix_hash::Kind::Sha1,
+            write_reverse_index: false,
         },
     )?;
     Ok((gix_odb::at(objects_dir.path())?, objects_dir))
@@ -759,6 +760,42 @@ fn iterate_over_a_bunch_of_loose_and_packed_objects() -> crate::Result {
     Ok(())
 }
 
+#[test]
+fn pack_data_can_be_read_instead_of_memory_mapped() -> crate::Result {
+    let (mmap_db, tmp) = db_with_all_object_sources()?;
+    let read_db = gix_odb::at_opts(
+        tmp.path(),
+        None,
+        store::init::Options {
+            pack_access: gix_odb::pack::data::Access::Read,
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(read_db.store_ref().pack_access(), gix_odb::pack::data::Access::Read);
+
+    let (mut mmap_buf, mut read_buf) = (Vec::new(), Vec::new());
+    for id in mmap_db.iter()? {
+        let id = id?;
+        let expected = mmap_db.find(id, &mut mmap_buf)?;
+        let actual = read_db.find(id, &mut read_buf)?;
+        assert_eq!(actual.kind, expected.kind);
+        assert_eq!(actual.data, expected.data, "{id} has the same data");
+        assert_eq!(read_db.try_header(id)?, mmap_db.try_header(id)?);
+    }
+
+    let outcome = read_db.store_ref().verify_integrity(
+        gix_features::progress::Discard,
+        &std::sync::atomic::AtomicBool::default(),
+        Default::default(),
+    )?;
+    assert_eq!(
+        outcome.index_statistics.len(),
+        2,
+        "the multi-pack index and the remaining pack index are verified"
+    );
+    Ok(())
+}
+
 #[test]
 fn auto_refresh_with_and_without_id_stability() -> crate::Result {
     let tmp = gix_testtools::tempfile::TempDir::new()?;
@@ -55,17 +55,21 @@ mod write {
 
         for oid in object_ids() {
             let obj = locate_oid(oid, &mut buf);
-            let actual = db.write(&obj.decode()?)?;
+            let actual = db.write(&obj.decode(gix_hash::Kind::Sha1)?)?;
             assert_eq!(actual, oid);
             assert_eq!(
-                db.try_find(oid, &mut buf2)?.expect("id present").decode()?,
-                obj.decode()?
+                db.try_find(oid, &mut buf2)?
+                    .expect("id present")
+                    .decode(gix_hash::Kind::Sha1)?,
+                obj.decode(gix_hash::Kind::Sha1)?
             );
             let actual = db.write_buf(obj.kind, obj.data)?;
             assert_eq!(actual, oid);
             assert_eq!(
-                db.try_find(oid, &mut buf2)?.expect("id present").decode()?,
-                obj.decode()?
+                db.try_find(oid, &mut buf2)?
+                    .expect("id present")
+                    .decode(gix_hash::Kind::Sha1)?,
+                obj.decode(gix_hash::Kind::Sha1)?
             );
         }
         Ok(())
@@ -283,7 +287,7 @@ cjHJZXWmV4CcRfmLsXzU8s2cR9A0DBvOxhPD1TlKC2JhBFXigjuL9U4Rbq9tdegB
             ),
             tagger: Some(signature(1528473343)),
         };
-        assert_eq!(o.decode()?.as_tag().expect("tag"), &expected);
+        assert_eq!(o.decode(gix_hash::Kind::Sha1)?.as_tag().expect("tag"), &expected);
         Ok(())
     }
 
@@ -302,7 +306,7 @@ cjHJZXWmV4CcRfmLsXzU8s2cR9A0DBvOxhPD1TlKC2JhBFXigjuL9U4Rbq9tdegB
             message: b"initial commit\n".as_bstr(),
             extra_headers: vec![(b"gpgsig".as_bstr(), b"-----BEGIN PGP SIGNATURE-----\nComment: GPGTools - https://gpgtools.org\n\niQIzBAABCgAdFiEEw7xSvXbiwjusbsBqZl+Z+p2ZlmwFAlsaptwACgkQZl+Z+p2Z\nlmxXSQ//fj6t7aWoEKeMdFigfj6OXWPUyrRbS0N9kpJeOfA0BIOea/6Jbn8J5qh1\nYRfrySOzHPXR5Y+w4GwLiVas66qyhAbk4yeqZM0JxBjHDyPyRGhjUd3y7WjEa6bj\nP0ACAIkYZQ/Q/LDE3eubmhAwEobBH3nZbwE+/zDIG0i265bD5C0iDumVOiKkSelw\ncr6FZVw1HH+GcabFkeLRZLNGmPqGdbeBwYERqb0U1aRCzV1xLYteoKwyWcYaH8E3\n97z1rwhUO/L7o8WUEJtP3CLB0zuocslMxskf6bCeubBnRNJ0YrRmxGarxCP3vn4D\n3a/MwECnl6mnUU9t+OnfvrzLDN73rlq8iasUq6hGe7Sje7waX6b2UGpxHqwykmXg\nVimD6Ah7svJanHryfJn38DvJW/wOMqmAnSUAp+Y8W9EIe0xVntCmtMyoKuqBoY7T\nJlZ1kHJte6ELIM5JOY9Gx7D0ZCSKZJQqyjoqtl36dsomT0I78/+7QS1DP4S6XB7d\nc3BYH0JkW81p7AAFbE543ttN0Z4wKXErMFqUKnPZUIEuybtlNYV+krRdfDBWQysT\n3MBebjguVQ60oGs06PzeYBosKGQrHggAcwduLFuqXhLTJqN4UQ18RkE0vbtG3YA0\n+XtZQM13vURdfwFI5qitAGgw4EzPVrkWWzApzLCrRPEMbvP+b9A=\n=2qqN\n-----END PGP SIGNATURE-----".as_bstr().into())]
         };
-        let object = o.decode()?;
+        let object = o.decode(gix_hash::Kind::Sha1)?;
         assert_eq!(object.as_commit().expect("commit"), &expected);
         Ok(())
     }
@@ -320,7 +324,7 @@ cjHJZXWmV4CcRfmLsXzU8s2cR9A0DBvOxhPD1TlKC2JhBFXigjuL9U4Rbq9tdegB
         let mut buf = Vec::new();
         let o = find("37d4e6c5c48ba0d245164c4e10d5f41140cab980", &mut buf);
         assert_eq!(
-            o.decode()?.as_blob().expect("blob"),
+            o.decode(gix_hash::Kind::Sha1)?.as_blob().expect("blob"),
             &BlobRef {
                 data: &[104, 105, 32, 116, 104, 101, 114, 101, 10]
             },
@@ -340,7 +344,7 @@ cjHJZXWmV4CcRfmLsXzU8s2cR9A0DBvOxhPD1TlKC2JhBFXigjuL9U4Rbq9tdegB
         let mut buf = Vec::new();
         let o = find("a706d7cd20fc8ce71489f34b50cf01011c104193", &mut buf);
         assert_eq!(
-            o.decode()?.as_blob().expect("blob").data.len(),
+            o.decode(gix_hash::Kind::Sha1)?.as_blob().expect("blob").data.len(),
             o.data.len(),
             "erm, blobs are the same as raw data?"
         );
@@ -380,7 +384,7 @@ cjHJZXWmV4CcRfmLsXzU8s2cR9A0DBvOxhPD1TlKC2JhBFXigjuL9U4Rbq9tdegB
                 },
             ],
         };
-        assert_eq!(o.decode()?.as_tree().expect("tree"), &expected);
+        assert_eq!(o.decode(gix_hash::Kind::Sha1)?.as_tree().expect("tree"), &expected);
         Ok(())
     }
 
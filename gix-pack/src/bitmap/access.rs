@@ -0,0 +1,88 @@
+use std::path::Path;
+
+use crate::bitmap::{words, File, Version};
+
+/// Access
+impl File {
+    /// Returns the version of the bitmap file.
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    /// Returns the path from which the bitmap file was loaded.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the kind of hash used for all object ids in the file.
+    pub fn object_hash(&self) -> gix_hash::Kind {
+        self.object_hash
+    }
+
+    /// Returns the [flags][crate::bitmap::flags] stored in the header.
+    pub fn flags(&self) -> u16 {
+        self.flags
+    }
+
+    /// Returns the checksum of the pack or multi-pack-index this bitmap belongs to.
+    pub fn checksum(&self) -> gix_hash::ObjectId {
+        self.checksum
+    }
+
+    /// Returns the checksum over the entire content of the file, stored in its trailer.
+    pub fn file_checksum(&self) -> gix_hash::ObjectId {
+        gix_hash::ObjectId::from(&self.data[self.data.len() - self.object_hash.len_in_bytes()..])
+    }
+
+    /// Returns the amount of commits that have a bitmap.
+    pub fn num_bitmaps(&self) -> u32 {
+        self.entries.len() as u32
+    }
+
+    /// Return the positions of all commits with a bitmap in the pack index or multi-pack-index, in the order they are stored.
+    pub fn index_positions(&self) -> impl Iterator<Item = u32> + '_ {
+        self.entries.iter().map(|e| e.index_position)
+    }
+
+    /// Return the bitmap of all objects of `kind`, with a bit set for each object of that kind.
+    pub fn objects_of_kind(&self, kind: gix_object::Kind) -> &gix_bitmap::ewah::Vec {
+        match kind {
+            gix_object::Kind::Commit => &self.commits,
+            gix_object::Kind::Tree => &self.trees,
+            gix_object::Kind::Blob => &self.blobs,
+            gix_object::Kind::Tag => &self.tags,
+        }
+    }
+
+    /// Return the uncompressed bitmap of all objects reachable from the commit at `index_position` in the pack index or
+    /// multi-pack-index, or `None` if there is no bitmap for it.
+    ///
+    /// Bitmaps that are stored as XOR with a previous one are resolved.
+    /// Bit `n` is found at `words[n / 64] & (1 << (n % 64))`.
+    pub fn bitmap_at_index_position(&self, index_position: u32) -> Option<Vec<u64>> {
+        let entry_index = self
+            .entries_by_index_position
+            .binary_search_by_key(&index_position, |idx| self.entries[*idx as usize].index_position)
+            .ok()
+            .map(|pos| self.entries_by_index_position[pos] as usize)?;
+        let mut entry = &self.entries[entry_index];
+        let mut entry_index = entry_index;
+        let mut out = entry.bitmap.to_words();
+        while entry.xor_offset != 0 {
+            entry_index -= entry.xor_offset as usize;
+            entry = &self.entries[entry_index];
+            words::xor_assign(&mut out, &entry.bitmap.to_words());
+        }
+        Some(out)
+    }
+
+    /// Return the hash of the name of the object at `index_position` in the pack index or multi-pack-index, if this file
+    /// has a name-hash cache.
+    ///
+    /// Objects for which the name isn't known have a hash of 0.
+    pub fn name_hash_at_index_position(&self, index_position: u32) -> Option<u32> {
+        let start = self.name_hash_ofs? + index_position as usize * 4;
+        let end = self.data.len() - self.object_hash.len_in_bytes();
+        (start + 4 <= end).then(|| crate::read_u32(&self.data[start..][..4]))
+    }
+}
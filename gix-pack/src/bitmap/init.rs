@@ -0,0 +1,127 @@
+use std::{convert::TryInto, path::Path};
+
+use crate::bitmap::{self, flags, Entry, File, Version};
+
+/// Returned by [`bitmap::File::at()`].
+#[derive(thiserror::Error, Debug)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not open bitmap file at '{path}'")]
+    Io {
+        source: std::io::Error,
+        path: std::path::PathBuf,
+    },
+    #[error("{message}")]
+    Corrupt { message: String },
+    #[error("Unsupported bitmap version: {version}")]
+    UnsupportedVersion { version: u16 },
+    #[error("Bitmap files without the full-dag flag aren't supported, flags were {flags:#x}")]
+    UnsupportedFlags { flags: u16 },
+    #[error("The {kind} bitmap could not be decoded")]
+    BitmapDecode {
+        kind: &'static str,
+        source: gix_bitmap::ewah::decode::Error,
+    },
+}
+
+const HEADER_LEN: usize = 4 /* signature */ + 2 /* version */ + 2 /* flags */ + 4 /* entry count */;
+
+/// Instantiation
+impl File {
+    /// Open the bitmap file at the given `path`, with all of its bitmaps being decoded right away.
+    ///
+    /// The `object_hash` is needed as the hash kind isn't stored within the file format itself.
+    pub fn at(path: impl AsRef<Path>, object_hash: gix_hash::Kind) -> Result<File, Error> {
+        Self::at_inner(path.as_ref(), object_hash)
+    }
+
+    fn at_inner(path: &Path, object_hash: gix_hash::Kind) -> Result<File, Error> {
+        let data = crate::mmap::read_only(path).map_err(|source| Error::Io {
+            source,
+            path: path.to_owned(),
+        })?;
+        let hash_len = object_hash.len_in_bytes();
+        if data.len() < HEADER_LEN + hash_len * 2 {
+            return Err(Error::Corrupt {
+                message: format!(
+                    "Bitmap file of size {} is too small for even an empty bitmap",
+                    data.len()
+                ),
+            });
+        }
+        let (header, rest) = data.split_at(HEADER_LEN);
+        if &header[..4] != bitmap::SIGNATURE {
+            return Err(Error::Corrupt {
+                message: "Bitmap file signature not found".into(),
+            });
+        }
+        let version = u16::from_be_bytes(header[4..6].try_into().expect("2 bytes"));
+        if version != Version::V1 as u16 {
+            return Err(Error::UnsupportedVersion { version });
+        }
+        let file_flags = u16::from_be_bytes(header[6..8].try_into().expect("2 bytes"));
+        if file_flags & flags::FULL_DAG == 0 {
+            return Err(Error::UnsupportedFlags { flags: file_flags });
+        }
+        let num_entries = crate::read_u32(&header[8..12]);
+        let (checksum, mut rest) = rest.split_at(hash_len);
+        let checksum = gix_hash::ObjectId::from(checksum);
+        // Don't let the trailing checksum be mistaken for bitmap data.
+        rest = &rest[..rest.len() - hash_len];
+
+        let mut type_bitmap = |kind: &'static str| {
+            let (bitmap, tail) =
+                gix_bitmap::ewah::decode(rest).map_err(|source| Error::BitmapDecode { kind, source })?;
+            rest = tail;
+            Ok::<_, Error>(bitmap)
+        };
+        let commits = type_bitmap("commits")?;
+        let trees = type_bitmap("trees")?;
+        let blobs = type_bitmap("blobs")?;
+        let tags = type_bitmap("tags")?;
+
+        let mut entries = Vec::with_capacity(num_entries as usize);
+        for entry_index in 0..num_entries {
+            if rest.len() < 6 {
+                return Err(Error::Corrupt {
+                    message: format!("Unexpected end of file while reading bitmap entry {entry_index}"),
+                });
+            }
+            let index_position = crate::read_u32(&rest[..4]);
+            let xor_offset = rest[4];
+            if xor_offset as u32 > entry_index {
+                return Err(Error::Corrupt {
+                    message: format!("Bitmap entry {entry_index} refers to an entry before the first one"),
+                });
+            }
+            let (bitmap, tail) = gix_bitmap::ewah::decode(&rest[6..])
+                .map_err(|source| Error::BitmapDecode { kind: "commit", source })?;
+            rest = tail;
+            entries.push(Entry {
+                index_position,
+                xor_offset,
+                bitmap,
+            });
+        }
+
+        let name_hash_ofs = (file_flags & flags::HASH_CACHE != 0).then(|| data.len() - hash_len - rest.len());
+        let mut entries_by_index_position: Vec<u32> = (0..num_entries).collect();
+        entries_by_index_position.sort_by_key(|idx| entries[*idx as usize].index_position);
+
+        Ok(File {
+            data,
+            path: path.to_owned(),
+            version: Version::V1,
+            object_hash,
+            flags: file_flags,
+            checksum,
+            commits,
+            trees,
+            blobs,
+            tags,
+            entries,
+            entries_by_index_position,
+            name_hash_ofs,
+        })
+    }
+}
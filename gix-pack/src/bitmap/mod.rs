@@ -0,0 +1,136 @@
+//! Reachability bitmaps, which store for selected commits which objects of a pack or multi-pack-index are reachable from them.
+use std::path::PathBuf;
+
+use gix_hash::ObjectId;
+
+/// Known bitmap file versions
+#[derive(Default, PartialEq, Eq, Ord, PartialOrd, Debug, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[allow(missing_docs)]
+pub enum Version {
+    #[default]
+    V1 = 1,
+}
+
+/// The signature at the beginning of each bitmap file.
+pub const SIGNATURE: &[u8] = b"BITM";
+
+/// Flags stored in the header of a bitmap file to indicate its features.
+pub mod flags {
+    /// Bitmaps of commits contain all objects reachable from them, which is the only supported mode.
+    pub const FULL_DAG: u16 = 0x1;
+    /// A name-hash for each object in index order follows the commit bitmaps.
+    pub const HASH_CACHE: u16 = 0x4;
+    /// A lookup table for the commit bitmaps follows the name-hash cache.
+    pub const LOOKUP_TABLE: u16 = 0x10;
+}
+
+/// A representation of a bitmap file, typically named `pack-<hash>.bitmap` or `multi-pack-index-<hash>.bitmap`,
+/// storing for selected commits which objects are reachable from them.
+///
+/// Bits refer to objects in pack order, or in pseudo-pack order for multi-pack-indices, see [`Reachability`] for translating
+/// them into object ids.
+pub struct File {
+    data: memmap2::Mmap,
+    path: PathBuf,
+    version: Version,
+    object_hash: gix_hash::Kind,
+    flags: u16,
+    checksum: ObjectId,
+    commits: gix_bitmap::ewah::Vec,
+    trees: gix_bitmap::ewah::Vec,
+    blobs: gix_bitmap::ewah::Vec,
+    tags: gix_bitmap::ewah::Vec,
+    /// All entries in the order they are stored.
+    entries: Vec<Entry>,
+    /// Indices into `entries`, sorted by the index position of their commit.
+    entries_by_index_position: Vec<u32>,
+    name_hash_ofs: Option<usize>,
+}
+
+/// A commit bitmap as stored in the file, possibly XORed with a previous one.
+pub(crate) struct Entry {
+    /// The position of the commit in the pack or multi-pack index.
+    pub index_position: u32,
+    /// If not 0, the amount of entries to go back for the bitmap this one is XORed with.
+    pub xor_offset: u8,
+    pub bitmap: gix_bitmap::ewah::Vec,
+}
+
+/// The index that [`Reachability`] uses to translate bits into object ids.
+enum ObjectIndex {
+    Pack(crate::index::File),
+    Multi(crate::multi_index::File),
+}
+
+/// A [bitmap file][File] along with its pack index or multi-pack-index, to map bits to objects and vice versa,
+/// allowing to answer which objects are reachable from a set of tips without traversing all of them.
+pub struct Reachability {
+    bitmap: File,
+    index: ObjectIndex,
+    /// The index position for each bit, i.e. the pack order.
+    index_position_by_bit: Vec<u32>,
+    /// The bit for each index position.
+    bit_by_index_position: Vec<u32>,
+}
+
+mod access;
+///
+pub mod init;
+///
+pub mod reachability;
+///
+pub mod write;
+
+pub(crate) mod walk;
+
+/// Utilities to deal with uncompressed bitmaps, where bit `n` is found at `words[n / 64] & (1 << (n % 64))`.
+pub(crate) mod words {
+    pub fn len_for(num_bits: usize) -> usize {
+        (num_bits + 63) / 64
+    }
+
+    /// Set `bit` and return `true` if it wasn't set before.
+    pub fn set(words: &mut Vec<u64>, bit: usize) -> bool {
+        let (word, mask) = (bit / 64, 1 << (bit % 64));
+        if word >= words.len() {
+            words.resize(word + 1, 0);
+        }
+        let was_set = words[word] & mask != 0;
+        words[word] |= mask;
+        !was_set
+    }
+
+    pub fn is_set(words: &[u64], bit: usize) -> bool {
+        words.get(bit / 64).map_or(false, |w| w & (1 << (bit % 64)) != 0)
+    }
+
+    pub fn or_assign(words: &mut Vec<u64>, other: &[u64]) {
+        if other.len() > words.len() {
+            words.resize(other.len(), 0);
+        }
+        for (lhs, rhs) in words.iter_mut().zip(other) {
+            *lhs |= rhs;
+        }
+    }
+
+    pub fn xor_assign(words: &mut Vec<u64>, other: &[u64]) {
+        if other.len() > words.len() {
+            words.resize(other.len(), 0);
+        }
+        for (lhs, rhs) in words.iter_mut().zip(other) {
+            *lhs ^= rhs;
+        }
+    }
+
+    /// Call `f(bit)` for each set bit in ascending order.
+    pub fn for_each_set_bit(words: &[u64], mut f: impl FnMut(usize)) {
+        for (idx, mut word) in words.iter().copied().enumerate() {
+            while word != 0 {
+                let bit = word.trailing_zeros() as usize;
+                f(idx * 64 + bit);
+                word &= word - 1;
+            }
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::AtomicBool,
+};
+
+use gix_hash::{oid, ObjectId};
+
+use crate::bitmap::{self, walk, words, ObjectIndex, Reachability};
+
+/// Returned by [`Reachability::at()`] and the other constructors.
+#[derive(thiserror::Error, Debug)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Bitmap(#[from] bitmap::init::Error),
+    #[error(transparent)]
+    Index(#[from] crate::index::init::Error),
+    #[error(transparent)]
+    MultiIndex(#[from] crate::multi_index::init::Error),
+    #[error("The bitmap was created for {expected}, but the index belongs to {actual}")]
+    ChecksumMismatch { expected: ObjectId, actual: ObjectId },
+    #[error("The multi-pack-index at '{path}' has neither a reverse-index chunk nor a valid reverse-index file")]
+    MissingReverseIndex { path: PathBuf },
+    #[error("The pack order of the {num_objects} objects in '{path}' doesn't mention each of them exactly once")]
+    InvalidPackOrder { path: PathBuf, num_objects: u32 },
+}
+
+/// Instantiation
+impl Reachability {
+    /// Open the bitmap at `path` along with the pack index or multi-pack-index it belongs to, which is expected to be
+    /// next to it.
+    ///
+    /// Bitmaps of multi-pack-indices are named `multi-pack-index-<checksum>.bitmap`, and `pack-<checksum>.bitmap` otherwise.
+    pub fn at(path: impl AsRef<Path>, object_hash: gix_hash::Kind) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let bitmap = bitmap::File::at(path, object_hash)?;
+        let is_multi_index = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .map_or(false, |name| name.starts_with("multi-pack-index"));
+        if is_multi_index {
+            let index = crate::multi_index::File::at(path.with_file_name("multi-pack-index"))?;
+            Self::from_multi_index(bitmap, index)
+        } else {
+            let index = crate::index::File::at(path.with_extension("idx"), object_hash)?;
+            Self::from_index(bitmap, index)
+        }
+    }
+
+    /// Combine `bitmap` with the pack `index` it was created for.
+    ///
+    /// The pack order of objects is read from the reverse index next to `index` if there is one, or computed otherwise.
+    pub fn from_index(bitmap: bitmap::File, index: crate::index::File) -> Result<Self, Error> {
+        assert_checksum(&bitmap, index.pack_checksum())?;
+        let index_position_by_bit = read_reverse_index(
+            &index.path().with_extension("rev"),
+            index.num_objects(),
+            index.pack_checksum(),
+        )
+        .unwrap_or_else(|| index.pack_order());
+        Self::new(bitmap, ObjectIndex::Pack(index), index_position_by_bit)
+    }
+
+    /// Combine `bitmap` with the multi-pack `index` it was created for.
+    ///
+    /// The pseudo-pack order of objects is read from the multi-pack-index itself, or from the `multi-pack-index-<checksum>.rev`
+    /// file next to it as written by older versions of git.
+    pub fn from_multi_index(bitmap: bitmap::File, index: crate::multi_index::File) -> Result<Self, Error> {
+        assert_checksum(&bitmap, index.checksum())?;
+        let index_position_by_bit = index
+            .pseudo_pack_order()
+            .or_else(|| {
+                let path = index
+                    .path()
+                    .with_file_name(format!("multi-pack-index-{}.rev", index.checksum()));
+                read_reverse_index(&path, index.num_objects(), index.checksum())
+            })
+            .ok_or_else(|| Error::MissingReverseIndex {
+                path: index.path().to_owned(),
+            })?;
+        Self::new(bitmap, ObjectIndex::Multi(index), index_position_by_bit)
+    }
+
+    fn new(bitmap: bitmap::File, index: ObjectIndex, index_position_by_bit: Vec<u32>) -> Result<Self, Error> {
+        let bit_by_index_position =
+            crate::index::rev::invert(&index_position_by_bit).ok_or_else(|| Error::InvalidPackOrder {
+                path: match &index {
+                    ObjectIndex::Pack(index) => index.path().to_owned(),
+                    ObjectIndex::Multi(index) => index.path().to_owned(),
+                },
+                num_objects: index_position_by_bit.len() as u32,
+            })?;
+        Ok(Reachability {
+            bitmap,
+            index,
+            index_position_by_bit,
+            bit_by_index_position,
+        })
+    }
+}
+
+/// Access
+impl Reachability {
+    /// Return the bitmap file we use.
+    pub fn bitmap(&self) -> &bitmap::File {
+        &self.bitmap
+    }
+
+    /// Return the amount of objects in the pack or multi-pack-index, and thus the amount of bits.
+    pub fn num_objects(&self) -> u32 {
+        self.index_position_by_bit.len() as u32
+    }
+
+    /// Return the id of the object identified by `bit`.
+    ///
+    /// # Panics
+    ///
+    /// If `bit` is out of bounds.
+    pub fn id_at_bit(&self, bit: usize) -> &oid {
+        let index_position = self.index_position_by_bit[bit];
+        match &self.index {
+            ObjectIndex::Pack(index) => index.oid_at_index(index_position),
+            ObjectIndex::Multi(index) => index.oid_at_index(index_position),
+        }
+    }
+
+    /// Return the bit identifying the object with `id`, or `None` if it isn't contained in our index.
+    pub fn bit_of(&self, id: impl AsRef<oid>) -> Option<usize> {
+        let index_position = match &self.index {
+            ObjectIndex::Pack(index) => index.lookup(id),
+            ObjectIndex::Multi(index) => index.lookup(id),
+        }?;
+        Some(self.bit_by_index_position[index_position as usize] as usize)
+    }
+
+    /// Return the uncompressed bitmap of all objects reachable from the commit identified by `bit`, if there is one.
+    pub fn bitmap_of_bit(&self, bit: usize) -> Option<Vec<u64>> {
+        self.bitmap
+            .bitmap_at_index_position(*self.index_position_by_bit.get(bit)?)
+    }
+
+    /// Return the uncompressed bitmap of all objects reachable from the commit with `id`, if there is one.
+    pub fn bitmap_of_commit(&self, id: impl AsRef<oid>) -> Option<Vec<u64>> {
+        self.bitmap_of_bit(self.bit_of(id)?)
+    }
+}
+
+/// The objects reachable from a set of tips as bits, the reachable objects outside of the bitmap, and statistics.
+pub(crate) type ReachableObjects = (Vec<u64>, gix_hashtable::HashSet<ObjectId>, walk::Outcome);
+
+/// Walking
+impl Reachability {
+    /// Mark all objects reachable from `tips` in the returned uncompressed bitmap, using commit bitmaps where possible
+    /// and walking the graph otherwise, using `db` to obtain objects.
+    ///
+    /// Objects that are reachable but aren't contained in our index are returned separately, along with the amount of
+    /// objects that had to be decoded.
+    pub(crate) fn reachable<Find: crate::Find>(
+        &self,
+        db: &Find,
+        tips: impl IntoIterator<Item = ObjectId>,
+        should_interrupt: &AtomicBool,
+    ) -> Result<ReachableObjects, walk::Error<Find::Error>> {
+        let mut bits = Vec::with_capacity(words::len_for(self.num_objects() as usize));
+        let mut outside = gix_hashtable::HashSet::default();
+        let outcome = walk::reachable(
+            db,
+            tips,
+            |id| self.bit_of(id),
+            |bit, bits| match self.bitmap_of_bit(bit) {
+                Some(commit_bits) => {
+                    words::or_assign(bits, &commit_bits);
+                    true
+                }
+                None => false,
+            },
+            &mut bits,
+            &mut outside,
+            &mut Vec::new(),
+            should_interrupt,
+        )?;
+        Ok((bits, outside, outcome))
+    }
+}
+
+fn assert_checksum(bitmap: &bitmap::File, actual: ObjectId) -> Result<(), Error> {
+    if bitmap.checksum() != actual {
+        return Err(Error::ChecksumMismatch {
+            expected: bitmap.checksum(),
+            actual,
+        });
+    }
+    Ok(())
+}
+
+/// Read the index positions in pack order from the reverse index at `path`, or `None` if it can't be read or
+/// doesn't belong to the pack or multi-pack-index with `checksum`.
+fn read_reverse_index(path: &Path, num_objects: u32, checksum: ObjectId) -> Option<Vec<u32>> {
+    let rev = crate::index::rev::File::at(path, num_objects, checksum.kind()).ok()?;
+    (rev.pack_checksum() == checksum).then(|| rev.iter().collect())
+}
@@ -0,0 +1,112 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use gix_hash::{oid, ObjectId};
+use gix_object::Kind;
+
+use crate::bitmap::words;
+
+/// The error returned by [`reachable()`].
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum Error<T: std::error::Error + 'static> {
+    #[error(transparent)]
+    Find(T),
+    #[error("An object with id {} could not be found", .oid)]
+    NotFound { oid: ObjectId },
+    #[error(transparent)]
+    Decode(#[from] gix_object::decode::Error),
+    #[error("Operation interrupted")]
+    Interrupted,
+}
+
+/// Statistics about a walk.
+#[derive(Default, Debug, Clone, Copy)]
+pub(crate) struct Outcome {
+    /// The amount of objects that had to be decoded.
+    pub decoded_objects: usize,
+    /// The amount of commit bitmaps that were used instead of walking the objects reachable from them.
+    pub used_bitmaps: usize,
+}
+
+/// Mark all objects reachable from `tips` in `bits`, or add them to `outside` if they don't have a bit as `bit_of(id)` returns `None`.
+///
+/// `or_commit_bitmap(bit, bits)` is called for each commit that is encountered and that has a bit, and it is expected to
+/// OR all objects reachable from that commit into `bits`, returning `true`, or to return `false` if no bitmap is known for it.
+/// Objects whose bit is already set are assumed to have been fully handled.
+/// Objects in `outside` are assumed to have been handled as well.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn reachable<Find>(
+    db: &Find,
+    tips: impl IntoIterator<Item = ObjectId>,
+    mut bit_of: impl FnMut(&oid) -> Option<usize>,
+    mut or_commit_bitmap: impl FnMut(usize, &mut Vec<u64>) -> bool,
+    bits: &mut Vec<u64>,
+    outside: &mut gix_hashtable::HashSet<ObjectId>,
+    buf: &mut Vec<u8>,
+    should_interrupt: &AtomicBool,
+) -> Result<Outcome, Error<Find::Error>>
+where
+    Find: crate::Find,
+{
+    let mut out = Outcome::default();
+    let mut queue: Vec<(ObjectId, Option<Kind>)> = tips.into_iter().map(|id| (id, None)).collect();
+    while let Some((id, kind)) = queue.pop() {
+        if should_interrupt.load(Ordering::Relaxed) {
+            return Err(Error::Interrupted);
+        }
+        match bit_of(&id) {
+            Some(bit) => {
+                if !words::set(bits, bit) {
+                    continue;
+                }
+                if kind.map_or(true, |kind| kind == Kind::Commit) && or_commit_bitmap(bit, bits) {
+                    out.used_bitmaps += 1;
+                    continue;
+                }
+            }
+            None => {
+                if !outside.insert(id) {
+                    continue;
+                }
+            }
+        }
+        if kind == Some(Kind::Blob) {
+            continue;
+        }
+
+        let obj = db
+            .try_find(id, buf)
+            .map_err(Error::Find)?
+            .ok_or(Error::NotFound { oid: id })?
+            .0;
+        out.decoded_objects += 1;
+        match obj.kind {
+            Kind::Commit => {
+                let mut iter = gix_object::CommitRefIter::from_bytes(obj.data);
+                queue.push((iter.tree_id()?, Some(Kind::Tree)));
+                for token in iter {
+                    match token? {
+                        gix_object::commit::ref_iter::Token::Parent { id } => queue.push((id, Some(Kind::Commit))),
+                        _ => break,
+                    }
+                }
+            }
+            Kind::Tree => {
+                for entry in gix_object::TreeRefIter::from_bytes(obj.data, id.kind()) {
+                    let entry = entry?;
+                    let kind = match entry.mode {
+                        gix_object::tree::EntryMode::Commit => continue,
+                        gix_object::tree::EntryMode::Tree => Kind::Tree,
+                        _ => Kind::Blob,
+                    };
+                    queue.push((entry.oid.to_owned(), Some(kind)));
+                }
+            }
+            Kind::Tag => {
+                let target = gix_object::TagRefIter::from_bytes(obj.data).target_id()?;
+                queue.push((target, None));
+            }
+            Kind::Blob => {}
+        }
+    }
+    Ok(out)
+}
@@ -0,0 +1,263 @@
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+    sync::atomic::{AtomicBool, Ordering},
+    time::Instant,
+};
+
+use gix_features::progress::Progress;
+use gix_hash::ObjectId;
+
+use crate::bitmap::{self, flags, walk, words};
+
+mod error {
+    /// The error returned by [`bitmap::File::write_from_index()`][crate::bitmap::File::write_from_index()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error<E: std::error::Error + 'static> {
+        #[error(transparent)]
+        Io(#[from] std::io::Error),
+        #[error("Interrupted")]
+        Interrupted,
+        #[error(transparent)]
+        Find(E),
+        #[error("An object with id {} could not be found", .oid)]
+        NotFound { oid: gix_hash::ObjectId },
+        #[error(transparent)]
+        Decode(#[from] gix_object::decode::Error),
+        #[error("Object {oid} is reachable from commit {commit}, but isn't contained in the pack")]
+        ObjectNotInPack {
+            oid: gix_hash::ObjectId,
+            commit: gix_hash::ObjectId,
+        },
+    }
+}
+pub use error::Error;
+
+impl<E: std::error::Error + 'static> From<walk::Error<E>> for Error<E> {
+    fn from(err: walk::Error<E>) -> Self {
+        match err {
+            walk::Error::Find(err) => Error::Find(err),
+            walk::Error::NotFound { oid } => Error::NotFound { oid },
+            walk::Error::Decode(err) => Error::Decode(err),
+            walk::Error::Interrupted => Error::Interrupted,
+        }
+    }
+}
+
+/// The result of [`bitmap::File::write_from_index()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Outcome {
+    /// The checksum over the written bitmap file.
+    pub bitmap_checksum: ObjectId,
+    /// The amount of commits for which a bitmap was written.
+    pub num_bitmaps: usize,
+}
+
+/// The progress ids used in [`bitmap::File::write_from_index()`].
+///
+/// Use this information to selectively extract the progress of interest in case the parent application has custom visualization.
+#[derive(Debug, Copy, Clone)]
+pub enum ProgressId {
+    /// The amount of objects whose kind was determined.
+    ObjectKinds,
+    /// The amount of commits for which a bitmap was computed.
+    ComputeBitmaps,
+}
+
+impl From<ProgressId> for gix_features::progress::Id {
+    fn from(v: ProgressId) -> Self {
+        match v {
+            ProgressId::ObjectKinds => *b"BWOK",
+            ProgressId::ComputeBitmaps => *b"BWCB",
+        }
+    }
+}
+
+/// The most recent commits all get a bitmap.
+const NUM_RECENT_COMMITS: usize = 100;
+/// Older commits get a bitmap every this many commits…
+const RECENT_COMMIT_INTERVAL: usize = 100;
+/// …until this many commits were seen…
+const MAX_COMMITS_AT_RECENT_INTERVAL: usize = 20_000;
+/// …and every this many commits after that.
+const DISTANT_COMMIT_INTERVAL: usize = 5_000;
+/// The amount of previously written bitmaps to consider for storing a bitmap as XOR with one of them.
+const MAX_XOR_OFFSET: usize = 10;
+
+impl bitmap::File {
+    /// Write a bitmap for the pack described by `index` to `out`, using `db` to obtain all objects in the pack.
+    ///
+    /// Commits are selected for bitmaps by commit time, with the most recent ones being selected more densely than older ones,
+    /// and `tips` always receive a bitmap if they are commits in the pack.
+    /// All objects reachable from selected commits must be contained in the pack.
+    ///
+    /// Progress is sent to `progress` and interruptions checked via `should_interrupt`.
+    ///
+    /// _Note_ that no name-hash cache and no lookup table are written.
+    pub fn write_from_index<Find>(
+        index: &crate::index::File,
+        db: Find,
+        tips: impl IntoIterator<Item = ObjectId>,
+        out: impl io::Write,
+        mut progress: impl Progress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<Outcome, Error<Find::Error>>
+    where
+        Find: crate::Find,
+    {
+        let num_objects = index.num_objects() as usize;
+        let index_position_by_bit = index.pack_order();
+        let mut bit_by_index_position = vec![0; num_objects];
+        for (bit, index_position) in index_position_by_bit.iter().enumerate() {
+            bit_by_index_position[*index_position as usize] = bit;
+        }
+        let bit_of = |id: &gix_hash::oid| index.lookup(id).map(|idx| bit_by_index_position[idx as usize]);
+
+        let mut buf = Vec::new();
+        let mut kinds = [Vec::new(), Vec::new(), Vec::new(), Vec::new()];
+        let mut commits_by_time = Vec::new();
+        {
+            let start = Instant::now();
+            let mut progress = progress.add_child_with_id("determine object kinds", ProgressId::ObjectKinds.into());
+            progress.init(Some(num_objects), gix_features::progress::count("objects"));
+            for (bit, index_position) in index_position_by_bit.iter().enumerate() {
+                let id = index.oid_at_index(*index_position);
+                let obj = db
+                    .try_find(id, &mut buf)
+                    .map_err(Error::Find)?
+                    .ok_or_else(|| Error::NotFound { oid: id.to_owned() })?
+                    .0;
+                let kind_index = match obj.kind {
+                    gix_object::Kind::Commit => {
+                        let time = gix_object::CommitRefIter::from_bytes(obj.data).committer()?.time;
+                        commits_by_time.push((time.seconds_since_unix_epoch, bit));
+                        0
+                    }
+                    gix_object::Kind::Tree => 1,
+                    gix_object::Kind::Blob => 2,
+                    gix_object::Kind::Tag => 3,
+                };
+                words::set(&mut kinds[kind_index], bit);
+                progress.inc();
+                if should_interrupt.load(Ordering::Relaxed) {
+                    return Err(Error::Interrupted);
+                }
+            }
+            progress.show_throughput(start);
+        }
+
+        let selected = {
+            let tip_bits: Vec<_> = tips
+                .into_iter()
+                .filter_map(|id| bit_of(&id))
+                .filter(|bit| words::is_set(&kinds[0], *bit))
+                .collect();
+            commits_by_time.sort_by(|a, b| b.cmp(a));
+            let mut selected: Vec<_> = commits_by_time
+                .iter()
+                .enumerate()
+                .filter(|(idx, (_time, bit))| {
+                    *idx < NUM_RECENT_COMMITS
+                        || (*idx < MAX_COMMITS_AT_RECENT_INTERVAL && idx % RECENT_COMMIT_INTERVAL == 0)
+                        || idx % DISTANT_COMMIT_INTERVAL == 0
+                        || tip_bits.contains(bit)
+                })
+                .map(|(_, (_time, bit))| *bit)
+                .collect();
+            // Compute the oldest commits first so their bitmaps can be reused for their descendants.
+            selected.reverse();
+            selected
+        };
+
+        let mut computed = HashMap::<usize, Vec<u64>>::with_capacity(selected.len());
+        {
+            let start = Instant::now();
+            let mut progress = progress.add_child_with_id("compute bitmaps", ProgressId::ComputeBitmaps.into());
+            progress.init(Some(selected.len()), gix_features::progress::count("commits"));
+            let mut outside = gix_hashtable::HashSet::default();
+            for bit in &selected {
+                let commit_id = index.oid_at_index(index_position_by_bit[*bit]).to_owned();
+                let mut bits = Vec::with_capacity(words::len_for(num_objects));
+                walk::reachable(
+                    &db,
+                    Some(commit_id),
+                    bit_of,
+                    |bit, bits| match computed.get(&bit) {
+                        Some(commit_bits) => {
+                            words::or_assign(bits, commit_bits);
+                            true
+                        }
+                        None => false,
+                    },
+                    &mut bits,
+                    &mut outside,
+                    &mut buf,
+                    should_interrupt,
+                )?;
+                if let Some(oid) = outside.iter().next() {
+                    return Err(Error::ObjectNotInPack {
+                        oid: *oid,
+                        commit: commit_id,
+                    });
+                }
+                bits.resize(words::len_for(num_objects), 0);
+                computed.insert(*bit, bits);
+                progress.inc();
+            }
+            progress.show_throughput(start);
+        }
+
+        let mut out = gix_features::hash::Write::new(out, index.object_hash());
+        out.write_all(bitmap::SIGNATURE)?;
+        out.write_all(&(bitmap::Version::V1 as u16).to_be_bytes())?;
+        out.write_all(&flags::FULL_DAG.to_be_bytes())?;
+        out.write_all(&(selected.len() as u32).to_be_bytes())?;
+        out.write_all(index.pack_checksum().as_slice())?;
+        for kind in &mut kinds {
+            kind.resize(words::len_for(num_objects), 0);
+            gix_bitmap::ewah::Vec::from_words(kind, num_objects as u32).write_to(&mut out)?;
+        }
+
+        let mut xored = Vec::new();
+        for (entry_index, bit) in selected.iter().enumerate() {
+            let bits = &computed[bit];
+            let mut best = (0, cost(bits));
+            for xor_offset in 1..=MAX_XOR_OFFSET.min(entry_index) {
+                let other = &computed[&selected[entry_index - xor_offset]];
+                let xor_cost = bits.iter().zip(other).filter(|(a, b)| is_costly(*a ^ *b)).count();
+                if xor_cost < best.1 {
+                    best = (xor_offset, xor_cost);
+                }
+            }
+            let bits = match best.0 {
+                0 => bits,
+                xor_offset => {
+                    xored.clear();
+                    xored.extend_from_slice(bits);
+                    words::xor_assign(&mut xored, &computed[&selected[entry_index - xor_offset]]);
+                    &xored
+                }
+            };
+            out.write_all(&(index_position_by_bit[*bit]).to_be_bytes())?;
+            out.write_all(&[best.0 as u8, 0 /* flags */])?;
+            gix_bitmap::ewah::Vec::from_words(bits, num_objects as u32).write_to(&mut out)?;
+        }
+
+        let bitmap_checksum: ObjectId = out.hash.digest();
+        out.inner.write_all(bitmap_checksum.as_slice())?;
+        Ok(Outcome {
+            bitmap_checksum,
+            num_bitmaps: selected.len(),
+        })
+    }
+}
+
+/// Return the amount of words that can't be run-length encoded, as an estimate of the size of the compressed bitmap.
+fn cost(words: &[u64]) -> usize {
+    words.iter().filter(|w| is_costly(**w)).count()
+}
+
+fn is_costly(word: u64) -> bool {
+    word != 0 && word != u64::MAX
+}
@@ -31,18 +31,17 @@ impl crate::Bundle {
         cache: &mut impl crate::cache::DecodeEntry,
     ) -> Result<(gix_object::Data<'a>, crate::data::entry::Location), crate::data::decode::Error> {
         let ofs = self.index.pack_offset_at_index(idx);
-        let pack_entry = self.pack.entry(ofs);
+        let pack_entry = self.pack.entry(ofs)?;
         let header_size = pack_entry.header_size();
         self.pack
             .decode_entry(
                 pack_entry,
                 out,
                 |id, _out| {
-                    self.index.lookup(id).map(|idx| {
-                        crate::data::decode::entry::ResolvedBase::InPack(
-                            self.pack.entry(self.index.pack_offset_at_index(idx)),
-                        )
-                    })
+                    self.index
+                        .lookup(id)
+                        .and_then(|idx| self.pack.entry(self.index.pack_offset_at_index(idx)).ok())
+                        .map(crate::data::decode::entry::ResolvedBase::InPack)
                 },
                 cache,
             )
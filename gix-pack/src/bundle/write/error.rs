@@ -14,4 +14,6 @@ pub enum Error {
     Persist(#[from] gix_tempfile::handle::persist::Error<Writable>),
     #[error(transparent)]
     IndexWrite(#[from] crate::index::write::Error),
+    #[error("Could not open the newly written index to create a reverse index from it")]
+    IndexOpen(#[from] crate::index::init::Error),
 }
@@ -114,7 +114,7 @@ impl crate::Bundle {
                         writer: data_file.clone(),
                     },
                     pack_version,
-                    gix_hash::Kind::Sha1, // Thin packs imply a pack being transported, and there we only ever know SHA1 at the moment.
+                    object_hash,
                 );
                 (Box::new(pack_entries_iter), pack_version)
             }
@@ -145,6 +145,7 @@ impl crate::Bundle {
             outcome,
             data_path,
             index_path,
+            reverse_index_path,
             keep_path,
         } = crate::Bundle::inner_write(
             directory,
@@ -162,6 +163,7 @@ impl crate::Bundle {
             pack_version,
             data_path,
             index_path,
+            reverse_index_path,
             keep_path,
         })
     }
@@ -248,6 +250,7 @@ impl crate::Bundle {
             outcome,
             data_path,
             index_path,
+            reverse_index_path,
             keep_path,
         } = crate::Bundle::inner_write(
             directory,
@@ -265,6 +268,7 @@ impl crate::Bundle {
             pack_version,
             data_path,
             index_path,
+            reverse_index_path,
             keep_path,
         })
     }
@@ -277,6 +281,7 @@ impl crate::Bundle {
             iteration_mode: _,
             index_version: index_kind,
             object_hash,
+            write_reverse_index,
         }: Options,
         data_file: SharedTempFile,
         pack_entries_iter: impl Iterator<Item = Result<data::input::Entry, data::input::Error>>,
@@ -310,6 +315,19 @@ impl crate::Bundle {
                 let data_path = directory.join(format!("pack-{}.pack", outcome.data_hash.to_hex()));
                 let index_path = data_path.with_extension("idx");
                 let keep_path = data_path.with_extension("keep");
+                let reverse_index_file = write_reverse_index
+                    .then(|| -> Result<_, Error> {
+                        let index = crate::index::File::at(index_file.with_mut(|f| f.path().to_owned())?, object_hash)?;
+                        let mut reverse_index_file =
+                            gix_tempfile::new(directory, ContainingDirectory::Exists, AutoRemove::Tempfile)?;
+                        crate::index::rev::File::write_to(
+                            index.pack_order(),
+                            &outcome.data_hash,
+                            &mut reverse_index_file,
+                        )?;
+                        Ok(reverse_index_file)
+                    })
+                    .transpose()?;
 
                 std::fs::write(&keep_path, b"")?;
                 Arc::try_unwrap(data_file)
@@ -318,6 +336,13 @@ impl crate::Bundle {
                     .into_inner()
                     .map_err(|err| Error::from(err.into_error()))?
                     .persist(&data_path)?;
+                let reverse_index_path = reverse_index_file
+                    .map(|file| -> Result<_, Error> {
+                        let path = data_path.with_extension("rev");
+                        file.persist(&path)?;
+                        Ok(path)
+                    })
+                    .transpose()?;
                 index_file
                     .persist(&index_path)
                     .map_err(|err| {
@@ -331,6 +356,7 @@ impl crate::Bundle {
                     outcome,
                     data_path: Some(data_path),
                     index_path: Some(index_path),
+                    reverse_index_path,
                     keep_path: Some(keep_path),
                 }
             }
@@ -348,6 +374,7 @@ impl crate::Bundle {
                 )?,
                 data_path: None,
                 index_path: None,
+                reverse_index_path: None,
                 keep_path: None,
             },
         })
@@ -374,5 +401,6 @@ struct WriteOutcome {
     outcome: crate::index::write::Outcome,
     data_path: Option<PathBuf>,
     index_path: Option<PathBuf>,
+    reverse_index_path: Option<PathBuf>,
     keep_path: Option<PathBuf>,
 }
@@ -14,6 +14,8 @@ pub struct Options {
     pub index_version: crate::index::Version,
     /// The kind of hash to use when writing the bundle.
     pub object_hash: gix_hash::Kind,
+    /// If `true`, write a [reverse index][crate::index::rev] next to the pack index, as configured by `pack.writeReverseIndex`.
+    pub write_reverse_index: bool,
 }
 
 impl Default for Options {
@@ -24,6 +26,7 @@ impl Default for Options {
             iteration_mode: crate::data::input::Mode::Verify,
             index_version: Default::default(),
             object_hash: Default::default(),
+            write_reverse_index: false,
         }
     }
 }
@@ -44,6 +47,8 @@ pub struct Outcome {
     pub index_path: Option<PathBuf>,
     /// The path to the pack data file
     pub data_path: Option<PathBuf>,
+    /// The path to the reverse index file, if one was written.
+    pub reverse_index_path: Option<PathBuf>,
     /// The path to the `.keep` file to prevent collection of the newly written pack until refs are pointing to it.
     ///
     /// The file is created right before moving the pack data and index data into place (i.e. `data_path` and `index_path`)
@@ -68,3 +68,214 @@ pub fn apply(base: &[u8], mut target: &mut [u8], data: &[u8]) {
     assert_eq!(i, data.len());
     assert_eq!(target.len(), 0);
 }
+
+/// The size of the blocks of the base object that are indexed, which is also the minimal size of copy instructions.
+const BLOCK_SIZE: usize = 16;
+/// The maximum amount of candidates with the same hash to consider, to bound the time spent on repetitive data.
+const MAX_CANDIDATES: usize = 64;
+/// The maximum amount of bytes to copy in a single instruction, the same limit git uses for compatibility.
+const MAX_COPY_SIZE: usize = 0x10000;
+/// The maximum amount of bytes to insert in a single instruction.
+const MAX_INSERT_SIZE: usize = 0x7f;
+/// The factor of the rolling hash.
+const HASH_FACTOR: u32 = 0x0100_0193;
+/// A sentinel value for the end of a chain of blocks.
+const NONE: u32 = u32::MAX;
+
+/// An index over the blocks of a base object to quickly find sections of it within target objects, to [encode][Index::encode()]
+/// them as delta against the base.
+pub(crate) struct Index {
+    base: Vec<u8>,
+    /// The mask to obtain a slot in `heads` from a hash.
+    mask: u32,
+    /// The index of the last block in `base` for each slot, or `NONE`.
+    heads: Vec<u32>,
+    /// For each block in `base`, the index of the previous block in the same slot, or `NONE`.
+    next: Vec<u32>,
+}
+
+fn hash_block(block: &[u8]) -> u32 {
+    block
+        .iter()
+        .fold(0u32, |h, b| h.wrapping_mul(HASH_FACTOR).wrapping_add(*b as u32))
+}
+
+impl Index {
+    /// Index all blocks of `base`.
+    pub(crate) fn new(base: Vec<u8>) -> Self {
+        let num_blocks = base.len() / BLOCK_SIZE;
+        let num_slots = num_blocks.max(1).next_power_of_two();
+        let mask = (num_slots - 1) as u32;
+        let mut heads = vec![NONE; num_slots];
+        let mut next = vec![NONE; num_blocks];
+        for (block_idx, block) in base.chunks_exact(BLOCK_SIZE).enumerate() {
+            let slot = (hash_block(block) & mask) as usize;
+            next[block_idx] = heads[slot];
+            heads[slot] = block_idx as u32;
+        }
+        Index {
+            base,
+            mask,
+            heads,
+            next,
+        }
+    }
+
+    /// The size of the base object in bytes.
+    pub(crate) fn base_size(&self) -> usize {
+        self.base.len()
+    }
+
+    /// Return the offset into our base and the length of the longest match of `target[pos..]`, whose first block hashes to `hash`.
+    fn longest_match(&self, target: &[u8], pos: usize, hash: u32) -> Option<(usize, usize)> {
+        let mut best: Option<(usize, usize)> = None;
+        let mut block_idx = self.heads[(hash & self.mask) as usize];
+        let mut candidates = 0;
+        while block_idx != NONE && candidates < MAX_CANDIDATES {
+            candidates += 1;
+            let base_pos = block_idx as usize * BLOCK_SIZE;
+            let len = self.base[base_pos..]
+                .iter()
+                .zip(&target[pos..])
+                .take_while(|(a, b)| a == b)
+                .count();
+            if len >= BLOCK_SIZE && best.map_or(true, |(_, best_len)| len > best_len) {
+                best = Some((base_pos, len));
+            }
+            block_idx = self.next[block_idx as usize];
+        }
+        best
+    }
+
+    /// Encode `target` as delta against our base into `out`, which is cleared beforehand.
+    ///
+    /// Return `false` if the delta would be larger than `max_size` bytes, in which case `out` is left in an unspecified state.
+    /// Note that the `target` is expected to be smaller than 4GB.
+    pub(crate) fn encode(&self, target: &[u8], max_size: usize, out: &mut Vec<u8>) -> bool {
+        out.clear();
+        encode_header_size(self.base.len() as u64, out);
+        encode_header_size(target.len() as u64, out);
+
+        let mut insert_start = 0;
+        let mut pos = 0;
+        let mut hash = target.get(..BLOCK_SIZE).map(hash_block).unwrap_or_default();
+        let factor_pow = (1..BLOCK_SIZE).fold(1u32, |f, _| f.wrapping_mul(HASH_FACTOR));
+        while pos + BLOCK_SIZE <= target.len() {
+            match self.longest_match(target, pos, hash) {
+                Some((mut base_pos, mut len)) => {
+                    // Grow the match backwards into the data that would otherwise be inserted.
+                    while pos > insert_start && base_pos > 0 && self.base[base_pos - 1] == target[pos - 1] {
+                        pos -= 1;
+                        base_pos -= 1;
+                        len += 1;
+                    }
+                    encode_insert(&target[insert_start..pos], out);
+                    encode_copy(base_pos, len, out);
+                    if out.len() > max_size {
+                        return false;
+                    }
+                    pos += len;
+                    insert_start = pos;
+                    if let Some(block) = target.get(pos..pos + BLOCK_SIZE) {
+                        hash = hash_block(block);
+                    }
+                }
+                None => {
+                    if pos + BLOCK_SIZE < target.len() {
+                        hash = hash
+                            .wrapping_sub((target[pos] as u32).wrapping_mul(factor_pow))
+                            .wrapping_mul(HASH_FACTOR)
+                            .wrapping_add(target[pos + BLOCK_SIZE] as u32);
+                    }
+                    pos += 1;
+                    if pos - insert_start > max_size {
+                        return false;
+                    }
+                }
+            }
+        }
+        encode_insert(&target[insert_start..], out);
+        out.len() <= max_size
+    }
+}
+
+/// Encode `size` in the format used for the base and result size at the beginning of a delta.
+fn encode_header_size(mut size: u64, out: &mut Vec<u8>) {
+    while size >= 0x80 {
+        out.push((size as u8 & 0x7f) | 0x80);
+        size >>= 7;
+    }
+    out.push(size as u8);
+}
+
+fn encode_insert(data: &[u8], out: &mut Vec<u8>) {
+    for chunk in data.chunks(MAX_INSERT_SIZE) {
+        out.push(chunk.len() as u8);
+        out.extend_from_slice(chunk);
+    }
+}
+
+fn encode_copy(mut offset: usize, mut len: usize, out: &mut Vec<u8>) {
+    while len != 0 {
+        let size = len.min(MAX_COPY_SIZE);
+        let cmd_pos = out.len();
+        let mut cmd = 0b1000_0000u8;
+        out.push(cmd);
+        for (bit, byte) in (offset as u32).to_le_bytes().iter().enumerate() {
+            if *byte != 0 {
+                cmd |= 1 << bit;
+                out.push(*byte);
+            }
+        }
+        // A size of zero is interpreted as `MAX_COPY_SIZE`, so no size bytes are needed in that case.
+        let encoded_size = if size == MAX_COPY_SIZE { 0 } else { size as u32 };
+        for (bit, byte) in encoded_size.to_le_bytes()[..3].iter().enumerate() {
+            if *byte != 0 {
+                cmd |= 1 << (bit + 4);
+                out.push(*byte);
+            }
+        }
+        out[cmd_pos] = cmd;
+        offset += size;
+        len -= size;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(base: &[u8], target: &[u8]) -> usize {
+        let mut delta = Vec::new();
+        assert!(Index::new(base.to_vec()).encode(target, usize::MAX, &mut delta));
+        let (base_size, consumed) = decode_header_size(&delta);
+        assert_eq!(base_size as usize, base.len());
+        let (target_size, consumed_2) = decode_header_size(&delta[consumed..]);
+        assert_eq!(target_size as usize, target.len());
+        let mut out = vec![0; target.len()];
+        apply(base, &mut out, &delta[consumed + consumed_2..]);
+        assert_eq!(out, target);
+        delta.len()
+    }
+
+    #[test]
+    fn encode_round_trips_through_apply() {
+        let base: Vec<u8> = (0..200_000u32).flat_map(|n| (n % 251).to_le_bytes()).collect();
+        let mut target = b"a new beginning".to_vec();
+        target.extend_from_slice(&base[..100_000]);
+        target.extend_from_slice(b"something in the middle");
+        target.extend_from_slice(&base[300_000..]);
+        assert!(round_trip(&base, &target) < 100, "large copies are cheap");
+
+        round_trip(b"", b"");
+        round_trip(b"", b"only inserted data");
+        round_trip(b"a base that won't be used", b"");
+        round_trip(&[0; 17], &[0; 1000]);
+    }
+
+    #[test]
+    fn encode_respects_max_size() {
+        let mut delta = Vec::new();
+        assert!(!Index::new(b"base".to_vec()).encode(&[1; 200], 100, &mut delta));
+    }
+}
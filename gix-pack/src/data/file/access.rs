@@ -0,0 +1,167 @@
+use std::{collections::VecDeque, convert::TryFrom, io, ops::Range, path::Path, sync::Arc};
+
+use memmap2::Mmap;
+
+/// The size of each window of pack data kept in memory.
+const WINDOW_SIZE: u64 = 64 * 1024;
+/// The amount of most recently used windows to keep per pack.
+const MAX_WINDOWS: usize = 16;
+/// Windows start at multiples of this value.
+const WINDOW_ALIGNMENT: u64 = 4096;
+
+/// The bytes of a pack data file, either mapped into memory or read on demand.
+pub(crate) enum Data {
+    Mmap(Mmap),
+    Read(Windows),
+}
+
+impl Data {
+    /// The length of the pack data file in bytes.
+    pub(crate) fn len(&self) -> u64 {
+        match self {
+            Data::Mmap(data) => data.len() as u64,
+            Data::Read(windows) => windows.len,
+        }
+    }
+
+    /// Call `f` with the bytes in `range`.
+    ///
+    /// # Panics
+    ///
+    /// If the pack is memory-mapped and `range` is out of bounds.
+    pub(crate) fn with_range<T>(&self, range: Range<u64>, f: impl FnOnce(&[u8]) -> T) -> io::Result<T> {
+        match self {
+            Data::Mmap(data) => {
+                // Offsets that don't fit into `usize` are out of bounds of any memory map.
+                let start = usize::try_from(range.start).unwrap_or(usize::MAX);
+                let end = usize::try_from(range.end).unwrap_or(usize::MAX);
+                Ok(f(&data[start..end]))
+            }
+            Data::Read(windows) => windows.with_range(range, f),
+        }
+    }
+}
+
+/// A pack data file which is read with positioned reads, keeping the most recently used windows of its data in memory.
+pub(crate) struct Windows {
+    file: std::fs::File,
+    len: u64,
+    /// Most recently used windows come first.
+    windows: parking_lot::Mutex<VecDeque<Window>>,
+}
+
+#[derive(Clone)]
+struct Window {
+    offset: u64,
+    data: Arc<[u8]>,
+}
+
+impl Window {
+    fn contains(&self, range: &Range<u64>) -> bool {
+        self.offset <= range.start && range.end <= self.offset + self.data.len() as u64
+    }
+}
+
+impl Windows {
+    pub(crate) fn at(path: &Path) -> io::Result<Self> {
+        let file = std::fs::File::open(path)?;
+        let len = file.metadata()?.len();
+        Ok(Windows {
+            file,
+            len,
+            windows: Default::default(),
+        })
+    }
+
+    /// Call `f` with the bytes in `range`, which are read from disk unless they are contained in a window.
+    ///
+    /// Ranges larger than half a window are read into a buffer of their own to avoid evicting all other windows.
+    pub(crate) fn with_range<T>(&self, range: Range<u64>, f: impl FnOnce(&[u8]) -> T) -> io::Result<T> {
+        if range.start > range.end || range.end > self.len {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!(
+                    "range {}..{} is out of bounds of pack data of size {}",
+                    range.start, range.end, self.len
+                ),
+            ));
+        }
+        let range_len = range.end - range.start;
+        if range_len > WINDOW_SIZE / 2 {
+            let range_len = usize::try_from(range_len).map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::Other,
+                    format!("range of {range_len} bytes is too large to be read into memory on this platform"),
+                )
+            })?;
+            let mut buf = vec![0; range_len];
+            read_exact_at(&self.file, &mut buf, range.start)?;
+            return Ok(f(&buf));
+        }
+        let window = self.window_containing(&range)?;
+        let start = (range.start - window.offset) as usize;
+        Ok(f(&window.data[start..][..range_len as usize]))
+    }
+
+    fn window_containing(&self, range: &Range<u64>) -> io::Result<Window> {
+        {
+            let mut windows = self.windows.lock();
+            if let Some(pos) = windows.iter().position(|w| w.contains(range)) {
+                let window = windows.remove(pos).expect("present");
+                windows.push_front(window.clone());
+                return Ok(window);
+            }
+        }
+
+        let offset = range.start - range.start % WINDOW_ALIGNMENT;
+        let end = (offset + WINDOW_SIZE).min(self.len);
+        let mut data = vec![0; (end - offset) as usize];
+        read_exact_at(&self.file, &mut data, offset)?;
+        let window = Window {
+            offset,
+            data: data.into(),
+        };
+
+        let mut windows = self.windows.lock();
+        if windows.len() == MAX_WINDOWS {
+            windows.pop_back();
+        }
+        windows.push_front(window.clone());
+        Ok(window)
+    }
+}
+
+#[cfg(unix)]
+fn read_exact_at(file: &std::fs::File, buf: &mut [u8], offset: u64) -> io::Result<()> {
+    std::os::unix::fs::FileExt::read_exact_at(file, buf, offset)
+}
+
+#[cfg(windows)]
+fn read_exact_at(file: &std::fs::File, mut buf: &mut [u8], mut offset: u64) -> io::Result<()> {
+    use std::os::windows::fs::FileExt;
+    while !buf.is_empty() {
+        match file.seek_read(buf, offset) {
+            Ok(0) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "failed to fill whole buffer",
+                ))
+            }
+            Ok(n) => {
+                buf = &mut buf[n..];
+                offset += n as u64;
+            }
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(any(unix, windows)))]
+fn read_exact_at(_file: &std::fs::File, _buf: &mut [u8], _offset: u64) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Other,
+        "positioned reads are not supported on this platform",
+    ))
+}
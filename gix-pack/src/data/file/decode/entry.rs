@@ -91,7 +91,6 @@ impl File {
         );
 
         self.decompress_entry_from_data_offset(entry.data_offset, out)
-            .map_err(Into::into)
     }
 
     fn assure_v2(&self) {
@@ -101,16 +100,20 @@ impl File {
         );
     }
 
-    /// Obtain the [`Entry`][crate::data::Entry] at the given `offset` into the pack.
+    /// Obtain the [`Entry`][crate::data::Entry] at the given `offset` into the pack, or an error if the pack data
+    /// couldn't be read.
     ///
     /// The `offset` is typically obtained from the pack index file.
-    pub fn entry(&self, offset: data::Offset) -> data::Entry {
+    pub fn entry(&self, offset: data::Offset) -> std::io::Result<data::Entry> {
         self.assure_v2();
-        let pack_offset: usize = offset.try_into().expect("offset representable by machine");
-        assert!(pack_offset <= self.data.len(), "offset out of bounds");
+        assert!(offset <= self.data.len(), "offset out of bounds");
 
-        let object_data = &self.data[pack_offset..];
-        data::Entry::from_bytes(object_data, offset, self.hash_len)
+        // The largest possible header has a 64 bit size and a 64 bit base distance, 10 bytes each, or a hash instead of the latter.
+        let max_header_len = 20 + self.hash_len as u64;
+        let header_end = (offset + max_header_len).min(self.data.len());
+        self.data.with_range(offset..header_end, |object_data| {
+            data::Entry::from_bytes(object_data, offset, self.hash_len)
+        })
     }
 
     /// Decompress the object expected at the given data offset, sans pack header. This information is only
@@ -122,13 +125,9 @@ impl File {
         &self,
         data_offset: data::Offset,
         out: &mut [u8],
-    ) -> Result<usize, zlib::inflate::Error> {
-        let offset: usize = data_offset.try_into().expect("offset representable by machine");
-        assert!(offset < self.data.len(), "entry offset out of bounds");
-
-        zlib::Inflate::default()
-            .once(&self.data[offset..], out)
-            .map(|(_status, consumed_in, _consumed_out)| consumed_in)
+    ) -> Result<usize, Error> {
+        self.decompress_entry_from_data_offset_2(data_offset, out)
+            .map(|(consumed_in, _consumed_out)| consumed_in)
     }
 
     /// Like `decompress_entry_from_data_offset`, but returns consumed input and output.
@@ -136,13 +135,35 @@ impl File {
         &self,
         data_offset: data::Offset,
         out: &mut [u8],
-    ) -> Result<(usize, usize), zlib::inflate::Error> {
-        let offset: usize = data_offset.try_into().expect("offset representable by machine");
-        assert!(offset < self.data.len(), "entry offset out of bounds");
-
-        zlib::Inflate::default()
-            .once(&self.data[offset..], out)
-            .map(|(_status, consumed_in, consumed_out)| (consumed_in, consumed_out))
+    ) -> Result<(usize, usize), Error> {
+        assert!(data_offset < self.data.len(), "entry offset out of bounds");
+
+        match &self.data {
+            // The memory map is addressable, so an in-bounds offset fits into `usize`.
+            data::Data::Mmap(data) => zlib::Inflate::default()
+                .once(&data[data_offset as usize..], out)
+                .map(|(_status, consumed_in, consumed_out)| (consumed_in, consumed_out))
+                .map_err(Into::into),
+            data::Data::Read(_) => {
+                // Feed the compressed stream chunk by chunk as we don't know its size, until it ends or there is no progress.
+                const CHUNK_SIZE: u64 = 16 * 1024;
+                let mut inflate = zlib::Inflate::default();
+                let (mut consumed_in, mut consumed_out) = (0, 0);
+                loop {
+                    let start = data_offset + consumed_in as u64;
+                    let end = (start + CHUNK_SIZE).min(self.data.len());
+                    let (status, chunk_in, chunk_out) = self
+                        .data
+                        .with_range(start..end, |input| inflate.once(input, &mut out[consumed_out..]))??;
+                    consumed_in += chunk_in;
+                    consumed_out += chunk_out;
+                    if status == zlib::Status::StreamEnd || (chunk_in == 0 && chunk_out == 0) || end == self.data.len()
+                    {
+                        break Ok((consumed_in, consumed_out));
+                    }
+                }
+            }
+        }
     }
 
     /// Decode an entry, resolving delta's as needed, while growing the `out` vector if there is not enough
@@ -232,7 +253,7 @@ impl File {
             });
             use crate::data::entry::Header;
             cursor = match cursor.header {
-                Header::OfsDelta { base_distance } => self.entry(cursor.base_pack_offset(base_distance)),
+                Header::OfsDelta { base_distance } => self.entry(cursor.base_pack_offset(base_distance))?,
                 Header::RefDelta { base_id } => match resolve(base_id.as_ref(), out) {
                     Some(ResolvedBase::InPack(entry)) => entry,
                     Some(ResolvedBase::OutOfPack { end, kind }) => {
@@ -1,6 +1,6 @@
 use std::{convert::TryInto, path::Path};
 
-use crate::data;
+use crate::{data, data::file::access::Windows};
 
 /// Instantiation
 impl data::File {
@@ -9,31 +9,58 @@ impl data::File {
     /// The `object_hash` is a way to read (and write) the same file format with different hashes, as the hash kind
     /// isn't stored within the file format itself.
     pub fn at(path: impl AsRef<Path>, object_hash: gix_hash::Kind) -> Result<data::File, data::header::decode::Error> {
-        Self::at_inner(path.as_ref(), object_hash)
+        Self::at_inner(path.as_ref(), object_hash, data::Access::default())
     }
 
-    fn at_inner(path: &Path, object_hash: gix_hash::Kind) -> Result<data::File, data::header::decode::Error> {
+    /// Like [`at()`][data::File::at()], but allows to control how the pack data is accessed with `access`.
+    pub fn at_with_access(
+        path: impl AsRef<Path>,
+        object_hash: gix_hash::Kind,
+        access: data::Access,
+    ) -> Result<data::File, data::header::decode::Error> {
+        Self::at_inner(path.as_ref(), object_hash, access)
+    }
+
+    fn at_inner(
+        path: &Path,
+        object_hash: gix_hash::Kind,
+        access: data::Access,
+    ) -> Result<data::File, data::header::decode::Error> {
         use crate::data::header::N32_SIZE;
         let hash_len = object_hash.len_in_bytes();
 
-        let data = crate::mmap::read_only(path).map_err(|e| data::header::decode::Error::Io {
-            source: e,
+        let io_err = |source| data::header::decode::Error::Io {
+            source,
             path: path.to_owned(),
-        })?;
+        };
+        let data = match access {
+            data::Access::MemoryMap => data::Data::Mmap(crate::mmap::read_only(path).map_err(io_err)?),
+            data::Access::Read => data::Data::Read(Windows::at(path).map_err(io_err)?),
+        };
         let pack_len = data.len();
-        if pack_len < N32_SIZE * 3 + hash_len {
+        if pack_len < (N32_SIZE * 3 + hash_len) as u64 {
             return Err(data::header::decode::Error::Corrupt(format!(
                 "Pack data of size {pack_len} is too small for even an empty pack with shortest hash"
             )));
         }
-        let (kind, num_objects) =
-            data::header::decode(&data[..12].try_into().expect("enough data after previous check"))?;
+        let header: data::Header = data
+            .with_range(0..12, |header| {
+                header.try_into().expect("enough data after previous check")
+            })
+            .map_err(io_err)?;
+        let (kind, num_objects) = data::header::decode(&header)?;
+        let checksum = data
+            .with_range(pack_len - hash_len as u64..pack_len, |trailer| {
+                gix_hash::ObjectId::from(trailer)
+            })
+            .map_err(io_err)?;
         Ok(data::File {
             data,
             path: path.to_owned(),
             id: gix_features::hash::crc32(path.as_os_str().to_string_lossy().as_bytes()),
             version: kind,
             num_objects,
+            checksum,
             hash_len,
             object_hash,
         })
@@ -1,3 +1,5 @@
+mod access;
+pub(crate) use access::Data;
 mod init;
 ///
 pub mod verify;
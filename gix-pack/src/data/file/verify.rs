@@ -2,7 +2,7 @@ use std::sync::atomic::AtomicBool;
 
 use gix_features::progress::Progress;
 
-use crate::data::File;
+use crate::data::{Data, File};
 
 ///
 pub mod checksum {
@@ -14,7 +14,7 @@ pub mod checksum {
 impl File {
     /// The checksum in the trailer of this pack data file
     pub fn checksum(&self) -> gix_hash::ObjectId {
-        gix_hash::ObjectId::from(&self.data[self.data.len() - self.hash_len..])
+        self.checksum
     }
 
     /// Verifies that the checksum of the packfile over all bytes preceding it indeed matches the actual checksum,
@@ -30,13 +30,23 @@ impl File {
         progress: impl Progress,
         should_interrupt: &AtomicBool,
     ) -> Result<gix_hash::ObjectId, checksum::Error> {
-        crate::verify::checksum_on_disk_or_mmap(
-            self.path(),
-            &self.data,
-            self.checksum(),
-            self.object_hash,
-            progress,
-            should_interrupt,
-        )
+        match &self.data {
+            Data::Mmap(data) => crate::verify::checksum_on_disk_or_mmap(
+                self.path(),
+                data,
+                self.checksum(),
+                self.object_hash,
+                progress,
+                should_interrupt,
+            ),
+            Data::Read(_) => crate::verify::checksum_on_disk(
+                self.path(),
+                self.pack_end(),
+                self.checksum(),
+                self.object_hash,
+                progress,
+                should_interrupt,
+            ),
+        }
     }
 }
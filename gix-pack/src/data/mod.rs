@@ -1,5 +1,5 @@
 //! a pack data file
-use std::{convert::TryInto, path::Path};
+use std::{borrow::Cow, path::Path};
 
 /// The offset to an entry into the pack data file, relative to its beginning.
 pub type Offset = u64;
@@ -7,8 +7,6 @@ pub type Offset = u64;
 /// An identifier to uniquely identify all packs loaded within a known context or namespace.
 pub type Id = u32;
 
-use memmap2::Mmap;
-
 /// An representing an full- or delta-object within a pack
 #[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -24,6 +22,7 @@ pub struct Entry {
 }
 
 mod file;
+pub(crate) use file::Data;
 pub use file::{decode, verify, Header};
 ///
 pub mod header;
@@ -57,9 +56,23 @@ pub enum Version {
     V3,
 }
 
+/// The way the bytes of a pack data file are accessed.
+#[derive(Default, PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Access {
+    /// Memory-map the entire file, which is fastest but turns IO errors into crashes (`SIGBUS` on unix),
+    /// and needs enough address space to map the whole pack, which can be a problem on 32 bit systems.
+    #[default]
+    MemoryMap,
+    /// Read the file in windows using positioned reads and keep the most recently used windows in memory.
+    ///
+    /// This is slower, but IO errors are reported as errors, which matters most on network filesystems.
+    Read,
+}
+
 /// A pack data file
 pub struct File {
-    data: Mmap,
+    data: Data,
     path: std::path::PathBuf,
     /// A value to represent this pack uniquely when used with cache lookup, or a way to identify this pack by its location on disk.
     /// The same location on disk should yield the same id.
@@ -71,6 +84,7 @@ pub struct File {
     pub id: Id,
     version: Version,
     num_objects: u32,
+    checksum: gix_hash::ObjectId,
     /// The size of the hash contained within. This is entirely determined by the caller, and repositories have to know which hash to use
     /// based on their configuration.
     hash_len: usize,
@@ -87,8 +101,8 @@ impl File {
     pub fn num_objects(&self) -> u32 {
         self.num_objects
     }
-    /// The length of all mapped data, including the pack header and the pack trailer
-    pub fn data_len(&self) -> usize {
+    /// The length of all pack data, including the pack header and the pack trailer
+    pub fn data_len(&self) -> u64 {
         self.data.len()
     }
     /// The kind of hash we use internally.
@@ -96,8 +110,8 @@ impl File {
         self.object_hash
     }
     /// The position of the byte one past the last pack entry, or in other terms, the first byte of the trailing hash.
-    pub fn pack_end(&self) -> usize {
-        self.data.len() - self.hash_len
+    pub fn pack_end(&self) -> u64 {
+        self.data.len() - self.hash_len as u64
     }
 
     /// The path to the pack data file on disk
@@ -105,24 +119,40 @@ impl File {
         &self.path
     }
 
-    /// Returns the pack data at the given slice if its range is contained in the mapped pack data
-    pub fn entry_slice(&self, slice: EntryRange) -> Option<&[u8]> {
-        let entry_end: usize = slice.end.try_into().expect("end of pack fits into usize");
-        let entry_start = slice.start as usize;
-        self.data.get(entry_start..entry_end)
+    /// The way the pack data is accessed.
+    pub fn access(&self) -> Access {
+        match self.data {
+            Data::Mmap(_) => Access::MemoryMap,
+            Data::Read(_) => Access::Read,
+        }
+    }
+
+    /// Returns the pack data at the given slice if its range is contained in the pack data, or an error if it couldn't be read.
+    ///
+    /// The data is borrowed if the pack is [memory-mapped][Access::MemoryMap].
+    pub fn entry_slice(&self, slice: EntryRange) -> std::io::Result<Option<Cow<'_, [u8]>>> {
+        if slice.start > slice.end || slice.end > self.data.len() {
+            return Ok(None);
+        }
+        Ok(Some(match &self.data {
+            // The memory map is addressable, so an in-bounds range fits into `usize`.
+            Data::Mmap(data) => Cow::Borrowed(&data[slice.start as usize..slice.end as usize]),
+            Data::Read(windows) => Cow::Owned(windows.with_range(slice, <[u8]>::to_vec)?),
+        }))
     }
 
-    /// Returns the CRC32 of the pack data indicated by `pack_offset` and the `size` of the mapped data.
+    /// Returns the CRC32 of the pack data indicated by `pack_offset` and the `size` of the data,
+    /// or an error if it couldn't be read.
     ///
     /// _Note:_ finding the right size is only possible by decompressing
     /// the pack entry beforehand, or by using the (to be sorted) offsets stored in an index file.
     ///
     /// # Panics
     ///
-    /// If `pack_offset` or `size` are pointing to a range outside of the mapped pack data.
-    pub fn entry_crc32(&self, pack_offset: Offset, size: usize) -> u32 {
-        let pack_offset: usize = pack_offset.try_into().expect("pack_size fits into usize");
-        gix_features::hash::crc32(&self.data[pack_offset..pack_offset + size])
+    /// If `pack_offset` or `size` are pointing to a range outside of the pack data.
+    pub fn entry_crc32(&self, pack_offset: Offset, size: usize) -> std::io::Result<u32> {
+        self.data
+            .with_range(pack_offset..pack_offset + size as u64, gix_features::hash::crc32)
     }
 }
 
@@ -35,13 +35,14 @@ impl Count {
         Count {
             id: oid.into(),
             entry_pack_location: PackLocation::LookedUp(location),
+            no_delta: false,
         }
     }
 }
 
 #[path = "objects/mod.rs"]
 mod objects_impl;
-pub use objects_impl::{objects, objects_unthreaded};
+pub use objects_impl::{objects, objects_unthreaded, objects_with_bitmap};
 
 ///
 pub mod objects {
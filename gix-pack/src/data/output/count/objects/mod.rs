@@ -135,6 +135,61 @@ where
     )
 }
 
+/// Like [`objects_unthreaded()`], but produces [`Count`][output::Count]s for all objects reachable from `object_ids` while using
+/// the commit bitmaps of `bitmap` to avoid traversing the objects reachable from them.
+///
+/// Objects reachable from `object_ids` that aren't part of the pack or multi-pack-index of `bitmap` are found by traversal,
+/// using `db`, which is also used to obtain objects for commits without bitmap.
+/// The pack location of the returned counts isn't looked up.
+pub fn objects_with_bitmap<Find, IterErr, Oid>(
+    db: Find,
+    bitmap: &crate::bitmap::Reachability,
+    object_ids: impl Iterator<Item = std::result::Result<Oid, IterErr>>,
+    mut progress: impl Progress,
+    should_interrupt: &AtomicBool,
+) -> Result<find::existing::Error<Find::Error>, IterErr>
+where
+    Find: crate::Find,
+    Oid: Into<ObjectId>,
+    IterErr: std::error::Error,
+{
+    use crate::bitmap::walk;
+
+    let tips = object_ids
+        .map(|id| id.map(Into::into).map_err(Error::InputIteration))
+        .collect::<std::result::Result<Vec<ObjectId>, _>>()?;
+    let input_objects = tips.len();
+    let (bits, outside, walk) = bitmap.reachable(&db, tips, should_interrupt).map_err(|err| match err {
+        walk::Error::Find(err) => Error::FindExisting(find::existing::Error::Find(err)),
+        walk::Error::NotFound { oid } => Error::FindExisting(find::existing::Error::NotFound { oid }),
+        walk::Error::Decode(err) => Error::CommitDecode(err),
+        walk::Error::Interrupted => Error::Interrupted,
+    })?;
+
+    let mut out = Vec::with_capacity(outside.len());
+    let mut push = |id: ObjectId| {
+        progress.inc();
+        out.push(output::Count {
+            id,
+            entry_pack_location: output::count::PackLocation::NotLookedUp,
+            no_delta: false,
+        });
+    };
+    crate::bitmap::words::for_each_set_bit(&bits, |bit| push(bitmap.id_at_bit(bit).to_owned()));
+    outside.into_iter().for_each(push);
+
+    let total_objects = out.len();
+    Ok((
+        out,
+        Outcome {
+            input_objects,
+            expanded_objects: total_objects.saturating_sub(input_objects),
+            decoded_objects: walk.decoded_objects,
+            total_objects,
+        },
+    ))
+}
+
 mod expand {
     use std::sync::atomic::{AtomicBool, Ordering};
 
@@ -229,7 +284,7 @@ mod expand {
                                     push_obj_count_unique(
                                         &mut out, seen_objs, &tree_id, location, progress, stats, true,
                                     );
-                                    gix_object::TreeRefIter::from_bytes(obj.data)
+                                    gix_object::TreeRefIter::from_bytes(obj.data, tree_id.kind())
                                 };
 
                                 let objects = if parent_commit_ids.is_empty() {
@@ -244,7 +299,7 @@ mod expand {
                                                     progress.inc();
                                                     stats.expanded_objects += 1;
                                                     out.push(output::Count::from_data(oid, location));
-                                                    obj.try_into_tree_iter()
+                                                    obj.try_into_tree_iter(oid.kind())
                                                 }
                                                 None => None,
                                             }
@@ -276,7 +331,10 @@ mod expand {
                                                 stats,
                                                 true,
                                             );
-                                            gix_object::TreeRefIter::from_bytes(parent_tree_obj.data)
+                                            gix_object::TreeRefIter::from_bytes(
+                                                parent_tree_obj.data,
+                                                parent_tree_id.kind(),
+                                            )
                                         };
 
                                         changes_delegate.clear();
@@ -312,7 +370,7 @@ mod expand {
                             Tree => {
                                 traverse_delegate.clear();
                                 gix_traverse::tree::breadthfirst(
-                                    gix_object::TreeRefIter::from_bytes(obj.0.data),
+                                    gix_object::TreeRefIter::from_bytes(obj.0.data, id.kind()),
                                     &mut tree_traversal_state,
                                     |oid, buf| {
                                         stats.decoded_objects += 1;
@@ -321,7 +379,7 @@ mod expand {
                                                 progress.inc();
                                                 stats.expanded_objects += 1;
                                                 out.push(output::Count::from_data(oid, location));
-                                                obj.try_into_tree_iter()
+                                                obj.try_into_tree_iter(oid.kind())
                                             }
                                             None => None,
                                         }
@@ -400,6 +458,7 @@ mod expand {
             } else {
                 PackLocation::NotLookedUp
             },
+            no_delta: false,
         }
     }
 }
@@ -3,7 +3,7 @@ pub(crate) mod function {
 
     use gix_features::{parallel, parallel::SequenceId, progress::Progress};
 
-    use super::{reduce, util, Error, Mode, Options, Outcome, ProgressId};
+    use super::{delta, reduce, util, Error, Mode, Options, Outcome, ProgressId};
     use crate::data::output;
 
     /// Given a known list of object `counts`, calculate entries ready to be put into a data pack.
@@ -29,6 +29,9 @@ pub(crate) mod function {
     /// * Begins writing immediately and supports back-pressure.
     /// * Abstract over object databases and how input is provided.
     ///
+    /// Note that with [delta compression][Mode::DeltaCompression], all objects have to be decoded and sorted before
+    /// entries can be produced, and chunks are as large as needed to keep all threads busy.
+    ///
     /// ### Disadvantages
     ///
     /// * ~~currently there is no way to easily write the pack index, even though the state here is uniquely positioned to do
@@ -56,7 +59,7 @@ pub(crate) mod function {
             matches!(version, crate::data::Version::V2),
             "currently we can only write version 2"
         );
-        let (chunk_size, thread_limit, _) =
+        let (chunk_size, thread_limit, num_threads) =
             parallel::optimize_chunk_size_and_thread_limit(chunk_size, Some(counts.len()), thread_limit, None);
         {
             let progress = Arc::new(parking_lot::Mutex::new(
@@ -127,6 +130,16 @@ pub(crate) mod function {
 
                 index
             }
+            Mode::DeltaCompression { .. } => {
+                let mut progress = progress.add_child_with_id("sorting", ProgressId::SortEntries.into());
+                delta::sort_by_kind_and_size(&mut counts, &db, chunk_size, thread_limit, &mut progress);
+                Vec::new()
+            }
+        };
+        let chunk_size = match mode {
+            Mode::PackCopyAndBaseObjects => chunk_size,
+            // Deltas are only searched for within a chunk, so make them as large as possible while keeping all threads busy.
+            Mode::DeltaCompression { .. } => ((counts.len() + num_threads - 1) / num_threads).max(chunk_size),
         };
 
         let counts = Arc::new(counts);
@@ -151,11 +164,24 @@ pub(crate) mod function {
                 let counts = Arc::clone(&counts);
                 move |(chunk_id, chunk_range): (SequenceId, std::ops::Range<usize>), (buf, progress)| {
                     let mut out = Vec::new();
-                    let chunk = &counts[chunk_range];
+                    let chunk = &counts[chunk_range.clone()];
                     let mut stats = Outcome::default();
                     let mut pack_offsets_to_id = None;
                     progress.init(Some(chunk.len()), gix_features::progress::count("objects"));
 
+                    if let Mode::DeltaCompression { window, depth } = mode {
+                        let out = delta::compress_chunk(
+                            &db,
+                            chunk,
+                            chunk_range.start,
+                            window as usize,
+                            depth as usize,
+                            buf,
+                            &mut stats,
+                            progress,
+                        )?;
+                        return Ok((chunk_id, out, stats));
+                    }
                     for count in chunk.iter() {
                         out.push(match count
                             .entry_pack_location
@@ -237,6 +263,164 @@ pub(crate) mod function {
     }
 }
 
+mod delta {
+    use std::collections::VecDeque;
+
+    use gix_features::{parallel, progress::Progress};
+
+    use super::{Error, Outcome};
+    use crate::data::{delta::Index, output};
+
+    /// The minimal amount of bytes a delta has to save to be worth it, similar to what git uses.
+    const MIN_SAVINGS: usize = 20;
+
+    /// The kind and size of an object by which to sort it, or `None` if it couldn't be found.
+    type SortKey = Option<(gix_object::Kind, std::cmp::Reverse<usize>)>;
+
+    /// Sort `counts` by object kind and by size, largest first, to make similar objects neighbours.
+    /// Objects that can't be found are placed last.
+    pub fn sort_by_kind_and_size<Find>(
+        counts: &mut Vec<output::Count>,
+        db: &Find,
+        chunk_size: usize,
+        thread_limit: Option<usize>,
+        progress: &mut impl Progress,
+    ) where
+        Find: crate::Find + Send + Clone,
+    {
+        progress.init(Some(counts.len()), gix_features::progress::count("objects"));
+        let start = std::time::Instant::now();
+        let mut keys: Vec<SortKey> = vec![None; counts.len()];
+        let shared_progress = parking_lot::Mutex::new(&mut *progress);
+        parallel::in_parallel_if(
+            || counts.len() > 4_000,
+            counts.chunks(chunk_size).zip(keys.chunks_mut(chunk_size)),
+            thread_limit,
+            |_n| Vec::<u8>::new(),
+            {
+                let db = db.clone();
+                let progress = &shared_progress;
+                move |(counts, keys): (&[output::Count], &mut [SortKey]), buf| {
+                    for (count, key) in counts.iter().zip(keys.iter_mut()) {
+                        *key = db
+                            .try_find(count.id, buf)
+                            .ok()
+                            .flatten()
+                            .map(|(obj, _location)| (obj.kind, std::cmp::Reverse(obj.data.len())));
+                    }
+                    progress.lock().inc_by(counts.len());
+                    Ok::<_, ()>(())
+                }
+            },
+            parallel::reduce::IdentityWithResult::<(), ()>::default(),
+        )
+        .expect("infallible - objects that can't be found are placed last");
+
+        let mut keyed: Vec<_> = keys.into_iter().zip(counts.drain(..)).collect();
+        keyed.sort_by(|(lhs, _), (rhs, _)| match (lhs, rhs) {
+            (Some(lhs), Some(rhs)) => lhs.cmp(rhs),
+            (lhs, rhs) => rhs.is_some().cmp(&lhs.is_some()),
+        });
+        counts.extend(keyed.into_iter().map(|(_, count)| count));
+        progress.show_throughput(start);
+    }
+
+    /// An object that may serve as base for the objects that follow it.
+    struct Candidate {
+        /// The index of the object within the chunk.
+        index: usize,
+        kind: gix_object::Kind,
+        /// The length of the delta chain that leads to this object.
+        depth: usize,
+        data: Index,
+    }
+
+    /// Produce entries for all objects in `chunk` which starts at `chunk_start` within all counts,
+    /// trying to store each of them as delta against the `window` objects before it, while keeping delta chains shorter than `max_depth`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn compress_chunk<Find>(
+        db: &Find,
+        chunk: &[output::Count],
+        chunk_start: usize,
+        window: usize,
+        max_depth: usize,
+        buf: &mut Vec<u8>,
+        stats: &mut Outcome,
+        progress: &mut impl Progress,
+    ) -> Result<Vec<output::Entry>, Error<Find::Error>>
+    where
+        Find: crate::Find,
+    {
+        let mut out = Vec::with_capacity(chunk.len());
+        let mut candidates = VecDeque::<Candidate>::with_capacity(window);
+        let mut delta_buf = Vec::new();
+        for (index, count) in chunk.iter().enumerate() {
+            let (kind, data) = match db.try_find(count.id, buf).map_err(Error::FindExisting)? {
+                Some((obj, _location)) => (obj.kind, obj.data.to_owned()),
+                None => {
+                    stats.missing_objects += 1;
+                    out.push(output::Entry::invalid());
+                    progress.inc();
+                    continue;
+                }
+            };
+            stats.decoded_and_recompressed_objects += 1;
+            if count.no_delta || window == 0 {
+                out.push(output::Entry::from_data(count, &gix_object::Data::new(kind, &data))?);
+                progress.inc();
+                continue;
+            }
+
+            // The index of the base object in the chunk, the depth of the delta chain leading to it and the delta itself.
+            let mut best: Option<(usize, usize, Vec<u8>)> = None;
+            for candidate in candidates.iter().rev() {
+                if candidate.kind != kind
+                    || candidate.depth >= max_depth
+                    || data.len() < candidate.data.base_size() / 32
+                {
+                    continue;
+                }
+                let (max_size, depth_of_best) = match &best {
+                    Some((_, base_depth, delta)) => (delta.len() - 1, base_depth + 1),
+                    None => (data.len().saturating_sub(MIN_SAVINGS) / 2, 1),
+                };
+                // Prefer bases with short delta chains by allowing less space for deltas against bases with long ones.
+                let max_size = max_size * (max_depth - candidate.depth) / (max_depth - depth_of_best + 1);
+                if max_size == 0 {
+                    continue;
+                }
+                if candidate.data.encode(&data, max_size, &mut delta_buf) {
+                    best = Some((candidate.index, candidate.depth, std::mem::take(&mut delta_buf)));
+                }
+            }
+
+            let depth = match best {
+                Some((base_index, base_depth, delta)) => {
+                    stats.delta_compressed_objects += 1;
+                    out.push(output::Entry::from_delta(count, chunk_start + base_index, &delta)?);
+                    delta_buf = delta;
+                    base_depth + 1
+                }
+                None => {
+                    out.push(output::Entry::from_data(count, &gix_object::Data::new(kind, &data))?);
+                    0
+                }
+            };
+            if candidates.len() == window {
+                candidates.pop_front();
+            }
+            candidates.push_back(Candidate {
+                index,
+                kind,
+                depth,
+                data: Index::new(data),
+            });
+            progress.inc();
+        }
+        Ok(out)
+    }
+}
+
 mod util {
     #[derive(Clone)]
     pub struct ChunkRanges {
@@ -328,6 +512,9 @@ mod types {
         pub objects_copied_from_pack: usize,
         /// The amount of objects that ref to their base as ref-delta, an indication for a thin back being created.
         pub ref_delta_objects: usize,
+        /// The amount of objects that were stored as delta against a similar object in the pack during
+        /// [delta compression][Mode::DeltaCompression].
+        pub delta_compressed_objects: usize,
     }
 
     impl Outcome {
@@ -338,12 +525,14 @@ mod types {
                 missing_objects,
                 objects_copied_from_pack,
                 ref_delta_objects,
+                delta_compressed_objects,
             }: Self,
         ) {
             self.decoded_and_recompressed_objects += decoded_objects;
             self.missing_objects += missing_objects;
             self.objects_copied_from_pack += objects_copied_from_pack;
             self.ref_delta_objects += ref_delta_objects;
+            self.delta_compressed_objects += delta_compressed_objects;
         }
     }
 
@@ -356,6 +545,18 @@ mod types {
         /// from existing pack compression and spending the smallest possible time on compressing unpacked objects at
         /// the cost of bandwidth.
         PackCopyAndBaseObjects,
+        /// Decode all objects and try to store each of them as delta against one of the `window` previous objects of the same kind,
+        /// after sorting them by kind and by size with the largest objects first. This produces the smallest packs and is
+        /// similar to what `git repack -f` does, at the cost of being much slower.
+        ///
+        /// Delta chains are no longer than `depth`, and objects are only compared to others within the same chunk of work.
+        /// [`Count::no_delta`][crate::data::output::Count::no_delta] is respected, and thin packs aren't created in this mode.
+        DeltaCompression {
+            /// The amount of previous objects to try as delta base for each object, like `pack.window`.
+            window: u32,
+            /// The maximum length of delta chains, like `pack.depth`.
+            depth: u32,
+        },
     }
 
     /// Configuration options for the pack generation functions provided in [`iter_from_counts()`][crate::data::output::entry::iter_from_counts()].
@@ -412,7 +613,8 @@ mod types {
     pub enum ProgressId {
         /// The amount of [`Count`][crate::data::output::Count] objects which are resolved to their pack location.
         ResolveCounts,
-        /// Layout pack entries for placement into a pack (by pack-id and by offset).
+        /// Layout pack entries for placement into a pack (by pack-id and by offset), or sort them by kind and size
+        /// for [delta compression][Mode::DeltaCompression].
         SortEntries,
     }
 
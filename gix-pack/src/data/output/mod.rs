@@ -14,6 +14,11 @@ pub struct Count {
     pub id: ObjectId,
     /// A way to locate a pack entry in the object database, only available if the object is in a pack.
     pub entry_pack_location: count::PackLocation,
+    /// If `true`, the object must not be stored as delta, nor serve as base for other deltas, when performing
+    /// [delta compression][entry::iter_from_counts::Mode::DeltaCompression].
+    ///
+    /// This is the case for blobs whose path has the `-delta` attribute set, and it's up to the caller to determine that.
+    pub no_delta: bool,
 }
 
 /// An entry to be written to a file.
@@ -174,6 +174,28 @@ impl index::File {
         ofs
     }
 
+    /// Return the index positions of all entries in the order in which they appear in the pack data file.
+    ///
+    /// This is what [reverse indices][index::rev::File] store, so it is read from the `.rev` file next to this index if it
+    /// belongs to the same pack, and computed by sorting all entries by offset otherwise.
+    pub fn pack_order(&self) -> Vec<EntryIndex> {
+        if let Some(order) = self.pack_order_from_reverse_index() {
+            return order;
+        }
+        let mut order: Vec<_> = (0..self.num_objects).collect();
+        order.sort_by_key(|idx| self.pack_offset_at_index(*idx));
+        order
+    }
+
+    fn pack_order_from_reverse_index(&self) -> Option<Vec<EntryIndex>> {
+        let rev = index::rev::File::at(self.path.with_extension("rev"), self.num_objects, self.object_hash).ok()?;
+        if rev.pack_checksum() != self.pack_checksum() {
+            return None;
+        }
+        let order: Vec<_> = rev.iter().collect();
+        index::rev::invert(&order).map(|_| order)
+    }
+
     #[inline]
     fn offset_crc32_v2(&self) -> usize {
         V2_HEADER_SIZE + self.num_objects as usize * self.hash_len
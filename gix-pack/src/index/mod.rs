@@ -86,7 +86,10 @@ pub enum Version {
 }
 
 impl Version {
-    /// The kind of hash to produce to be compatible to this kind of index
+    /// The kind of hash to produce by default to be compatible to this kind of index.
+    ///
+    /// Note that version 2 indices may also contain Sha256 hashes, which is why the actual kind of hash is always
+    /// the one of the repository.
     pub fn hash(&self) -> gix_hash::Kind {
         match self {
             Version::V1 | Version::V2 => gix_hash::Kind::Sha1,
@@ -141,6 +144,10 @@ pub mod init;
 pub(crate) mod access;
 pub use access::Entry;
 
+///
+pub mod mtimes;
+///
+pub mod rev;
 ///
 pub mod traverse;
 mod util;
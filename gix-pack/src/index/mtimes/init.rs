@@ -0,0 +1,69 @@
+use std::path::Path;
+
+use crate::index::mtimes::{File, Version, HEADER_LEN, SIGNATURE};
+
+/// Returned by [`mtimes::File::at()`][crate::index::mtimes::File::at()].
+#[derive(thiserror::Error, Debug)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not open object modification time file at '{path}'")]
+    Io {
+        source: std::io::Error,
+        path: std::path::PathBuf,
+    },
+    #[error("{message}")]
+    Corrupt { message: String },
+    #[error("Unsupported object modification time file version: {version}")]
+    UnsupportedVersion { version: u32 },
+    #[error("The object modification time file uses hash id {actual}, but {expected} was expected")]
+    ObjectHashMismatch { expected: u32, actual: u32 },
+}
+
+/// Instantiation
+impl File {
+    /// Open the object modification time file at the given `path` for a cruft pack with `num_objects` objects.
+    ///
+    /// The `object_hash` is used to validate the hash id stored in the file, and to determine the size of its checksums.
+    pub fn at(path: impl AsRef<Path>, num_objects: u32, object_hash: gix_hash::Kind) -> Result<File, Error> {
+        Self::at_inner(path.as_ref(), num_objects, object_hash)
+    }
+
+    fn at_inner(path: &Path, num_objects: u32, object_hash: gix_hash::Kind) -> Result<File, Error> {
+        let data = crate::mmap::read_only(path).map_err(|source| Error::Io {
+            source,
+            path: path.to_owned(),
+        })?;
+        let expected_len = HEADER_LEN + num_objects as usize * 4 + object_hash.len_in_bytes() * 2;
+        if data.len() != expected_len {
+            return Err(Error::Corrupt {
+                message: format!(
+                    "Object modification time file of size {} doesn't match the expected size of {expected_len} for {num_objects} objects",
+                    data.len()
+                ),
+            });
+        }
+        if &data[..4] != SIGNATURE {
+            return Err(Error::Corrupt {
+                message: "Object modification time file signature not found".into(),
+            });
+        }
+        let version = crate::read_u32(&data[4..8]);
+        if version != Version::V1 as u32 {
+            return Err(Error::UnsupportedVersion { version });
+        }
+        let hash_id = crate::read_u32(&data[8..12]);
+        if hash_id != object_hash as u32 {
+            return Err(Error::ObjectHashMismatch {
+                expected: object_hash as u32,
+                actual: hash_id,
+            });
+        }
+        Ok(File {
+            data,
+            path: path.to_owned(),
+            version: Version::V1,
+            num_objects,
+            object_hash,
+        })
+    }
+}
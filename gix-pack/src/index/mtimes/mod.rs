@@ -0,0 +1,87 @@
+//! Modification times of all objects in a _cruft pack_, typically stored in `pack-<hash>.mtimes` files.
+//!
+//! Cruft packs store unreachable objects which are kept until their modification time is past the grace period
+//! for pruning, and the modification times are listed in the order of objects in the pack index.
+use memmap2::Mmap;
+
+use crate::index::EntryIndex;
+
+/// Known object modification time file versions
+#[derive(Default, PartialEq, Eq, Ord, PartialOrd, Debug, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[allow(missing_docs)]
+pub enum Version {
+    #[default]
+    V1 = 1,
+}
+
+/// The signature at the beginning of each object modification time file.
+pub const SIGNATURE: &[u8] = b"MTME";
+
+const HEADER_LEN: usize = 4 /* signature */ + 4 /* version */ + 4 /* hash id */;
+
+/// A representation of a file with the modification times of all objects in a cruft pack.
+pub struct File {
+    data: Mmap,
+    path: std::path::PathBuf,
+    version: Version,
+    num_objects: u32,
+    object_hash: gix_hash::Kind,
+}
+
+/// Access
+impl File {
+    /// The version of the file.
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    /// The path of the opened file.
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+
+    /// The amount of objects in the pack.
+    pub fn num_objects(&self) -> u32 {
+        self.num_objects
+    }
+
+    /// The kind of hash used for the checksums.
+    pub fn object_hash(&self) -> gix_hash::Kind {
+        self.object_hash
+    }
+
+    /// Return the modification time in seconds since UNIX epoch of the object at `index` in the pack index.
+    ///
+    /// # Panics
+    ///
+    /// If `index` is out of bounds.
+    pub fn mtime_at_index(&self, index: EntryIndex) -> u32 {
+        assert!(index < self.num_objects, "index out of bounds");
+        let start = HEADER_LEN + index as usize * 4;
+        crate::read_u32(&self.data[start..][..4])
+    }
+
+    /// Return the modification times of all objects in the order of the pack index.
+    pub fn iter(&self) -> impl Iterator<Item = u32> + '_ {
+        self.data[HEADER_LEN..][..self.num_objects as usize * 4]
+            .chunks(4)
+            .map(crate::read_u32)
+    }
+
+    /// Return the checksum of the pack that this file belongs to.
+    pub fn pack_checksum(&self) -> gix_hash::ObjectId {
+        let start = HEADER_LEN + self.num_objects as usize * 4;
+        gix_hash::ObjectId::from(&self.data[start..][..self.object_hash.len_in_bytes()])
+    }
+
+    /// Return the checksum over the file itself.
+    pub fn checksum(&self) -> gix_hash::ObjectId {
+        gix_hash::ObjectId::from(&self.data[self.data.len() - self.object_hash.len_in_bytes()..])
+    }
+}
+
+///
+pub mod init;
+///
+pub mod write;
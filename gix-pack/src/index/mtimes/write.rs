@@ -0,0 +1,28 @@
+use std::io::Write;
+
+use crate::index::mtimes::{File, Version, SIGNATURE};
+
+/// Writing
+impl File {
+    /// Write the modification times in seconds since UNIX epoch of all objects in the order of the pack index as `mtimes`
+    /// to `out`, for the pack with `pack_checksum`, and return the checksum of the written file.
+    pub fn write_to(
+        mtimes: impl IntoIterator<Item = u32>,
+        pack_checksum: &gix_hash::oid,
+        out: impl std::io::Write,
+    ) -> std::io::Result<gix_hash::ObjectId> {
+        let object_hash = pack_checksum.kind();
+        let mut out = gix_features::hash::Write::new(out, object_hash);
+        out.write_all(SIGNATURE)?;
+        out.write_all(&(Version::V1 as u32).to_be_bytes())?;
+        out.write_all(&(object_hash as u32).to_be_bytes())?;
+        for mtime in mtimes {
+            out.write_all(&mtime.to_be_bytes())?;
+        }
+        out.write_all(pack_checksum.as_bytes())?;
+
+        let checksum: gix_hash::ObjectId = out.hash.digest();
+        out.inner.write_all(checksum.as_slice())?;
+        Ok(checksum)
+    }
+}
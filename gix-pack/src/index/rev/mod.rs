@@ -0,0 +1,102 @@
+//! Reverse indices, typically stored in `pack-<hash>.rev` files, list the index positions of all objects in the order
+//! in which they appear in the pack, to map pack offsets to index positions without sorting all offsets first.
+//!
+//! The same format is used for `multi-pack-index-<hash>.rev` files, which store the pseudo-pack order of a multi-pack-index.
+use memmap2::Mmap;
+
+use crate::index::EntryIndex;
+
+/// Known reverse index file versions
+#[derive(Default, PartialEq, Eq, Ord, PartialOrd, Debug, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[allow(missing_docs)]
+pub enum Version {
+    #[default]
+    V1 = 1,
+}
+
+/// The signature at the beginning of each reverse index file.
+pub const SIGNATURE: &[u8] = b"RIDX";
+
+const HEADER_LEN: usize = 4 /* signature */ + 4 /* version */ + 4 /* hash id */;
+
+/// A representation of a reverse index file, mapping positions in a pack to positions in its index.
+pub struct File {
+    data: Mmap,
+    path: std::path::PathBuf,
+    version: Version,
+    num_objects: u32,
+    object_hash: gix_hash::Kind,
+}
+
+/// Access
+impl File {
+    /// The version of the reverse index.
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    /// The path of the opened reverse index file.
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+
+    /// The amount of objects in the pack or multi-pack-index.
+    pub fn num_objects(&self) -> u32 {
+        self.num_objects
+    }
+
+    /// The kind of hash used for the checksums.
+    pub fn object_hash(&self) -> gix_hash::Kind {
+        self.object_hash
+    }
+
+    /// Return the position in the index of the object at `pack_position`, that is the `pack_position`-th object
+    /// when ordering all objects by their pack offset.
+    ///
+    /// # Panics
+    ///
+    /// If `pack_position` is out of bounds.
+    pub fn index_position_at(&self, pack_position: u32) -> EntryIndex {
+        assert!(pack_position < self.num_objects, "pack position out of bounds");
+        let start = HEADER_LEN + pack_position as usize * 4;
+        crate::read_u32(&self.data[start..][..4])
+    }
+
+    /// Return the index positions of all objects in the order in which they appear in the pack.
+    pub fn iter(&self) -> impl Iterator<Item = EntryIndex> + '_ {
+        self.data[HEADER_LEN..][..self.num_objects as usize * 4]
+            .chunks(4)
+            .map(crate::read_u32)
+    }
+
+    /// Return the checksum of the pack, or the multi-pack-index, that this reverse index belongs to.
+    pub fn pack_checksum(&self) -> gix_hash::ObjectId {
+        let start = HEADER_LEN + self.num_objects as usize * 4;
+        gix_hash::ObjectId::from(&self.data[start..][..self.object_hash.len_in_bytes()])
+    }
+
+    /// Return the checksum over the reverse index itself.
+    pub fn checksum(&self) -> gix_hash::ObjectId {
+        gix_hash::ObjectId::from(&self.data[self.data.len() - self.object_hash.len_in_bytes()..])
+    }
+}
+
+/// Return the inverse of `index_positions` in pack order, that is the pack position of each index position,
+/// or `None` if `index_positions` isn't a permutation of all index positions as it happens with corrupt reverse indices.
+pub(crate) fn invert(index_positions: &[EntryIndex]) -> Option<Vec<u32>> {
+    let mut pack_positions = vec![u32::MAX; index_positions.len()];
+    for (pack_position, index_position) in index_positions.iter().enumerate() {
+        let slot = pack_positions.get_mut(*index_position as usize)?;
+        if *slot != u32::MAX {
+            return None;
+        }
+        *slot = pack_position as u32;
+    }
+    Some(pack_positions)
+}
+
+///
+pub mod init;
+///
+pub mod write;
@@ -0,0 +1,33 @@
+use std::io::Write;
+
+use crate::index::{
+    rev::{File, Version, SIGNATURE},
+    EntryIndex,
+};
+
+/// Writing
+impl File {
+    /// Write a reverse index to `out` for the index positions of all objects in pack order as `index_positions`,
+    /// for the pack or multi-pack-index with `pack_checksum`, and return the checksum of the written file.
+    ///
+    /// For pack indices, the index positions can be obtained with [`index::File::pack_order()`][crate::index::File::pack_order()].
+    pub fn write_to(
+        index_positions: impl IntoIterator<Item = EntryIndex>,
+        pack_checksum: &gix_hash::oid,
+        out: impl std::io::Write,
+    ) -> std::io::Result<gix_hash::ObjectId> {
+        let object_hash = pack_checksum.kind();
+        let mut out = gix_features::hash::Write::new(out, object_hash);
+        out.write_all(SIGNATURE)?;
+        out.write_all(&(Version::V1 as u32).to_be_bytes())?;
+        out.write_all(&(object_hash as u32).to_be_bytes())?;
+        for index_position in index_positions {
+            out.write_all(&index_position.to_be_bytes())?;
+        }
+        out.write_all(pack_checksum.as_bytes())?;
+
+        let checksum: gix_hash::ObjectId = out.hash.digest();
+        out.inner.write_all(checksum.as_slice())?;
+        Ok(checksum)
+    }
+}
@@ -166,16 +166,20 @@ impl index::File {
         P: Progress,
         E: std::error::Error + Send + Sync + 'static,
     {
-        let pack_entry = pack.entry(index_entry.pack_offset);
+        let pack_entry = pack.entry(index_entry.pack_offset).map_err(|err| Error::PackDecode {
+            source: err.into(),
+            id: index_entry.oid,
+            offset: index_entry.pack_offset,
+        })?;
         let pack_entry_data_offset = pack_entry.data_offset;
         let entry_stats = pack
             .decode_entry(
                 pack_entry,
                 buf,
                 |id, _| {
-                    self.lookup(id).map(|index| {
-                        crate::data::decode::entry::ResolvedBase::InPack(pack.entry(self.pack_offset_at_index(index)))
-                    })
+                    self.lookup(id)
+                        .and_then(|index| pack.entry(self.pack_offset_at_index(index)).ok())
+                        .map(crate::data::decode::entry::ResolvedBase::InPack)
                 },
                 cache,
             )
@@ -208,7 +212,7 @@ fn process_entry<P, E>(
     decompressed: &[u8],
     progress: &mut P,
     index_entry: &crate::index::Entry,
-    pack_entry_crc32: impl FnOnce() -> u32,
+    pack_entry_crc32: impl FnOnce() -> std::io::Result<u32>,
     processor: &mut impl FnMut(gix_object::Kind, &[u8], &index::Entry, &mut P) -> Result<(), E>,
 ) -> Result<(), Error<E>>
 where
@@ -226,7 +230,11 @@ where
             });
         }
         if let Some(desired_crc32) = index_entry.crc32 {
-            let actual_crc32 = pack_entry_crc32();
+            let actual_crc32 = pack_entry_crc32().map_err(|err| Error::PackDecode {
+                source: err.into(),
+                id: index_entry.oid,
+                offset: index_entry.pack_offset,
+            })?;
             if actual_crc32 != desired_crc32 {
                 return Err(Error::Crc32Mismatch {
                     actual: actual_crc32,
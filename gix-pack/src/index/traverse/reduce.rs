@@ -43,12 +43,12 @@ where
 {
     pub fn from_progress(
         progress: OwnShared<Mutable<P>>,
-        pack_data_len_in_bytes: usize,
+        pack_data_len_in_bytes: u64,
         check: traverse::SafetyCheck,
         should_interrupt: &'a AtomicBool,
     ) -> Self {
         let stats = traverse::Statistics {
-            pack_size: pack_data_len_in_bytes as u64,
+            pack_size: pack_data_len_in_bytes,
             ..Default::default()
         };
         Reducer {
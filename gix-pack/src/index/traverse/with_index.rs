@@ -113,8 +113,13 @@ impl index::File {
                     self.object_hash,
                 )?;
                 let mut outcome = digest_statistics(tree.traverse(
-                    |slice, out| pack.entry_slice(slice).map(|entry| out.copy_from_slice(entry)),
-                    pack.pack_end() as u64,
+                    |slice, out| {
+                        pack.entry_slice(slice)
+                            .ok()
+                            .flatten()
+                            .map(|entry| out.copy_from_slice(&entry))
+                    },
+                    pack.pack_end(),
                     new_processor,
                     |data,
                      progress,
@@ -141,10 +146,11 @@ impl index::File {
                                 // TODO: Fix this - we overwrite the header of 'data' which also changes the computed entry size,
                                 // causing index and pack to seemingly mismatch. This is surprising, and should be done differently.
                                 // debug_assert_eq!(&data.index_entry.pack_offset, &pack_entry.pack_offset());
-                                gix_features::hash::crc32(
-                                    pack.entry_slice(data.index_entry.pack_offset..entry_end)
-                                        .expect("slice pointing into the pack (by now data is verified)"),
-                                )
+                                pack.entry_slice(data.index_entry.pack_offset..entry_end).map(|entry| {
+                                    gix_features::hash::crc32(
+                                        &entry.expect("slice pointing into the pack (by now data is verified)"),
+                                    )
+                                })
                             },
                             processor,
                         );
@@ -164,7 +170,7 @@ impl index::File {
                         object_hash: self.object_hash,
                     },
                 )?);
-                outcome.pack_size = pack.data_len() as u64;
+                outcome.pack_size = pack.data_len();
                 Ok(outcome)
             },
         );
@@ -22,4 +22,6 @@ pub enum Error {
     Tree(#[from] crate::cache::delta::Error),
     #[error(transparent)]
     TreeTraversal(#[from] crate::cache::delta::traverse::Error),
+    #[error("An object in the pack was crafted to cause a hash collision")]
+    CollisionAttack(#[source] gix_features::hash::try_digest::Error),
 }
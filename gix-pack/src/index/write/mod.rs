@@ -182,28 +182,34 @@ impl crate::index::File {
 
         let resolver = make_resolver()?;
         let sorted_pack_offsets_by_oid = {
-            let traverse::Outcome { roots, children } = tree.traverse(
-                resolver,
-                pack_entries_end,
-                || (),
-                |data,
-                 _progress,
-                 traverse::Context {
-                     entry,
-                     decompressed: bytes,
-                     ..
-                 }| {
-                    modify_base(data, entry, bytes, version.hash());
-                    Ok::<_, Error>(())
-                },
-                traverse::Options {
-                    object_progress: root_progress.add_child_with_id("Resolving", ProgressId::ResolveObjects.into()),
-                    size_progress: root_progress.add_child_with_id("Decoding", ProgressId::DecodedBytes.into()),
-                    thread_limit,
-                    should_interrupt,
-                    object_hash,
-                },
-            )?;
+            let traverse::Outcome { roots, children } = tree
+                .traverse(
+                    resolver,
+                    pack_entries_end,
+                    || (),
+                    |data,
+                     _progress,
+                     traverse::Context {
+                         entry,
+                         decompressed: bytes,
+                         ..
+                     }| modify_base(data, entry, bytes, object_hash),
+                    traverse::Options {
+                        object_progress: root_progress
+                            .add_child_with_id("Resolving", ProgressId::ResolveObjects.into()),
+                        size_progress: root_progress.add_child_with_id("Decoding", ProgressId::DecodedBytes.into()),
+                        thread_limit,
+                        should_interrupt,
+                        object_hash,
+                    },
+                )
+                .map_err(|err| match err {
+                    traverse::Error::Inspect(err) => match err.downcast::<gix_features::hash::try_digest::Error>() {
+                        Ok(err) => Error::CollisionAttack(*err),
+                        Err(err) => Error::TreeTraversal(traverse::Error::Inspect(err)),
+                    },
+                    err => Error::TreeTraversal(err),
+                })?;
             root_progress.inc();
 
             let mut items = roots;
@@ -223,7 +229,7 @@ impl crate::index::File {
                 let header = crate::data::header::encode(pack_version, 0);
                 let mut hasher = gix_features::hash::hasher(object_hash);
                 hasher.update(&header);
-                gix_hash::ObjectId::from(hasher.digest())
+                hasher.digest()
             }
             None => return Err(Error::IteratorInvariantTrailer),
         };
@@ -249,15 +255,25 @@ impl crate::index::File {
     }
 }
 
-fn modify_base(entry: &mut TreeEntry, pack_entry: &crate::data::Entry, decompressed: &[u8], hash: gix_hash::Kind) {
-    fn compute_hash(kind: gix_object::Kind, bytes: &[u8], object_hash: gix_hash::Kind) -> gix_hash::ObjectId {
+fn modify_base(
+    entry: &mut TreeEntry,
+    pack_entry: &crate::data::Entry,
+    decompressed: &[u8],
+    hash: gix_hash::Kind,
+) -> Result<(), gix_features::hash::try_digest::Error> {
+    fn compute_hash(
+        kind: gix_object::Kind,
+        bytes: &[u8],
+        object_hash: gix_hash::Kind,
+    ) -> Result<gix_hash::ObjectId, gix_features::hash::try_digest::Error> {
         let mut hasher = gix_features::hash::hasher(object_hash);
         hasher.update(&gix_object::encode::loose_header(kind, bytes.len()));
         hasher.update(bytes);
-        gix_hash::ObjectId::from(hasher.digest())
+        hasher.try_digest()
     }
 
     let object_kind = pack_entry.header.as_kind().expect("base object as source of iteration");
-    let id = compute_hash(object_kind, decompressed, hash);
+    let id = compute_hash(object_kind, decompressed, hash)?;
     entry.id = id;
+    Ok(())
 }
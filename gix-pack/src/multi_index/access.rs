@@ -129,6 +129,20 @@ impl File {
         (pack_index, pack_offset)
     }
 
+    /// Return the indices of all entries in the order they would have in a single pack containing all of them, or `None`
+    /// if this file doesn't have a reverse index chunk.
+    ///
+    /// This order is used by multi-pack reachability bitmaps.
+    pub fn pseudo_pack_order(&self) -> Option<Vec<EntryIndex>> {
+        let start = self.reverse_index_ofs?;
+        Some(
+            self.data[start..][..self.num_objects as usize * 4]
+                .chunks(4)
+                .map(crate::read_u32)
+                .collect(),
+        )
+    }
+
     /// Return an iterator over all entries within this file.
     pub fn iter(&self) -> impl Iterator<Item = Entry> + '_ {
         (0..self.num_objects).map(move |idx| {
@@ -274,3 +274,40 @@ pub mod large_offsets {
         8 * large_offsets as u64
     }
 }
+
+/// Information about the reverse index, which orders all objects as if they were in a single pack.
+pub mod reverse_index {
+    use std::ops::Range;
+
+    use crate::multi_index;
+
+    /// The id uniquely identifying the reverse index.
+    pub const ID: gix_chunk::Id = *b"RIDX";
+
+    /// Write the positions of all `sorted_entries` in pseudo-pack order, which orders them by pack and then by
+    /// offset within their pack.
+    pub(crate) fn write(
+        sorted_entries: &[multi_index::write::Entry],
+        mut out: impl std::io::Write,
+    ) -> std::io::Result<()> {
+        let mut order: Vec<_> = (0..sorted_entries.len() as u32).collect();
+        order.sort_by_key(|idx| {
+            let entry = &sorted_entries[*idx as usize];
+            (entry.pack_index, entry.pack_offset)
+        });
+        for idx in order {
+            out.write_all(&idx.to_be_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Return the amount of bytes needed to store the reverse index for `num_entries`.
+    pub(crate) fn storage_size(num_entries: usize) -> u64 {
+        4 * num_entries as u64
+    }
+
+    /// Returns true if the `offset` range seems to match the size required for `num_objects`.
+    pub fn is_valid(offset: &Range<usize>, num_objects: u32) -> bool {
+        offset.end - offset.start == num_objects as usize * 4
+    }
+}
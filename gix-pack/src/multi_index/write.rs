@@ -37,6 +37,8 @@ pub(crate) struct Entry {
 pub struct Options {
     /// The kind of hash to use for objects and to expect in the input files.
     pub object_hash: gix_hash::Kind,
+    /// If `true`, write the pseudo-pack order of all objects into a reverse index chunk, as needed for multi-pack-index bitmaps.
+    pub write_reverse_index: bool,
 }
 
 /// The result of [`multi_index::File::write_from_index_paths()`].
@@ -84,7 +86,10 @@ impl multi_index::File {
         out: impl std::io::Write,
         mut progress: P,
         should_interrupt: &AtomicBool,
-        Options { object_hash }: Options,
+        Options {
+            object_hash,
+            write_reverse_index,
+        }: Options,
     ) -> Result<Outcome<P>, Error>
     where
         P: Progress,
@@ -168,6 +173,13 @@ impl multi_index::File {
             );
         }
 
+        if write_reverse_index {
+            cf.plan_chunk(
+                multi_index::chunk::reverse_index::ID,
+                multi_index::chunk::reverse_index::storage_size(entries.len()),
+            );
+        }
+
         let mut write_progress = progress.add_child_with_id("Writing multi-index", ProgressId::BytesWritten.into());
         let write_start = Instant::now();
         write_progress.init(
@@ -206,6 +218,9 @@ impl multi_index::File {
                         num_large_offsets.expect("available if planned"),
                         &mut chunk_write,
                     )?,
+                    multi_index::chunk::reverse_index::ID => {
+                        multi_index::chunk::reverse_index::write(&entries, &mut chunk_write)?
+                    }
                     unknown => unreachable!("BUG: forgot to implement chunk {:?}", std::str::from_utf8(&unknown)),
                 }
                 progress.inc();
@@ -216,7 +231,7 @@ impl multi_index::File {
         }
 
         // write trailing checksum
-        let multi_index_checksum: gix_hash::ObjectId = out.inner.hash.digest().into();
+        let multi_index_checksum: gix_hash::ObjectId = out.inner.hash.digest();
         out.inner.inner.write_all(multi_index_checksum.as_slice())?;
         out.progress.show_throughput(write_start);
 
@@ -1,4 +1,4 @@
-use std::{path::Path, sync::atomic::AtomicBool};
+use std::{convert::TryFrom, path::Path, sync::atomic::AtomicBool};
 
 use gix_features::progress::Progress;
 
@@ -10,6 +10,8 @@ pub mod checksum {
     pub enum Error {
         #[error("Interrupted by user")]
         Interrupted,
+        #[error("Could not read the file to compute its checksum")]
+        Io(#[from] std::io::Error),
         #[error("index checksum mismatch: expected {expected}, got {actual}")]
         Mismatch {
             expected: gix_hash::ObjectId,
@@ -52,7 +54,7 @@ pub fn checksum_on_disk_or_mmap(
             hasher.update(&data[..data_len_without_trailer]);
             progress.inc_by(data_len_without_trailer);
             progress.show_throughput(start);
-            gix_hash::ObjectId::from(hasher.digest())
+            hasher.digest()
         }
     };
 
@@ -62,3 +64,38 @@ pub fn checksum_on_disk_or_mmap(
         Err(checksum::Error::Mismatch { actual, expected })
     }
 }
+
+/// Calculate the hash of the given kind over the first `data_len_without_trailer` bytes of the file at `data_path`,
+/// and compare it to `expected`. Use this if there is no mapped content to fall back on.
+pub fn checksum_on_disk(
+    data_path: &Path,
+    data_len_without_trailer: u64,
+    expected: gix_hash::ObjectId,
+    object_hash: gix_hash::Kind,
+    mut progress: impl Progress,
+    should_interrupt: &AtomicBool,
+) -> Result<gix_hash::ObjectId, checksum::Error> {
+    let data_len_without_trailer = usize::try_from(data_len_without_trailer).map_err(|_| {
+        std::io::Error::new(
+            std::io::ErrorKind::Other,
+            "pack data is too large to be hashed on this platform",
+        )
+    })?;
+    let actual = match gix_features::hash::bytes_of_file(
+        data_path,
+        data_len_without_trailer,
+        object_hash,
+        &mut progress,
+        should_interrupt,
+    ) {
+        Ok(id) => id,
+        Err(err) if err.kind() == std::io::ErrorKind::Interrupted => return Err(checksum::Error::Interrupted),
+        Err(err) => return Err(err.into()),
+    };
+
+    if actual == expected {
+        Ok(actual)
+    } else {
+        Err(checksum::Error::Mismatch { actual, expected })
+    }
+}
@@ -0,0 +1,63 @@
+#!/bin/bash
+set -eu -o pipefail
+
+function write_files() {
+  local base_dir=${1:?directory to write them into}
+  local num_files=${2:?amount of files to write}
+  local nonce=${3:?something to make files more unique}
+
+  mkdir -p "$base_dir"
+  for file_id in $(seq -w "$num_files"); do
+    seq "$file_id" > "$base_dir/$file_id"
+    echo "$nonce" >> "$base_dir/$file_id"
+  done
+}
+
+function make_history() {
+  local dirs=(. a b c a/a a/b a/c a/a/a)
+  local from=${1:?first round}
+  local to=${2:?last round}
+
+  for round in $(seq "$from" "$to"); do
+    dir_index=$(( round % ${#dirs[@]} ))
+    num_files=$(( (round + 1) * 3 ))
+    write_files "${dirs[$dir_index]}" $num_files "$round"
+    git add .
+    git commit -qm "$round $num_files"
+  done
+}
+
+git init -q single-pack
+(cd single-pack
+  git checkout -q -b main
+  make_history 1 10
+  git tag -m "a tag object" annotated
+  git checkout -q -b other HEAD~3
+  make_history 11 12
+  git checkout -q main
+
+  git -c pack.writeReverseIndex=true repack -adb
+)
+
+git init -q multi-pack
+(cd multi-pack
+  git checkout -q -b main
+  make_history 1 6
+  git repack -q
+  make_history 7 10
+  git tag -m "a tag object" annotated
+  git repack -q
+  git multi-pack-index write --bitmap
+)
+
+git init -q cruft
+(cd cruft
+  git checkout -q -b main
+  make_history 1 3
+  git checkout -q -b unreachable
+  make_history 4 5
+  git checkout -q main
+  git branch -q -D unreachable
+  git reflog expire --expire=all --all
+  git repack -q -d --cruft
+)
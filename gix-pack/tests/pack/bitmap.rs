@@ -0,0 +1,276 @@
+use bstr::ByteSlice;
+use std::{collections::BTreeSet, convert::Infallible, path::PathBuf, sync::atomic::AtomicBool};
+
+use gix_features::progress;
+use gix_hash::ObjectId;
+use gix_odb::pack::FindExt;
+use gix_pack::{bitmap, data::output::count};
+use gix_traverse::commit;
+
+fn repo(name: &str) -> crate::Result<PathBuf> {
+    Ok(crate::scripted_fixture_read_only("make_pack_gen_repo_bitmaps.sh")?.join(name))
+}
+
+fn db(repo: &std::path::Path) -> crate::Result<gix_odb::Handle> {
+    Ok(gix_odb::at(repo.join(".git/objects"))?)
+}
+
+fn ref_id(repo: &std::path::Path, name: &str) -> crate::Result<ObjectId> {
+    let hex = std::fs::read(repo.join(".git").join(name))?;
+    Ok(ObjectId::from_hex(hex.trim_end())?)
+}
+
+fn bitmap_path(repo: &std::path::Path) -> crate::Result<PathBuf> {
+    std::fs::read_dir(repo.join(".git/objects/pack"))?
+        .map(|entry| entry.map(|e| e.path()))
+        .find(|path| {
+            path.as_ref()
+                .map_or(false, |p| p.extension().map_or(false, |ext| ext == "bitmap"))
+        })
+        .expect("a bitmap was written")
+        .map_err(Into::into)
+}
+
+/// Return all objects reachable from the `commits` by traversal.
+fn reachable_by_traversal(db: &gix_odb::Handle, commits: &[ObjectId]) -> crate::Result<BTreeSet<ObjectId>> {
+    let commits = commit::Ancestors::new(
+        commits.iter().copied(),
+        commit::ancestors::State::default(),
+        |oid, buf| db.find_commit_iter(oid, buf).map(|t| t.0),
+    )
+    .collect::<Result<Vec<_>, _>>()?;
+    let (counts, _) = count::objects_unthreaded(
+        db,
+        commits.into_iter().map(Ok::<_, Infallible>),
+        progress::Discard,
+        &AtomicBool::new(false),
+        count::objects::ObjectExpansion::TreeContents,
+    )?;
+    Ok(counts.into_iter().map(|c| c.id).collect())
+}
+
+fn to_ids(reachability: &bitmap::Reachability, words: &[u64]) -> BTreeSet<ObjectId> {
+    (0..reachability.num_objects() as usize)
+        .filter(|bit| words.get(bit / 64).map_or(false, |w| w & (1 << (bit % 64)) != 0))
+        .map(|bit| reachability.id_at_bit(bit).to_owned())
+        .collect()
+}
+
+fn count_with_bitmap(
+    db: &gix_odb::Handle,
+    reachability: &bitmap::Reachability,
+    tips: &[ObjectId],
+) -> crate::Result<(BTreeSet<ObjectId>, count::objects::Outcome)> {
+    let (counts, outcome) = count::objects_with_bitmap(
+        db,
+        reachability,
+        tips.iter().copied().map(Ok::<_, Infallible>),
+        progress::Discard,
+        &AtomicBool::new(false),
+    )?;
+    let ids: BTreeSet<_> = counts.iter().map(|c| c.id).collect();
+    assert_eq!(ids.len(), counts.len(), "there are no duplicates");
+    Ok((ids, outcome))
+}
+
+/// Remove trailing zero words, as git only stores bits up to the last one that is set.
+fn trimmed(mut words: Vec<u64>) -> Vec<u64> {
+    while words.last() == Some(&0) {
+        words.pop();
+    }
+    words
+}
+
+fn assert_type_bitmaps_cover_all_objects(file: &bitmap::File, num_objects: u32) {
+    let mut seen = vec![false; num_objects as usize];
+    for kind in [
+        gix_object::Kind::Commit,
+        gix_object::Kind::Tree,
+        gix_object::Kind::Blob,
+        gix_object::Kind::Tag,
+    ] {
+        file.objects_of_kind(kind).for_each_set_bit(|bit| {
+            assert!(!seen[bit], "each object has exactly one kind");
+            seen[bit] = true;
+            Some(())
+        });
+    }
+    assert!(seen.into_iter().all(|seen| seen), "each object has a kind");
+}
+
+mod pack {
+    use gix_pack::bitmap;
+
+    use super::{
+        assert_type_bitmaps_cover_all_objects, bitmap_path, count_with_bitmap, db, reachable_by_traversal, ref_id,
+        repo, to_ids, trimmed,
+    };
+
+    #[test]
+    fn read_and_count() -> crate::Result {
+        let repo = repo("single-pack")?;
+        let db = db(&repo)?;
+        let path = bitmap_path(&repo)?;
+        let reachability = bitmap::Reachability::at(&path, gix_hash::Kind::Sha1)?;
+        let file = reachability.bitmap();
+        assert_eq!(file.version(), bitmap::Version::V1);
+        assert_ne!(file.flags() & bitmap::flags::FULL_DAG, 0);
+        assert!(file.num_bitmaps() > 0, "git selected commits for bitmaps");
+        assert_type_bitmaps_cover_all_objects(file, reachability.num_objects());
+
+        let main = ref_id(&repo, "refs/heads/main")?;
+        let other = ref_id(&repo, "refs/heads/other")?;
+        let tag = ref_id(&repo, "refs/tags/annotated")?;
+
+        let bits = reachability
+            .bitmap_of_commit(main)
+            .expect("the tip of main has a bitmap");
+        assert_eq!(to_ids(&reachability, &bits), reachable_by_traversal(&db, &[main])?);
+
+        let (ids, outcome) = count_with_bitmap(&db, &reachability, &[main, other])?;
+        assert_eq!(ids, reachable_by_traversal(&db, &[main, other])?);
+        assert_eq!(outcome.input_objects, 2);
+        assert_eq!(outcome.total_objects, ids.len());
+
+        let (ids, _) = count_with_bitmap(&db, &reachability, &[tag])?;
+        let mut expected = reachable_by_traversal(&db, &[main])?;
+        expected.insert(tag);
+        assert_eq!(ids, expected, "tags are followed to their target");
+        Ok(())
+    }
+
+    #[test]
+    fn write_and_read_back() -> crate::Result {
+        let repo = repo("single-pack")?;
+        let db = db(&repo)?;
+        let git_reachability = bitmap::Reachability::at(bitmap_path(&repo)?, gix_hash::Kind::Sha1)?;
+        let index_path = bitmap_path(&repo)?.with_extension("idx");
+        let index = gix_pack::index::File::at(&index_path, gix_hash::Kind::Sha1)?;
+
+        let main = ref_id(&repo, "refs/heads/main")?;
+        let other = ref_id(&repo, "refs/heads/other")?;
+        let mut buf = Vec::new();
+        let outcome = bitmap::File::write_from_index(
+            &index,
+            &db,
+            [main, other],
+            &mut buf,
+            gix_features::progress::Discard,
+            &std::sync::atomic::AtomicBool::new(false),
+        )?;
+        assert_eq!(
+            outcome.num_bitmaps, 12,
+            "there are so few commits in the pack that all of them are selected"
+        );
+
+        let tmp = tempfile::TempDir::new()?;
+        let written_path = tmp.path().join("pack.bitmap");
+        std::fs::write(&written_path, &buf)?;
+        let file = bitmap::File::at(&written_path, gix_hash::Kind::Sha1)?;
+        assert_eq!(file.file_checksum(), outcome.bitmap_checksum);
+        assert_eq!(file.num_bitmaps() as usize, outcome.num_bitmaps);
+        assert_eq!(file.checksum(), index.pack_checksum());
+        assert_type_bitmaps_cover_all_objects(&file, index.num_objects());
+        for kind in [
+            gix_object::Kind::Commit,
+            gix_object::Kind::Tree,
+            gix_object::Kind::Blob,
+            gix_object::Kind::Tag,
+        ] {
+            assert_eq!(
+                trimmed(file.objects_of_kind(kind).to_words()),
+                trimmed(git_reachability.bitmap().objects_of_kind(kind).to_words()),
+                "type bitmaps are the same as the ones git wrote"
+            );
+        }
+
+        let reachability = bitmap::Reachability::from_index(file, index)?;
+        for index_position in reachability.bitmap().index_positions().collect::<Vec<_>>() {
+            let id = gix_pack::index::File::at(&index_path, gix_hash::Kind::Sha1)?
+                .oid_at_index(index_position)
+                .to_owned();
+            let bits = reachability
+                .bitmap_of_commit(id)
+                .expect("selected commits have a bitmap");
+            assert_eq!(to_ids(&reachability, &bits), reachable_by_traversal(&db, &[id])?);
+            if let Some(git_bits) = git_reachability.bitmap_of_commit(id) {
+                assert_eq!(
+                    trimmed(bits),
+                    trimmed(git_bits),
+                    "bitmaps are the same as the ones written by git"
+                );
+            }
+        }
+
+        let (ids, _) = count_with_bitmap(&db, &reachability, &[main, other])?;
+        assert_eq!(ids, reachable_by_traversal(&db, &[main, other])?);
+        Ok(())
+    }
+
+    #[test]
+    fn reverse_indices_which_are_no_permutation_are_rejected() -> crate::Result {
+        let repo = repo("single-pack")?;
+        let tmp = tempfile::TempDir::new()?;
+        let path = tmp.path().join(bitmap_path(&repo)?.file_name().expect("file name"));
+        for extension in ["bitmap", "idx", "rev"] {
+            std::fs::copy(
+                bitmap_path(&repo)?.with_extension(extension),
+                path.with_extension(extension),
+            )?;
+        }
+        let rev_path = path.with_extension("rev");
+        let original = std::fs::read(&rev_path)?;
+        let first_position = 12;
+
+        for (replacement, kind) in [
+            (original[first_position + 4..][..4].to_vec(), "a duplicate"),
+            (u32::MAX.to_be_bytes().to_vec(), "out of bounds"),
+        ] {
+            let mut rev = original.clone();
+            rev[first_position..][..4].copy_from_slice(&replacement);
+            std::fs::write(&rev_path, rev)?;
+            assert!(
+                matches!(
+                    bitmap::Reachability::at(&path, gix_hash::Kind::Sha1),
+                    Err(bitmap::reachability::Error::InvalidPackOrder { .. })
+                ),
+                "an index position that is {} is detected",
+                kind
+            );
+        }
+        Ok(())
+    }
+}
+
+mod multi_pack {
+    use gix_pack::bitmap;
+
+    use super::{
+        assert_type_bitmaps_cover_all_objects, bitmap_path, count_with_bitmap, db, reachable_by_traversal, ref_id,
+        repo, to_ids,
+    };
+
+    #[test]
+    fn read_and_count() -> crate::Result {
+        let repo = repo("multi-pack")?;
+        let db = db(&repo)?;
+        let path = bitmap_path(&repo)?;
+        assert!(path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .map_or(false, |n| n.starts_with("multi-pack-index-")));
+        let reachability = bitmap::Reachability::at(&path, gix_hash::Kind::Sha1)?;
+        assert_type_bitmaps_cover_all_objects(reachability.bitmap(), reachability.num_objects());
+
+        let main = ref_id(&repo, "refs/heads/main")?;
+        let bits = reachability
+            .bitmap_of_commit(main)
+            .expect("the tip of main has a bitmap");
+        assert_eq!(to_ids(&reachability, &bits), reachable_by_traversal(&db, &[main])?);
+
+        let (ids, outcome) = count_with_bitmap(&db, &reachability, &[main])?;
+        assert_eq!(ids, reachable_by_traversal(&db, &[main])?);
+        assert_eq!(outcome.decoded_objects, 0, "everything was answered by the bitmap");
+        Ok(())
+    }
+}
@@ -48,7 +48,7 @@ mod locate {
             b"GitPython is a python library used to interact with Git repositories.\n\nHi there\n".as_bstr()
         );
         assert_eq!(obj.kind, Kind::Blob);
-        let object = obj.decode()?;
+        let object = obj.decode(gix_hash::Kind::Sha1)?;
         assert_eq!(object.kind(), Kind::Blob);
         assert_eq!(object.as_blob().expect("blob").data, obj.data);
         Ok(())
@@ -60,7 +60,7 @@ mod locate {
         let obj = locate("e90926b07092bccb7bf7da445fae6ffdfacf3eae", &mut out);
 
         assert_eq!(obj.kind, Kind::Tree);
-        assert_eq!(obj.decode()?.kind(), Kind::Tree);
+        assert_eq!(obj.decode(gix_hash::Kind::Sha1)?.kind(), Kind::Tree);
         Ok(())
     }
 
@@ -70,7 +70,7 @@ mod locate {
         let obj = locate("779c5451ba9fe210ffd1f55db202e55f51acecac", &mut out);
 
         assert_eq!(obj.kind, Kind::Commit);
-        assert_eq!(obj.decode()?.kind(), Kind::Commit);
+        assert_eq!(obj.decode(gix_hash::Kind::Sha1)?.kind(), Kind::Commit);
         Ok(())
     }
 }
@@ -98,6 +98,7 @@ mod write_to_directory {
             pack_version: pack::data::Version::V2,
             index_path: None,
             data_path: None,
+            reverse_index_path: None,
             keep_path: None,
             object_hash: gix_hash::Kind::Sha1,
         })
@@ -105,7 +106,7 @@ mod write_to_directory {
 
     #[test]
     fn without_providing_one() -> Result<(), Box<dyn std::error::Error>> {
-        let res = write_pack(None::<&Path>, SMALL_PACK)?;
+        let res = write_pack(None::<&Path>, SMALL_PACK, false)?;
         assert_eq!(res, expected_outcome()?);
         assert_eq!(
             res.index.index_hash,
@@ -118,7 +119,7 @@ mod write_to_directory {
     #[test]
     fn given_a_directory() -> Result<(), Box<dyn std::error::Error>> {
         let dir = TempDir::new()?;
-        let mut res = write_pack(Some(&dir), SMALL_PACK)?;
+        let mut res = write_pack(Some(&dir), SMALL_PACK, false)?;
         let (index_path, data_path, keep_path) = (res.index_path.take(), res.data_path.take(), res.keep_path.take());
         assert_eq!(res, expected_outcome()?);
         let mut sorted_entries = fs::read_dir(&dir)?.filter_map(Result::ok).collect::<Vec<_>>();
@@ -142,6 +143,28 @@ mod write_to_directory {
         Ok(())
     }
 
+    #[test]
+    fn given_a_directory_with_reverse_index() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = TempDir::new()?;
+        let res = write_pack(Some(&dir), SMALL_PACK, true)?;
+        let rev_path = res.reverse_index_path.clone().expect("reverse index was written");
+        assert_eq!(
+            rev_path,
+            dir.path().join(format!("pack-{}.rev", res.index.data_hash.to_hex()))
+        );
+        assert_eq!(
+            fs::read_dir(&dir)?.count(),
+            4,
+            "a pack, its index, its reverse index and the keep file"
+        );
+
+        let index = pack::index::File::at(res.index_path.as_ref().expect("present"), gix_hash::Kind::Sha1)?;
+        let rev = pack::index::rev::File::at(&rev_path, index.num_objects(), gix_hash::Kind::Sha1)?;
+        assert_eq!(rev.pack_checksum(), res.index.data_hash);
+        assert_eq!(rev.iter().collect::<Vec<_>>(), index.pack_order());
+        Ok(())
+    }
+
     fn file_name(entry: &fs::DirEntry) -> String {
         entry.path().file_name().unwrap().to_str().unwrap().to_owned()
     }
@@ -149,6 +172,7 @@ mod write_to_directory {
     fn write_pack(
         directory: Option<impl AsRef<Path>>,
         pack_file: &str,
+        write_reverse_index: bool,
     ) -> Result<pack::bundle::write::Outcome, Box<dyn std::error::Error>> {
         let pack_file = fs::File::open(fixture_path(pack_file))?;
         static SHOULD_INTERRUPT: AtomicBool = AtomicBool::new(false);
@@ -164,6 +188,7 @@ mod write_to_directory {
                 iteration_mode: pack::data::input::Mode::Verify,
                 index_version: pack::index::Version::V2,
                 object_hash: gix_hash::Kind::Sha1,
+                write_reverse_index,
             },
         )
         .map_err(Into::into)
@@ -6,6 +6,19 @@ fn pack_at(at: &str) -> pack::data::File {
     pack::data::File::at(fixture_path(at).as_path(), gix_hash::Kind::Sha1).expect("valid pack file")
 }
 
+/// Call `f` with the pack at `at` for each way of accessing it, assert all results are equal and return it.
+fn with_each_access<T: PartialEq + std::fmt::Debug>(at: &str, f: impl Fn(pack::data::File) -> T) -> T {
+    let mmap = f(pack_at(at));
+    let read = f(pack::data::File::at_with_access(
+        fixture_path(at).as_path(),
+        gix_hash::Kind::Sha1,
+        pack::data::Access::Read,
+    )
+    .expect("valid pack file"));
+    assert_eq!(read, mmap, "both ways of accessing pack data yield the same result");
+    mmap
+}
+
 mod method {
     use std::sync::atomic::AtomicBool;
 
@@ -32,6 +45,44 @@ mod method {
         Ok(())
     }
 
+    #[test]
+    fn access() -> Result<(), Box<dyn std::error::Error>> {
+        let p = pack_at(SMALL_PACK);
+        assert_eq!(
+            p.access(),
+            gix_pack::data::Access::MemoryMap,
+            "memory-mapping is the default"
+        );
+        let read = gix_pack::data::File::at_with_access(
+            crate::fixture_path(SMALL_PACK),
+            gix_hash::Kind::Sha1,
+            gix_pack::data::Access::Read,
+        )?;
+        assert_eq!(read.access(), gix_pack::data::Access::Read);
+        assert_eq!(read.data_len(), p.data_len());
+        assert_eq!(read.checksum(), p.checksum());
+        assert_eq!(
+            read.verify_checksum(progress::Discard, &AtomicBool::new(false))?,
+            p.checksum()
+        );
+
+        let entry_range = 1968..2097;
+        assert_eq!(
+            read.entry_slice(entry_range.clone())?,
+            p.entry_slice(entry_range.clone())?
+        );
+        assert_eq!(
+            read.entry_crc32(entry_range.start, 129)?,
+            p.entry_crc32(entry_range.start, 129)?
+        );
+        assert_eq!(
+            read.entry_slice(0..p.data_len() + 1)?,
+            None,
+            "out of bounds ranges yield nothing"
+        );
+        Ok(())
+    }
+
     #[test]
     fn iter() -> Result<(), Box<dyn std::error::Error>> {
         let pack = pack_at(SMALL_PACK);
@@ -48,7 +99,7 @@ mod decode_entry {
 
     use crate::{
         fixture_path, fixup,
-        pack::{data::file::pack_at, SMALL_PACK},
+        pack::{data::file::with_each_access, SMALL_PACK},
     };
 
     fn content_of(path: &str) -> Vec<u8> {
@@ -101,18 +152,21 @@ mod decode_entry {
             panic!("should not want to resolve an id here")
         }
 
-        let p = pack_at(SMALL_PACK);
-        let entry = p.entry(offset);
-        let mut buf = Vec::new();
-        p.decode_entry(entry, &mut buf, resolve_with_panic, &mut cache::Never)
-            .expect("valid offset provides valid entry");
-        buf
+        with_each_access(SMALL_PACK, |p| {
+            let entry = p.entry(offset).expect("readable");
+            let mut buf = Vec::new();
+            let outcome = p
+                .decode_entry(entry, &mut buf, resolve_with_panic, &mut cache::Never)
+                .expect("valid offset provides valid entry");
+            (buf, outcome)
+        })
+        .0
     }
 }
 
 /// All hardcoded offsets are obtained via `git pack-verify --verbose  tests/fixtures/packs/pack-a2bf8e71d8c18879e499335762dd95119d93d9f1.idx`
 mod resolve_header {
-    use crate::pack::{data::file::pack_at, SMALL_PACK};
+    use crate::pack::{data::file::with_each_access, SMALL_PACK};
 
     #[test]
     fn commit() {
@@ -151,17 +205,18 @@ mod resolve_header {
             panic!("should not want to resolve an id here")
         }
 
-        let p = pack_at(SMALL_PACK);
-        let entry = p.entry(offset);
-        p.decode_header(entry, resolve_with_panic)
-            .expect("valid offset provides valid entry")
+        with_each_access(SMALL_PACK, |p| {
+            let entry = p.entry(offset).expect("readable");
+            p.decode_header(entry, resolve_with_panic)
+                .expect("valid offset provides valid entry")
+        })
     }
 }
 
 mod decompress_entry {
     use gix_object::bstr::ByteSlice;
 
-    use crate::pack::{data::file::pack_at, SMALL_PACK};
+    use crate::pack::{data::file::with_each_access, SMALL_PACK};
 
     #[test]
     fn commit() {
@@ -200,16 +255,17 @@ mod decompress_entry {
     }
 
     fn decompress_entry_at_offset(offset: u64) -> Vec<u8> {
-        let p = pack_at(SMALL_PACK);
-        let entry = p.entry(offset);
+        with_each_access(SMALL_PACK, |p| {
+            let entry = p.entry(offset).expect("readable");
 
-        let size = entry.decompressed_size as usize;
-        let mut buf = Vec::with_capacity(size);
-        buf.resize(size, 0);
+            let size = entry.decompressed_size as usize;
+            let mut buf = Vec::with_capacity(size);
+            buf.resize(size, 0);
 
-        p.decompress_entry(&entry, &mut buf).expect("valid offset");
+            p.decompress_entry(&entry, &mut buf).expect("valid offset");
 
-        buf.resize(entry.decompressed_size as usize, 0);
-        buf
+            buf.resize(entry.decompressed_size as usize, 0);
+            buf
+        })
     }
 }
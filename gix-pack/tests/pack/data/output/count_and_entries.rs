@@ -122,6 +122,7 @@ fn traversals() -> crate::Result {
                     missing_objects: 0,
                     objects_copied_from_pack: 16,
                     ref_delta_objects: 0,
+                    delta_compressed_objects: 0,
                 },
                 hex_to_id("b920bbb055e1efb9080592a409d3975738b6efb3"),
                 None,
@@ -155,6 +156,7 @@ fn traversals() -> crate::Result {
                     missing_objects: 0,
                     objects_copied_from_pack: 103,
                     ref_delta_objects: 74,
+                    delta_compressed_objects: 0,
                 },
                 hex_to_id("25114bd8820b393c402cd53ad8ec7f6a84bb0633"),
                 Some(hex_to_id("29ab9797aff1ca826afb699680356695d19c5acb")),
@@ -188,6 +190,7 @@ fn traversals() -> crate::Result {
                     missing_objects: 0,
                     objects_copied_from_pack: 29,
                     ref_delta_objects: 0,
+                    delta_compressed_objects: 0,
                 },
                 hex_to_id("d83d42128e40957c5174920189a0390b5a70f446"),
                 None,
@@ -209,6 +212,7 @@ fn traversals() -> crate::Result {
                     missing_objects: 0,
                     objects_copied_from_pack: 868,
                     ref_delta_objects: 0,
+                    delta_compressed_objects: 0,
                 },
                 hex_to_id("542ad1d1c7c762ea4e36907570ff9e4b5b7dde1b"),
                 None,
@@ -230,6 +234,7 @@ fn traversals() -> crate::Result {
                     missing_objects: 0,
                     objects_copied_from_pack: 868,
                     ref_delta_objects: 0,
+                    delta_compressed_objects: 0,
                 },
                 hex_to_id("542ad1d1c7c762ea4e36907570ff9e4b5b7dde1b"),
                 None,
@@ -332,6 +337,93 @@ fn empty_pack_is_allowed() {
     .unwrap();
 }
 
+#[test]
+fn delta_compression() -> crate::Result {
+    let db = db(DbKind::DeterministicGeneratedContent)?;
+    let head = hex_to_id("dfcb5e39ac6eb30179808bbab721e8a28ce1b52e");
+    let commits = commit::Ancestors::new(Some(head), commit::ancestors::State::default(), {
+        let db = db.clone();
+        move |oid, buf| db.find_commit_iter(oid, buf).map(|t| t.0)
+    })
+    .map(Result::unwrap);
+    let (counts, _stats) = output::count::objects(
+        db.clone(),
+        commits.map(Ok::<_, Infallible>),
+        progress::Discard,
+        &AtomicBool::new(false),
+        count::objects::Options {
+            input_object_expansion: count::objects::ObjectExpansion::TreeContents,
+            thread_limit: Some(1),
+            ..Default::default()
+        },
+    )?;
+
+    let entries_of = |counts: Vec<output::Count>, mode| -> crate::Result<_> {
+        let mut entries_iter = output::entry::iter_from_counts(
+            counts,
+            db.clone(),
+            progress::Discard,
+            output::entry::iter_from_counts::Options {
+                mode,
+                thread_limit: Some(1),
+                ..Default::default()
+            },
+        );
+        let entries: Vec<_> = InOrderIter::from(entries_iter.by_ref())
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .flatten()
+            .collect();
+        Ok((entries, entries_iter.finalize()?))
+    };
+    let compressed_size = |entries: &[output::Entry]| entries.iter().map(|e| e.compressed_data.len()).sum::<usize>();
+
+    let mode = output::entry::iter_from_counts::Mode::DeltaCompression { window: 10, depth: 50 };
+    let (entries, stats) = entries_of(counts.clone(), mode)?;
+    assert_eq!(entries.len(), counts.len());
+    assert_eq!(stats.decoded_and_recompressed_objects, counts.len());
+    assert_eq!(stats.objects_copied_from_pack, 0);
+    assert!(
+        stats.delta_compressed_objects > counts.len() / 2,
+        "most objects in this repository are similar to each other"
+    );
+    for (index, entry) in entries.iter().enumerate() {
+        if let entry::Kind::DeltaRef { object_index } = entry.kind {
+            assert!(object_index < index, "bases are always written before their deltas");
+        }
+    }
+
+    let (copied_entries, _stats) = entries_of(
+        counts.clone(),
+        output::entry::iter_from_counts::Mode::PackCopyAndBaseObjects,
+    )?;
+    assert!(
+        compressed_size(&entries) < compressed_size(&copied_entries),
+        "the delta search finds at least as good deltas as the ones in the original pack"
+    );
+    write_and_verify(
+        db.clone(),
+        entries,
+        hex_to_id("304cf05425cb957c9871212534138e5b760e8bbc"),
+        None,
+    )?;
+
+    let no_delta_counts = counts
+        .into_iter()
+        .map(|mut count| {
+            count.no_delta = true;
+            count
+        })
+        .collect();
+    let (entries, stats) = entries_of(no_delta_counts, mode)?;
+    assert_eq!(
+        stats.delta_compressed_objects, 0,
+        "objects marked as such are never deltified"
+    );
+    assert!(entries.iter().all(|e| matches!(e.kind, entry::Kind::Base(_))));
+    Ok(())
+}
+
 fn write_and_verify(
     db: gix_odb::HandleArc,
     entries: Vec<output::Entry>,
@@ -412,7 +412,7 @@ fn pack_lookup() -> Result<(), Box<dyn std::error::Error>> {
         let sorted_offsets = idx.sorted_offsets();
         assert_eq!(num_objects, sorted_offsets.len());
         for idx_entry in idx.iter() {
-            let pack_entry = pack.entry(idx_entry.pack_offset);
+            let pack_entry = pack.entry(idx_entry.pack_offset)?;
             assert_ne!(pack_entry.data_offset, idx_entry.pack_offset);
             assert!(sorted_offsets.binary_search(&idx_entry.pack_offset).is_ok());
         }
@@ -425,7 +425,7 @@ fn pack_lookup() -> Result<(), Box<dyn std::error::Error>> {
 
             let mut buf = Vec::new();
             buf.resize(entry.decompressed_size as usize, 0);
-            let pack_entry = pack.entry(offset_from_index);
+            let pack_entry = pack.entry(offset_from_index)?;
             assert_eq!(
                 pack_entry.pack_offset(),
                 entry.pack_offset,
@@ -444,7 +444,7 @@ fn pack_lookup() -> Result<(), Box<dyn std::error::Error>> {
                 .expect("correct offset")
                 + 1;
             let next_offset = if next_offset_index == sorted_offsets.len() {
-                (pack.data_len() - SHA1_SIZE) as u64
+                pack.data_len() - SHA1_SIZE as u64
             } else {
                 sorted_offsets[next_offset_index]
             };
@@ -504,3 +504,180 @@ fn iter() -> Result<(), Box<dyn std::error::Error>> {
     }
     Ok(())
 }
+
+mod rev {
+    use gix_odb::pack::index;
+
+    use crate::{
+        fixture_path,
+        pack::{INDEX_V1, INDEX_V2, SMALL_PACK_INDEX},
+    };
+
+    #[test]
+    fn write_and_read() -> crate::Result {
+        let dir = tempfile::TempDir::new()?;
+        for path in [INDEX_V1, INDEX_V2, SMALL_PACK_INDEX] {
+            let idx = index::File::at(fixture_path(path), gix_hash::Kind::Sha1)?;
+            let order = idx.pack_order();
+            assert_eq!(
+                order
+                    .iter()
+                    .map(|index_position| idx.pack_offset_at_index(*index_position))
+                    .collect::<Vec<_>>(),
+                idx.sorted_offsets(),
+                "the pack order is the order of offsets"
+            );
+
+            let rev_path = dir.path().join("pack.rev");
+            let checksum = index::rev::File::write_to(
+                order.iter().copied(),
+                &idx.pack_checksum(),
+                std::fs::File::create(&rev_path)?,
+            )?;
+            let rev = index::rev::File::at(&rev_path, idx.num_objects(), gix_hash::Kind::Sha1)?;
+            assert_eq!(rev.version(), index::rev::Version::V1);
+            assert_eq!(rev.checksum(), checksum);
+            assert_eq!(rev.pack_checksum(), idx.pack_checksum());
+            assert_eq!(rev.iter().collect::<Vec<_>>(), order);
+            assert_eq!(rev.index_position_at(0), order[0]);
+
+            assert!(
+                matches!(
+                    index::rev::File::at(&rev_path, idx.num_objects() + 1, gix_hash::Kind::Sha1),
+                    Err(index::rev::init::Error::Corrupt { .. })
+                ),
+                "the amount of objects must match"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn written_like_git() -> crate::Result {
+        let pack_dir =
+            crate::scripted_fixture_read_only("make_pack_gen_repo_bitmaps.sh")?.join("single-pack/.git/objects/pack");
+        let rev_path = std::fs::read_dir(&pack_dir)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .find(|p| p.extension().map_or(false, |ext| ext == "rev"))
+            .expect("git wrote a reverse index");
+        let idx = index::File::at(rev_path.with_extension("idx"), gix_hash::Kind::Sha1)?;
+        let rev = index::rev::File::at(&rev_path, idx.num_objects(), gix_hash::Kind::Sha1)?;
+        let order = sorted_by_offset(&idx);
+        assert_eq!(rev.iter().collect::<Vec<_>>(), order);
+
+        let mut buf = Vec::new();
+        index::rev::File::write_to(order, &idx.pack_checksum(), &mut buf)?;
+        assert_eq!(buf, std::fs::read(&rev_path)?, "we write exactly what git writes");
+        Ok(())
+    }
+
+    #[test]
+    fn pack_order_is_read_from_a_valid_reverse_index() -> crate::Result {
+        let pack_dir =
+            crate::scripted_fixture_read_only("make_pack_gen_repo_bitmaps.sh")?.join("single-pack/.git/objects/pack");
+        let idx_path = std::fs::read_dir(&pack_dir)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .find(|p| p.extension().map_or(false, |ext| ext == "idx"))
+            .expect("git wrote an index");
+        let dir = tempfile::TempDir::new()?;
+        let tmp_idx_path = dir.path().join("pack.idx");
+        std::fs::copy(&idx_path, &tmp_idx_path)?;
+        let idx = index::File::at(&tmp_idx_path, gix_hash::Kind::Sha1)?;
+        let sorted = sorted_by_offset(&idx);
+        assert_eq!(idx.pack_order(), sorted, "without reverse index, the order is computed");
+
+        let rev_path = tmp_idx_path.with_extension("rev");
+        let original = std::fs::read(idx_path.with_extension("rev"))?;
+        let first_position = 12;
+        let mut swapped = original.clone();
+        swapped[first_position..][..8]
+            .copy_from_slice(&[&original[first_position + 4..][..4], &original[first_position..][..4]].concat());
+        std::fs::write(&rev_path, swapped)?;
+        let mut expected = sorted.clone();
+        expected.swap(0, 1);
+        assert_eq!(
+            idx.pack_order(),
+            expected,
+            "the reverse index is trusted as long as it is a permutation"
+        );
+
+        let mut duplicate = original.clone();
+        duplicate[first_position..][..4].copy_from_slice(&original[first_position + 4..][..4]);
+        std::fs::write(&rev_path, duplicate)?;
+        assert_eq!(
+            idx.pack_order(),
+            sorted,
+            "invalid reverse indices are ignored in favor of computing the order"
+        );
+        Ok(())
+    }
+
+    fn sorted_by_offset(idx: &index::File) -> Vec<index::EntryIndex> {
+        let mut order: Vec<_> = (0..idx.num_objects()).collect();
+        order.sort_by_key(|index_position| idx.pack_offset_at_index(*index_position));
+        order
+    }
+}
+
+mod mtimes {
+    use gix_odb::pack::index;
+
+    use crate::{fixture_path, pack::SMALL_PACK_INDEX};
+
+    #[test]
+    fn write_and_read() -> crate::Result {
+        let dir = tempfile::TempDir::new()?;
+        let idx = index::File::at(fixture_path(SMALL_PACK_INDEX), gix_hash::Kind::Sha1)?;
+        let mtimes: Vec<_> = (0..idx.num_objects()).map(|index| 1_000_000 + index).collect();
+
+        let path = dir.path().join("pack.mtimes");
+        let checksum = index::mtimes::File::write_to(
+            mtimes.iter().copied(),
+            &idx.pack_checksum(),
+            std::fs::File::create(&path)?,
+        )?;
+        let file = index::mtimes::File::at(&path, idx.num_objects(), gix_hash::Kind::Sha1)?;
+        assert_eq!(file.version(), index::mtimes::Version::V1);
+        assert_eq!(file.checksum(), checksum);
+        assert_eq!(file.pack_checksum(), idx.pack_checksum());
+        assert_eq!(file.iter().collect::<Vec<_>>(), mtimes);
+        assert_eq!(file.mtime_at_index(1), 1_000_001);
+
+        assert!(
+            matches!(
+                index::mtimes::File::at(&path, idx.num_objects() - 1, gix_hash::Kind::Sha1),
+                Err(index::mtimes::init::Error::Corrupt { .. })
+            ),
+            "the amount of objects must match"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn written_like_git() -> crate::Result {
+        let pack_dir =
+            crate::scripted_fixture_read_only("make_pack_gen_repo_bitmaps.sh")?.join("cruft/.git/objects/pack");
+        let mtimes_path = std::fs::read_dir(&pack_dir)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .find(|p| p.extension().map_or(false, |ext| ext == "mtimes"))
+            .expect("git wrote a cruft pack");
+        let idx = index::File::at(mtimes_path.with_extension("idx"), gix_hash::Kind::Sha1)?;
+        let file = index::mtimes::File::at(&mtimes_path, idx.num_objects(), gix_hash::Kind::Sha1)?;
+        assert_eq!(file.pack_checksum(), idx.pack_checksum());
+        assert!(
+            file.iter().all(|mtime| mtime > 0),
+            "all objects have a modification time"
+        );
+
+        let mut buf = Vec::new();
+        index::mtimes::File::write_to(file.iter(), &idx.pack_checksum(), &mut buf)?;
+        assert_eq!(buf, std::fs::read(&mtimes_path)?, "we write exactly what git writes");
+        Ok(())
+    }
+}
@@ -28,6 +28,7 @@ fn from_paths() -> crate::Result {
         &AtomicBool::new(false),
         gix_pack::multi_index::write::Options {
             object_hash: gix_hash::Kind::Sha1,
+            write_reverse_index: false,
         },
     )?;
 
@@ -66,3 +67,39 @@ fn from_paths() -> crate::Result {
     assert_eq!(outcome.0, file.checksum());
     Ok(())
 }
+
+#[test]
+fn with_reverse_index() -> crate::Result {
+    let dir = tempfile::TempDir::new()?;
+    let input_indices = std::fs::read_dir(fixture_path_standalone("objects/pack"))?
+        .filter_map(|r| {
+            r.ok()
+                .map(|e| e.path())
+                .filter(|p| p.extension().and_then(|e| e.to_str()).unwrap_or("") == "idx")
+        })
+        .collect::<Vec<_>>();
+    let output_path = dir.path().join("multi-pack-index");
+    let outcome = gix_pack::multi_index::File::write_from_index_paths(
+        input_indices,
+        std::fs::File::create(&output_path)?,
+        progress::Discard,
+        &AtomicBool::new(false),
+        gix_pack::multi_index::write::Options {
+            object_hash: gix_hash::Kind::Sha1,
+            write_reverse_index: true,
+        },
+    )?;
+
+    let file = gix_pack::multi_index::File::at(output_path)?;
+    assert_eq!(file.checksum(), outcome.multi_index_checksum);
+    let order = file.pseudo_pack_order().expect("reverse index chunk was written");
+    assert_eq!(order.len(), file.num_objects() as usize);
+    let pack_positions: Vec<_> = order
+        .iter()
+        .map(|idx| file.pack_id_and_pack_offset_at_index(*idx))
+        .collect();
+    let mut sorted = pack_positions.clone();
+    sorted.sort();
+    assert_eq!(pack_positions, sorted, "objects are ordered by pack and then by offset");
+    Ok(())
+}
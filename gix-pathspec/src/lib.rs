@@ -4,11 +4,28 @@
 #![forbid(unsafe_code)]
 
 use bitflags::bitflags;
-use bstr::BString;
+use bstr::{BString, ByteSlice};
 
 ///
 pub mod parse;
 
+///
+pub mod normalize;
+
+///
+pub mod search;
+
+/// A list of pathspec patterns to match paths against, created with [`Search::from_specs()`].
+///
+/// Note that without any patterns, or only with excluding ones, all paths that aren't excluded will be matched
+/// just like in `git`.
+pub struct Search {
+    /// All patterns in the order they were given, along with additional information to speed up matching.
+    patterns: Vec<search::Spec>,
+    /// The length of the directory path shared by all non-excluding patterns, which contains no wildcard.
+    common_prefix_len: usize,
+}
+
 /// The output of a pathspec [parsing][parse()] operation. It can be used to match against a one or more paths.
 #[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
 pub struct Pattern {
@@ -39,6 +56,28 @@ bitflags! {
     }
 }
 
+/// Access
+impl Pattern {
+    /// Return `true` if this pattern excludes paths it matches, i.e. if it has the [`EXCLUDE`][MagicSignature::EXCLUDE] signature.
+    pub fn is_excluded(&self) -> bool {
+        self.signature.contains(MagicSignature::EXCLUDE)
+    }
+
+    /// Return the length of the leading part of our path which doesn't contain any wildcard, which is all of it if our
+    /// [search mode][MatchMode::Literal] is literal.
+    pub fn nowildcard_len(&self) -> usize {
+        match self.search_mode {
+            MatchMode::Literal => self.path.len(),
+            MatchMode::ShellGlob | MatchMode::PathAwareGlob => {
+                self.path.find_byteset(GLOB_CHARACTERS).unwrap_or(self.path.len())
+            }
+        }
+    }
+}
+
+/// Characters which have special meaning in a glob.
+const GLOB_CHARACTERS: &[u8] = br"*?[\";
+
 /// Parts of [magic signatures][MagicSignature] which don't stack as they all configure
 /// the way path specs are matched.
 #[derive(Default, PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
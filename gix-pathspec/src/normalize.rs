@@ -0,0 +1,49 @@
+use bstr::{BStr, BString, ByteSlice, ByteVec};
+
+use crate::{MagicSignature, Pattern};
+
+/// The error returned by [Pattern::normalize()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("The path '{path}' is outside of the worktree")]
+    OutsideOfWorktree { path: BString },
+}
+
+impl Pattern {
+    /// Adjust our path so that it is relative to the root of the worktree, assuming it's relative to `prefix`, the path
+    /// from the root of the worktree to the current working directory, unless the pattern has the [`TOP`][MagicSignature::TOP]
+    /// signature. `prefix` is expected to be separated by slashes.
+    ///
+    /// Empty components as well as `.` and `..` components are resolved, and a trailing slash is kept.
+    /// It's an error if the resulting path would lie outside of the worktree.
+    pub fn normalize(&mut self, prefix: &BStr) -> Result<&mut Self, Error> {
+        let mut path = BString::default();
+        if !self.signature.contains(MagicSignature::TOP) {
+            path.push_str(prefix);
+            path.push(b'/');
+        }
+        path.push_str(&self.path);
+
+        let mut components = Vec::new();
+        for component in path.split_str("/") {
+            match component {
+                b"" | b"." => {}
+                b".." => {
+                    if components.pop().is_none() {
+                        return Err(Error::OutsideOfWorktree {
+                            path: self.path.clone(),
+                        });
+                    }
+                }
+                _ => components.push(component),
+            }
+        }
+        let mut normalized = BString::from(components.join(&b'/'));
+        if !normalized.is_empty() && self.path.ends_with(b"/") {
+            normalized.push(b'/');
+        }
+        self.path = normalized;
+        Ok(self)
+    }
+}
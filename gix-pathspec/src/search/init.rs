@@ -0,0 +1,93 @@
+use bstr::BStr;
+
+use crate::{normalize, search::Spec, MagicSignature, MatchMode, Pattern, Search};
+
+/// Instantiation
+impl Search {
+    /// Create a search from `patterns`, [normalizing][Pattern::normalize()] their paths with `prefix`, the directory of
+    /// the current working directory relative to the root of the worktree, so that they are relative to the root afterwards.
+    ///
+    /// Paths matched with this instance are expected to be relative to the root of the worktree as well.
+    pub fn from_specs(patterns: impl IntoIterator<Item = Pattern>, prefix: &BStr) -> Result<Self, normalize::Error> {
+        let mut patterns = patterns
+            .into_iter()
+            .enumerate()
+            .map(|(sequence_number, mut pattern)| {
+                pattern.normalize(prefix)?;
+                Ok(Spec::new(pattern, sequence_number))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if patterns.iter().all(|p| p.value.is_excluded()) {
+            let sequence_number = patterns.len();
+            patterns.push(Spec::new(
+                Pattern {
+                    path: Default::default(),
+                    signature: MagicSignature::empty(),
+                    search_mode: MatchMode::default(),
+                    attributes: Vec::new(),
+                },
+                sequence_number,
+            ));
+        }
+
+        // Excluded patterns go first, so they are the first match if they apply.
+        patterns.sort_by_key(|p| (!p.value.is_excluded(), p.sequence_number));
+        let common_prefix_len = common_prefix_len(&patterns);
+        Ok(Search {
+            patterns,
+            common_prefix_len,
+        })
+    }
+}
+
+impl Spec {
+    fn new(value: Pattern, sequence_number: usize) -> Self {
+        let attributes = (!value.attributes.is_empty()).then(|| {
+            let mut out = gix_attributes::search::Outcome::default();
+            out.initialize_with_selection(
+                &Default::default(),
+                value.attributes.iter().map(|attr| attr.name.as_str()),
+            );
+            out
+        });
+        let nowildcard_len = value.nowildcard_len();
+        Spec {
+            value,
+            sequence_number,
+            nowildcard_len,
+            attributes,
+        }
+    }
+
+    /// Return the part of our path that is a prefix of all paths we can match.
+    fn literal_prefix(&self) -> &[u8] {
+        let path = self.value.path.as_slice();
+        if self.nowildcard_len == path.len() {
+            path.strip_suffix(b"/").unwrap_or(path)
+        } else {
+            &path[..self.nowildcard_len]
+        }
+    }
+}
+
+fn common_prefix_len(patterns: &[Spec]) -> usize {
+    let mut including = patterns.iter().filter(|p| !p.value.is_excluded());
+    let first = match including.next() {
+        Some(first) => first,
+        None => return 0,
+    };
+    if patterns
+        .iter()
+        .any(|p| p.value.signature.contains(MagicSignature::ICASE))
+    {
+        return 0;
+    }
+    including.fold(first.literal_prefix().len(), |len, p| {
+        first.literal_prefix()[..len]
+            .iter()
+            .zip(p.literal_prefix())
+            .take_while(|(a, b)| a == b)
+            .count()
+    })
+}
@@ -0,0 +1,177 @@
+use bstr::{BStr, BString, ByteSlice, ByteVec};
+use gix_glob::pattern::Case;
+
+use crate::{
+    search::{Match, MatchKind, Spec},
+    MagicSignature, MatchMode, Pattern, Search,
+};
+
+/// Access
+impl Search {
+    /// Return an iterator over all patterns, with excluding ones first, and all paths [normalized][Pattern::normalize()].
+    ///
+    /// This includes the implied pattern which matches everything if all given patterns are excluding.
+    pub fn patterns(&self) -> impl Iterator<Item = &Pattern> + '_ {
+        self.patterns.iter().map(|p| &p.value)
+    }
+
+    /// Return the portion of the path that all paths matched by this search start with, which is empty if there is none
+    /// or if any pattern matches case-insensitively.
+    ///
+    /// Use it to quickly skip paths which can't be matched, for example in sorted lists of paths.
+    pub fn common_prefix(&self) -> &BStr {
+        self.patterns
+            .iter()
+            .find(|p| !p.value.is_excluded())
+            .map_or("".into(), |p| p.value.path[..self.common_prefix_len].as_bstr())
+    }
+}
+
+/// Matching
+impl Search {
+    /// Return the first pattern that matches `relative_path`, which is relative to the root of the worktree,
+    /// or `None` if no pattern matched.
+    ///
+    /// If the returned match [is excluded][Match::is_excluded()], the path is explicitly excluded by a pattern and
+    /// shouldn't be considered matching.
+    /// `is_dir` is `true` if `relative_path` is a directory, and is only needed for patterns that should only match
+    /// directories, like `dir/`. If it's `None`, `relative_path` is assumed not to be a directory.
+    ///
+    /// `attributes` is called for patterns with attribute requirements, like `:(attr:text)`, with the path, its case-sensitivity
+    /// and `is_dir` to fill in all matching attributes into the given outcome, which it has to
+    /// [initialize][gix_attributes::search::Outcome::initialize()] with the collection of attribute names it knows first.
+    /// Its return value is ignored.
+    pub fn pattern_matching_relative_path<'a, Attributes>(
+        &mut self,
+        relative_path: impl Into<&'a BStr>,
+        is_dir: Option<bool>,
+        mut attributes: Attributes,
+    ) -> Option<Match<'_>>
+    where
+        Attributes: FnMut(&BStr, Case, Option<bool>, &mut gix_attributes::search::Outcome) -> bool,
+    {
+        let relative_path = relative_path.into();
+        if self.common_prefix_len != 0 && !relative_path.starts_with(self.common_prefix()) {
+            return None;
+        }
+        let (idx, kind) = self.patterns.iter_mut().enumerate().find_map(|(idx, spec)| {
+            let kind = spec.matches(relative_path, is_dir.unwrap_or(false))?;
+            spec.attributes_match(relative_path, is_dir, &mut attributes)
+                .then_some((idx, kind))
+        })?;
+        let spec = &self.patterns[idx];
+        Some(Match {
+            pattern: &spec.value,
+            sequence_number: spec.sequence_number,
+            kind,
+        })
+    }
+
+    /// Return `true` if the directory at `relative_path`, which is relative to the root of the worktree, could contain
+    /// paths that are matched by this search, or if it could be matched itself.
+    ///
+    /// Use this to avoid traversing into directories that can't contain any matches. Note that `false` positives
+    /// are possible, as attributes and excluding patterns aren't considered.
+    pub fn can_match_directory<'a>(&self, relative_path: impl Into<&'a BStr>) -> bool {
+        let dir = relative_path.into();
+        self.patterns
+            .iter()
+            .filter(|p| !p.value.is_excluded())
+            .any(|p| p.can_match_directory(dir))
+    }
+}
+
+impl Spec {
+    fn case(&self) -> Case {
+        if self.value.signature.contains(MagicSignature::ICASE) {
+            Case::Fold
+        } else {
+            Case::Sensitive
+        }
+    }
+
+    fn eq(&self, a: &[u8], b: &[u8]) -> bool {
+        match self.case() {
+            Case::Fold => a.eq_ignore_ascii_case(b),
+            Case::Sensitive => a == b,
+        }
+    }
+
+    /// Return how our path matches `path` without considering attributes, or `None` if it doesn't match.
+    fn matches(&self, path: &BStr, is_dir: bool) -> Option<MatchKind> {
+        let pattern = self.value.path.as_slice();
+        if pattern.is_empty() {
+            return Some(MatchKind::Always);
+        }
+        if self.nowildcard_len == pattern.len() {
+            let (pattern, wants_dir) = match pattern.strip_suffix(b"/") {
+                Some(pattern) => (pattern, true),
+                None => (pattern, false),
+            };
+            return if path.len() == pattern.len() && self.eq(path, pattern) {
+                (!wants_dir || is_dir).then_some(MatchKind::Verbatim)
+            } else if path.len() > pattern.len()
+                && path[pattern.len()] == b'/'
+                && self.eq(&path[..pattern.len()], pattern)
+            {
+                Some(MatchKind::Prefix)
+            } else {
+                None
+            };
+        }
+
+        if path.len() < self.nowildcard_len || !self.eq(&path[..self.nowildcard_len], &pattern[..self.nowildcard_len]) {
+            return None;
+        }
+        let mut mode = gix_glob::wildmatch::Mode::empty();
+        if self.case() == Case::Fold {
+            mode |= gix_glob::wildmatch::Mode::IGNORE_CASE;
+        }
+        if self.value.search_mode == MatchMode::PathAwareGlob {
+            mode |= gix_glob::wildmatch::Mode::NO_MATCH_SLASH_LITERAL;
+        }
+        let is_match = gix_glob::wildmatch(pattern.as_bstr(), path, mode)
+            || (is_dir && pattern.ends_with(b"/") && {
+                let mut dir = BString::from(path);
+                dir.push_byte(b'/');
+                gix_glob::wildmatch(pattern.as_bstr(), dir.as_bstr(), mode)
+            });
+        is_match.then_some(MatchKind::WildcardMatch)
+    }
+
+    /// Return `true` if the attributes of `path` are what our pattern requires, or if it has no requirements.
+    fn attributes_match<Attributes>(&mut self, path: &BStr, is_dir: Option<bool>, attributes: &mut Attributes) -> bool
+    where
+        Attributes: FnMut(&BStr, Case, Option<bool>, &mut gix_attributes::search::Outcome) -> bool,
+    {
+        let case = self.case();
+        let out = match self.attributes.as_mut() {
+            Some(out) => out,
+            None => return true,
+        };
+        attributes(path, case, is_dir, out);
+        out.iter_selected()
+            .zip(self.value.attributes.iter())
+            .all(|(actual, required)| actual.assignment.state == required.state.as_ref())
+    }
+
+    fn can_match_directory(&self, dir: &BStr) -> bool {
+        let pattern = self.value.path.as_slice();
+        if pattern.is_empty() {
+            return true;
+        }
+        let literal = &pattern[..self.nowildcard_len];
+        let len = dir.len().min(literal.len());
+        if !self.eq(&dir[..len], &literal[..len]) {
+            return false;
+        }
+        if dir.len() < literal.len() {
+            literal[dir.len()] == b'/'
+        } else if self.nowildcard_len == pattern.len() {
+            let literal = literal.strip_suffix(b"/").unwrap_or(literal);
+            dir.len() == literal.len() || dir.get(literal.len()) == Some(&b'/')
+        } else {
+            true
+        }
+    }
+}
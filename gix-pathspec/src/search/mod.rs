@@ -0,0 +1,46 @@
+use crate::Pattern;
+
+/// Describes a matching pattern within a search for pathspecs.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+pub struct Match<'a> {
+    /// The matching pathspec pattern.
+    pub pattern: &'a Pattern,
+    /// The index of the pattern within all patterns the search was created with.
+    ///
+    /// If all patterns are excluding, this is the amount of patterns for the implied pattern that matches everything.
+    pub sequence_number: usize,
+    /// How the pattern matched.
+    pub kind: MatchKind,
+}
+
+/// Describe how a pathspec pattern matched.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+pub enum MatchKind {
+    /// The pattern has no path and matches everything.
+    Always,
+    /// The pattern matched the path exactly.
+    Verbatim,
+    /// The pattern matched a leading directory of the path.
+    Prefix,
+    /// The pattern matched the path using wildcards.
+    WildcardMatch,
+}
+
+impl<'a> Match<'a> {
+    /// Return `true` if the pattern that matched excludes the path.
+    pub fn is_excluded(&self) -> bool {
+        self.pattern.is_excluded()
+    }
+}
+
+/// A pattern of a [`Search`][crate::Search], along with additional information to speed up matching.
+pub(crate) struct Spec {
+    pub(crate) value: Pattern,
+    pub(crate) sequence_number: usize,
+    pub(crate) nowildcard_len: usize,
+    /// Storage for attributes of matching paths, present only if the pattern has attribute requirements.
+    pub(crate) attributes: Option<gix_attributes::search::Outcome>,
+}
+
+mod init;
+mod matching;
@@ -0,0 +1,55 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+mkdir -p dir/sub Dir other
+touch a.c b.txt c.C dir/file dir/sub/f.c dir/sub/g.txt Dir/x dir2 other/a.c 'with*star' 'with?question'
+cat <<EOT > .gitattributes
+*.c text
+*.txt -text
+dir/** generated=yes
+Dir/* generated=no
+EOT
+git add .
+
+git ls-files > paths
+
+function baseline() {
+  local pathspec=$1
+  {
+    echo "$pathspec"
+    git ls-files -- "$pathspec"
+    echo
+  } >> baseline.git
+}
+
+baseline '.'
+baseline ':'
+baseline ':/'
+baseline 'dir'
+baseline 'dir/'
+baseline 'di'
+baseline 'd*r'
+baseline 'dir/*'
+baseline '*.c'
+baseline 'd?r/file'
+baseline '*.[cC]'
+baseline ':(glob)*.c'
+baseline ':(glob)**/*.c'
+baseline ':(glob)dir/*'
+baseline ':(glob)dir/**'
+baseline ':(literal)with*star'
+baseline ':(literal)*.c'
+baseline 'with\*star'
+baseline ':(icase)dir'
+baseline ':(icase)*.c'
+baseline ':(icase,glob)DIR/*'
+baseline ':!dir'
+baseline ':^*.c'
+baseline ':(exclude,icase)dir'
+baseline ':(attr:text)'
+baseline ':(attr:-text)'
+baseline ':(attr:!text)'
+baseline ':(attr:generated=yes)'
+baseline ':(attr:generated=no)dir'
+baseline ':(attr:text generated=yes)'
@@ -1,5 +1,7 @@
 pub use gix_testtools::Result;
 
+mod search;
+
 mod parse {
     use std::collections::HashMap;
 
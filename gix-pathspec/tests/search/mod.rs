@@ -0,0 +1,162 @@
+use bstr::{BStr, BString, ByteSlice};
+use gix_attributes::search::{MetadataCollection, Outcome};
+use gix_glob::pattern::Case;
+
+#[test]
+fn baseline() -> crate::Result {
+    let dir = gix_testtools::scripted_fixture_read_only("match_baseline_files.sh")?;
+    let paths = std::fs::read(dir.join("paths"))?;
+    let paths: Vec<_> = paths.lines().map(|p| p.as_bstr()).collect();
+
+    let mut collection = MetadataCollection::default();
+    let mut attributes = gix_attributes::Search::default();
+    attributes.add_patterns_buffer(
+        &std::fs::read(dir.join(".gitattributes"))?,
+        dir.join(".gitattributes"),
+        None,
+        &mut collection,
+        true,
+    );
+
+    for (spec, expected) in parse_baseline(&std::fs::read(dir.join("baseline.git"))?) {
+        let pattern = gix_pathspec::parse(spec.as_bytes())?;
+        let mut search = gix_pathspec::Search::from_specs(Some(pattern), "".into())?;
+        let actual: Vec<_> = paths
+            .iter()
+            .copied()
+            .filter(|path| {
+                search
+                    .pattern_matching_relative_path(*path, Some(false), |path, case, _is_dir, out| {
+                        out.initialize(&collection);
+                        attributes.pattern_matching_relative_path(path, case, out)
+                    })
+                    .map_or(false, |m| !m.is_excluded())
+            })
+            .collect();
+        assert_eq!(actual, expected, "{spec}: disagrees with baseline");
+    }
+    Ok(())
+}
+
+#[test]
+fn multiple_patterns_with_exclusions() -> crate::Result {
+    let mut search = search_of(&["dir", "*.c", ":!dir/sub"], "")?;
+    assert_eq!(search.common_prefix(), "", "there is no common prefix");
+    for (path, sequence_number) in [
+        ("dir/file", Some(0)),
+        ("a.c", Some(1)),
+        ("dir/a.c", Some(0)),
+        ("b", None),
+    ] {
+        let m = search.pattern_matching_relative_path(path, None, no_attributes);
+        assert_eq!(m.map(|m| m.sequence_number), sequence_number, "{path}");
+    }
+    let m = search
+        .pattern_matching_relative_path("dir/sub/a.c", None, no_attributes)
+        .expect("match");
+    assert!(m.is_excluded(), "exclusions win over inclusions");
+    assert_eq!(m.sequence_number, 2);
+    assert_eq!(m.kind, gix_pathspec::search::MatchKind::Prefix);
+    Ok(())
+}
+
+#[test]
+fn directories_can_be_matched_only_if_requested() -> crate::Result {
+    let mut search = search_of(&["dir/"], "")?;
+    assert!(search
+        .pattern_matching_relative_path("dir", Some(true), no_attributes)
+        .is_some());
+    assert!(search
+        .pattern_matching_relative_path("dir", Some(false), no_attributes)
+        .is_none());
+    assert!(search
+        .pattern_matching_relative_path("dir", None, no_attributes)
+        .is_none());
+    Ok(())
+}
+
+#[test]
+fn prefixes_are_applied_unless_top_is_used() -> crate::Result {
+    let mut search = search_of(&["file", "../other/*.c", ":/top", "."], "dir/sub")?;
+    assert_eq!(
+        search.patterns().map(|p| p.path.clone()).collect::<Vec<_>>(),
+        ["dir/sub/file", "dir/other/*.c", "top", "dir/sub"]
+            .into_iter()
+            .map(BString::from)
+            .collect::<Vec<_>>()
+    );
+    assert!(search
+        .pattern_matching_relative_path("dir/other/a.c", None, no_attributes)
+        .is_some());
+    assert!(search
+        .pattern_matching_relative_path("top", None, no_attributes)
+        .is_some());
+    assert!(search
+        .pattern_matching_relative_path("file", None, no_attributes)
+        .is_none());
+
+    let err = search_err(&["../../../outside"], "dir/sub");
+    assert_eq!(
+        err.to_string(),
+        "The path '../../../outside' is outside of the worktree"
+    );
+    Ok(())
+}
+
+#[test]
+fn common_prefix_and_directory_pruning() -> crate::Result {
+    let search = search_of(&["dir/sub/*.c", "dir/subdir/file", ":!dir/sub/x.c"], "")?;
+    assert_eq!(search.common_prefix(), "dir/sub");
+    for (dir, expected) in [
+        ("dir", true),
+        ("dir/sub", true),
+        ("dir/subdir", true),
+        ("dir/su", false),
+        ("dir/subdir/deeper", false),
+        ("dir/sub/deeper", true),
+        ("other", false),
+    ] {
+        assert_eq!(search.can_match_directory(dir), expected, "{dir}");
+    }
+
+    let excluding = search_of(&[":!dir"], "")?;
+    assert_eq!(excluding.common_prefix(), "");
+    assert!(excluding.can_match_directory("dir"), "exclusions are not considered");
+    Ok(())
+}
+
+fn search_of(specs: &[&str], prefix: &str) -> crate::Result<gix_pathspec::Search> {
+    let patterns = specs
+        .iter()
+        .map(|spec| gix_pathspec::parse(spec.as_bytes()))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(gix_pathspec::Search::from_specs(patterns, prefix.into())?)
+}
+
+fn search_err(specs: &[&str], prefix: &str) -> gix_pathspec::normalize::Error {
+    let patterns = specs
+        .iter()
+        .map(|spec| gix_pathspec::parse(spec.as_bytes()).expect("valid"));
+    match gix_pathspec::Search::from_specs(patterns, prefix.into()) {
+        Ok(_) => panic!("expected an error"),
+        Err(err) => err,
+    }
+}
+
+fn no_attributes(_path: &BStr, _case: Case, _is_dir: Option<bool>, _out: &mut Outcome) -> bool {
+    unreachable!("no attributes are requested")
+}
+
+fn parse_baseline(input: &[u8]) -> Vec<(&str, Vec<&BStr>)> {
+    let mut out = Vec::new();
+    let mut lines = input.lines();
+    while let Some(spec) = lines.next() {
+        let paths = lines
+            .by_ref()
+            .take_while(|line| !line.is_empty())
+            .map(|l| l.as_bstr())
+            .collect();
+        out.push((spec.to_str().expect("valid UTF-8"), paths));
+    }
+    out
+}
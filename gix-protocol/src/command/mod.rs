@@ -113,10 +113,29 @@ mod with_io {
 
         /// Turns on all modern features for V1 and all supported features for V2, returning them as a vector of features.
         /// Note that this is the basis for any fetch operation as these features fulfil basic requirements and reasonably up-to-date servers.
+        ///
+        /// If the server advertises an object format other than `sha1`, it will be passed back to it as `object-format` feature
+        /// as the server won't accept object ids of other formats otherwise.
         pub fn default_features(
             &self,
             version: gix_transport::Protocol,
             server_capabilities: &Capabilities,
+        ) -> Vec<Feature> {
+            let mut features = self.default_features_sans_object_format(version, server_capabilities);
+            if let Some(object_format) = server_capabilities
+                .capability("object-format")
+                .and_then(|c| c.value())
+                .filter(|format| *format != "sha1")
+            {
+                features.push(("object-format", Some(object_format.to_string().into())));
+            }
+            features
+        }
+
+        fn default_features_sans_object_format(
+            &self,
+            version: gix_transport::Protocol,
+            server_capabilities: &Capabilities,
         ) -> Vec<Feature> {
             match self {
                 Command::Fetch => match version {
@@ -200,7 +219,7 @@ mod with_io {
                             continue;
                         }
                         match *feature {
-                            "agent" => {}
+                            "agent" | "object-format" => {}
                             _ => panic!("{}: V2 feature/capability {} is not supported", self.as_str(), feature),
                         }
                     }
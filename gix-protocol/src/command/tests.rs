@@ -46,6 +46,25 @@ mod v1 {
                     "we don't enforce no-progress"
                 );
             }
+
+            #[test]
+            fn object_format_is_passed_back_unless_it_is_sha1() {
+                assert_eq!(
+                    Command::Fetch.default_features(
+                        gix_transport::Protocol::V1,
+                        &capabilities("ofs-delta object-format=sha256")
+                    ),
+                    &[("ofs-delta", None), ("object-format", Some("sha256".into()))]
+                );
+                assert_eq!(
+                    Command::Fetch.default_features(
+                        gix_transport::Protocol::V1,
+                        &capabilities("ofs-delta object-format=sha1")
+                    ),
+                    &[("ofs-delta", None)],
+                    "sha1 is the default which doesn't need to be specified"
+                );
+            }
         }
     }
 }
@@ -101,6 +120,8 @@ mod v2 {
 
     mod ls_refs {
         mod default_features {
+            use gix_transport::client::Capabilities;
+
             use crate::{command::tests::v2::capabilities, Command};
 
             #[test]
@@ -113,6 +134,20 @@ mod v2 {
                     &[]
                 );
             }
+
+            #[test]
+            fn object_format_is_passed_back_unless_it_is_sha1() {
+                let capabilities = Capabilities::from_lines("version 2\nls-refs=unborn\nobject-format=sha256".into())
+                    .expect("valid input for V2 capabilities");
+                let features = Command::LsRefs.default_features(gix_transport::Protocol::V2, &capabilities);
+                assert_eq!(features, &[("object-format", Some("sha256".into()))]);
+                Command::LsRefs.validate_argument_prefixes_or_panic(
+                    gix_transport::Protocol::V2,
+                    &capabilities,
+                    &[],
+                    &features,
+                );
+            }
         }
 
         mod validate {
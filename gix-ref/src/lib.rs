@@ -14,6 +14,8 @@
 //!     * one reference maps to a file on disk
 //!   * **packed**
 //!     * references are stored in a single human-readable file, along with their targets if they are symbolic.
+//! * **[reftable][reftable::Store]**
+//!   * references and their logs are stored in a stack of binary tables, which scales to millions of references.
 //!
 //! ## Feature Flags
 #![cfg_attr(
@@ -31,7 +33,7 @@ use gix_object::bstr::{BStr, BString};
 
 #[path = "store/mod.rs"]
 mod store_impl;
-pub use store_impl::{file, packed};
+pub use store_impl::{file, packed, reftable};
 
 mod fullname;
 ///
@@ -68,10 +70,23 @@ pub mod store {
         Disable,
     }
 
-    /// A thread-local handle for interacting with a [`Store`][crate::Store] to find and iterate references.
-    #[derive(Clone)]
-    #[allow(dead_code)]
-    pub(crate) struct Handle {
+    /// The format in which references are stored in a repository, as configured with `extensions.refStorage`.
+    #[derive(Default, Debug, PartialOrd, PartialEq, Ord, Eq, Hash, Clone, Copy)]
+    pub enum Format {
+        /// Loose references in files, along with a `packed-refs` file, as handled by [`file::Store`][crate::file::Store].
+        #[default]
+        Files,
+        /// A stack of binary tables in the `reftable` directory, as handled by [`reftable::Store`][crate::reftable::Store].
+        Reftable,
+    }
+
+    /// A handle for interacting with references independently of the format they are stored in, to find, iterate and edit
+    /// references and their logs.
+    ///
+    /// It can be created from any of the supported stores, like a [`file::Store`] or a [`reftable::Store`][crate::reftable::Store],
+    /// and is cheap to clone.
+    #[derive(Clone, Debug)]
+    pub struct Handle {
         /// A way to access shared state with the requirement that interior mutability doesn't leak or is incorporated into error types
         /// if it could. The latter can't happen if references to said internal aren't ever returned.
         state: handle::State,
@@ -80,6 +95,7 @@ pub mod store {
     #[allow(dead_code)]
     pub(crate) enum State {
         Loose { store: file::Store },
+        Reftable { store: crate::reftable::Store },
     }
 
     pub(crate) mod general;
@@ -87,7 +103,7 @@ pub mod store {
     ///
     #[path = "general/handle/mod.rs"]
     mod handle;
-    pub use handle::find;
+    pub use handle::{expire, find, iter, log, rename, set_namespace, transaction};
 
     use crate::file;
 }
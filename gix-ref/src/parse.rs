@@ -2,6 +2,7 @@ use gix_object::bstr::{BStr, ByteSlice};
 use nom::{
     branch::alt,
     bytes::complete::{tag, take_while_m_n},
+    combinator::verify,
     error::ParseError,
     IResult,
 };
@@ -14,10 +15,13 @@ fn is_hex_digit_lc(b: u8) -> bool {
 pub fn hex_hash<'a, E: ParseError<&'a [u8]>>(i: &'a [u8]) -> IResult<&'a [u8], &'a BStr, E> {
     // NOTE: It's important to be able to read all hashes, do not parameterize it. Hashes can be rejected at a later stage
     // if needed.
-    take_while_m_n(
-        gix_hash::Kind::shortest().len_in_hex(),
-        gix_hash::Kind::longest().len_in_hex(),
-        is_hex_digit_lc,
+    verify(
+        take_while_m_n(
+            gix_hash::Kind::shortest().len_in_hex(),
+            gix_hash::Kind::longest().len_in_hex(),
+            is_hex_digit_lc,
+        ),
+        |hex: &[u8]| matches!(gix_hash::Kind::from_hex_len(hex.len()), Some(kind) if kind.len_in_hex() == hex.len()),
     )(i)
     .map(|(i, hex)| (i, hex.as_bstr()))
 }
@@ -39,6 +39,7 @@ mod init {
                 common_dir: None,
                 write_reflog,
                 namespace: None,
+                transaction_hook: None,
                 packed: gix_fs::SharedFileSnapshotMut::new().into(),
                 object_hash,
             }
@@ -57,6 +58,7 @@ mod init {
                 common_dir: Some(common_dir.into()),
                 write_reflog,
                 namespace: None,
+                transaction_hook: None,
                 packed: gix_fs::SharedFileSnapshotMut::new().into(),
                 object_hash,
             }
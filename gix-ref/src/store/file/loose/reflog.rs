@@ -172,6 +172,22 @@ pub mod create_or_update {
             }
         }
 
+        /// Return `true` if the reflog of `name` is created if it doesn't exist yet when appending to it, with
+        /// `force_create_reflog` as passed to [`reflog_create_or_append()`][file::Store::reflog_create_or_append()].
+        pub(in crate::store_impl::file) fn creates_reflog(
+            &self,
+            name: &FullNameRef,
+            force_create_reflog: bool,
+        ) -> bool {
+            match self.write_reflog {
+                WriteReflog::Always => true,
+                WriteReflog::Normal => {
+                    force_create_reflog || self.should_autocreate_reflog(&self.reflog_base_and_relative_path(name).1)
+                }
+                WriteReflog::Disable => false,
+            }
+        }
+
         fn should_autocreate_reflog(&self, full_name: &Path) -> bool {
             full_name.starts_with("refs/heads/")
                 || full_name.starts_with("refs/remotes/")
@@ -25,6 +25,8 @@ pub struct Store {
     pub write_reflog: WriteReflog,
     /// The namespace to use for edits and reads
     pub namespace: Option<Namespace>,
+    /// A hook to call at each stage of all transactions.
+    pub transaction_hook: Option<crate::transaction::hook::Hook>,
     /// A packed buffer which can be mapped in one version and shared as such.
     /// It's updated only in one spot, which is prior to reading it based on file stamps.
     /// Doing it like this has the benefit of being able to hand snapshots out to people without blocking others from updating it.
@@ -58,6 +60,11 @@ mod access {
         pub fn common_dir_resolved(&self) -> &Path {
             self.common_dir.as_deref().unwrap_or(&self.git_dir)
         }
+
+        /// Return the kind of hash to assume for object ids.
+        pub fn object_hash(&self) -> gix_hash::Kind {
+            self.object_hash
+        }
     }
 }
 
@@ -100,5 +107,9 @@ pub mod transaction;
 ///
 pub mod packed;
 
+///
+pub mod pack_refs;
+
 mod raw_ext;
+pub(crate) use raw_ext::peel_object_in_place;
 pub use raw_ext::ReferenceExt;
@@ -0,0 +1,153 @@
+use crate::{
+    file,
+    store_impl::file::transaction::{FindObjectFn, PackedRefs},
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    FullName, Target,
+};
+
+/// Determine which loose references to move into the `packed-refs` file.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum Filter {
+    /// Pack tags and references that are already packed, like `git pack-refs` does by default.
+    ///
+    /// Branches are left loose as they are expected to change frequently.
+    #[default]
+    TagsAndPacked,
+    /// Pack all references, like `git pack-refs --all`.
+    All,
+}
+
+/// Configuration for [`Store::pack_refs()`][crate::file::Store::pack_refs()].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// Which references to pack.
+    pub filter: Filter,
+    /// If `true`, delete the loose references once they are packed, like `git pack-refs --prune` does by default.
+    pub prune: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            filter: Filter::default(),
+            prune: true,
+        }
+    }
+}
+
+/// The outcome of [`Store::pack_refs()`][crate::file::Store::pack_refs()].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// The names of all references which were written into the `packed-refs` file, in lexical order.
+    pub packed: Vec<FullName>,
+}
+
+impl file::Store {
+    /// Move loose references into the `packed-refs` file according to `options`, similar to `git pack-refs`, and return the names
+    /// of the packed references.
+    ///
+    /// Symbolic references are never packed, and neither are references which are private to a worktree, like `HEAD` or
+    /// those in `refs/bisect/`. Loose references which can't be parsed are skipped.
+    /// `find` is used to peel tags for storage in the `packed-refs` file.
+    ///
+    /// All references are packed in a single transaction which locks them according to `lock_fail_mode` while the `packed-refs` file
+    /// is locked according to `packed_refs_lock_fail_mode`. It fails if one of the references changes concurrently.
+    /// Note that the namespace of the store is ignored, as are [transaction hooks][crate::transaction::hook::Hook] as the value
+    /// of the references doesn't change.
+    pub fn pack_refs<'a>(
+        &self,
+        options: Options,
+        lock_fail_mode: gix_lock::acquire::Fail,
+        packed_refs_lock_fail_mode: gix_lock::acquire::Fail,
+        find: Box<FindObjectFn<'a>>,
+    ) -> Result<Outcome, Error> {
+        let mut store = self.clone();
+        store.namespace = None;
+        store.transaction_hook = None;
+
+        let packed = store.cached_packed_buffer()?;
+        let mut edits = Vec::new();
+        for reference in store.loose_iter()? {
+            let reference = match reference {
+                Ok(reference) => reference,
+                Err(file::iter::loose_then_packed::Error::Traversal(err)) => return Err(Error::Traversal(err)),
+                Err(_) => continue,
+            };
+            let id = match reference.target {
+                Target::Peeled(id) => id,
+                Target::Symbolic(_) => continue,
+            };
+            let name = reference.name;
+            let category = name.category();
+            if category.map_or(false, |c| c.is_worktree_private()) {
+                continue;
+            }
+            let should_pack = match options.filter {
+                Filter::All => true,
+                Filter::TagsAndPacked => {
+                    category == Some(crate::Category::Tag)
+                        || packed
+                            .as_ref()
+                            .map(|packed| packed.try_find(name.as_ref()).map(|r| r.is_some()))
+                            .transpose()?
+                            .unwrap_or(false)
+                }
+            };
+            if !should_pack {
+                continue;
+            }
+            edits.push(RefEdit {
+                change: Change::Update {
+                    log: LogChange {
+                        mode: RefLog::AndReference,
+                        force_create_reflog: false,
+                        message: "pack-refs".into(),
+                    },
+                    expected: PreviousValue::MustExistAndMatch(Target::Peeled(id)),
+                    new: Target::Peeled(id),
+                },
+                name,
+                deref: false,
+            });
+        }
+        drop(packed);
+
+        let mut out = Outcome::default();
+        if edits.is_empty() {
+            return Ok(out);
+        }
+        out.packed = edits.iter().map(|edit| edit.name.clone()).collect();
+        store
+            .transaction()
+            .packed_refs(if options.prune {
+                PackedRefs::DeletionsAndNonSymbolicUpdatesRemoveLooseSourceReference(find)
+            } else {
+                PackedRefs::DeletionsAndNonSymbolicUpdates(find)
+            })
+            .prepare(edits, lock_fail_mode, packed_refs_lock_fail_mode)?
+            // The reflog remains unchanged as the value of the references stays the same.
+            .commit(None)?;
+        Ok(out)
+    }
+}
+
+mod error {
+    use crate::store_impl::{file, packed};
+
+    /// The error returned by [`Store::pack_refs()`][crate::file::Store::pack_refs()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The loose references could not be traversed")]
+        Traversal(#[from] std::io::Error),
+        #[error(transparent)]
+        OpenPacked(#[from] packed::buffer::open::Error),
+        #[error(transparent)]
+        FindPacked(#[from] packed::find::Error),
+        #[error(transparent)]
+        Prepare(#[from] file::transaction::prepare::Error),
+        #[error(transparent)]
+        Commit(#[from] file::transaction::commit::Error),
+    }
+}
+pub use error::Error;
@@ -80,7 +80,7 @@ impl ReferenceExt for Reference {
     fn peel_to_id_in_place_packed<E: std::error::Error + Send + Sync + 'static>(
         &mut self,
         store: &file::Store,
-        mut find: impl FnMut(gix_hash::ObjectId, &mut Vec<u8>) -> Result<Option<(gix_object::Kind, &[u8])>, E>,
+        find: impl FnMut(gix_hash::ObjectId, &mut Vec<u8>) -> Result<Option<(gix_object::Kind, &[u8])>, E>,
         packed: Option<&packed::Buffer>,
     ) -> Result<ObjectId, peel::to_id::Error> {
         match self.peeled {
@@ -109,30 +109,7 @@ impl ReferenceExt for Reference {
                         }
                     }
                 };
-                let mut buf = Vec::new();
-                let mut oid = self.target.try_id().expect("peeled ref").to_owned();
-                let peeled_id = loop {
-                    let (kind, data) = find(oid, &mut buf)
-                        .map_err(|err| Box::new(err) as Box<dyn std::error::Error + Send + Sync + 'static>)?
-                        .ok_or_else(|| peel::to_id::Error::NotFound {
-                            oid,
-                            name: self.name.0.clone(),
-                        })?;
-                    match kind {
-                        gix_object::Kind::Tag => {
-                            oid = gix_object::TagRefIter::from_bytes(data).target_id().map_err(|_err| {
-                                peel::to_id::Error::NotFound {
-                                    oid,
-                                    name: self.name.0.clone(),
-                                }
-                            })?;
-                        }
-                        _ => break oid,
-                    };
-                };
-                self.peeled = Some(peeled_id);
-                self.target = Target::Peeled(peeled_id);
-                Ok(peeled_id)
+                peel_object_in_place(self, find)
             }
         }
     }
@@ -172,3 +149,35 @@ impl ReferenceExt for Reference {
         }
     }
 }
+
+/// Peel the target of the non-symbolic reference `r` to the first object which isn't a tag, using `find` to lookup objects,
+/// and store the result in `r`.
+pub(crate) fn peel_object_in_place<E: std::error::Error + Send + Sync + 'static>(
+    r: &mut Reference,
+    mut find: impl FnMut(gix_hash::ObjectId, &mut Vec<u8>) -> Result<Option<(gix_object::Kind, &[u8])>, E>,
+) -> Result<ObjectId, peel::to_id::Error> {
+    let mut buf = Vec::new();
+    let mut oid = r.target.try_id().expect("peeled ref").to_owned();
+    let peeled_id = loop {
+        let (kind, data) = find(oid, &mut buf)
+            .map_err(|err| Box::new(err) as Box<dyn std::error::Error + Send + Sync + 'static>)?
+            .ok_or_else(|| peel::to_id::Error::NotFound {
+                oid,
+                name: r.name.0.clone(),
+            })?;
+        match kind {
+            gix_object::Kind::Tag => {
+                oid = gix_object::TagRefIter::from_bytes(data).target_id().map_err(|_err| {
+                    peel::to_id::Error::NotFound {
+                        oid,
+                        name: r.name.0.clone(),
+                    }
+                })?;
+            }
+            _ => break oid,
+        };
+    };
+    r.peeled = Some(peeled_id);
+    r.target = Target::Peeled(peeled_id);
+    Ok(peeled_id)
+}
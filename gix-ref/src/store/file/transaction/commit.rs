@@ -1,6 +1,6 @@
 use crate::{
     store_impl::file::{transaction::PackedRefs, Transaction},
-    transaction::{Change, LogChange, RefEdit, RefLog},
+    transaction::{hook::Stage, Change, LogChange, RefEdit, RefLog},
     Target,
 };
 
@@ -25,8 +25,22 @@ impl<'s, 'p> Transaction<'s, 'p> {
     ///   along with empty parent directories
     ///
     /// Note that transactions will be prepared automatically as needed.
+    ///
+    /// If a [hook][crate::transaction::hook::Hook] is set, it will be informed about the committed edits, or that the transaction
+    /// was aborted if the commit failed.
     pub fn commit<'a>(self, committer: impl Into<Option<gix_actor::SignatureRef<'a>>>) -> Result<Vec<RefEdit>, Error> {
-        self.commit_inner(committer.into())
+        let store = self.store;
+        let hook_edits: Option<Vec<_>> = store
+            .transaction_hook
+            .as_ref()
+            .and(self.updates.as_ref())
+            .map(|updates| updates.iter().map(|edit| edit.update.clone()).collect());
+        let res = self.commit_inner(committer.into());
+        if let Some((hook, edits)) = store.transaction_hook.as_ref().zip(hook_edits) {
+            let stage = if res.is_ok() { Stage::Committed } else { Stage::Aborted };
+            hook.call(stage, &edits, store.object_hash()).ok();
+        }
+        res
     }
 
     fn commit_inner(self, committer: Option<gix_actor::SignatureRef<'_>>) -> Result<Vec<RefEdit>, Error> {
@@ -35,6 +49,7 @@ impl<'s, 'p> Transaction<'s, 'p> {
             self.packed_refs,
             PackedRefs::DeletionsAndNonSymbolicUpdatesRemoveLooseSourceReference(_)
         );
+        let assume_empty_store = matches!(self.packed_refs, PackedRefs::Initial(_));
 
         // Perform updates first so live commits remain referenced
         for change in updates.iter_mut() {
@@ -43,18 +58,41 @@ impl<'s, 'p> Transaction<'s, 'p> {
                 // reflog first, then reference
                 Change::Update { log, new, expected } => {
                     let lock = change.lock.take();
-                    let (update_ref, update_reflog) = match log.mode {
+                    let (update_ref, mut update_reflog) = match log.mode {
                         RefLog::Only => (false, true),
                         RefLog::AndReference => (true, true),
                     };
+                    if assume_empty_store {
+                        // There are no reflogs to append to, so avoid looking for them unless they are created.
+                        update_reflog &= self
+                            .store
+                            .creates_reflog(change.update.name.as_ref(), log.force_create_reflog);
+                    }
                     if update_reflog {
                         let log_update = match new {
-                            Target::Symbolic(_) => {
-                                // no reflog for symref changes, unless the ref is new and we can obtain a peeled id
-                                // identified by the expectation of what could be there, as is the case when cloning.
+                            Target::Symbolic(referent) => {
                                 match expected {
+                                    // If the ref is new we can obtain a peeled id identified by the expectation of what
+                                    // could be there, as is the case when cloning.
                                     PreviousValue::ExistingMustMatch(Target::Peeled(oid)) => {
-                                        Some((Some(gix_hash::ObjectId::null(oid.kind())), oid))
+                                        Some((Some(gix_hash::ObjectId::null(oid.kind())), oid.to_owned()))
+                                    }
+                                    // If an existing ref is pointed to another one, like when `HEAD` is moved to another branch,
+                                    // log the change of the ids they are pointing to, as long as the new referent exists.
+                                    PreviousValue::MustExistAndMatch(previous) if previous != new => {
+                                        peeled_id(self.store, referent.as_ref()).map(|new_oid| {
+                                            let previous = match previous {
+                                                Target::Peeled(oid) => Some(oid.to_owned()),
+                                                Target::Symbolic(name) => peeled_id(self.store, name.as_ref()),
+                                            };
+                                            (
+                                                Some(
+                                                    previous
+                                                        .unwrap_or_else(|| gix_hash::ObjectId::null(new_oid.kind())),
+                                                ),
+                                                new_oid,
+                                            )
+                                        })
                                     }
                                     _ => None,
                                 }
@@ -64,17 +102,31 @@ impl<'s, 'p> Transaction<'s, 'p> {
                                     PreviousValue::MustExistAndMatch(Target::Peeled(oid)) => Some(oid.to_owned()),
                                     _ => None,
                                 }
-                                .or(change.leaf_referent_previous_oid);
-                                Some((previous, new_oid))
+                                .or(change.leaf_referent_previous_oid)
+                                .or_else(|| match expected {
+                                    // A symbolic ref is detached, like `HEAD` when checking out a commit.
+                                    PreviousValue::MustExistAndMatch(Target::Symbolic(name))
+                                        if log.mode == RefLog::AndReference =>
+                                    {
+                                        peeled_id(self.store, name.as_ref())
+                                    }
+                                    _ => None,
+                                });
+                                Some((previous, new_oid.to_owned()))
                             }
                         };
                         if let Some((previous, new_oid)) = log_update {
-                            let do_update = previous.as_ref().map_or(true, |previous| previous != new_oid);
+                            // Moving or detaching a symbolic ref is always logged, even if the id doesn't change,
+                            // to remember the move.
+                            let is_move = matches!(new, Target::Symbolic(_))
+                                || (log.mode == RefLog::AndReference
+                                    && matches!(expected, PreviousValue::MustExistAndMatch(Target::Symbolic(_))));
+                            let do_update = is_move || previous.as_ref().map_or(true, |previous| *previous != new_oid);
                             if do_update {
                                 self.store.reflog_create_or_append(
                                     change.update.name.as_ref(),
                                     previous,
-                                    new_oid,
+                                    &new_oid,
                                     committer,
                                     log.message.as_ref(),
                                     log.force_create_reflog,
@@ -173,6 +225,20 @@ impl<'s, 'p> Transaction<'s, 'p> {
         Ok(updates.into_iter().map(|edit| edit.update).collect())
     }
 }
+
+/// Return the id the reference `name` points to in `store` if it exists and isn't symbolic.
+fn peeled_id(store: &crate::file::Store, name: &crate::FullNameRef) -> Option<gix_hash::ObjectId> {
+    let packed = store.assure_packed_refs_uptodate().ok()?;
+    match store
+        .find_one_with_verified_input(name.into(), packed.as_ref().map(|b| &***b))
+        .ok()??
+        .target
+    {
+        Target::Peeled(oid) => Some(oid),
+        Target::Symbolic(_) => None,
+    }
+}
+
 mod error {
     use gix_object::bstr::BString;
 
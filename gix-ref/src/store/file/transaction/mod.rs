@@ -30,6 +30,15 @@ pub enum PackedRefs<'a> {
     /// reference which is originally updated if it exists. If it doesn't, the new value will be written into the packed ref right away.
     /// Note that this doesn't affect symbolic references at all, which can't be placed into packed refs.
     DeletionsAndNonSymbolicUpdatesRemoveLooseSourceReference(Box<FindObjectFn<'a>>),
+    /// Assume the store is empty, as is the case when cloning, and write all updates to references which are peeled into a new
+    /// `packed-refs` file right away, without looking up existing references or locking them individually.
+    /// Symbolic references are written as loose references, and reflogs are only written for references that would
+    /// have them automatically, which excludes tags.
+    ///
+    /// This is much faster than the alternatives for large amounts of references, but will fail if a `packed-refs` file exists.
+    /// It's the caller's responsibility to assure there are no loose references either, except for `HEAD`, as these are assumed
+    /// to not exist.
+    Initial(Box<FindObjectFn<'a>>),
 }
 
 #[derive(Debug)]
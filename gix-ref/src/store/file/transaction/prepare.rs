@@ -9,7 +9,7 @@ use crate::{
             Transaction,
         },
     },
-    transaction::{Change, LogChange, PreviousValue, RefEdit, RefEditsExt, RefLog},
+    transaction::{hook::Stage, Change, LogChange, PreviousValue, RefEdit, RefEditsExt, RefLog},
     FullName, FullNameRef, Reference, Target,
 };
 
@@ -21,6 +21,7 @@ impl<'s, 'p> Transaction<'s, 'p> {
         change: &mut Edit,
         has_global_lock: bool,
         direct_to_packed_refs: bool,
+        assume_empty_store: bool,
     ) -> Result<(), Error> {
         use std::io::Write;
         assert!(
@@ -28,30 +29,11 @@ impl<'s, 'p> Transaction<'s, 'p> {
             "locks can only be acquired once and it's all or nothing"
         );
 
-        let existing_ref = store
-            .ref_contents(change.update.name.as_ref())
-            .map_err(Error::from)
-            .and_then(|maybe_loose| {
-                maybe_loose
-                    .map(|buf| {
-                        loose::Reference::try_from_path(change.update.name.clone(), &buf)
-                            .map(Reference::from)
-                            .map_err(Error::from)
-                    })
-                    .transpose()
-            })
-            .or_else(|err| match err {
-                Error::ReferenceDecode(_) => Ok(None),
-                other => Err(other),
-            })
-            .and_then(|maybe_loose| match (maybe_loose, packed) {
-                (None, Some(packed)) => packed
-                    .try_find(change.update.name.as_ref())
-                    .map(|opt| opt.map(Into::into))
-                    .map_err(Error::from),
-                (None, None) => Ok(None),
-                (maybe_loose, _) => Ok(maybe_loose),
-            });
+        let existing_ref = if assume_empty_store {
+            Ok(None)
+        } else {
+            Self::find_existing_ref(store, packed, change.update.name.as_ref())
+        };
         let lock = match &mut change.update.change {
             Change::Delete { expected, .. } => {
                 let (base, relative_path) = store.reference_path_with_base(change.update.name.as_ref());
@@ -195,6 +177,38 @@ impl<'s, 'p> Transaction<'s, 'p> {
         change.lock = lock;
         Ok(())
     }
+
+    /// Find the loose reference `name`, or the packed one if there is no loose one.
+    fn find_existing_ref(
+        store: &file::Store,
+        packed: Option<&packed::Buffer>,
+        name: &FullNameRef,
+    ) -> Result<Option<Reference>, Error> {
+        store
+            .ref_contents(name)
+            .map_err(Error::from)
+            .and_then(|maybe_loose| {
+                maybe_loose
+                    .map(|buf| {
+                        loose::Reference::try_from_path(name.to_owned(), &buf)
+                            .map(Reference::from)
+                            .map_err(Error::from)
+                    })
+                    .transpose()
+            })
+            .or_else(|err| match err {
+                Error::ReferenceDecode(_) => Ok(None),
+                other => Err(other),
+            })
+            .and_then(|maybe_loose| match (maybe_loose, packed) {
+                (None, Some(packed)) => packed
+                    .try_find(name)
+                    .map(|opt| opt.map(Into::into))
+                    .map_err(Error::from),
+                (None, None) => Ok(None),
+                (maybe_loose, _) => Ok(maybe_loose),
+            })
+    }
 }
 
 impl<'s, 'p> Transaction<'s, 'p> {
@@ -240,9 +254,16 @@ impl<'s, 'p> Transaction<'s, 'p> {
 
         let mut maybe_updates_for_packed_refs = match self.packed_refs {
             PackedRefs::DeletionsAndNonSymbolicUpdates(_)
-            | PackedRefs::DeletionsAndNonSymbolicUpdatesRemoveLooseSourceReference(_) => Some(0_usize),
+            | PackedRefs::DeletionsAndNonSymbolicUpdatesRemoveLooseSourceReference(_)
+            | PackedRefs::Initial(_) => Some(0_usize),
             PackedRefs::DeletionsOnly => None,
         };
+        let assume_empty_store = matches!(self.packed_refs, PackedRefs::Initial(_));
+        if assume_empty_store && self.store.packed_refs_path().is_file() {
+            return Err(Error::InitialTransactionWithPackedRefs {
+                path: self.store.packed_refs_path(),
+            });
+        }
         if maybe_updates_for_packed_refs.is_some()
             || self.store.packed_refs_path().is_file()
             || self.store.packed_refs_lock_path().is_file()
@@ -324,9 +345,8 @@ impl<'s, 'p> Transaction<'s, 'p> {
                 if let Some(transaction) = packed_transaction {
                     self.packed_transaction = Some(match &mut self.packed_refs {
                         PackedRefs::DeletionsAndNonSymbolicUpdatesRemoveLooseSourceReference(f)
-                        | PackedRefs::DeletionsAndNonSymbolicUpdates(f) => {
-                            transaction.prepare(edits_for_packed_transaction, f)?
-                        }
+                        | PackedRefs::DeletionsAndNonSymbolicUpdates(f)
+                        | PackedRefs::Initial(f) => transaction.prepare(edits_for_packed_transaction, f)?,
                         PackedRefs::DeletionsOnly => transaction
                             .prepare(edits_for_packed_transaction, &mut |_, _| {
                                 unreachable!("BUG: deletions never trigger object lookups")
@@ -346,8 +366,9 @@ impl<'s, 'p> Transaction<'s, 'p> {
                 self.packed_transaction.is_some(),
                 matches!(
                     self.packed_refs,
-                    PackedRefs::DeletionsAndNonSymbolicUpdatesRemoveLooseSourceReference(_)
+                    PackedRefs::DeletionsAndNonSymbolicUpdatesRemoveLooseSourceReference(_) | PackedRefs::Initial(_)
                 ),
+                assume_empty_store,
             ) {
                 let err = match err {
                     Error::LockAcquire {
@@ -387,6 +408,13 @@ impl<'s, 'p> Transaction<'s, 'p> {
                 }
             }
         }
+        if let Some(hook) = &store.transaction_hook {
+            let edits = updates.iter().map(|edit| &edit.update);
+            if let Err(err) = hook.call(Stage::Prepared, edits.clone(), store.object_hash()) {
+                hook.call(Stage::Aborted, edits, store.object_hash()).ok();
+                return Err(Error::Hook(err));
+            }
+        }
         self.updates = Some(updates);
         Ok(self)
     }
@@ -398,11 +426,18 @@ impl<'s, 'p> Transaction<'s, 'p> {
     ///
     /// # Note
     ///
-    /// A rollback happens automatically as this instance is dropped as well.
+    /// A rollback happens automatically as this instance is dropped as well, but without informing the hook.
+    ///
+    /// If a [hook][crate::transaction::hook::Hook] is set and the transaction was prepared, it will be informed that it was aborted.
     pub fn rollback(self) -> Vec<RefEdit> {
-        self.updates
+        let edits: Vec<_> = self
+            .updates
             .map(|updates| updates.into_iter().map(|u| u.update).collect())
-            .unwrap_or_default()
+            .unwrap_or_default();
+        if let Some(hook) = &self.store.transaction_hook {
+            hook.call(Stage::Aborted, &edits, self.store.object_hash()).ok();
+        }
+        edits
     }
 }
 
@@ -472,6 +507,10 @@ mod error {
         },
         #[error("Could not read reference")]
         ReferenceDecode(#[from] file::loose::reference::decode::Error),
+        #[error("An initial transaction can't be performed as there already is a packed-refs file at '{}'", path.display())]
+        InitialTransactionWithPackedRefs { path: std::path::PathBuf },
+        #[error("The reference-transaction hook rejected the transaction")]
+        Hook(#[source] crate::transaction::hook::Error),
     }
 }
 
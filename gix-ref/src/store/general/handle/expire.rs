@@ -0,0 +1,165 @@
+use std::{
+    io::Write,
+    time::{Duration, SystemTime},
+};
+
+use gix_hash::oid;
+
+use crate::{
+    file, log,
+    store::{self, handle},
+    FullNameRef,
+};
+
+/// Configuration for [`Handle::expire_reflog()`][crate::store::Handle::expire_reflog()], similar to the options of
+/// `git reflog expire`.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// Entries created before this point in time are removed, as configured with `gc.reflogExpire`.
+    /// If `None`, entries are never removed due to their age alone.
+    pub expire: Option<SystemTime>,
+    /// Entries created before this point in time are removed if one of their objects isn't reachable from the tip of the
+    /// reference anymore, as configured with `gc.reflogExpireUnreachable`. If `None`, reachability isn't checked.
+    pub expire_unreachable: Option<SystemTime>,
+    /// If `true`, remove entries that refer to objects which don't exist anymore, like `git reflog expire --stale-fix`.
+    pub stale_fix: bool,
+    /// If `true`, determine which entries would be removed without changing the reflog.
+    pub dry_run: bool,
+}
+
+/// The outcome of [`Handle::expire_reflog()`][crate::store::Handle::expire_reflog()].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// The amount of entries which were kept.
+    pub kept: usize,
+    /// The entries which were removed, or would have been removed in a dry run, from oldest to most recent.
+    pub removed: Vec<log::Line>,
+}
+
+impl Options {
+    /// Return `true` if `line` should be removed from the reflog. `is_reachable(id)` returns `true` if `id` can be reached from the
+    /// tip of the reference, and `exists(id)` returns `true` if the object with `id` is present. Null ids are neither checked for
+    /// reachability nor for existence.
+    pub fn should_expire(
+        &self,
+        line: &log::Line,
+        mut is_reachable: impl FnMut(&oid) -> bool,
+        mut exists: impl FnMut(&oid) -> bool,
+    ) -> bool {
+        let time = SystemTime::UNIX_EPOCH + Duration::from_secs(line.signature.time.seconds_since_unix_epoch.into());
+        let is_older_than = |expire: Option<SystemTime>| expire.map_or(false, |expire| time < expire);
+        let ids = [line.previous_oid, line.new_oid];
+        is_older_than(self.expire)
+            || (self.stale_fix && ids.iter().any(|id| !id.is_null() && !exists(id)))
+            || (is_older_than(self.expire_unreachable) && ids.iter().any(|id| !id.is_null() && !is_reachable(id)))
+    }
+}
+
+impl store::Handle {
+    /// Remove all entries from the reflog of `name` according to `options`, and return information about kept and removed entries.
+    ///
+    /// `is_reachable(id)` returns `true` if `id` can be reached from the current tip of the reference, and `exists(id)` returns `true`
+    /// if the object with `id` is present in the object database, which is only used if [`Options::stale_fix`] is set.
+    ///
+    /// The reflog is rewritten atomically while the reference is locked, failing according to `lock_fail_mode` if the lock can't be
+    /// obtained. Nothing is done if there is no reflog for `name`.
+    pub fn expire_reflog(
+        &self,
+        name: &FullNameRef,
+        options: Options,
+        lock_fail_mode: gix_lock::acquire::Fail,
+        mut is_reachable: impl FnMut(&oid) -> bool,
+        mut exists: impl FnMut(&oid) -> bool,
+    ) -> Result<Outcome, Error> {
+        let mut out = Outcome::default();
+        match &self.state {
+            handle::State::Loose { store } => {
+                let path = store.reflog_path(name);
+                // Hold the reference itself to prevent transactions from appending to the log while it is rewritten.
+                let _ref_lock = (!options.dry_run)
+                    .then(|| {
+                        let (base, relative_path) = store.reference_path_with_base(name);
+                        gix_lock::Marker::acquire_to_hold_resource(
+                            base.join(relative_path),
+                            lock_fail_mode,
+                            Some(base.into_owned()),
+                        )
+                    })
+                    .transpose()?;
+                let buf = match std::fs::read(&path) {
+                    Ok(buf) => buf,
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(out),
+                    Err(_) if path.is_dir() => return Ok(out),
+                    Err(err) => return Err(err.into()),
+                };
+                let mut kept = Vec::new();
+                for line in file::log::iter::forward(&buf) {
+                    let line = line?.to_owned();
+                    if options.should_expire(&line, &mut is_reachable, &mut exists) {
+                        out.removed.push(line);
+                    } else {
+                        kept.push(line);
+                    }
+                }
+                out.kept = kept.len();
+                if !options.dry_run && !out.removed.is_empty() {
+                    let mut lock = gix_lock::File::acquire_to_update_resource(&path, lock_fail_mode, None)?;
+                    for line in &kept {
+                        line.write_to(&mut lock)?;
+                    }
+                    lock.flush()?;
+                    lock.commit().map_err(|err| err.error)?;
+                }
+            }
+            handle::State::Reftable { store } => {
+                let logs = match store.stack()? {
+                    Some(stack) => stack.logs_of(name.as_bstr())?,
+                    None => return Ok(out),
+                };
+                let mut removed = Vec::new();
+                for (update_index, line) in logs {
+                    if options.should_expire(&line, &mut is_reachable, &mut exists) {
+                        removed.push(update_index);
+                        out.removed.push(line);
+                    } else {
+                        out.kept += 1;
+                    }
+                }
+                if !options.dry_run && !removed.is_empty() {
+                    // Entries are removed by their position in the history, which keeps entries added in the meantime.
+                    store
+                        .transaction()
+                        .delete_reflog_entries(name.to_owned(), removed)
+                        .prepare(None, lock_fail_mode)?
+                        .commit(None)?;
+                }
+            }
+        }
+        Ok(out)
+    }
+}
+
+mod error {
+    use crate::{file, reftable};
+
+    /// The error returned by [`Handle::expire_reflog()`][crate::store::Handle::expire_reflog()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The reference or its reflog could not be locked")]
+        LockAcquire(#[from] gix_lock::acquire::Error),
+        #[error("The reflog could not be read or written")]
+        Io(#[from] std::io::Error),
+        #[error("A reflog entry could not be parsed")]
+        Decode(#[from] file::log::iter::decode::Error),
+        #[error(transparent)]
+        OpenStack(#[from] reftable::stack::open::Error),
+        #[error("The reflog could not be read from the reftable stack")]
+        DecodeTable(#[from] reftable::table::decode::Error),
+        #[error(transparent)]
+        Prepare(#[from] reftable::transaction::prepare::Error),
+        #[error(transparent)]
+        Commit(#[from] reftable::transaction::commit::Error),
+    }
+}
+pub use error::Error;
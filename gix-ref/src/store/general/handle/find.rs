@@ -5,12 +5,14 @@ use crate::{store, PartialNameRef, Reference};
 mod error {
     use std::convert::Infallible;
 
-    /// The error returned by [crate::file::Store::find_loose()].
+    /// The error returned by [crate::store::Handle::try_find()].
     #[derive(Debug, thiserror::Error)]
     #[allow(missing_docs)]
     pub enum Error {
         #[error("An error occurred while finding a reference in the loose file database")]
         Loose(#[from] crate::file::find::Error),
+        #[error("An error occurred while finding a reference in the reftable")]
+        Reftable(#[from] crate::reftable::find::Error),
         #[error("The ref name or path is not a valid ref name")]
         RefnameValidation(#[from] crate::name::Error),
     }
@@ -27,26 +29,30 @@ pub use error::Error;
 use crate::store::handle;
 
 impl store::Handle {
-    /// TODO: actually implement this with handling of the packed buffer.
+    /// Find a single reference by the given `path` which is required to be a valid reference name.
+    ///
+    /// Returns `Ok(None)` if no such ref exists.
+    ///
+    /// See [`file::Store::try_find()`][crate::file::Store::try_find()] for details on the lookup algorithm.
     pub fn try_find<'a, Name, E>(&self, partial: Name) -> Result<Option<Reference>, Error>
     where
         Name: TryInto<&'a PartialNameRef, Error = E>,
         Error: From<E>,
     {
-        let _name = partial.try_into()?;
-        match &self.state {
-            handle::State::Loose { store: _, .. } => {
-                todo!()
-            }
-        }
+        let name = partial.try_into()?;
+        Ok(match &self.state {
+            handle::State::Loose { store } => store.try_find(name)?,
+            handle::State::Reftable { store } => store.try_find(name)?,
+        })
     }
 }
 
-mod existing {
+///
+pub mod existing {
     mod error {
         use std::path::PathBuf;
 
-        /// The error returned by [file::Store::find_existing()][crate::file::Store::find_existing()].
+        /// The error returned by [crate::store::Handle::find()].
         #[derive(Debug, thiserror::Error)]
         #[allow(missing_docs)]
         pub enum Error {
@@ -55,6 +61,24 @@ mod existing {
             #[error("The ref partially named {name:?} could not be found")]
             NotFound { name: PathBuf },
         }
+
+        impl From<crate::file::find::existing::Error> for Error {
+            fn from(err: crate::file::find::existing::Error) -> Self {
+                match err {
+                    crate::file::find::existing::Error::Find(err) => Error::Find(err.into()),
+                    crate::file::find::existing::Error::NotFound { name } => Error::NotFound { name },
+                }
+            }
+        }
+
+        impl From<crate::reftable::find::existing::Error> for Error {
+            fn from(err: crate::reftable::find::existing::Error) -> Self {
+                match err {
+                    crate::reftable::find::existing::Error::Find(err) => Error::Find(err.into()),
+                    crate::reftable::find::existing::Error::NotFound { name } => Error::NotFound { name },
+                }
+            }
+        }
     }
 
     use std::convert::TryInto;
@@ -64,19 +88,22 @@ mod existing {
     use crate::{store, PartialNameRef, Reference};
 
     impl store::Handle {
-        /// Similar to [`crate::file::Store::find()`] but a non-existing ref is treated as error.
-        pub fn find<'a, Name, E>(&self, _partial: Name) -> Result<Reference, Error>
+        /// Similar to [`crate::store::Handle::try_find()`] but a non-existing ref is treated as error.
+        pub fn find<'a, Name, E>(&self, partial: Name) -> Result<Reference, Error>
         where
             Name: TryInto<&'a PartialNameRef, Error = E>,
             crate::name::Error: From<E>,
         {
-            todo!()
-            // match self.try_find(partial) {}
-            // match self.find_one_with_verified_input(path.to_partial_path().as_ref(), packed) {
-            //     Ok(Some(r)) => Ok(r),
-            //     Ok(None) => Err(Error::NotFound(path.to_partial_path().into_owned())),
-            //     Err(err) => Err(err.into()),
-            // }
+            let path = partial
+                .try_into()
+                .map_err(|err| Error::Find(store::find::Error::RefnameValidation(err.into())))?;
+            match self.try_find(path) {
+                Ok(Some(r)) => Ok(r),
+                Ok(None) => Err(Error::NotFound {
+                    name: path.to_partial_path().to_owned(),
+                }),
+                Err(err) => Err(err.into()),
+            }
         }
     }
 }
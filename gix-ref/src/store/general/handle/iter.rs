@@ -0,0 +1,106 @@
+use std::path::Path;
+
+use crate::{file, packed, reftable, store, store::handle, Reference};
+
+/// A platform to obtain iterators over all references, or prefixed ones, sorted by their name.
+#[must_use = "Iterators should be obtained from this iterator platform"]
+pub struct Platform<'s> {
+    inner: PlatformInner<'s>,
+}
+
+enum PlatformInner<'s> {
+    Loose(file::iter::Platform<'s>),
+    Reftable(&'s reftable::Store),
+}
+
+/// An iterator over references, sorted by their name.
+pub struct Iter<'s> {
+    inner: IterInner<'s>,
+}
+
+#[allow(clippy::large_enum_variant)]
+enum IterInner<'s> {
+    Loose(file::iter::LooseThenPacked<'s, 's>),
+    Reftable(reftable::iter::Iter),
+}
+
+impl store::Handle {
+    /// Return a platform to obtain iterators over all references, or prefixed ones, sorted by their name.
+    ///
+    /// Errors are returned similarly to what would happen when loose and packed refs where iterated by themselves.
+    pub fn iter(&self) -> Result<Platform<'_>, packed::buffer::open::Error> {
+        Ok(Platform {
+            inner: match &self.state {
+                handle::State::Loose { store } => PlatformInner::Loose(store.iter()?),
+                handle::State::Reftable { store } => PlatformInner::Reftable(store),
+            },
+        })
+    }
+}
+
+impl<'s> Platform<'s> {
+    /// Return an iterator over all references below `refs/`, sorted by their name.
+    ///
+    /// Pseudo-references like `HEAD` are never returned, no matter how references are stored.
+    pub fn all(&self) -> Result<Iter<'_>, init::Error> {
+        Ok(Iter {
+            inner: match &self.inner {
+                PlatformInner::Loose(platform) => IterInner::Loose(platform.all()?),
+                PlatformInner::Reftable(store) => IterInner::Reftable(store.iter_prefixed("refs/")?),
+            },
+        })
+    }
+
+    /// Return an iterator over all references that match the given `prefix`, i.e. `refs/heads`.
+    ///
+    /// Note that `refs/heads` is equivalent to `refs/heads/` for references stored in files, whereas reftables match
+    /// the prefix byte by byte.
+    pub fn prefixed(&self, prefix: impl AsRef<Path>) -> Result<Iter<'_>, init::Error> {
+        Ok(Iter {
+            inner: match &self.inner {
+                PlatformInner::Loose(platform) => IterInner::Loose(platform.prefixed(prefix)?),
+                PlatformInner::Reftable(store) => {
+                    let prefix = gix_path::to_unix_separators_on_windows(gix_path::into_bstr(prefix.as_ref()));
+                    IterInner::Reftable(store.iter_prefixed(prefix.into_owned())?)
+                }
+            },
+        })
+    }
+}
+
+impl<'s> Iterator for Iter<'s> {
+    type Item = Result<Reference, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.inner {
+            IterInner::Loose(iter) => iter.next().map(|res| res.map_err(Into::into)),
+            IterInner::Reftable(iter) => iter.next().map(|res| res.map_err(Into::into)),
+        }
+    }
+}
+
+///
+pub mod init {
+    /// The error returned by [`Platform::all()`][super::Platform::all()] or [`Platform::prefixed()`][super::Platform::prefixed()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Io(#[from] std::io::Error),
+        #[error(transparent)]
+        Reftable(#[from] crate::reftable::iter::Error),
+    }
+}
+
+mod error {
+    /// The error returned by the [`Iter`][super::Iter] iterator.
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Loose(#[from] crate::file::iter::loose_then_packed::Error),
+        #[error("A reference could not be read from its table")]
+        Reftable(#[from] crate::reftable::table::decode::Error),
+    }
+}
+pub use error::Error;
@@ -0,0 +1,130 @@
+use std::io::{Read, Seek, SeekFrom};
+
+use crate::{
+    file::{self, log::iter},
+    store::{self, handle},
+    FullNameRef,
+};
+
+/// A platform to store a buffer to hold ref log lines for iteration, independently of the format the log is stored in.
+#[must_use = "Iterators should be obtained from this platform"]
+pub struct Platform<'a, 's> {
+    /// The store containing the reflogs
+    pub store: &'s store::Handle,
+    /// The full name of the reference whose reflog to retrieve.
+    pub name: &'a FullNameRef,
+    /// A reusable buffer for storing log lines read from disk.
+    pub buf: Vec<u8>,
+}
+
+/// The source of log lines for reverse iteration, which are either read from a file or from memory.
+pub enum Reader {
+    /// The reflog is read from a file.
+    File(std::fs::File),
+    /// The reflog was serialized into memory as it can't be read from a file directly.
+    InMemory(std::io::Cursor<Vec<u8>>),
+}
+
+impl Read for Reader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            Reader::File(file) => file.read(buf),
+            Reader::InMemory(cursor) => cursor.read(buf),
+        }
+    }
+}
+
+impl Seek for Reader {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        match self {
+            Reader::File(file) => file.seek(pos),
+            Reader::InMemory(cursor) => cursor.seek(pos),
+        }
+    }
+}
+
+impl store::Handle {
+    /// A step towards obtaining forward or reverse iterators on the reference log of `name`.
+    pub fn log_iter<'a>(&self, name: &'a FullNameRef) -> Platform<'a, '_> {
+        Platform {
+            store: self,
+            name,
+            buf: Vec::new(),
+        }
+    }
+
+    /// Returns true if a reflog exists for the given reference `name`.
+    pub fn reflog_exists(&self, name: &FullNameRef) -> std::io::Result<bool> {
+        match &self.state {
+            handle::State::Loose { store } => Ok(store.reflog_exists(name).expect("infallible name conversion")),
+            handle::State::Reftable { store } => store.reflog_exists(name).map_err(into_io_err),
+        }
+    }
+}
+
+impl<'a, 's> Platform<'a, 's> {
+    /// Return a reverse iterator over all log-lines, most recent to oldest.
+    pub fn rev(&mut self) -> std::io::Result<Option<iter::Reverse<'_, Reader>>> {
+        let reader = match &self.store.state {
+            handle::State::Loose { store } => match loose_reflog(store, self.name)? {
+                Some(file) => Reader::File(file),
+                None => return Ok(None),
+            },
+            handle::State::Reftable { store } => {
+                let mut lines = Vec::new();
+                if !serialize_reftable_log(store, self.name, &mut lines)? {
+                    return Ok(None);
+                }
+                Reader::InMemory(std::io::Cursor::new(lines))
+            }
+        };
+        self.buf.clear();
+        self.buf.resize(512, 0);
+        Ok(Some(iter::reverse(reader, &mut self.buf)?))
+    }
+
+    /// Return a forward iterator over all log-lines, oldest to most recent.
+    pub fn all(&mut self) -> std::io::Result<Option<iter::Forward<'_>>> {
+        self.buf.clear();
+        match &self.store.state {
+            handle::State::Loose { store } => store
+                .reflog_iter(self.name, &mut self.buf)
+                .map_err(file::loose::reference::logiter::must_be_io_err),
+            handle::State::Reftable { store } => {
+                Ok(serialize_reftable_log(store, self.name, &mut self.buf)?.then(|| iter::forward(&self.buf)))
+            }
+        }
+    }
+}
+
+fn loose_reflog(store: &file::Store, name: &FullNameRef) -> std::io::Result<Option<std::fs::File>> {
+    let path = store.reflog_path(name);
+    if path.is_dir() {
+        return Ok(None);
+    }
+    match std::fs::File::open(path) {
+        Ok(file) => Ok(Some(file)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Write all log lines of `name` in `store` into `out` in the format used by reflog files, and return `false` if there is no log.
+fn serialize_reftable_log(
+    store: &crate::reftable::Store,
+    name: &FullNameRef,
+    out: &mut Vec<u8>,
+) -> std::io::Result<bool> {
+    let lines = match store.reflog(name).map_err(into_io_err)? {
+        Some(lines) => lines,
+        None => return Ok(false),
+    };
+    for line in lines {
+        line.write_to(&mut *out)?;
+    }
+    Ok(true)
+}
+
+fn into_io_err(err: crate::reftable::reflog::Error) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::Other, err)
+}
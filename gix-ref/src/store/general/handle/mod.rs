@@ -1,43 +1,192 @@
-#![allow(dead_code)]
-use crate::{store, Namespace};
+use std::path::Path;
 
-#[derive(Clone)]
+use crate::{
+    file, reftable,
+    store::{self, Format, WriteReflog},
+    transaction::hook::Hook,
+    Namespace,
+};
+
+#[derive(Clone, Debug)]
 pub(crate) enum State {
-    Loose { store: crate::file::Store },
+    Loose { store: file::Store },
+    Reftable { store: reftable::Store },
 }
 
+#[allow(dead_code)]
 impl crate::Store {
     /// Return a new handle which sees all references if `namespace` is `None` or all read and write operations are limited
     /// to the given `namespace` if `Some`.
     pub fn to_handle(&self) -> store::Handle {
-        Self::new_handle_inner(&self.inner, None)
+        Self::new_handle_inner(&self.inner)
+    }
+
+    /// As above, but supports a namespace to be set, which fails if the references are stored in reftables.
+    pub fn to_handle_namespaced(
+        &self,
+        namespace: Option<Namespace>,
+    ) -> Result<store::Handle, set_namespace::Error> {
+        let mut handle = Self::new_handle_inner(&self.inner);
+        if let Some(namespace) = namespace {
+            handle.set_namespace(namespace)?;
+        }
+        Ok(handle)
     }
 
-    /// As above, but supports a namespace to be set
-    pub fn to_handle_namespaced(&self, namespace: Option<Namespace>) -> store::Handle {
-        Self::new_handle_inner(&self.inner, namespace)
+    fn new_handle_inner(state: &store::State) -> store::Handle {
+        match state {
+            store::State::Loose { store } => store.clone().into(),
+            store::State::Reftable { store } => store.clone().into(),
+        }
+    }
+}
+
+impl From<file::Store> for store::Handle {
+    fn from(store: file::Store) -> Self {
+        store::Handle {
+            state: State::Loose { store },
+        }
     }
+}
 
-    fn new_handle_inner(state: &store::State, namespace: Option<Namespace>) -> store::Handle {
+impl From<reftable::Store> for store::Handle {
+    fn from(store: reftable::Store) -> Self {
         store::Handle {
-            state: match state {
-                store::State::Loose { store } => store::handle::State::Loose {
-                    store: {
-                        let mut store = store.clone();
-                        store.namespace = namespace;
-                        store
-                    },
-                },
-            },
+            state: State::Reftable { store },
+        }
+    }
+}
+
+/// Access
+impl store::Handle {
+    /// Return the format in which references are stored.
+    pub fn format(&self) -> Format {
+        match &self.state {
+            State::Loose { .. } => Format::Files,
+            State::Reftable { .. } => Format::Reftable,
+        }
+    }
+
+    /// Return the underlying store if references are stored in files.
+    pub fn as_file(&self) -> Option<&file::Store> {
+        match &self.state {
+            State::Loose { store } => Some(store),
+            State::Reftable { .. } => None,
+        }
+    }
+
+    /// Return the underlying store if references are stored in reftables.
+    pub fn as_reftable(&self) -> Option<&reftable::Store> {
+        match &self.state {
+            State::Reftable { store } => Some(store),
+            State::Loose { .. } => None,
+        }
+    }
+
+    /// Return the `.git` directory of the repository whose references are handled.
+    pub fn git_dir(&self) -> &Path {
+        match &self.state {
+            State::Loose { store } => store.git_dir(),
+            State::Reftable { store } => store.git_dir(),
+        }
+    }
+
+    /// Return the kind of hash used for all object ids.
+    pub fn object_hash(&self) -> gix_hash::Kind {
+        match &self.state {
+            State::Loose { store } => store.object_hash(),
+            State::Reftable { store } => store.object_hash(),
+        }
+    }
+
+    /// Return the way reflogs are written when editing references.
+    pub fn write_reflog(&self) -> WriteReflog {
+        match &self.state {
+            State::Loose { store } => store.write_reflog,
+            State::Reftable { store } => store.write_reflog,
+        }
+    }
+
+    /// Set the way reflogs are written when editing references to `mode`.
+    pub fn set_write_reflog(&mut self, mode: WriteReflog) {
+        match &mut self.state {
+            State::Loose { store } => store.write_reflog = mode,
+            State::Reftable { store } => store.write_reflog = mode,
+        }
+    }
+
+    /// Return the namespace all operations are limited to, if set.
+    pub fn namespace(&self) -> Option<&Namespace> {
+        match &self.state {
+            State::Loose { store } => store.namespace.as_ref(),
+            State::Reftable { .. } => None,
+        }
+    }
+
+    /// Set the `namespace` to limit all operations to and return the previous one.
+    ///
+    /// Namespaces aren't supported by reftables yet, which is why it's an error to set one there.
+    pub fn set_namespace(&mut self, namespace: Namespace) -> Result<Option<Namespace>, set_namespace::Error> {
+        match &mut self.state {
+            State::Loose { store } => Ok(store.namespace.replace(namespace)),
+            State::Reftable { .. } => Err(set_namespace::Error::Reftable),
+        }
+    }
+
+    /// Remove the namespace all operations are limited to and return it, if it was set.
+    pub fn clear_namespace(&mut self) -> Option<Namespace> {
+        match &mut self.state {
+            State::Loose { store } => store.namespace.take(),
+            State::Reftable { .. } => None,
+        }
+    }
+
+    /// Return the hook called at each stage of all transactions, if set.
+    pub fn transaction_hook(&self) -> Option<&Hook> {
+        match &self.state {
+            State::Loose { store } => store.transaction_hook.as_ref(),
+            State::Reftable { store } => store.transaction_hook.as_ref(),
+        }
+    }
+
+    /// Set the `hook` to call at each stage of all transactions, or remove it if `None`, and return the previous one.
+    pub fn set_transaction_hook(&mut self, hook: Option<Hook>) -> Option<Hook> {
+        match &mut self.state {
+            State::Loose { store } => std::mem::replace(&mut store.transaction_hook, hook),
+            State::Reftable { store } => std::mem::replace(&mut store.transaction_hook, hook),
         }
     }
 }
 
+///
+pub mod expire;
+
 ///
 pub mod find;
 
-mod iter {
-    // impl store::Handle {
-    //     pub fn iter<'p, 's>(&'s self, packed: Option<&'p packed::Buffer>) -> std::io::Result<LooseThenPacked<'p, 's>> {
-    // }
+///
+pub mod iter;
+
+///
+pub mod log;
+
+///
+pub mod peel;
+
+///
+pub mod rename;
+
+///
+pub mod transaction;
+
+///
+pub mod set_namespace {
+    /// The error returned by [`Handle::set_namespace()`][crate::store::Handle::set_namespace()] and
+    /// [`Store::to_handle_namespaced()`][crate::Store::to_handle_namespaced()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Reference namespaces are not supported by reftables yet")]
+        Reftable,
+    }
 }
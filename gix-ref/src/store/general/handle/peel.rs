@@ -0,0 +1,69 @@
+use std::collections::BTreeSet;
+
+use gix_hash::ObjectId;
+
+use crate::{
+    file::{self, ReferenceExt},
+    peel,
+    store::{self, handle},
+    Reference, Target,
+};
+
+impl store::Handle {
+    /// Follow all symbolic targets of `r` and peel the final object id to the first non-tag object using `find`,
+    /// storing the result in `r` and returning it.
+    ///
+    /// See [`ReferenceExt::peel_to_id_in_place()`] for details.
+    pub fn peel_to_id_in_place<E: std::error::Error + Send + Sync + 'static>(
+        &self,
+        r: &mut Reference,
+        find: impl FnMut(ObjectId, &mut Vec<u8>) -> Result<Option<(gix_object::Kind, &[u8])>, E>,
+    ) -> Result<ObjectId, peel::to_id::Error> {
+        let store = match &self.state {
+            handle::State::Loose { store } => return r.peel_to_id_in_place(store, find),
+            handle::State::Reftable { store } => store,
+        };
+        if let Some(peeled) = r.peeled {
+            r.target = Target::Peeled(peeled);
+            return Ok(peeled);
+        }
+        if r.target.kind() == crate::Kind::Symbolic {
+            let mut seen = BTreeSet::new();
+            while let Target::Symbolic(name) = &r.target {
+                let next = store.find(name.as_ref())?;
+                if seen.contains(&next.name) {
+                    return Err(peel::to_id::Error::Cycle {
+                        start_absolute: store.path().join(r.name.to_path()),
+                    });
+                }
+                *r = next;
+                seen.insert(r.name.clone());
+                const MAX_REF_DEPTH: usize = 5;
+                if seen.len() == MAX_REF_DEPTH {
+                    return Err(peel::to_id::Error::DepthLimitExceeded {
+                        max_depth: MAX_REF_DEPTH,
+                    });
+                }
+            }
+        }
+        file::peel_object_in_place(r, find)
+    }
+
+    /// Follow the symbolic reference `r` one level and return the reference it refers to.
+    ///
+    /// Returns `None` if `r` is not a symbolic reference, hence the leaf of the chain.
+    pub fn follow(&self, r: &Reference) -> Option<Result<Reference, store::find::existing::Error>> {
+        match &self.state {
+            handle::State::Loose { store } => r.follow(store).map(|res| res.map_err(Into::into)),
+            handle::State::Reftable { .. } => match (&r.peeled, &r.target) {
+                (Some(peeled), _) => Some(Ok(Reference {
+                    name: r.name.clone(),
+                    target: Target::Peeled(*peeled),
+                    peeled: None,
+                })),
+                (None, Target::Peeled(_)) => None,
+                (None, Target::Symbolic(name)) => Some(self.find(name.as_ref())),
+            },
+        }
+    }
+}
@@ -0,0 +1,264 @@
+use gix_object::bstr::BString;
+
+use crate::{
+    store::{self, handle, transaction},
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    FullName, FullNameRef, Target,
+};
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Mode {
+    Rename,
+    Copy,
+}
+
+impl store::Handle {
+    /// Rename the reference `source` to `destination` in a single transaction and return the performed edits, writing `message`
+    /// into the reflog of `destination` and failing according to `lock_fail_mode` if a lock can't be obtained.
+    ///
+    /// `destination` must not exist yet. All reflog entries of `source` are moved to `destination`, and symbolic references
+    /// like `HEAD`, including the `HEAD` of all other worktrees, which pointed to `source` will point to `destination` afterwards.
+    /// `committer` is used for reflog entries, see [`Transaction::commit()`][store::transaction::Transaction::commit()].
+    pub fn rename_reference<'a>(
+        &self,
+        source: &FullNameRef,
+        destination: FullName,
+        message: impl Into<BString>,
+        lock_fail_mode: gix_lock::acquire::Fail,
+        committer: impl Into<Option<gix_actor::SignatureRef<'a>>>,
+    ) -> Result<Vec<RefEdit>, Error> {
+        self.copy_or_rename(
+            Mode::Rename,
+            source,
+            destination,
+            message.into(),
+            lock_fail_mode,
+            committer.into(),
+        )
+    }
+
+    /// Like [`rename_reference()`][store::Handle::rename_reference()], but keeps `source` and its reflog as is, while
+    /// `destination` receives a copy of both.
+    ///
+    /// Symbolic references pointing to `source` are left unchanged.
+    pub fn copy_reference<'a>(
+        &self,
+        source: &FullNameRef,
+        destination: FullName,
+        message: impl Into<BString>,
+        lock_fail_mode: gix_lock::acquire::Fail,
+        committer: impl Into<Option<gix_actor::SignatureRef<'a>>>,
+    ) -> Result<Vec<RefEdit>, Error> {
+        self.copy_or_rename(
+            Mode::Copy,
+            source,
+            destination,
+            message.into(),
+            lock_fail_mode,
+            committer.into(),
+        )
+    }
+
+    fn copy_or_rename(
+        &self,
+        mode: Mode,
+        source: &FullNameRef,
+        destination: FullName,
+        message: BString,
+        lock_fail_mode: gix_lock::acquire::Fail,
+        committer: Option<gix_actor::SignatureRef<'_>>,
+    ) -> Result<Vec<RefEdit>, Error> {
+        if source == destination.as_ref() {
+            return Err(Error::SameName {
+                name: destination.into_inner(),
+            });
+        }
+        let existing = self
+            .try_find(source)?
+            .filter(|r| r.name.as_ref() == source)
+            .ok_or_else(|| Error::NotFound {
+                name: source.as_bstr().to_owned(),
+            })?;
+        // Transactions accept existing references with the desired value, which would still lose the reflog of `destination`.
+        if self.try_find(destination.as_ref())?.is_some() {
+            return Err(Error::DestinationExists {
+                name: destination.into_inner(),
+            });
+        }
+        let has_reflog = self.reflog_exists(source).map_err(Error::ReadReflog)?;
+
+        let mut edits = vec![RefEdit {
+            change: Change::Update {
+                log: LogChange {
+                    mode: RefLog::AndReference,
+                    force_create_reflog: has_reflog,
+                    message: message.clone(),
+                },
+                expected: PreviousValue::MustNotExist,
+                new: existing.target.clone(),
+            },
+            name: destination.clone(),
+            deref: false,
+        }];
+        if mode == Mode::Rename {
+            edits.push(RefEdit {
+                change: Change::Delete {
+                    expected: PreviousValue::MustExistAndMatch(existing.target),
+                    log: RefLog::AndReference,
+                },
+                name: source.to_owned(),
+                deref: false,
+            });
+            for name in self.symbolic_references_to(source)? {
+                edits.push(RefEdit {
+                    change: Change::Update {
+                        log: LogChange {
+                            message: message.clone(),
+                            ..Default::default()
+                        },
+                        expected: PreviousValue::MustExistAndMatch(Target::Symbolic(source.to_owned())),
+                        new: Target::Symbolic(destination.clone()),
+                    },
+                    name,
+                    deref: false,
+                });
+            }
+        }
+
+        Ok(match &self.state {
+            handle::State::Loose { store } => {
+                let transaction = store
+                    .transaction()
+                    .prepare(edits, lock_fail_mode, lock_fail_mode)
+                    .map_err(transaction::prepare::Error::from)?;
+                let copied_log = if has_reflog {
+                    // The destination is locked, so we can place its reflog before the rename is logged into it.
+                    let destination_log = store.reflog_path(destination.as_ref());
+                    if let Some(parent) = destination_log.parent() {
+                        std::fs::create_dir_all(parent).map_err(Error::CopyReflog)?;
+                    }
+                    if let Err(err) = std::fs::copy(store.reflog_path(source), &destination_log) {
+                        std::fs::remove_file(&destination_log).ok();
+                        return Err(Error::CopyReflog(err));
+                    }
+                    Some(destination_log)
+                } else {
+                    None
+                };
+                match transaction.commit(committer) {
+                    Ok(edits) => edits,
+                    Err(err) => {
+                        // The destination didn't exist, so the reflog we placed for it would otherwise be left behind.
+                        if let Some(destination_log) = copied_log {
+                            std::fs::remove_file(destination_log).ok();
+                        }
+                        return Err(transaction::commit::Error::from(err).into());
+                    }
+                }
+            }
+            handle::State::Reftable { store } => store
+                .transaction()
+                .copy_reflog(source.to_owned(), destination)
+                .prepare(edits, lock_fail_mode)
+                .map_err(transaction::prepare::Error::from)?
+                .commit(committer)
+                .map_err(transaction::commit::Error::from)?,
+        })
+    }
+
+    /// Return the names of all symbolic references, including `HEAD`, which point to `name` directly.
+    ///
+    /// References which can't be read are ignored as they can't point to `name` in a meaningful way.
+    fn symbolic_references_to(&self, name: &FullNameRef) -> Result<Vec<FullName>, Error> {
+        let points_to_name = |r: &crate::Reference| r.target.try_name() == Some(name);
+        let mut names: Vec<_> = self
+            .try_find("HEAD")?
+            .filter(points_to_name)
+            .map(|r| r.name)
+            .into_iter()
+            .collect();
+        for head in self.other_worktree_heads()? {
+            names.extend(self.try_find(head.as_ref())?.filter(points_to_name).map(|r| r.name));
+        }
+        names.extend(
+            self.iter()?
+                .all()?
+                .filter_map(Result::ok)
+                .filter(points_to_name)
+                .map(|r| r.name),
+        );
+        Ok(names)
+    }
+
+    /// Return the names of the `HEAD` references of all worktrees but our own, like `main-worktree/HEAD` and `worktrees/<id>/HEAD`,
+    /// as git moves these along with the branch they point to as well.
+    fn other_worktree_heads(&self) -> Result<Vec<FullName>, Error> {
+        let store = match &self.state {
+            handle::State::Loose { store } => store,
+            // Linked worktrees aren't supported with reftables.
+            handle::State::Reftable { .. } => return Ok(Vec::new()),
+        };
+        let mut out = Vec::new();
+        if store.common_dir().is_some() {
+            out.push(FullName::try_from("main-worktree/HEAD").expect("valid"));
+        }
+        let entries = match std::fs::read_dir(store.common_dir_resolved().join("worktrees")) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(out),
+            Err(err) => return Err(Error::ListWorktrees(err)),
+        };
+        let own_git_dir = store.git_dir().canonicalize().ok();
+        for entry in entries {
+            let entry = entry.map_err(Error::ListWorktrees)?;
+            let is_own_worktree = own_git_dir.is_some() && entry.path().canonicalize().ok() == own_git_dir;
+            if is_own_worktree {
+                continue;
+            }
+            if let Some(head) = entry
+                .file_name()
+                .to_str()
+                .and_then(|id| FullName::try_from(format!("worktrees/{id}/HEAD")).ok())
+            {
+                out.push(head);
+            }
+        }
+        out.sort();
+        Ok(out)
+    }
+}
+
+mod error {
+    use gix_object::bstr::BString;
+
+    use crate::store;
+
+    /// The error returned by [`Handle::rename_reference()`][crate::store::Handle::rename_reference()] and
+    /// [`Handle::copy_reference()`][crate::store::Handle::copy_reference()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Reference {name:?} can't be renamed or copied onto itself")]
+        SameName { name: BString },
+        #[error("The reference {name:?} to rename or copy did not exist")]
+        NotFound { name: BString },
+        #[error("The reference {name:?} to rename or copy to exists already")]
+        DestinationExists { name: BString },
+        #[error(transparent)]
+        Find(#[from] store::find::Error),
+        #[error(transparent)]
+        PackedOpen(#[from] crate::packed::buffer::open::Error),
+        #[error(transparent)]
+        IterInit(#[from] store::iter::init::Error),
+        #[error("The reflog of the reference to rename or copy could not be read")]
+        ReadReflog(#[source] std::io::Error),
+        #[error("The linked worktrees could not be listed to update their HEAD")]
+        ListWorktrees(#[source] std::io::Error),
+        #[error("The reflog could not be copied to its new location")]
+        CopyReflog(#[source] std::io::Error),
+        #[error(transparent)]
+        Prepare(#[from] store::transaction::prepare::Error),
+        #[error(transparent)]
+        Commit(#[from] store::transaction::commit::Error),
+    }
+}
+pub use error::Error;
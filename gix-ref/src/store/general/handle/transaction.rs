@@ -0,0 +1,109 @@
+use crate::{
+    file::{self, transaction::PackedRefs},
+    reftable,
+    store::{self, handle},
+    transaction::RefEdit,
+};
+
+/// A transaction on the store a [handle][store::Handle] refers to, independently of the format references are stored in.
+pub struct Transaction<'s, 'p> {
+    inner: Inner<'s, 'p>,
+}
+
+enum Inner<'s, 'p> {
+    Loose(file::Transaction<'s, 'p>),
+    Reftable(reftable::Transaction<'s>),
+}
+
+impl store::Handle {
+    /// Open a transaction to apply edits to references atomically, as far as the underlying store permits.
+    ///
+    /// See [`file::Store::transaction()`] and [`reftable::Store::transaction()`] for details.
+    pub fn transaction(&self) -> Transaction<'_, '_> {
+        Transaction {
+            inner: match &self.state {
+                handle::State::Loose { store } => Inner::Loose(store.transaction()),
+                handle::State::Reftable { store } => Inner::Reftable(store.transaction()),
+            },
+        }
+    }
+}
+
+impl<'s, 'p> Transaction<'s, 'p> {
+    /// Configure the way packed refs are handled during the transaction.
+    ///
+    /// This has no effect if references are stored in reftables.
+    pub fn packed_refs(self, packed_refs: PackedRefs<'p>) -> Self {
+        Transaction {
+            inner: match self.inner {
+                Inner::Loose(t) => Inner::Loose(t.packed_refs(packed_refs)),
+                Inner::Reftable(t) => Inner::Reftable(t),
+            },
+        }
+    }
+
+    /// Prepare for calling [`commit(…)`][Transaction::commit()] in a way that can be rolled back perfectly.
+    ///
+    /// Reftables are locked according to `ref_files_lock_fail_mode`, whereas `packed_refs_lock_fail_mode` only affects
+    /// references stored in files.
+    pub fn prepare(
+        self,
+        edits: impl IntoIterator<Item = RefEdit>,
+        ref_files_lock_fail_mode: gix_lock::acquire::Fail,
+        packed_refs_lock_fail_mode: gix_lock::acquire::Fail,
+    ) -> Result<Self, prepare::Error> {
+        Ok(Transaction {
+            inner: match self.inner {
+                Inner::Loose(t) => {
+                    Inner::Loose(t.prepare(edits, ref_files_lock_fail_mode, packed_refs_lock_fail_mode)?)
+                }
+                Inner::Reftable(t) => Inner::Reftable(t.prepare(edits, ref_files_lock_fail_mode)?),
+            },
+        })
+    }
+
+    /// Make all [prepared][Transaction::prepare()] edits permanent and return them with their previous values filled in.
+    pub fn commit<'a>(
+        self,
+        committer: impl Into<Option<gix_actor::SignatureRef<'a>>>,
+    ) -> Result<Vec<RefEdit>, commit::Error> {
+        Ok(match self.inner {
+            Inner::Loose(t) => t.commit(committer)?,
+            Inner::Reftable(t) => t.commit(committer)?,
+        })
+    }
+
+    /// Rollback all intermediate state and return the `RefEdits` as we know them thus far.
+    pub fn rollback(self) -> Vec<RefEdit> {
+        match self.inner {
+            Inner::Loose(t) => t.rollback(),
+            Inner::Reftable(t) => t.rollback(),
+        }
+    }
+}
+
+///
+pub mod prepare {
+    /// The error returned by [`Transaction::prepare()`][super::Transaction::prepare()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Loose(#[from] crate::file::transaction::prepare::Error),
+        #[error(transparent)]
+        Reftable(#[from] crate::reftable::transaction::prepare::Error),
+    }
+}
+
+///
+pub mod commit {
+    /// The error returned by [`Transaction::commit()`][super::Transaction::commit()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Loose(#[from] crate::file::transaction::commit::Error),
+        #[error(transparent)]
+        Reftable(#[from] crate::reftable::transaction::commit::Error),
+    }
+}
@@ -0,0 +1,309 @@
+use std::borrow::Cow;
+
+use gix_features::zlib;
+
+use crate::store_impl::reftable::{
+    format::{
+        be16, be24, get_varint, put_be24, put_varint, BLOCK_TYPE_INDEX, BLOCK_TYPE_LOG, BLOCK_TYPE_REF,
+        RESTART_INTERVAL,
+    },
+    record::{IndexRecord, LogRecord, Record, RefRecord},
+    table::decode::Error,
+};
+
+/// A single decoded block of a table, with the data of log blocks being decompressed.
+pub(crate) struct Block<'a> {
+    pub typ: u8,
+    /// The data of the block starting at its first byte, which includes the file header for the first block of a table,
+    /// up to and including the restart table.
+    data: Cow<'a, [u8]>,
+    records_start: usize,
+    restarts_start: usize,
+    restart_count: usize,
+    /// The amount of bytes the block occupies in the table, including padding or compression, to find the next block.
+    pub len_in_table: usize,
+    min_update_index: u64,
+    object_hash: gix_hash::Kind,
+}
+
+/// A position within a block along with the key of the previous record, which is needed to decode prefix-compressed keys.
+#[derive(Clone, Debug)]
+pub(crate) struct Cursor {
+    pos: usize,
+    pub key: Vec<u8>,
+}
+
+impl<'a> Block<'a> {
+    /// Decode the block at the beginning of `data`, with `header_len` bytes of file header before the block type.
+    ///
+    /// `block_size` is the block size of the table, used to skip padding.
+    pub fn from_bytes(
+        data: &'a [u8],
+        header_len: usize,
+        block_size: usize,
+        min_update_index: u64,
+        object_hash: gix_hash::Kind,
+    ) -> Result<Self, Error> {
+        let records_start = header_len + 4;
+        let header = data
+            .get(header_len..records_start)
+            .ok_or(Error::Corrupt("block header"))?;
+        let typ = header[0];
+        let block_len = be24(&header[1..]) as usize;
+        if block_len < records_start + 2 {
+            return Err(Error::Corrupt("block length"));
+        }
+        let (data, len_in_table) = if typ == BLOCK_TYPE_LOG {
+            let mut inflated = Vec::with_capacity(block_len + 1);
+            inflated.extend_from_slice(&data[..records_start]);
+            inflated.resize(block_len + 1, 0);
+            let compressed_len = inflate(&data[records_start..], &mut inflated[records_start..])?;
+            inflated.pop();
+            (Cow::Owned(inflated), records_start + compressed_len)
+        } else {
+            let block = data.get(..block_len).ok_or(Error::Corrupt("truncated block"))?;
+            let is_padded = block_len < block_size && data.len() >= block_size && data[block_len] == 0;
+            (Cow::Borrowed(block), if is_padded { block_size } else { block_len })
+        };
+        let restart_count = be16(&data[data.len() - 2..]) as usize;
+        let restarts_start = data
+            .len()
+            .checked_sub(2 + 3 * restart_count)
+            .filter(|start| *start >= records_start)
+            .ok_or(Error::Corrupt("restart table"))?;
+        Ok(Block {
+            typ,
+            data,
+            records_start,
+            restarts_start,
+            restart_count,
+            len_in_table,
+            min_update_index,
+            object_hash,
+        })
+    }
+
+    /// Return a cursor to the first record of the block.
+    pub fn cursor(&self) -> Cursor {
+        Cursor {
+            pos: self.records_start,
+            key: Vec::new(),
+        }
+    }
+
+    /// Return a cursor to the first record whose key is equal or greater than `key`, which is at the end of the
+    /// block if there is no such record.
+    pub fn seek(&self, key: &[u8]) -> Result<Cursor, Error> {
+        let (mut lo, mut hi) = (0, self.restart_count);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if self.restart_key(mid)? > key {
+                hi = mid;
+            } else {
+                lo = mid + 1;
+            }
+        }
+        let mut cursor = Cursor {
+            pos: if lo == 0 {
+                self.records_start
+            } else {
+                self.restart_offset(lo - 1)?
+            },
+            key: Vec::new(),
+        };
+        loop {
+            let previous = cursor.clone();
+            match self.next(&mut cursor)? {
+                Some(_) if cursor.key.as_slice() < key => continue,
+                Some(_) => return Ok(previous),
+                None => return Ok(cursor),
+            }
+        }
+    }
+
+    /// Decode the record at `cursor` and advance it, or return `None` if there are no more records in this block.
+    pub fn next(&self, cursor: &mut Cursor) -> Result<Option<Record>, Error> {
+        if cursor.pos >= self.restarts_start {
+            return Ok(None);
+        }
+        let data = &self.data[cursor.pos..self.restarts_start];
+        let (prefix_len, mut consumed) = get_varint(data).ok_or(Error::Corrupt("record key prefix"))?;
+        let (suffix_and_type, len) = get_varint(&data[consumed..]).ok_or(Error::Corrupt("record key suffix"))?;
+        consumed += len;
+        let (prefix_len, suffix_len) = (prefix_len as usize, (suffix_and_type >> 3) as usize);
+        let value_type = (suffix_and_type & 0x7) as u8;
+        let suffix = data
+            .get(consumed..consumed + suffix_len)
+            .ok_or(Error::Corrupt("truncated record key"))?;
+        consumed += suffix_len;
+        if prefix_len > cursor.key.len() {
+            return Err(Error::Corrupt("record key prefix exceeds previous key"));
+        }
+        cursor.key.truncate(prefix_len);
+        cursor.key.extend_from_slice(suffix);
+
+        let value = &data[consumed..];
+        let (record, len) = match self.typ {
+            BLOCK_TYPE_REF => {
+                let (record, len) =
+                    RefRecord::decode_value(&cursor.key, value_type, value, self.min_update_index, self.object_hash)?;
+                (Record::Ref(record), len)
+            }
+            BLOCK_TYPE_LOG => {
+                let (record, len) = LogRecord::decode_value(&cursor.key, value_type, value, self.object_hash)?;
+                (Record::Log(record), len)
+            }
+            BLOCK_TYPE_INDEX => {
+                let (record, len) = IndexRecord::decode_value(value)?;
+                (Record::Index(record), len)
+            }
+            _ => return Err(Error::Corrupt("unsupported block type")),
+        };
+        cursor.pos += consumed + len;
+        Ok(Some(record))
+    }
+
+    fn restart_offset(&self, idx: usize) -> Result<usize, Error> {
+        let offset = be24(&self.data[self.restarts_start + idx * 3..]) as usize;
+        if offset < self.records_start || offset >= self.restarts_start {
+            return Err(Error::Corrupt("restart offset"));
+        }
+        Ok(offset)
+    }
+
+    fn restart_key(&self, idx: usize) -> Result<&[u8], Error> {
+        let data = &self.data[self.restart_offset(idx)?..self.restarts_start];
+        let (prefix_len, mut consumed) = get_varint(data).ok_or(Error::Corrupt("restart key"))?;
+        let (suffix_and_type, len) = get_varint(&data[consumed..]).ok_or(Error::Corrupt("restart key"))?;
+        consumed += len;
+        if prefix_len != 0 {
+            return Err(Error::Corrupt("restart key is prefix-compressed"));
+        }
+        data.get(consumed..consumed + (suffix_and_type >> 3) as usize)
+            .ok_or(Error::Corrupt("truncated restart key"))
+    }
+}
+
+/// Decompress `input` into `out`, which must be one byte larger than the expected output to detect the end of the stream,
+/// and return the amount of consumed input bytes.
+fn inflate(input: &[u8], out: &mut [u8]) -> Result<usize, Error> {
+    let mut state = zlib::Inflate::default();
+    let (mut consumed, mut written) = (0, 0);
+    loop {
+        let (status, read, wrote) = state.once(&input[consumed..], &mut out[written..])?;
+        consumed += read;
+        written += wrote;
+        match status {
+            zlib::Status::StreamEnd => break,
+            zlib::Status::Ok | zlib::Status::BufError if read == 0 && wrote == 0 => {
+                return Err(Error::Corrupt("truncated log block"))
+            }
+            zlib::Status::Ok | zlib::Status::BufError => {}
+        }
+    }
+    if written + 1 != out.len() {
+        return Err(Error::Corrupt("log block size mismatch"));
+    }
+    Ok(consumed)
+}
+
+/// Assembles records into a single block.
+pub(crate) struct Writer {
+    typ: u8,
+    header_len: usize,
+    block_size: usize,
+    records: Vec<u8>,
+    restarts: Vec<u32>,
+    last_key: Vec<u8>,
+    num_records: usize,
+}
+
+impl Writer {
+    pub fn new(typ: u8, header_len: usize, block_size: usize) -> Self {
+        Writer {
+            typ,
+            header_len,
+            block_size,
+            records: Vec::new(),
+            restarts: Vec::new(),
+            last_key: Vec::new(),
+            num_records: 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.num_records == 0
+    }
+
+    /// Return the length of the file header that precedes this block, which is only non-zero for the first block of a table.
+    pub fn header_len(&self) -> usize {
+        self.header_len
+    }
+
+    pub fn last_key(&self) -> &[u8] {
+        &self.last_key
+    }
+
+    /// Allow this block to exceed the block size, which is only valid for log blocks.
+    pub fn unlimited(&mut self) {
+        self.block_size = crate::store_impl::reftable::format::MAX_BLOCK_SIZE as usize;
+    }
+
+    /// Add a record with `key`, `value_type` and the encoded `value`, and return `false` if it doesn't fit into the block.
+    pub fn add(&mut self, key: &[u8], value_type: u8, value: &[u8]) -> bool {
+        let is_restart = self.num_records % RESTART_INTERVAL == 0;
+        let prefix_len = if is_restart {
+            0
+        } else {
+            self.last_key.iter().zip(key).take_while(|(a, b)| a == b).count()
+        };
+        let records_len = self.records.len();
+        put_varint(&mut self.records, prefix_len as u64);
+        put_varint(
+            &mut self.records,
+            (((key.len() - prefix_len) as u64) << 3) | u64::from(value_type),
+        );
+        self.records.extend_from_slice(&key[prefix_len..]);
+        self.records.extend_from_slice(value);
+
+        let num_restarts = self.restarts.len() + usize::from(is_restart);
+        if self.header_len + 4 + self.records.len() + 3 * num_restarts + 2 > self.block_size
+            || num_restarts > u16::MAX as usize
+        {
+            self.records.truncate(records_len);
+            return false;
+        }
+        if is_restart {
+            self.restarts.push((self.header_len + 4 + records_len) as u32);
+        }
+        self.last_key.clear();
+        self.last_key.extend_from_slice(key);
+        self.num_records += 1;
+        true
+    }
+
+    /// Append the finished block to `out`, which is expected to contain the file header already if this is the first block,
+    /// and pad it to the block size unless it is a log block.
+    pub fn finish_into(self, out: &mut Vec<u8>) -> std::io::Result<()> {
+        use std::io::Write;
+        let mut body = self.records;
+        for restart in &self.restarts {
+            put_be24(&mut body, *restart);
+        }
+        body.extend_from_slice(&(self.restarts.len() as u16).to_be_bytes());
+        let block_len = self.header_len + 4 + body.len();
+
+        out.push(self.typ);
+        put_be24(out, block_len as u32);
+        if self.typ == BLOCK_TYPE_LOG {
+            let mut deflate = zlib::stream::deflate::Write::new(Vec::new());
+            deflate.write_all(&body)?;
+            deflate.flush()?;
+            out.extend_from_slice(&deflate.into_inner());
+        } else {
+            out.extend_from_slice(&body);
+            out.resize(out.len() + self.block_size.saturating_sub(block_len), 0);
+        }
+        Ok(())
+    }
+}
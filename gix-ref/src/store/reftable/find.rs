@@ -0,0 +1,127 @@
+use std::convert::TryInto;
+
+pub use error::Error;
+
+use crate::{
+    store_impl::reftable::{self, stack::SharedSnapshot},
+    BString, PartialNameRef, Reference,
+};
+
+impl reftable::Store {
+    /// Find a single reference by the given `path` which is required to be a valid reference name.
+    ///
+    /// Returns `Ok(None)` if no such ref exists.
+    ///
+    /// ### Note
+    ///
+    /// * The lookup algorithm follows the one in [the git documentation][git-lookup-docs].
+    /// * The stack of tables is checked for modifications each time the method is called.
+    ///
+    /// [git-lookup-docs]: https://github.com/git/git/blob/5d5b1473453400224ebb126bf3947e0a3276bdf5/Documentation/revisions.txt#L34-L46
+    pub fn try_find<'a, Name, E>(&self, partial: Name) -> Result<Option<Reference>, Error>
+    where
+        Name: TryInto<&'a PartialNameRef, Error = E>,
+        Error: From<E>,
+    {
+        let partial_name = partial.try_into()?;
+        let stack = self.stack()?;
+        Self::find_one_with_verified_input(stack.as_ref(), partial_name)
+    }
+
+    pub(crate) fn find_one_with_verified_input(
+        stack: Option<&SharedSnapshot>,
+        partial_name: &PartialNameRef,
+    ) -> Result<Option<Reference>, Error> {
+        let stack = match stack {
+            Some(stack) => stack,
+            None => return Ok(None),
+        };
+        let mut buf = BString::default();
+        for inbetween in &["", "tags", "heads", "remotes"] {
+            let full_name = partial_name.construct_full_name_ref(inbetween, &mut buf);
+            if let Some(record) = stack.find_ref(full_name.as_bstr())? {
+                return record.into_reference().transpose().map_err(Into::into);
+            }
+        }
+        if partial_name.as_bstr() != "HEAD" {
+            let partial_name = partial_name.to_owned().join("HEAD").expect("HEAD is valid name");
+            let full_name = partial_name.as_ref().construct_full_name_ref("remotes", &mut buf);
+            if let Some(record) = stack.find_ref(full_name.as_bstr())? {
+                return record.into_reference().transpose().map_err(Into::into);
+            }
+        }
+        Ok(None)
+    }
+}
+
+///
+pub mod existing {
+    use std::convert::TryInto;
+
+    pub use error::Error;
+
+    use crate::{
+        store_impl::reftable::{self, find},
+        PartialNameRef, Reference,
+    };
+
+    impl reftable::Store {
+        /// Similar to [`reftable::Store::try_find()`] but a non-existing ref is treated as error.
+        pub fn find<'a, Name, E>(&self, partial: Name) -> Result<Reference, Error>
+        where
+            Name: TryInto<&'a PartialNameRef, Error = E>,
+            crate::name::Error: From<E>,
+        {
+            let path = partial
+                .try_into()
+                .map_err(|err| Error::Find(find::Error::RefnameValidation(err.into())))?;
+            match self.try_find(path) {
+                Ok(Some(r)) => Ok(r),
+                Ok(None) => Err(Error::NotFound {
+                    name: path.to_partial_path().to_owned(),
+                }),
+                Err(err) => Err(err.into()),
+            }
+        }
+    }
+
+    mod error {
+        use std::path::PathBuf;
+
+        use crate::store_impl::reftable::find;
+
+        /// The error returned by [reftable::Store::find()][crate::reftable::Store::find()].
+        #[derive(Debug, thiserror::Error)]
+        #[allow(missing_docs)]
+        pub enum Error {
+            #[error("An error occurred while trying to find a reference")]
+            Find(#[from] find::Error),
+            #[error("The ref partially named {name:?} could not be found")]
+            NotFound { name: PathBuf },
+        }
+    }
+}
+
+mod error {
+    use std::convert::Infallible;
+
+    use crate::store_impl::reftable::{stack, table};
+
+    /// The error returned by [reftable::Store::try_find()][crate::reftable::Store::try_find()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The ref name or path is not a valid ref name")]
+        RefnameValidation(#[from] crate::name::Error),
+        #[error(transparent)]
+        Open(#[from] stack::open::Error),
+        #[error("A reference could not be read from its table")]
+        Decode(#[from] table::decode::Error),
+    }
+
+    impl From<Infallible> for Error {
+        fn from(_: Infallible) -> Self {
+            unreachable!("this impl is needed to allow passing a known valid partial path as parameter")
+        }
+    }
+}
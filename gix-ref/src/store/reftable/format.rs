@@ -0,0 +1,193 @@
+//! Constants and primitive encodings shared by all parts of a reftable.
+//!
+//! See the [format documentation](https://git-scm.com/docs/reftable) for details.
+
+pub(crate) const MAGIC: &[u8; 4] = b"REFT";
+
+pub(crate) const BLOCK_TYPE_REF: u8 = b'r';
+pub(crate) const BLOCK_TYPE_LOG: u8 = b'g';
+pub(crate) const BLOCK_TYPE_INDEX: u8 = b'i';
+
+/// Every n-th record in a block is stored with its full key, which allows binary searches across restart points.
+pub(crate) const RESTART_INTERVAL: usize = 16;
+/// Write an index for a section only if it has more blocks than this.
+pub(crate) const INDEX_THRESHOLD: usize = 3;
+/// The largest block size that can be represented, as block lengths are stored in 24 bits.
+pub(crate) const MAX_BLOCK_SIZE: u32 = (1 << 24) - 1;
+
+const HASH_ID_SHA1: u32 = 0x7368_6131;
+const HASH_ID_SHA256: u32 = 0x7332_3536;
+
+/// The fields of a table header, which are repeated in its footer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Header {
+    pub version: u8,
+    pub block_size: u32,
+    pub min_update_index: u64,
+    pub max_update_index: u64,
+    pub object_hash: gix_hash::Kind,
+}
+
+impl Header {
+    /// Create a header for a table whose records use `object_hash`, choosing the version accordingly.
+    pub fn new(object_hash: gix_hash::Kind, block_size: u32, min_update_index: u64, max_update_index: u64) -> Self {
+        Header {
+            version: match object_hash {
+                gix_hash::Kind::Sha1 => 1,
+                _ => 2,
+            },
+            block_size,
+            min_update_index,
+            max_update_index,
+            object_hash,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        header_len(self.version)
+    }
+
+    pub fn write_to(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(MAGIC);
+        out.push(self.version);
+        put_be24(out, self.block_size);
+        out.extend_from_slice(&self.min_update_index.to_be_bytes());
+        out.extend_from_slice(&self.max_update_index.to_be_bytes());
+        if self.version != 1 {
+            let id = match self.object_hash {
+                gix_hash::Kind::Sha1 => HASH_ID_SHA1,
+                gix_hash::Kind::Sha256 => HASH_ID_SHA256,
+            };
+            out.extend_from_slice(&id.to_be_bytes());
+        }
+    }
+
+    /// Parse a header from the beginning of `data`, or return `None` if it isn't valid.
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        if data.get(..4)? != MAGIC {
+            return None;
+        }
+        let version = *data.get(4)?;
+        if version != 1 && version != 2 {
+            return None;
+        }
+        let data = data.get(..header_len(version))?;
+        let object_hash = if version == 1 {
+            gix_hash::Kind::Sha1
+        } else {
+            match be32(&data[24..]) {
+                HASH_ID_SHA1 => gix_hash::Kind::Sha1,
+                HASH_ID_SHA256 => gix_hash::Kind::Sha256,
+                _ => return None,
+            }
+        };
+        Some(Header {
+            version,
+            block_size: be24(&data[5..]),
+            min_update_index: be64(&data[8..]),
+            max_update_index: be64(&data[16..]),
+            object_hash,
+        })
+    }
+}
+
+pub(crate) fn header_len(version: u8) -> usize {
+    if version == 1 {
+        24
+    } else {
+        28
+    }
+}
+
+pub(crate) fn footer_len(version: u8) -> usize {
+    header_len(version) + 5 * 8 + 4
+}
+
+/// Append `value` using the variable length integer encoding of reftables, which is the same as the one used for
+/// offsets in packs.
+pub(crate) fn put_varint(out: &mut Vec<u8>, mut value: u64) {
+    let mut buf = [0u8; 10];
+    let mut pos = buf.len() - 1;
+    buf[pos] = (value & 0x7f) as u8;
+    value >>= 7;
+    while value != 0 {
+        value -= 1;
+        pos -= 1;
+        buf[pos] = 0x80 | (value & 0x7f) as u8;
+        value >>= 7;
+    }
+    out.extend_from_slice(&buf[pos..]);
+}
+
+/// Decode a variable length integer from the beginning of `data` and return it along with the amount of consumed bytes.
+pub(crate) fn get_varint(data: &[u8]) -> Option<(u64, usize)> {
+    let mut byte = *data.first()?;
+    let mut value = u64::from(byte & 0x7f);
+    let mut consumed = 1;
+    while byte & 0x80 != 0 {
+        byte = *data.get(consumed)?;
+        consumed += 1;
+        if value > (u64::MAX >> 7) - 1 {
+            return None;
+        }
+        value = ((value + 1) << 7) | u64::from(byte & 0x7f);
+    }
+    Some((value, consumed))
+}
+
+pub(crate) fn put_be24(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_be_bytes()[1..]);
+}
+
+pub(crate) fn be16(data: &[u8]) -> u16 {
+    u16::from_be_bytes([data[0], data[1]])
+}
+
+pub(crate) fn be24(data: &[u8]) -> u32 {
+    u32::from_be_bytes([0, data[0], data[1], data[2]])
+}
+
+pub(crate) fn be32(data: &[u8]) -> u32 {
+    u32::from_be_bytes([data[0], data[1], data[2], data[3]])
+}
+
+pub(crate) fn be64(data: &[u8]) -> u64 {
+    let mut buf = [0u8; 8];
+    buf.copy_from_slice(&data[..8]);
+    u64::from_be_bytes(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_varint, put_varint};
+
+    #[test]
+    fn varint_round_trip() {
+        for value in [
+            0,
+            1,
+            127,
+            128,
+            255,
+            256,
+            16383,
+            16384,
+            16511,
+            1 << 32,
+            u64::MAX - 1,
+            u64::MAX,
+        ] {
+            let mut buf = Vec::new();
+            put_varint(&mut buf, value);
+            assert_eq!(get_varint(&buf), Some((value, buf.len())), "{value}");
+        }
+    }
+
+    #[test]
+    fn varint_matches_known_encodings() {
+        let mut buf = Vec::new();
+        put_varint(&mut buf, 128);
+        assert_eq!(buf, [0x80, 0x00], "128 is encoded with the offset trick");
+        assert_eq!(get_varint(&[0xff; 11]), None, "overflows are detected");
+    }
+}
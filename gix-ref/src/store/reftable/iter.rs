@@ -0,0 +1,90 @@
+use gix_object::bstr::{BString, ByteSlice};
+
+pub use error::Error;
+
+use crate::{
+    store_impl::reftable::{
+        self,
+        stack::{MergedRefs, SharedSnapshot},
+        table::decode,
+    },
+    Reference,
+};
+
+/// An iterator over all references of a [`Stack`][crate::reftable::Stack] ordered by name, with references in newer tables
+/// overriding the ones in older tables.
+///
+/// It keeps the snapshot of the stack it was created with and won't see later changes.
+pub struct Iter {
+    stack: Option<SharedSnapshot>,
+    merged: Option<MergedRefs>,
+    prefix: Option<BString>,
+}
+
+impl reftable::Store {
+    /// Return an iterator over all references, ordered by reference name.
+    pub fn iter(&self) -> Result<Iter, Error> {
+        Iter::new(self.stack()?, None)
+    }
+
+    /// Return an iterator yielding only references whose name starts with the given `prefix`, ordered by reference name.
+    ///
+    /// Note that the prefix is matched byte-wise, so `refs/heads` would also match `refs/heads-backup/main`.
+    pub fn iter_prefixed(&self, prefix: impl Into<BString>) -> Result<Iter, Error> {
+        Iter::new(self.stack()?, Some(prefix.into()))
+    }
+}
+
+impl Iter {
+    fn new(stack: Option<SharedSnapshot>, prefix: Option<BString>) -> Result<Self, Error> {
+        let merged = stack
+            .as_ref()
+            .map(|stack| MergedRefs::new(stack.tables(), prefix.as_ref().map(|prefix| prefix.as_slice())))
+            .transpose()?;
+        Ok(Iter { stack, merged, prefix })
+    }
+}
+
+impl Iterator for Iter {
+    type Item = Result<Reference, decode::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let tables = self.stack.as_ref()?.tables();
+        loop {
+            let record = match self.merged.as_mut()?.next(tables) {
+                Ok(Some(record)) => record,
+                Ok(None) => {
+                    self.merged = None;
+                    return None;
+                }
+                Err(err) => {
+                    self.merged = None;
+                    return Some(Err(err));
+                }
+            };
+            if let Some(prefix) = &self.prefix {
+                if !record.name.starts_with(prefix.as_bytes()) {
+                    self.merged = None;
+                    return None;
+                }
+            }
+            if let Some(reference) = record.into_reference() {
+                return Some(reference);
+            }
+        }
+    }
+}
+
+mod error {
+    use crate::store_impl::reftable::{stack, table};
+
+    /// The error returned by [reftable::Store::iter()][crate::reftable::Store::iter()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Open(#[from] stack::open::Error),
+        #[error("The first reference could not be located")]
+        Decode(#[from] table::decode::Error),
+    }
+}
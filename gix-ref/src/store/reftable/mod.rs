@@ -0,0 +1,135 @@
+use std::path::{Path, PathBuf};
+
+use gix_features::threading::OwnShared;
+
+use crate::store::WriteReflog;
+
+/// A store for references in the [reftable format](https://git-scm.com/docs/reftable), which keeps all references and
+/// their logs in a stack of immutable, binary tables in the `reftable` directory of a repository.
+///
+/// The tables are listed from oldest to newest in `tables.list`, and records in newer tables override the ones in older tables.
+/// Each transaction adds a new table, and tables are compacted automatically afterwards to keep the stack small.
+///
+/// # Limitations
+///
+/// * Object blocks that allow finding references by the object they point to are skipped when reading and not written.
+/// * Namespaces and linked worktrees, which need a stack of their own for their private references, are not supported yet.
+#[derive(Debug, Clone)]
+pub struct Store {
+    /// The `.git` directory of the repository.
+    git_dir: PathBuf,
+    /// The `reftable` directory containing `tables.list` and all tables.
+    path: PathBuf,
+    /// The kind of hash to use for all object ids.
+    object_hash: gix_hash::Kind,
+    /// The way to handle reflog edits
+    pub write_reflog: WriteReflog,
+    /// The size of blocks in newly written tables, which affects the granularity of lookups.
+    pub block_size: u32,
+    /// A hook to call at each stage of all transactions.
+    pub transaction_hook: Option<crate::transaction::hook::Hook>,
+    /// The currently loaded stack of tables, which is reloaded whenever `tables.list` changes.
+    stack: OwnShared<gix_fs::SharedFileSnapshotMut<Stack>>,
+}
+
+/// The stack of tables as listed in `tables.list`, from oldest to newest.
+#[derive(Debug)]
+pub struct Stack {
+    tables: Vec<Table>,
+}
+
+/// A single immutable table of a [`Stack`].
+#[derive(Debug)]
+pub struct Table {
+    path: PathBuf,
+    data: table::Backing,
+    header: format::Header,
+    /// The offset at which the footer starts, which is also the end of all blocks.
+    footer_start: usize,
+    /// If `true`, the first block of the table contains references.
+    has_refs: bool,
+    ref_index_position: Option<usize>,
+    log_position: Option<usize>,
+    log_index_position: Option<usize>,
+}
+
+/// A transaction on a reftable store, which adds a single new table with all edits.
+pub struct Transaction<'s> {
+    store: &'s Store,
+    /// The lock on `tables.list`, held until the transaction is committed.
+    lock: Option<gix_lock::File>,
+    /// The stack as seen while holding the lock.
+    stack: Option<gix_fs::SharedFileSnapshot<Stack>>,
+    updates: Option<Vec<transaction::Edit>>,
+    /// Pairs of `(source, destination)` names whose reflog entries are copied when committing.
+    reflog_copies: Vec<(crate::FullName, crate::FullName)>,
+    /// Reflog entries of the given name and update index which are removed when committing.
+    reflog_deletions: Vec<(crate::FullName, u64)>,
+}
+
+/// Initialization
+impl Store {
+    /// Create a new instance for the reftable repository at `git_dir`, using `write_reflog` to determine when to write
+    /// reflog entries, and `object_hash` for all object ids.
+    ///
+    /// Note that the `reftable` directory is created on the first transaction if it doesn't exist yet.
+    /// Linked worktrees aren't supported as their private references, like `HEAD`, would have to be kept in a stack
+    /// of their own.
+    pub fn at(git_dir: impl AsRef<Path>, write_reflog: WriteReflog, object_hash: gix_hash::Kind) -> Self {
+        let git_dir = git_dir.as_ref();
+        Store {
+            git_dir: git_dir.to_owned(),
+            path: git_dir.join("reftable"),
+            object_hash,
+            write_reflog,
+            block_size: 4096,
+            transaction_hook: None,
+            stack: OwnShared::new(gix_fs::SharedFileSnapshotMut::new()),
+        }
+    }
+}
+
+/// Access
+impl Store {
+    /// Return the `.git` directory of the repository this store was created for.
+    pub fn git_dir(&self) -> &Path {
+        &self.git_dir
+    }
+
+    /// Return the `reftable` directory in which all tables are stored.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Return the path to the file listing all tables of the stack.
+    pub fn tables_list_path(&self) -> PathBuf {
+        self.path.join("tables.list")
+    }
+
+    /// Return the kind of hash used for all object ids.
+    pub fn object_hash(&self) -> gix_hash::Kind {
+        self.object_hash
+    }
+}
+
+mod block;
+mod format;
+mod record;
+
+///
+pub mod table;
+
+///
+pub mod stack;
+
+///
+pub mod find;
+
+///
+pub mod iter;
+
+///
+pub mod reflog;
+
+///
+pub mod transaction;
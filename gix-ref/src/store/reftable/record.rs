@@ -0,0 +1,287 @@
+use std::convert::TryFrom;
+
+use gix_hash::ObjectId;
+use gix_object::bstr::{BString, ByteSlice};
+
+use crate::{
+    log,
+    store_impl::reftable::{
+        format::{get_varint, put_varint},
+        table::decode::Error,
+    },
+    FullName, Reference, Target,
+};
+
+/// The value of a reference record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum RefValue {
+    /// The reference was deleted, which hides it in older tables.
+    Deletion,
+    /// The reference points to an object.
+    Object(ObjectId),
+    /// The reference points to an annotated tag, which is peeled to `peeled`.
+    PeeledTag { target: ObjectId, peeled: ObjectId },
+    /// The reference points to another reference.
+    Symbolic(BString),
+}
+
+/// A record of the ref section, keyed by its name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RefRecord {
+    pub name: BString,
+    pub update_index: u64,
+    pub value: RefValue,
+}
+
+/// A record of the log section, keyed by its name and the reversed update index so the most recent entries come first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct LogRecord {
+    pub name: BString,
+    pub update_index: u64,
+    /// The log line, or `None` if the log entry was deleted.
+    pub line: Option<log::Line>,
+}
+
+/// A record of an index block, pointing to a block whose last key is the key of this record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct IndexRecord {
+    pub block_offset: u64,
+}
+
+/// Any record of a supported block type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Record {
+    Ref(RefRecord),
+    Log(LogRecord),
+    Index(IndexRecord),
+}
+
+impl RefRecord {
+    pub fn value_type(&self) -> u8 {
+        match self.value {
+            RefValue::Deletion => 0,
+            RefValue::Object(_) => 1,
+            RefValue::PeeledTag { .. } => 2,
+            RefValue::Symbolic(_) => 3,
+        }
+    }
+
+    pub fn encode_value(&self, min_update_index: u64, out: &mut Vec<u8>) {
+        put_varint(out, self.update_index - min_update_index);
+        match &self.value {
+            RefValue::Deletion => {}
+            RefValue::Object(id) => out.extend_from_slice(id.as_slice()),
+            RefValue::PeeledTag { target, peeled } => {
+                out.extend_from_slice(target.as_slice());
+                out.extend_from_slice(peeled.as_slice());
+            }
+            RefValue::Symbolic(name) => {
+                put_varint(out, name.len() as u64);
+                out.extend_from_slice(name);
+            }
+        }
+    }
+
+    pub fn decode_value(
+        name: &[u8],
+        value_type: u8,
+        data: &[u8],
+        min_update_index: u64,
+        object_hash: gix_hash::Kind,
+    ) -> Result<(Self, usize), Error> {
+        let (delta, mut consumed) = get_varint(data).ok_or(Error::Corrupt("ref record update index"))?;
+        let hash_len = object_hash.len_in_bytes();
+        let take_id = |consumed: &mut usize| {
+            let id = data
+                .get(*consumed..*consumed + hash_len)
+                .map(ObjectId::from)
+                .ok_or(Error::Corrupt("ref record object id"))?;
+            *consumed += hash_len;
+            Ok::<_, Error>(id)
+        };
+        let value = match value_type {
+            0 => RefValue::Deletion,
+            1 => RefValue::Object(take_id(&mut consumed)?),
+            2 => RefValue::PeeledTag {
+                target: take_id(&mut consumed)?,
+                peeled: take_id(&mut consumed)?,
+            },
+            3 => {
+                let (len, header) =
+                    get_varint(&data[consumed..]).ok_or(Error::Corrupt("symbolic ref target length"))?;
+                consumed += header;
+                let target = data
+                    .get(consumed..consumed + len as usize)
+                    .ok_or(Error::Corrupt("symbolic ref target"))?;
+                consumed += target.len();
+                RefValue::Symbolic(target.into())
+            }
+            _ => return Err(Error::Corrupt("ref record value type")),
+        };
+        Ok((
+            RefRecord {
+                name: name.into(),
+                update_index: min_update_index + delta,
+                value,
+            },
+            consumed,
+        ))
+    }
+
+    /// Convert this record into a reference, or return `None` if it is a deletion.
+    pub fn into_reference(self) -> Option<Result<Reference, Error>> {
+        let name = match FullName::try_from(self.name) {
+            Ok(name) => name,
+            Err(err) => return Some(Err(err.into())),
+        };
+        let (target, peeled) = match self.value {
+            RefValue::Deletion => return None,
+            RefValue::Object(id) => (Target::Peeled(id), None),
+            RefValue::PeeledTag { target, peeled } => (Target::Peeled(target), Some(peeled)),
+            RefValue::Symbolic(target) => match FullName::try_from(target) {
+                Ok(target) => (Target::Symbolic(target), None),
+                Err(err) => return Some(Err(err.into())),
+            },
+        };
+        Some(Ok(Reference { name, target, peeled }))
+    }
+}
+
+impl LogRecord {
+    /// Return the key of the log record of `name` at `update_index`.
+    pub fn key(name: &[u8], update_index: u64) -> Vec<u8> {
+        let mut key = Vec::with_capacity(name.len() + 9);
+        key.extend_from_slice(name);
+        key.push(0);
+        key.extend_from_slice(&(u64::MAX - update_index).to_be_bytes());
+        key
+    }
+
+    /// Split a log `key` into the reference name and update index.
+    pub fn parse_key(key: &[u8]) -> Result<(&[u8], u64), Error> {
+        if key.len() < 9 || key[key.len() - 9] != 0 {
+            return Err(Error::Corrupt("log record key"));
+        }
+        let (name, index) = key.split_at(key.len() - 9);
+        Ok((name, u64::MAX - crate::store_impl::reftable::format::be64(&index[1..])))
+    }
+
+    pub fn value_type(&self) -> u8 {
+        match self.line {
+            None => 0,
+            Some(_) => 1,
+        }
+    }
+
+    pub fn encode_value(&self, out: &mut Vec<u8>) {
+        let line = match &self.line {
+            Some(line) => line,
+            None => return,
+        };
+        let put_string = |out: &mut Vec<u8>, s: &[u8]| {
+            put_varint(out, s.len() as u64);
+            out.extend_from_slice(s);
+        };
+        out.extend_from_slice(line.previous_oid.as_slice());
+        out.extend_from_slice(line.new_oid.as_slice());
+        put_string(out, &line.signature.name);
+        put_string(out, &line.signature.email);
+        put_varint(out, u64::from(line.signature.time.seconds_since_unix_epoch));
+        let offset_minutes = line.signature.time.offset_in_seconds.abs() / 60;
+        let offset = (offset_minutes / 60 * 100 + offset_minutes % 60) as i16;
+        let offset = match line.signature.time.sign {
+            gix_actor::Sign::Plus => offset,
+            gix_actor::Sign::Minus => -offset,
+        };
+        out.extend_from_slice(&offset.to_be_bytes());
+        // git always terminates non-empty messages with a newline, which we strip when decoding.
+        if line.message.is_empty() {
+            put_varint(out, 0);
+        } else {
+            put_varint(out, line.message.len() as u64 + 1);
+            out.extend_from_slice(&line.message);
+            out.push(b'\n');
+        }
+    }
+
+    pub fn decode_value(
+        key: &[u8],
+        value_type: u8,
+        data: &[u8],
+        object_hash: gix_hash::Kind,
+    ) -> Result<(Self, usize), Error> {
+        let (name, update_index) = Self::parse_key(key)?;
+        let mut record = LogRecord {
+            name: name.into(),
+            update_index,
+            line: None,
+        };
+        match value_type {
+            0 => return Ok((record, 0)),
+            1 => {}
+            _ => return Err(Error::Corrupt("log record value type")),
+        }
+
+        let mut consumed = 0;
+        let hash_len = object_hash.len_in_bytes();
+        let take = |len: usize, consumed: &mut usize| {
+            let bytes = data
+                .get(*consumed..*consumed + len)
+                .ok_or(Error::Corrupt("truncated log record"))?;
+            *consumed += len;
+            Ok::<_, Error>(bytes)
+        };
+        let take_varint = |consumed: &mut usize| {
+            let (value, len) = get_varint(&data[(*consumed).min(data.len())..]).ok_or(Error::Corrupt("log record"))?;
+            *consumed += len;
+            Ok::<_, Error>(value)
+        };
+        let previous_oid = ObjectId::from(take(hash_len, &mut consumed)?);
+        let new_oid = ObjectId::from(take(hash_len, &mut consumed)?);
+        let len = take_varint(&mut consumed)? as usize;
+        let name = take(len, &mut consumed)?.into();
+        let len = take_varint(&mut consumed)? as usize;
+        let email = take(len, &mut consumed)?.into();
+        let seconds = take_varint(&mut consumed)?;
+        let offset = i16::from_be_bytes({
+            let bytes = take(2, &mut consumed)?;
+            [bytes[0], bytes[1]]
+        });
+        let len = take_varint(&mut consumed)? as usize;
+        let message = take(len, &mut consumed)?;
+        let message = message.strip_suffix(b"\n").unwrap_or(message);
+
+        let offset_minutes = i32::from(offset.abs() / 100 * 60 + offset.abs() % 100);
+        record.line = Some(log::Line {
+            previous_oid,
+            new_oid,
+            signature: gix_actor::Signature {
+                name,
+                email,
+                time: gix_actor::Time {
+                    seconds_since_unix_epoch: u32::try_from(seconds)
+                        .map_err(|_| Error::Corrupt("log record time out of range"))?,
+                    offset_in_seconds: offset_minutes * 60 * if offset < 0 { -1 } else { 1 },
+                    sign: if offset < 0 {
+                        gix_actor::Sign::Minus
+                    } else {
+                        gix_actor::Sign::Plus
+                    },
+                },
+            },
+            message: message.as_bstr().to_owned(),
+        });
+        Ok((record, consumed))
+    }
+}
+
+impl IndexRecord {
+    pub fn encode_value(&self, out: &mut Vec<u8>) {
+        put_varint(out, self.block_offset);
+    }
+
+    pub fn decode_value(data: &[u8]) -> Result<(Self, usize), Error> {
+        let (block_offset, consumed) = get_varint(data).ok_or(Error::Corrupt("index record"))?;
+        Ok((IndexRecord { block_offset }, consumed))
+    }
+}
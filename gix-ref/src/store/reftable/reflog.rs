@@ -0,0 +1,48 @@
+use std::convert::TryInto;
+
+pub use error::Error;
+
+use crate::{log, store_impl::reftable, FullNameRef};
+
+impl reftable::Store {
+    /// Returns true if a reflog exists for the given reference `name`.
+    pub fn reflog_exists<'a, Name, E>(&self, name: Name) -> Result<bool, Error>
+    where
+        Name: TryInto<&'a FullNameRef, Error = E>,
+        crate::name::Error: From<E>,
+    {
+        Ok(self.reflog(name)?.is_some())
+    }
+
+    /// Return all reflog entries of the given fully qualified `name` from oldest to newest,
+    /// or `Ok(None)` if there is no reflog.
+    pub fn reflog<'a, Name, E>(&self, name: Name) -> Result<Option<Vec<log::Line>>, Error>
+    where
+        Name: TryInto<&'a FullNameRef, Error = E>,
+        crate::name::Error: From<E>,
+    {
+        let name: &FullNameRef = name.try_into().map_err(|err| Error::RefnameValidation(err.into()))?;
+        let stack = match self.stack()? {
+            Some(stack) => stack,
+            None => return Ok(None),
+        };
+        let lines: Vec<_> = stack.logs_of(name.as_bstr())?.into_values().collect();
+        Ok((!lines.is_empty()).then_some(lines))
+    }
+}
+
+mod error {
+    use crate::store_impl::reftable::{stack, table};
+
+    /// The error returned by [reftable::Store::reflog()][crate::reftable::Store::reflog()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The reflog name or path is not a valid ref name")]
+        RefnameValidation(#[from] crate::name::Error),
+        #[error(transparent)]
+        Open(#[from] stack::open::Error),
+        #[error("A reflog entry could not be read from its table")]
+        Decode(#[from] table::decode::Error),
+    }
+}
@@ -0,0 +1,388 @@
+use std::{collections::BTreeMap, io::Write, ops::Range, path::Path};
+
+use gix_object::bstr::{BString, ByteSlice};
+
+use crate::{
+    log,
+    store_impl::reftable::{
+        format::{BLOCK_TYPE_LOG, BLOCK_TYPE_REF},
+        record::{LogRecord, Record, RefRecord, RefValue},
+        table::{self, decode, SectionCursor},
+        Stack, Store, Table,
+    },
+};
+
+/// An up-to-date snapshot of the stack of tables.
+pub type SharedSnapshot = gix_fs::SharedFileSnapshot<Stack>;
+
+///
+pub mod open {
+    use std::path::PathBuf;
+
+    use crate::store_impl::reftable::table;
+
+    /// The error returned by [`Stack::at()`][crate::reftable::Stack::at()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The list of tables at '{}' could not be read", path.display())]
+        Io { source: std::io::Error, path: PathBuf },
+        #[error(transparent)]
+        Table(#[from] table::open::Error),
+    }
+}
+
+///
+pub mod compact {
+    use crate::store_impl::reftable::{stack, table};
+
+    /// The error returned by [`Store::compact()`][crate::reftable::Store::compact()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The lock for the list of tables could not be obtained")]
+        LockAcquire(#[from] gix_lock::acquire::Error),
+        #[error(transparent)]
+        Open(#[from] stack::open::Error),
+        #[error("A table to be compacted could not be read")]
+        Decode(#[from] table::decode::Error),
+        #[error("The compacted table could not be created")]
+        Write(#[from] table::write::Error),
+        #[error("The compacted table or the list of tables could not be written")]
+        Io(#[from] std::io::Error),
+    }
+}
+
+/// Lifecycle
+impl Stack {
+    /// Load all tables listed in the `tables.list` file in the reftable directory at `path`,
+    /// or return `None` if there is no such file.
+    pub fn at(path: impl AsRef<Path>) -> Result<Option<Self>, open::Error> {
+        let path = path.as_ref();
+        let list_path = path.join("tables.list");
+        let mut attempts = 0;
+        loop {
+            let list = match std::fs::read(&list_path) {
+                Ok(list) => list,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+                Err(source) => {
+                    return Err(open::Error::Io {
+                        source,
+                        path: list_path,
+                    })
+                }
+            };
+            let tables = list
+                .lines()
+                .filter(|name| !name.is_empty())
+                .map(|name| Table::at(path.join(gix_path::from_byte_slice(name))))
+                .collect::<Result<Vec<_>, _>>();
+            match tables {
+                Ok(tables) => return Ok(Some(Stack { tables })),
+                // The stack was compacted while we were reading it, so read the new list.
+                Err(table::open::Error::Io { source, .. })
+                    if source.kind() == std::io::ErrorKind::NotFound && attempts < 3 =>
+                {
+                    attempts += 1;
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+}
+
+/// Access
+impl Stack {
+    /// Return all tables from oldest to newest.
+    pub fn tables(&self) -> &[Table] {
+        &self.tables
+    }
+
+    /// Return the update index to use for the next table to be added.
+    pub(crate) fn next_update_index(&self) -> u64 {
+        self.tables.last().map_or(1, |table| table.max_update_index() + 1)
+    }
+
+    /// Find the most recent record for the reference named `name`, ignoring deletions.
+    pub(crate) fn find_ref(&self, name: &[u8]) -> Result<Option<RefRecord>, decode::Error> {
+        for table in self.tables.iter().rev() {
+            if let Some(record) = table.find_ref(name)? {
+                return Ok((record.value != RefValue::Deletion).then_some(record));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Return all log records of `name` that weren't deleted, keyed by their update index.
+    pub(crate) fn logs_of(&self, name: &[u8]) -> Result<BTreeMap<u64, log::Line>, decode::Error> {
+        let mut logs = BTreeMap::new();
+        for table in &self.tables {
+            for record in table.logs_of(name)? {
+                match record.line {
+                    Some(line) => logs.insert(record.update_index, line),
+                    None => logs.remove(&record.update_index),
+                };
+            }
+        }
+        Ok(logs)
+    }
+}
+
+/// Merges the ref records of multiple tables, with records in newer tables overriding those of older ones.
+pub(crate) struct MergedRefs {
+    heads: Vec<(Option<SectionCursor>, Option<RefRecord>)>,
+}
+
+impl MergedRefs {
+    /// Merge the ref records of `tables`, starting at the first record equal to or greater than `start` if given.
+    pub fn new(tables: &[Table], start: Option<&[u8]>) -> Result<Self, decode::Error> {
+        let heads = tables
+            .iter()
+            .map(|table| {
+                let cursor = match start {
+                    Some(start) => table.seek(BLOCK_TYPE_REF, start)?,
+                    None => table.section_start(BLOCK_TYPE_REF),
+                };
+                Ok((cursor, None))
+            })
+            .collect::<Result<_, decode::Error>>()?;
+        Ok(MergedRefs { heads })
+    }
+
+    /// Return the next record in `tables`, which must be the ones this instance was created with, including deletions.
+    pub fn next(&mut self, tables: &[Table]) -> Result<Option<RefRecord>, decode::Error> {
+        for ((cursor, peeked), table) in self.heads.iter_mut().zip(tables) {
+            if peeked.is_some() {
+                continue;
+            }
+            if let Some(section) = cursor {
+                match section.next(table)? {
+                    Some(Record::Ref(record)) => *peeked = Some(record),
+                    Some(_) => return Err(decode::Error::Corrupt("unexpected record in ref section")),
+                    None => *cursor = None,
+                }
+            }
+        }
+
+        let mut winner: Option<(usize, &BString)> = None;
+        for (idx, (_, peeked)) in self.heads.iter().enumerate() {
+            if let Some(record) = peeked {
+                // Newer tables come later and win if the names are the same.
+                if winner.map_or(true, |(_, name)| record.name <= *name) {
+                    winner = Some((idx, &record.name));
+                }
+            }
+        }
+        let winner = match winner {
+            Some((idx, _)) => idx,
+            None => return Ok(None),
+        };
+        let record = self.heads[winner].1.take().expect("winner was peeked");
+        for (_, peeked) in &mut self.heads {
+            if peeked.as_ref().map_or(false, |other| other.name == record.name) {
+                *peeked = None;
+            }
+        }
+        Ok(Some(record))
+    }
+}
+
+/// Stack management
+impl Store {
+    /// Return an up-to-date snapshot of the stack of tables, or `None` if the `tables.list` file doesn't exist.
+    ///
+    /// The snapshot is cached and only reloaded if `tables.list` changed.
+    pub fn stack(&self) -> Result<Option<SharedSnapshot>, open::Error> {
+        self.stack.recent_snapshot(
+            || self.tables_list_path().metadata().and_then(|m| m.modified()).ok(),
+            || Stack::at(&self.path),
+        )
+    }
+
+    pub(crate) fn force_refresh_stack(&self) -> Result<Option<SharedSnapshot>, open::Error> {
+        let list_path = self.tables_list_path();
+        self.stack.force_refresh(|| {
+            let modified = match list_path.metadata().and_then(|m| m.modified()) {
+                Ok(modified) => modified,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+                Err(source) => {
+                    return Err(open::Error::Io {
+                        source,
+                        path: list_path.clone(),
+                    })
+                }
+            };
+            Stack::at(&self.path).map(|stack| stack.map(|stack| (modified, stack)))
+        })?;
+        Ok(gix_features::threading::get_ref(&**self.stack).clone())
+    }
+
+    /// Acquire the lock for `tables.list`, creating the reftable directory if needed, and return it along with the stack
+    /// as it is while holding the lock.
+    pub(crate) fn lock_stack(
+        &self,
+        lock_fail_mode: gix_lock::acquire::Fail,
+    ) -> Result<(gix_lock::File, Option<SharedSnapshot>), compact::Error> {
+        let lock = gix_lock::File::acquire_to_update_resource(
+            self.tables_list_path(),
+            lock_fail_mode,
+            self.path.parent().map(ToOwned::to_owned),
+        )?;
+        Ok((lock, self.force_refresh_stack()?))
+    }
+
+    /// Write `data` as a new table covering the update indices `min..=max` and return its file name.
+    pub(crate) fn write_table(&self, data: &[u8], min: u64, max: u64) -> std::io::Result<String> {
+        use std::hash::{BuildHasher, Hasher};
+        let suffix = std::collections::hash_map::RandomState::new().build_hasher().finish() as u32;
+        let name = format!("0x{min:012x}-0x{max:012x}-{suffix:08x}.ref");
+        let mut lock = gix_lock::File::acquire_to_update_resource(
+            self.path.join(&name),
+            gix_lock::acquire::Fail::Immediately,
+            None,
+        )
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
+        lock.write_all(data)?;
+        lock.commit().map_err(|err| err.error)?;
+        Ok(name)
+    }
+
+    /// Write the table `names` into the `lock` for `tables.list` and commit it.
+    pub(crate) fn commit_tables_list(&self, mut lock: gix_lock::File, names: &[String]) -> std::io::Result<()> {
+        for name in names {
+            writeln!(lock, "{name}")?;
+        }
+        lock.commit().map_err(|err| err.error)?;
+        self.force_refresh_stack().ok();
+        Ok(())
+    }
+
+    /// Merge all tables of the stack into a single one, dropping all deleted references and reflog entries in the process,
+    /// and fail according to `lock_fail_mode` if the stack is locked.
+    ///
+    /// Return `true` if tables were compacted.
+    pub fn compact(&self, lock_fail_mode: gix_lock::acquire::Fail) -> Result<bool, compact::Error> {
+        let (lock, stack) = self.lock_stack(lock_fail_mode)?;
+        match stack {
+            Some(stack) if stack.tables.len() > 1 => {
+                self.compact_locked(lock, &stack, 0..stack.tables.len())?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Compact the most recent tables so that the size of each table is at least twice the size of all newer tables,
+    /// which keeps the amount of tables logarithmic to the amount of changes.
+    ///
+    /// Nothing happens if the stack is currently locked.
+    pub(crate) fn auto_compact(&self) -> Result<(), compact::Error> {
+        let (lock, stack) = match self.lock_stack(gix_lock::acquire::Fail::Immediately) {
+            Ok(locked) => locked,
+            Err(compact::Error::LockAcquire(_)) => return Ok(()),
+            Err(err) => return Err(err),
+        };
+        let stack = match stack {
+            Some(stack) => stack,
+            None => return Ok(()),
+        };
+        let sizes: Vec<_> = stack.tables.iter().map(Table::size_in_bytes).collect();
+        if let Some(range) = compaction_range(&sizes) {
+            self.compact_locked(lock, &stack, range)?;
+        }
+        Ok(())
+    }
+
+    fn compact_locked(&self, lock: gix_lock::File, stack: &Stack, range: Range<usize>) -> Result<(), compact::Error> {
+        let tables = &stack.tables[range.clone()];
+        let drop_deletions = range.start == 0;
+
+        let mut refs = Vec::new();
+        let mut merged = MergedRefs::new(tables, None)?;
+        while let Some(record) = merged.next(tables)? {
+            if drop_deletions && record.value == RefValue::Deletion {
+                continue;
+            }
+            refs.push(record);
+        }
+
+        let mut logs = BTreeMap::new();
+        for table in tables {
+            let mut cursor = match table.section_start(BLOCK_TYPE_LOG) {
+                Some(cursor) => cursor,
+                None => continue,
+            };
+            while let Some(record) = cursor.next(table)? {
+                match record {
+                    Record::Log(record) => {
+                        logs.insert(LogRecord::key(&record.name, record.update_index), record);
+                    }
+                    _ => return Err(decode::Error::Corrupt("unexpected record in log section").into()),
+                }
+            }
+        }
+        let logs = logs
+            .into_values()
+            .filter(|record| !(drop_deletions && record.line.is_none()));
+
+        let (min, max) = (
+            tables.first().map_or(0, Table::min_update_index),
+            tables.last().map_or(0, Table::max_update_index),
+        );
+        let data = table::Writer {
+            block_size: self.block_size,
+            object_hash: self.object_hash,
+            min_update_index: min,
+            max_update_index: max,
+        }
+        .write(refs, logs)?;
+        let compacted = self.write_table(&data, min, max)?;
+
+        let mut names: Vec<_> = stack.tables[..range.start].iter().map(table_name).collect();
+        names.push(compacted);
+        names.extend(stack.tables[range.end..].iter().map(table_name));
+        self.commit_tables_list(lock, &names)?;
+
+        for table in tables {
+            // Readers may still use the table, which may prevent deletion on some platforms. It's garbage then.
+            std::fs::remove_file(table.path()).ok();
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn table_name(table: &Table) -> String {
+    table
+        .path()
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Return the range of tables to compact so that each table is at least twice as large as all newer ones combined,
+/// or `None` if no compaction is needed.
+fn compaction_range(sizes: &[usize]) -> Option<Range<usize>> {
+    let end = sizes.len();
+    let mut start = end.checked_sub(1)?;
+    let mut total = sizes[start];
+    while start > 0 && sizes[start - 1] <= 2 * total {
+        start -= 1;
+        total += sizes[start];
+    }
+    (start + 1 < end).then_some(start..end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::compaction_range;
+
+    #[test]
+    fn compaction_keeps_sizes_geometric() {
+        assert_eq!(compaction_range(&[]), None);
+        assert_eq!(compaction_range(&[10]), None);
+        assert_eq!(compaction_range(&[100, 10]), None, "already geometric");
+        assert_eq!(compaction_range(&[100, 10, 10]), Some(1..3));
+        assert_eq!(compaction_range(&[200, 30, 10, 10]), Some(1..4), "merged tables grow");
+        assert_eq!(compaction_range(&[10, 10]), Some(0..2));
+    }
+}
@@ -0,0 +1,510 @@
+use std::path::{Path, PathBuf};
+
+use memmap2::Mmap;
+
+use crate::store_impl::reftable::{
+    block::{self, Block},
+    format::{
+        be32, be64, footer_len, Header, BLOCK_TYPE_INDEX, BLOCK_TYPE_LOG, BLOCK_TYPE_REF, INDEX_THRESHOLD,
+        MAX_BLOCK_SIZE,
+    },
+    record::{IndexRecord, LogRecord, Record, RefRecord},
+    Table,
+};
+
+#[derive(Debug)]
+pub(crate) enum Backing {
+    InMemory(Vec<u8>),
+    Mapped(Mmap),
+}
+
+impl AsRef<[u8]> for Backing {
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            Backing::InMemory(data) => data,
+            Backing::Mapped(map) => map,
+        }
+    }
+}
+
+///
+pub mod open {
+    use std::path::PathBuf;
+
+    /// The error returned by [`Table::at()`][crate::reftable::Table::at()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The reftable at '{}' could not be read", path.display())]
+        Io { source: std::io::Error, path: PathBuf },
+        #[error("The header or footer of the reftable at '{}' is invalid: {message}", path.display())]
+        Corrupt { message: &'static str, path: PathBuf },
+    }
+}
+
+///
+pub mod decode {
+    /// The error returned when reading records from a table.
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The reftable is corrupt: {0}")]
+        Corrupt(&'static str),
+        #[error("A log block could not be decompressed")]
+        Inflate(#[from] gix_features::zlib::inflate::Error),
+        #[error("A reference in the reftable has an invalid name")]
+        RefnameValidation(#[from] crate::name::Error),
+    }
+}
+
+///
+pub mod write {
+    use gix_object::bstr::BString;
+
+    /// The error returned when writing a new table.
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The record with key {key:?} doesn't fit into a block of {block_size} bytes")]
+        RecordTooLarge { key: BString, block_size: u32 },
+        #[error("The block size must be between 256 and {} bytes, got {0}", super::MAX_BLOCK_SIZE)]
+        BlockSize(u32),
+        #[error("A log block could not be compressed")]
+        Io(#[from] std::io::Error),
+    }
+}
+
+/// Lifecycle
+impl Table {
+    /// Open the table at `path`, reading it into memory or memory-mapping it depending on its size.
+    pub fn at(path: impl Into<PathBuf>) -> Result<Self, open::Error> {
+        let path = path.into();
+        let io_err = |source| open::Error::Io {
+            source,
+            path: path.clone(),
+        };
+        let use_memory_map_if_larger_than_bytes = 32 * 1024;
+        let data = if std::fs::metadata(&path).map_err(io_err)?.len() <= use_memory_map_if_larger_than_bytes {
+            Backing::InMemory(std::fs::read(&path).map_err(io_err)?)
+        } else {
+            // SAFETY: tables are immutable once written and are only ever deleted, never changed.
+            #[allow(unsafe_code)]
+            Backing::Mapped(unsafe { Mmap::map(&std::fs::File::open(&path).map_err(io_err)?) }.map_err(io_err)?)
+        };
+        Self::from_backing(data, path)
+    }
+
+    /// Parse the table from `data` as read from `path`.
+    pub(crate) fn from_backing(data: Backing, path: PathBuf) -> Result<Self, open::Error> {
+        let corrupt = |message| open::Error::Corrupt {
+            message,
+            path: path.clone(),
+        };
+        let bytes = data.as_ref();
+        let header = Header::from_bytes(bytes).ok_or_else(|| corrupt("invalid header"))?;
+        let footer_len = footer_len(header.version);
+        let footer_start = bytes
+            .len()
+            .checked_sub(footer_len)
+            .filter(|start| *start >= header.len())
+            .ok_or_else(|| corrupt("file too small"))?;
+        let footer = &bytes[footer_start..];
+        if footer[..header.len()] != bytes[..header.len()] {
+            return Err(corrupt("footer doesn't repeat the header"));
+        }
+        let crc = gix_features::hash::crc32(&footer[..footer_len - 4]);
+        if crc != be32(&footer[footer_len - 4..]) {
+            return Err(corrupt("footer checksum mismatch"));
+        }
+        let fields = &footer[header.len()..];
+        let ref_index_position = be64(fields);
+        let log_position = be64(&fields[24..]);
+        let log_index_position = be64(&fields[32..]);
+        let first_block_type = (footer_start > header.len()).then(|| bytes[header.len()]);
+        let log_position = match first_block_type {
+            Some(BLOCK_TYPE_LOG) => Some(0),
+            _ => (log_position > 0).then_some(log_position),
+        };
+        let offset_is_valid = |offset: u64| offset < footer_start as u64;
+        if !offset_is_valid(ref_index_position)
+            || !offset_is_valid(log_index_position)
+            || !log_position.map_or(true, offset_is_valid)
+        {
+            return Err(corrupt("section offset out of bounds"));
+        }
+
+        Ok(Table {
+            path,
+            data,
+            header,
+            footer_start,
+            has_refs: first_block_type == Some(BLOCK_TYPE_REF),
+            ref_index_position: (ref_index_position > 0).then_some(ref_index_position as usize),
+            log_position: log_position.map(|pos| pos as usize),
+            log_index_position: (log_index_position > 0).then_some(log_index_position as usize),
+        })
+    }
+}
+
+/// Access
+impl Table {
+    /// Return the path from which the table was loaded.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Return the smallest update index of all records in this table.
+    pub fn min_update_index(&self) -> u64 {
+        self.header.min_update_index
+    }
+
+    /// Return the largest update index of all records in this table.
+    pub fn max_update_index(&self) -> u64 {
+        self.header.max_update_index
+    }
+
+    /// Return the size of blocks in this table.
+    pub fn block_size(&self) -> u32 {
+        self.header.block_size
+    }
+
+    /// Return the kind of hash used by object ids in this table.
+    pub fn object_hash(&self) -> gix_hash::Kind {
+        self.header.object_hash
+    }
+
+    /// Return `true` if the ref section of this table is indexed, which speeds up lookups in large tables.
+    pub fn has_ref_index(&self) -> bool {
+        self.ref_index_position.is_some()
+    }
+
+    /// Return the size of the table on disk in bytes.
+    pub fn size_in_bytes(&self) -> usize {
+        self.data.as_ref().len()
+    }
+
+    /// Return `true` if this table has no records at all.
+    pub fn is_empty(&self) -> bool {
+        self.footer_start == self.header.len()
+    }
+}
+
+/// Reading
+impl Table {
+    /// Return the block at `offset`, or `None` if it is past the last block.
+    pub(crate) fn block_at(&self, offset: usize) -> Result<Option<Block<'_>>, decode::Error> {
+        if offset >= self.footer_start {
+            return Ok(None);
+        }
+        let header_len = if offset == 0 { self.header.len() } else { 0 };
+        Block::from_bytes(
+            &self.data.as_ref()[offset..self.footer_start],
+            header_len,
+            self.header.block_size as usize,
+            self.header.min_update_index,
+            self.header.object_hash,
+        )
+        .map(Some)
+    }
+
+    /// Return the block of type `typ` at `offset`, or `None` if there is no such block.
+    fn block_of_type(&self, offset: usize, typ: u8) -> Result<Option<Block<'_>>, decode::Error> {
+        Ok(self.block_at(offset)?.filter(|block| block.typ == typ))
+    }
+
+    /// Find the block of type `typ` that may contain `key` using the index starting at `index_position`, by scanning
+    /// the index blocks of the top level and descending into lower levels.
+    fn seek_indexed(&self, index_position: usize, typ: u8, key: &[u8]) -> Result<Option<usize>, decode::Error> {
+        let mut offset = index_position;
+        while let Some(block) = self.block_of_type(offset, BLOCK_TYPE_INDEX)? {
+            let mut cursor = block.seek(key)?;
+            let mut target = match block.next(&mut cursor)? {
+                Some(Record::Index(IndexRecord { block_offset })) => block_offset as usize,
+                Some(_) => return Err(decode::Error::Corrupt("index block with unexpected record")),
+                None => {
+                    offset += block.len_in_table;
+                    continue;
+                }
+            };
+            loop {
+                let block = self.block_at(target)?.ok_or(decode::Error::Corrupt("index offset"))?;
+                if block.typ == typ {
+                    return Ok(Some(target));
+                } else if block.typ != BLOCK_TYPE_INDEX {
+                    return Err(decode::Error::Corrupt("index points to unexpected block type"));
+                }
+                let mut cursor = block.seek(key)?;
+                target = match block.next(&mut cursor)? {
+                    Some(Record::Index(IndexRecord { block_offset })) => block_offset as usize,
+                    _ => return Err(decode::Error::Corrupt("index level doesn't cover key")),
+                };
+            }
+        }
+        Ok(None)
+    }
+
+    /// Return the section cursor pointing to the first record of type `typ` with a key equal or greater than `key`.
+    pub(crate) fn seek(&self, typ: u8, key: &[u8]) -> Result<Option<SectionCursor>, decode::Error> {
+        let (start, index_position) = match typ {
+            BLOCK_TYPE_REF => (self.has_refs.then_some(0), self.ref_index_position),
+            BLOCK_TYPE_LOG => (self.log_position, self.log_index_position),
+            _ => unreachable!("BUG: only refs and logs can be sought"),
+        };
+        let mut offset = match (start, index_position) {
+            (None, _) => return Ok(None),
+            (Some(_), Some(index_position)) => match self.seek_indexed(index_position, typ, key)? {
+                Some(offset) => offset,
+                None => return Ok(None),
+            },
+            (Some(start), None) => start,
+        };
+        while let Some(block) = self.block_of_type(offset, typ)? {
+            let cursor = block.seek(key)?;
+            let mut probe = cursor.clone();
+            if block.next(&mut probe)?.is_some() {
+                return Ok(Some(SectionCursor {
+                    typ,
+                    block_offset: offset,
+                    cursor: Some(cursor),
+                }));
+            }
+            offset += block.len_in_table;
+        }
+        Ok(None)
+    }
+
+    /// Return a cursor to the first record of the section with type `typ`.
+    pub(crate) fn section_start(&self, typ: u8) -> Option<SectionCursor> {
+        let start = match typ {
+            BLOCK_TYPE_REF => self.has_refs.then_some(0),
+            BLOCK_TYPE_LOG => self.log_position,
+            _ => unreachable!("BUG: only refs and logs can be iterated"),
+        };
+        start.map(|block_offset| SectionCursor {
+            typ,
+            block_offset,
+            cursor: None,
+        })
+    }
+
+    /// Find the ref record named `name`, which may be a deletion.
+    pub(crate) fn find_ref(&self, name: &[u8]) -> Result<Option<RefRecord>, decode::Error> {
+        let mut cursor = match self.seek(BLOCK_TYPE_REF, name)? {
+            Some(cursor) => cursor,
+            None => return Ok(None),
+        };
+        Ok(match cursor.next(self)? {
+            Some(Record::Ref(record)) if record.name == name => Some(record),
+            _ => None,
+        })
+    }
+
+    /// Return all log records of the reference `name`, most recent first.
+    pub(crate) fn logs_of(&self, name: &[u8]) -> Result<Vec<LogRecord>, decode::Error> {
+        let mut key = name.to_owned();
+        key.push(0);
+        let mut cursor = match self.seek(BLOCK_TYPE_LOG, &key)? {
+            Some(cursor) => cursor,
+            None => return Ok(Vec::new()),
+        };
+        let mut out = Vec::new();
+        while let Some(Record::Log(record)) = cursor.next(self)? {
+            if record.name != name {
+                break;
+            }
+            out.push(record);
+        }
+        Ok(out)
+    }
+}
+
+/// A position in the section of a table, which can move across block boundaries.
+///
+/// It doesn't borrow the table which allows it to be stored alongside it.
+#[derive(Clone, Debug)]
+pub(crate) struct SectionCursor {
+    typ: u8,
+    block_offset: usize,
+    /// The position in the current block, or `None` if we are at its beginning.
+    cursor: Option<block::Cursor>,
+}
+
+impl SectionCursor {
+    /// Return the next record in the section of `table`, which must be the table this cursor was created for.
+    ///
+    /// Note that log blocks are decompressed each time this method is called.
+    pub fn next(&mut self, table: &Table) -> Result<Option<Record>, decode::Error> {
+        while let Some(block) = table.block_of_type(self.block_offset, self.typ)? {
+            let cursor = self.cursor.get_or_insert_with(|| block.cursor());
+            if let Some(record) = block.next(cursor)? {
+                return Ok(Some(record));
+            }
+            self.block_offset += block.len_in_table;
+            self.cursor = None;
+        }
+        Ok(None)
+    }
+}
+
+/// Writes a new table from sorted records.
+pub(crate) struct Writer {
+    pub block_size: u32,
+    pub object_hash: gix_hash::Kind,
+    pub min_update_index: u64,
+    pub max_update_index: u64,
+}
+
+impl Writer {
+    /// Write all `refs` and `logs`, which must be sorted by key, into a new table and return its bytes.
+    pub fn write(
+        &self,
+        refs: impl IntoIterator<Item = RefRecord>,
+        logs: impl IntoIterator<Item = LogRecord>,
+    ) -> Result<Vec<u8>, write::Error> {
+        if !(256..=MAX_BLOCK_SIZE).contains(&self.block_size) {
+            return Err(write::Error::BlockSize(self.block_size));
+        }
+        let header = Header::new(
+            self.object_hash,
+            self.block_size,
+            self.min_update_index,
+            self.max_update_index,
+        );
+        let mut out = Vec::new();
+        header.write_to(&mut out);
+
+        let mut value = Vec::new();
+        let blocks = self.write_section(
+            &mut out,
+            &header,
+            BLOCK_TYPE_REF,
+            refs.into_iter().map(|record| {
+                value.clear();
+                record.encode_value(self.min_update_index, &mut value);
+                let value_type = record.value_type();
+                (record.name, value_type, value.clone())
+            }),
+        )?;
+        let ref_index_position = self.write_index(&mut out, blocks)?;
+
+        let blocks = self.write_section(
+            &mut out,
+            &header,
+            BLOCK_TYPE_LOG,
+            logs.into_iter().map(|record| {
+                value.clear();
+                record.encode_value(&mut value);
+                (
+                    LogRecord::key(&record.name, record.update_index).into(),
+                    record.value_type(),
+                    value.clone(),
+                )
+            }),
+        )?;
+        let log_position = blocks.first().map_or(0, |(_, offset)| *offset);
+        let log_index_position = self.write_index(&mut out, blocks)?;
+
+        let footer_start = out.len();
+        header.write_to(&mut out);
+        for field in [ref_index_position, 0, 0, log_position, log_index_position] {
+            out.extend_from_slice(&field.to_be_bytes());
+        }
+        let crc = gix_features::hash::crc32(&out[footer_start..]);
+        out.extend_from_slice(&crc.to_be_bytes());
+        Ok(out)
+    }
+
+    /// Write all `records` as blocks of type `typ` and return the last key and offset of each written block.
+    fn write_section(
+        &self,
+        out: &mut Vec<u8>,
+        header: &Header,
+        typ: u8,
+        records: impl Iterator<Item = (gix_object::bstr::BString, u8, Vec<u8>)>,
+    ) -> Result<Vec<(Vec<u8>, u64)>, write::Error> {
+        let block_size = self.block_size as usize;
+        let header_len = |out: &Vec<u8>| if out.len() == header.len() { header.len() } else { 0 };
+        let mut blocks = Vec::new();
+        let mut writer = block::Writer::new(typ, header_len(out), block_size);
+        for (key, value_type, value) in records {
+            if writer.add(&key, value_type, &value) {
+                continue;
+            }
+            if !writer.is_empty() {
+                Self::finish_block(out, writer, &mut blocks)?;
+                writer = block::Writer::new(typ, header_len(out), block_size);
+                if writer.add(&key, value_type, &value) {
+                    continue;
+                }
+            }
+            if typ != BLOCK_TYPE_LOG {
+                return Err(write::Error::RecordTooLarge {
+                    key,
+                    block_size: self.block_size,
+                });
+            }
+            writer.unlimited();
+            if !writer.add(&key, value_type, &value) {
+                return Err(write::Error::RecordTooLarge {
+                    key,
+                    block_size: MAX_BLOCK_SIZE,
+                });
+            }
+            Self::finish_block(out, writer, &mut blocks)?;
+            writer = block::Writer::new(typ, header_len(out), block_size);
+        }
+        if !writer.is_empty() {
+            Self::finish_block(out, writer, &mut blocks)?;
+        }
+        Ok(blocks)
+    }
+
+    fn finish_block(
+        out: &mut Vec<u8>,
+        writer: block::Writer,
+        blocks: &mut Vec<(Vec<u8>, u64)>,
+    ) -> Result<(), write::Error> {
+        let offset = out.len() - writer.header_len();
+        let last_key = writer.last_key().to_owned();
+        writer.finish_into(out)?;
+        blocks.push((last_key, offset as u64));
+        Ok(())
+    }
+
+    /// Write a possibly multi-level index for `blocks` if there are enough of them, and return the position of its top level,
+    /// or 0 if no index was written.
+    fn write_index(&self, out: &mut Vec<u8>, mut blocks: Vec<(Vec<u8>, u64)>) -> Result<u64, write::Error> {
+        let mut index_position = 0;
+        let mut value = Vec::new();
+        while blocks.len() > INDEX_THRESHOLD {
+            index_position = out.len() as u64;
+            let num_blocks = blocks.len();
+            let mut index_blocks = Vec::new();
+            let mut writer = block::Writer::new(BLOCK_TYPE_INDEX, 0, self.block_size as usize);
+            for (key, block_offset) in blocks {
+                value.clear();
+                IndexRecord { block_offset }.encode_value(&mut value);
+                if writer.add(&key, 0, &value) {
+                    continue;
+                }
+                Self::finish_block(out, writer, &mut index_blocks)?;
+                writer = block::Writer::new(BLOCK_TYPE_INDEX, 0, self.block_size as usize);
+                if !writer.add(&key, 0, &value) {
+                    return Err(write::Error::RecordTooLarge {
+                        key: key.into(),
+                        block_size: self.block_size,
+                    });
+                }
+            }
+            Self::finish_block(out, writer, &mut index_blocks)?;
+            if index_blocks.len() >= num_blocks {
+                return Err(write::Error::RecordTooLarge {
+                    key: index_blocks.pop().map(|(key, _)| key).unwrap_or_default().into(),
+                    block_size: self.block_size,
+                });
+            }
+            blocks = index_blocks;
+        }
+        Ok(index_position)
+    }
+}
@@ -0,0 +1,516 @@
+use std::fmt::Formatter;
+
+use gix_hash::ObjectId;
+use gix_object::bstr::BString;
+
+use crate::{
+    store_impl::reftable::{Store, Transaction},
+    transaction::RefEdit,
+};
+
+#[derive(Debug)]
+pub(crate) struct Edit {
+    update: RefEdit,
+    /// Set if this update is coming from a symbolic reference and used to make it appear like it is the one that is handled,
+    /// instead of the referent reference.
+    parent_index: Option<usize>,
+    /// For symbolic refs, this is the previous OID to put into the reflog instead of our own previous value. It's the
+    /// peeled value of the leaf referent.
+    leaf_referent_previous_oid: Option<ObjectId>,
+}
+
+impl Edit {
+    fn name(&self) -> BString {
+        self.update.name.0.clone()
+    }
+}
+
+impl std::borrow::Borrow<RefEdit> for Edit {
+    fn borrow(&self) -> &RefEdit {
+        &self.update
+    }
+}
+
+impl std::borrow::BorrowMut<RefEdit> for Edit {
+    fn borrow_mut(&mut self) -> &mut RefEdit {
+        &mut self.update
+    }
+}
+
+/// Edits
+impl Store {
+    /// Open a transaction which will write all of its edits into a single new table once committed.
+    pub fn transaction(&self) -> Transaction<'_> {
+        Transaction {
+            store: self,
+            lock: None,
+            stack: None,
+            updates: None,
+            reflog_copies: Vec::new(),
+            reflog_deletions: Vec::new(),
+        }
+    }
+}
+
+impl<'s> Transaction<'s> {
+    /// Copy all reflog entries of `source` to `destination` when committing, keeping their position in the history.
+    pub(crate) fn copy_reflog(mut self, source: crate::FullName, destination: crate::FullName) -> Self {
+        self.reflog_copies.push((source, destination));
+        self
+    }
+
+    /// Remove the reflog entries of `name` at the given `update_indices` when committing.
+    pub(crate) fn delete_reflog_entries(mut self, name: crate::FullName, update_indices: Vec<u64>) -> Self {
+        self.reflog_deletions.extend(
+            update_indices
+                .into_iter()
+                .map(|update_index| (name.clone(), update_index)),
+        );
+        self
+    }
+}
+
+impl std::fmt::Debug for Transaction<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Transaction")
+            .field("store", self.store)
+            .field("edits", &self.updates.as_ref().map(|u| u.len()))
+            .finish_non_exhaustive()
+    }
+}
+
+///
+pub mod prepare {
+    use crate::{
+        store_impl::reftable::{transaction::Edit, Transaction},
+        transaction::{hook::Stage, Change, PreviousValue, RefEdit, RefEditsExt},
+        Reference, Target,
+    };
+
+    impl<'s> Transaction<'s> {
+        /// Prepare for calling [`commit(…)`][Transaction::commit()] by locking the stack of tables, failing according to
+        /// `lock_fail_mode` if it is locked already, and validating all `edits` against the current state of the stack.
+        ///
+        /// The lock is held until the transaction is committed or dropped, which rolls it back.
+        pub fn prepare(
+            mut self,
+            edits: impl IntoIterator<Item = RefEdit>,
+            lock_fail_mode: gix_lock::acquire::Fail,
+        ) -> Result<Self, Error> {
+            assert!(self.updates.is_none(), "BUG: Must not call prepare(…) multiple times");
+            let store = self.store;
+            let lock = gix_lock::File::acquire_to_update_resource(
+                store.tables_list_path(),
+                lock_fail_mode,
+                store.path().parent().map(ToOwned::to_owned),
+            )?;
+            let stack = store.force_refresh_stack()?;
+
+            let mut updates: Vec<_> = edits
+                .into_iter()
+                .map(|update| Edit {
+                    update,
+                    parent_index: None,
+                    leaf_referent_previous_oid: None,
+                })
+                .collect();
+            updates
+                .pre_process(
+                    |name| {
+                        crate::reftable::Store::find_one_with_verified_input(stack.as_ref(), name)
+                            .ok()
+                            .flatten()
+                            .map(|r| r.target)
+                    },
+                    |idx, update| Edit {
+                        update,
+                        parent_index: Some(idx),
+                        leaf_referent_previous_oid: None,
+                    },
+                )
+                .map_err(Error::PreprocessingFailed)?;
+
+            for cid in 0..updates.len() {
+                let change = &mut updates[cid];
+                let existing_ref = match &stack {
+                    Some(stack) => stack
+                        .find_ref(change.update.name.as_bstr())?
+                        .and_then(|record| record.into_reference())
+                        .transpose()?,
+                    None => None,
+                };
+                apply_change(store.object_hash(), change, existing_ref)?;
+
+                // traverse parent chain from leaf/peeled ref and set the leaf previous oid accordingly
+                // to help with their reflog entries
+                if let (Some(crate::TargetRef::Peeled(oid)), Some(parent_idx)) =
+                    (change.update.change.previous_value(), change.parent_index)
+                {
+                    let oid = oid.to_owned();
+                    let mut parent_idx_cursor = Some(parent_idx);
+                    while let Some(parent) = parent_idx_cursor.take().map(|idx| &mut updates[idx]) {
+                        parent_idx_cursor = parent.parent_index;
+                        parent.leaf_referent_previous_oid = Some(oid);
+                    }
+                }
+            }
+            if let Some(hook) = &store.transaction_hook {
+                let edits = updates.iter().map(|edit| &edit.update);
+                if let Err(err) = hook.call(Stage::Prepared, edits.clone(), store.object_hash()) {
+                    hook.call(Stage::Aborted, edits, store.object_hash()).ok();
+                    return Err(Error::Hook(err));
+                }
+            }
+            self.lock = Some(lock);
+            self.stack = stack;
+            self.updates = Some(updates);
+            Ok(self)
+        }
+
+        /// Rollback all intermediate state and return the `RefEdits` as we know them thus far.
+        ///
+        /// Note that they have been altered compared to what was initially provided as they have
+        /// been split and know about their current state on disk.
+        ///
+        /// # Note
+        ///
+        /// A rollback happens automatically as this instance is dropped as well, but without informing the hook.
+        ///
+        /// If a [hook][crate::transaction::hook::Hook] is set and the transaction was prepared, it will be informed that it was aborted.
+        pub fn rollback(self) -> Vec<RefEdit> {
+            let edits: Vec<_> = self
+                .updates
+                .map(|updates| updates.into_iter().map(|u| u.update).collect())
+                .unwrap_or_default();
+            if let Some(hook) = &self.store.transaction_hook {
+                hook.call(Stage::Aborted, &edits, self.store.object_hash()).ok();
+            }
+            edits
+        }
+    }
+
+    /// Validate the expectations of `change` against the `existing_ref` and remember its previous value.
+    fn apply_change(
+        object_hash: gix_hash::Kind,
+        change: &mut Edit,
+        existing_ref: Option<Reference>,
+    ) -> Result<(), Error> {
+        let full_name = change.name();
+        match &mut change.update.change {
+            Change::Delete { expected, .. } => {
+                match (&expected, &existing_ref) {
+                    (PreviousValue::MustNotExist, _) => {
+                        panic!("BUG: MustNotExist constraint makes no sense if references are to be deleted")
+                    }
+                    (PreviousValue::ExistingMustMatch(_), None)
+                    | (PreviousValue::MustExist, Some(_))
+                    | (PreviousValue::Any, Some(_))
+                    | (PreviousValue::Any, None) => {}
+                    (PreviousValue::MustExist, None) | (PreviousValue::MustExistAndMatch(_), None) => {
+                        return Err(Error::DeleteReferenceMustExist { full_name })
+                    }
+                    (PreviousValue::MustExistAndMatch(previous), Some(existing))
+                    | (PreviousValue::ExistingMustMatch(previous), Some(existing)) => {
+                        if *previous != existing.target {
+                            return Err(Error::ReferenceOutOfDate {
+                                full_name,
+                                expected: previous.clone(),
+                                actual: existing.target.clone(),
+                            });
+                        }
+                    }
+                }
+                if let Some(existing) = existing_ref {
+                    *expected = PreviousValue::MustExistAndMatch(existing.target);
+                }
+            }
+            Change::Update { expected, new, .. } => {
+                match (&expected, &existing_ref) {
+                    (PreviousValue::Any, _)
+                    | (PreviousValue::MustExist, Some(_))
+                    | (PreviousValue::MustNotExist, None)
+                    | (PreviousValue::ExistingMustMatch(_), None) => {}
+                    (PreviousValue::MustExist, None) => {
+                        let expected = Target::Peeled(object_hash.null());
+                        return Err(Error::MustExist { full_name, expected });
+                    }
+                    (PreviousValue::MustNotExist, Some(existing)) => {
+                        if existing.target != *new {
+                            return Err(Error::MustNotExist {
+                                full_name,
+                                actual: existing.target.clone(),
+                                new: new.clone(),
+                            });
+                        }
+                    }
+                    (PreviousValue::MustExistAndMatch(previous), Some(existing))
+                    | (PreviousValue::ExistingMustMatch(previous), Some(existing)) => {
+                        if *previous != existing.target {
+                            return Err(Error::ReferenceOutOfDate {
+                                full_name,
+                                expected: previous.clone(),
+                                actual: existing.target.clone(),
+                            });
+                        }
+                    }
+                    (PreviousValue::MustExistAndMatch(previous), None) => {
+                        let expected = previous.clone();
+                        return Err(Error::MustExist { full_name, expected });
+                    }
+                }
+                if let Some(existing) = existing_ref {
+                    *expected = PreviousValue::MustExistAndMatch(existing.target);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    mod error {
+        use gix_object::bstr::BString;
+
+        use crate::{
+            store_impl::reftable::{stack, table},
+            Target,
+        };
+
+        /// The error returned by [`Transaction::prepare()`][crate::reftable::Transaction::prepare()].
+        #[derive(Debug, thiserror::Error)]
+        #[allow(missing_docs)]
+        pub enum Error {
+            #[error("The lock for the list of tables could not be obtained")]
+            LockAcquire(#[from] gix_lock::acquire::Error),
+            #[error(transparent)]
+            Open(#[from] stack::open::Error),
+            #[error("An existing reference could not be read")]
+            Decode(#[from] table::decode::Error),
+            #[error("Edit preprocessing failed with an error")]
+            PreprocessingFailed(#[source] std::io::Error),
+            #[error("The reference {full_name:?} for deletion did not exist")]
+            DeleteReferenceMustExist { full_name: BString },
+            #[error("Reference {full_name:?} was not supposed to exist when writing it with value {new:?}, but actual content was {actual:?}")]
+            MustNotExist {
+                full_name: BString,
+                actual: Target,
+                new: Target,
+            },
+            #[error("Reference {full_name:?} was supposed to exist with value {expected}, but didn't.")]
+            MustExist { full_name: BString, expected: Target },
+            #[error("The reference {full_name:?} should have content {expected}, actual content was {actual}")]
+            ReferenceOutOfDate {
+                full_name: BString,
+                expected: Target,
+                actual: Target,
+            },
+            #[error("The reference-transaction hook rejected the transaction")]
+            Hook(#[source] crate::transaction::hook::Error),
+        }
+    }
+    pub use error::Error;
+}
+
+///
+pub mod commit {
+    use crate::{
+        log,
+        store::WriteReflog,
+        store_impl::reftable::{
+            record::{LogRecord, RefRecord, RefValue},
+            stack::table_name,
+            table, Transaction,
+        },
+        transaction::{hook::Stage, Change, PreviousValue, RefEdit, RefLog},
+        FullNameRef, Target,
+    };
+
+    impl<'s> Transaction<'s> {
+        /// Make all [prepared][Transaction::prepare()] edits permanent by writing them into a new table and return the
+        /// performed edits, which may have been adjusted to contain more dependent edits or additional information.
+        ///
+        /// `committer` is used in the reflog and only if the reflog is actually written, which is why it is optional.
+        /// Unlike with loose references, the transaction is atomic and no edit is visible if it fails.
+        ///
+        /// Tables may be compacted afterwards, which is skipped if the stack is locked by another process.
+        ///
+        /// If a [hook][crate::transaction::hook::Hook] is set, it will be informed about the committed edits, or that the
+        /// transaction was aborted if the commit failed.
+        pub fn commit<'a>(
+            self,
+            committer: impl Into<Option<gix_actor::SignatureRef<'a>>>,
+        ) -> Result<Vec<RefEdit>, Error> {
+            let store = self.store;
+            let hook_edits: Option<Vec<_>> = store
+                .transaction_hook
+                .as_ref()
+                .and(self.updates.as_ref())
+                .map(|updates| updates.iter().map(|edit| edit.update.clone()).collect());
+            let res = self.commit_inner(committer.into());
+            if let Some((hook, edits)) = store.transaction_hook.as_ref().zip(hook_edits) {
+                let stage = if res.is_ok() { Stage::Committed } else { Stage::Aborted };
+                hook.call(stage, &edits, store.object_hash()).ok();
+            }
+            res
+        }
+
+        fn commit_inner(self, committer: Option<gix_actor::SignatureRef<'_>>) -> Result<Vec<RefEdit>, Error> {
+            let updates = self.updates.expect("BUG: must call prepare before commit");
+            let lock = self.lock.expect("BUG: prepare acquires the lock");
+            let store = self.store;
+            let stack = self.stack;
+            let update_index = stack.as_ref().map_or(1, |stack| stack.next_update_index());
+
+            let mut refs = Vec::new();
+            let mut logs = Vec::new();
+            if let Some(stack) = &stack {
+                for (source, destination) in &self.reflog_copies {
+                    logs.extend(
+                        stack
+                            .logs_of(source.as_bstr())?
+                            .into_iter()
+                            .map(|(update_index, line)| LogRecord {
+                                name: destination.as_bstr().to_owned(),
+                                update_index,
+                                line: Some(line),
+                            }),
+                    );
+                }
+            }
+            logs.extend(self.reflog_deletions.into_iter().map(|(name, update_index)| LogRecord {
+                name: name.into_inner(),
+                update_index,
+                line: None,
+            }));
+            for change in &updates {
+                assert!(!change.update.deref, "Deref mode is turned into splits and turned off");
+                let name = change.update.name.as_ref();
+                let existing_logs = match &stack {
+                    Some(stack) => stack.logs_of(name.as_bstr())?,
+                    None => Default::default(),
+                };
+                match &change.update.change {
+                    Change::Update { log, new, expected } => {
+                        let log_update = match new {
+                            Target::Symbolic(_) => {
+                                // no reflog for symref changes, unless the ref is new and we can obtain a peeled id
+                                // identified by the expectation of what could be there, as is the case when cloning.
+                                match expected {
+                                    PreviousValue::ExistingMustMatch(Target::Peeled(oid)) => {
+                                        Some((Some(gix_hash::ObjectId::null(oid.kind())), oid))
+                                    }
+                                    _ => None,
+                                }
+                            }
+                            Target::Peeled(new_oid) => {
+                                let previous = match expected {
+                                    PreviousValue::MustExistAndMatch(Target::Peeled(oid)) => Some(oid.to_owned()),
+                                    _ => None,
+                                }
+                                .or(change.leaf_referent_previous_oid);
+                                Some((previous, new_oid))
+                            }
+                        };
+                        if let Some((previous, new_oid)) = log_update {
+                            let do_update = previous.as_ref() != Some(new_oid);
+                            let write_log = match store.write_reflog {
+                                WriteReflog::Disable => false,
+                                WriteReflog::Always => true,
+                                WriteReflog::Normal => {
+                                    log.force_create_reflog
+                                        || should_autocreate_reflog(name)
+                                        || !existing_logs.is_empty()
+                                }
+                            };
+                            if do_update && write_log {
+                                let committer = committer.ok_or(Error::MissingCommitter)?;
+                                logs.push(LogRecord {
+                                    name: name.as_bstr().to_owned(),
+                                    update_index,
+                                    line: Some(log::Line {
+                                        previous_oid: previous.unwrap_or_else(|| new_oid.kind().null()),
+                                        new_oid: new_oid.to_owned(),
+                                        signature: committer.to_owned(),
+                                        message: log.message.clone(),
+                                    }),
+                                });
+                            }
+                        }
+                        if log.mode == RefLog::AndReference {
+                            refs.push(RefRecord {
+                                name: name.as_bstr().to_owned(),
+                                update_index,
+                                value: match new {
+                                    Target::Peeled(id) => RefValue::Object(*id),
+                                    Target::Symbolic(target) => RefValue::Symbolic(target.as_bstr().to_owned()),
+                                },
+                            });
+                        }
+                    }
+                    Change::Delete { log: mode, .. } => {
+                        logs.extend(existing_logs.into_keys().map(|idx| LogRecord {
+                            name: name.as_bstr().to_owned(),
+                            update_index: idx,
+                            line: None,
+                        }));
+                        if *mode == RefLog::AndReference {
+                            refs.push(RefRecord {
+                                name: name.as_bstr().to_owned(),
+                                update_index,
+                                value: RefValue::Deletion,
+                            });
+                        }
+                    }
+                }
+            }
+
+            if !refs.is_empty() || !logs.is_empty() {
+                refs.sort_by(|a, b| a.name.cmp(&b.name));
+                logs.sort_by_cached_key(|record| LogRecord::key(&record.name, record.update_index));
+                let data = table::Writer {
+                    block_size: store.block_size,
+                    object_hash: store.object_hash(),
+                    min_update_index: update_index,
+                    max_update_index: update_index,
+                }
+                .write(refs, logs)?;
+                let name = store.write_table(&data, update_index, update_index)?;
+                let mut names: Vec<_> = stack
+                    .as_ref()
+                    .map(|stack| stack.tables().iter().map(table_name).collect())
+                    .unwrap_or_default();
+                names.push(name);
+                store.commit_tables_list(lock, &names)?;
+                // Compaction is an optimization which may be retried with the next transaction.
+                store.auto_compact().ok();
+            }
+            Ok(updates.into_iter().map(|edit| edit.update).collect())
+        }
+    }
+
+    fn should_autocreate_reflog(name: &FullNameRef) -> bool {
+        let name = name.as_bstr();
+        name.starts_with(b"refs/heads/")
+            || name.starts_with(b"refs/remotes/")
+            || name.starts_with(b"refs/notes/")
+            || name.starts_with(b"refs/worktree/") // NOTE: git does not write reflogs for worktree private refs
+            || name == "HEAD"
+    }
+
+    mod error {
+        use crate::store_impl::reftable::table;
+
+        /// The error returned by [`Transaction::commit()`][crate::reftable::Transaction::commit()].
+        #[derive(Debug, thiserror::Error)]
+        #[allow(missing_docs)]
+        pub enum Error {
+            #[error("Reflog entries need a committer, but none was provided")]
+            MissingCommitter,
+            #[error("Existing reflog entries could not be read")]
+            Decode(#[from] table::decode::Error),
+            #[error("The new table could not be created")]
+            Write(#[from] table::write::Error),
+            #[error("The new table or the list of tables could not be written")]
+            Io(#[from] std::io::Error),
+        }
+    }
+    pub use error::Error;
+}
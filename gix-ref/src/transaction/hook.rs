@@ -0,0 +1,108 @@
+use std::io::Write;
+
+use gix_features::threading::OwnShared;
+
+use crate::{
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    Target,
+};
+
+/// The stage of a transaction at which a [`Hook`] is invoked.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Stage {
+    /// All references are locked and the transaction is about to be committed. Failing in this stage aborts the transaction.
+    Prepared,
+    /// The transaction was committed.
+    Committed,
+    /// The transaction was rolled back after it was prepared, or because the hook failed in the [`Prepared`][Stage::Prepared] stage.
+    Aborted,
+}
+
+impl Stage {
+    /// Return the name of the stage as passed to git's `reference-transaction` hook.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Stage::Prepared => "prepared",
+            Stage::Committed => "committed",
+            Stage::Aborted => "aborted",
+        }
+    }
+}
+
+/// The error returned by the function of a [`Hook`].
+pub type Error = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// The function invoked by a [`Hook`] with the stage of the transaction and a description of all changes in the format
+/// git passes to the `reference-transaction` hook on standard input, that is one `<old-value> SP <new-value> SP <ref-name> LF`
+/// line per changed reference.
+///
+/// Values are hexadecimal object ids, `ref:<name>` for symbolic references, or the null id if the reference
+/// doesn't exist before or after the change.
+pub type HookFn = dyn Fn(Stage, &[u8]) -> Result<(), Error> + Send + Sync;
+
+/// A function to call at each [stage][Stage] of a transaction, typically to run git's `reference-transaction` hook.
+///
+/// Changes which only affect the reflog aren't passed to the hook, and it is only invoked if there is at least one change.
+#[derive(Clone)]
+pub struct Hook(OwnShared<HookFn>);
+
+impl Hook {
+    /// Create a new instance which calls `hook` at each stage of a transaction.
+    pub fn new(hook: impl Fn(Stage, &[u8]) -> Result<(), Error> + Send + Sync + 'static) -> Self {
+        Hook(OwnShared::new(hook))
+    }
+
+    /// Call the hook for `stage` with all `edits` that change references, after they were prepared and know their
+    /// previous value.
+    pub(crate) fn call<'a>(
+        &self,
+        stage: Stage,
+        edits: impl IntoIterator<Item = &'a RefEdit>,
+        object_hash: gix_hash::Kind,
+    ) -> Result<(), Error> {
+        let input = input(edits, object_hash);
+        if input.is_empty() {
+            return Ok(());
+        }
+        (self.0)(stage, &input)
+    }
+}
+
+impl std::fmt::Debug for Hook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Hook(..)")
+    }
+}
+
+fn input<'a>(edits: impl IntoIterator<Item = &'a RefEdit>, object_hash: gix_hash::Kind) -> Vec<u8> {
+    let null = object_hash.null();
+    let write_target = |out: &mut Vec<u8>, target: Option<&Target>| match target {
+        Some(Target::Peeled(id)) => write!(out, "{id}"),
+        Some(Target::Symbolic(name)) => write!(out, "ref:{}", name.as_bstr()),
+        None => write!(out, "{null}"),
+    };
+    let mut out = Vec::new();
+    for edit in edits {
+        let (expected, new) = match &edit.change {
+            Change::Update {
+                log: LogChange { mode: RefLog::Only, .. },
+                ..
+            }
+            | Change::Delete { log: RefLog::Only, .. } => continue,
+            Change::Update { expected, new, .. } => (expected, Some(new)),
+            Change::Delete { expected, .. } => (expected, None),
+        };
+        // Prepared transactions know the previous value of all existing references.
+        let previous = match expected {
+            PreviousValue::MustExistAndMatch(previous) => Some(previous),
+            _ => None,
+        };
+        write_target(&mut out, previous).expect("in-memory writes don't fail");
+        out.push(b' ');
+        write_target(&mut out, new).expect("in-memory writes don't fail");
+        out.push(b' ');
+        out.extend_from_slice(edit.name.as_bstr());
+        out.push(b'\n');
+    }
+    out
+}
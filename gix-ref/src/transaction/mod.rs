@@ -5,6 +5,10 @@
 //!
 //! * create or update reference
 //! * delete references
+//! * rename or copy references along with their reflog, via [`store::Handle::rename_reference()`][crate::store::Handle::rename_reference()]
+//!
+//! A [`Hook`][hook::Hook] can be set on a store to be informed about each stage of its transactions, and to veto them
+//! before they are committed.
 //!
 //! The following guarantees are made:
 //!
@@ -141,3 +145,6 @@ pub enum RefLog {
 
 mod ext;
 pub use ext::RefEditsExt;
+
+///
+pub mod hook;
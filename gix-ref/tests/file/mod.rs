@@ -22,7 +22,7 @@ pub fn store_at(name: &str) -> crate::Result<Store> {
     ))
 }
 
-fn store_writable(name: &str) -> crate::Result<(gix_testtools::tempfile::TempDir, Store)> {
+pub(crate) fn store_writable(name: &str) -> crate::Result<(gix_testtools::tempfile::TempDir, Store)> {
     let dir = gix_testtools::scripted_fixture_writable_standalone(name)?;
     let git_dir = dir.path().join(".git");
     Ok((
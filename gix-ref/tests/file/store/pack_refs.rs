@@ -0,0 +1,124 @@
+use gix_lock::acquire::Fail;
+use gix_object::bstr::BString;
+use gix_odb::Find;
+use gix_ref::file::pack_refs::{Filter, Options};
+
+use crate::file::{store_with_packed_refs, store_writable, Store};
+
+fn find_in_odb(store: &Store) -> crate::Result<Box<gix_ref::file::transaction::FindObjectFn<'static>>> {
+    let odb = gix_odb::at(store.git_dir().join("objects"))?;
+    Ok(Box::new(move |oid, buf| {
+        odb.try_find(oid, buf)
+            .map(|obj| obj.map(|obj| obj.kind))
+            .map_err(|err| Box::new(err) as Box<dyn std::error::Error + Send + Sync>)
+    }))
+}
+
+fn loose_names(store: &Store) -> crate::Result<Vec<String>> {
+    Ok(store
+        .loose_iter()?
+        .filter_map(Result::ok)
+        .map(|r| r.name.as_bstr().to_string())
+        .collect())
+}
+
+#[test]
+fn all_peeled_references_are_packed_and_pruned_like_git_does() -> crate::Result {
+    let (_keep, store) = store_writable("make_ref_repository.sh")?;
+    let bisect = store.git_dir().join("refs").join("bisect");
+    std::fs::create_dir_all(&bisect)?;
+    std::fs::copy(store.git_dir().join("refs/heads/main"), bisect.join("bad"))?;
+
+    let outcome = store.pack_refs(
+        Options {
+            filter: Filter::All,
+            prune: true,
+        },
+        Fail::Immediately,
+        Fail::Immediately,
+        find_in_odb(&store)?,
+    )?;
+    assert_eq!(outcome.packed.len(), 8, "all peeled refs are packed");
+    assert!(
+        outcome.packed.iter().all(|name| name.as_bstr() != "refs/bisect/bad"),
+        "worktree-private references are never packed"
+    );
+
+    assert_eq!(
+        loose_names(&store)?,
+        [
+            "refs/bisect/bad",
+            "refs/heads/multi-link-target1",
+            "refs/loop-a",
+            "refs/loop-b",
+            "refs/multi-link",
+            "refs/remotes/origin/HEAD",
+            "refs/tags/multi-link-target2"
+        ],
+        "only symbolic and worktree-private refs are left"
+    );
+    let expected_pack_data: BString = std::fs::read(store_with_packed_refs()?.packed_refs_path())?.into();
+    let actual_packed_data: BString = std::fs::read(store.packed_refs_path())?.into();
+    assert_eq!(
+        actual_packed_data, expected_pack_data,
+        "both gitoxide and git must agree on the packed refs file perfectly"
+    );
+    assert_eq!(
+        store
+            .reflog_iter("refs/heads/main", &mut Vec::new())?
+            .expect("still present")
+            .count(),
+        1,
+        "reflogs are kept without adding entries"
+    );
+    Ok(())
+}
+
+#[test]
+fn by_default_only_tags_and_previously_packed_references_are_packed() -> crate::Result {
+    let (_keep, store) = store_writable("make_packed_ref_repository_for_overlay.sh")?;
+    let tag = store.git_dir().join("refs").join("tags");
+    std::fs::create_dir_all(&tag)?;
+    std::fs::copy(store.git_dir().join("refs/heads/newer-as-loose"), tag.join("new-tag"))?;
+    std::fs::copy(
+        store.git_dir().join("refs/heads/newer-as-loose"),
+        store.git_dir().join("refs/heads/new-branch"),
+    )?;
+
+    let loose_value = store.find_loose("newer-as-loose")?.target;
+
+    let outcome = store.pack_refs(
+        Options::default(),
+        Fail::Immediately,
+        Fail::Immediately,
+        find_in_odb(&store)?,
+    )?;
+    assert_eq!(
+        outcome
+            .packed
+            .iter()
+            .map(|name| name.as_bstr().to_string())
+            .collect::<Vec<_>>(),
+        ["refs/heads/newer-as-loose", "refs/tags/new-tag"],
+        "branches are only packed if they are packed already"
+    );
+    assert_eq!(
+        loose_names(&store)?,
+        ["refs/heads/new-branch", "refs/remotes/origin/HEAD"],
+        "new branches and symbolic refs stay loose"
+    );
+    assert_eq!(
+        store.find("newer-as-loose")?.target,
+        loose_value,
+        "the packed value was updated to the one of the loose reference"
+    );
+
+    let outcome = store.pack_refs(
+        Options::default(),
+        Fail::Immediately,
+        Fail::Immediately,
+        find_in_odb(&store)?,
+    )?;
+    assert!(outcome.packed.is_empty(), "there is nothing left to pack");
+    Ok(())
+}
@@ -359,6 +359,87 @@ fn reference_with_must_not_exist_constraint_may_exist_already_if_the_new_value_m
     Ok(())
 }
 
+#[test]
+fn moving_a_symbolic_reference_to_another_existing_reference_is_logged() -> crate::Result {
+    let (_keep, store) = store_writable("make_repo_for_reflog.sh")?;
+    let main_id = store.find_loose("main")?.target.into_id();
+    let previous_reflog_count = reflog_lines(&store, "HEAD")?.len();
+
+    let move_head = |target: Target, message: &str| -> crate::Result<Vec<RefEdit>> {
+        Ok(store
+            .transaction()
+            .prepare(
+                Some(RefEdit {
+                    change: Change::Update {
+                        log: LogChange {
+                            mode: RefLog::AndReference,
+                            force_create_reflog: false,
+                            message: message.into(),
+                        },
+                        new: target,
+                        expected: PreviousValue::Any,
+                    },
+                    name: "HEAD".try_into()?,
+                    deref: false,
+                }),
+                Fail::Immediately,
+                Fail::Immediately,
+            )?
+            .commit(committer().to_ref())?)
+    };
+
+    store
+        .transaction()
+        .prepare(
+            Some(RefEdit {
+                change: Change::Update {
+                    log: LogChange::default(),
+                    new: Target::Peeled(main_id),
+                    expected: PreviousValue::MustNotExist,
+                },
+                name: "refs/heads/other".try_into()?,
+                deref: false,
+            }),
+            Fail::Immediately,
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+    move_head(
+        Target::Symbolic("refs/heads/other".try_into()?),
+        "checkout: moving from main to other",
+    )?;
+    let lines = reflog_lines(&store, "HEAD")?;
+    assert_eq!(lines.len(), previous_reflog_count + 1, "a new reflog line is added");
+    assert_eq!(
+        lines.last().expect("present"),
+        &log_line(main_id, main_id, "checkout: moving from main to other"),
+        "the move is logged even though the id doesn't change"
+    );
+
+    move_head(Target::Symbolic("refs/heads/other".try_into()?), "no change")?;
+    move_head(
+        Target::Symbolic("refs/heads/unborn".try_into()?),
+        "checkout: moving from other to unborn",
+    )?;
+    assert_eq!(
+        reflog_lines(&store, "HEAD")?.len(),
+        previous_reflog_count + 1,
+        "nothing is logged if the target doesn't change or if the new referent doesn't exist"
+    );
+
+    move_head(
+        Target::Symbolic("refs/heads/other".try_into()?),
+        "checkout: moving from unborn to other",
+    )?;
+    move_head(Target::Peeled(main_id), "checkout: moving from other to main")?;
+    assert_eq!(
+        reflog_lines(&store, "HEAD")?.last().expect("present"),
+        &log_line(main_id, main_id, "checkout: moving from other to main"),
+        "detaching is logged as well, with the previous id obtained from the former referent"
+    );
+    Ok(())
+}
+
 #[test]
 fn cancellation_after_preparation_leaves_no_change() -> crate::Result {
     let (dir, store) = empty_store()?;
@@ -761,6 +842,87 @@ fn packed_refs_creation_with_packed_refs_mode_prune_removes_original_loose_refs(
     Ok(())
 }
 
+#[test]
+fn packed_refs_creation_with_initial_transaction_writes_packed_refs_directly() -> crate::Result {
+    let (_keep, source) = store_writable("make_ref_repository.sh")?;
+    let (_keep, store) = empty_store()?;
+    let odb = gix_odb::at(source.git_dir().join("objects"))?;
+    let find = move |oid, buf: &mut Vec<u8>| {
+        odb.try_find(oid, buf)
+            .map(|obj| obj.map(|obj| obj.kind))
+            .map_err(|err| Box::new(err) as Box<dyn std::error::Error + Send + Sync>)
+    };
+    let mut edits: Vec<_> = source
+        .loose_iter()?
+        .filter_map(|r| r.ok().filter(|r| r.kind() == gix_ref::Kind::Peeled))
+        .map(|r| RefEdit {
+            change: Change::Update {
+                log: LogChange {
+                    mode: RefLog::AndReference,
+                    force_create_reflog: false,
+                    message: "clone".into(),
+                },
+                expected: PreviousValue::MustNotExist,
+                new: r.target,
+            },
+            name: r.name,
+            deref: false,
+        })
+        .collect();
+    let num_peeled = edits.len();
+    edits.push(create_symbolic_at(
+        "refs/remotes/origin/HEAD",
+        "refs/remotes/origin/main",
+    ));
+
+    let edits = store
+        .transaction()
+        .packed_refs(PackedRefs::Initial(Box::new(find.clone())))
+        .prepare(edits, Fail::Immediately, Fail::Immediately)?
+        .commit(committer().to_ref())?;
+    assert_eq!(edits.len(), num_peeled + 1, "all edits were performed");
+
+    assert_eq!(
+        store
+            .loose_iter()?
+            .map(|r| r.map(|r| r.name.as_bstr().to_owned()))
+            .collect::<Result<Vec<_>, _>>()?,
+        ["refs/remotes/origin/HEAD"],
+        "only symbolic refs are written as loose refs"
+    );
+    let expected_pack_data: BString = std::fs::read(store_with_packed_refs()?.packed_refs_path())?.into();
+    let actual_packed_data: BString = std::fs::read(store.packed_refs_path())?.into();
+    assert_eq!(
+        actual_packed_data, expected_pack_data,
+        "the result is the same as if the refs were packed by git"
+    );
+
+    assert_eq!(
+        reflog_lines(&store, "refs/heads/main")?.len(),
+        1,
+        "reflogs are written where needed"
+    );
+    let mut buf = Vec::new();
+    assert!(
+        store.reflog_iter("refs/tags/dt1", &mut buf)?.is_none(),
+        "tags don't get reflogs"
+    );
+
+    let err = store
+        .transaction()
+        .packed_refs(PackedRefs::Initial(Box::new(find)))
+        .prepare(Some(create_at("refs/heads/new")), Fail::Immediately, Fail::Immediately)
+        .unwrap_err();
+    assert!(
+        matches!(
+            err,
+            transaction::prepare::Error::InitialTransactionWithPackedRefs { .. }
+        ),
+        "initial transactions can only be performed once"
+    );
+    Ok(())
+}
+
 #[test]
 fn packed_refs_creation_with_packed_refs_mode_leave_keeps_original_loose_refs() -> crate::Result {
     let (_keep, store) = store_writable("make_packed_ref_repository_for_overlay.sh")?;
@@ -0,0 +1,330 @@
+use std::convert::TryInto;
+
+use gix_lock::acquire::Fail;
+use gix_ref::{
+    reftable,
+    store::WriteReflog,
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    Target,
+};
+
+use crate::{
+    file::transaction::prepare_and_commit::{committer, empty_store},
+    util::hex_to_id,
+};
+
+fn store() -> (tempfile::TempDir, reftable::Store) {
+    let dir = tempfile::TempDir::new().unwrap();
+    let store = reftable::Store::at(dir.path(), WriteReflog::Normal, gix_hash::Kind::Sha1);
+    (dir, store)
+}
+
+fn id(n: u8) -> gix_hash::ObjectId {
+    gix_hash::ObjectId::from([n; 20])
+}
+
+fn update(name: &str, new: Target, expected: PreviousValue) -> RefEdit {
+    RefEdit {
+        change: Change::Update {
+            log: LogChange {
+                mode: RefLog::AndReference,
+                force_create_reflog: false,
+                message: "update".into(),
+            },
+            expected,
+            new,
+        },
+        name: name.try_into().expect("valid"),
+        deref: false,
+    }
+}
+
+fn delete(name: &str) -> RefEdit {
+    RefEdit {
+        change: Change::Delete {
+            expected: PreviousValue::Any,
+            log: RefLog::AndReference,
+        },
+        name: name.try_into().expect("valid"),
+        deref: false,
+    }
+}
+
+fn commit(store: &reftable::Store, edits: impl IntoIterator<Item = RefEdit>) -> crate::Result<Vec<RefEdit>> {
+    Ok(store
+        .transaction()
+        .prepare(edits, Fail::Immediately)?
+        .commit(committer().to_ref())?)
+}
+
+#[test]
+fn empty_store_has_no_references() -> crate::Result {
+    let (_dir, store) = store();
+    assert!(store.try_find("HEAD")?.is_none());
+    assert!(store.stack()?.is_none(), "there is no tables.list yet");
+    assert_eq!(store.iter()?.count(), 0);
+    assert!(store.reflog("refs/heads/main")?.is_none());
+    Ok(())
+}
+
+#[test]
+fn transactions_create_find_and_iterate_references() -> crate::Result {
+    let (dir, store) = store();
+    commit(
+        &store,
+        [
+            update(
+                "HEAD",
+                Target::Symbolic("refs/heads/main".try_into()?),
+                PreviousValue::MustNotExist,
+            ),
+            update("refs/heads/main", Target::Peeled(id(1)), PreviousValue::MustNotExist),
+            update("refs/tags/v1", Target::Peeled(id(2)), PreviousValue::MustNotExist),
+            update("refs/remotes/origin/HEAD", Target::Peeled(id(3)), PreviousValue::Any),
+        ],
+    )?;
+    assert!(dir.path().join("reftable/tables.list").is_file());
+
+    let head = store.find("HEAD")?;
+    assert_eq!(head.target, Target::Symbolic("refs/heads/main".try_into()?));
+    assert_eq!(
+        store.find("main")?.target,
+        Target::Peeled(id(1)),
+        "partial names are found"
+    );
+    assert_eq!(store.find("v1")?.name.as_bstr(), "refs/tags/v1");
+    assert_eq!(
+        store.find("origin")?.name.as_bstr(),
+        "refs/remotes/origin/HEAD",
+        "remote HEADs are found by their remote name"
+    );
+    assert!(matches!(
+        store.find("does-not-exist"),
+        Err(reftable::find::existing::Error::NotFound { .. })
+    ));
+
+    let names = store
+        .iter()?
+        .map(|r| r.map(|r| r.name.as_bstr().to_string()))
+        .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(
+        names,
+        ["HEAD", "refs/heads/main", "refs/remotes/origin/HEAD", "refs/tags/v1"],
+        "references are sorted by name"
+    );
+    assert_eq!(store.iter_prefixed("refs/tags/")?.count(), 1);
+    assert_eq!(store.iter_prefixed("refs/notes/")?.count(), 0);
+
+    let log = store.reflog("refs/heads/main")?.expect("branches have reflogs");
+    assert_eq!(log.len(), 1);
+    assert_eq!(log[0].previous_oid, gix_hash::Kind::Sha1.null());
+    assert_eq!(log[0].new_oid, id(1));
+    assert_eq!(
+        log[0].signature,
+        committer(),
+        "signatures round-trip including their timezone"
+    );
+    assert_eq!(log[0].message, "update");
+    assert!(
+        !store.reflog_exists("refs/tags/v1")?,
+        "tags don't get reflogs by default"
+    );
+    Ok(())
+}
+
+#[test]
+fn updates_through_symbolic_refs_are_logged_and_deletions_remove_logs() -> crate::Result {
+    let (_dir, store) = store();
+    commit(
+        &store,
+        [
+            update(
+                "HEAD",
+                Target::Symbolic("refs/heads/main".try_into()?),
+                PreviousValue::MustNotExist,
+            ),
+            update("refs/heads/main", Target::Peeled(id(1)), PreviousValue::MustNotExist),
+        ],
+    )?;
+
+    let mut edit = update(
+        "HEAD",
+        Target::Peeled(id(2)),
+        PreviousValue::MustExistAndMatch(Target::Peeled(id(1))),
+    );
+    edit.deref = true;
+    let edits = commit(&store, Some(edit))?;
+    assert_eq!(edits.len(), 2, "the symbolic ref is split into an edit of its referent");
+    assert_eq!(
+        store.find("HEAD")?.target,
+        Target::Symbolic("refs/heads/main".try_into()?),
+        "HEAD keeps pointing to its branch"
+    );
+    assert_eq!(store.find("main")?.target, Target::Peeled(id(2)));
+
+    let log = store.reflog("refs/heads/main")?.expect("present");
+    assert_eq!(log.len(), 2);
+    assert_eq!((log[1].previous_oid, log[1].new_oid), (id(1), id(2)));
+    let head_log = store.reflog("HEAD")?.expect("HEAD is logged as well");
+    assert_eq!((head_log[0].previous_oid, head_log[0].new_oid), (id(1), id(2)));
+
+    let err = commit(
+        &store,
+        Some(update(
+            "refs/heads/main",
+            Target::Peeled(id(3)),
+            PreviousValue::MustExistAndMatch(Target::Peeled(id(1))),
+        )),
+    )
+    .unwrap_err();
+    assert!(
+        err.to_string().contains("should have content"),
+        "expectations are validated: {err}"
+    );
+
+    commit(&store, Some(delete("refs/heads/main")))?;
+    assert!(store.try_find("main")?.is_none());
+    assert!(
+        store.reflog("refs/heads/main")?.is_none(),
+        "the reflog is deleted as well"
+    );
+    assert_eq!(store.iter()?.count(), 1, "only HEAD remains");
+    Ok(())
+}
+
+#[test]
+fn a_prepared_transaction_locks_the_stack_until_it_is_dropped() -> crate::Result {
+    let (_dir, store) = store();
+    let transaction = store.transaction().prepare(
+        Some(update("refs/heads/a", Target::Peeled(id(1)), PreviousValue::Any)),
+        Fail::Immediately,
+    )?;
+    assert!(store
+        .transaction()
+        .prepare(
+            Some(update("refs/heads/b", Target::Peeled(id(1)), PreviousValue::Any)),
+            Fail::Immediately
+        )
+        .is_err());
+    drop(transaction);
+    assert!(store.try_find("a")?.is_none(), "nothing was committed");
+    commit(
+        &store,
+        Some(update("refs/heads/b", Target::Peeled(id(1)), PreviousValue::Any)),
+    )?;
+    Ok(())
+}
+
+#[test]
+fn many_references_use_restarts_and_index_blocks_and_compaction_keeps_the_stack_small() -> crate::Result {
+    let (_dir, mut store) = store();
+    store.block_size = 256;
+    for batch in 0..20u8 {
+        commit(
+            &store,
+            (0..50u8).map(|n| {
+                update(
+                    &format!("refs/pull/{batch:02}{n:02}/head"),
+                    Target::Peeled(id(n)),
+                    PreviousValue::MustNotExist,
+                )
+            }),
+        )?;
+    }
+    let num_tables = store.stack()?.expect("present").tables().len();
+    assert!(
+        num_tables < 10,
+        "automatic compaction keeps the amount of tables logarithmic, got {num_tables}"
+    );
+
+    commit(&store, Some(delete("refs/pull/0000/head")))?;
+    assert!(store.compact(Fail::Immediately)?);
+    let stack = store.stack()?.expect("present");
+    assert_eq!(stack.tables().len(), 1, "everything was merged into one table");
+    assert!(
+        stack.tables()[0].has_ref_index(),
+        "with small blocks, an index is needed to find references quickly"
+    );
+    assert!(!store.compact(Fail::Immediately)?, "a single table can't be compacted");
+
+    assert_eq!(store.iter()?.count(), 20 * 50 - 1, "the deleted reference is gone");
+    assert!(store.try_find("refs/pull/0000/head")?.is_none());
+    for name in ["refs/pull/0001/head", "refs/pull/1049/head", "refs/pull/0742/head"] {
+        assert!(store.try_find(name)?.is_some(), "{name} is found through the index");
+    }
+    assert_eq!(store.iter_prefixed("refs/pull/07")?.count(), 50);
+    assert_eq!(
+        store.reflog("refs/pull/0742/head")?,
+        None,
+        "pull refs don't get reflogs by default"
+    );
+    Ok(())
+}
+
+#[test]
+fn large_reflogs_span_multiple_compressed_blocks() -> crate::Result {
+    let (_dir, mut store) = store();
+    store.block_size = 256;
+    store.write_reflog = WriteReflog::Always;
+    for n in 1..=100u8 {
+        commit(
+            &store,
+            Some(update("refs/heads/main", Target::Peeled(id(n)), PreviousValue::Any)),
+        )?;
+    }
+    store.compact(Fail::Immediately)?;
+    let log = store.reflog("refs/heads/main")?.expect("present");
+    assert_eq!(log.len(), 100);
+    assert!(
+        log.windows(2).all(|w| w[0].new_oid == w[1].previous_oid),
+        "entries are ordered from oldest to newest"
+    );
+    assert_eq!(log.last().expect("present").new_oid, id(100));
+    Ok(())
+}
+
+#[test]
+fn sha256_tables_round_trip() -> crate::Result {
+    let dir = tempfile::TempDir::new()?;
+    let store = reftable::Store::at(dir.path(), WriteReflog::Normal, gix_hash::Kind::Sha256);
+    let oid = gix_hash::ObjectId::Sha256([7; 32]);
+    commit(
+        &store,
+        Some(update(
+            "refs/heads/main",
+            Target::Peeled(oid),
+            PreviousValue::MustNotExist,
+        )),
+    )?;
+    assert_eq!(store.find("main")?.target, Target::Peeled(oid));
+    assert_eq!(
+        store.stack()?.expect("present").tables()[0].object_hash(),
+        gix_hash::Kind::Sha256
+    );
+    Ok(())
+}
+
+#[test]
+fn loose_and_reftable_stores_agree_on_the_outcome_of_edits() -> crate::Result {
+    let (_loose_dir, loose) = empty_store()?;
+    let (_dir, reftable) = store();
+    let edits = vec![
+        update(
+            "HEAD",
+            Target::Symbolic("refs/heads/main".try_into()?),
+            PreviousValue::MustNotExist,
+        ),
+        update(
+            "refs/heads/main",
+            Target::Peeled(hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391")),
+            PreviousValue::MustNotExist,
+        ),
+    ];
+    let expected = loose
+        .transaction()
+        .prepare(edits.clone(), Fail::Immediately, Fail::Immediately)?
+        .commit(committer().to_ref())?;
+    let actual = commit(&reftable, edits)?;
+    assert_eq!(actual, expected, "the returned edits are the same");
+    Ok(())
+}
@@ -0,0 +1,512 @@
+use std::convert::TryInto;
+
+use gix_lock::acquire::Fail;
+use gix_ref::{
+    file, reftable,
+    store::{self, Format, WriteReflog},
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    Target,
+};
+
+use crate::file::transaction::prepare_and_commit::committer;
+
+fn handles() -> Vec<(tempfile::TempDir, store::Handle)> {
+    let file_dir = tempfile::TempDir::new().unwrap();
+    let file_store = file::Store::at(file_dir.path(), WriteReflog::Normal, gix_hash::Kind::Sha1);
+    let reftable_dir = tempfile::TempDir::new().unwrap();
+    let reftable_store = reftable::Store::at(reftable_dir.path(), WriteReflog::Normal, gix_hash::Kind::Sha1);
+    vec![(file_dir, file_store.into()), (reftable_dir, reftable_store.into())]
+}
+
+fn id(n: u8) -> gix_hash::ObjectId {
+    gix_hash::ObjectId::from([n; 20])
+}
+
+fn update(name: &str, new: Target) -> RefEdit {
+    RefEdit {
+        change: Change::Update {
+            log: LogChange {
+                mode: RefLog::AndReference,
+                force_create_reflog: false,
+                message: "update".into(),
+            },
+            expected: PreviousValue::Any,
+            new,
+        },
+        name: name.try_into().expect("valid"),
+        deref: false,
+    }
+}
+
+fn commit(store: &store::Handle, edits: impl IntoIterator<Item = RefEdit>) -> crate::Result<Vec<RefEdit>> {
+    Ok(store
+        .transaction()
+        .prepare(edits, Fail::Immediately, Fail::Immediately)?
+        .commit(committer().to_ref())?)
+}
+
+fn names(iter: store::iter::Iter<'_>) -> Vec<String> {
+    iter.map(|r| r.expect("valid").name.as_bstr().to_string()).collect()
+}
+
+#[test]
+fn all_operations_work_the_same_for_each_backend() -> crate::Result {
+    for (_keep, store) in handles() {
+        let format = store.format();
+        commit(
+            &store,
+            [
+                update("refs/heads/main", Target::Peeled(id(1))),
+                update("refs/heads/dev", Target::Peeled(id(2))),
+                update("refs/tags/v1", Target::Peeled(id(3))),
+                update("HEAD", Target::Symbolic("refs/heads/main".try_into()?)),
+            ],
+        )?;
+        commit(&store, Some(update("refs/heads/main", Target::Peeled(id(4)))))?;
+
+        assert_eq!(store.find("main")?.target.try_id(), Some(id(4).as_ref()), "{format:?}");
+        assert!(store.try_find("refs/heads/missing")?.is_none());
+        assert!(matches!(
+            store.find("missing"),
+            Err(store::find::existing::Error::NotFound { .. })
+        ));
+
+        let platform = store.iter()?;
+        assert_eq!(
+            names(platform.all()?),
+            ["refs/heads/dev", "refs/heads/main", "refs/tags/v1"],
+            "{format:?}: HEAD is never part of the iteration"
+        );
+        assert_eq!(
+            names(platform.prefixed("refs/heads/")?),
+            ["refs/heads/dev", "refs/heads/main"]
+        );
+
+        let mut head = store.find("HEAD")?;
+        let next = store.follow(&head).expect("symbolic")?;
+        assert_eq!(next.name.as_bstr(), "refs/heads/main");
+        assert_eq!(store.peel_to_id_in_place(&mut head, gix_ref::peel::none)?, id(4));
+        assert_eq!(
+            head.name.as_bstr(),
+            "refs/heads/main",
+            "peeling follows the symbolic ref"
+        );
+        assert!(
+            store.follow(&head).is_some(),
+            "peeled references are followed to themselves"
+        );
+
+        let name = "refs/heads/main".try_into()?;
+        assert!(store.reflog_exists(name)?);
+        let mut log = store.log_iter(name);
+        let forward: Vec<_> = log
+            .all()?
+            .expect("log present")
+            .map(|line| line.map(|l| l.new_oid()))
+            .collect::<Result<_, _>>()?;
+        assert_eq!(forward, [id(1), id(4)], "{format:?}: oldest first");
+        let reverse: Vec<_> = log
+            .rev()?
+            .expect("log present")
+            .map(|line| line.map(|l| l.new_oid))
+            .collect::<Result<_, _>>()?;
+        assert_eq!(reverse, [id(4), id(1)], "{format:?}: most recent first");
+
+        let missing = "refs/heads/missing".try_into()?;
+        assert!(!store.reflog_exists(missing)?);
+        assert!(store.log_iter(missing).all()?.is_none());
+        assert!(store.log_iter(missing).rev()?.is_none());
+    }
+    Ok(())
+}
+
+#[test]
+fn backend_specific_accessors() -> crate::Result {
+    let mut handles = handles();
+    let (_dir, mut store) = handles.remove(0);
+    assert_eq!(store.format(), Format::Files);
+    assert!(store.as_file().is_some() && store.as_reftable().is_none());
+    assert_eq!(store.set_namespace(gix_ref::namespace::expand("foo").unwrap())?, None);
+    assert!(store.namespace().is_some());
+    assert!(store.clear_namespace().is_some());
+
+    let (dir, mut store) = handles.remove(0);
+    assert_eq!(store.format(), Format::Reftable);
+    assert!(store.as_reftable().is_some() && store.as_file().is_none());
+    assert_eq!(store.git_dir(), dir.path());
+    store.set_write_reflog(WriteReflog::Disable);
+    assert_eq!(store.write_reflog(), WriteReflog::Disable);
+    assert!(
+        matches!(
+            store.set_namespace(gix_ref::namespace::expand("foo").unwrap()),
+            Err(gix_ref::store::set_namespace::Error::Reftable)
+        ),
+        "namespaces aren't supported by reftables"
+    );
+    assert!(store.namespace().is_none());
+    Ok(())
+}
+
+fn reflog(store: &store::Handle, name: &str) -> crate::Result<Option<Vec<(gix_hash::ObjectId, String)>>> {
+    let mut platform = store.log_iter(name.try_into()?);
+    let log = match platform.all()? {
+        Some(log) => log,
+        None => return Ok(None),
+    };
+    let mut lines = Vec::new();
+    for line in log {
+        let line = line?;
+        lines.push((line.new_oid(), line.message.to_string()));
+    }
+    Ok(Some(lines))
+}
+
+#[test]
+fn rename_and_copy_move_reflogs_and_update_symbolic_references() -> crate::Result {
+    for (_keep, store) in handles() {
+        let format = store.format();
+        commit(
+            &store,
+            [
+                update("refs/heads/main", Target::Peeled(id(1))),
+                update("refs/heads/other", Target::Peeled(id(2))),
+                update("HEAD", Target::Symbolic("refs/heads/main".try_into()?)),
+                update(
+                    "refs/remotes/origin/HEAD",
+                    Target::Symbolic("refs/heads/main".try_into()?),
+                ),
+            ],
+        )?;
+        commit(&store, Some(update("refs/heads/main", Target::Peeled(id(3)))))?;
+
+        let edits = store.rename_reference(
+            "refs/heads/main".try_into()?,
+            "refs/heads/trunk".try_into()?,
+            "renamed",
+            Fail::Immediately,
+            committer().to_ref(),
+        )?;
+        assert_eq!(edits.len(), 4, "{format:?}: create, delete and two symbolic refs");
+        assert!(store.try_find("refs/heads/main")?.is_none());
+        assert_eq!(store.find("trunk")?.target.try_id(), Some(id(3).as_ref()));
+        for symref in ["HEAD", "refs/remotes/origin/HEAD"] {
+            assert_eq!(
+                store.find(symref)?.target.try_name().map(|n| n.as_bstr().to_string()),
+                Some("refs/heads/trunk".into()),
+                "{format:?}: symbolic refs follow the rename"
+            );
+        }
+        assert_eq!(reflog(&store, "refs/heads/main")?, None, "{format:?}: the log moved");
+        assert_eq!(
+            reflog(&store, "refs/heads/trunk")?.expect("present"),
+            [
+                (id(1), "update".into()),
+                (id(3), "update".into()),
+                (id(3), "renamed".into())
+            ],
+            "{format:?}: history is preserved and the rename is logged"
+        );
+
+        store.copy_reference(
+            "refs/heads/trunk".try_into()?,
+            "refs/heads/copy".try_into()?,
+            "copied",
+            Fail::Immediately,
+            committer().to_ref(),
+        )?;
+        assert_eq!(store.find("trunk")?.target, store.find("copy")?.target);
+        assert_eq!(
+            store.find("HEAD")?.target.try_name().map(|n| n.as_bstr().to_string()),
+            Some("refs/heads/trunk".into()),
+            "copies don't affect symbolic refs"
+        );
+        assert_eq!(reflog(&store, "refs/heads/trunk")?.expect("kept").len(), 3);
+        assert_eq!(
+            reflog(&store, "refs/heads/copy")?.expect("present").last(),
+            Some(&(id(3), "copied".into()))
+        );
+        assert_eq!(reflog(&store, "refs/heads/copy")?.expect("present").len(), 4);
+
+        let err = store
+            .rename_reference(
+                "refs/heads/copy".try_into()?,
+                "refs/heads/other".try_into()?,
+                "",
+                Fail::Immediately,
+                committer().to_ref(),
+            )
+            .unwrap_err();
+        assert!(
+            matches!(err, store::rename::Error::DestinationExists { .. }),
+            "{format:?}: destinations must not exist: {err:?}"
+        );
+        let err = store
+            .rename_reference(
+                "refs/heads/copy".try_into()?,
+                "refs/heads/trunk".try_into()?,
+                "",
+                Fail::Immediately,
+                committer().to_ref(),
+            )
+            .unwrap_err();
+        assert!(
+            matches!(err, store::rename::Error::DestinationExists { .. }),
+            "{format:?}: even if they have the same value as the source: {err:?}"
+        );
+        assert!(matches!(
+            store.copy_reference(
+                "refs/heads/missing".try_into()?,
+                "refs/heads/new".try_into()?,
+                "",
+                Fail::Immediately,
+                committer().to_ref(),
+            ),
+            Err(store::rename::Error::NotFound { .. })
+        ));
+        assert!(matches!(
+            store.rename_reference(
+                "refs/heads/copy".try_into()?,
+                "refs/heads/copy".try_into()?,
+                "",
+                Fail::Immediately,
+                committer().to_ref(),
+            ),
+            Err(store::rename::Error::SameName { .. })
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+fn rename_moves_packed_references_into_loose_ones() -> crate::Result {
+    let (_keep, store) = crate::file::store_writable("make_packed_ref_repository.sh")?;
+    let store: store::Handle = store.into();
+    let previous = store.find("refs/tags/dt1")?.target;
+    store.rename_reference(
+        "refs/tags/dt1".try_into()?,
+        "refs/tags/renamed".try_into()?,
+        "renamed",
+        Fail::Immediately,
+        committer().to_ref(),
+    )?;
+    assert!(
+        store.try_find("refs/tags/dt1")?.is_none(),
+        "the packed entry is removed"
+    );
+    assert_eq!(store.find("refs/tags/renamed")?.target, previous);
+
+    let file_store = store.as_file().expect("file store");
+    assert!(file_store.git_dir().join("refs/tags/renamed").is_file());
+    assert!(
+        file_store
+            .open_packed_buffer()?
+            .expect("packed refs remain")
+            .try_find("refs/tags/dt1")?
+            .is_none(),
+        "the rename is reflected in packed-refs"
+    );
+    Ok(())
+}
+
+#[test]
+fn expire_reflog_removes_old_unreachable_and_stale_entries() -> crate::Result {
+    let at = |seconds: u64| Some(std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(seconds));
+    for (_keep, store) in handles() {
+        let format = store.format();
+        for n in 1..=4u8 {
+            let mut committer = committer();
+            committer.time.seconds_since_unix_epoch = u32::from(n) * 100;
+            store
+                .transaction()
+                .prepare(
+                    Some(update("refs/heads/main", Target::Peeled(id(n)))),
+                    Fail::Immediately,
+                    Fail::Immediately,
+                )?
+                .commit(committer.to_ref())?;
+        }
+        let new_ids = |store: &store::Handle| -> crate::Result<Vec<_>> {
+            Ok(reflog(store, "refs/heads/main")?
+                .expect("present")
+                .into_iter()
+                .map(|(id, _)| id)
+                .collect())
+        };
+        let name = "refs/heads/main".try_into()?;
+
+        let outcome = store.expire_reflog(
+            name,
+            store::expire::Options {
+                expire: at(250),
+                dry_run: true,
+                ..Default::default()
+            },
+            Fail::Immediately,
+            |_| true,
+            |_| true,
+        )?;
+        assert_eq!(outcome.kept, 2, "{format:?}");
+        assert_eq!(
+            outcome.removed.iter().map(|l| l.new_oid).collect::<Vec<_>>(),
+            [id(1), id(2)]
+        );
+        assert_eq!(new_ids(&store)?.len(), 4, "{format:?}: dry runs don't change anything");
+
+        let outcome = store.expire_reflog(
+            name,
+            store::expire::Options {
+                expire_unreachable: at(350),
+                ..Default::default()
+            },
+            Fail::Immediately,
+            |oid| oid != id(1),
+            |_| unreachable!("existence is only checked with stale-fix"),
+        )?;
+        assert_eq!(
+            outcome.removed.len(),
+            2,
+            "{format:?}: entries mentioning the unreachable id"
+        );
+        assert_eq!(
+            new_ids(&store)?,
+            [id(3), id(4)],
+            "{format:?}: recent entries are kept even if unreachable"
+        );
+
+        let outcome = store.expire_reflog(
+            name,
+            store::expire::Options {
+                stale_fix: true,
+                ..Default::default()
+            },
+            Fail::Immediately,
+            |_| true,
+            |oid| oid != id(4),
+        )?;
+        assert_eq!(outcome.kept, 1, "{format:?}");
+        assert_eq!(
+            new_ids(&store)?,
+            [id(3)],
+            "{format:?}: entries with missing objects are dropped"
+        );
+        assert!(
+            store.find("main")?.target.try_id() == Some(id(4).as_ref()),
+            "{format:?}: the reference itself is unaffected"
+        );
+
+        let outcome = store.expire_reflog(
+            "refs/heads/missing".try_into()?,
+            store::expire::Options {
+                expire: at(u64::from(u32::MAX)),
+                ..Default::default()
+            },
+            Fail::Immediately,
+            |_| true,
+            |_| true,
+        )?;
+        assert_eq!(outcome, Default::default(), "{format:?}: missing reflogs are ignored");
+    }
+    Ok(())
+}
+
+#[test]
+fn transaction_hook_sees_each_stage_and_can_reject_the_transaction() -> crate::Result {
+    use std::sync::{Arc, Mutex};
+
+    use gix_ref::transaction::hook::{Hook, Stage};
+
+    for (_keep, mut store) in handles() {
+        let format = store.format();
+        commit(&store, Some(update("refs/heads/main", Target::Peeled(id(1)))))?;
+
+        let calls = Arc::new(Mutex::new(Vec::<(Stage, String)>::new()));
+        let reject = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        store.set_transaction_hook(Some(Hook::new({
+            let calls = calls.clone();
+            let reject = reject.clone();
+            move |stage, input| {
+                calls
+                    .lock()
+                    .unwrap()
+                    .push((stage, String::from_utf8(input.to_owned())?));
+                if stage == Stage::Prepared && reject.load(std::sync::atomic::Ordering::SeqCst) {
+                    return Err("rejected".into());
+                }
+                Ok(())
+            }
+        })));
+        assert!(store.transaction_hook().is_some());
+
+        let null = gix_hash::Kind::Sha1.null();
+        let expected_input = format!(
+            "{old} {new} refs/heads/main\n{null} ref:refs/heads/main HEAD\n",
+            old = id(1),
+            new = id(2)
+        );
+        commit(
+            &store,
+            [
+                update("refs/heads/main", Target::Peeled(id(2))),
+                update("HEAD", Target::Symbolic("refs/heads/main".try_into()?)),
+            ],
+        )?;
+        assert_eq!(
+            std::mem::take(&mut *calls.lock().unwrap()),
+            [
+                (Stage::Prepared, expected_input.clone()),
+                (Stage::Committed, expected_input)
+            ],
+            "{format:?}: previous values are known once prepared"
+        );
+
+        let prepared = store.transaction().prepare(
+            Some(update("refs/heads/dev", Target::Peeled(id(3)))),
+            Fail::Immediately,
+            Fail::Immediately,
+        )?;
+        prepared.rollback();
+        let expected_input = format!("{null} {new} refs/heads/dev\n", new = id(3));
+        assert_eq!(
+            std::mem::take(&mut *calls.lock().unwrap()),
+            [
+                (Stage::Prepared, expected_input.clone()),
+                (Stage::Aborted, expected_input)
+            ],
+            "{format:?}"
+        );
+
+        reject.store(true, std::sync::atomic::Ordering::SeqCst);
+        let err = match store.transaction().prepare(
+            Some(update("refs/heads/dev", Target::Peeled(id(3)))),
+            Fail::Immediately,
+            Fail::Immediately,
+        ) {
+            Ok(_) => unreachable!("{format:?}: the hook rejects the transaction"),
+            Err(err) => err,
+        };
+        assert_eq!(
+            err.to_string(),
+            "The reference-transaction hook rejected the transaction",
+            "{format:?}"
+        );
+        assert_eq!(
+            calls
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(stage, _)| *stage)
+                .collect::<Vec<_>>(),
+            [Stage::Prepared, Stage::Aborted],
+            "{format:?}"
+        );
+        assert!(store.try_find("dev")?.is_none(), "{format:?}: nothing was written");
+        assert!(
+            store
+                .transaction()
+                .prepare(None, Fail::Immediately, Fail::Immediately)
+                .is_ok(),
+            "{format:?}: the lock was released, and the hook isn't called without changes"
+        );
+    }
+    Ok(())
+}
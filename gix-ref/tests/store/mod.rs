@@ -18,3 +18,5 @@ fn is_send_and_sync() {
     assert_type(&store);
     assert_type(store);
 }
+
+mod handle;
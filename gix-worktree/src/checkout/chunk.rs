@@ -96,8 +96,7 @@ where
     let mut bytes_written = 0;
 
     for (entry, entry_path) in entries_with_paths {
-        // TODO: write test for that
-        if entry.flags.contains(gix_index::entry::Flags::SKIP_WORKTREE) {
+        if entry.flags.contains(gix_index::entry::Flags::SKIP_WORKTREE) || entry.mode.is_sparse() {
             files.inc();
             continue;
         }
@@ -99,7 +99,8 @@ where
             entry.stat = Stat::from_fs(&std::fs::symlink_metadata(dest)?)?;
             obj.data.len()
         }
-        gix_index::entry::Mode::DIR => todo!(),
+        // Sparse directories are excluded from the worktree by definition, and there is nothing to write.
+        gix_index::entry::Mode::DIR => 0,
         gix_index::entry::Mode::COMMIT => todo!(),
         _ => unreachable!(),
     };
@@ -161,6 +162,7 @@ fn open_options(path: &Path, destination_is_initially_empty: bool, overwrite_exi
     options
         .create_new(destination_is_initially_empty && !overwrite_existing)
         .create(!destination_is_initially_empty || overwrite_existing)
+        .truncate(!destination_is_initially_empty || overwrite_existing)
         .write(true);
     options
 }
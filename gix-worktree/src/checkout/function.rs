@@ -16,12 +16,15 @@ pub fn checkout<Find, E>(
     files: &mut impl Progress,
     bytes: &mut impl Progress,
     should_interrupt: &AtomicBool,
-    options: crate::checkout::Options,
+    mut options: crate::checkout::Options,
 ) -> Result<crate::checkout::Outcome, crate::checkout::Error<E>>
 where
     Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Result<gix_object::BlobRef<'a>, E> + Send + Clone,
     E: std::error::Error + Send + Sync + 'static,
 {
+    if let Some(patterns) = options.sparse_checkout.take() {
+        index.apply_sparse_patterns(&patterns, case(&options));
+    }
     let paths = index.take_path_backing();
     let res = checkout_inner(index, &paths, dir, find, files, bytes, should_interrupt, options);
     index.return_path_backing(paths);
@@ -45,11 +48,7 @@ where
 {
     let num_files = AtomicUsize::default();
     let dir = dir.into();
-    let case = if options.fs.ignore_case {
-        gix_glob::pattern::Case::Fold
-    } else {
-        gix_glob::pattern::Case::Sensitive
-    };
+    let case = case(&options);
     let (chunk_size, thread_limit, num_threads) = gix_features::parallel::optimize_chunk_size_and_thread_limit(
         100,
         index.entries().len().into(),
@@ -117,3 +116,11 @@ where
         bytes_written,
     })
 }
+
+fn case(options: &crate::checkout::Options) -> gix_glob::pattern::Case {
+    if options.fs.ignore_case {
+        gix_glob::pattern::Case::Fold
+    } else {
+        gix_glob::pattern::Case::Sensitive
+    }
+}
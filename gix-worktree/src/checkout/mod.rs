@@ -53,6 +53,13 @@ pub struct Options {
     pub stat_options: stat::Options,
     /// A stack of attributes to use with the filesystem cache to use as driver for filters.
     pub attributes: crate::cache::state::Attributes,
+    /// If set, the patterns of the `.git/info/sparse-checkout` file, to set or clear the
+    /// [`SKIP_WORKTREE`][gix_index::entry::Flags::SKIP_WORKTREE] flag of all index entries before checking them out.
+    /// Entries with this flag are never checked out.
+    ///
+    /// Note that [sparse directory entries][gix_index::entry::Mode::DIR] always remain excluded, so the index should be
+    /// expanded beforehand if the patterns may include their content.
+    pub sparse_checkout: Option<gix_index::sparse::Patterns>,
 }
 
 #[derive(Debug, thiserror::Error)]
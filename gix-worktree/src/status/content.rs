@@ -80,7 +80,7 @@ impl CompareBlobs for HashEq {
         let mut hasher = hash::hasher(entry.id.kind());
         hasher.update(&header);
         hasher.update(blob);
-        let file_hash: ObjectId = hasher.digest().into();
+        let file_hash: ObjectId = hasher.digest();
         Ok((entry.id != file_hash).then_some(file_hash))
     }
 }
@@ -146,6 +146,30 @@ fn overwriting_files_and_lone_directories_works() {
     assert_eq!(std::fs::read(symlink).unwrap(), b"other content");
 }
 
+#[test]
+fn overwriting_a_longer_file_truncates_it() -> crate::Result {
+    let mut opts = opts_from_probe();
+    opts.overwrite_existing = true;
+    opts.destination_is_initially_empty = false;
+    let (_source_tree, destination, _index, outcome) = checkout_index_in_tmp_dir_opts(
+        opts,
+        "make_mixed_without_submodules",
+        |_| true,
+        |d| {
+            std::fs::write(d.join("executable"), b"longer than the new content")?;
+            Ok(())
+        },
+    )?;
+
+    assert!(outcome.collisions.is_empty());
+    assert_eq!(
+        std::fs::read(destination.path().join("executable"))?,
+        b"content",
+        "no trailing bytes of the previous, longer file remain"
+    );
+    Ok(())
+}
+
 #[test]
 fn symlinks_become_files_if_disabled() -> crate::Result {
     let mut opts = opts_from_probe();
@@ -172,6 +196,34 @@ fn allow_or_disallow_symlinks() -> crate::Result {
     Ok(())
 }
 
+#[test]
+fn sparse_checkout_patterns_exclude_entries_from_the_worktree() -> crate::Result {
+    for cone_mode in [true, false] {
+        let mut opts = opts_from_probe();
+        opts.sparse_checkout = Some(gix_index::sparse::Patterns::from_bytes(
+            b"/*\n!/*/\n/dir/\n!/dir/*/\n",
+            cone_mode,
+        ));
+        let (_source_tree, destination, index, outcome) =
+            checkout_index_in_tmp_dir(opts, "make_mixed_without_submodules")?;
+
+        assert_eq!(
+            stripped_prefix(&destination, &dir_structure(&destination)),
+            paths(["dir/content", "empty", "executable"]),
+            "the symlink in dir/sub-dir is excluded, cone_mode = {cone_mode}"
+        );
+        assert_eq!(outcome.files_updated, 3);
+        let skipped: Vec<_> = index
+            .entries()
+            .iter()
+            .filter(|e| e.flags.contains(gix_index::entry::Flags::SKIP_WORKTREE))
+            .map(|e| e.path(&index))
+            .collect();
+        assert_eq!(skipped, ["dir/sub-dir/symlink"]);
+    }
+    Ok(())
+}
+
 #[test]
 fn keep_going_collects_results() {
     let mut opts = opts_from_probe();
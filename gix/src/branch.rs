@@ -0,0 +1,210 @@
+//! Manage local branches, which are references in `refs/heads/`, along with their configuration in `branch.<name>`.
+//!
+//! A branch may have an [upstream][Upstream], which is the branch on a remote it integrates with, as configured in
+//! `branch.<name>.remote` and `branch.<name>.merge`. The local tracking branch of the upstream is used to learn how
+//! many commits a branch is [ahead and behind][crate::Repository::branch_ahead_behind()].
+use std::path::PathBuf;
+
+use gix_hash::ObjectId;
+use gix_ref::FullName;
+
+use crate::remote;
+
+/// The upstream of a branch, as configured in `branch.<name>.remote` and `branch.<name>.merge`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Upstream {
+    /// The name or URL of the remote, which is `.` if the upstream is a local branch.
+    pub remote: remote::Name<'static>,
+    /// The name of the branch on the remote, like `refs/heads/main`.
+    pub merge: FullName,
+    /// The local reference tracking `merge` as mapped by the fetch refspecs of the remote, like `refs/remotes/origin/main`,
+    /// or `merge` itself if the upstream is a local branch.
+    /// It's `None` if no refspec maps `merge` to a local reference.
+    pub tracking: Option<FullName>,
+}
+
+/// Information about a local branch, as returned by [`Repository::branches()`][crate::Repository::branches()].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Info {
+    /// The full name of the branch, like `refs/heads/main`.
+    pub name: FullName,
+    /// The id of the commit the branch points to, or `None` if it couldn't be peeled.
+    pub id: Option<ObjectId>,
+    /// The worktree in which the branch is checked out, if any.
+    pub worktree_dir: Option<PathBuf>,
+    /// The upstream of the branch, if configured.
+    pub upstream: Option<Upstream>,
+    /// The amount of commits the branch is ahead and behind of its upstream, or `None` if the local tracking reference of the
+    /// upstream doesn't exist.
+    pub ahead_behind: Option<(usize, usize)>,
+}
+
+///
+pub mod create {
+    use std::path::PathBuf;
+
+    use crate::bstr::BString;
+
+    /// The error returned by [`Repository::branch_create()`][crate::Repository::branch_create()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        NameValidation(#[from] gix_validate::reference::name::Error),
+        #[error(transparent)]
+        RevParse(#[from] crate::revision::spec::parse::single::Error),
+        #[error("The start point {spec:?} is not a commit")]
+        NotACommit {
+            spec: BString,
+            source: crate::object::peel::to_kind::Error,
+        },
+        #[error(transparent)]
+        FindObject(#[from] crate::object::find::existing::Error),
+        #[error(transparent)]
+        FindReference(#[from] crate::reference::find::Error),
+        #[error("A branch named {name:?} already exists")]
+        AlreadyExists { name: BString },
+        #[error("Cannot force-update the branch {name:?} as it is checked out at '{}'", worktree_dir.display())]
+        CheckedOut { name: BString, worktree_dir: PathBuf },
+        #[error(transparent)]
+        Worktrees(#[from] super::worktrees::Error),
+        #[error(transparent)]
+        Edit(#[from] crate::reference::edit::Error),
+    }
+}
+
+///
+pub mod delete {
+    use std::path::PathBuf;
+
+    use crate::bstr::BString;
+
+    /// The error returned by [`Repository::branch_delete()`][crate::Repository::branch_delete()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        NameValidation(#[from] gix_validate::reference::name::Error),
+        #[error(transparent)]
+        FindReference(#[from] crate::reference::find::existing::Error),
+        #[error("Cannot delete the branch {name:?} as it is checked out at '{}'", worktree_dir.display())]
+        CheckedOut { name: BString, worktree_dir: PathBuf },
+        #[error("The branch {name:?} is not fully merged")]
+        NotFullyMerged { name: BString },
+        #[error(transparent)]
+        Peel(#[from] crate::reference::peel::Error),
+        #[error(transparent)]
+        HeadId(#[from] crate::reference::head_id::Error),
+        #[error(transparent)]
+        Upstream(#[from] super::ahead_behind::Error),
+        #[error(transparent)]
+        Reachable(#[from] super::reachable::Error),
+        #[error(transparent)]
+        Worktrees(#[from] super::worktrees::Error),
+        #[error(transparent)]
+        Edit(#[from] crate::reference::edit::Error),
+        #[error(transparent)]
+        Config(#[from] crate::reference::branch_config::Error),
+    }
+}
+
+///
+pub mod upstream {
+    /// The error returned by [`Repository::branch_upstream()`][crate::Repository::branch_upstream()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The value of branch.<name>.merge is not a valid reference name")]
+        MergeName(#[from] gix_validate::reference::name::Error),
+        #[error(transparent)]
+        FindRemote(#[from] crate::remote::find::existing::Error),
+    }
+
+    ///
+    pub mod set {
+        use crate::bstr::BString;
+
+        /// The error returned by [`Repository::branch_set_upstream()`][crate::Repository::branch_set_upstream()] and
+        /// [`Repository::branch_unset_upstream()`][crate::Repository::branch_unset_upstream()].
+        #[derive(Debug, thiserror::Error)]
+        #[allow(missing_docs)]
+        pub enum Error {
+            #[error(transparent)]
+            NameValidation(#[from] gix_validate::reference::name::Error),
+            #[error(transparent)]
+            FindReference(#[from] crate::reference::find::Error),
+            #[error("There is no branch named {name:?}")]
+            NotFound { name: BString },
+            #[error(transparent)]
+            Config(#[from] crate::reference::branch_config::Error),
+        }
+    }
+}
+
+///
+pub mod ahead_behind {
+    /// The error returned by [`Repository::branch_ahead_behind()`][crate::Repository::branch_ahead_behind()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        NameValidation(#[from] gix_validate::reference::name::Error),
+        #[error(transparent)]
+        FindReference(#[from] crate::reference::find::Error),
+        #[error(transparent)]
+        Peel(#[from] crate::reference::peel::Error),
+        #[error(transparent)]
+        Upstream(#[from] super::upstream::Error),
+        #[error(transparent)]
+        Reachable(#[from] super::reachable::Error),
+    }
+}
+
+///
+pub mod list {
+    /// The error returned by [`Repository::branches()`][crate::Repository::branches()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        ReferencesInit(#[from] crate::reference::iter::Error),
+        #[error(transparent)]
+        ReferencesIterInit(#[from] crate::reference::iter::init::Error),
+        #[error("Could not read a reference")]
+        ReferencesIter(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+        #[error(transparent)]
+        Worktrees(#[from] super::worktrees::Error),
+        #[error(transparent)]
+        Upstream(#[from] super::upstream::Error),
+        #[error(transparent)]
+        AheadBehind(#[from] super::ahead_behind::Error),
+    }
+}
+
+///
+pub mod reachable {
+    /// The error returned when the commits reachable from a branch couldn't be determined.
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        FindExisting(#[from] crate::object::find::existing::Error),
+        #[error(transparent)]
+        Decode(#[from] gix_object::decode::Error),
+        #[error(transparent)]
+        CommitGraph(#[from] gix_commitgraph::file::commit::Error),
+    }
+}
+
+///
+pub mod worktrees {
+    /// The error returned when the branches checked out in worktrees couldn't be determined.
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The worktrees of the repository could not be listed")]
+        List(#[from] std::io::Error),
+        #[error("A worktree could not be opened to learn which branch is checked out")]
+        Open(#[from] crate::open::Error),
+    }
+}
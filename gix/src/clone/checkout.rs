@@ -35,6 +35,8 @@ pub mod main_worktree {
         FindHead(#[from] crate::reference::find::existing::Error),
         #[error("The HEAD reference could not be located")]
         PeelHeadToId(#[from] crate::head::peel::Error),
+        #[error("Could not run the post-checkout hook")]
+        PostCheckoutHook(#[from] crate::hook::run::Error),
     }
 
     /// The progress ids used in [`PrepareCheckout::main_worktree()`].
@@ -64,6 +66,9 @@ pub mod main_worktree {
         ///
         /// Note that this is a no-op if the remote was empty, leaving this repository empty as well. This can be validated by checking
         /// if the `head()` of the returned repository is not unborn.
+        ///
+        /// The `post-checkout` hook is run afterwards if it exists, which typically requires it to be configured with `core.hooksPath`
+        /// as new repositories only contain sample hooks.
         pub fn main_worktree(
             &mut self,
             mut progress: impl crate::Progress,
@@ -76,8 +81,11 @@ pub mod main_worktree {
             let workdir = repo.work_dir().ok_or_else(|| Error::BareRepository {
                 git_dir: repo.git_dir().to_owned(),
             })?;
-            let root_tree = match repo.head()?.peel_to_id_in_place().transpose()? {
-                Some(id) => id.object().expect("downloaded from remote").peel_to_tree()?.id,
+            let (head_id, root_tree) = match repo.head()?.peel_to_id_in_place().transpose()? {
+                Some(id) => (
+                    id.detach(),
+                    id.object().expect("downloaded from remote").peel_to_tree()?.id,
+                ),
                 None => {
                     return Ok((
                         self.repo.take().expect("still present"),
@@ -118,6 +126,12 @@ pub mod main_worktree {
             bytes.show_throughput(start);
 
             index.write(Default::default())?;
+            // Like git, the exit status of the hook doesn't affect the checkout.
+            repo.run_hook(
+                "post-checkout",
+                [head_id.kind().null().to_string(), head_id.to_string(), "1".into()],
+                &[],
+            )?;
             Ok((self.repo.take().expect("still present"), outcome))
         }
     }
@@ -31,6 +31,10 @@ pub enum Error {
     },
     #[error("Failed to update HEAD with values from remote")]
     HeadUpdate(#[from] crate::reference::edit::Error),
+    #[error("Failed to configure the object format of the repository to match the one of the remote")]
+    SetObjectFormat(#[from] gix_config::file::set_raw_value::Error),
+    #[error("Failed to reopen the repository after changing its object format")]
+    ReopenRepository(#[from] crate::open::Error),
 }
 
 /// Modification
@@ -43,6 +47,9 @@ impl PrepareFetch {
     /// If the remote repository was empty, that is newly initialized, the returned repository will also be empty and like
     /// it was newly initialized.
     ///
+    /// If the remote uses a different object format than the one our repository was initialized with, like `sha256`,
+    /// the repository will be reconfigured to use the remote's object format before fetching.
+    ///
     /// Note that all data we created will be removed once this instance drops if the operation wasn't successful.
     pub fn fetch_only<P>(
         &mut self,
@@ -85,7 +92,7 @@ impl PrepareFetch {
             clone_fetch_tags = remote::fetch::Tags::All.into();
         }
 
-        let config = util::write_remote_to_local_config_file(&mut remote, remote_name.clone())?;
+        let config = util::remote_config(&mut remote, remote_name.clone())?;
 
         // Now we are free to apply remote configuration we don't want to be written to disk.
         if let Some(fetch_tags) = clone_fetch_tags {
@@ -113,16 +120,20 @@ impl PrepareFetch {
                 opts
             })?
         };
-        if pending_pack.ref_map().object_hash != repo.object_hash() {
-            unimplemented!("configure repository to expect a different object hash as advertised by the server")
+        let remote_object_hash = pending_pack.ref_map().object_hash;
+        if remote_object_hash != repo.object_hash() {
+            drop(pending_pack);
+            util::set_object_hash(repo, remote_object_hash)?;
+            return self.fetch_only(progress, should_interrupt);
         }
+        util::write_to_local_config(&config, util::WriteMode::Append)?;
         let reflog_message = {
             let mut b = self.url.to_bstring();
             b.insert_str(0, "clone: from ");
             b
         };
         let outcome = pending_pack
-            .with_write_packed_refs_only(true)
+            .with_initial_ref_transaction()
             .with_reflog_message(RefLogMessage::Override {
                 message: reflog_message.clone(),
             })
@@ -12,23 +12,46 @@ use crate::{
     Repository,
 };
 
-enum WriteMode {
+pub enum WriteMode {
     Overwrite,
     Append,
 }
 
+/// Return a configuration file with `remote` saved as `remote_name`, to be written into the local configuration once it's
+/// clear the clone can proceed.
 #[allow(clippy::result_large_err)]
-pub fn write_remote_to_local_config_file(
-    remote: &mut crate::Remote<'_>,
-    remote_name: BString,
-) -> Result<gix_config::File<'static>, Error> {
+pub fn remote_config(remote: &mut crate::Remote<'_>, remote_name: BString) -> Result<gix_config::File<'static>, Error> {
     let mut config = gix_config::File::new(local_config_meta(remote.repo));
     remote.save_as_to(remote_name, &mut config)?;
-
-    write_to_local_config(&config, WriteMode::Append)?;
     Ok(config)
 }
 
+/// Configure `repo` to use `object_hash` in its local configuration file, and reopen it for the change to affect
+/// all of its parts.
+#[allow(clippy::result_large_err)]
+pub fn set_object_hash(repo: &mut Repository, object_hash: gix_hash::Kind) -> Result<(), Error> {
+    use crate::config::tree::{Core, Extensions, Section};
+
+    let path = local_config_meta(repo).path.expect("local config with path set");
+    let mut config = gix_config::File::from_path_no_includes(path.clone(), gix_config::Source::Local)?;
+    config.set_raw_value(
+        Core.name(),
+        None,
+        Core::REPOSITORY_FORMAT_VERSION.name,
+        if object_hash == gix_hash::Kind::Sha1 { "0" } else { "1" },
+    )?;
+    config.set_raw_value(
+        Extensions.name(),
+        None,
+        Extensions::OBJECT_FORMAT.name,
+        object_hash.to_string().to_ascii_lowercase().as_str(),
+    )?;
+    std::fs::write(path, config.to_bstring())?;
+
+    *repo = crate::open_opts(repo.git_dir(), repo.options.clone())?;
+    Ok(())
+}
+
 fn local_config_meta(repo: &Repository) -> gix_config::file::Metadata {
     let meta = repo.config.resolved.meta().clone();
     assert_eq!(
@@ -39,7 +62,7 @@ fn local_config_meta(repo: &Repository) -> gix_config::file::Metadata {
     meta
 }
 
-fn write_to_local_config(config: &gix_config::File<'static>, mode: WriteMode) -> std::io::Result<()> {
+pub fn write_to_local_config(config: &gix_config::File<'static>, mode: WriteMode) -> std::io::Result<()> {
     assert_eq!(
         config.meta().source,
         gix_config::Source::Local,
@@ -107,6 +107,13 @@ impl Cache {
             .into()
     }
 
+    /// The path to the directory containing all hooks, if configured with `core.hooksPath`.
+    pub(crate) fn hooks_path(&self) -> Option<Result<PathBuf, gix_config::path::interpolate::Error>> {
+        self.trusted_file_path("core", None, Core::HOOKS_PATH.name)?
+            .map(|p| p.into_owned())
+            .into()
+    }
+
     /// A helper to obtain a file from trusted configuration at `section_name`, `subsection_name`, and `key`, which is interpolated
     /// if present.
     pub(crate) fn trusted_file_path(
@@ -166,6 +173,17 @@ impl Cache {
             executable_bit: boolean(self, "core.fileMode", &Core::FILE_MODE, true)?,
             symlink: boolean(self, "core.symlinks", &Core::SYMLINKS, true)?,
         };
+        let sparse_checkout = if boolean(self, "core.sparseCheckout", &Core::SPARSE_CHECKOUT, false)? {
+            let cone_mode = boolean(self, "core.sparseCheckoutCone", &Core::SPARSE_CHECKOUT_CONE, true)?;
+            gix_index::sparse::Patterns::from_file(
+                git_dir.join("info").join("sparse-checkout"),
+                cone_mode,
+                &mut Vec::new(),
+            )
+            .map_err(checkout_options::Error::SparseCheckoutPatterns)?
+        } else {
+            None
+        };
         Ok(gix_worktree::checkout::Options {
             attributes: self
                 .assemble_attribute_globals(
@@ -179,6 +197,7 @@ impl Cache {
             destination_is_initially_empty: false,
             overwrite_existing: false,
             keep_going: false,
+            sparse_checkout,
             stat_options: gix_index::entry::stat::Options {
                 trust_ctime: boolean(self, "core.trustCTime", &Core::TRUST_C_TIME, true)?,
                 use_nsec: boolean(self, "gitoxide.core.useNsec", &gitoxide::Core::USE_NSEC, false)?,
@@ -11,6 +11,7 @@ pub(crate) struct StageOne {
     pub is_bare: bool,
     pub lossy: Option<bool>,
     pub object_hash: gix_hash::Kind,
+    pub ref_format: gix_ref::store::Format,
     pub reflog: Option<gix_ref::store::WriteReflog>,
 }
 
@@ -50,6 +51,15 @@ impl StageOne {
             })
             .transpose()?
             .unwrap_or(gix_hash::Kind::Sha1);
+        let ref_format = (repo_format_version == 1)
+            .then(|| {
+                config
+                    .string("extensions", None, "refStorage")
+                    .map(|format| Extensions::REF_STORAGE.try_into_ref_format(format))
+            })
+            .flatten()
+            .transpose()?
+            .unwrap_or_default();
 
         let extension_worktree = util::config_bool(
             &config,
@@ -77,6 +87,7 @@ impl StageOne {
             is_bare,
             lossy,
             object_hash,
+            ref_format,
             reflog,
         })
     }
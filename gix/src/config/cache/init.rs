@@ -25,6 +25,7 @@ impl Cache {
             lossy,
             is_bare,
             object_hash,
+            ref_format: _,
             reflog: _,
         }: StageOne,
         git_dir: &std::path::Path,
@@ -150,6 +151,17 @@ impl Cache {
             true,
             lenient_config,
         )?;
+        let pack_access = if config_bool(
+            &config,
+            &gitoxide::Objects::NO_MMAP,
+            "gitoxide.objects.noMmap",
+            false,
+            lenient_config,
+        )? {
+            gix_pack::data::Access::Read
+        } else {
+            gix_pack::data::Access::MemoryMap
+        };
         let object_kind_hint = util::disambiguate_hint(&config, lenient_config)?;
         let (pack_cache_bytes, object_cache_bytes) =
             util::parse_object_caches(&config, lenient_config, filter_config_section)?;
@@ -157,6 +169,7 @@ impl Cache {
         Ok(Cache {
             resolved: config.into(),
             use_multi_pack_index,
+            pack_access,
             object_hash,
             object_kind_hint,
             pack_cache_bytes,
@@ -270,7 +283,8 @@ impl crate::Repository {
     }
 
     fn apply_changed_values(&mut self) {
-        self.refs.write_reflog = util::reflog_or_default(self.config.reflog, self.work_dir().is_some());
+        let write_reflog = util::reflog_or_default(self.config.reflog, self.work_dir().is_some());
+        self.refs.set_write_reflog(write_reflog);
     }
 }
 
@@ -434,6 +448,10 @@ fn apply_environment_overrides(
                     let key = &gitoxide::Objects::CACHE_LIMIT;
                     (env(key), key.name)
                 },
+                {
+                    let key = &gitoxide::Objects::QUARANTINE_PATH;
+                    (env(key), key.name)
+                },
             ],
         ),
         (
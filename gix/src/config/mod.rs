@@ -115,6 +115,8 @@ pub mod checkout_options {
         CheckoutWorkers(#[from] super::checkout::workers::Error),
         #[error(transparent)]
         Attributes(#[from] super::attribute_stack::Error),
+        #[error("Could not read the sparse-checkout patterns")]
+        SparseCheckoutPatterns(#[source] std::io::Error),
     }
 }
 
@@ -450,6 +452,8 @@ pub(crate) struct Cache {
     pub object_hash: gix_hash::Kind,
     /// If true, multi-pack indices, whether present or not, may be used by the object database.
     pub use_multi_pack_index: bool,
+    /// The way the object database accesses pack data files.
+    pub pack_access: gix_pack::data::Access,
     /// The representation of `core.logallrefupdates`, or `None` if the variable wasn't set.
     pub reflog: Option<gix_ref::store::WriteReflog>,
     /// The configured user agent for presentation to servers.
@@ -24,6 +24,8 @@ pub(crate) mod root {
         pub const CHECKOUT: sections::Checkout = sections::Checkout;
         /// The `clone` section.
         pub const CLONE: sections::Clone = sections::Clone;
+        /// The `commitGraph` section.
+        pub const COMMIT_GRAPH: sections::CommitGraph = sections::CommitGraph;
         /// The `committer` section.
         pub const COMMITTER: sections::Committer = sections::Committer;
         /// The `core` section.
@@ -34,6 +36,10 @@ pub(crate) mod root {
         pub const DIFF: sections::Diff = sections::Diff;
         /// The `extensions` section.
         pub const EXTENSIONS: sections::Extensions = sections::Extensions;
+        /// The `fetch` section.
+        pub const FETCH: sections::Fetch = sections::Fetch;
+        /// The `gc` section.
+        pub const GC: sections::Gc = sections::Gc;
         /// The `gitoxide` section.
         pub const GITOXIDE: sections::Gitoxide = sections::Gitoxide;
         /// The `http` section.
@@ -48,6 +54,8 @@ pub(crate) mod root {
         pub const PROTOCOL: sections::Protocol = sections::Protocol;
         /// The `remote` section.
         pub const REMOTE: sections::Remote = sections::Remote;
+        /// The `repack` section.
+        pub const REPACK: sections::Repack = sections::Repack;
         /// The `safe` section.
         pub const SAFE: sections::Safe = sections::Safe;
         /// The `ssh` section.
@@ -64,11 +72,14 @@ pub(crate) mod root {
                 &Self::BRANCH,
                 &Self::CHECKOUT,
                 &Self::CLONE,
+                &Self::COMMIT_GRAPH,
                 &Self::COMMITTER,
                 &Self::CORE,
                 &Self::CREDENTIAL,
                 &Self::DIFF,
                 &Self::EXTENSIONS,
+                &Self::FETCH,
+                &Self::GC,
                 &Self::GITOXIDE,
                 &Self::HTTP,
                 &Self::INDEX,
@@ -76,6 +87,7 @@ pub(crate) mod root {
                 &Self::PACK,
                 &Self::PROTOCOL,
                 &Self::REMOTE,
+                &Self::REPACK,
                 &Self::SAFE,
                 &Self::SSH,
                 &Self::USER,
@@ -87,9 +99,9 @@ pub(crate) mod root {
 
 mod sections;
 pub use sections::{
-    branch, checkout, core, credential, diff, extensions, gitoxide, http, index, protocol, remote, ssh, Author, Branch,
-    Checkout, Clone, Committer, Core, Credential, Diff, Extensions, Gitoxide, Http, Index, Init, Pack, Protocol,
-    Remote, Safe, Ssh, Url, User,
+    branch, checkout, core, credential, diff, extensions, gc, gitoxide, http, index, protocol, remote, ssh, Author,
+    Branch, Checkout, Clone, CommitGraph, Committer, Core, Credential, Diff, Extensions, Fetch, Gc, Gitoxide, Http,
+    Index, Init, Pack, Protocol, Remote, Repack, Safe, Ssh, Url, User,
 };
 
 /// Generic value implementations for static instantiation.
@@ -0,0 +1,24 @@
+use crate::{
+    config,
+    config::tree::{keys, CommitGraph, Key, Section},
+};
+
+impl CommitGraph {
+    /// The `commitGraph.generationVersion` key.
+    pub const GENERATION_VERSION: keys::UnsignedInteger =
+        keys::UnsignedInteger::new_unsigned_integer("generationVersion", &config::Tree::COMMIT_GRAPH)
+            .with_note("Version 1 doesn't write corrected commit dates, any other version does");
+    /// The `commitGraph.readChangedPaths` key.
+    pub const READ_CHANGED_PATHS: keys::Boolean =
+        keys::Boolean::new_boolean("readChangedPaths", &config::Tree::COMMIT_GRAPH);
+}
+
+impl Section for CommitGraph {
+    fn name(&self) -> &str {
+        "commitGraph"
+    }
+
+    fn keys(&self) -> &[&dyn Key] {
+        &[&Self::GENERATION_VERSION, &Self::READ_CHANGED_PATHS]
+    }
+}
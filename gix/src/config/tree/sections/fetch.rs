@@ -0,0 +1,19 @@
+use crate::{
+    config,
+    config::tree::{keys, Fetch, Key, Section},
+};
+
+impl Fetch {
+    /// The `fetch.writeCommitGraph` key.
+    pub const WRITE_COMMIT_GRAPH: keys::Boolean = keys::Boolean::new_boolean("writeCommitGraph", &config::Tree::FETCH);
+}
+
+impl Section for Fetch {
+    fn name(&self) -> &str {
+        "fetch"
+    }
+
+    fn keys(&self) -> &[&dyn Key] {
+        &[&Self::WRITE_COMMIT_GRAPH]
+    }
+}
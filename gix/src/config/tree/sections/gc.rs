@@ -0,0 +1,95 @@
+use crate::{
+    config,
+    config::tree::{keys, Gc, Key, Section},
+};
+
+impl Gc {
+    /// The `gc.cruftPacks` key.
+    pub const CRUFT_PACKS: keys::Boolean = keys::Boolean::new_boolean("cruftPacks", &config::Tree::GC)
+        .with_deviation("Defaults to false, storing unreachable objects as loose objects instead");
+
+    /// The `gc.pruneExpire` key.
+    pub const PRUNE_EXPIRE: PruneExpire =
+        PruneExpire::new_with_validate("pruneExpire", &config::Tree::GC, validate::PruneExpire);
+
+    /// The `gc.reflogExpire` key, which applies to references matching `<pattern>` if set as `gc.<pattern>.reflogExpire`.
+    pub const REFLOG_EXPIRE: PruneExpire =
+        PruneExpire::new_with_validate("reflogExpire", &config::Tree::GC, validate::PruneExpire)
+            .with_subsection_requirement(None);
+
+    /// The `gc.reflogExpireUnreachable` key, which applies to references matching `<pattern>` if set as
+    /// `gc.<pattern>.reflogExpireUnreachable`.
+    pub const REFLOG_EXPIRE_UNREACHABLE: PruneExpire =
+        PruneExpire::new_with_validate("reflogExpireUnreachable", &config::Tree::GC, validate::PruneExpire)
+            .with_subsection_requirement(None);
+}
+
+/// The `gc.pruneExpire`, `gc.reflogExpire` and `gc.reflogExpireUnreachable` keys.
+pub type PruneExpire = keys::Any<validate::PruneExpire>;
+
+mod prune_expire {
+    use std::{borrow::Cow, time::SystemTime};
+
+    use crate::{
+        bstr::{BStr, ByteSlice},
+        config,
+        config::tree::gc::PruneExpire,
+    };
+
+    impl PruneExpire {
+        /// Parse `value` into the point in time before which unreachable objects may be removed, with `now` as reference
+        /// for relative dates like `2.weeks.ago`.
+        ///
+        /// `never` or `false` yield `None` as objects never expire, and `now` or `all` make all objects expire right away.
+        pub fn try_into_expiry(
+            &'static self,
+            value: Cow<'_, BStr>,
+            now: SystemTime,
+        ) -> Result<Option<SystemTime>, config::time::Error> {
+            let bytes: &[u8] = value.as_ref().as_ref();
+            Ok(match bytes {
+                b"never" | b"false" => None,
+                b"now" | b"all" => Some(now),
+                _ => {
+                    let time = value
+                        .to_str()
+                        .map_err(|_| gix_date::parse::Error::InvalidDateString {
+                            input: value.to_string(),
+                        })
+                        .and_then(|value| gix_date::parse(value, Some(now)))
+                        .map_err(|err| {
+                            config::time::Error::from_value(self, value.clone().into_owned()).with_source(err)
+                        })?;
+                    Some(std::time::UNIX_EPOCH + std::time::Duration::from_secs(time.seconds_since_unix_epoch.into()))
+                }
+            })
+        }
+    }
+}
+
+impl Section for Gc {
+    fn name(&self) -> &str {
+        "gc"
+    }
+
+    fn keys(&self) -> &[&dyn Key] {
+        &[
+            &Self::CRUFT_PACKS,
+            &Self::PRUNE_EXPIRE,
+            &Self::REFLOG_EXPIRE,
+            &Self::REFLOG_EXPIRE_UNREACHABLE,
+        ]
+    }
+}
+
+mod validate {
+    use crate::{bstr::BStr, config::tree::keys};
+
+    pub struct PruneExpire;
+    impl keys::Validate for PruneExpire {
+        fn validate(&self, value: &BStr) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+            super::Gc::PRUNE_EXPIRE.try_into_expiry(value.into(), std::time::SystemTime::now())?;
+            Ok(())
+        }
+    }
+}
@@ -14,6 +14,8 @@ impl Gitoxide {
     pub const COMMIT: Commit = Commit;
     /// The `gitoxide.committer` section.
     pub const COMMITTER: Committer = Committer;
+    /// The `gitoxide.fetch` section.
+    pub const FETCH: Fetch = Fetch;
     /// The `gitoxide.http` section.
     pub const HTTP: Http = Http;
     /// The `gitoxide.https` section.
@@ -47,6 +49,7 @@ impl Section for Gitoxide {
             &Self::CORE,
             &Self::COMMIT,
             &Self::COMMITTER,
+            &Self::FETCH,
             &Self::HTTP,
             &Self::HTTPS,
             &Self::OBJECTS,
@@ -97,6 +100,32 @@ mod subsections {
         }
     }
 
+    /// The `Fetch` sub-section.
+    #[derive(Copy, Clone, Default)]
+    pub struct Fetch;
+
+    impl Fetch {
+        /// The `gitoxide.fetch.packRefsThreshold` key.
+        pub const PACK_REFS_THRESHOLD: keys::UnsignedInteger =
+            keys::UnsignedInteger::new_unsigned_integer("packRefsThreshold", &Gitoxide::FETCH).with_note(
+                "If set and not 0, all references are packed after a fetch if there are at least this many loose references",
+            );
+    }
+
+    impl Section for Fetch {
+        fn name(&self) -> &str {
+            "fetch"
+        }
+
+        fn keys(&self) -> &[&dyn Key] {
+            &[&Self::PACK_REFS_THRESHOLD]
+        }
+
+        fn parent(&self) -> Option<&dyn Section> {
+            Some(&Tree::GITOXIDE)
+        }
+    }
+
     /// The `Http` sub-section.
     #[derive(Copy, Clone, Default)]
     pub struct Http;
@@ -312,6 +341,13 @@ mod subsections {
         /// The `gitoxide.objects.replaceRefBase` key.
         pub const REPLACE_REF_BASE: keys::Any =
             keys::Any::new("replaceRefBase", &Gitoxide::OBJECTS).with_environment_override("GIT_REPLACE_REF_BASE");
+        /// The `gitoxide.objects.noMmap` key.
+        pub const NO_MMAP: keys::Boolean = keys::Boolean::new_boolean("noMmap", &Gitoxide::OBJECTS)
+            .with_note("If true, pack data files are read instead of memory-mapped so IO errors are reported instead of crashing the process");
+        /// The `gitoxide.objects.quarantinePath` key.
+        pub const QUARANTINE_PATH: keys::Path = keys::Path::new_path("quarantinePath", &Gitoxide::OBJECTS)
+            .with_environment_override("GIT_QUARANTINE_PATH")
+            .with_note("If set, objects are received into a quarantine environment and references must not be changed");
     }
 
     impl Section for Objects {
@@ -320,7 +356,13 @@ mod subsections {
         }
 
         fn keys(&self) -> &[&dyn Key] {
-            &[&Self::CACHE_LIMIT, &Self::NO_REPLACE, &Self::REPLACE_REF_BASE]
+            &[
+                &Self::CACHE_LIMIT,
+                &Self::NO_REPLACE,
+                &Self::REPLACE_REF_BASE,
+                &Self::NO_MMAP,
+                &Self::QUARANTINE_PATH,
+            ]
         }
 
         fn parent(&self) -> Option<&dyn Section> {
@@ -382,7 +424,7 @@ mod subsections {
         }
     }
 }
-pub use subsections::{Allow, Author, Commit, Committer, Core, Http, Https, Objects, Ssh, User};
+pub use subsections::{Allow, Author, Commit, Committer, Core, Fetch, Http, Https, Objects, Ssh, User};
 
 pub mod validate {
     use std::error::Error;
@@ -20,6 +20,11 @@ pub mod checkout;
 pub struct Clone;
 mod clone;
 
+/// The `commitGraph` top-level section.
+#[derive(Copy, Clone, Default)]
+pub struct CommitGraph;
+mod commit_graph;
+
 /// The `committer` top-level section.
 #[derive(Copy, Clone, Default)]
 pub struct Committer;
@@ -45,6 +50,16 @@ pub mod diff;
 pub struct Extensions;
 pub mod extensions;
 
+/// The `fetch` top-level section.
+#[derive(Copy, Clone, Default)]
+pub struct Fetch;
+mod fetch;
+
+/// The `gc` top-level section.
+#[derive(Copy, Clone, Default)]
+pub struct Gc;
+pub mod gc;
+
 /// The `gitoxide` top-level section.
 #[derive(Copy, Clone, Default)]
 pub struct Gitoxide;
@@ -80,6 +95,11 @@ pub mod protocol;
 pub struct Remote;
 pub mod remote;
 
+/// The `repack` top-level section.
+#[derive(Copy, Clone, Default)]
+pub struct Repack;
+mod repack;
+
 /// The `safe` top-level section.
 #[derive(Copy, Clone, Default)]
 pub struct Safe;
@@ -118,6 +118,11 @@ pub struct Options {
     /// If set, use these filesystem capabilities to populate the respective gix-config fields.
     /// If `None`, the directory will be probed.
     pub fs_capabilities: Option<gix_fs::Capabilities>,
+    /// The kind of hash to use for all objects in the new repository, like `git init --object-format`.
+    ///
+    /// Anything but [`Sha1`][gix_hash::Kind::Sha1] is configured with `extensions.objectFormat`, which requires
+    /// `core.repositoryFormatVersion = 1`.
+    pub object_hash: gix_hash::Kind,
 }
 
 /// Create a new `.git` repository of `kind` within the possibly non-existing `directory`
@@ -131,6 +136,7 @@ pub fn into(
     Options {
         fs_capabilities,
         destination_must_be_empty,
+        object_hash,
     }: Options,
 ) -> Result<gix_discover::repository::Path, Error> {
     let mut dot_git = directory.into();
@@ -211,13 +217,25 @@ pub fn into(
             let caps = fs_capabilities.unwrap_or_else(|| gix_fs::Capabilities::probe(&dot_git));
             let mut core = config.new_section("core", None).expect("valid section name");
 
-            core.push(key("repositoryformatversion"), Some("0".into()));
+            let needs_extensions = object_hash != gix_hash::Kind::Sha1;
+            core.push(
+                key("repositoryformatversion"),
+                Some(if needs_extensions { "1" } else { "0" }.into()),
+            );
             core.push(key("filemode"), Some(bool(caps.executable_bit).into()));
             core.push(key("bare"), Some(bool(bare).into()));
             core.push(key("logallrefupdates"), Some(bool(!bare).into()));
             core.push(key("symlinks"), Some(bool(caps.symlink).into()));
             core.push(key("ignorecase"), Some(bool(caps.ignore_case).into()));
             core.push(key("precomposeunicode"), Some(bool(caps.precompose_unicode).into()));
+
+            if needs_extensions {
+                let mut extensions = config.new_section("extensions", None).expect("valid section name");
+                extensions.push(
+                    key("objectformat"),
+                    Some(object_hash.to_string().to_ascii_lowercase().as_str().into()),
+                );
+            }
         }
         let mut cursor = PathCursor(&mut dot_git);
         let config_path = cursor.at("config");
@@ -9,12 +9,10 @@ use crate::{
 
 impl<'repo> Head<'repo> {
     /// Return a platform for obtaining iterators on the reference log associated with the `HEAD` reference.
-    pub fn log_iter(&self) -> gix_ref::file::log::iter::Platform<'static, 'repo> {
-        gix_ref::file::log::iter::Platform {
-            store: &self.repo.refs,
-            name: "HEAD".try_into().expect("HEAD is always valid"),
-            buf: Vec::new(),
-        }
+    pub fn log_iter(&self) -> gix_ref::store::log::Platform<'static, 'repo> {
+        self.repo
+            .refs
+            .log_iter("HEAD".try_into().expect("HEAD is always valid"))
     }
 
     /// Return a list of all branch names that were previously checked out with the first-ever checked out branch
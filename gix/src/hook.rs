@@ -0,0 +1,177 @@
+//! Run [hooks](https://git-scm.com/docs/githooks) of a repository, which are found in `core.hooksPath` or the `hooks` directory
+//! of the common `.git` directory.
+//!
+//! Hooks run in the worktree, or in the `.git` directory of bare repositories, with `GIT_DIR` and `GIT_INDEX_FILE` set accordingly.
+//! They are only run in repositories that are [fully trusted][gix_sec::Trust::Full] to prevent running code
+//! of other users.
+//!
+//! The following hooks are run by the operations that git runs them for:
+//!
+//! * `reference-transaction` for all reference edits.
+//! * `post-checkout` after checking out the main worktree of a [clone][crate::clone::PrepareCheckout::main_worktree()],
+//!   after a [switch][crate::Repository::switch()] and after [adding a worktree][crate::Repository::worktree_add()].
+//! * `pre-commit` and `commit-msg` with [`Repository::run_commit_hooks()`][crate::Repository::run_commit_hooks()].
+//!
+//! All other hooks, like `post-merge`, aren't run automatically as no operation here triggers them. [Merges][crate::Repository::merge_trees()],
+//! for instance, only produce trees and don't touch the worktree. Use [`Repository::run_hook()`][crate::Repository::run_hook()]
+//! to run them as part of operations implemented on top.
+use std::{
+    ffi::OsStr,
+    io::Write,
+    path::{Path, PathBuf},
+    process::Stdio,
+};
+
+use gix_ref::transaction::hook::{Hook, Stage};
+
+///
+pub mod run {
+    use std::path::PathBuf;
+
+    /// The error returned by [`Repository::run_hook()`][crate::Repository::run_hook()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not interpolate the value of core.hooksPath")]
+        HooksPath(#[from] gix_config::path::interpolate::Error),
+        #[error("Could not determine the current working directory")]
+        CurrentDir(#[source] std::io::Error),
+        #[error("Failed to run the hook at '{}'", path.display())]
+        Spawn { path: PathBuf, source: std::io::Error },
+    }
+}
+
+///
+pub mod commit {
+    /// The error returned by [`Repository::run_commit_hooks()`][crate::Repository::run_commit_hooks()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Run(#[from] super::run::Error),
+        #[error("The {name} hook rejected the commit with {status}")]
+        Rejected {
+            name: &'static str,
+            status: std::process::ExitStatus,
+        },
+        #[error("Could not write or read the commit message to pass to the commit-msg hook")]
+        Io(#[from] std::io::Error),
+        #[error("The commit message is not valid UTF-8 after running the commit-msg hook")]
+        Utf8(#[from] std::string::FromUtf8Error),
+        #[error("The commit message is empty after running the commit-msg hook")]
+        EmptyMessage,
+    }
+}
+
+/// Everything needed to run hooks of a repository.
+#[derive(Debug, Clone)]
+pub(crate) struct Context {
+    /// The directory containing all hooks, either `core.hooksPath` or the `hooks` directory in the common `.git` directory.
+    pub hooks_dir: PathBuf,
+    /// The `.git` directory of the repository or linked worktree, passed to hooks as `GIT_DIR`.
+    pub git_dir: PathBuf,
+    /// The index of the worktree, if there is one, passed to hooks as `GIT_INDEX_FILE`.
+    pub index_file: Option<PathBuf>,
+    /// The directory in which hooks run, which is the worktree or the `.git` directory of bare repositories.
+    pub cwd: PathBuf,
+}
+
+impl Context {
+    /// Create a new instance from `config` for the repository at `git_dir` and `common_dir` with an optional `work_dir`,
+    /// with all relative paths, including those in `core.hooksPath`, made absolute using `current_dir`.
+    pub fn new(
+        config: &crate::config::Cache,
+        git_dir: &Path,
+        common_dir: &Path,
+        work_dir: Option<&Path>,
+        current_dir: &Path,
+    ) -> Result<Self, gix_config::path::interpolate::Error> {
+        let git_dir = current_dir.join(git_dir);
+        let cwd = work_dir.map_or_else(|| git_dir.clone(), |wt| current_dir.join(wt));
+        let hooks_dir = match config.hooks_path().transpose()? {
+            Some(path) => cwd.join(path),
+            None => current_dir.join(common_dir).join("hooks"),
+        };
+        Ok(Context {
+            hooks_dir,
+            index_file: work_dir.map(|_| git_dir.join("index")),
+            git_dir,
+            cwd,
+        })
+    }
+
+    /// Return the path to the hook with `name` if it exists and is executable.
+    pub fn find(&self, name: &str) -> Option<PathBuf> {
+        let path = self.hooks_dir.join(name);
+        is_executable(&path).then_some(path)
+    }
+
+    /// Run the hook at `path` with `args`, passing `stdin` to it, and return its exit status.
+    pub fn run(
+        &self,
+        path: &Path,
+        args: impl IntoIterator<Item = impl AsRef<OsStr>>,
+        stdin: &[u8],
+    ) -> std::io::Result<std::process::ExitStatus> {
+        let mut prepare = gix_command::prepare(path)
+            .args(args.into_iter().map(|arg| arg.as_ref().to_owned()))
+            .env("GIT_DIR", &self.git_dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::inherit());
+        if let Some(index_file) = &self.index_file {
+            prepare = prepare.env("GIT_INDEX_FILE", index_file);
+        }
+        let mut cmd: std::process::Command = prepare.into();
+        let mut child = cmd.current_dir(&self.cwd).spawn()?;
+        if let Some(mut child_stdin) = child.stdin.take() {
+            // Hooks may exit without consuming their input, which is fine.
+            match child_stdin.write_all(stdin) {
+                Err(err) if err.kind() != std::io::ErrorKind::BrokenPipe => return Err(err),
+                _ => {}
+            }
+        }
+        child.wait()
+    }
+}
+
+/// Create a hook for reference transactions which refuses all of them if `quarantined`, or which runs the
+/// `reference-transaction` hook of `ctx` if it is present.
+pub(crate) fn reference_transaction(ctx: Option<Context>, quarantined: bool) -> Hook {
+    Hook::new(move |stage, input| {
+        if quarantined {
+            return match stage {
+                Stage::Prepared => Err("ref updates forbidden inside quarantine environment".into()),
+                Stage::Committed | Stage::Aborted => Ok(()),
+            };
+        }
+        let ctx = match &ctx {
+            Some(ctx) => ctx,
+            None => return Ok(()),
+        };
+        let path = match ctx.find("reference-transaction") {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let status = ctx.run(&path, [stage.as_str()], input)?;
+        if stage == Stage::Prepared && !status.success() {
+            return Err(format!(
+                "The reference-transaction hook at '{}' failed with {status}",
+                path.display()
+            )
+            .into());
+        }
+        Ok(())
+    })
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .map_or(false, |m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
@@ -68,6 +68,7 @@
 // APIs/instances anyway.
 pub use gix_actor as actor;
 pub use gix_attributes as attrs;
+pub use gix_commitgraph as commitgraph;
 pub use gix_credentials as credentials;
 pub use gix_date as date;
 pub use gix_features as features;
@@ -109,18 +110,20 @@ pub mod prelude;
 ///
 pub mod path;
 
-/// The standard type for a store to handle git references.
-pub type RefStore = gix_ref::file::Store;
+/// The standard type for a store to handle git references, independently of the format they are stored in.
+pub type RefStore = gix_ref::store::Handle;
 /// A handle for finding objects in an object database, abstracting away caches for thread-local use.
-pub type OdbHandle = gix_odb::Handle;
+///
+/// It can optionally keep new objects in memory, see [`Repository::with_object_memory()`].
+pub type OdbHandle = gix_odb::memory::Proxy<gix_odb::Handle>;
 /// A way to access git configuration
 pub(crate) type Config = OwnShared<gix_config::File<'static>>;
 
 ///
 mod types;
 pub use types::{
-    Commit, Head, Id, Kind, Object, ObjectDetached, Reference, Remote, Repository, Tag, ThreadSafeRepository, Tree,
-    Worktree,
+    Commit, Head, Id, Kind, Object, ObjectDetached, Pathspec, Reference, Remote, Repository, Tag, ThreadSafeRepository,
+    Tree, Worktree,
 };
 
 ///
@@ -235,12 +238,24 @@ pub mod open;
 ///
 pub mod config;
 
+///
+pub mod branch;
+
 ///
 pub mod mailmap;
 
 ///
 pub mod worktree;
 
+///
+pub mod maintenance;
+
+///
+pub mod merge;
+
+///
+pub mod pathspec;
+
 pub mod revision;
 
 ///
@@ -249,6 +264,8 @@ pub mod remote;
 ///
 pub mod init;
 
+pub mod hook;
+
 /// Not to be confused with 'status'.
 pub mod state;
 
@@ -0,0 +1,214 @@
+#![allow(clippy::result_large_err)]
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use gix_commitgraph::graph::write::{Options, Outcome};
+use gix_features::progress::Progress;
+use gix_hash::ObjectId;
+
+use crate::Repository;
+
+/// The error returned by [`Repository::write_commit_graph()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Commit-graphs can't be written in shallow repositories as not all commits are available")]
+    Shallow,
+    #[error(transparent)]
+    ReferencesInit(#[from] crate::reference::iter::Error),
+    #[error(transparent)]
+    ReferencesIterInit(#[from] crate::reference::iter::init::Error),
+    #[error("Could not read a reference")]
+    ReferencesIter(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+    #[error(transparent)]
+    FindHead(#[from] crate::reference::find::existing::Error),
+    #[error(transparent)]
+    FindObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    Decode(#[from] gix_object::decode::Error),
+    #[error(transparent)]
+    WalkInit(#[from] crate::revision::walk::Error),
+    #[error(transparent)]
+    Walk(#[from] gix_traverse::commit::ancestors::Error),
+    #[error(transparent)]
+    DiffInit(#[from] crate::object::tree::diff::rewrites::Error),
+    #[error("Could not compute the paths changed by a commit")]
+    Diff(#[from] crate::object::tree::diff::for_each::Error),
+    #[error(transparent)]
+    Write(#[from] gix_commitgraph::graph::write::Error),
+    #[error("Interrupted")]
+    Interrupted,
+}
+
+/// The progress ids used in [`Repository::write_commit_graph()`].
+///
+/// Use this information to selectively extract the progress of interest in case the parent application has custom visualization.
+#[derive(Debug, Copy, Clone)]
+pub enum ProgressId {
+    /// The amount of commits collected to be written into the commit-graph.
+    CollectCommits,
+}
+
+impl From<ProgressId> for gix_features::progress::Id {
+    fn from(v: ProgressId) -> Self {
+        match v {
+            ProgressId::CollectCommits => *b"MCGC",
+        }
+    }
+}
+
+impl Repository {
+    /// Write all commits reachable from `tips` into the commit-graph of this repository, along with the commits it already
+    /// contains, like `git commit-graph write`, using `options` typically obtained with
+    /// [`commit_graph_options()`][Self::commit_graph_options()].
+    ///
+    /// `tips` that are tags are peeled, while those that don't point to commits are ignored. The traversal stops at commits
+    /// which are already contained in the commit-graph, which makes writing split commit-graphs fast.
+    ///
+    /// Changed-path Bloom filters are computed for all new commits if [`Options::changed_paths`] is set, or if the existing
+    /// commit-graph has them, just like git does.
+    pub fn write_commit_graph(
+        &self,
+        tips: impl IntoIterator<Item = impl Into<ObjectId>>,
+        mut progress: impl Progress,
+        should_interrupt: &AtomicBool,
+        mut options: Options,
+    ) -> Result<Outcome, Error> {
+        if self.is_shallow() {
+            return Err(Error::Shallow);
+        }
+        let info_dir = self.objects.store_ref().path().join("info");
+        let existing = self.commit_graph().ok();
+        options.changed_paths = options.changed_paths.or_else(|| {
+            existing
+                .as_ref()
+                .and_then(|graph| graph.files().iter().find_map(|file| file.bloom_settings()))
+        });
+
+        let mut commit_tips = Vec::new();
+        for tip in tips {
+            let mut id = tip.into();
+            loop {
+                let object = self.find_object(id)?;
+                match object.kind {
+                    gix_object::Kind::Tag => id = object.to_tag_ref_iter().target_id()?,
+                    gix_object::Kind::Commit => {
+                        commit_tips.push(id);
+                        break;
+                    }
+                    gix_object::Kind::Tree | gix_object::Kind::Blob => break,
+                }
+            }
+        }
+        commit_tips.sort();
+        commit_tips.dedup();
+
+        progress.init(None, gix_features::progress::count("commits"));
+        progress.set_name("collect commits");
+        let start = std::time::Instant::now();
+        let mut commits = Vec::new();
+        for id in self
+            .rev_walk(commit_tips)
+            .selected(move |id| existing.as_ref().map_or(true, |graph| graph.lookup(id).is_none()))?
+        {
+            if should_interrupt.load(Ordering::Relaxed) {
+                return Err(Error::Interrupted);
+            }
+            let id = id?;
+            let commit = id.object()?.into_commit();
+            let commit = commit.decode()?;
+            let bloom_filter = options
+                .changed_paths
+                .map(|settings| self.changed_paths_filter(commit.tree(), commit.parents().next(), &settings))
+                .transpose()?;
+            commits.push(gix_commitgraph::file::write::Commit {
+                id: id.detach(),
+                tree: commit.tree(),
+                parents: commit.parents().collect(),
+                committer_timestamp: commit.committer.time.seconds_since_unix_epoch.into(),
+                bloom_filter,
+            });
+            progress.inc();
+        }
+        progress.show_throughput(start);
+
+        Ok(gix_commitgraph::Graph::write_to_info_dir(
+            info_dir,
+            commits,
+            self.object_hash(),
+            options,
+        )?)
+    }
+
+    /// Like [`write_commit_graph()`][Self::write_commit_graph()], but use all references and `HEAD` as tips, like
+    /// `git commit-graph write --reachable`.
+    pub fn write_commit_graph_for_references(
+        &self,
+        progress: impl Progress,
+        should_interrupt: &AtomicBool,
+        options: Options,
+    ) -> Result<Outcome, Error> {
+        let mut tips = Vec::new();
+        for reference in self.references()?.all()? {
+            let reference = reference.map_err(Error::ReferencesIter)?;
+            if let Some(id) = reference.target().try_id() {
+                tips.push(id.to_owned());
+            }
+        }
+        if let Some(id) = self.head()?.id() {
+            tips.push(id.detach());
+        }
+        self.write_commit_graph(tips, progress, should_interrupt, options)
+    }
+
+    /// Compute the changed-path Bloom filter for a commit with `tree` compared to the tree of its `first_parent`.
+    fn changed_paths_filter(
+        &self,
+        tree: ObjectId,
+        first_parent: Option<ObjectId>,
+        settings: &gix_commitgraph::bloom::Settings,
+    ) -> Result<Vec<u8>, Error> {
+        use crate::object::tree::diff::{change::Event, Action};
+
+        let tree = self.find_object(tree)?.into_tree();
+        let parent_tree = match first_parent {
+            Some(parent) => {
+                let parent_tree = self.find_object(parent)?.to_commit_ref_iter().tree_id()?;
+                self.find_object(parent_tree)?.into_tree()
+            }
+            None => self.empty_tree(),
+        };
+        let mut paths = Vec::new();
+        let res = parent_tree
+            .changes()?
+            .track_path()
+            .track_rewrites(None)
+            .for_each_to_obtain_tree(&tree, |change| {
+                let is_tree_change = match change.event {
+                    Event::Modification {
+                        previous_entry_mode,
+                        entry_mode,
+                        ..
+                    } => previous_entry_mode.is_tree() && entry_mode.is_tree(),
+                    event => event.entry_mode().is_tree(),
+                };
+                if !is_tree_change {
+                    paths.push(change.location.to_owned());
+                }
+                Ok::<_, std::convert::Infallible>(if paths.len() > gix_commitgraph::bloom::MAX_CHANGED_PATHS {
+                    Action::Cancel
+                } else {
+                    Action::Continue
+                })
+            });
+        match res {
+            Ok(_) => {}
+            Err(crate::object::tree::diff::for_each::Error::Diff(gix_diff::tree::changes::Error::Cancelled))
+                if paths.len() > gix_commitgraph::bloom::MAX_CHANGED_PATHS => {}
+            Err(err) => return Err(err.into()),
+        }
+        Ok(gix_commitgraph::bloom::compute_filter(
+            paths.iter().map(AsRef::as_ref),
+            settings,
+        ))
+    }
+}
@@ -0,0 +1,107 @@
+#![allow(clippy::result_large_err)]
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use gix_hash::ObjectId;
+use gix_odb::FindExt;
+use gix_pack::data::output;
+
+use crate::{
+    bstr::{BString, ByteSlice, ByteVec},
+    Repository,
+};
+
+/// The error returned by [`Repository::mark_objects_without_delta()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    AttributeCache(#[from] crate::worktree::attributes::Error),
+    #[error("Could not read the attributes for '{path}'")]
+    Attributes { path: BString, source: std::io::Error },
+    #[error(transparent)]
+    FindObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    Decode(#[from] gix_object::decode::Error),
+    #[error("Interrupted")]
+    Interrupted,
+}
+
+impl Repository {
+    /// Set [`no_delta`][output::Count::no_delta] on all blobs in `counts` whose path has the `delta` attribute unset,
+    /// for example with `*.zip -delta` in `.gitattributes`, and return the amount of objects that were marked.
+    ///
+    /// Paths are learned by traversing the trees of `roots`, which are the commits and trees the counts were produced from,
+    /// while other kinds of objects are ignored. Like `git pack-objects`, the first path a blob is encountered at determines
+    /// its attributes, which are read from the worktree if there is one, and from the index otherwise.
+    pub fn mark_objects_without_delta(
+        &self,
+        roots: impl IntoIterator<Item = ObjectId>,
+        counts: &mut [output::Count],
+        should_interrupt: &AtomicBool,
+    ) -> Result<usize, Error> {
+        let mut attributes = self.checkout_attributes()?;
+        let mut matches = attributes.selected_attribute_matches(Some("delta"));
+
+        let mut unnamed: gix_hashtable::HashMap<ObjectId, usize> = counts
+            .iter()
+            .enumerate()
+            .map(|(count_index, count)| (count.id, count_index))
+            .collect();
+        let mut seen_trees = gix_hashtable::HashSet::<ObjectId>::default();
+        let mut trees = Vec::<(ObjectId, BString)>::new();
+        let mut num_marked = 0;
+        for root in roots {
+            let object = self.find_object(root)?;
+            let tree_id = match object.kind {
+                gix_object::Kind::Commit => object.to_commit_ref_iter().tree_id()?,
+                gix_object::Kind::Tree => root,
+                gix_object::Kind::Tag | gix_object::Kind::Blob => continue,
+            };
+            drop(object);
+            if seen_trees.insert(tree_id) {
+                trees.push((tree_id, BString::default()));
+            }
+            while let Some((tree_id, path)) = trees.pop() {
+                if should_interrupt.load(Ordering::Relaxed) {
+                    return Err(Error::Interrupted);
+                }
+                let tree = self.find_object(tree_id)?;
+                for entry in gix_object::TreeRefIter::from_bytes(&tree.data, self.object_hash()) {
+                    let entry = entry?;
+                    let mut entry_path = path.clone();
+                    if !entry_path.is_empty() {
+                        entry_path.push(b'/');
+                    }
+                    entry_path.push_str(entry.filename);
+                    if entry.mode.is_tree() {
+                        if seen_trees.insert(entry.oid.to_owned()) {
+                            trees.push((entry.oid.to_owned(), entry_path));
+                        }
+                        continue;
+                    }
+                    let count_index = match unnamed.remove(entry.oid) {
+                        Some(count_index) => count_index,
+                        None => continue,
+                    };
+                    let platform = attributes
+                        .at_entry(entry_path.as_bstr(), Some(false), |id, buf| {
+                            self.objects.find_blob(id, buf)
+                        })
+                        .map_err(|source| Error::Attributes {
+                            path: entry_path.clone(),
+                            source,
+                        })?;
+                    platform.matching_attributes(&mut matches);
+                    if matches
+                        .iter_selected()
+                        .any(|m| m.assignment.state == gix_attributes::StateRef::Unset)
+                    {
+                        counts[count_index].no_delta = true;
+                        num_marked += 1;
+                    }
+                }
+            }
+        }
+        Ok(num_marked)
+    }
+}
@@ -0,0 +1,195 @@
+#![allow(clippy::result_large_err)]
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::SystemTime,
+};
+
+use gix_hash::ObjectId;
+use gix_odb::Find;
+use gix_ref::{store::expire, FullName, FullNameRef};
+
+use crate::{
+    bstr::{BString, ByteSlice},
+    config, Repository,
+};
+
+/// Configuration for [`Repository::expire_reflogs()`], typically obtained with [`Repository::expire_reflogs_options()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// Entries created before this point in time are removed, unless a pattern matching the reference overrides it.
+    /// If `None`, entries are never removed due to their age alone.
+    pub expire: Option<SystemTime>,
+    /// Entries created before this point in time are removed if they aren't reachable from the tip of their reference,
+    /// unless a pattern matching the reference overrides it. If `None`, entries are never removed due to being unreachable.
+    pub expire_unreachable: Option<SystemTime>,
+    /// Overrides of the expiry dates for particular references, where the first matching pattern is used.
+    pub patterns: Vec<Pattern>,
+    /// If `true`, remove entries that refer to objects which don't exist anymore.
+    pub stale_fix: bool,
+    /// If `true`, determine which entries would be removed without changing any reflog.
+    pub dry_run: bool,
+}
+
+/// Expiry dates for references matching a pattern, as configured with `gc.<pattern>.reflogExpire` and
+/// `gc.<pattern>.reflogExpireUnreachable`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    /// A wildmatch pattern to match against full reference names, like `refs/remotes/*`.
+    pub pattern: BString,
+    /// If `Some`, the value to use instead of [`Options::expire`].
+    pub expire: Option<Option<SystemTime>>,
+    /// If `Some`, the value to use instead of [`Options::expire_unreachable`].
+    pub expire_unreachable: Option<Option<SystemTime>>,
+}
+
+impl Options {
+    /// Return the options to use when expiring the reflog of the reference `name`.
+    pub fn for_reference(&self, name: &FullNameRef) -> expire::Options {
+        let pattern = self
+            .patterns
+            .iter()
+            .find(|p| gix_glob::wildmatch(p.pattern.as_bstr(), name.as_bstr(), gix_glob::wildmatch::Mode::empty()));
+        expire::Options {
+            expire: pattern.and_then(|p| p.expire).unwrap_or(self.expire),
+            expire_unreachable: pattern
+                .and_then(|p| p.expire_unreachable)
+                .unwrap_or(self.expire_unreachable),
+            stale_fix: self.stale_fix,
+            dry_run: self.dry_run,
+        }
+    }
+}
+
+/// The outcome of [`Repository::expire_reflogs()`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// The name of each reference whose reflog was processed, along with the outcome of expiring it, in the order of processing.
+    pub references: Vec<(FullName, expire::Outcome)>,
+}
+
+/// The error returned by [`Repository::expire_reflogs()`] and [`Repository::expire_reflog()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    LockTimeoutConfiguration(#[from] config::lock_timeout::Error),
+    #[error(transparent)]
+    FindReference(#[from] crate::reference::find::Error),
+    #[error(transparent)]
+    ReferencesInit(#[from] crate::reference::iter::Error),
+    #[error(transparent)]
+    ReferencesIterInit(#[from] crate::reference::iter::init::Error),
+    #[error("Could not read a reference")]
+    ReferencesIter(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+    #[error("Could not check for the reference log of '{name}'")]
+    ReflogExists { name: BString, source: std::io::Error },
+    #[error(transparent)]
+    FindObject(#[from] crate::object::find::Error),
+    #[error(transparent)]
+    WalkInit(#[from] crate::revision::walk::Error),
+    #[error(transparent)]
+    Walk(#[from] gix_traverse::commit::ancestors::Error),
+    #[error(transparent)]
+    Expire(#[from] expire::Error),
+    #[error("Interrupted")]
+    Interrupted,
+}
+
+impl Repository {
+    /// Remove old entries from the reflogs of `HEAD` and all references according to `options`, similar to
+    /// `git reflog expire --all`.
+    ///
+    /// Entries are unreachable if their objects can't be reached from the tip of their reference, while for `HEAD`, which
+    /// changes its branch frequently, any reference may be used to reach them.
+    pub fn expire_reflogs(&self, should_interrupt: &AtomicBool, options: &Options) -> Result<Outcome, Error> {
+        let mut names = vec![FullName::try_from("HEAD").expect("valid")];
+        for reference in self.references()?.all()? {
+            names.push(reference.map_err(Error::ReferencesIter)?.detach().name);
+        }
+
+        let mut out = Outcome::default();
+        for name in names {
+            if should_interrupt.load(Ordering::Relaxed) {
+                return Err(Error::Interrupted);
+            }
+            let has_reflog = self
+                .refs
+                .reflog_exists(name.as_ref())
+                .map_err(|source| Error::ReflogExists {
+                    name: name.as_bstr().to_owned(),
+                    source,
+                })?;
+            if !has_reflog {
+                continue;
+            }
+            let outcome = self.expire_reflog(name.as_ref(), options)?;
+            out.references.push((name, outcome));
+        }
+        Ok(out)
+    }
+
+    /// Remove old entries from the reflog of the reference `name` according to `options`, similar to `git reflog expire <name>`,
+    /// and rewrite it atomically.
+    ///
+    /// If `name` is `HEAD`, entries are considered reachable if any reference can reach them.
+    pub fn expire_reflog(&self, name: &FullNameRef, options: &Options) -> Result<expire::Outcome, Error> {
+        let options = options.for_reference(name);
+        let reachable = if options.expire_unreachable.is_some() {
+            let tip = self.reference_tip(name)?;
+            if name.as_bstr() == "HEAD" {
+                let mut tips: Vec<_> = tip.into_iter().collect();
+                for reference in self.references()?.all()? {
+                    let name = reference.map_err(Error::ReferencesIter)?.detach().name;
+                    tips.extend(self.reference_tip(name.as_ref())?);
+                }
+                self.reachable_commits(tips)?
+            } else {
+                self.reachable_commits(tip)?
+            }
+        } else {
+            Default::default()
+        };
+        let (lock_fail_mode, _packed_refs_lock_fail_mode) = self.config.lock_timeout()?;
+        Ok(self.refs.expire_reflog(
+            name,
+            options,
+            lock_fail_mode,
+            |id| reachable.contains(id),
+            |id| self.objects.contains(id),
+        )?)
+    }
+
+    /// Return the peeled id of the reference `name` if it exists.
+    ///
+    /// References which can't be peeled have no tip, which makes all of their old reflog entries unreachable, like in git.
+    fn reference_tip(&self, name: &FullNameRef) -> Result<Option<ObjectId>, Error> {
+        Ok(self
+            .try_find_reference(name)?
+            .and_then(|r| r.into_fully_peeled_id().ok())
+            .map(crate::Id::detach))
+    }
+
+    /// Return all commits reachable from `tips`, ignoring tips that don't exist or don't point to a commit.
+    fn reachable_commits(
+        &self,
+        tips: impl IntoIterator<Item = ObjectId>,
+    ) -> Result<gix_hashtable::HashSet<ObjectId>, Error> {
+        let mut commits = Vec::new();
+        for tip in tips {
+            if let Some(commit) = self
+                .try_find_object(tip)?
+                .and_then(|object| object.peel_to_kind(gix_object::Kind::Commit).ok())
+            {
+                commits.push(commit.id);
+            }
+        }
+        let mut out = gix_hashtable::HashSet::default();
+        if commits.is_empty() {
+            return Ok(out);
+        }
+        for commit in self.rev_walk(commits).all()? {
+            out.insert(commit?.detach());
+        }
+        Ok(out)
+    }
+}
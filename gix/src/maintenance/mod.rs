@@ -0,0 +1,122 @@
+//! Maintenance of the object database, like consolidating packs and removing objects that aren't reachable anymore.
+//!
+//! Objects are considered reachable if they can be reached from any reference, from the reference logs, or from the index
+//! of any worktree, which is what [`Repository::reachable_objects()`][crate::Repository::reachable_objects()] computes.
+//!
+//! Old reflog entries can be removed with [`Repository::expire_reflogs()`][crate::Repository::expire_reflogs()], which makes the
+//! objects only they refer to unreachable.
+//!
+//! Commit-graphs to accelerate history traversals can be written with [`Repository::write_commit_graph()`][crate::Repository::write_commit_graph()].
+//!
+//! Loose references can be moved into the `packed-refs` file with [`Repository::pack_refs()`][crate::Repository::pack_refs()].
+use std::{path::Path, time::SystemTime};
+
+use gix_hash::ObjectId;
+
+///
+pub mod commit_graph;
+///
+pub mod delta_attribute;
+///
+pub mod expire_reflogs;
+///
+pub mod pack_refs;
+///
+pub mod prune;
+///
+pub mod reachable;
+///
+pub mod repack;
+
+/// Return the modification time of the file at `path` in seconds since UNIX epoch.
+fn mtime_in_seconds(path: &Path) -> std::io::Result<u32> {
+    Ok(seconds_since_epoch(path.metadata()?.modified()?))
+}
+
+fn seconds_since_epoch(time: SystemTime) -> u32 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs().try_into().unwrap_or(u32::MAX))
+}
+
+/// Return true if an object with modification time `mtime` is older than `expire`, which never happens if it is `None`.
+fn is_expired(mtime: u32, expire: Option<SystemTime>) -> bool {
+    expire.map_or(false, |expire| mtime < seconds_since_epoch(expire))
+}
+
+/// Return the paths to all pack indices in the `pack` directory of the object database at `objects_dir`,
+/// sorted by path. Packs with a `.keep` or `.promisor` file are only returned if `include_kept` is true.
+fn pack_indices(objects_dir: &Path, include_kept: bool) -> std::io::Result<Vec<std::path::PathBuf>> {
+    let mut out = Vec::new();
+    let iter = match std::fs::read_dir(objects_dir.join("pack")) {
+        Ok(iter) => iter,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(out),
+        Err(err) => return Err(err),
+    };
+    for entry in iter {
+        let path = entry?.path();
+        let is_pack_index = path.extension().map_or(false, |ext| ext == "idx")
+            && path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .map_or(false, |name| name.starts_with("pack-"));
+        if !is_pack_index || !path.with_extension("pack").is_file() {
+            continue;
+        }
+        let is_kept = ["keep", "promisor"]
+            .iter()
+            .any(|ext| path.with_extension(ext).is_file());
+        if include_kept || !is_kept {
+            out.push(path);
+        }
+    }
+    out.sort();
+    Ok(out)
+}
+
+/// Return the modification time of each object in the pack of `index` in the order of the index, in seconds since UNIX epoch.
+///
+/// These are read from the `.mtimes` file of cruft packs, or are the modification time of the pack itself otherwise.
+fn pack_object_mtimes(index: &gix_pack::index::File) -> std::io::Result<Vec<u32>> {
+    let index_path = index.path();
+    let mtimes = gix_pack::index::mtimes::File::at(
+        index_path.with_extension("mtimes"),
+        index.num_objects(),
+        index.object_hash(),
+    )
+    .ok()
+    .filter(|mtimes| mtimes.pack_checksum() == index.pack_checksum());
+    Ok(match mtimes {
+        Some(mtimes) => mtimes.iter().collect(),
+        None => vec![mtime_in_seconds(&index_path.with_extension("pack"))?; index.num_objects() as usize],
+    })
+}
+
+/// Remove the loose object with `id` from `loose`, returning `true` if it existed.
+fn remove_loose_object(loose: &gix_odb::loose::Store, id: &gix_hash::oid) -> std::io::Result<bool> {
+    let path = loose.object_path(id);
+    match std::fs::remove_file(&path) {
+        Ok(()) => {
+            if let Some(dir) = path.parent() {
+                // Remove the fan-out directory if it's empty now, like git does.
+                std::fs::remove_dir(dir).ok();
+            }
+            Ok(true)
+        }
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+/// The ids of loose objects in `loose` along with their modification time in seconds since UNIX epoch.
+///
+/// Objects that disappear while iterating are skipped.
+fn loose_objects(loose: &gix_odb::loose::Store) -> Result<Vec<(ObjectId, u32)>, gix_odb::loose::iter::Error> {
+    let mut out = Vec::new();
+    for id in loose.iter() {
+        let id = id?;
+        if let Ok(mtime) = mtime_in_seconds(&loose.object_path(&id)) {
+            out.push((id, mtime));
+        }
+    }
+    Ok(out)
+}
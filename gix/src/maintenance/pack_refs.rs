@@ -0,0 +1,40 @@
+use gix_odb::Find;
+pub use gix_ref::file::pack_refs::{Filter, Options, Outcome};
+
+use crate::{config, Repository};
+
+/// The error returned by [`Repository::pack_refs()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    LockTimeoutConfiguration(#[from] config::lock_timeout::Error),
+    #[error(transparent)]
+    PackRefs(#[from] gix_ref::file::pack_refs::Error),
+}
+
+impl Repository {
+    /// Move loose references into the `packed-refs` file according to `options`, similar to `git pack-refs`, which speeds up
+    /// iterating references and reduces the amount of files in the repository.
+    ///
+    /// References private to a worktree are never packed. Nothing is done if references are stored in reftables,
+    /// which have no loose references.
+    pub fn pack_refs(&self, options: Options) -> Result<Outcome, Error> {
+        let store = match self.refs.as_file() {
+            Some(store) => store,
+            None => return Ok(Outcome::default()),
+        };
+        let (lock_fail_mode, packed_refs_lock_fail_mode) = self.config.lock_timeout()?;
+        Ok(store.pack_refs(
+            options,
+            lock_fail_mode,
+            packed_refs_lock_fail_mode,
+            Box::new(|oid, buf| {
+                self.objects
+                    .try_find(oid, buf)
+                    .map(|obj| obj.map(|obj| obj.kind))
+                    .map_err(|err| Box::new(err) as Box<dyn std::error::Error + Send + Sync + 'static>)
+            }),
+        )?)
+    }
+}
@@ -0,0 +1,103 @@
+#![allow(clippy::result_large_err)]
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::SystemTime,
+};
+
+use gix_features::progress::Progress;
+use gix_hash::ObjectId;
+
+use crate::{maintenance, Repository};
+
+/// Configuration for [`Repository::prune()`], typically obtained with [`Repository::prune_options()`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// Unreachable loose objects older than this point in time are removed. If `None`, no unreachable object is removed.
+    pub expire: Option<SystemTime>,
+    /// If `true`, determine which objects would be removed without removing them.
+    pub dry_run: bool,
+}
+
+/// The outcome of [`Repository::prune()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// The unreachable loose objects which were removed, or would have been removed in a dry run.
+    pub pruned_objects: Vec<ObjectId>,
+    /// The amount of loose objects which were removed, or would have been removed in a dry run, as they are also contained in a pack.
+    pub packed_objects: usize,
+}
+
+/// The error returned by [`Repository::prune()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Reachable(#[from] maintenance::reachable::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    IterateLoose(#[from] gix_odb::loose::iter::Error),
+    #[error(transparent)]
+    OpenIndex(#[from] gix_pack::index::init::Error),
+    #[error("Interrupted")]
+    Interrupted,
+}
+
+impl Repository {
+    /// Remove loose objects which aren't reachable and are older than the grace period set in `options`, along with loose objects
+    /// that are also contained in a pack, similar to `git prune` and `git prune-packed`.
+    ///
+    /// Objects are considered reachable as described in [`reachable_objects()`][Self::reachable_objects()], and expired objects
+    /// are kept if unreachable objects that aren't expired yet refer to them, directly or indirectly. Packs aren't
+    /// altered, use [`repack()`][Self::repack()] to drop unreachable packed objects.
+    pub fn prune(
+        &self,
+        mut progress: impl Progress,
+        should_interrupt: &AtomicBool,
+        options: Options,
+    ) -> Result<Outcome, Error> {
+        let object_hash = self.object_hash();
+        let objects_dir = self.objects.store_ref().path().to_owned();
+        let loose = gix_odb::loose::Store::at(&objects_dir, object_hash);
+        let loose_objects = maintenance::loose_objects(&loose)?;
+        let reachable = self.reachable_objects(progress.add_child("find reachable objects"), should_interrupt)?;
+        let recent = match options.expire {
+            Some(_) => self.recent_objects(&reachable, options.expire, should_interrupt)?,
+            None => Default::default(),
+        };
+
+        let mut packed = gix_hashtable::HashSet::default();
+        for index_path in maintenance::pack_indices(&objects_dir, true)? {
+            let index = gix_pack::index::File::at(index_path, object_hash)?;
+            packed.extend(index.iter().map(|entry| entry.oid));
+        }
+
+        let mut out = Outcome {
+            pruned_objects: Vec::new(),
+            packed_objects: 0,
+        };
+        let mut remove_progress = progress.add_child("remove objects");
+        remove_progress.init(Some(loose_objects.len()), gix_features::progress::count("objects"));
+        for (id, mtime) in loose_objects {
+            if should_interrupt.load(Ordering::Relaxed) {
+                return Err(Error::Interrupted);
+            }
+            remove_progress.inc();
+            let is_packed = packed.contains(&id);
+            let is_expired = maintenance::is_expired(mtime, options.expire) && !recent.contains(&id);
+            if !is_packed && (reachable.contains(&id) || !is_expired) {
+                continue;
+            }
+            if !options.dry_run && !maintenance::remove_loose_object(&loose, &id)? {
+                continue;
+            }
+            if is_packed {
+                out.packed_objects += 1;
+            } else {
+                out.pruned_objects.push(id);
+            }
+        }
+        out.pruned_objects.sort();
+        Ok(out)
+    }
+}
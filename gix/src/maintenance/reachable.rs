@@ -0,0 +1,394 @@
+#![allow(clippy::result_large_err)]
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::SystemTime,
+};
+
+use gix_features::progress::Progress;
+use gix_hash::ObjectId;
+use gix_odb::Find;
+use gix_pack::data::output;
+
+use crate::{bstr::BString, maintenance, Repository};
+
+/// The error returned by [`Repository::reachable_objects()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not open the object database for use across threads")]
+    OpenArcOdb(#[source] std::io::Error),
+    #[error(transparent)]
+    ReferencesInit(#[from] crate::reference::iter::Error),
+    #[error(transparent)]
+    ReferencesIterInit(#[from] crate::reference::iter::init::Error),
+    #[error("Could not read a reference")]
+    ReferencesIter(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+    #[error(transparent)]
+    FindHead(#[from] crate::reference::find::existing::Error),
+    #[error("Could not read the reference log of '{name}'")]
+    ReadReflog { name: BString, source: std::io::Error },
+    #[error("Could not decode a line in the reference log of '{name}'")]
+    DecodeReflog {
+        name: BString,
+        source: gix_ref::file::log::iter::decode::Error,
+    },
+    #[error(transparent)]
+    OpenMainRepo(#[from] crate::open::Error),
+    #[error("Could not list the linked worktrees")]
+    ListWorktrees(#[source] std::io::Error),
+    #[error(transparent)]
+    OpenIndex(#[from] crate::worktree::open_index::Error),
+    #[error(transparent)]
+    FindObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    TryFindObject(#[from] crate::object::find::Error),
+    #[error(transparent)]
+    Decode(#[from] gix_object::decode::Error),
+    #[error("Could not list the objects of the object database")]
+    ListObjects(#[source] std::io::Error),
+    #[error(transparent)]
+    IterateLoose(#[from] gix_odb::loose::iter::Error),
+    #[error(transparent)]
+    OpenPackIndex(#[from] gix_pack::index::init::Error),
+    #[error(transparent)]
+    WalkInit(#[from] crate::revision::walk::Error),
+    #[error(transparent)]
+    Walk(#[from] gix_traverse::commit::ancestors::Error),
+    #[error(transparent)]
+    Count(#[from] Box<CountError>),
+    #[error("Interrupted")]
+    Interrupted,
+}
+
+/// The error produced when counting objects.
+pub type CountError = output::count::objects::Error<
+    gix_pack::find::existing::Error<gix_odb::store::find::Error>,
+    std::convert::Infallible,
+>;
+
+/// The progress ids used in [`Repository::reachable_objects()`].
+///
+/// Use this information to selectively extract the progress of interest in case the parent application has custom visualization.
+#[derive(Debug, Copy, Clone)]
+pub enum ProgressId {
+    /// The amount of commits traversed.
+    WalkCommits,
+    /// The amount of objects found reachable.
+    CountObjects,
+}
+
+impl From<ProgressId> for gix_features::progress::Id {
+    fn from(v: ProgressId) -> Self {
+        match v {
+            ProgressId::WalkCommits => *b"MRWC",
+            ProgressId::CountObjects => *b"MRCO",
+        }
+    }
+}
+
+/// All objects that were found to be reachable, in a form suitable to write them into a pack with the same `odb`.
+pub(crate) struct Outcome {
+    /// All reachable objects.
+    pub objects: Vec<output::Count>,
+    /// The commits that references point to, possibly after peeling tags.
+    pub ref_commits: Vec<ObjectId>,
+    /// All reachable commits along with the trees and blobs references point to, which `objects` were counted from.
+    pub roots: Vec<ObjectId>,
+    /// Objects that aren't reachable, but aren't expired yet or are reachable from unreachable objects that aren't expired yet.
+    ///
+    /// This is only computed if an expiry time was given.
+    pub recent: gix_hashtable::HashSet<ObjectId>,
+}
+
+/// Information about the starting points of the traversal.
+#[derive(Default)]
+struct Tips {
+    /// Object ids of references and worktree `HEAD`s, which must exist.
+    references: Vec<ObjectId>,
+    /// Object ids from reference logs and indices, which are ignored if they don't exist.
+    optional: Vec<ObjectId>,
+}
+
+impl Repository {
+    /// Return the ids of all objects that are reachable from references, including `HEAD` of all worktrees, from reference logs
+    /// and from the indices of all worktrees.
+    ///
+    /// Objects that are mentioned in reference logs or indices but don't exist are ignored, like git does, while references
+    /// that point to missing objects cause an error.
+    ///
+    /// Note that the traversal stops at shallow boundaries, and that objects in alternate object databases are included.
+    pub fn reachable_objects(
+        &self,
+        progress: impl Progress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<gix_hashtable::HashSet<ObjectId>, Error> {
+        let mut odb = self
+            .objects
+            .clone()
+            .into_inner()
+            .into_arc()
+            .map_err(Error::OpenArcOdb)?;
+        odb.prevent_pack_unload();
+        odb.ignore_replacements = true;
+        Ok(self
+            .reachable(odb, None, None, progress, should_interrupt)?
+            .objects
+            .into_iter()
+            .map(|count| count.id)
+            .collect())
+    }
+
+    /// Like [`reachable_objects()`][Self::reachable_objects()], but produces counts that are suitable to be written into a pack
+    /// using `odb`, which must be configured to prevent packs from being unloaded.
+    ///
+    /// If `expire` is set, the [recent objects][Self::recent_objects()] are computed as well.
+    pub(crate) fn reachable(
+        &self,
+        odb: gix_odb::HandleArc,
+        thread_limit: Option<usize>,
+        expire: Option<SystemTime>,
+        mut progress: impl Progress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<Outcome, Error> {
+        let tips = self.reachability_tips()?;
+
+        let mut commits = Vec::new();
+        let mut other_objects = Vec::new();
+        let mut ref_commits = Vec::new();
+        for (id, is_reference) in tips
+            .references
+            .into_iter()
+            .map(|id| (id, true))
+            .chain(tips.optional.into_iter().map(|id| (id, false)))
+        {
+            if !is_reference && !self.objects.contains(id) {
+                continue;
+            }
+            let mut id = id;
+            loop {
+                let object = self.find_object(id)?;
+                match object.kind {
+                    gix_object::Kind::Tag => {
+                        other_objects.push(id);
+                        id = object.to_tag_ref_iter().target_id()?;
+                    }
+                    gix_object::Kind::Commit => {
+                        if is_reference {
+                            ref_commits.push(id);
+                        }
+                        commits.push(id);
+                        break;
+                    }
+                    gix_object::Kind::Tree | gix_object::Kind::Blob => {
+                        other_objects.push(id);
+                        break;
+                    }
+                }
+            }
+        }
+        commits.sort();
+        commits.dedup();
+        ref_commits.sort();
+        ref_commits.dedup();
+
+        let mut walk_progress = progress.add_child_with_id("traverse commits", ProgressId::WalkCommits.into());
+        walk_progress.init(None, gix_features::progress::count("commits"));
+        let start = std::time::Instant::now();
+        let mut objects = Vec::new();
+        for commit in self.rev_walk(commits).all()? {
+            if should_interrupt.load(Ordering::Relaxed) {
+                return Err(Error::Interrupted);
+            }
+            objects.push(commit?.detach());
+            walk_progress.inc();
+        }
+        walk_progress.show_throughput(start);
+        objects.extend(other_objects);
+        let roots = objects.clone();
+
+        let mut count_progress = progress.add_child_with_id("count objects", ProgressId::CountObjects.into());
+        count_progress.init(None, gix_features::progress::count("objects"));
+        let (objects, _stats) = output::count::objects(
+            odb,
+            objects.into_iter().map(Ok::<_, std::convert::Infallible>),
+            count_progress,
+            should_interrupt,
+            output::count::objects::Options {
+                thread_limit,
+                input_object_expansion: output::count::objects::ObjectExpansion::TreeContents,
+                chunk_size: 50,
+            },
+        )
+        .map_err(|err| match err {
+            output::count::objects::Error::Interrupted => Error::Interrupted,
+            err => Error::Count(Box::new(err)),
+        })?;
+        let recent = match expire {
+            Some(_) => {
+                let reachable = objects.iter().map(|count| count.id).collect();
+                self.recent_objects(&reachable, expire, should_interrupt)?
+            }
+            None => Default::default(),
+        };
+        Ok(Outcome {
+            objects,
+            ref_commits,
+            roots,
+            recent,
+        })
+    }
+
+    /// Return all objects which aren't `reachable`, but which aren't expired according to `expire` or which are reachable from
+    /// unreachable objects that aren't expired, like git does to keep everything that recent objects need.
+    ///
+    /// Loose objects and packed objects are considered, with the latter using the modification time of their pack, or the one
+    /// stored for them in cruft packs. Objects which are referred to but don't exist are ignored, as they might have
+    /// been removed before.
+    pub(crate) fn recent_objects(
+        &self,
+        reachable: &gix_hashtable::HashSet<ObjectId>,
+        expire: Option<SystemTime>,
+        should_interrupt: &AtomicBool,
+    ) -> Result<gix_hashtable::HashSet<ObjectId>, Error> {
+        let object_hash = self.object_hash();
+        let objects_dir = self.objects.store_ref().path();
+        let loose = gix_odb::loose::Store::at(objects_dir, object_hash);
+        let mut tips: Vec<_> = maintenance::loose_objects(&loose)?
+            .into_iter()
+            .filter(|(_, mtime)| !maintenance::is_expired(*mtime, expire))
+            .map(|(id, _)| id)
+            .collect();
+        for index_path in maintenance::pack_indices(objects_dir, true).map_err(Error::ListObjects)? {
+            let index = gix_pack::index::File::at(index_path, object_hash)?;
+            let mtimes = maintenance::pack_object_mtimes(&index).map_err(Error::ListObjects)?;
+            tips.extend(
+                index
+                    .iter()
+                    .zip(mtimes)
+                    .filter(|(_, mtime)| !maintenance::is_expired(*mtime, expire))
+                    .map(|(entry, _)| entry.oid),
+            );
+        }
+
+        let mut out = gix_hashtable::HashSet::default();
+        while let Some(id) = tips.pop() {
+            if should_interrupt.load(Ordering::Relaxed) {
+                return Err(Error::Interrupted);
+            }
+            if reachable.contains(&id) || out.contains(&id) {
+                continue;
+            }
+            let object = match self.try_find_object(id)? {
+                Some(object) => object,
+                None => continue,
+            };
+            out.insert(id);
+            match object.kind {
+                gix_object::Kind::Commit => {
+                    let mut commit = object.to_commit_ref_iter();
+                    tips.push(commit.tree_id()?);
+                    tips.extend(commit.parent_ids());
+                }
+                gix_object::Kind::Tree => {
+                    for entry in gix_object::TreeRefIter::from_bytes(&object.data, object_hash) {
+                        let entry = entry?;
+                        if entry.mode != gix_object::tree::EntryMode::Commit {
+                            tips.push(entry.oid.to_owned());
+                        }
+                    }
+                }
+                gix_object::Kind::Tag => tips.push(object.to_tag_ref_iter().target_id()?),
+                gix_object::Kind::Blob => {}
+            }
+        }
+        Ok(out)
+    }
+
+    fn reachability_tips(&self) -> Result<Tips, Error> {
+        let mut tips = Tips::default();
+        for reference in self.references()?.all()? {
+            let reference = reference.map_err(Error::ReferencesIter)?;
+            if let Some(id) = reference.target().try_id() {
+                tips.references.push(id.to_owned());
+            }
+            let name = reference.name().as_bstr().to_owned();
+            let mut log = reference.log_iter();
+            reflog_ids(log.all(), name, &mut tips.optional)?;
+        }
+
+        let main_repo = self.main_repo()?;
+        let worktree_repos = main_repo
+            .worktrees()
+            .map_err(Error::ListWorktrees)?
+            .into_iter()
+            .map(|proxy| proxy.into_repo_with_possibly_inaccessible_worktree())
+            .collect::<Result<Vec<_>, _>>()?;
+        for repo in std::iter::once(&main_repo).chain(worktree_repos.iter()) {
+            let head = repo.head()?;
+            if let Some(id) = head.id() {
+                tips.references.push(id.detach());
+            }
+            let mut log = head.log_iter();
+            reflog_ids(log.all(), "HEAD".into(), &mut tips.optional)?;
+
+            let index = match repo.open_index() {
+                Ok(index) => index,
+                Err(crate::worktree::open_index::Error::IndexFile(gix_index::file::init::Error::Io(err)))
+                    if err.kind() == std::io::ErrorKind::NotFound =>
+                {
+                    continue
+                }
+                Err(err) => return Err(err.into()),
+            };
+            tips.optional.extend(
+                index
+                    .entries()
+                    .iter()
+                    .filter(|entry| entry.mode != gix_index::entry::Mode::COMMIT)
+                    .map(|entry| entry.id),
+            );
+            if let Some(tree) = index.tree() {
+                cache_tree_ids(tree, &mut tips.optional);
+            }
+        }
+        tips.references.sort();
+        tips.references.dedup();
+        tips.optional.sort();
+        tips.optional.dedup();
+        Ok(tips)
+    }
+}
+
+fn reflog_ids(
+    log: std::io::Result<Option<gix_ref::file::log::iter::Forward<'_>>>,
+    name: BString,
+    out: &mut Vec<ObjectId>,
+) -> Result<(), Error> {
+    let log = match log {
+        Ok(Some(log)) => log,
+        Ok(None) => return Ok(()),
+        Err(source) => return Err(Error::ReadReflog { name, source }),
+    };
+    for line in log {
+        let line = line.map_err(|source| Error::DecodeReflog {
+            name: name.clone(),
+            source,
+        })?;
+        out.extend(
+            [line.previous_oid(), line.new_oid()]
+                .into_iter()
+                .filter(|id| !id.is_null()),
+        );
+    }
+    Ok(())
+}
+
+/// Add the ids of all valid trees in the cache-tree extension to `out`.
+fn cache_tree_ids(tree: &gix_index::extension::Tree, out: &mut Vec<ObjectId>) {
+    if tree.num_entries.is_some() {
+        out.push(tree.id);
+    }
+    for child in &tree.children {
+        cache_tree_ids(child, out);
+    }
+}
@@ -0,0 +1,496 @@
+#![allow(clippy::result_large_err)]
+use std::{
+    io::{Seek, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+    time::SystemTime,
+};
+
+use gix_features::progress::Progress;
+use gix_hash::ObjectId;
+use gix_odb::{FindExt, Write as _};
+use gix_pack::data::output;
+use gix_tempfile::{AutoRemove, ContainingDirectory};
+
+use crate::{maintenance, Repository};
+
+/// What to do with objects of the packs to be removed that aren't reachable anymore.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Unreachable {
+    /// Drop unreachable packed objects along with the packs that contain them, and leave unreachable loose objects
+    /// to [`Repository::prune()`].
+    Drop,
+    /// Write unreachable packed objects as loose objects unless they are older than `expire`, so that
+    /// [`Repository::prune()`] can remove them once they are older than its grace period.
+    ///
+    /// Note that these loose objects receive the current time as modification time.
+    Loose {
+        /// Objects older than this point in time are dropped. If `None`, no object is dropped.
+        expire: Option<SystemTime>,
+    },
+    /// Write unreachable packed objects and unreachable loose objects into a _cruft pack_ along with their modification time,
+    /// unless they are older than `expire` in which case they are removed.
+    Cruft {
+        /// Objects older than this point in time are dropped. If `None`, no object is dropped.
+        expire: Option<SystemTime>,
+    },
+}
+
+/// Configuration for [`Repository::repack()`], typically obtained with [`Repository::repack_options()`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// What to do with unreachable objects.
+    pub unreachable: Unreachable,
+    /// If `true`, objects will be decoded and compressed as deltas against similar objects, which is slow but yields the
+    /// smallest packs, like `git repack -f` does. Blobs whose path has the `delta` attribute unset are never stored as deltas.
+    /// Otherwise, deltas are copied from existing packs and loose objects are stored as they are.
+    pub delta_compression: bool,
+    /// The amount of previous objects to try as delta base for each object if `delta_compression` is enabled, like `pack.window`.
+    pub window: u32,
+    /// The maximum length of delta chains if `delta_compression` is enabled, like `pack.depth`.
+    pub depth: u32,
+    /// If `true`, write a reachability bitmap for the pack with all reachable objects, like `repack.writeBitmaps`.
+    ///
+    /// Bitmaps aren't written in shallow repositories as not all commits are available.
+    pub write_bitmap: bool,
+    /// If `true`, write a reverse index for new packs, like `pack.writeReverseIndex`.
+    pub write_reverse_index: bool,
+    /// The amount of threads to use at most, or all logical cores if `None`.
+    pub thread_limit: Option<usize>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            unreachable: Unreachable::Loose { expire: None },
+            delta_compression: false,
+            window: 10,
+            depth: 50,
+            write_bitmap: false,
+            write_reverse_index: false,
+            thread_limit: None,
+        }
+    }
+}
+
+/// The outcome of [`Repository::repack()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// The pack with all reachable objects, or `None` if there were no reachable objects.
+    pub pack: Option<gix_pack::bundle::write::Outcome>,
+    /// The path to the bitmap written for `pack`, if one was written.
+    pub bitmap_path: Option<PathBuf>,
+    /// The cruft pack with unreachable objects, if one was written.
+    pub cruft_pack: Option<gix_pack::bundle::write::Outcome>,
+    /// The paths to the indices of all packs that were removed as their objects are now contained in the new packs, or
+    /// were dropped.
+    pub removed_packs: Vec<PathBuf>,
+    /// The amount of unreachable objects that were written as loose objects.
+    pub loosened_objects: usize,
+    /// The amount of loose objects that were removed as they are now packed, or dropped.
+    pub removed_loose_objects: usize,
+}
+
+///
+pub mod options {
+    /// The error returned by [`Repository::repack_options()`][crate::Repository::repack_options()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Boolean(#[from] crate::config::boolean::Error),
+        #[error(transparent)]
+        UnsignedInteger(#[from] crate::config::unsigned_integer::Error),
+        #[error(transparent)]
+        PruneExpire(#[from] crate::config::time::Error),
+    }
+}
+
+/// The error returned by [`Repository::repack()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Reachable(#[from] maintenance::reachable::Error),
+    #[error(transparent)]
+    DeltaAttribute(#[from] maintenance::delta_attribute::Error),
+    #[error("Could not open the object database for use across threads")]
+    OpenArcOdb(#[source] std::io::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    IterateLoose(#[from] gix_odb::loose::iter::Error),
+    #[error(transparent)]
+    WriteLoose(#[from] gix_odb::loose::write::Error),
+    #[error(transparent)]
+    FindExisting(#[from] gix_odb::find::existing::Error<gix_odb::store::find::Error>),
+    #[error(transparent)]
+    CreateEntries(#[from] output::entry::iter_from_counts::Error<gix_odb::store::find::Error>),
+    #[error(transparent)]
+    WritePackData(#[from] output::bytes::Error<output::entry::iter_from_counts::Error<gix_odb::store::find::Error>>),
+    #[error(transparent)]
+    WriteBundle(#[from] gix_pack::bundle::write::Error),
+    #[error(transparent)]
+    OpenIndex(#[from] gix_pack::index::init::Error),
+    #[error(transparent)]
+    WriteBitmap(#[from] gix_pack::bitmap::write::Error<gix_odb::store::find::Error>),
+    #[error("Interrupted")]
+    Interrupted,
+}
+
+impl Repository {
+    /// Write all reachable objects into a single new pack and remove all packs and loose objects that are obsolete afterwards,
+    /// with unreachable objects being handled according to `options`, similar to `git repack -a -d`.
+    ///
+    /// Packs with a `.keep` or `.promisor` file are left untouched. Objects are considered reachable as described in
+    /// [`reachable_objects()`][Self::reachable_objects()], and objects that are created while repacking remain untouched.
+    /// Expired unreachable objects are kept nonetheless if unreachable objects that aren't expired yet refer to them, directly
+    /// or indirectly.
+    ///
+    /// Packs are removed in a way that allows concurrent readers, like other instances of this repository, to continue
+    /// working, as these will pick up the new pack when they can't find an object anymore.
+    /// A multi-pack-index referring to removed packs is removed as well.
+    pub fn repack<P>(&self, mut progress: P, should_interrupt: &AtomicBool, options: Options) -> Result<Outcome, Error>
+    where
+        P: Progress,
+        P::SubProgress: 'static,
+    {
+        let object_hash = self.object_hash();
+        let objects_dir = self.objects.store_ref().path().to_owned();
+        let pack_dir = objects_dir.join("pack");
+        std::fs::create_dir_all(&pack_dir)?;
+        let existing_packs = maintenance::pack_indices(&objects_dir, false)?;
+        let kept_packs: Vec<_> = maintenance::pack_indices(&objects_dir, true)?
+            .into_iter()
+            .filter(|path| !existing_packs.contains(path))
+            .collect();
+        let loose = gix_odb::loose::Store::at(&objects_dir, object_hash);
+        let loose_objects = maintenance::loose_objects(&loose)?;
+
+        let mut odb = self
+            .objects
+            .clone()
+            .into_inner()
+            .into_arc()
+            .map_err(Error::OpenArcOdb)?;
+        odb.prevent_pack_unload();
+        odb.ignore_replacements = true;
+        let expire = match options.unreachable {
+            Unreachable::Drop => None,
+            Unreachable::Loose { expire } | Unreachable::Cruft { expire } => expire,
+        };
+        let mut reachable = self.reachable(
+            odb.clone(),
+            options.thread_limit,
+            expire,
+            progress.add_child("find reachable objects"),
+            should_interrupt,
+        )?;
+        let reachable_ids: gix_hashtable::HashSet<ObjectId> = reachable.objects.iter().map(|count| count.id).collect();
+
+        let mode = if options.delta_compression {
+            self.mark_objects_without_delta(
+                std::mem::take(&mut reachable.roots),
+                &mut reachable.objects,
+                should_interrupt,
+            )?;
+            output::entry::iter_from_counts::Mode::DeltaCompression {
+                window: options.window,
+                depth: options.depth,
+            }
+        } else {
+            output::entry::iter_from_counts::Mode::PackCopyAndBaseObjects
+        };
+        let mut out = Outcome {
+            pack: write_pack(
+                &odb,
+                reachable.objects,
+                &pack_dir,
+                mode,
+                &options,
+                object_hash,
+                progress.add_child("write pack"),
+                should_interrupt,
+            )?,
+            bitmap_path: None,
+            cruft_pack: None,
+            removed_packs: Vec::new(),
+            loosened_objects: 0,
+            removed_loose_objects: 0,
+        };
+        let new_pack_index = out.pack.as_ref().and_then(|pack| pack.index_path.clone());
+        if let Some(index_path) = new_pack_index
+            .as_ref()
+            .filter(|_| options.write_bitmap && !self.is_shallow())
+        {
+            out.bitmap_path = Some(write_bitmap(
+                &odb,
+                index_path,
+                reachable.ref_commits,
+                object_hash,
+                progress.add_child("write bitmap"),
+                should_interrupt,
+            )?);
+        }
+
+        let obsolete_packs: Vec<_> = existing_packs
+            .into_iter()
+            .filter(|path| Some(path) != new_pack_index.as_ref())
+            .collect();
+        let mut unreachable = gix_hashtable::HashMap::<ObjectId, u32>::default();
+        if options.unreachable != Unreachable::Drop {
+            for index_path in &obsolete_packs {
+                let index = gix_pack::index::File::at(index_path, object_hash)?;
+                let mtimes = maintenance::pack_object_mtimes(&index)?;
+                for (entry, mtime) in index.iter().zip(mtimes) {
+                    if reachable_ids.contains(&entry.oid) {
+                        continue;
+                    }
+                    let previous = unreachable.entry(entry.oid).or_insert(mtime);
+                    *previous = mtime.max(*previous);
+                }
+            }
+        }
+
+        match options.unreachable {
+            Unreachable::Drop => {}
+            Unreachable::Loose { expire } => {
+                let mut buf = Vec::new();
+                for (id, mtime) in &unreachable {
+                    if should_interrupt.load(Ordering::Relaxed) {
+                        return Err(Error::Interrupted);
+                    }
+                    let is_expired = maintenance::is_expired(*mtime, expire) && !reachable.recent.contains(id);
+                    if is_expired || loose.contains(id) {
+                        continue;
+                    }
+                    let object = odb.find(id, &mut buf)?;
+                    loose.write_buf(object.kind, object.data)?;
+                    out.loosened_objects += 1;
+                }
+            }
+            Unreachable::Cruft { expire } => {
+                for (id, mtime) in &loose_objects {
+                    if !reachable_ids.contains(id) {
+                        let previous = unreachable.entry(*id).or_insert(*mtime);
+                        *previous = (*mtime).max(*previous);
+                    }
+                }
+                unreachable
+                    .retain(|id, mtime| !maintenance::is_expired(*mtime, expire) || reachable.recent.contains(id));
+                let counts = unreachable
+                    .keys()
+                    .map(|id| output::Count {
+                        id: *id,
+                        entry_pack_location: output::count::PackLocation::NotLookedUp,
+                        no_delta: false,
+                    })
+                    .collect();
+                out.cruft_pack = write_pack(
+                    &odb,
+                    counts,
+                    &pack_dir,
+                    output::entry::iter_from_counts::Mode::PackCopyAndBaseObjects,
+                    &options,
+                    object_hash,
+                    progress.add_child("write cruft pack"),
+                    should_interrupt,
+                )?;
+                if let Some(index_path) = out.cruft_pack.as_ref().and_then(|pack| pack.index_path.as_ref()) {
+                    write_mtimes(index_path, &unreachable, object_hash)?;
+                }
+            }
+        }
+
+        let is_cruft = matches!(options.unreachable, Unreachable::Cruft { .. });
+        for (id, _mtime) in &loose_objects {
+            if (is_cruft || reachable_ids.contains(id)) && maintenance::remove_loose_object(&loose, id)? {
+                out.removed_loose_objects += 1;
+            }
+        }
+
+        let new_packs: Vec<_> = [&out.pack, &out.cruft_pack]
+            .into_iter()
+            .flatten()
+            .filter_map(|pack| pack.index_path.clone())
+            .collect();
+        let obsolete_packs: Vec<_> = obsolete_packs
+            .into_iter()
+            .filter(|path| !new_packs.contains(path))
+            .collect();
+        if !obsolete_packs.is_empty() {
+            remove_multi_pack_index(&pack_dir)?;
+        }
+        for index_path in obsolete_packs {
+            remove_pack(&index_path)?;
+            out.removed_packs.push(index_path);
+        }
+
+        for pack in [&out.pack, &out.cruft_pack].into_iter().flatten() {
+            let was_kept = pack.index_path.as_ref().map_or(false, |path| kept_packs.contains(path));
+            if let Some(keep_path) = pack.keep_path.as_ref().filter(|_| !was_kept) {
+                match std::fs::remove_file(keep_path) {
+                    Ok(()) => {}
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                    Err(err) => return Err(err.into()),
+                }
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// Write all objects in `counts` into a new pack in `pack_dir` along with its index, using `odb` to obtain their data,
+/// or return `None` if there are no objects to write.
+#[allow(clippy::too_many_arguments)]
+fn write_pack<P>(
+    odb: &gix_odb::HandleArc,
+    counts: Vec<output::Count>,
+    pack_dir: &Path,
+    mode: output::entry::iter_from_counts::Mode,
+    options: &Options,
+    object_hash: gix_hash::Kind,
+    mut progress: P,
+    should_interrupt: &AtomicBool,
+) -> Result<Option<gix_pack::bundle::write::Outcome>, Error>
+where
+    P: Progress,
+    P::SubProgress: 'static,
+{
+    if counts.is_empty() {
+        return Ok(None);
+    }
+    let num_entries = counts.len() as u32;
+    let mut entries = gix_features::parallel::InOrderIter::from(output::entry::iter_from_counts(
+        counts,
+        odb.clone(),
+        progress.add_child("create entries"),
+        output::entry::iter_from_counts::Options {
+            thread_limit: options.thread_limit,
+            mode,
+            allow_thin_pack: false,
+            chunk_size: 1000,
+            version: Default::default(),
+        },
+    ));
+
+    let mut pack_file = gix_tempfile::new(pack_dir, ContainingDirectory::Exists, AutoRemove::Tempfile)?;
+    {
+        let mut write_progress = progress.add_child("write pack data");
+        write_progress.init(None, gix_features::progress::bytes());
+        let mut writer = std::io::BufWriter::new(&mut pack_file);
+        let mut pack_data = output::bytes::FromEntriesIter::new(
+            entries.by_ref(),
+            &mut writer,
+            num_entries,
+            gix_pack::data::Version::default(),
+            object_hash,
+        );
+        for written in pack_data.by_ref() {
+            if should_interrupt.load(Ordering::Relaxed) {
+                return Err(Error::Interrupted);
+            }
+            write_progress.inc_by(written? as usize);
+        }
+        drop(pack_data);
+        writer.flush()?;
+    }
+    pack_file.seek(std::io::SeekFrom::Start(0))?;
+
+    let outcome = gix_pack::Bundle::write_to_directory(
+        std::io::BufReader::new(pack_file),
+        Some(pack_dir),
+        progress.add_child("index pack"),
+        should_interrupt,
+        None,
+        gix_pack::bundle::write::Options {
+            thread_limit: options.thread_limit,
+            iteration_mode: gix_pack::data::input::Mode::Verify,
+            index_version: Default::default(),
+            object_hash,
+            write_reverse_index: options.write_reverse_index,
+        },
+    )?;
+    Ok(Some(outcome))
+}
+
+/// Write a bitmap for the pack at `index_path` next to it, with a bitmap for each of the `tips`, and return its path.
+fn write_bitmap(
+    odb: &gix_odb::HandleArc,
+    index_path: &Path,
+    tips: Vec<ObjectId>,
+    object_hash: gix_hash::Kind,
+    progress: impl Progress,
+    should_interrupt: &AtomicBool,
+) -> Result<PathBuf, Error> {
+    let index = gix_pack::index::File::at(index_path, object_hash)?;
+    let pack_dir = index_path.parent().expect("index is in pack directory");
+    let mut file = gix_tempfile::new(pack_dir, ContainingDirectory::Exists, AutoRemove::Tempfile)?;
+    {
+        let mut writer = std::io::BufWriter::new(&mut file);
+        gix_pack::bitmap::File::write_from_index(&index, odb.clone(), tips, &mut writer, progress, should_interrupt)?;
+        writer.flush()?;
+    }
+    let path = index_path.with_extension("bitmap");
+    file.persist(&path).map_err(|err| err.error)?;
+    Ok(path)
+}
+
+/// Write the modification times of all objects in the cruft pack at `index_path` next to it, as found in `mtimes`.
+fn write_mtimes(
+    index_path: &Path,
+    mtimes: &gix_hashtable::HashMap<ObjectId, u32>,
+    object_hash: gix_hash::Kind,
+) -> Result<(), Error> {
+    let index = gix_pack::index::File::at(index_path, object_hash)?;
+    let pack_dir = index_path.parent().expect("index is in pack directory");
+    let mut file = gix_tempfile::new(pack_dir, ContainingDirectory::Exists, AutoRemove::Tempfile)?;
+    {
+        let mut writer = std::io::BufWriter::new(&mut file);
+        gix_pack::index::mtimes::File::write_to(
+            index
+                .iter()
+                .map(|entry| mtimes.get(&entry.oid).copied().expect("all packed objects have a time")),
+            &index.pack_checksum(),
+            &mut writer,
+        )?;
+        writer.flush()?;
+    }
+    file.persist(index_path.with_extension("mtimes"))
+        .map_err(|err| err.error)?;
+    Ok(())
+}
+
+/// Remove the multi-pack-index in `pack_dir` along with its bitmap and reverse index, if present.
+fn remove_multi_pack_index(pack_dir: &Path) -> std::io::Result<()> {
+    remove_if_present(&pack_dir.join("multi-pack-index"))?;
+    for entry in std::fs::read_dir(pack_dir)? {
+        let path = entry?.path();
+        let is_midx_file = path.file_name().and_then(|name| name.to_str()).map_or(false, |name| {
+            name.starts_with("multi-pack-index-") && (name.ends_with(".bitmap") || name.ends_with(".rev"))
+        });
+        if is_midx_file {
+            remove_if_present(&path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Remove the pack with the index at `index_path` and all of its auxiliary files.
+///
+/// The index goes first so the pack isn't discovered anymore, while readers that already use it may continue
+/// to do so on systems that allow removing files that are still open.
+fn remove_pack(index_path: &Path) -> std::io::Result<()> {
+    for extension in ["idx", "pack", "rev", "bitmap", "mtimes"] {
+        remove_if_present(&index_path.with_extension(extension))?;
+    }
+    Ok(())
+}
+
+fn remove_if_present(path: &Path) -> std::io::Result<()> {
+    match std::fs::remove_file(path) {
+        Ok(()) => Ok(()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(err),
+    }
+}
@@ -0,0 +1,4 @@
+pub use gix_diff::blob::merge as blob;
+
+///
+pub mod tree;
@@ -0,0 +1,684 @@
+#![allow(clippy::result_large_err)]
+use std::collections::{BTreeMap, BTreeSet};
+
+use gix_hash::ObjectId;
+use gix_object::tree::EntryMode;
+use gix_odb::FindExt;
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice, ByteVec},
+    ext::ObjectIdExt,
+    object::tree::diff::{change::Event, Action, Rewrites},
+    Id, Repository,
+};
+
+/// The error returned by [`Repository::merge_trees()`][crate::Repository::merge_trees()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    FindObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    PeelToTree(#[from] crate::object::peel::to_kind::Error),
+    #[error(transparent)]
+    Traverse(#[from] gix_traverse::tree::breadthfirst::Error),
+    #[error(transparent)]
+    RewritesConfig(#[from] crate::object::tree::diff::rewrites::Error),
+    #[error("Could not track renames")]
+    Diff(#[from] crate::object::tree::diff::for_each::Error),
+    #[error(transparent)]
+    WriteObject(#[from] crate::object::write::Error),
+    #[error(transparent)]
+    AttributeCache(#[from] crate::worktree::attributes::Error),
+    #[error("Could not read the attributes for '{path}'")]
+    Attributes { path: BString, source: std::io::Error },
+}
+
+/// The labels to show next to conflict markers, and to use when moving files out of the way.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct Labels {
+    /// The label of the common ancestor.
+    pub ancestor: Option<BString>,
+    /// The label of *ours*. Files moved out of the way use `ours` as suffix if unset.
+    pub current: Option<BString>,
+    /// The label of *theirs*. Files moved out of the way use `theirs` as suffix if unset.
+    pub other: Option<BString>,
+}
+
+/// Options for use in [`Repository::merge_trees_opts()`][crate::Repository::merge_trees_opts()].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    /// Control how renames are detected on each side, or disable rename tracking with `None`.
+    pub rewrites: Option<Rewrites>,
+    /// Options to use when merging the content of files that were changed on both sides.
+    pub blob_merge: crate::merge::blob::Options,
+    /// The labels to use next to conflict markers.
+    pub labels: Labels,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            rewrites: Some(Default::default()),
+            blob_merge: Default::default(),
+            labels: Default::default(),
+        }
+    }
+}
+
+/// A version of a file as it was seen in one of the trees participating in a merge.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Version {
+    /// The path to the file, relative to the root of its tree.
+    pub location: BString,
+    /// The mode of the file.
+    pub mode: EntryMode,
+    /// The id of the object.
+    pub id: ObjectId,
+}
+
+/// The side of a merge.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Side {
+    /// The tree we merge into.
+    Ours,
+    /// The tree we merge.
+    Theirs,
+}
+
+/// A conflict encountered when merging trees, which isn't fully resolved in the resulting tree.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Conflict {
+    /// The kind of conflict.
+    pub kind: conflict::Kind,
+    /// The location of the conflicting file in the resulting tree, which may contain conflict markers.
+    pub location: BString,
+    /// The version of the file in the common ancestor, if there was one.
+    pub base: Option<Version>,
+    /// The version of the file in *ours*, if there was one.
+    pub ours: Option<Version>,
+    /// The version of the file in *theirs*, if there was one.
+    pub theirs: Option<Version>,
+}
+
+///
+pub mod conflict {
+    use super::Side;
+
+    /// Identify the kind of [`Conflict`][super::Conflict].
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+    pub enum Kind {
+        /// Both sides changed the same lines of a file, or its mode, differently. The file in the resulting tree contains
+        /// conflict markers if it could be merged line by line, or is the version of *ours* otherwise.
+        Content,
+        /// Both sides added a file at the same location. The file in the resulting tree contains conflict markers
+        /// if it could be merged line by line.
+        AddAdd,
+        /// One side modified a file which was deleted by the other side. The modified version is kept in the resulting tree.
+        ModifyDelete {
+            /// The side that deleted the file.
+            deleted_by: Side,
+        },
+        /// One side renamed a file which was deleted by the other side. The renamed file is kept in the resulting tree.
+        RenameDelete {
+            /// The side that deleted the file.
+            deleted_by: Side,
+        },
+        /// Both sides renamed the same file to different locations. Both locations contain the merged content in the resulting
+        /// tree, with [`location`][super::Conflict::location] being the one chosen by *ours*.
+        RenameRename,
+        /// One side added a file where the other side added a directory. The file is moved to a location with the
+        /// label of its side as suffix, like `file~ours`, which is its [`location`][super::Conflict::location].
+        DirectoryFile {
+            /// The side that added the file.
+            file_side: Side,
+        },
+    }
+}
+
+/// The outcome of [`Repository::merge_trees()`][crate::Repository::merge_trees()].
+#[derive(Debug, Clone)]
+pub struct Outcome<'repo> {
+    /// The id of the merged tree, which was written to the object database.
+    ///
+    /// Conflicting files in it contain conflict markers if possible.
+    pub tree: Id<'repo>,
+    /// All conflicts that were encountered, sorted by location. If empty, the merge was clean.
+    pub conflicts: Vec<Conflict>,
+}
+
+impl<'repo> Outcome<'repo> {
+    /// Return `true` if there were conflicts which need to be resolved.
+    pub fn has_conflicts(&self) -> bool {
+        !self.conflicts.is_empty()
+    }
+
+    /// Create an index from the [merged tree][Outcome::tree] which has the stage 1, 2 and 3 entries of each [conflict][Outcome::conflicts]
+    /// instead of the stage 0 entries of the conflicting files, similar to what `git merge` leaves behind.
+    pub fn index(&self) -> Result<gix_index::State, gix_traverse::tree::breadthfirst::Error> {
+        use gix_index::entry::{Flags, Stat};
+
+        let repo = self.tree.repo;
+        let merged = gix_index::State::from_tree(&self.tree, |oid, buf| repo.objects.find_tree_iter(oid, buf).ok())?;
+        let conflicting_locations: BTreeSet<&BStr> = self
+            .conflicts
+            .iter()
+            .flat_map(|c| {
+                std::iter::once(c.location.as_bstr()).chain(
+                    [&c.base, &c.ours, &c.theirs]
+                        .into_iter()
+                        .flatten()
+                        .map(|v| v.location.as_bstr()),
+                )
+            })
+            .collect();
+
+        let mut index = gix_index::State::new(repo.object_hash());
+        for entry in merged.entries() {
+            let path = entry.path(&merged);
+            if !conflicting_locations.contains(path) {
+                index.dangerously_push_entry(entry.stat, entry.id, entry.flags, entry.mode, path);
+            }
+        }
+        for conflict in &self.conflicts {
+            for (stage, version) in [(1, &conflict.base), (2, &conflict.ours), (3, &conflict.theirs)] {
+                if let Some(version) = version {
+                    index.dangerously_push_entry(
+                        Stat::default(),
+                        version.id,
+                        Flags::from_bits_retain(stage << 12),
+                        index_mode(version.mode),
+                        version.location.as_ref(),
+                    );
+                }
+            }
+        }
+        index.sort_entries();
+        Ok(index)
+    }
+}
+
+fn index_mode(mode: EntryMode) -> gix_index::entry::Mode {
+    use gix_index::entry::Mode;
+    match mode {
+        EntryMode::Tree => Mode::DIR,
+        EntryMode::Blob => Mode::FILE,
+        EntryMode::BlobExecutable => Mode::FILE_EXECUTABLE,
+        EntryMode::Link => Mode::SYMLINK,
+        EntryMode::Commit => Mode::COMMIT,
+    }
+}
+
+/// All non-tree entries of a tree by their location.
+type Entries = BTreeMap<BString, (EntryMode, ObjectId)>;
+
+/// Renames from a location in the common ancestor to a location in one side.
+type Renames = BTreeMap<BString, BString>;
+
+pub(crate) fn merge<'repo>(
+    repo: &'repo Repository,
+    base: ObjectId,
+    ours: ObjectId,
+    theirs: ObjectId,
+    options: Options,
+) -> Result<Outcome<'repo>, Error> {
+    let base = repo.find_object(base)?.peel_to_tree()?;
+    let ours = repo.find_object(ours)?.peel_to_tree()?;
+    let theirs = repo.find_object(theirs)?.peel_to_tree()?;
+
+    let mut state = State {
+        repo,
+        base: entries(&base)?,
+        ours: entries(&ours)?,
+        theirs: entries(&theirs)?,
+        ours_renames: renames(&base, &ours, options.rewrites)?,
+        theirs_renames: renames(&base, &theirs, options.rewrites)?,
+        result: Default::default(),
+        conflicts: Default::default(),
+        attributes: None,
+        options,
+    };
+    state.merge_renames()?;
+    state.merge_paths()?;
+    state.resolve_directory_file_conflicts();
+
+    let tree = write_tree(repo, &state.result)?;
+    let mut conflicts = state.conflicts;
+    conflicts.sort_by(|a, b| a.location.cmp(&b.location));
+    Ok(Outcome {
+        tree: tree.attach(repo),
+        conflicts,
+    })
+}
+
+struct State<'repo> {
+    repo: &'repo Repository,
+    base: Entries,
+    ours: Entries,
+    theirs: Entries,
+    ours_renames: Renames,
+    theirs_renames: Renames,
+    result: Entries,
+    conflicts: Vec<Conflict>,
+    /// The attribute stack to obtain the merge driver of a path with, created on first use.
+    attributes: Option<(gix_worktree::Cache, gix_attributes::search::Outcome)>,
+    options: Options,
+}
+
+impl<'repo> State<'repo> {
+    /// Merge all files that were renamed on at least one side.
+    fn merge_renames(&mut self) -> Result<(), Error> {
+        let renamed: BTreeSet<_> = self
+            .ours_renames
+            .keys()
+            .chain(self.theirs_renames.keys())
+            .cloned()
+            .collect();
+        for source in renamed {
+            let base = version(&self.base, &source).expect("rename sources exist in base");
+            let ours_location = self.ours_renames.get(&source).cloned();
+            let theirs_location = self.theirs_renames.get(&source).cloned();
+            let ours = version(&self.ours, ours_location.as_ref().unwrap_or(&source));
+            let theirs = version(&self.theirs, theirs_location.as_ref().unwrap_or(&source));
+            match (ours_location, theirs_location) {
+                (Some(ours_location), Some(theirs_location)) if ours_location != theirs_location => {
+                    let (ours, theirs) = (ours.expect("rename destination"), theirs.expect("rename destination"));
+                    let (mode, id, _) = self.merge_versions(Some(&base), &ours, &theirs)?;
+                    self.add(ours_location.clone(), mode, id);
+                    self.add(theirs_location, mode, id);
+                    self.conflicts.push(Conflict {
+                        kind: conflict::Kind::RenameRename,
+                        location: ours_location,
+                        base: Some(base),
+                        ours: Some(ours),
+                        theirs: Some(theirs),
+                    });
+                }
+                (ours_location, theirs_location) => {
+                    let location = ours_location.or(theirs_location).expect("at least one side renamed");
+                    match (ours, theirs) {
+                        (Some(ours), Some(theirs)) => {
+                            let (mode, id, conflicted) = self.merge_versions(Some(&base), &ours, &theirs)?;
+                            self.add(location.clone(), mode, id);
+                            if conflicted {
+                                self.conflicts.push(Conflict {
+                                    kind: conflict::Kind::Content,
+                                    location,
+                                    base: Some(base),
+                                    ours: Some(ours),
+                                    theirs: Some(theirs),
+                                });
+                            }
+                        }
+                        (ours, theirs) => {
+                            let (deleted_by, kept) = match (ours, theirs) {
+                                (Some(ours), None) => (Side::Theirs, ours),
+                                (None, Some(theirs)) => (Side::Ours, theirs),
+                                _ => unreachable!("renamed files exist on at least one side"),
+                            };
+                            self.add(location.clone(), kept.mode, kept.id);
+                            let (ours, theirs) = match deleted_by {
+                                Side::Theirs => (Some(kept), None),
+                                Side::Ours => (None, Some(kept)),
+                            };
+                            self.conflicts.push(Conflict {
+                                kind: conflict::Kind::RenameDelete { deleted_by },
+                                location,
+                                base: Some(base),
+                                ours,
+                                theirs,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Merge all files that weren't involved in renames, by location.
+    fn merge_paths(&mut self) -> Result<(), Error> {
+        let locations: BTreeSet<_> = self
+            .base
+            .keys()
+            .chain(self.ours.keys())
+            .chain(self.theirs.keys())
+            .cloned()
+            .collect();
+        let is_rename_source =
+            |location: &BString| self.ours_renames.contains_key(location) || self.theirs_renames.contains_key(location);
+        let ours_rename_destinations: BTreeSet<_> = self.ours_renames.values().cloned().collect();
+        let theirs_rename_destinations: BTreeSet<_> = self.theirs_renames.values().cloned().collect();
+        let mut changes = Vec::new();
+        for location in locations {
+            let renamed = is_rename_source(&location);
+            let base = (!renamed).then(|| version(&self.base, &location)).flatten();
+            let ours = (!renamed && !ours_rename_destinations.contains(&location))
+                .then(|| version(&self.ours, &location))
+                .flatten();
+            let theirs = (!renamed && !theirs_rename_destinations.contains(&location))
+                .then(|| version(&self.theirs, &location))
+                .flatten();
+            changes.push((location, base, ours, theirs));
+        }
+
+        for (location, base, ours, theirs) in changes {
+            let (mode_and_id, kind) = match (&base, &ours, &theirs) {
+                (_, ours, theirs) if same(ours, theirs) => (ours.as_ref().map(|v| (v.mode, v.id)), None),
+                (base, ours, theirs) if same(base, ours) => (theirs.as_ref().map(|v| (v.mode, v.id)), None),
+                (base, ours, theirs) if same(base, theirs) => (ours.as_ref().map(|v| (v.mode, v.id)), None),
+                (None, Some(ours), Some(theirs)) => {
+                    let (mode, id, conflicted) = self.merge_versions(None, ours, theirs)?;
+                    (Some((mode, id)), conflicted.then_some(conflict::Kind::AddAdd))
+                }
+                (Some(base), Some(ours), Some(theirs)) => {
+                    let (mode, id, conflicted) = self.merge_versions(Some(base), ours, theirs)?;
+                    (Some((mode, id)), conflicted.then_some(conflict::Kind::Content))
+                }
+                (Some(_), None, Some(theirs)) => (
+                    Some((theirs.mode, theirs.id)),
+                    Some(conflict::Kind::ModifyDelete { deleted_by: Side::Ours }),
+                ),
+                (Some(_), Some(ours), None) => (
+                    Some((ours.mode, ours.id)),
+                    Some(conflict::Kind::ModifyDelete {
+                        deleted_by: Side::Theirs,
+                    }),
+                ),
+                _ => unreachable!("changes on only one side, or none, are handled by comparing with the base"),
+            };
+            let (mode, id) = match mode_and_id {
+                Some(mode_and_id) => mode_and_id,
+                None => continue,
+            };
+            if let Some((previous_mode, previous_id)) = self.result.get(&location).copied() {
+                // A file was renamed to a location which was also used by a file on the other side.
+                let previous = Version {
+                    location: location.clone(),
+                    mode: previous_mode,
+                    id: previous_id,
+                };
+                let current = Version {
+                    location: location.clone(),
+                    mode,
+                    id,
+                };
+                let (mode, id, conflicted) = self.merge_versions(None, &previous, &current)?;
+                self.add(location.clone(), mode, id);
+                if conflicted {
+                    self.conflicts.push(Conflict {
+                        kind: conflict::Kind::AddAdd,
+                        location,
+                        base: None,
+                        ours: Some(previous),
+                        theirs: Some(current),
+                    });
+                }
+                continue;
+            }
+            self.add(location.clone(), mode, id);
+            if let Some(kind) = kind {
+                self.conflicts.push(Conflict {
+                    kind,
+                    location,
+                    base,
+                    ours,
+                    theirs,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Move files out of the way that are located where the other side placed a directory.
+    fn resolve_directory_file_conflicts(&mut self) {
+        let files_in_the_way: Vec<_> = self
+            .result
+            .keys()
+            .filter(|location| {
+                let mut prefix = (*location).clone();
+                prefix.push(b'/');
+                self.result
+                    .range(prefix.clone()..)
+                    .next()
+                    .map_or(false, |(next, _)| next.starts_with(&prefix))
+            })
+            .cloned()
+            .collect();
+        for location in files_in_the_way {
+            let (mode, id) = self.result.remove(&location).expect("present");
+            let file_side = if self.ours.get(&location) == Some(&(mode, id)) {
+                Side::Ours
+            } else {
+                Side::Theirs
+            };
+            let label = match file_side {
+                Side::Ours => self.options.labels.current.as_ref(),
+                Side::Theirs => self.options.labels.other.as_ref(),
+            }
+            .map_or_else(
+                || {
+                    match file_side {
+                        Side::Ours => "ours",
+                        Side::Theirs => "theirs",
+                    }
+                    .as_bytes()
+                    .to_owned()
+                },
+                |label| label.replace("/", "_"),
+            );
+            let mut new_location = location.clone();
+            new_location.push(b'~');
+            new_location.push_str(&label);
+            let mut unique_location = new_location.clone();
+            let mut count = 0;
+            while self.result.contains_key(&unique_location) {
+                count += 1;
+                unique_location = new_location.clone();
+                unique_location.push_str(format!("_{count}"));
+            }
+            self.add(unique_location.clone(), mode, id);
+
+            let moved = Version {
+                location: unique_location.clone(),
+                mode,
+                id,
+            };
+            let (ours, theirs) = match file_side {
+                Side::Ours => (Some(moved), None),
+                Side::Theirs => (None, Some(moved)),
+            };
+            // Replace previous conflicts about the file, as the file is now elsewhere.
+            self.conflicts.retain(|c| c.location != location);
+            self.conflicts.push(Conflict {
+                kind: conflict::Kind::DirectoryFile { file_side },
+                location: unique_location,
+                base: version(&self.base, &location),
+                ours,
+                theirs,
+            });
+        }
+    }
+
+    /// Merge the mode and content of `ours` and `theirs`, using `base` as common ancestor or an empty file if there is none,
+    /// and return the resulting mode and id, along with `true` if there was a conflict.
+    ///
+    /// The content is merged with the driver set by the `merge` attribute of the location of `ours`.
+    fn merge_versions(
+        &mut self,
+        base: Option<&Version>,
+        ours: &Version,
+        theirs: &Version,
+    ) -> Result<(EntryMode, ObjectId, bool), Error> {
+        let is_blob = |mode: EntryMode| matches!(mode, EntryMode::Blob | EntryMode::BlobExecutable);
+        if ours.mode == theirs.mode && ours.id == theirs.id {
+            return Ok((ours.mode, ours.id, false));
+        }
+        if !is_blob(ours.mode) || !is_blob(theirs.mode) || base.map_or(false, |base| !is_blob(base.mode)) {
+            return Ok(match base {
+                Some(base) if base.mode == ours.mode && base.id == ours.id => (theirs.mode, theirs.id, false),
+                Some(base) if base.mode == theirs.mode && base.id == theirs.id => (ours.mode, ours.id, false),
+                _ => (ours.mode, ours.id, true),
+            });
+        }
+
+        let (mode, mut conflicted) = match base {
+            _ if ours.mode == theirs.mode => (ours.mode, false),
+            Some(base) if base.mode == ours.mode => (theirs.mode, false),
+            Some(base) if base.mode == theirs.mode => (ours.mode, false),
+            _ => (ours.mode, true),
+        };
+        let id = match base {
+            _ if ours.id == theirs.id => ours.id,
+            Some(base) if base.id == ours.id => theirs.id,
+            Some(base) if base.id == theirs.id => ours.id,
+            _ => {
+                let base_data = match base {
+                    Some(base) => self.repo.find_object(base.id)?.detach().data,
+                    None => Vec::new(),
+                };
+                let ours_data = self.repo.find_object(ours.id)?.detach().data;
+                let theirs_data = self.repo.find_object(theirs.id)?.detach().data;
+                let driver = self.driver(ours.location.as_ref())?;
+                let labels = &self.options.labels;
+                let mut out = Vec::new();
+                let resolution = crate::merge::blob::blob(
+                    &mut out,
+                    &base_data,
+                    &ours_data,
+                    &theirs_data,
+                    driver,
+                    crate::merge::blob::Labels {
+                        ancestor: labels.ancestor.as_ref().map(AsRef::as_ref),
+                        current: labels.current.as_ref().map(AsRef::as_ref),
+                        other: labels.other.as_ref().map(AsRef::as_ref),
+                    },
+                    self.options.blob_merge,
+                );
+                conflicted |= resolution == crate::merge::blob::Resolution::Conflict;
+                self.repo.write_blob(out)?.detach()
+            }
+        };
+        Ok((mode, id, conflicted))
+    }
+
+    /// Return the merge driver for the file at `location` as set by its `merge` attribute.
+    fn driver(&mut self, location: &BStr) -> Result<crate::merge::blob::Driver, Error> {
+        if self.attributes.is_none() {
+            let cache = self.repo.checkout_attributes()?;
+            let matches = cache.selected_attribute_matches(Some("merge"));
+            self.attributes = Some((cache, matches));
+        }
+        let (cache, matches) = self.attributes.as_mut().expect("initialized");
+        let repo = self.repo;
+        cache
+            .at_entry(location, Some(false), |id, buf| repo.objects.find_blob(id, buf))
+            .map_err(|source| Error::Attributes {
+                path: location.to_owned(),
+                source,
+            })?
+            .matching_attributes(matches);
+        Ok(matches
+            .iter_selected()
+            .next()
+            .map_or(crate::merge::blob::Driver::Text, |m| {
+                crate::merge::blob::Driver::from_attribute(m.assignment.state)
+            }))
+    }
+
+    fn add(&mut self, location: BString, mode: EntryMode, id: ObjectId) {
+        self.result.insert(location, (mode, id));
+    }
+}
+
+fn same(a: &Option<Version>, b: &Option<Version>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => a.mode == b.mode && a.id == b.id,
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+fn version(entries: &Entries, location: &BString) -> Option<Version> {
+    entries.get(location).map(|(mode, id)| Version {
+        location: location.clone(),
+        mode: *mode,
+        id: *id,
+    })
+}
+
+fn entries(tree: &crate::Tree<'_>) -> Result<Entries, Error> {
+    let mut recorder = gix_traverse::tree::Recorder::default();
+    tree.traverse().breadthfirst(&mut recorder)?;
+    Ok(recorder
+        .records
+        .into_iter()
+        .filter(|entry| !entry.mode.is_tree())
+        .map(|entry| (entry.filepath, (entry.mode, entry.oid)))
+        .collect())
+}
+
+fn renames(base: &crate::Tree<'_>, other: &crate::Tree<'_>, rewrites: Option<Rewrites>) -> Result<Renames, Error> {
+    let mut renames = Renames::new();
+    if rewrites.is_none() {
+        return Ok(renames);
+    }
+    base.changes()?
+        .track_path()
+        .track_rewrites(rewrites)
+        .for_each_to_obtain_tree(other, |change| {
+            if let Event::Rewrite {
+                source_location,
+                entry_mode,
+                copy: false,
+                ..
+            } = change.event
+            {
+                if !entry_mode.is_tree() {
+                    renames.insert(source_location.to_owned(), change.location.to_owned());
+                }
+            }
+            Ok::<_, std::convert::Infallible>(Action::Continue)
+        })?;
+    Ok(renames)
+}
+
+/// Write all `entries` as trees and return the id of the root tree.
+fn write_tree(repo: &Repository, entries: &Entries) -> Result<ObjectId, Error> {
+    #[derive(Default)]
+    struct Directory {
+        files: Vec<gix_object::tree::Entry>,
+        directories: BTreeMap<BString, Directory>,
+    }
+
+    fn write(repo: &Repository, dir: Directory) -> Result<ObjectId, Error> {
+        let mut tree = gix_object::Tree { entries: dir.files };
+        for (filename, dir) in dir.directories {
+            tree.entries.push(gix_object::tree::Entry {
+                mode: EntryMode::Tree,
+                filename,
+                oid: write(repo, dir)?,
+            });
+        }
+        tree.entries.sort();
+        Ok(repo.write_object(&tree)?.detach())
+    }
+
+    let mut root = Directory::default();
+    for (location, (mode, id)) in entries {
+        let mut components = location.split_str("/").peekable();
+        let mut dir = &mut root;
+        while let Some(component) = components.next() {
+            if components.peek().is_none() {
+                dir.files.push(gix_object::tree::Entry {
+                    mode: *mode,
+                    filename: component.into(),
+                    oid: *id,
+                });
+            } else {
+                dir = dir.directories.entry(component.into()).or_default();
+            }
+        }
+    }
+    write(repo, root)
+}
@@ -157,7 +157,7 @@ impl<'repo> Object<'repo> {
     /// Obtain a fully parsed commit whose fields reference our data buffer.
     pub fn try_to_commit_ref(&self) -> Result<gix_object::CommitRef<'_>, conversion::Error> {
         gix_object::Data::new(self.kind, &self.data)
-            .decode()?
+            .decode(self.repo.object_hash())?
             .into_commit()
             .ok_or(conversion::Error::UnexpectedType {
                 expected: gix_object::Kind::Commit,
@@ -214,7 +214,7 @@ impl<'repo> Object<'repo> {
     /// Obtain a fully parsed tag object whose fields reference our data buffer.
     pub fn try_to_tag_ref(&self) -> Result<gix_object::TagRef<'_>, conversion::Error> {
         gix_object::Data::new(self.kind, &self.data)
-            .decode()?
+            .decode(self.repo.object_hash())?
             .into_tag()
             .ok_or(conversion::Error::UnexpectedType {
                 expected: gix_object::Kind::Tag,
@@ -58,12 +58,13 @@ impl<'a, 'old> Platform<'a, 'old> {
             tracked: self.rewrites.map(|r| tracked::State::new(r, self.tracking)),
             err: None,
         };
-        match gix_diff::tree::Changes::from(TreeRefIter::from_bytes(&self.lhs.data)).needed_to_obtain(
-            TreeRefIter::from_bytes(&other.data),
-            &mut self.state,
-            |oid, buf| repo.objects.find_tree_iter(oid, buf),
-            &mut delegate,
-        ) {
+        match gix_diff::tree::Changes::from(TreeRefIter::from_bytes(&self.lhs.data, repo.object_hash()))
+            .needed_to_obtain(
+                TreeRefIter::from_bytes(&other.data, repo.object_hash()),
+                &mut self.state,
+                |oid, buf| repo.objects.find_tree_iter(oid, buf),
+                &mut delegate,
+            ) {
             Ok(()) => {
                 let outcome = Outcome {
                     rewrites: delegate.process_tracked_changes()?,
@@ -24,7 +24,7 @@ impl<'repo> Tree<'repo> {
 
     /// Parse our tree data and return the parse tree for direct access to its entries.
     pub fn decode(&self) -> Result<gix_object::TreeRef<'_>, gix_object::decode::Error> {
-        gix_object::TreeRef::from_bytes(&self.data)
+        gix_object::TreeRef::from_bytes(&self.data, self.repo.object_hash())
     }
 
     // TODO: tests.
@@ -49,7 +49,7 @@ impl<'repo> Tree<'repo> {
     {
         let mut path = path.into_iter().peekable();
         while let Some(component) = path.next() {
-            match TreeRefIter::from_bytes(&self.data)
+            match TreeRefIter::from_bytes(&self.data, self.repo.object_hash())
                 .filter_map(Result::ok)
                 .find(|entry| component.eq(entry.filename))
             {
@@ -54,6 +54,8 @@ pub enum Error {
     Io(#[from] std::io::Error),
     #[error("The git directory at '{}' is considered unsafe as it's not owned by the current user.", .path.display())]
     UnsafeGitDir { path: PathBuf },
+    #[error("The linked worktree at '{}' belongs to a repository which stores its references in reftables, which isn't supported for linked worktrees yet", .path.display())]
+    UnsupportedRefStorage { path: PathBuf },
     #[error(transparent)]
     EnvironmentAccessDenied(#[from] gix_sec::permission::Error<std::path::PathBuf>),
 }
@@ -95,7 +95,6 @@ impl ThreadSafeRepository {
     ///
     /// Note that this will read various `GIT_*` environment variables to check for overrides, and is probably most useful when implementing
     /// custom hooks.
-    // TODO: tests, with hooks, GIT_QUARANTINE for ref-log and transaction control (needs gix-sec support to remove write access in gix-ref)
     // TODO: The following vars should end up as overrides of the respective configuration values (see gix-config).
     //       GIT_PROXY_SSL_CERT, GIT_PROXY_SSL_KEY, GIT_PROXY_SSL_CERT_PASSWORD_PROTECTED.
     //       GIT_PROXY_SSL_CAINFO, GIT_SSL_CIPHER_LIST, GIT_HTTP_MAX_REQUESTS, GIT_CURL_FTP_NO_EPSV,
@@ -176,12 +175,21 @@ impl ThreadSafeRepository {
             lossy_config,
             lenient_config,
         )?;
-        let mut refs = {
+        let mut refs: crate::RefStore = {
             let reflog = repo_config.reflog.unwrap_or(gix_ref::store::WriteReflog::Disable);
             let object_hash = repo_config.object_hash;
-            match &common_dir {
-                Some(common_dir) => crate::RefStore::for_linked_worktree(&git_dir, common_dir, reflog, object_hash),
-                None => crate::RefStore::at(&git_dir, reflog, object_hash),
+            match repo_config.ref_format {
+                gix_ref::store::Format::Files => match &common_dir {
+                    Some(common_dir) => {
+                        gix_ref::file::Store::for_linked_worktree(&git_dir, common_dir, reflog, object_hash).into()
+                    }
+                    None => gix_ref::file::Store::at(&git_dir, reflog, object_hash).into(),
+                },
+                gix_ref::store::Format::Reftable => match &common_dir {
+                    // Linked worktrees need their own stack for private references like `HEAD`, which isn't supported yet.
+                    Some(_) => return Err(Error::UnsupportedRefStorage { path: git_dir }),
+                    None => gix_ref::reftable::Store::at(&git_dir, reflog, object_hash).into(),
+                },
             }
         };
         let head = refs.find("HEAD").ok();
@@ -235,7 +243,27 @@ impl ThreadSafeRepository {
             None => {}
         }
 
-        refs.write_reflog = config::cache::util::reflog_or_default(config.reflog, worktree_dir.is_some());
+        refs.set_write_reflog(config::cache::util::reflog_or_default(
+            config.reflog,
+            worktree_dir.is_some(),
+        ));
+        refs.set_transaction_hook(Some(crate::hook::reference_transaction(
+            (git_dir_trust == gix_sec::Trust::Full)
+                .then(|| {
+                    crate::hook::Context::new(&config, &git_dir, common_dir_ref, worktree_dir.as_deref(), current_dir)
+                        .map_err(config::Error::PathInterpolation)
+                })
+                .transpose()?,
+            config
+                .resolved
+                .path_filter(
+                    "gitoxide",
+                    Some("objects".into()),
+                    gitoxide::Objects::QUARANTINE_PATH.name,
+                    &mut filter_config_section,
+                )
+                .is_some(),
+        )));
         let replacements = replacement_objects_refs_prefix(&config.resolved, lenient_config, filter_config_section)?
             .and_then(|prefix| {
                 let platform = refs.iter().ok()?;
@@ -263,6 +291,7 @@ impl ThreadSafeRepository {
                     object_hash: config.object_hash,
                     use_multi_pack_index: config.use_multi_pack_index,
                     current_dir: current_dir.to_owned().into(),
+                    pack_access: config.pack_access,
                 },
             )?),
             common_dir,
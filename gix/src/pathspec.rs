@@ -0,0 +1,202 @@
+//! Pathspec plumbing and abstractions
+use gix_odb::FindExt;
+pub use gix_pathspec::*;
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice},
+    Pathspec, Repository,
+};
+
+///
+pub mod init {
+    /// The error returned by [`Repository::pathspec()`][crate::Repository::pathspec()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Parse(#[from] gix_pathspec::parse::Error),
+        #[error(transparent)]
+        Normalize(#[from] gix_pathspec::normalize::Error),
+        #[error(transparent)]
+        MakeAttributes(#[from] crate::attributes::Error),
+    }
+}
+
+/// Lifecycle
+impl<'repo> Pathspec<'repo> {
+    /// Create a new instance by parsing `patterns` into [patterns][Pattern] to make them usable for searches.
+    /// `make_attributes` may be called if one of the patterns has a `(attr:a)` element which requires attribute matching. It should
+    /// be used to control where attributes are coming from.
+    /// If `inherit_ignore_case` is `true`, the `core.ignoreCase` configuration is taken into account to make all patterns
+    /// case-insensitive if it's set.
+    pub(crate) fn new(
+        repo: &'repo Repository,
+        patterns: impl IntoIterator<Item = impl AsRef<BStr>>,
+        inherit_ignore_case: bool,
+        make_attributes: impl FnOnce() -> Result<gix_worktree::Cache, crate::attributes::Error>,
+    ) -> Result<Self, init::Error> {
+        let prefix = repo
+            .prefix()
+            .and_then(Result::ok)
+            .map(|prefix| gix_path::to_unix_separators_on_windows(gix_path::into_bstr(prefix)).into_owned())
+            .unwrap_or_default();
+        let ignore_case = inherit_ignore_case && repo.config.ignore_case;
+        let patterns = patterns
+            .into_iter()
+            .map(|pattern| {
+                let mut pattern = gix_pathspec::parse(pattern.as_ref())?;
+                if ignore_case {
+                    pattern.signature |= MagicSignature::ICASE;
+                }
+                Ok(pattern)
+            })
+            .collect::<Result<Vec<_>, init::Error>>()?;
+        let needs_attributes = patterns.iter().any(|p| !p.attributes.is_empty());
+        let search = Search::from_specs(patterns, prefix.as_bstr())?;
+        let stack = needs_attributes.then(make_attributes).transpose()?;
+        Ok(Pathspec { repo, stack, search })
+    }
+
+    /// Turn ourselves into the functional parts for direct usage.
+    /// Note that the cache will only be set if one of the patterns requires attribute matching.
+    pub fn into_parts(self) -> (Search, Option<gix_worktree::Cache>) {
+        (self.search, self.stack)
+    }
+}
+
+/// Access
+impl<'repo> Pathspec<'repo> {
+    /// Return the attributes cache which is used when matching attributes in pathspecs, or `None` if none of the pathspecs require that.
+    pub fn attributes(&self) -> Option<&gix_worktree::Cache> {
+        self.stack.as_ref()
+    }
+
+    /// Return the search itself which can be used for matching paths or accessing the actual patterns that will be used.
+    pub fn search(&self) -> &Search {
+        &self.search
+    }
+
+    /// Return the first [`Match`][search::Match] of `relative_path`, or `None`.
+    /// Note that the match might [be excluded][search::Match::is_excluded()].
+    /// `is_dir` is true if `relative_path` is a directory.
+    pub fn pattern_matching_relative_path<'a>(
+        &mut self,
+        relative_path: impl Into<&'a BStr>,
+        is_dir: Option<bool>,
+    ) -> Option<search::Match<'_>> {
+        let repo = self.repo;
+        let stack = &mut self.stack;
+        self.search
+            .pattern_matching_relative_path(relative_path, is_dir, |relative_path, _case, is_dir, out| {
+                let stack = stack.as_mut().expect("initialized in advance");
+                stack
+                    .at_entry(relative_path, is_dir, |id, buf| repo.objects.find_blob(id, buf))
+                    .map_or(false, |platform| platform.matching_attributes(out))
+            })
+    }
+
+    /// The simplified version of [`pattern_matching_relative_path()`](Self::pattern_matching_relative_path()) which returns
+    /// `true` if `relative_path` is included in the set of positive pathspecs, while not being excluded.
+    pub fn is_included<'a>(&mut self, relative_path: impl Into<&'a BStr>, is_dir: Option<bool>) -> bool {
+        self.pattern_matching_relative_path(relative_path, is_dir)
+            .map_or(false, |m| !m.is_excluded())
+    }
+
+    /// Return an iterator over all entries along with their path in `index` which are included by this pathspec.
+    pub fn index_entries_with_paths<'s: 'repo, 'a: 'repo>(
+        &'s mut self,
+        index: &'a gix_index::State,
+    ) -> impl Iterator<Item = (&'a BStr, &'a gix_index::Entry)> + 'repo + 's {
+        let prefix = BString::from(self.search.common_prefix());
+        let entries = index.entries();
+        let start = entries.partition_point(|e| e.path(index) < prefix.as_bstr());
+        entries[start..]
+            .iter()
+            .map(move |entry| (entry.path(index), entry))
+            .take_while(move |(path, _)| path.starts_with(prefix.as_slice()))
+            .filter(move |(path, entry)| self.is_included(*path, Some(entry.mode.is_sparse())))
+    }
+
+    /// Return a new index with only those entries of `index` which are included by this pathspec, to limit
+    /// [`gix_worktree::status()`] or [`gix_worktree::checkout()`] to the matching paths.
+    ///
+    /// The timestamp of `index` is kept for racy-git detection, but none of its extensions are.
+    pub fn index_with_included_entries(&mut self, index: &gix_index::State) -> gix_index::State {
+        let mut out = gix_index::State::new(index.object_hash());
+        out.set_timestamp(index.timestamp());
+        let prefix = BString::from(self.search.common_prefix());
+        let entries = index.entries();
+        let start = entries.partition_point(|e| e.path(index) < prefix.as_bstr());
+        for entry in entries[start..]
+            .iter()
+            .take_while(|entry| entry.path(index).starts_with(prefix.as_slice()))
+        {
+            let path = entry.path(index);
+            if self.is_included(path, Some(entry.mode.is_sparse())) {
+                out.dangerously_push_entry(entry.stat, entry.id, entry.flags, entry.mode, path);
+            }
+        }
+        out
+    }
+
+    /// Return all non-tree entries of `tree` along with their path which are included by this pathspec.
+    ///
+    /// Trees that can't contain included entries aren't traversed.
+    pub fn tree_entries(
+        &mut self,
+        tree: &crate::Tree<'_>,
+    ) -> Result<Vec<gix_traverse::tree::recorder::Entry>, gix_traverse::tree::breadthfirst::Error> {
+        let mut delegate = TreeDelegate {
+            pathspec: self,
+            recorder: Default::default(),
+            records: Vec::new(),
+        };
+        tree.traverse().breadthfirst(&mut delegate)?;
+        Ok(delegate.records)
+    }
+}
+
+/// A tree traversal delegate to record all entries that are included by a pathspec.
+struct TreeDelegate<'a, 'repo> {
+    pathspec: &'a mut Pathspec<'repo>,
+    /// Used only for tracking the path.
+    recorder: gix_traverse::tree::Recorder,
+    records: Vec<gix_traverse::tree::recorder::Entry>,
+}
+
+impl<'a, 'repo> gix_traverse::tree::Visit for TreeDelegate<'a, 'repo> {
+    fn pop_front_tracked_path_and_set_current(&mut self) {
+        self.recorder.pop_front_tracked_path_and_set_current()
+    }
+
+    fn push_back_tracked_path_component(&mut self, component: &BStr) {
+        self.recorder.push_back_tracked_path_component(component)
+    }
+
+    fn push_path_component(&mut self, component: &BStr) {
+        self.recorder.push_path_component(component)
+    }
+
+    fn pop_path_component(&mut self) {
+        self.recorder.pop_path_component()
+    }
+
+    fn visit_tree(&mut self, _entry: &gix_object::tree::EntryRef<'_>) -> gix_traverse::tree::visit::Action {
+        if self.pathspec.search.can_match_directory(self.recorder.path()) {
+            gix_traverse::tree::visit::Action::Continue
+        } else {
+            gix_traverse::tree::visit::Action::Skip
+        }
+    }
+
+    fn visit_nontree(&mut self, entry: &gix_object::tree::EntryRef<'_>) -> gix_traverse::tree::visit::Action {
+        if self.pathspec.is_included(self.recorder.path(), Some(false)) {
+            self.records.push(gix_traverse::tree::recorder::Entry {
+                mode: entry.mode,
+                filepath: self.recorder.path_clone(),
+                oid: entry.oid.to_owned(),
+            });
+        }
+        gix_traverse::tree::visit::Action::Continue
+    }
+}
@@ -8,9 +8,9 @@ pub mod edit {
     #[allow(missing_docs)]
     pub enum Error {
         #[error(transparent)]
-        FileTransactionPrepare(#[from] gix_ref::file::transaction::prepare::Error),
+        TransactionPrepare(#[from] gix_ref::store::transaction::prepare::Error),
         #[error(transparent)]
-        FileTransactionCommit(#[from] gix_ref::file::transaction::commit::Error),
+        TransactionCommit(#[from] gix_ref::store::transaction::commit::Error),
         #[error(transparent)]
         NameValidation(#[from] gix_validate::reference::name::Error),
         #[error("Could not interpret core.filesRefLockTimeout or core.packedRefsTimeout, it must be the number in milliseconds to wait for locks or negative to wait forever")]
@@ -20,6 +20,68 @@ pub mod edit {
     }
 }
 
+///
+pub mod rename {
+    use crate::config;
+
+    /// The error returned by [rename_reference(…)][crate::Repository::rename_reference()] and
+    /// [copy_reference(…)][crate::Repository::copy_reference()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Rename(#[from] gix_ref::store::rename::Error),
+        #[error(transparent)]
+        NameValidation(#[from] gix_validate::reference::name::Error),
+        #[error("Could not interpret core.filesRefLockTimeout or core.packedRefsTimeout, it must be the number in milliseconds to wait for locks or negative to wait forever")]
+        LockTimeoutConfiguration(#[from] config::lock_timeout::Error),
+        #[error(transparent)]
+        ParseCommitterTime(#[from] crate::config::time::Error),
+        #[error(transparent)]
+        BranchConfig(#[from] super::branch_config::Error),
+    }
+}
+
+///
+pub mod branch_config {
+    use crate::config;
+
+    /// The error returned when the `branch.<name>` configuration couldn't be changed, for example when renaming a branch.
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not interpret core.filesRefLockTimeout or core.packedRefsTimeout, it must be the number in milliseconds to wait for locks or negative to wait forever")]
+        LockTimeoutConfiguration(#[from] config::lock_timeout::Error),
+        #[error("The local configuration file could not be locked to update the branch configuration")]
+        LockConfig(#[from] gix_lock::acquire::Error),
+        #[error("The local configuration file could not be read to update the branch configuration")]
+        ReadConfig(#[from] gix_config::file::init::from_paths::Error),
+        #[error("The branch configuration could not be changed")]
+        SetValue(#[from] gix_config::file::set_raw_value::Error),
+        #[error("The branch configuration could not be changed")]
+        SectionHeader(#[from] gix_config::parse::section::header::Error),
+        #[error("The local configuration file could not be written")]
+        WriteConfig(#[from] std::io::Error),
+        #[error("The local configuration file could not be written")]
+        CommitConfig(#[from] gix_lock::commit::Error<gix_lock::File>),
+        #[error("The updated configuration could not be applied to the repository")]
+        ApplyConfig(#[from] config::Error),
+    }
+}
+
+///
+pub mod set_namespace {
+    /// The error returned by [set_namespace(…)][crate::Repository::set_namespace()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        NameValidation(#[from] gix_validate::refname::Error),
+        #[error(transparent)]
+        Unsupported(#[from] gix_ref::store::set_namespace::Error),
+    }
+}
+
 ///
 pub mod peel {
     /// The error returned by [Reference::peel_to_id_in_place(…)][crate::Reference::peel_to_id_in_place()] and
@@ -82,7 +144,7 @@ pub mod find {
     #[allow(missing_docs)]
     pub enum Error {
         #[error(transparent)]
-        Find(#[from] gix_ref::file::find::Error),
+        Find(#[from] gix_ref::store::find::Error),
         #[error(transparent)]
         PackedRefsOpen(#[from] gix_ref::packed::buffer::open::Error),
     }
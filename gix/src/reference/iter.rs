@@ -2,24 +2,23 @@
 use std::path::Path;
 
 use gix_odb::pack::Find;
-use gix_ref::file::ReferenceExt;
 
 /// A platform to create iterators over references.
 #[must_use = "Iterators should be obtained from this iterator platform"]
 pub struct Platform<'r> {
-    pub(crate) platform: gix_ref::file::iter::Platform<'r>,
+    pub(crate) platform: gix_ref::store::iter::Platform<'r>,
     pub(crate) repo: &'r crate::Repository,
 }
 
 /// An iterator over references, with or without filter.
 pub struct Iter<'r> {
-    inner: gix_ref::file::iter::LooseThenPacked<'r, 'r>,
+    inner: gix_ref::store::iter::Iter<'r>,
     peel: bool,
     repo: &'r crate::Repository,
 }
 
 impl<'r> Iter<'r> {
-    fn new(repo: &'r crate::Repository, platform: gix_ref::file::iter::LooseThenPacked<'r, 'r>) -> Self {
+    fn new(repo: &'r crate::Repository, platform: gix_ref::store::iter::Iter<'r>) -> Self {
         Iter {
             inner: platform,
             peel: false,
@@ -95,14 +94,16 @@ impl<'r> Iterator for Iter<'r> {
                 .and_then(|mut r| {
                     if self.peel {
                         let handle = &self.repo;
-                        r.peel_to_id_in_place(&handle.refs, |oid, buf| {
-                            handle
-                                .objects
-                                .try_find(oid, buf)
-                                .map(|po| po.map(|(o, _l)| (o.kind, o.data)))
-                        })
-                        .map_err(|err| Box::new(err) as Box<dyn std::error::Error + Send + Sync + 'static>)
-                        .map(|_| r)
+                        handle
+                            .refs
+                            .peel_to_id_in_place(&mut r, |oid, buf| {
+                                handle
+                                    .objects
+                                    .try_find(oid, buf)
+                                    .map(|po| po.map(|(o, _l)| (o.kind, o.data)))
+                            })
+                            .map_err(|err| Box::new(err) as Box<dyn std::error::Error + Send + Sync + 'static>)
+                            .map(|_| r)
                     } else {
                         Ok(r)
                     }
@@ -120,6 +121,8 @@ pub mod init {
     pub enum Error {
         #[error(transparent)]
         Io(#[from] std::io::Error),
+        #[error(transparent)]
+        Store(#[from] gix_ref::store::iter::init::Error),
     }
 }
 
@@ -1,6 +1,5 @@
 //!
 use gix_object::commit::MessageRef;
-use gix_ref::file::ReferenceExt;
 
 use crate::{
     bstr::{BStr, BString, ByteVec},
@@ -9,8 +8,8 @@ use crate::{
 
 impl<'repo> Reference<'repo> {
     /// Return a platform for obtaining iterators over reference logs.
-    pub fn log_iter(&self) -> gix_ref::file::log::iter::Platform<'_, '_> {
-        self.inner.log_iter(&self.repo.refs)
+    pub fn log_iter(&self) -> gix_ref::store::log::Platform<'_, '_> {
+        self.repo.refs.log_iter(self.inner.name.as_ref())
     }
 }
 
@@ -1,6 +1,9 @@
 use super::Error;
 use crate::{
-    config::{cache::util::ApplyLeniency, tree::Pack},
+    config::{
+        cache::util::ApplyLeniency,
+        tree::{gitoxide, Fetch, Pack},
+    },
     Repository,
 };
 
@@ -14,6 +17,57 @@ pub fn index_threads(repo: &Repository) -> Result<Option<usize>, Error> {
         .with_leniency(repo.options.lenient_config)?)
 }
 
+pub fn pack_write_reverse_index(repo: &Repository) -> Result<bool, Error> {
+    Ok(repo
+        .config
+        .resolved
+        .boolean_filter(
+            "pack",
+            None,
+            Pack::WRITE_REVERSE_INDEX.name,
+            &mut repo.filter_config_section(),
+        )
+        .map(|value| Pack::WRITE_REVERSE_INDEX.enrich_error(value))
+        .transpose()
+        .with_leniency(repo.options.lenient_config)
+        .map_err(Error::PackWriteReverseIndex)?
+        .unwrap_or(false))
+}
+
+pub fn write_commit_graph(repo: &Repository) -> Result<bool, Error> {
+    Ok(repo
+        .config
+        .resolved
+        .boolean_filter(
+            "fetch",
+            None,
+            Fetch::WRITE_COMMIT_GRAPH.name,
+            &mut repo.filter_config_section(),
+        )
+        .map(|value| Fetch::WRITE_COMMIT_GRAPH.enrich_error(value))
+        .transpose()
+        .with_leniency(repo.options.lenient_config)
+        .map_err(Error::WriteCommitGraphConfig)?
+        .unwrap_or(false))
+}
+
+pub fn pack_refs_threshold(repo: &Repository) -> Result<Option<usize>, Error> {
+    Ok(repo
+        .config
+        .resolved
+        .integer_filter(
+            "gitoxide",
+            Some("fetch".into()),
+            gitoxide::Fetch::PACK_REFS_THRESHOLD.name,
+            &mut repo.filter_config_section(),
+        )
+        .map(|value| gitoxide::Fetch::PACK_REFS_THRESHOLD.try_into_usize(value))
+        .transpose()
+        .with_leniency(repo.options.lenient_config)
+        .map_err(Error::PackRefsThresholdConfig)?
+        .filter(|threshold| *threshold != 0))
+}
+
 pub fn pack_index_version(repo: &Repository) -> Result<gix_pack::index::Version, Error> {
     Ok(repo
         .config
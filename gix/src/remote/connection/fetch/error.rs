@@ -8,6 +8,20 @@ pub enum Error {
     PackThreads(#[from] config::unsigned_integer::Error),
     #[error("The value to configure the pack index version should be 1 or 2")]
     PackIndexVersion(#[from] config::key::GenericError),
+    #[error("The value of `pack.writeReverseIndex` could not be interpreted")]
+    PackWriteReverseIndex(#[source] config::boolean::Error),
+    #[error("The value of `fetch.writeCommitGraph` could not be interpreted")]
+    WriteCommitGraphConfig(#[source] config::boolean::Error),
+    #[error("The value of `commitGraph.generationVersion` could not be interpreted")]
+    CommitGraphGenerationVersion(#[source] config::unsigned_integer::Error),
+    #[error("Could not write the commit-graph after fetching as configured by `fetch.writeCommitGraph`")]
+    WriteCommitGraph(#[from] crate::maintenance::commit_graph::Error),
+    #[error("The value of `gitoxide.fetch.packRefsThreshold` could not be interpreted")]
+    PackRefsThresholdConfig(#[source] config::unsigned_integer::Error),
+    #[error("Could not count the loose references to see if they should be packed after fetching")]
+    CountLooseReferences(#[source] std::io::Error),
+    #[error("Could not pack references after fetching as configured by `gitoxide.fetch.packRefsThreshold`")]
+    PackRefs(#[from] crate::maintenance::pack_refs::Error),
     #[error("Could not decode server reply")]
     FetchResponse(#[from] gix_protocol::fetch::response::Error),
     #[error("Cannot fetch from a remote that uses {remote} while local repository uses {local} for object hashes")]
@@ -209,6 +209,12 @@ where
         self
     }
 
+    /// Assume the reference store is empty and write all ref updates straight into a new packed-refs file, which is what a clone does.
+    pub(crate) fn with_initial_ref_transaction(mut self) -> Self {
+        self.write_packed_refs = WritePackedRefs::Initial;
+        self
+    }
+
     /// Set the reflog message to use when updating refs after fetching a pack.
     pub fn with_reflog_message(mut self, reflog_message: RefLogMessage) -> Self {
         self.reflog_message = reflog_message.into();
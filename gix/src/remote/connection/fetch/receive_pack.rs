@@ -62,6 +62,8 @@ where
     /// ### Configuration
     ///
     /// - `gitoxide.userAgent` is read to obtain the application user agent for git servers and for HTTP servers as well.
+    /// - `fetch.writeCommitGraph` causes a split commit-graph to be written for all references after a pack was received,
+    ///   using `commitGraph.generationVersion`.
     ///
     #[gix_protocol::maybe_async::maybe_async]
     pub async fn receive<P>(mut self, mut progress: P, should_interrupt: &AtomicBool) -> Result<Outcome, Error>
@@ -186,6 +188,7 @@ where
             index_version: config::pack_index_version(repo)?,
             iteration_mode: gix_pack::data::input::Mode::Verify,
             object_hash: con.remote.repo.object_hash(),
+            write_reverse_index: config::pack_write_reverse_index(repo)?,
         };
 
         let mut write_pack_bundle = if matches!(self.dry_run, fetch::DryRun::No) {
@@ -241,6 +244,31 @@ where
                     std::fs::remove_file(&path).map_err(|err| Error::RemovePackKeepFile { path, source: err })?;
                 }
             }
+            if bundle.index.num_objects != 0 && !repo.is_shallow() && config::write_commit_graph(repo)? {
+                let options = gix_commitgraph::graph::write::Options {
+                    split: Some(Default::default()),
+                    ..repo
+                        .commit_graph_options()
+                        .map_err(Error::CommitGraphGenerationVersion)?
+                };
+                repo.write_commit_graph_for_references(crate::progress::Discard, should_interrupt, options)?;
+            }
+        }
+
+        // Updates are written as loose references only if they aren't written to packed-refs directly.
+        if write_pack_bundle.is_some()
+            && !update_refs.edits.is_empty()
+            && self.write_packed_refs == fetch::WritePackedRefs::Never
+        {
+            if let Some((threshold, store)) = config::pack_refs_threshold(repo)?.zip(repo.refs.as_file()) {
+                let loose_refs = store.loose_iter().map_err(Error::CountLooseReferences)?.count();
+                if loose_refs >= threshold {
+                    repo.pack_refs(crate::maintenance::pack_refs::Options {
+                        filter: crate::maintenance::pack_refs::Filter::All,
+                        prune: true,
+                    })?;
+                }
+            }
         }
 
         Ok(Outcome {
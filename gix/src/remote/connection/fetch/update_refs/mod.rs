@@ -56,6 +56,11 @@ pub(crate) fn update(
     let mut edits = Vec::new();
     let mut updates = Vec::new();
 
+    let assume_empty_store = matches!(write_packed_refs, fetch::WritePackedRefs::Initial);
+    let checked_out_branches = repo.worktree_branches().map_err(|err| match err {
+        crate::branch::worktrees::Error::List(err) => update::Error::WorktreeListing(err),
+        crate::branch::worktrees::Error::Open(err) => update::Error::OpenWorktreeRepo(err),
+    })?;
     let implicit_tag_refspec = fetch_tags
         .to_refspec()
         .filter(|_| matches!(fetch_tags, crate::remote::fetch::Tags::Included));
@@ -88,10 +93,14 @@ pub(crate) fn update(
             updates.push(update);
             continue;
         }
-        let checked_out_branches = worktree_branches(repo)?;
         let (mode, edit_index) = match local {
             Some(name) => {
-                let (mode, reflog_message, name, previous_value) = match repo.try_find_reference(name)? {
+                let existing = if assume_empty_store {
+                    None
+                } else {
+                    repo.try_find_reference(name)?
+                };
+                let (mode, reflog_message, name, previous_value) = match existing {
                     Some(existing) => {
                         if let Some(wt_dir) = checked_out_branches.get(existing.name()) {
                             let mode = update::Mode::RejectedCurrentlyCheckedOut {
@@ -242,6 +251,13 @@ pub(crate) fn update(
                                     .map(|obj| obj.map(|obj| obj.kind))
                                     .map_err(|err| Box::new(err) as Box<dyn std::error::Error + Send + Sync + 'static>)
                             }))},
+                        fetch::WritePackedRefs::Initial => {
+                            gix_ref::file::transaction::PackedRefs::Initial(Box::new(|oid, buf| {
+                                repo.objects
+                                    .try_find(oid, buf)
+                                    .map(|obj| obj.map(|obj| obj.kind))
+                                    .map_err(|err| Box::new(err) as Box<dyn std::error::Error + Send + Sync + 'static>)
+                            }))},
                         fetch::WritePackedRefs::Never => gix_ref::file::transaction::PackedRefs::DeletionsOnly
                     }
                 )
@@ -256,19 +272,5 @@ pub(crate) fn update(
     Ok(update::Outcome { edits, updates })
 }
 
-fn worktree_branches(repo: &Repository) -> Result<BTreeMap<gix_ref::FullName, PathBuf>, update::Error> {
-    let mut map = BTreeMap::new();
-    if let Some((wt_dir, head_ref)) = repo.work_dir().zip(repo.head_ref().ok().flatten()) {
-        map.insert(head_ref.inner.name, wt_dir.to_owned());
-    }
-    for proxy in repo.worktrees()? {
-        let repo = proxy.into_repo_with_possibly_inaccessible_worktree()?;
-        if let Some((wt_dir, head_ref)) = repo.work_dir().zip(repo.head_ref().ok().flatten()) {
-            map.insert(head_ref.inner.name, wt_dir.to_owned());
-        }
-    }
-    Ok(map)
-}
-
 #[cfg(test)]
 mod tests;
@@ -16,6 +16,9 @@ pub(crate) enum WritePackedRefs {
     Never,
     /// Put ref updates straight into the `packed-refs` file, without creating loose refs first or dealing with them in any way.
     Only,
+    /// Like [`Only`][WritePackedRefs::Only], but assume the reference store is empty, as is the case when cloning,
+    /// which allows to write the `packed-refs` file directly without looking up existing references.
+    Initial,
 }
 
 /// Describe how to handle tags when fetching
@@ -47,4 +47,36 @@ impl Repository {
             attribute_list,
         ))
     }
+
+    /// Like [`attributes()`][Self::attributes()], but read attributes the way `git` does when acting on the current checkout,
+    /// that is from the worktree if there is one and from the index otherwise, with a missing index treated as empty.
+    pub(crate) fn checkout_attributes(&self) -> Result<gix_worktree::Cache, crate::worktree::attributes::Error> {
+        let index = match self.index() {
+            Ok(index) => Some(index),
+            Err(crate::worktree::open_index::Error::IndexFile(gix_index::file::init::Error::Io(err)))
+                if err.kind() == std::io::ErrorKind::NotFound =>
+            {
+                None
+            }
+            Err(err) => return Err(err.into()),
+        };
+        let empty_index;
+        let index = match index.as_ref() {
+            Some(index) => &***index,
+            None => {
+                empty_index = gix_index::State::new(self.object_hash());
+                &empty_index
+            }
+        };
+        Ok(self.attributes(
+            index,
+            if self.work_dir().is_some() {
+                gix_worktree::cache::state::attributes::Source::WorktreeThenIdMapping
+            } else {
+                gix_worktree::cache::state::attributes::Source::IdMapping
+            },
+            gix_worktree::cache::state::ignore::Source::IdMapping,
+            None,
+        )?)
+    }
 }
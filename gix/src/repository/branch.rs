@@ -0,0 +1,424 @@
+#![allow(clippy::result_large_err)]
+use std::{
+    collections::{BTreeMap, BinaryHeap},
+    convert::TryFrom,
+    path::PathBuf,
+};
+
+use gix_hash::ObjectId;
+use gix_ref::{
+    transaction::{Change, PreviousValue, RefEdit, RefLog},
+    FullName, FullNameRef,
+};
+
+use super::reference::branch_section_ids;
+use crate::{
+    branch,
+    bstr::{BStr, BString, ByteVec},
+    reference, remote, Reference,
+};
+
+/// Create, delete and inspect local branches.
+impl crate::Repository {
+    /// Create the branch `refs/heads/<short_name>` pointing to the commit that `start` resolves to, like `main` or `HEAD~1`,
+    /// and return it.
+    ///
+    /// If the branch exists already it's an error, unless `force` is `true` in which case it will be reset to `start` as long
+    /// as it isn't checked out in any worktree. As with `git branch`, the upstream of the new branch isn't configured, use
+    /// [`branch_set_upstream()`][Self::branch_set_upstream()] for that.
+    pub fn branch_create<'a, 'b>(
+        &self,
+        short_name: impl Into<&'a BStr>,
+        start: impl Into<&'b BStr>,
+        force: bool,
+    ) -> Result<Reference<'_>, branch::create::Error> {
+        let short_name = short_name.into();
+        let start = start.into();
+        let name = branch_name(short_name)?;
+        let id = self
+            .rev_parse_single(start)?
+            .object()?
+            .peel_to_kind(gix_object::Kind::Commit)
+            .map_err(|source| branch::create::Error::NotACommit {
+                spec: start.to_owned(),
+                source,
+            })?
+            .id;
+
+        let (constraint, message) = match self.try_find_reference(&name)? {
+            Some(existing) => {
+                if !force {
+                    return Err(branch::create::Error::AlreadyExists {
+                        name: short_name.to_owned(),
+                    });
+                }
+                if let Some(worktree_dir) = self.worktree_branches()?.remove(&name) {
+                    return Err(branch::create::Error::CheckedOut {
+                        name: short_name.to_owned(),
+                        worktree_dir,
+                    });
+                }
+                (
+                    PreviousValue::MustExistAndMatch(existing.inner.target),
+                    format!("branch: Reset to {start}"),
+                )
+            }
+            None => (PreviousValue::MustNotExist, format!("branch: Created from {start}")),
+        };
+        Ok(self.reference(name, id, constraint, message)?)
+    }
+
+    /// Delete the branch `refs/heads/<short_name>` along with its reflog and its `branch.<short_name>` configuration,
+    /// and return the id it pointed to.
+    ///
+    /// Branches which are checked out in any worktree can't be deleted. Unless `force` is `true`, the branch must also be
+    /// fully merged into its upstream, or into `HEAD` if it has no upstream, just like `git branch -d` requires.
+    pub fn branch_delete<'a>(
+        &mut self,
+        short_name: impl Into<&'a BStr>,
+        force: bool,
+    ) -> Result<ObjectId, branch::delete::Error> {
+        let short_name = short_name.into();
+        let name = branch_name(short_name)?;
+        let mut branch = self.find_reference(&name)?;
+        if let Some(worktree_dir) = self.worktree_branches()?.remove(&name) {
+            return Err(branch::delete::Error::CheckedOut {
+                name: short_name.to_owned(),
+                worktree_dir,
+            });
+        }
+        let id = branch.peel_to_id_in_place()?.detach();
+        if !force {
+            let base = match self.branch_upstream_id(short_name)? {
+                Some(id) => Some(id),
+                None => match self.head_id() {
+                    Ok(id) => Some(id.detach()),
+                    Err(reference::head_id::Error::Unborn { .. }) => None,
+                    Err(err) => return Err(err.into()),
+                },
+            };
+            let is_merged = match base {
+                Some(base) => self.ahead_behind(id, base)?.0 == 0,
+                None => false,
+            };
+            if !is_merged {
+                return Err(branch::delete::Error::NotFullyMerged {
+                    name: short_name.to_owned(),
+                });
+            }
+        }
+
+        let expected = PreviousValue::MustExistAndMatch(branch.inner.target);
+        self.edit_reference(RefEdit {
+            change: Change::Delete {
+                expected,
+                log: RefLog::AndReference,
+            },
+            name,
+            deref: false,
+        })?;
+        self.edit_branch_config(&[short_name], |config| {
+            let ids = branch_section_ids(config, short_name, |_| true);
+            for id in &ids {
+                config.remove_section_by_id(*id);
+            }
+            Ok(!ids.is_empty())
+        })?;
+        Ok(id)
+    }
+
+    /// Return the upstream of the branch `refs/heads/<short_name>` as configured in `branch.<short_name>.remote` and
+    /// `branch.<short_name>.merge`, or `None` if one of these isn't set.
+    ///
+    /// The [local tracking branch][branch::Upstream::tracking] is obtained by mapping `branch.<short_name>.merge` through
+    /// the fetch refspecs of the remote.
+    pub fn branch_upstream<'a>(
+        &self,
+        short_name: impl Into<&'a BStr>,
+    ) -> Result<Option<branch::Upstream>, branch::upstream::Error> {
+        let short_name = short_name.into();
+        let merge = match self.branch_remote_ref(short_name) {
+            Some(merge) => merge?.into_owned(),
+            None => return Ok(None),
+        };
+        let remote = match self.branch_remote_name(short_name) {
+            Some(remote) => remote::Name::from(remote.as_bstr().to_owned()),
+            None => return Ok(None),
+        };
+        let tracking = match &remote {
+            remote::Name::Url(url) if url.as_ref() == "." => Some(merge.clone()),
+            remote::Name::Url(_) => None,
+            remote::Name::Symbol(name) => {
+                let remote = self.find_remote(name.as_ref())?;
+                let null = ObjectId::null(self.object_hash());
+                let mappings = gix_refspec::MatchGroup::from_fetch_specs(
+                    remote
+                        .refspecs(remote::Direction::Fetch)
+                        .iter()
+                        .map(|spec| spec.to_ref()),
+                )
+                .match_remotes(std::iter::once(gix_refspec::match_group::Item {
+                    full_ref_name: merge.as_bstr(),
+                    target: &null,
+                    object: None,
+                }))
+                .mappings;
+                mappings
+                    .into_iter()
+                    .find_map(|mapping| mapping.rhs)
+                    .and_then(|rhs| FullName::try_from(rhs.into_owned()).ok())
+            }
+        };
+        Ok(Some(branch::Upstream {
+            remote,
+            merge,
+            tracking,
+        }))
+    }
+
+    /// Configure the upstream of the branch `refs/heads/<short_name>` to be `merge`, like `refs/heads/main`, on `remote`,
+    /// which is the name or URL of a remote or `.` for a local branch.
+    ///
+    /// The values are written into `branch.<short_name>.remote` and `branch.<short_name>.merge` of the repository-local
+    /// configuration file, and are applied to the configuration of this instance as well.
+    pub fn branch_set_upstream<'a>(
+        &mut self,
+        short_name: impl Into<&'a BStr>,
+        remote: impl AsRef<BStr>,
+        merge: &FullNameRef,
+    ) -> Result<(), branch::upstream::set::Error> {
+        let short_name = short_name.into();
+        self.require_branch(short_name)?;
+        let remote = remote.as_ref();
+        self.edit_branch_config(&[short_name], |config| {
+            config.set_raw_value("branch", Some(short_name), "remote", remote)?;
+            config.set_raw_value("branch", Some(short_name), "merge", merge.as_bstr())?;
+            Ok(true)
+        })?;
+        Ok(())
+    }
+
+    /// Remove the upstream configuration of the branch `refs/heads/<short_name>`, which are `branch.<short_name>.remote`
+    /// and `branch.<short_name>.merge` in the repository-local configuration file.
+    pub fn branch_unset_upstream<'a>(
+        &mut self,
+        short_name: impl Into<&'a BStr>,
+    ) -> Result<(), branch::upstream::set::Error> {
+        let short_name = short_name.into();
+        self.require_branch(short_name)?;
+        self.edit_branch_config(&[short_name], |config| {
+            let ids = branch_section_ids(config, short_name, |_| true);
+            for id in &ids {
+                if let Some(mut section) = config.section_mut_by_id(*id) {
+                    while section.remove("remote").is_some() {}
+                    while section.remove("merge").is_some() {}
+                }
+            }
+            Ok(!ids.is_empty())
+        })?;
+        Ok(())
+    }
+
+    /// Return the amount of commits the branch `refs/heads/<short_name>` is ahead and behind of the local tracking branch
+    /// of its [upstream][Self::branch_upstream()], like `git status` does.
+    ///
+    /// Return `None` if the branch doesn't exist, or if its upstream isn't configured or wasn't fetched yet.
+    pub fn branch_ahead_behind<'a>(
+        &self,
+        short_name: impl Into<&'a BStr>,
+    ) -> Result<Option<(usize, usize)>, branch::ahead_behind::Error> {
+        let short_name = short_name.into();
+        let tip = match self.try_find_reference(&branch_name(short_name)?)? {
+            Some(mut branch) => branch.peel_to_id_in_place()?.detach(),
+            None => return Ok(None),
+        };
+        let upstream = match self.branch_upstream_id(short_name)? {
+            Some(id) => id,
+            None => return Ok(None),
+        };
+        if tip == upstream {
+            return Ok(Some((0, 0)));
+        }
+        Ok(Some(self.ahead_behind(tip, upstream)?))
+    }
+
+    /// Return information about all local branches, sorted by name, including their upstream and how many commits
+    /// they are ahead and behind of it.
+    pub fn branches(&self) -> Result<Vec<branch::Info>, branch::list::Error> {
+        let worktree_branches = self.worktree_branches()?;
+        let mut out = Vec::new();
+        for branch in self.references()?.local_branches()? {
+            let mut branch = branch.map_err(branch::list::Error::ReferencesIter)?;
+            let id = branch.peel_to_id_in_place().ok().map(crate::Id::detach);
+            let name = branch.inner.name;
+            let short_name = name.shorten();
+            let upstream = self.branch_upstream(short_name)?;
+            let ahead_behind = match upstream {
+                Some(_) => self.branch_ahead_behind(short_name)?,
+                None => None,
+            };
+            out.push(branch::Info {
+                worktree_dir: worktree_branches.get(&name).cloned(),
+                name,
+                id,
+                upstream,
+                ahead_behind,
+            });
+        }
+        Ok(out)
+    }
+}
+
+impl crate::Repository {
+    /// Return the branches checked out in the main worktree and all linked worktrees, along with the worktree directory.
+    ///
+    /// This works the same no matter which worktree this instance belongs to.
+    pub(crate) fn worktree_branches(&self) -> Result<BTreeMap<FullName, PathBuf>, branch::worktrees::Error> {
+        let mut map = BTreeMap::new();
+        let main_repo;
+        let main = if self.git_dir() == self.common_dir() {
+            self
+        } else {
+            main_repo = self.main_repo()?;
+            &main_repo
+        };
+        if let Some((wt_dir, head_ref)) = main.work_dir().zip(main.head_ref().ok().flatten()) {
+            map.insert(head_ref.inner.name, wt_dir.to_owned());
+        }
+        for proxy in main.worktrees()? {
+            let repo = proxy.into_repo_with_possibly_inaccessible_worktree()?;
+            if let Some((wt_dir, head_ref)) = repo.work_dir().zip(repo.head_ref().ok().flatten()) {
+                map.insert(head_ref.inner.name, wt_dir.to_owned());
+            }
+        }
+        Ok(map)
+    }
+
+    /// Return the id of the local tracking branch of the upstream of `short_name`, if it is configured and exists.
+    fn branch_upstream_id(&self, short_name: &BStr) -> Result<Option<ObjectId>, branch::ahead_behind::Error> {
+        let tracking = match self.branch_upstream(short_name)?.and_then(|upstream| upstream.tracking) {
+            Some(tracking) => tracking,
+            None => return Ok(None),
+        };
+        Ok(match self.try_find_reference(&tracking)? {
+            Some(mut tracking) => Some(tracking.peel_to_id_in_place()?.detach()),
+            None => None,
+        })
+    }
+
+    /// Return the amount of commits reachable from `ours` but not from `theirs`, and the amount of commits reachable from
+    /// `theirs` but not from `ours`.
+    ///
+    /// Commits are visited by descending generation as stored in the commit-graph, or by descending commit time for commits
+    /// that aren't in it, and the walk stops as soon as all commits left to visit are reachable from both tips, which is
+    /// at their merge-base.
+    fn ahead_behind(&self, ours: ObjectId, theirs: ObjectId) -> Result<(usize, usize), branch::reachable::Error> {
+        const OURS: u8 = 1;
+        const THEIRS: u8 = 1 << 1;
+        const BOTH: u8 = OURS | THEIRS;
+        const QUEUED: u8 = 1 << 2;
+
+        let graph = self.commit_graph().ok();
+        let mut flags = gix_hashtable::HashMap::<ObjectId, u8>::default();
+        let mut queue = BinaryHeap::<(u32, u64, ObjectId)>::new();
+        let mut num_queued_not_in_both = 0;
+        let enqueue = |id: ObjectId,
+                       new_flags: u8,
+                       flags: &mut gix_hashtable::HashMap<ObjectId, u8>,
+                       queue: &mut BinaryHeap<(u32, u64, ObjectId)>,
+                       num_queued_not_in_both: &mut usize|
+         -> Result<(), branch::reachable::Error> {
+            let entry = flags.entry(id).or_default();
+            let old = *entry;
+            let merged = old | new_flags;
+            if merged == old {
+                return Ok(());
+            }
+            if old & QUEUED != 0 {
+                if merged & BOTH == BOTH {
+                    *num_queued_not_in_both -= 1;
+                }
+                *entry = merged;
+                return Ok(());
+            }
+            *entry = merged | QUEUED;
+            if merged & BOTH != BOTH {
+                *num_queued_not_in_both += 1;
+            }
+            let (generation, time) = match graph.as_ref().and_then(|graph| graph.commit_by_id(id)) {
+                Some(commit) => (commit.generation(), commit.committer_timestamp()),
+                None => (
+                    gix_commitgraph::GENERATION_NUMBER_INFINITY,
+                    self.find_object(id)?
+                        .to_commit_ref_iter()
+                        .committer()?
+                        .time
+                        .seconds_since_unix_epoch as u64,
+                ),
+            };
+            queue.push((generation, time, id));
+            Ok(())
+        };
+        enqueue(ours, OURS, &mut flags, &mut queue, &mut num_queued_not_in_both)?;
+        enqueue(theirs, THEIRS, &mut flags, &mut queue, &mut num_queued_not_in_both)?;
+
+        let mut parents = Vec::new();
+        while num_queued_not_in_both != 0 {
+            let id = match queue.pop() {
+                Some((_, _, id)) => id,
+                None => break,
+            };
+            let entry = flags.get_mut(&id).expect("queued commits have flags");
+            *entry &= !QUEUED;
+            let commit_flags = *entry;
+            if commit_flags & BOTH != BOTH {
+                num_queued_not_in_both -= 1;
+            }
+
+            parents.clear();
+            match graph
+                .as_ref()
+                .and_then(|graph| graph.commit_by_id(id).map(|commit| (graph, commit)))
+            {
+                Some((graph, commit)) => {
+                    for pos in commit.iter_parents() {
+                        parents.push(graph.id_at(pos?).to_owned());
+                    }
+                }
+                None => parents.extend(self.find_object(id)?.to_commit_ref_iter().parent_ids()),
+            }
+            for parent in parents.drain(..) {
+                enqueue(
+                    parent,
+                    commit_flags,
+                    &mut flags,
+                    &mut queue,
+                    &mut num_queued_not_in_both,
+                )?;
+            }
+        }
+
+        Ok(flags
+            .values()
+            .fold((0, 0), |(ahead, behind), flags| match flags & BOTH {
+                OURS => (ahead + 1, behind),
+                THEIRS => (ahead, behind + 1),
+                _ => (ahead, behind),
+            }))
+    }
+
+    fn require_branch(&self, short_name: &BStr) -> Result<(), branch::upstream::set::Error> {
+        match self.try_find_reference(&branch_name(short_name)?)? {
+            Some(_) => Ok(()),
+            None => Err(branch::upstream::set::Error::NotFound {
+                name: short_name.to_owned(),
+            }),
+        }
+    }
+}
+
+fn branch_name(short_name: &BStr) -> Result<FullName, gix_validate::reference::name::Error> {
+    let mut name = BString::from("refs/heads/");
+    name.push_str(short_name);
+    FullName::try_from(name)
+}
@@ -0,0 +1,61 @@
+use std::{ffi::OsStr, process::ExitStatus};
+
+use crate::hook;
+
+impl crate::Repository {
+    /// Run the hook with `name`, like `pre-commit`, with `args` and `stdin` as its standard input, and return its exit status,
+    /// or `None` if the hook doesn't exist or isn't executable.
+    ///
+    /// Hooks are never run in repositories which aren't [fully trusted][gix_sec::Trust::Full], which is also signalled by
+    /// returning `None`.
+    pub fn run_hook(
+        &self,
+        name: &str,
+        args: impl IntoIterator<Item = impl AsRef<OsStr>>,
+        stdin: &[u8],
+    ) -> Result<Option<ExitStatus>, hook::run::Error> {
+        if self.git_dir_trust() != gix_sec::Trust::Full {
+            return Ok(None);
+        }
+        let current_dir = match &self.options.current_dir {
+            Some(dir) => dir.clone(),
+            None => std::env::current_dir().map_err(hook::run::Error::CurrentDir)?,
+        };
+        let ctx = hook::Context::new(
+            &self.config,
+            self.git_dir(),
+            self.common_dir(),
+            self.work_dir(),
+            &current_dir,
+        )?;
+        let path = match ctx.find(name) {
+            Some(path) => path,
+            None => return Ok(None),
+        };
+        ctx.run(&path, args, stdin)
+            .map(Some)
+            .map_err(|source| hook::run::Error::Spawn { path, source })
+    }
+
+    /// Run the `pre-commit` and `commit-msg` hooks for a commit with `message`, like `git commit` does before creating
+    /// the commit, and return the message as possibly edited by `commit-msg`.
+    ///
+    /// The message is passed to `commit-msg` in `COMMIT_EDITMSG` within the `.git` directory. The commit should be
+    /// [created][crate::Repository::commit()] with the returned message if no hook rejected it.
+    pub fn run_commit_hooks(&self, message: impl AsRef<str>) -> Result<String, hook::commit::Error> {
+        let reject = |name: &'static str, status: Option<ExitStatus>| match status {
+            Some(status) if !status.success() => Err(hook::commit::Error::Rejected { name, status }),
+            _ => Ok(()),
+        };
+        reject("pre-commit", self.run_hook("pre-commit", None::<&str>, &[])?)?;
+
+        let message_path = self.git_dir().join("COMMIT_EDITMSG");
+        std::fs::write(&message_path, message.as_ref())?;
+        reject("commit-msg", self.run_hook("commit-msg", Some(&message_path), &[])?)?;
+        let message = String::from_utf8(std::fs::read(&message_path)?)?;
+        if message.trim().is_empty() {
+            return Err(hook::commit::Error::EmptyMessage);
+        }
+        Ok(message)
+    }
+}
@@ -65,7 +65,7 @@ impl From<crate::Repository> for crate::ThreadSafeRepository {
     fn from(r: crate::Repository) -> Self {
         crate::ThreadSafeRepository {
             refs: r.refs,
-            objects: r.objects.into_inner().store(),
+            objects: r.objects.into_inner().into_inner().store(),
             work_tree: r.work_tree,
             common_dir: r.common_dir,
             config: r.config,
@@ -0,0 +1,173 @@
+use std::time::{Duration, SystemTime};
+
+use crate::{
+    config::{
+        cache::util::ApplyLeniency,
+        tree::{CommitGraph, Gc, Pack, Repack},
+    },
+    maintenance::{expire_reflogs, prune, repack},
+    Repository,
+};
+
+/// The default for `gc.pruneExpire`, which is `2.weeks.ago`.
+const DEFAULT_PRUNE_EXPIRE: Duration = Duration::from_secs(14 * 24 * 60 * 60);
+/// The default for `gc.reflogExpire`, which is `90.days.ago`.
+const DEFAULT_REFLOG_EXPIRE: Duration = Duration::from_secs(90 * 24 * 60 * 60);
+/// The default for `gc.reflogExpireUnreachable`, which is `30.days.ago`.
+const DEFAULT_REFLOG_EXPIRE_UNREACHABLE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Maintenance
+impl Repository {
+    /// Return options for [`repack()`][Self::repack()] as configured in `gc.cruftPacks`, `gc.pruneExpire`, `repack.writeBitmaps`,
+    /// `pack.writeReverseIndex`, `pack.window`, `pack.depth` and `pack.threads`.
+    ///
+    /// Bitmaps are written by default in bare repositories only, like git does.
+    pub fn repack_options(&self) -> Result<repack::Options, repack::options::Error> {
+        let defaults = repack::Options::default();
+        let expire = self.prune_expire()?;
+        let lenient = self.options.lenient_config;
+        let config = &self.config.resolved;
+        let boolean = |section: &str, key: &'static crate::config::tree::keys::Boolean| {
+            config
+                .boolean_filter(section, None, key.name, &mut self.filter_config_section())
+                .map(|value| key.enrich_error(value))
+                .transpose()
+                .with_leniency(lenient)
+        };
+        let unsigned = |key: &'static crate::config::tree::keys::UnsignedInteger| {
+            config
+                .integer_filter("pack", None, key.name, &mut self.filter_config_section())
+                .map(|value| key.try_into_u32(value))
+                .transpose()
+                .with_leniency(lenient)
+        };
+        Ok(repack::Options {
+            unreachable: if boolean("gc", &Gc::CRUFT_PACKS)?.unwrap_or(false) {
+                repack::Unreachable::Cruft { expire }
+            } else {
+                repack::Unreachable::Loose { expire }
+            },
+            delta_compression: defaults.delta_compression,
+            window: unsigned(&Pack::WINDOW)?.unwrap_or(defaults.window),
+            depth: unsigned(&Pack::DEPTH)?.unwrap_or(defaults.depth),
+            write_bitmap: boolean("repack", &Repack::WRITE_BITMAPS)?.unwrap_or_else(|| self.is_bare()),
+            write_reverse_index: boolean("pack", &Pack::WRITE_REVERSE_INDEX)?.unwrap_or(defaults.write_reverse_index),
+            thread_limit: config
+                .integer_filter("pack", None, Pack::THREADS.name, &mut self.filter_config_section())
+                .map(|threads| Pack::THREADS.try_into_usize(threads))
+                .transpose()
+                .with_leniency(lenient)?
+                .and_then(|threads| (threads != 0).then_some(threads)),
+        })
+    }
+
+    /// Return options for [`write_commit_graph()`][Self::write_commit_graph()] which write a single commit-graph file
+    /// with corrected commit dates, unless `commitGraph.generationVersion` is 1.
+    pub fn commit_graph_options(
+        &self,
+    ) -> Result<gix_commitgraph::graph::write::Options, crate::config::unsigned_integer::Error> {
+        let version = self
+            .config
+            .resolved
+            .integer_filter(
+                "commitGraph",
+                None,
+                CommitGraph::GENERATION_VERSION.name,
+                &mut self.filter_config_section(),
+            )
+            .map(|value| CommitGraph::GENERATION_VERSION.try_into_u32(value))
+            .transpose()
+            .with_leniency(self.options.lenient_config)?;
+        Ok(gix_commitgraph::graph::write::Options {
+            split: None,
+            generation_data: version != Some(1),
+            changed_paths: None,
+        })
+    }
+
+    /// Return options for [`prune()`][Self::prune()] with the grace period configured in `gc.pruneExpire`, which defaults
+    /// to two weeks.
+    pub fn prune_options(&self) -> Result<prune::Options, crate::config::time::Error> {
+        Ok(prune::Options {
+            expire: self.prune_expire()?,
+            dry_run: false,
+        })
+    }
+
+    /// Return options for [`expire_reflogs()`][Self::expire_reflogs()] as configured in `gc.reflogExpire` and
+    /// `gc.reflogExpireUnreachable`, which default to 90 and 30 days respectively, along with their per-pattern variants
+    /// `gc.<pattern>.reflogExpire` and `gc.<pattern>.reflogExpireUnreachable`.
+    ///
+    /// Like in git, entries of `refs/stash` never expire unless configured otherwise.
+    pub fn expire_reflogs_options(&self) -> Result<expire_reflogs::Options, crate::config::time::Error> {
+        let now = SystemTime::now();
+        let lenient = self.options.lenient_config;
+        let mut expire = None;
+        let mut expire_unreachable = None;
+        let mut patterns = Vec::<expire_reflogs::Pattern>::new();
+        if let Some(sections) = self
+            .config
+            .resolved
+            .sections_by_name_and_filter("gc", &mut self.filter_config_section())
+        {
+            for section in sections {
+                let parse = |key: &'static crate::config::tree::gc::PruneExpire| {
+                    section
+                        .value(key.name)
+                        .map(|value| key.try_into_expiry(value, now))
+                        .transpose()
+                        .with_leniency(lenient)
+                };
+                let (total, unreachable) = (parse(&Gc::REFLOG_EXPIRE)?, parse(&Gc::REFLOG_EXPIRE_UNREACHABLE)?);
+                match section.header().subsection_name() {
+                    None => {
+                        expire = total.or(expire);
+                        expire_unreachable = unreachable.or(expire_unreachable);
+                    }
+                    Some(pattern) if total.is_some() || unreachable.is_some() => {
+                        let entry = match patterns.iter_mut().position(|p| p.pattern == pattern) {
+                            Some(idx) => &mut patterns[idx],
+                            None => {
+                                patterns.push(expire_reflogs::Pattern {
+                                    pattern: pattern.to_owned(),
+                                    expire: None,
+                                    expire_unreachable: None,
+                                });
+                                patterns.last_mut().expect("just pushed")
+                            }
+                        };
+                        entry.expire = total.or(entry.expire);
+                        entry.expire_unreachable = unreachable.or(entry.expire_unreachable);
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+        if expire.is_none() || expire_unreachable.is_none() {
+            patterns.push(expire_reflogs::Pattern {
+                pattern: "refs/stash".into(),
+                expire: expire.is_none().then_some(None),
+                expire_unreachable: expire_unreachable.is_none().then_some(None),
+            });
+        }
+        Ok(expire_reflogs::Options {
+            expire: expire.unwrap_or_else(|| Some(now - DEFAULT_REFLOG_EXPIRE)),
+            expire_unreachable: expire_unreachable.unwrap_or_else(|| Some(now - DEFAULT_REFLOG_EXPIRE_UNREACHABLE)),
+            patterns,
+            stale_fix: false,
+            dry_run: false,
+        })
+    }
+
+    fn prune_expire(&self) -> Result<Option<SystemTime>, crate::config::time::Error> {
+        let now = SystemTime::now();
+        Ok(self
+            .config
+            .resolved
+            .string_filter("gc", None, Gc::PRUNE_EXPIRE.name, &mut self.filter_config_section())
+            .map(|value| Gc::PRUNE_EXPIRE.try_into_expiry(value, now))
+            .transpose()
+            .with_leniency(self.options.lenient_config)?
+            .unwrap_or_else(|| Some(now - DEFAULT_PRUNE_EXPIRE)))
+    }
+}
@@ -0,0 +1,44 @@
+#![allow(clippy::result_large_err)]
+use gix_hash::ObjectId;
+
+use crate::{merge, Repository};
+
+/// Merging
+impl Repository {
+    /// Merge the trees of `ours` and `theirs`, using `base` as their common ancestor, with the rename tracking configured in
+    /// `diff.renames`, and write the resulting tree to the object database.
+    ///
+    /// All inputs may be trees, or objects that can be peeled to trees, like commits.
+    /// See [`merge_trees_opts()`][Self::merge_trees_opts()] for details.
+    pub fn merge_trees(
+        &self,
+        base: impl Into<ObjectId>,
+        ours: impl Into<ObjectId>,
+        theirs: impl Into<ObjectId>,
+    ) -> Result<merge::tree::Outcome<'_>, merge::tree::Error> {
+        let options = merge::tree::Options {
+            rewrites: self.config.diff_renames()?.unwrap_or_default().into(),
+            ..Default::default()
+        };
+        self.merge_trees_opts(base, ours, theirs, options)
+    }
+
+    /// Merge the trees of `ours` and `theirs`, using `base` as their common ancestor, and configure the merge with `options`.
+    ///
+    /// Files that were changed on both sides are merged line by line, and renames are tracked on each side to match
+    /// files that were renamed on one side and modified on the other. Changes that can't be merged cleanly are returned
+    /// as [conflicts][merge::tree::Outcome::conflicts], while the resulting tree contains a version of each conflicting file
+    /// with conflict markers where possible.
+    ///
+    /// Only objects are read and written, making this usable in bare repositories as well. Use
+    /// [`Outcome::index()`][merge::tree::Outcome::index()] to obtain an index with conflicting entries.
+    pub fn merge_trees_opts(
+        &self,
+        base: impl Into<ObjectId>,
+        ours: impl Into<ObjectId>,
+        theirs: impl Into<ObjectId>,
+        options: merge::tree::Options,
+    ) -> Result<merge::tree::Outcome<'_>, merge::tree::Error> {
+        merge::tree::merge(self, base.into(), ours.into(), theirs.into(), options)
+    }
+}
@@ -20,14 +20,19 @@ impl crate::Repository {
 }
 
 mod attributes;
+mod branch;
 mod cache;
 mod config;
 mod excludes;
+mod hook;
 pub(crate) mod identity;
 mod impls;
 mod init;
 mod location;
+mod maintenance;
+mod merge;
 mod object;
+mod pathspec;
 mod reference;
 mod remote;
 mod revision;
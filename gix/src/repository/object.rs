@@ -59,6 +59,19 @@ impl crate::Repository {
         }
     }
 
+    /// Keep all objects written from now on in memory instead of writing them to the object database, which is useful
+    /// for dry-runs or speculative changes.
+    ///
+    /// Objects in memory are visible to all lookups through this instance, and can be discarded with
+    /// [`take_object_memory()`][gix_odb::memory::Proxy::take_object_memory()] or written into a single pack
+    /// with [`persist_as_pack()`][gix_odb::memory::Proxy::persist_as_pack()] via [`objects`][crate::Repository::objects].
+    /// Note that clones receive a copy of all objects in memory at that time, and that they are lost when converting
+    /// into a [thread-safe repository][crate::ThreadSafeRepository].
+    pub fn with_object_memory(mut self) -> Self {
+        self.objects.enable_object_memory();
+        self
+    }
+
     fn shared_empty_buf(&self) -> std::cell::RefMut<'_, Vec<u8>> {
         let mut bufs = self.bufs.borrow_mut();
         if bufs.last().is_none() {
@@ -84,10 +97,7 @@ impl crate::Repository {
             return Ok(oid.attach(self));
         }
 
-        self.objects
-            .write_buf(object.kind(), &buf)
-            .map(|oid| oid.attach(self))
-            .map_err(Into::into)
+        self.objects.write_buf(object.kind(), &buf).map(|oid| oid.attach(self))
     }
 
     /// Write a blob from the given `bytes`.
@@ -226,6 +236,9 @@ impl crate::Repository {
     /// If there is no parent, the `reference` is expected to not exist yet.
     ///
     /// The method fails immediately if a `reference` lock can't be acquired.
+    ///
+    /// Like `git commit-tree`, no hooks are run apart from the `reference-transaction` hook. Call
+    /// [`run_commit_hooks()`][crate::Repository::run_commit_hooks()] beforehand to run `pre-commit` and `commit-msg` like `git commit` does.
     pub fn commit<Name, E>(
         &self,
         reference: Name,
@@ -1,12 +1,17 @@
-use std::convert::TryInto;
+#![allow(clippy::result_large_err)]
+use std::{borrow::Cow, convert::TryInto};
 
 use gix_hash::ObjectId;
 use gix_ref::{
     transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
-    FullName, PartialNameRef, Target,
+    Category, FullName, PartialNameRef, Target,
 };
 
-use crate::{bstr::BString, ext::ReferenceExt, reference, Reference};
+use crate::{
+    bstr::{BStr, BString},
+    ext::ReferenceExt,
+    reference, Reference,
+};
 
 /// Obtain and alter references comfortably
 impl crate::Repository {
@@ -46,27 +51,28 @@ impl crate::Repository {
     ///
     /// Namespaces allow to partition references, and is configured per `Easy`.
     pub fn namespace(&self) -> Option<&gix_ref::Namespace> {
-        self.refs.namespace.as_ref()
+        self.refs.namespace()
     }
 
     /// Remove the currently set reference namespace and return it, affecting only this `Easy`.
     pub fn clear_namespace(&mut self) -> Option<gix_ref::Namespace> {
-        self.refs.namespace.take()
+        self.refs.clear_namespace()
     }
 
     /// Set the reference namespace to the given value, like `"foo"` or `"foo/bar"`.
     ///
     /// Note that this value is shared across all `Easy…` instances as the value is stored in the shared `Repository`.
+    /// It's an error to set a namespace if references are stored in reftables.
     pub fn set_namespace<'a, Name, E>(
         &mut self,
         namespace: Name,
-    ) -> Result<Option<gix_ref::Namespace>, gix_validate::refname::Error>
+    ) -> Result<Option<gix_ref::Namespace>, reference::set_namespace::Error>
     where
         Name: TryInto<&'a PartialNameRef, Error = E>,
         gix_validate::refname::Error: From<E>,
     {
         let namespace = gix_ref::namespace::expand(namespace)?;
-        Ok(self.refs.namespace.replace(namespace))
+        Ok(self.refs.set_namespace(namespace)?)
     }
 
     // TODO: more tests or usage
@@ -140,6 +146,119 @@ impl crate::Repository {
             .map_err(Into::into)
     }
 
+    /// Rename the reference `source` to `destination`, like `refs/heads/old` to `refs/heads/new`, and write `log_message`
+    /// into the reflog of `destination`, which must not exist yet.
+    ///
+    /// The reflog of `source` is moved to `destination`, and symbolic references like `HEAD`, including the `HEAD` of all other
+    /// worktrees, which pointed to `source` will point to `destination` afterwards. If both are local branches, their `branch.<name>` configuration sections in
+    /// the local configuration file are renamed as well, which is also reflected in the configuration of this instance.
+    ///
+    /// Returns all performed reference edits.
+    pub fn rename_reference<Name, E>(
+        &mut self,
+        source: Name,
+        destination: Name,
+        log_message: impl Into<BString>,
+    ) -> Result<Vec<RefEdit>, reference::rename::Error>
+    where
+        Name: TryInto<FullName, Error = E>,
+        gix_validate::reference::name::Error: From<E>,
+    {
+        self.copy_or_rename_reference(source, destination, log_message.into(), BranchConfig::Rename)
+    }
+
+    /// Like [`rename_reference()`][Self::rename_reference()], but keeps `source`, its reflog and its branch configuration,
+    /// while `destination` receives a copy of them.
+    ///
+    /// Symbolic references pointing to `source` are left unchanged.
+    pub fn copy_reference<Name, E>(
+        &mut self,
+        source: Name,
+        destination: Name,
+        log_message: impl Into<BString>,
+    ) -> Result<Vec<RefEdit>, reference::rename::Error>
+    where
+        Name: TryInto<FullName, Error = E>,
+        gix_validate::reference::name::Error: From<E>,
+    {
+        self.copy_or_rename_reference(source, destination, log_message.into(), BranchConfig::Copy)
+    }
+
+    fn copy_or_rename_reference<Name, E>(
+        &mut self,
+        source: Name,
+        destination: Name,
+        log_message: BString,
+        mode: BranchConfig,
+    ) -> Result<Vec<RefEdit>, reference::rename::Error>
+    where
+        Name: TryInto<FullName, Error = E>,
+        gix_validate::reference::name::Error: From<E>,
+    {
+        let source = source.try_into().map_err(gix_validate::reference::name::Error::from)?;
+        let destination = destination
+            .try_into()
+            .map_err(gix_validate::reference::name::Error::from)?;
+        let (file_lock_fail, _packed_refs_lock_fail) = self.config.lock_timeout()?;
+        let committer = self.committer().transpose()?;
+        let edits = match mode {
+            BranchConfig::Rename => self.refs.rename_reference(
+                source.as_ref(),
+                destination.clone(),
+                log_message,
+                file_lock_fail,
+                committer,
+            )?,
+            BranchConfig::Copy => self.refs.copy_reference(
+                source.as_ref(),
+                destination.clone(),
+                log_message,
+                file_lock_fail,
+                committer,
+            )?,
+        };
+        if let (Some((Category::LocalBranch, source)), Some((Category::LocalBranch, destination))) =
+            (source.category_and_short_name(), destination.category_and_short_name())
+        {
+            self.edit_branch_config(&[source, destination], |config| {
+                Ok(copy_or_rename_branch_sections(config, source, destination, mode)?)
+            })?;
+        }
+        Ok(edits)
+    }
+
+    /// Lock the repository-local configuration file, apply `edit` to its current content and write it back if `edit` returns
+    /// `true`, then make the `branch.<name>` sections of our configuration match the ones in the file for all `short_names`.
+    pub(crate) fn edit_branch_config(
+        &mut self,
+        short_names: &[&BStr],
+        edit: impl FnOnce(&mut gix_config::File<'static>) -> Result<bool, reference::branch_config::Error>,
+    ) -> Result<(), reference::branch_config::Error> {
+        let path = self.common_dir().join("config");
+        let (lock_fail_mode, _) = self.config.lock_timeout()?;
+        let mut lock = gix_lock::File::acquire_to_update_resource(&path, lock_fail_mode, None)?;
+        let mut local = gix_config::File::from_path_no_includes(path, gix_config::Source::Local)?;
+        if !edit(&mut local)? {
+            return Ok(());
+        }
+        local.write_to(&mut lock)?;
+        lock.commit()?;
+
+        let mut config = self.config_snapshot_mut();
+        for short_name in short_names {
+            for id in branch_section_ids(&config, short_name, |meta| meta.source == gix_config::Source::Local) {
+                config.remove_section_by_id(id);
+            }
+            if let Some(sections) = local.sections_by_name("branch") {
+                for section in sections.filter(|section| section.header().subsection_name() == Some(short_name)) {
+                    push_branch_section(&mut config, short_name, section)?;
+                }
+            }
+        }
+        config.commit()?;
+        Ok(())
+    }
+
     /// Return the repository head, an abstraction to help dealing with the `HEAD` reference.
     ///
     /// The `HEAD` reference can be in various states, for more information, the documentation of [`Head`][crate::Head].
@@ -205,7 +324,7 @@ impl crate::Repository {
     pub fn find_reference<'a, Name, E>(&self, name: Name) -> Result<Reference<'_>, reference::find::existing::Error>
     where
         Name: TryInto<&'a PartialNameRef, Error = E>,
-        gix_ref::file::find::Error: From<E>,
+        gix_ref::store::find::Error: From<E>,
     {
         self.try_find_reference(name)?
             .ok_or(reference::find::existing::Error::NotFound)
@@ -229,7 +348,7 @@ impl crate::Repository {
     pub fn try_find_reference<'a, Name, E>(&self, name: Name) -> Result<Option<Reference<'_>>, reference::find::Error>
     where
         Name: TryInto<&'a PartialNameRef, Error = E>,
-        gix_ref::file::find::Error: From<E>,
+        gix_ref::store::find::Error: From<E>,
     {
         let state = self;
         match state.refs.try_find(name) {
@@ -241,3 +360,65 @@ impl crate::Repository {
         }
     }
 }
+
+#[derive(Copy, Clone)]
+enum BranchConfig {
+    Rename,
+    Copy,
+}
+
+/// Copy or rename all `branch.<source>` sections in `config` to `branch.<destination>`, and return `true` if there was at
+/// least one such section.
+fn copy_or_rename_branch_sections(
+    config: &mut gix_config::File<'static>,
+    source: &BStr,
+    destination: &BStr,
+    mode: BranchConfig,
+) -> Result<bool, gix_config::parse::section::header::Error> {
+    let sections: Vec<_> = match config.sections_and_ids_by_name("branch") {
+        Some(sections) => sections
+            .filter(|(section, _)| section.header().subsection_name() == Some(source))
+            .map(|(section, id)| (section.clone(), id))
+            .collect(),
+        None => return Ok(false),
+    };
+    for (section, id) in &sections {
+        if let BranchConfig::Rename = mode {
+            config.remove_section_by_id(*id);
+        }
+        push_branch_section(config, destination, section)?;
+    }
+    Ok(!sections.is_empty())
+}
+
+/// Return the ids of all `branch.<short_name>` sections in `config` whose metadata passes `filter`.
+pub(super) fn branch_section_ids(
+    config: &gix_config::File<'static>,
+    short_name: &BStr,
+    filter: impl Fn(&gix_config::file::Metadata) -> bool,
+) -> Vec<gix_config::file::SectionId> {
+    match config.sections_and_ids_by_name("branch") {
+        Some(sections) => sections
+            .filter(|(section, _)| section.header().subsection_name() == Some(short_name) && filter(section.meta()))
+            .map(|(_, id)| id)
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+/// Append a copy of `section` to `config`, named `branch.<short_name>`.
+fn push_branch_section(
+    config: &mut gix_config::File<'static>,
+    short_name: &BStr,
+    section: &gix_config::file::Section<'static>,
+) -> Result<(), gix_config::parse::section::header::Error> {
+    let mut copy = config.push_section(gix_config::file::Section::new(
+        "branch",
+        Some(Cow::Owned(short_name.to_owned())),
+        section.meta().clone(),
+    )?)?;
+    for (key, value) in section.body().key_values_implicit() {
+        copy.push(key.clone(), value.as_deref());
+    }
+    Ok(())
+}
@@ -1,3 +1,4 @@
+#![allow(clippy::result_large_err)]
 use crate::{bstr::BStr, revision, Id};
 
 /// Methods for resolving revisions by spec or working with the commit graph.
@@ -39,4 +40,10 @@ impl crate::Repository {
     ) -> revision::walk::Platform<'_> {
         revision::walk::Platform::new(tips, self)
     }
+
+    /// Open the commit-graph of this repository in the `objects/info` directory, which is either a single file or a chain
+    /// of files, to accelerate traversals and lookups of commit information.
+    pub fn commit_graph(&self) -> Result<gix_commitgraph::Graph, gix_commitgraph::graph::Error> {
+        gix_commitgraph::Graph::from_info_dir(self.objects.store_ref().path().join("info"))
+    }
 }
@@ -1,5 +1,5 @@
 use crate::config::cache::util::ApplyLeniencyDefault;
-use crate::{worktree, Worktree};
+use crate::{bstr::BString, worktree, Worktree};
 
 /// Interact with individual worktrees and their information.
 impl crate::Repository {
@@ -26,6 +26,76 @@ impl crate::Repository {
         res.sort_by(|a, b| a.git_dir.cmp(&b.git_dir));
         Ok(res)
     }
+
+    /// Remove the private git directories of all _linked_ worktrees which can be pruned as their base isn't available anymore,
+    /// and return the ids of the worktrees that were pruned.
+    ///
+    /// [Locked][worktree::Proxy::is_locked()] worktrees are never pruned. If `expire` is set, worktrees whose base merely doesn't exist
+    /// anymore are only pruned if they weren't touched since that time, which is useful to keep worktrees on removable media.
+    /// See [`Proxy::prune_reason()`][worktree::Proxy::prune_reason()] for learning why a worktree can be pruned.
+    pub fn prune_worktrees(&self, expire: Option<std::time::SystemTime>) -> std::io::Result<Vec<BString>> {
+        let mut res = Vec::new();
+        let worktrees_dir = self.common_dir().join("worktrees");
+        let iter = match std::fs::read_dir(&worktrees_dir) {
+            Ok(iter) => iter,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(res),
+            Err(err) => return Err(err),
+        };
+        for entry in iter {
+            let proxy = worktree::Proxy::new(self, entry?.path());
+            if proxy.prune_reason().is_none() {
+                continue;
+            }
+            if let Some(expire) = expire {
+                let gitdir_file = proxy.git_dir().join("gitdir");
+                let is_expired = gitdir_file
+                    .metadata()
+                    .and_then(|m| m.modified())
+                    .map_or(true, |modified| modified <= expire);
+                if gitdir_file.is_file() && !is_expired {
+                    continue;
+                }
+            }
+            if proxy.git_dir().is_dir() {
+                std::fs::remove_dir_all(proxy.git_dir())?;
+            } else {
+                std::fs::remove_file(proxy.git_dir())?;
+            }
+            res.push(proxy.id().to_owned());
+        }
+        if std::fs::read_dir(&worktrees_dir)?.next().is_none() {
+            std::fs::remove_dir(worktrees_dir)?;
+        }
+        res.sort();
+        Ok(res)
+    }
+
+    /// Make the _linked_ worktree whose base was moved to `path` known to its private git directory again, so that it
+    /// is valid and won't be pruned, and return it.
+    ///
+    /// Use [`Proxy::repair()`][worktree::Proxy::repair()] if the private git directory was moved instead.
+    pub fn repair_worktree(
+        &self,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<worktree::Proxy<'_>, worktree::repair::Error> {
+        let path = worktree::proxy::absolute(path.as_ref())?;
+        let dot_git = path.join(gix_discover::DOT_GIT_DIR);
+        let git_dir = gix_discover::path::from_gitdir_file(&dot_git)?;
+        let worktrees_dir = self.common_dir().join("worktrees");
+        let is_ours = git_dir
+            .parent()
+            .and_then(|parent| Some((parent.canonicalize().ok()?, worktrees_dir.canonicalize().ok()?)))
+            .map_or(false, |(actual, expected)| actual == expected);
+        if !is_ours || !git_dir.is_dir() {
+            return Err(worktree::repair::Error::NotALinkedWorktree { path });
+        }
+        std::fs::write(
+            git_dir.join("gitdir"),
+            format!("{}\n", gix_path::into_bstr(dot_git.as_path())),
+        )?;
+        Ok(worktree::Proxy::new(self, git_dir))
+    }
+
     /// Return the repository owning the main worktree, typically from a linked worktree.
     ///
     /// Note that it might be the one that is currently open if this repository doesn't point to a linked worktree.
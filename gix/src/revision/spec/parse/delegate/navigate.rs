@@ -1,3 +1,4 @@
+#![allow(clippy::result_large_err)]
 use std::collections::HashSet;
 
 use gix_hash::ObjectId;
@@ -136,7 +136,7 @@ pub enum Error {
     #[error(transparent)]
     IdFromHex(#[from] gix_hash::decode::Error),
     #[error(transparent)]
-    FindReference(#[from] gix_ref::file::find::existing::Error),
+    FindReference(#[from] gix_ref::store::find::existing::Error),
     #[error(transparent)]
     FindObject(#[from] object::find::existing::Error),
     #[error(transparent)]
@@ -1,7 +1,11 @@
 use gix_hash::ObjectId;
 use gix_odb::FindExt;
 
-use crate::{revision, Repository};
+use crate::{
+    bstr::{BStr, BString, ByteSlice},
+    config::{cache::util::ApplyLeniency, tree::CommitGraph},
+    revision, Repository,
+};
 
 /// The error returned by [`Platform::all()`].
 #[derive(Debug, thiserror::Error)]
@@ -11,6 +15,8 @@ pub enum Error {
     AncestorIter(#[from] gix_traverse::commit::ancestors::Error),
     #[error(transparent)]
     ShallowCommits(#[from] crate::shallow::open::Error),
+    #[error("The value of `commitGraph.readChangedPaths` could not be interpreted")]
+    ReadChangedPathsConfig(#[from] crate::config::boolean::Error),
 }
 
 /// A platform to traverse the revision graph by adding starting points as well as points which shouldn't be crossed,
@@ -20,6 +26,7 @@ pub struct Platform<'repo> {
     pub(crate) tips: Vec<ObjectId>,
     pub(crate) sorting: gix_traverse::commit::Sorting,
     pub(crate) parents: gix_traverse::commit::Parents,
+    pub(crate) path: Option<BString>,
 }
 
 impl<'repo> Platform<'repo> {
@@ -29,6 +36,7 @@ impl<'repo> Platform<'repo> {
             tips: tips.into_iter().map(Into::into).collect(),
             sorting: Default::default(),
             parents: Default::default(),
+            path: None,
         }
     }
 }
@@ -46,6 +54,23 @@ impl<'repo> Platform<'repo> {
         self.parents = gix_traverse::commit::Parents::First;
         self
     }
+
+    /// Only yield commits in which the entry at `path` differs from the one in any of their parents, or which add it
+    /// if they have no parent, like `git log --full-history -- <path>`.
+    ///
+    /// `path` is relative to the root of the repository and uses slashes as separators, like `dir/file`.
+    ///
+    /// The changed-path Bloom filters of the [commit-graph][Repository::commit_graph()] are used to skip commits which
+    /// definitely didn't change `path` without looking at their trees, unless `commitGraph.readChangedPaths` is `false`.
+    ///
+    /// ### Deviation
+    ///
+    /// The history isn't simplified like `git log -- <path>` does by default, so all parents of merge commits are traversed
+    /// even if `path` in the merge commit is the same as in one of its parents.
+    pub fn limit_to_path(mut self, path: impl Into<BString>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
 }
 
 /// Produce the iterator
@@ -63,10 +88,30 @@ impl<'repo> Platform<'repo> {
             tips,
             sorting,
             parents,
+            path,
         } = self;
-        Ok(revision::Walk {
-            repo,
-            inner: Box::new(
+        let path_limit = path
+            .map(|path| -> Result<_, Error> {
+                let read_changed_paths = repo
+                    .config
+                    .resolved
+                    .boolean_filter(
+                        "commitGraph",
+                        None,
+                        CommitGraph::READ_CHANGED_PATHS.name,
+                        &mut repo.filter_config_section(),
+                    )
+                    .map(|value| CommitGraph::READ_CHANGED_PATHS.enrich_error(value))
+                    .transpose()
+                    .with_leniency(repo.options.lenient_config)?
+                    .unwrap_or(true);
+                let graph = read_changed_paths.then(|| repo.commit_graph().ok()).flatten();
+                Ok((path, graph))
+            })
+            .transpose()?;
+        let first_parent_only = matches!(parents, gix_traverse::commit::Parents::First);
+        let inner: Box<dyn Iterator<Item = Result<ObjectId, gix_traverse::commit::ancestors::Error>> + 'repo> =
+            Box::new(
                 gix_traverse::commit::Ancestors::filtered(
                     tips,
                     gix_traverse::commit::ancestors::State::default(),
@@ -101,7 +146,19 @@ impl<'repo> Platform<'repo> {
                 )
                 .sorting(sorting)?
                 .parents(parents),
-            ),
+            );
+        Ok(revision::Walk {
+            repo,
+            inner: match path_limit {
+                Some((path, graph)) => Box::new(inner.filter_map(move |res| {
+                    res.and_then(|id| {
+                        changes_path(repo, graph.as_ref(), path.as_ref(), first_parent_only, &id)
+                            .map(|changed| changed.then_some(id))
+                    })
+                    .transpose()
+                })),
+                None => inner,
+            },
         })
     }
     /// Return an iterator to traverse all commits reachable as configured by the [Platform].
@@ -115,6 +172,66 @@ impl<'repo> Platform<'repo> {
     }
 }
 
+/// Return `true` if the commit with `id` changed the entry at `path` compared to any of its parents, or just its first parent
+/// if `first_parent_only` is set, possibly using the Bloom filters in `graph` to learn that it didn't.
+fn changes_path(
+    repo: &Repository,
+    graph: Option<&gix_commitgraph::Graph>,
+    path: &BStr,
+    first_parent_only: bool,
+    id: &gix_hash::oid,
+) -> Result<bool, gix_traverse::commit::ancestors::Error> {
+    // Filters only know about changes compared to the first parent.
+    if let Some(commit) = graph.and_then(|graph| graph.commit_by_id(id)) {
+        if (first_parent_only || commit.iter_parents().count() < 2) && commit.maybe_changed_path(path) == Some(false) {
+            return Ok(false);
+        }
+    }
+
+    let find_err = |oid: ObjectId| {
+        move |err: crate::object::find::existing::Error| gix_traverse::commit::ancestors::Error::FindExisting {
+            oid,
+            source: err.into(),
+        }
+    };
+    let entry_at = |commit: ObjectId| -> Result<
+        Option<(gix_object::tree::EntryMode, ObjectId)>,
+        gix_traverse::commit::ancestors::Error,
+    > {
+        let tree = repo
+            .find_object(commit)
+            .map_err(find_err(commit))?
+            .to_commit_ref_iter()
+            .tree_id()?;
+        Ok(repo
+            .find_object(tree)
+            .map_err(find_err(tree))?
+            .into_tree()
+            .lookup_entry(path.split_str("/"))
+            .map_err(find_err(tree))?
+            .map(|entry| (entry.mode(), entry.object_id())))
+    };
+
+    let id = id.to_owned();
+    let parents: Vec<_> = repo
+        .find_object(id)
+        .map_err(find_err(id))?
+        .to_commit_ref_iter()
+        .parent_ids()
+        .take(if first_parent_only { 1 } else { usize::MAX })
+        .collect();
+    let entry = entry_at(id)?;
+    if parents.is_empty() {
+        return Ok(entry.is_some());
+    }
+    for parent in parents {
+        if entry_at(parent)? != entry {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
 pub(crate) mod iter {
     use crate::{ext::ObjectIdExt, Id};
 
@@ -0,0 +1,237 @@
+#![allow(clippy::result_large_err)]
+use std::path::{Path, PathBuf};
+
+use gix_odb::FindExt;
+
+use crate::{
+    bstr::{BStr, BString},
+    Repository,
+};
+
+/// The error returned by [`Repository::worktree_add()`][crate::Repository::worktree_add()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Linked worktrees can only be added to repositories with a valid git directory")]
+    CurrentDir(#[source] std::io::Error),
+    #[error("The destination at \"{}\" already exists and is not an empty directory", path.display())]
+    DestinationNotEmpty { path: PathBuf },
+    #[error("The branch '{name}' is already checked out at \"{}\"", path.display())]
+    BranchCheckedOut { name: BString, path: PathBuf },
+    #[error(transparent)]
+    FindBranch(#[from] crate::reference::find::Error),
+    #[error(transparent)]
+    RevParse(#[from] crate::revision::spec::parse::single::Error),
+    #[error(transparent)]
+    PeelBranch(#[from] crate::reference::peel::Error),
+    #[error(transparent)]
+    FindObject(#[from] crate::object::find::existing::Error),
+    #[error("The revision to check out could not be peeled to a commit")]
+    PeelToCommit(#[from] crate::object::peel::to_kind::Error),
+    #[error(transparent)]
+    OpenMainRepo(#[from] crate::open::Error),
+    #[error(transparent)]
+    HeadName(#[from] crate::reference::find::existing::Error),
+    #[error("Could not access the destination or create the administrative files of the new worktree")]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    OpenWorktree(#[from] crate::worktree::proxy::into_repo::Error),
+    #[error("Could not create index from tree at {id}")]
+    IndexFromTree {
+        id: gix_hash::ObjectId,
+        source: gix_traverse::tree::breadthfirst::Error,
+    },
+    #[error(transparent)]
+    CheckoutOptions(#[from] crate::config::checkout_options::Error),
+    #[error(transparent)]
+    IndexCheckout(
+        #[from] gix_worktree::checkout::Error<gix_odb::find::existing_object::Error<gix_odb::store::find::Error>>,
+    ),
+    #[error(transparent)]
+    WriteIndex(#[from] gix_index::file::write::Error),
+    #[error("Could not run the post-checkout hook")]
+    PostCheckoutHook(#[from] crate::hook::run::Error),
+}
+
+/// What `HEAD` of a new worktree should point to.
+enum Head {
+    Branch(gix_ref::FullName),
+    Detached(gix_hash::ObjectId),
+}
+
+impl Repository {
+    /// Create a new linked worktree at `path` which is checked out at `rev`, and return it as repository.
+    ///
+    /// If `rev` is the name of a local branch, like `main`, the `HEAD` of the new worktree will point to it, which fails if the branch
+    /// is already checked out in another worktree. Otherwise, `rev` is parsed as revision and the `HEAD` will be detached at the commit
+    /// it points to.
+    /// `path` must not exist or be an empty directory, and the worktree's private git directory is placed in the `worktrees`
+    /// directory within the common git directory. Its id is derived from the last component of `path`.
+    ///
+    /// The new worktree is locked while it is being created, and everything created so far is removed if there is an error.
+    /// The `post-checkout` hook is run in the new worktree afterwards if it exists.
+    pub fn worktree_add<'a>(&self, path: impl AsRef<Path>, rev: impl Into<&'a BStr>) -> Result<Repository, Error> {
+        let (path, rev) = (path.as_ref(), rev.into());
+        let path = super::proxy::absolute(path).map_err(Error::CurrentDir)?;
+        let created_dir = match std::fs::read_dir(&path) {
+            Ok(mut entries) => {
+                if entries.next().is_some() {
+                    return Err(Error::DestinationNotEmpty { path });
+                }
+                false
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => true,
+            Err(_) if path.symlink_metadata().map_or(false, |meta| !meta.is_dir()) => {
+                return Err(Error::DestinationNotEmpty { path })
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        let branch = gix_ref::FullName::try_from(BString::from(format!("refs/heads/{rev}"))).ok();
+        let reference = match &branch {
+            Some(branch) => self.try_find_reference(branch)?,
+            None => None,
+        };
+        let (head, commit) = match branch.zip(reference) {
+            Some((branch, mut reference)) => {
+                self.assure_branch_is_not_checked_out(branch.as_bstr())?;
+                let commit = reference
+                    .peel_to_id_in_place()?
+                    .object()?
+                    .peel_to_kind(gix_object::Kind::Commit)?;
+                (Head::Branch(branch), commit.id)
+            }
+            None => {
+                let commit = self
+                    .rev_parse_single(rev)?
+                    .object()?
+                    .peel_to_kind(gix_object::Kind::Commit)?;
+                (Head::Detached(commit.id), commit.id)
+            }
+        };
+
+        let worktrees_dir = self.common_dir().join("worktrees");
+        std::fs::create_dir_all(&worktrees_dir)?;
+        let private_git_dir = create_unique_private_git_dir(&worktrees_dir, &path)?;
+        let res = self.populate_worktree(&path, &private_git_dir, head, commit);
+        if res.is_err() {
+            std::fs::remove_dir_all(&private_git_dir).ok();
+            if created_dir {
+                std::fs::remove_dir_all(&path).ok();
+            } else if let Ok(entries) = std::fs::read_dir(&path) {
+                for entry in entries.filter_map(Result::ok) {
+                    let entry_path = entry.path();
+                    if entry.file_type().map_or(false, |t| t.is_dir()) {
+                        std::fs::remove_dir_all(entry_path).ok();
+                    } else {
+                        std::fs::remove_file(entry_path).ok();
+                    }
+                }
+            }
+        }
+        let repo = res?;
+        // Like git, the exit status of the hook doesn't affect the new worktree.
+        repo.run_hook(
+            "post-checkout",
+            [commit.kind().null().to_string(), commit.to_string(), "1".into()],
+            &[],
+        )?;
+        Ok(repo)
+    }
+
+    fn assure_branch_is_not_checked_out(&self, branch: &BStr) -> Result<(), Error> {
+        let main = self.main_repo()?;
+        if let Some(work_dir) = main.work_dir() {
+            if main.head_name()?.map_or(false, |name| name.as_bstr() == branch) {
+                return Err(Error::BranchCheckedOut {
+                    name: branch.to_owned(),
+                    path: work_dir.to_owned(),
+                });
+            }
+        }
+        for proxy in self.worktrees()? {
+            let path = proxy.base().unwrap_or_else(|_| proxy.git_dir().to_owned());
+            let repo = proxy.into_repo_with_possibly_inaccessible_worktree()?;
+            if repo.head_name()?.map_or(false, |name| name.as_bstr() == branch) {
+                return Err(Error::BranchCheckedOut {
+                    name: branch.to_owned(),
+                    path,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn populate_worktree(
+        &self,
+        path: &Path,
+        private_git_dir: &Path,
+        head: Head,
+        commit: gix_hash::ObjectId,
+    ) -> Result<Repository, Error> {
+        std::fs::write(private_git_dir.join("locked"), "initializing")?;
+        std::fs::create_dir_all(path)?;
+        let dot_git = path.join(gix_discover::DOT_GIT_DIR);
+        std::fs::write(
+            private_git_dir.join("gitdir"),
+            format!("{}\n", gix_path::into_bstr(dot_git.as_path())),
+        )?;
+        std::fs::write(private_git_dir.join("commondir"), "../..\n")?;
+        let head = match head {
+            Head::Branch(name) => format!("ref: {}\n", name.as_bstr()),
+            Head::Detached(id) => format!("{id}\n"),
+        };
+        std::fs::write(private_git_dir.join("HEAD"), head)?;
+        std::fs::write(&dot_git, format!("gitdir: {}\n", gix_path::into_bstr(private_git_dir)))?;
+
+        let repo = crate::worktree::Proxy::new(self, private_git_dir).into_repo()?;
+        let root_tree = repo.find_object(commit)?.peel_to_tree()?.id;
+        let index = gix_index::State::from_tree(&root_tree, |oid, buf| repo.objects.find_tree_iter(oid, buf).ok())
+            .map_err(|err| Error::IndexFromTree {
+                id: root_tree,
+                source: err,
+            })?;
+        let mut index = gix_index::File::from_state(index, repo.index_path());
+
+        let mut opts = repo.config.checkout_options(repo.git_dir())?;
+        opts.destination_is_initially_empty = true;
+        gix_worktree::checkout(
+            &mut index,
+            path,
+            {
+                let objects = repo.objects.clone().into_arc()?;
+                move |oid, buf| objects.find_blob(oid, buf)
+            },
+            &mut gix_features::progress::Discard,
+            &mut gix_features::progress::Discard,
+            &std::sync::atomic::AtomicBool::default(),
+            opts,
+        )?;
+        index.write(Default::default())?;
+
+        std::fs::remove_file(private_git_dir.join("locked"))?;
+        Ok(repo)
+    }
+}
+
+/// Create a new directory in `worktrees_dir` named after the last component of `path`, which is made unique
+/// by appending a number if needed.
+fn create_unique_private_git_dir(worktrees_dir: &Path, path: &Path) -> std::io::Result<PathBuf> {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "worktree".into());
+    let mut counter = 0;
+    loop {
+        let candidate = if counter == 0 {
+            worktrees_dir.join(&name)
+        } else {
+            worktrees_dir.join(format!("{name}{counter}"))
+        };
+        match std::fs::create_dir(&candidate) {
+            Ok(()) => return Ok(candidate),
+            Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => counter += 1,
+            Err(err) => return Err(err),
+        }
+    }
+}
@@ -69,6 +69,27 @@ pub(crate) fn id(git_dir: &std::path::Path, has_common_dir: bool) -> Option<&BSt
 ///
 pub mod proxy;
 
+///
+pub mod add;
+
+///
+pub mod switch;
+
+///
+pub mod repair {
+    /// The error returned by [`Repository::repair_worktree()`][crate::Repository::repair_worktree()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The worktree at \"{}\" doesn't belong to this repository", path.display())]
+        NotALinkedWorktree { path: std::path::PathBuf },
+        #[error(transparent)]
+        ReadGitDirFile(#[from] gix_discover::path::from_gitdir_file::Error),
+        #[error("Could not update the private git directory of the worktree")]
+        Io(#[from] std::io::Error),
+    }
+}
+
 ///
 pub mod open_index {
     /// The error returned by [`Worktree::open_index()`][crate::Worktree::open_index()].
@@ -157,3 +178,207 @@ pub mod attributes {
         }
     }
 }
+
+///
+pub mod pathspec {
+    use crate::bstr::BStr;
+
+    /// The error returned by [`Worktree::pathspec()`][crate::Worktree::pathspec()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        OpenIndex(#[from] crate::worktree::open_index::Error),
+        #[error(transparent)]
+        Init(#[from] crate::pathspec::init::Error),
+    }
+
+    impl<'repo> crate::Worktree<'repo> {
+        /// Configure pathspecs `patterns` to be matched against files in this worktree, as needed by `status`, `checkout` or
+        /// `ls-files` style operations.
+        ///
+        /// This takes `core.ignoreCase` into consideration, and reads attributes from the worktree with the index as fallback
+        /// if one of the `patterns` uses `:(attr:…)`.
+        pub fn pathspec(
+            &self,
+            patterns: impl IntoIterator<Item = impl AsRef<BStr>>,
+        ) -> Result<crate::Pathspec<'repo>, Error> {
+            let index = self.index()?;
+            Ok(self.parent.pathspec(
+                patterns,
+                true,
+                &index,
+                gix_worktree::cache::state::attributes::Source::WorktreeThenIdMapping,
+            )?)
+        }
+    }
+}
+
+///
+#[allow(clippy::result_large_err)]
+pub mod is_dirty {
+    /// The error returned by [`Worktree::is_dirty()`][crate::Worktree::is_dirty()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        OpenIndex(#[from] crate::worktree::open_index::Error),
+        #[error(transparent)]
+        HeadId(#[from] crate::reference::head_id::Error),
+        #[error(transparent)]
+        FindHeadObject(#[from] crate::object::find::existing::Error),
+        #[error(transparent)]
+        PeelHeadToTree(#[from] crate::object::peel::to_kind::Error),
+        #[error("Could not create index from tree at {id}")]
+        IndexFromTree {
+            id: gix_hash::ObjectId,
+            source: gix_traverse::tree::breadthfirst::Error,
+        },
+        #[error(transparent)]
+        CheckoutOptions(#[from] crate::config::checkout_options::Error),
+        #[error("Failed to reopen object database as Arc (only if thread-safety wasn't compiled in)")]
+        OpenArcOdb(#[source] std::io::Error),
+        #[error(transparent)]
+        Status(#[from] gix_worktree::status::Error),
+        #[error(transparent)]
+        CreateExcludes(#[from] crate::config::exclude_stack::Error),
+        #[error("Could not read the worktree directory at \"{}\"", path.display())]
+        ReadDir {
+            path: std::path::PathBuf,
+            source: std::io::Error,
+        },
+        #[error("Could not check if a path is excluded")]
+        Excludes(#[source] std::io::Error),
+    }
+
+    impl<'repo> crate::Worktree<'repo> {
+        /// Return true if this worktree has changes that would be lost if it was removed, which is the case if the index
+        /// differs from the tree at `HEAD`, if tracked files were changed or removed, or if there are untracked files
+        /// that aren't ignored.
+        pub fn is_dirty(&self) -> Result<bool, Error> {
+            let repo = self.parent;
+            let index = repo.index()?;
+            let mut index: gix_index::State = (***index).clone();
+            if has_staged_changes(repo, &index)? {
+                return Ok(true);
+            }
+
+            let options = repo.config.checkout_options(repo.git_dir())?;
+            let mut recorder = gix_worktree::status::Recorder::default();
+            let objects = repo.objects.clone().into_arc().map_err(Error::OpenArcOdb)?;
+            gix_worktree::status(
+                &mut index,
+                self.path,
+                &mut recorder,
+                gix_worktree::status::content::FastEq,
+                {
+                    use gix_odb::FindExt;
+                    move |oid, buf| objects.find_blob(oid, buf)
+                },
+                gix_worktree::status::Options {
+                    fs: options.fs,
+                    thread_limit: options.thread_limit,
+                    stat: options.stat_options,
+                },
+            )?;
+            if !recorder.records.is_empty() {
+                return Ok(true);
+            }
+
+            let mut excludes = repo.excludes(
+                &index,
+                None,
+                gix_worktree::cache::state::ignore::Source::WorktreeThenIdMappingIfNotSkipped,
+            )?;
+            has_untracked_files(repo, &index, &mut excludes, self.path, &mut Vec::new())
+        }
+    }
+
+    fn has_staged_changes(repo: &crate::Repository, index: &gix_index::State) -> Result<bool, Error> {
+        use gix_odb::FindExt;
+        let head_tree = match repo.head_id() {
+            Ok(id) => id.object()?.peel_to_tree()?.id,
+            Err(crate::reference::head_id::Error::Unborn { .. }) => return Ok(!index.entries().is_empty()),
+            Err(err) => return Err(err.into()),
+        };
+        let head_index = gix_index::State::from_tree(&head_tree, |oid, buf| repo.objects.find_tree_iter(oid, buf).ok())
+            .map_err(|err| Error::IndexFromTree {
+                id: head_tree,
+                source: err,
+            })?;
+        Ok(index.entries().len() != head_index.entries().len()
+            || index.entries().iter().zip(head_index.entries()).any(|(a, b)| {
+                a.id != b.id || a.mode != b.mode || a.stage() != 0 || a.path(index) != b.path(&head_index)
+            }))
+    }
+
+    fn has_untracked_files(
+        repo: &crate::Repository,
+        index: &gix_index::State,
+        excludes: &mut gix_worktree::Cache,
+        dir: &std::path::Path,
+        rela_path: &mut Vec<u8>,
+    ) -> Result<bool, Error> {
+        use gix_odb::FindExt;
+
+        use crate::bstr::ByteSlice;
+        let entries = std::fs::read_dir(dir).map_err(|source| Error::ReadDir {
+            path: dir.to_owned(),
+            source,
+        })?;
+        for entry in entries {
+            let entry = entry.map_err(|source| Error::ReadDir {
+                path: dir.to_owned(),
+                source,
+            })?;
+            if rela_path.is_empty() && entry.file_name() == ".git" {
+                continue;
+            }
+            let prev_len = rela_path.len();
+            if !rela_path.is_empty() {
+                rela_path.push(b'/');
+            }
+            rela_path.extend_from_slice(
+                gix_path::os_str_into_bstr(&entry.file_name()).unwrap_or_else(|_| b"\xff".as_bstr()),
+            );
+
+            let path = entry.path();
+            let is_dir = entry.file_type().map_or(false, |t| t.is_dir());
+            let is_tracked = if is_dir {
+                let prefix_len = rela_path.len();
+                rela_path.push(b'/');
+                let is_tracked = index
+                    .entries()
+                    .binary_search_by(|e| {
+                        let path = e.path(index);
+                        if path.starts_with(rela_path.as_slice()) {
+                            std::cmp::Ordering::Equal
+                        } else {
+                            path.cmp(rela_path.as_bstr())
+                        }
+                    })
+                    .is_ok();
+                rela_path.truncate(prefix_len);
+                is_tracked && !path.join(".git").exists()
+            } else {
+                index.entry_by_path_and_stage(rela_path.as_bstr(), 0).is_some()
+            };
+
+            let is_untracked = if is_tracked {
+                is_dir && has_untracked_files(repo, index, excludes, &path, rela_path)?
+            } else {
+                !excludes
+                    .at_entry(rela_path.as_bstr(), Some(is_dir), |oid, buf| {
+                        repo.objects.find_blob(oid, buf)
+                    })
+                    .map_err(Error::Excludes)?
+                    .is_excluded()
+            };
+            rela_path.truncate(prev_len);
+            if is_untracked {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
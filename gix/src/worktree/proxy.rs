@@ -24,6 +24,62 @@ pub mod into_repo {
     }
 }
 
+///
+pub mod remove {
+    /// The error returned by [`Proxy::remove()`][super::Proxy::remove()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Cannot remove worktree '{id}' as it is locked: '{reason}'")]
+        Locked {
+            id: crate::bstr::BString,
+            reason: crate::bstr::BString,
+        },
+        #[error("Cannot remove worktree '{id}' as it contains modified or untracked files")]
+        Dirty { id: crate::bstr::BString },
+        #[error(transparent)]
+        IntoRepo(#[from] super::into_repo::Error),
+        #[error(transparent)]
+        IsDirty(#[from] crate::worktree::is_dirty::Error),
+        #[error("Could not delete the worktree or its private git directory")]
+        Io(#[from] std::io::Error),
+    }
+
+    /// Options for use in [`Proxy::remove()`][super::Proxy::remove()].
+    #[derive(Default, Debug, Copy, Clone)]
+    pub struct Options {
+        /// If `true`, remove the worktree even if it contains changes or untracked files that would be lost.
+        pub allow_dirty: bool,
+        /// If `true`, remove the worktree even if it is [locked][super::Proxy::is_locked()].
+        pub allow_locked: bool,
+    }
+}
+
+///
+pub mod move_to {
+    use std::path::PathBuf;
+
+    /// The error returned by [`Proxy::move_to()`][super::Proxy::move_to()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Cannot move worktree '{id}' as it is locked: '{reason}'")]
+        Locked {
+            id: crate::bstr::BString,
+            reason: crate::bstr::BString,
+        },
+        #[error("Cannot move worktree '{id}' as its base directory is inaccessible")]
+        MissingWorktree {
+            id: crate::bstr::BString,
+            source: std::io::Error,
+        },
+        #[error("The destination at \"{}\" already exists", path.display())]
+        DestinationExists { path: PathBuf },
+        #[error("Could not move the worktree or update its private git directory")]
+        Io(#[from] std::io::Error),
+    }
+}
+
 impl<'repo> Proxy<'repo> {
     pub(crate) fn new(parent: &'repo Repository, git_dir: impl Into<PathBuf>) -> Self {
         Proxy {
@@ -74,6 +130,141 @@ impl<'repo> Proxy<'repo> {
             .map(|contents| contents.trim().into())
     }
 
+    /// Return the reason for why this worktree can be pruned, or `None` if it is still valid or [locked][Self::is_locked()].
+    ///
+    /// A worktree can be pruned if its private git directory doesn't know the location of its base, or if the base doesn't
+    /// exist anymore, like when it was deleted without using [`remove()`][Self::remove()].
+    pub fn prune_reason(&self) -> Option<BString> {
+        if !self.git_dir.is_dir() {
+            return Some("not a valid directory".into());
+        }
+        if self.is_locked() {
+            return None;
+        }
+        let gitdir_file = self.git_dir.join("gitdir");
+        if !gitdir_file.is_file() {
+            return Some("gitdir file does not exist".into());
+        }
+        let base_dot_git = match std::fs::read(&gitdir_file) {
+            Ok(contents) => contents,
+            Err(_) => return Some("unable to read gitdir file".into()),
+        };
+        let base_dot_git = base_dot_git.trim();
+        if base_dot_git.is_empty() {
+            return Some("invalid gitdir file".into());
+        }
+        if !gix_path::from_byte_slice(base_dot_git).exists() {
+            return Some("gitdir file points to non-existent location".into());
+        }
+        None
+    }
+
+    /// Lock this worktree with the given `reason`, which may be empty, to prevent it from being pruned, moved or removed.
+    ///
+    /// Fails if it is already locked.
+    pub fn lock(&self, reason: impl AsRef<BStr>) -> std::io::Result<()> {
+        use std::io::Write;
+        std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(self.git_dir.join("locked"))?
+            .write_all(reason.as_ref())
+    }
+
+    /// Unlock this worktree, and return `true` if it was locked before.
+    pub fn unlock(&self) -> std::io::Result<bool> {
+        match std::fs::remove_file(self.git_dir.join("locked")) {
+            Ok(()) => Ok(true),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Make sure the `.git` file in the base of this worktree points to our [private git directory][Self::git_dir()],
+    /// and rewrite it if it doesn't. Return `true` if the file had to be rewritten.
+    ///
+    /// Use [`Repository::repair_worktree()`] if the worktree base was moved instead.
+    pub fn repair(&self) -> std::io::Result<bool> {
+        let dot_git = self.base()?.join(gix_discover::DOT_GIT_DIR);
+        let points_to_us = gix_discover::path::from_gitdir_file(&dot_git)
+            .ok()
+            .and_then(|git_dir| Some((git_dir.canonicalize().ok()?, self.git_dir.canonicalize().ok()?)))
+            .map_or(false, |(actual, expected)| actual == expected);
+        if points_to_us {
+            return Ok(false);
+        }
+        let git_dir = absolute(&self.git_dir)?;
+        std::fs::write(dot_git, format!("gitdir: {}\n", gix_path::into_bstr(git_dir.as_path())))?;
+        Ok(true)
+    }
+
+    /// Remove the base of this worktree along with everything in it, as well as its private git directory.
+    ///
+    /// Unless configured otherwise in `options`, this fails if the worktree is locked, or if it has modified or untracked files
+    /// which would be lost.
+    /// If the base of the worktree doesn't exist anymore, only the private git directory is removed.
+    pub fn remove(self, options: remove::Options) -> Result<(), remove::Error> {
+        if !options.allow_locked {
+            if let Some(reason) = self.lock_reason() {
+                return Err(remove::Error::Locked {
+                    id: self.id().to_owned(),
+                    reason,
+                });
+            }
+        }
+        let git_dir = self.git_dir.clone();
+        match self.base() {
+            Ok(base) if base.is_dir() => {
+                if !options.allow_dirty {
+                    let id = self.id().to_owned();
+                    let repo = self.into_repo()?;
+                    if repo.worktree().expect("linked worktrees have a base").is_dirty()? {
+                        return Err(remove::Error::Dirty { id });
+                    }
+                }
+                std::fs::remove_dir_all(base)?;
+            }
+            _ => {}
+        }
+        std::fs::remove_dir_all(git_dir)?;
+        Ok(())
+    }
+
+    /// Move the base of this worktree to `new_base`, or into it if it's an existing directory, and update our
+    /// [private git directory][Self::git_dir()] accordingly.
+    ///
+    /// This fails if the worktree is locked, or if the destination already exists.
+    pub fn move_to(&self, new_base: impl AsRef<Path>) -> Result<PathBuf, move_to::Error> {
+        if let Some(reason) = self.lock_reason() {
+            return Err(move_to::Error::Locked {
+                id: self.id().to_owned(),
+                reason,
+            });
+        }
+        let base = self.base().map_err(|source| move_to::Error::MissingWorktree {
+            id: self.id().to_owned(),
+            source,
+        })?;
+        let mut new_base = absolute(new_base.as_ref())?;
+        if new_base.is_dir() {
+            if let Some(name) = base.file_name() {
+                new_base.push(name);
+            }
+        }
+        if new_base.exists() {
+            return Err(move_to::Error::DestinationExists { path: new_base });
+        }
+        std::fs::rename(&base, &new_base)?;
+        std::fs::write(
+            self.git_dir.join("gitdir"),
+            format!(
+                "{}\n",
+                gix_path::into_bstr(new_base.join(gix_discover::DOT_GIT_DIR).as_path())
+            ),
+        )?;
+        Ok(new_base)
+    }
+
     /// Transform this proxy into a [`Repository`] while ignoring issues reading `base()` and ignoring that it might not exist.
     ///
     /// Most importantly, the `Repository` might be initialized with a non-existing work tree directory as the checkout
@@ -99,3 +290,11 @@ impl<'repo> Proxy<'repo> {
         Ok(repo.into())
     }
 }
+
+pub(crate) fn absolute(path: &Path) -> std::io::Result<PathBuf> {
+    Ok(if path.is_relative() {
+        std::env::current_dir()?.join(path)
+    } else {
+        path.to_owned()
+    })
+}
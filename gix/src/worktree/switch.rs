@@ -0,0 +1,517 @@
+#![allow(clippy::result_large_err)]
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+    process::ExitStatus,
+    sync::atomic::AtomicBool,
+};
+
+use gix_hash::ObjectId;
+use gix_index::{entry::Flags, Entry};
+use gix_odb::FindExt;
+use gix_ref::{
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    FullName, Target,
+};
+
+use crate::{
+    bstr::{BStr, BString},
+    Progress, Repository,
+};
+
+/// The error returned by [`Repository::switch()`][crate::Repository::switch()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Repository at \"{}\" is a bare repository and has no worktree to switch", git_dir.display())]
+    BareRepository { git_dir: PathBuf },
+    #[error("The branch '{name}' is already checked out at \"{}\"", path.display())]
+    BranchCheckedOut { name: BString, path: PathBuf },
+    #[error(transparent)]
+    FindBranch(#[from] crate::reference::find::Error),
+    #[error(transparent)]
+    PeelBranch(#[from] crate::reference::peel::Error),
+    #[error(transparent)]
+    RevParse(#[from] crate::revision::spec::parse::single::Error),
+    #[error(transparent)]
+    FindObject(#[from] crate::object::find::existing::Error),
+    #[error("The revision to switch to could not be peeled to a commit")]
+    PeelToCommit(#[from] crate::object::peel::to_kind::Error),
+    #[error(transparent)]
+    Worktrees(#[from] crate::branch::worktrees::Error),
+    #[error(transparent)]
+    HeadId(#[from] crate::reference::head_id::Error),
+    #[error(transparent)]
+    HeadName(#[from] crate::reference::find::existing::Error),
+    #[error("Could not create index from tree at {id}")]
+    IndexFromTree {
+        id: ObjectId,
+        source: gix_traverse::tree::breadthfirst::Error,
+    },
+    #[error(transparent)]
+    OpenIndex(#[from] crate::worktree::open_index::Error),
+    #[error("Could not expand the sparse directories of the index")]
+    ExpandSparseIndex(#[source] gix_traverse::tree::breadthfirst::Error),
+    #[error(transparent)]
+    CheckoutOptions(#[from] crate::config::checkout_options::Error),
+    #[error("Failed to reopen object database as Arc (only if thread-safety wasn't compiled in)")]
+    OpenArcOdb(#[source] std::io::Error),
+    #[error(transparent)]
+    Status(#[from] gix_worktree::status::Error),
+    #[error("The index has {} unmerged path(s) which have to be resolved first", paths.len())]
+    UnmergedPaths { paths: Vec<BString> },
+    #[error("Local changes or untracked files at {} path(s) would be overwritten", paths.len())]
+    WouldOverwrite { paths: Vec<BString> },
+    #[error("Could not remove \"{}\" from the worktree", path.display())]
+    RemoveFile { path: PathBuf, source: std::io::Error },
+    #[error(transparent)]
+    IndexCheckout(
+        #[from] gix_worktree::checkout::Error<gix_odb::find::existing_object::Error<gix_odb::store::find::Error>>,
+    ),
+    #[error(transparent)]
+    WriteIndex(#[from] gix_index::file::write::Error),
+    #[error(transparent)]
+    EditHead(#[from] crate::reference::edit::Error),
+}
+
+/// Configuration for [`Repository::switch()`][crate::Repository::switch()].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// If `true`, local changes to tracked files, unmerged paths and untracked files in the way are overwritten,
+    /// similar to `git checkout --force`.
+    ///
+    /// Otherwise, the switch fails without changing anything if any of these would be lost.
+    pub force: bool,
+}
+
+/// The outcome of [`Repository::switch()`][crate::Repository::switch()].
+#[derive(Debug)]
+pub struct Outcome {
+    /// The commit `HEAD` pointed to before the switch, or `None` if it was unborn.
+    pub previous_id: Option<ObjectId>,
+    /// The commit `HEAD` points to now.
+    pub id: ObjectId,
+    /// The branch `HEAD` points to now, or `None` if it is detached.
+    pub branch: Option<FullName>,
+    /// The worktree-relative paths of all files that were written as they were added or changed, in index order.
+    ///
+    /// Files that are excluded from the worktree by sparse checkout aren't written, and thus aren't listed.
+    pub updated: Vec<BString>,
+    /// The worktree-relative paths of all files that were removed, in index order.
+    ///
+    /// Files that were excluded from the worktree by sparse checkout are only removed from the index, and thus aren't listed.
+    pub removed: Vec<BString>,
+    /// The exit status of the `post-checkout` hook, or `None` if it doesn't exist, or the error that prevented it from running.
+    ///
+    /// Like in git, neither affects the switch which is complete at that point.
+    pub post_checkout_hook: Result<Option<ExitStatus>, crate::hook::run::Error>,
+}
+
+/// The progress ids used in [`Repository::switch()`][crate::Repository::switch()].
+///
+/// Use this information to selectively extract the progress of interest in case the parent application has custom visualization.
+#[derive(Debug, Copy, Clone)]
+pub enum ProgressId {
+    /// The amount of files checked out thus far.
+    CheckoutFiles,
+    /// The amount of bytes written in total, the aggregate of the size of the content of all files thus far.
+    BytesWritten,
+}
+
+impl From<ProgressId> for gix_features::progress::Id {
+    fn from(v: ProgressId) -> Self {
+        match v {
+            ProgressId::CheckoutFiles => *b"WSCF",
+            ProgressId::BytesWritten => *b"WSCB",
+        }
+    }
+}
+
+/// What to do with a path to get from the current state of the worktree to the one of the target.
+enum Action<'a> {
+    /// Keep the entry of the current index, leaving the worktree untouched.
+    Keep(&'a Entry),
+    /// Write the entry of the target to the worktree and the index.
+    Write(&'a Entry),
+    /// Remove the file from the worktree, unless it is excluded from it.
+    Remove { skip_worktree: bool },
+}
+
+impl Repository {
+    /// Switch the worktree of this repository to `target`, which is either the name of a local branch like `main` or a revision
+    /// like `HEAD~1`, similar to `git checkout`.
+    ///
+    /// If `target` is a local branch, `HEAD` will point to it, which fails if it is checked out in another worktree.
+    /// Otherwise `HEAD` is detached at the commit `target` resolves to. Either way, a reflog entry is written for `HEAD`.
+    ///
+    /// Only files that differ between the tree of the current `HEAD` and the one of the target are written or removed.
+    /// Local changes, be it in the index or in the worktree, are kept if the respective file doesn't differ between both trees.
+    /// Otherwise, unless `options` [force][Options::force] the switch, it fails before anything is changed, which also happens
+    /// if untracked files would be overwritten or if the index has unmerged paths.
+    /// The index is updated to match the target, including the stat information of all written files.
+    ///
+    /// The `post-checkout` hook is run afterwards if it exists, with its result being part of the [outcome][Outcome::post_checkout_hook].
+    pub fn switch<'a>(
+        &self,
+        target: impl Into<&'a BStr>,
+        mut progress: impl Progress,
+        should_interrupt: &AtomicBool,
+        options: Options,
+    ) -> Result<Outcome, Error> {
+        let target = target.into();
+        let workdir = self.work_dir().ok_or_else(|| Error::BareRepository {
+            git_dir: self.git_dir().to_owned(),
+        })?;
+        let branch = FullName::try_from(BString::from(format!("refs/heads/{target}"))).ok();
+        let reference = match &branch {
+            Some(branch) => self.try_find_reference(branch)?,
+            None => None,
+        };
+        let (branch, commit) = match branch.zip(reference) {
+            Some((branch, mut reference)) => {
+                if let Some(path) = self
+                    .worktree_branches()?
+                    .remove(&branch)
+                    .filter(|path| path.as_path() != workdir)
+                {
+                    return Err(Error::BranchCheckedOut {
+                        name: branch.as_bstr().to_owned(),
+                        path,
+                    });
+                }
+                let commit = reference
+                    .peel_to_id_in_place()?
+                    .object()?
+                    .peel_to_kind(gix_object::Kind::Commit)?;
+                (Some(branch), commit)
+            }
+            None => {
+                let commit = self
+                    .rev_parse_single(target)?
+                    .object()?
+                    .peel_to_kind(gix_object::Kind::Commit)?;
+                (None, commit)
+            }
+        };
+        let id = commit.id;
+        let target_index = self.index_from_tree(commit.peel_to_tree()?.id)?;
+
+        let previous_id = match self.head_id() {
+            Ok(id) => Some(id.detach()),
+            Err(crate::reference::head_id::Error::Unborn { .. }) => None,
+            Err(err) => return Err(err.into()),
+        };
+        let head_index = match previous_id {
+            Some(previous_id) => self.index_from_tree(self.find_object(previous_id)?.peel_to_tree()?.id)?,
+            None => gix_index::State::new(self.object_hash()),
+        };
+        let mut index = match self.index() {
+            Ok(index) => (***index).clone(),
+            Err(crate::worktree::open_index::Error::IndexFile(gix_index::file::init::Error::Io(err)))
+                if err.kind() == std::io::ErrorKind::NotFound =>
+            {
+                gix_index::State::new(self.object_hash())
+            }
+            Err(err) => return Err(err.into()),
+        };
+        if index.is_sparse() {
+            index
+                .expand_sparse_directories(|oid, buf| self.objects.find_tree_iter(oid, buf).ok())
+                .map_err(Error::ExpandSparseIndex)?;
+        }
+
+        let mut opts = self.config.checkout_options(self.git_dir())?;
+        let objects = self.objects.clone().into_arc().map_err(Error::OpenArcOdb)?;
+        let changed = {
+            let mut recorder = gix_worktree::status::Recorder::default();
+            gix_worktree::status(
+                &mut index,
+                workdir,
+                &mut recorder,
+                gix_worktree::status::content::FastEq,
+                {
+                    let objects = objects.clone();
+                    move |oid, buf| objects.find_blob(oid, buf)
+                },
+                gix_worktree::status::Options {
+                    fs: opts.fs,
+                    thread_limit: opts.thread_limit,
+                    stat: opts.stat_options,
+                },
+            )?;
+            recorder
+                .records
+                .into_iter()
+                .map(|(path, _, _)| path.to_owned())
+                .collect::<BTreeSet<_>>()
+        };
+
+        let head = stage_zero_entries_by_path(&head_index);
+        let target_entries = stage_zero_entries_by_path(&target_index);
+        let current = stage_zero_entries_by_path(&index);
+        let unmerged: BTreeSet<_> = index
+            .entries()
+            .iter()
+            .filter(|entry| entry.stage() != 0)
+            .map(|entry| entry.path(&index))
+            .collect();
+        let paths: BTreeSet<&BStr> = head
+            .keys()
+            .chain(target_entries.keys())
+            .chain(current.keys())
+            .chain(unmerged.iter())
+            .copied()
+            .collect();
+
+        let mut actions = Vec::with_capacity(paths.len());
+        let mut unmerged_paths = Vec::new();
+        let mut would_overwrite = Vec::new();
+        let mut known_dirs = BTreeSet::new();
+        for path in paths {
+            let (h, t, c) = (
+                head.get(path).copied(),
+                target_entries.get(path).copied(),
+                current.get(path).copied(),
+            );
+            let is_unmerged = unmerged.contains(path);
+            if is_unmerged {
+                if !options.force {
+                    unmerged_paths.push(path.to_owned());
+                    continue;
+                }
+            } else if is_same(h, t) || is_same(c, t) {
+                let is_reset_by_force = options.force && (!is_same(c, t) || changed.contains(path));
+                if !is_reset_by_force {
+                    if let Some(c) = c {
+                        actions.push((path, Action::Keep(c)));
+                    }
+                    continue;
+                }
+            }
+
+            let has_local_changes = is_unmerged || !is_same(c, h) || changed.contains(path);
+            let is_blocked_by_untracked = c.is_none()
+                && t.is_some()
+                && is_untracked_in_the_way(workdir, path, |prefix| {
+                    current
+                        .range::<&BStr, _>(prefix..)
+                        .next()
+                        .map_or(false, |(path, _)| path.starts_with(prefix))
+                });
+            if (has_local_changes || is_blocked_by_untracked) && !options.force {
+                would_overwrite.push(path.to_owned());
+                continue;
+            }
+            if t.is_some() && !options.force {
+                if let Some(leading) = untracked_leading_non_directory(workdir, path, &mut known_dirs, |leading| {
+                    current.contains_key(leading)
+                }) {
+                    would_overwrite.push(leading.to_owned());
+                    continue;
+                }
+            }
+            match t {
+                Some(t) => actions.push((path, Action::Write(t))),
+                None => actions.push((
+                    path,
+                    Action::Remove {
+                        skip_worktree: c.map_or(false, |c| c.flags.contains(Flags::SKIP_WORKTREE)),
+                    },
+                )),
+            }
+        }
+        if !unmerged_paths.is_empty() {
+            return Err(Error::UnmergedPaths { paths: unmerged_paths });
+        }
+        if !would_overwrite.is_empty() {
+            would_overwrite.sort();
+            would_overwrite.dedup();
+            return Err(Error::WouldOverwrite { paths: would_overwrite });
+        }
+
+        let mut removed = Vec::new();
+        for (path, action) in &actions {
+            if let Action::Remove { skip_worktree } = action {
+                if !skip_worktree {
+                    remove_file_and_empty_parents(workdir, path)?;
+                    removed.push((*path).to_owned());
+                }
+            }
+        }
+
+        let mut to_write = gix_index::State::new(self.object_hash());
+        for (path, action) in &actions {
+            if let Action::Write(entry) = action {
+                to_write.dangerously_push_entry(entry.stat, entry.id, entry.flags, entry.mode, path);
+            }
+        }
+        to_write.sort_entries();
+        if !to_write.entries().is_empty() {
+            opts.overwrite_existing = true;
+            let mut files = progress.add_child_with_id("checkout", ProgressId::CheckoutFiles.into());
+            let mut bytes = progress.add_child_with_id("writing", ProgressId::BytesWritten.into());
+            files.init(Some(to_write.entries().len()), crate::progress::count("files"));
+            bytes.init(None, crate::progress::bytes());
+
+            let start = std::time::Instant::now();
+            gix_worktree::checkout(
+                &mut to_write,
+                workdir,
+                move |oid, buf| objects.find_blob(oid, buf),
+                &mut files,
+                &mut bytes,
+                should_interrupt,
+                opts,
+            )?;
+            files.show_throughput(start);
+            bytes.show_throughput(start);
+        }
+        let updated: Vec<BString> = to_write
+            .entries()
+            .iter()
+            .filter(|entry| !entry.flags.contains(Flags::SKIP_WORKTREE))
+            .map(|entry| entry.path(&to_write).to_owned())
+            .collect();
+
+        let mut new_index = gix_index::State::new(self.object_hash());
+        for (path, action) in &actions {
+            if let Action::Keep(entry) = action {
+                new_index.dangerously_push_entry(entry.stat, entry.id, entry.flags, entry.mode, path);
+            }
+        }
+        for entry in to_write.entries() {
+            new_index.dangerously_push_entry(entry.stat, entry.id, entry.flags, entry.mode, entry.path(&to_write));
+        }
+        new_index.sort_entries();
+        gix_index::File::from_state(new_index, self.index_path()).write(Default::default())?;
+
+        let message = format!(
+            "checkout: moving from {} to {target}",
+            match self.head_name()? {
+                Some(name) => name.shorten().to_owned(),
+                None => previous_id.map(|id| id.to_string().into()).unwrap_or_default(),
+            }
+        );
+        self.edit_reference(RefEdit {
+            change: Change::Update {
+                log: LogChange {
+                    mode: RefLog::AndReference,
+                    force_create_reflog: false,
+                    message: message.into(),
+                },
+                expected: PreviousValue::Any,
+                new: match &branch {
+                    Some(branch) => Target::Symbolic(branch.clone()),
+                    None => Target::Peeled(id),
+                },
+            },
+            name: "HEAD".try_into().expect("valid"),
+            deref: false,
+        })?;
+
+        let post_checkout_hook = self.run_hook(
+            "post-checkout",
+            [
+                previous_id.unwrap_or_else(|| id.kind().null()).to_string(),
+                id.to_string(),
+                "1".into(),
+            ],
+            &[],
+        );
+        Ok(Outcome {
+            previous_id,
+            id,
+            branch,
+            updated,
+            removed,
+            post_checkout_hook,
+        })
+    }
+
+    fn index_from_tree(&self, tree: ObjectId) -> Result<gix_index::State, Error> {
+        gix_index::State::from_tree(&tree, |oid, buf| self.objects.find_tree_iter(oid, buf).ok())
+            .map_err(|err| Error::IndexFromTree { id: tree, source: err })
+    }
+}
+
+fn stage_zero_entries_by_path(state: &gix_index::State) -> BTreeMap<&BStr, &Entry> {
+    state
+        .entries()
+        .iter()
+        .filter(|entry| entry.stage() == 0)
+        .map(|entry| (entry.path(state), entry))
+        .collect()
+}
+
+fn is_same(a: Option<&Entry>, b: Option<&Entry>) -> bool {
+    match (a, b) {
+        (None, None) => true,
+        (Some(a), Some(b)) => a.id == b.id && a.mode == b.mode,
+        _ => false,
+    }
+}
+
+/// Return `true` if there is something at the untracked `path` in `workdir`, which isn't a directory that only contains
+/// tracked files as determined by `has_tracked_files_in(path/)`.
+fn is_untracked_in_the_way(workdir: &Path, path: &BStr, has_tracked_files_in: impl FnOnce(&BStr) -> bool) -> bool {
+    match std::fs::symlink_metadata(workdir.join(gix_path::from_bstr(path))) {
+        Ok(meta) if meta.is_dir() => {
+            let mut prefix = path.to_owned();
+            prefix.push(b'/');
+            !has_tracked_files_in(prefix.as_ref())
+        }
+        Ok(_) => true,
+        Err(_) => false,
+    }
+}
+
+/// Return the first leading directory of `path` in `workdir` which exists but isn't a directory and isn't tracked as determined
+/// by `is_tracked(leading)`, as writing `path` would remove it.
+///
+/// Leading directories known to exist are stored in `known_dirs` to avoid checking them once per path.
+fn untracked_leading_non_directory<'a>(
+    workdir: &Path,
+    path: &'a BStr,
+    known_dirs: &mut BTreeSet<&'a BStr>,
+    is_tracked: impl FnOnce(&BStr) -> bool,
+) -> Option<&'a BStr> {
+    for (pos, _) in path.iter().enumerate().filter(|(_, b)| **b == b'/') {
+        let leading: &BStr = path[..pos].into();
+        if known_dirs.contains(leading) {
+            continue;
+        }
+        return match std::fs::symlink_metadata(workdir.join(gix_path::from_bstr(leading))) {
+            Ok(meta) if meta.is_dir() => {
+                known_dirs.insert(leading);
+                continue;
+            }
+            Ok(_) => (!is_tracked(leading)).then_some(leading),
+            Err(_) => None,
+        };
+    }
+    None
+}
+
+/// Remove the file at `path` in `workdir` if it exists, along with all of its parent directories that became empty.
+fn remove_file_and_empty_parents(workdir: &Path, path: &BStr) -> Result<(), Error> {
+    let path = workdir.join(gix_path::from_bstr(path));
+    match std::fs::symlink_metadata(&path) {
+        Ok(meta) if !meta.is_dir() => {
+            std::fs::remove_file(&path).map_err(|err| Error::RemoveFile {
+                path: path.clone(),
+                source: err,
+            })?;
+        }
+        Ok(_) => return Ok(()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+        Err(err) => return Err(Error::RemoveFile { path, source: err }),
+    }
+    let mut dir = path.parent();
+    while let Some(parent) = dir.filter(|dir| *dir != workdir) {
+        if std::fs::remove_dir(parent).is_err() {
+            break;
+        }
+        dir = parent.parent();
+    }
+    Ok(())
+}
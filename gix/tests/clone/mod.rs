@@ -297,12 +297,10 @@ mod blocking_io {
             &out.ref_map.extra_refspecs.len() - 1,
             "mappings don't refer to non-existing implicit refspecs"
         );
-        let packed_refs = repo
-            .refs
-            .cached_packed_buffer()?
-            .expect("packed refs should be present");
+        let refs = repo.refs.as_file().expect("refs are stored in files");
+        let packed_refs = refs.cached_packed_buffer()?.expect("packed refs should be present");
         assert_eq!(
-            repo.refs.loose_iter()?.count(),
+            refs.loose_iter()?.count(),
             2,
             "HEAD and an actual symbolic ref we received"
         );
@@ -478,6 +476,99 @@ mod blocking_io {
         Ok(())
     }
 
+    #[test]
+    #[cfg(unix)]
+    fn fetch_and_checkout_runs_the_post_checkout_hook() -> crate::Result {
+        use std::os::unix::fs::PermissionsExt;
+
+        let hooks = gix_testtools::tempfile::TempDir::new()?;
+        let hook = hooks.path().join("post-checkout");
+        std::fs::write(&hook, "#!/bin/sh\necho \"$@\" > \"$GIT_DIR/post-checkout.out\"\n")?;
+        std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755))?;
+
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let mut prepare = gix::clone::PrepareFetch::new(
+            remote::repo("base").path(),
+            tmp.path(),
+            gix::create::Kind::WithWorktree,
+            Default::default(),
+            restricted().config_overrides([format!("core.hooksPath={}", hooks.path().display())]),
+        )?;
+        let (mut checkout, _out) =
+            prepare.fetch_then_checkout(gix::progress::Discard, &std::sync::atomic::AtomicBool::default())?;
+        let (repo, _) = checkout.main_worktree(gix::progress::Discard, &std::sync::atomic::AtomicBool::default())?;
+
+        let head_id = repo.head_id()?;
+        assert_eq!(
+            std::fs::read_to_string(repo.git_dir().join("post-checkout.out"))?,
+            format!("{} {head_id} 1\n", gix::hash::Kind::Sha1.null()),
+            "the null id is the previous HEAD, and a branch was checked out"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn fetch_and_checkout_sha256_repo() -> crate::Result {
+        for version in [1, 2] {
+            fetch_and_checkout_sha256_repo_with_protocol(version)?;
+        }
+        Ok(())
+    }
+
+    fn fetch_and_checkout_sha256_repo_with_protocol(version: u8) -> crate::Result {
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let remote_repo = gix_testtools::scripted_fixture_read_only("make_sha256_repo.sh")?;
+        let mut prepare = gix::clone::PrepareFetch::new(
+            remote_repo.as_path(),
+            tmp.path(),
+            gix::create::Kind::WithWorktree,
+            Default::default(),
+            gix::open::Options::isolated().config_overrides([
+                "user.name=gitoxide".to_string(),
+                "user.email=gitoxide@localhost".into(),
+                format!("protocol.version={version}"),
+            ]),
+        )?;
+        let (mut checkout, out) =
+            prepare.fetch_then_checkout(gix::progress::Discard, &std::sync::atomic::AtomicBool::default())?;
+        assert_eq!(out.ref_map.object_hash, gix_hash::Kind::Sha256);
+        assert_eq!(out.ref_map.handshake.server_protocol_version as u8, version);
+        let (repo, _) = checkout.main_worktree(gix::progress::Discard, &std::sync::atomic::AtomicBool::default())?;
+
+        assert_eq!(
+            repo.object_hash(),
+            gix_hash::Kind::Sha256,
+            "the object format of the remote is adopted"
+        );
+        assert_eq!(
+            gix::open(repo.git_dir())?.object_hash(),
+            gix_hash::Kind::Sha256,
+            "it's persisted in the configuration"
+        );
+        let remote_repo = gix::open_opts(remote_repo, restricted())?;
+        assert_eq!(repo.head_id()?, remote_repo.head_id()?);
+        assert_eq!(
+            repo.find_reference("v1")?.peel_to_id_in_place()?,
+            remote_repo.find_reference("v1")?.peel_to_id_in_place()?
+        );
+
+        let index = repo.index()?;
+        assert_eq!(index.entries().len(), 3, "All entries are known as per HEAD tree");
+        let work_dir = repo.work_dir().expect("non-bare");
+        for entry in index.entries() {
+            assert_eq!(entry.id.kind(), gix_hash::Kind::Sha256);
+            let entry_path = work_dir.join(gix_path::from_bstr(entry.path(&index)));
+            assert!(entry_path.is_file(), "{:?} not found on disk", entry_path)
+        }
+
+        let output = std::process::Command::new("git")
+            .args(["fsck", "--strict", "--no-progress"])
+            .current_dir(work_dir)
+            .output()?;
+        assert!(output.status.success(), "{output:?}");
+        Ok(())
+    }
+
     #[test]
     fn fetch_and_checkout_empty_remote_repo() -> crate::Result {
         let tmp = gix_testtools::tempfile::TempDir::new()?;
@@ -391,6 +391,10 @@ mod extensions {
             gix_hash::Kind::Sha1,
             "case-insensitive"
         );
+        assert_eq!(
+            Extensions::OBJECT_FORMAT.try_into_object_format(bcow("sha256"))?,
+            gix_hash::Kind::Sha256
+        );
         assert_eq!(
             Extensions::OBJECT_FORMAT
                 .try_into_object_format(bcow("invalid"))
@@ -399,9 +403,32 @@ mod extensions {
             "The key \"extensions.objectFormat=invalid\" was invalid"
         );
         assert!(Extensions::OBJECT_FORMAT.validate("sha1".into()).is_ok());
+        assert!(Extensions::OBJECT_FORMAT.validate("sha256".into()).is_ok());
         assert!(Extensions::OBJECT_FORMAT.validate("invalid".into()).is_err());
         Ok(())
     }
+
+    #[test]
+    fn ref_storage() -> crate::Result {
+        assert_eq!(
+            Extensions::REF_STORAGE.try_into_ref_format(bcow("files"))?,
+            gix::refs::store::Format::Files
+        );
+        assert_eq!(
+            Extensions::REF_STORAGE.try_into_ref_format(bcow("reftable"))?,
+            gix::refs::store::Format::Reftable
+        );
+        assert_eq!(
+            Extensions::REF_STORAGE
+                .try_into_ref_format(bcow("invalid"))
+                .unwrap_err()
+                .to_string(),
+            "The key \"extensions.refStorage=invalid\" was invalid"
+        );
+        assert!(Extensions::REF_STORAGE.validate("reftable".into()).is_ok());
+        assert!(Extensions::REF_STORAGE.validate("invalid".into()).is_err());
+        Ok(())
+    }
 }
 
 mod checkout {
@@ -424,6 +451,72 @@ mod checkout {
     }
 }
 
+mod commit_graph {
+    use gix::config::tree::{CommitGraph, Key};
+
+    #[test]
+    fn generation_version() -> crate::Result {
+        assert_eq!(CommitGraph::GENERATION_VERSION.try_into_u32(Ok(1))?, 1);
+        assert!(CommitGraph::GENERATION_VERSION.validate("2".into()).is_ok());
+        assert!(CommitGraph::GENERATION_VERSION.validate("-1".into()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn read_changed_paths() {
+        assert!(CommitGraph::READ_CHANGED_PATHS.validate("false".into()).is_ok());
+        assert!(CommitGraph::READ_CHANGED_PATHS.validate("maybe".into()).is_err());
+    }
+}
+
+mod gc {
+    use std::time::{Duration, SystemTime};
+
+    use gix::config::tree::{Gc, Key};
+
+    use crate::config::tree::bcow;
+
+    #[test]
+    fn prune_expire() -> crate::Result {
+        let now = SystemTime::now();
+        assert_eq!(Gc::PRUNE_EXPIRE.try_into_expiry(bcow("never"), now)?, None);
+        assert_eq!(Gc::PRUNE_EXPIRE.try_into_expiry(bcow("now"), now)?, Some(now));
+        let two_weeks_ago = Gc::PRUNE_EXPIRE
+            .try_into_expiry(bcow("2.weeks.ago"), now)?
+            .expect("a point in time");
+        let expected = now - Duration::from_secs(14 * 24 * 60 * 60);
+        assert!(two_weeks_ago <= expected && two_weeks_ago > expected - Duration::from_secs(1));
+        assert!(Gc::PRUNE_EXPIRE.validate("1 day ago".into()).is_ok());
+        assert_eq!(
+            Gc::PRUNE_EXPIRE
+                .try_into_expiry(bcow("whenever"), now)
+                .unwrap_err()
+                .to_string(),
+            "The date format at key \"gc.pruneExpire=whenever\" was invalid"
+        );
+        assert!(Gc::PRUNE_EXPIRE.validate("whenever".into()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn reflog_expire() -> crate::Result {
+        let now = SystemTime::now();
+        for key in [&Gc::REFLOG_EXPIRE, &Gc::REFLOG_EXPIRE_UNREACHABLE] {
+            assert_eq!(key.try_into_expiry(bcow("false"), now)?, None);
+            assert_eq!(key.try_into_expiry(bcow("all"), now)?, Some(now));
+            assert!(key.validate("90.days.ago".into()).is_ok());
+        }
+        assert_eq!(
+            Gc::REFLOG_EXPIRE_UNREACHABLE
+                .try_into_expiry(bcow("whenever"), now)
+                .unwrap_err()
+                .to_string(),
+            "The date format at key \"gc.reflogExpireUnreachable=whenever\" was invalid"
+        );
+        Ok(())
+    }
+}
+
 mod pack {
     use gix::config::tree::{Key, Pack};
 
@@ -0,0 +1,32 @@
+#!/bin/bash
+set -eu -o pipefail
+
+function commit() {
+  local name=${1:?file name and message}
+  echo "$name" > "$name"
+  git add "$name"
+  git commit -qm "$name"
+}
+
+git init -q base
+(cd base
+  git checkout -q -b main
+  commit 1
+  commit 2
+)
+
+git clone -q base clone
+(cd base
+  commit 3
+)
+
+(cd clone
+  git fetch -q
+  git checkout -q -b feature
+  commit f1
+  commit f2
+  git branch -q --set-upstream-to origin/main feature
+  git checkout -q main
+  git branch merged main~1
+  git branch unmerged feature
+)
@@ -0,0 +1,44 @@
+#!/bin/bash
+set -eu -o pipefail
+
+function commit() {
+  local message=${1:?message}
+  git add -A
+  git commit -q --allow-empty -m "$message"
+}
+
+git init -q repo
+(cd repo
+  git checkout -q -b main
+  echo a > a
+  mkdir -p dir/sub
+  echo c > dir/sub/c
+  commit initial
+
+  echo b > b
+  commit "add b"
+
+  git checkout -q -b side
+  echo side > dir/sub/c
+  commit "change c on side"
+  echo side > a
+  commit "change a on side"
+
+  git checkout -q main
+  echo main > dir/sub/c
+  commit "change c on main"
+  git merge -q --no-ff -m merge side >/dev/null || true
+  echo merged > dir/sub/c
+  commit merge
+
+  git rm -q b
+  commit "remove b"
+  commit empty
+
+  git commit-graph write --no-progress --reachable --changed-paths
+  cp .git/objects/info/commit-graph ../commit-graph
+
+  for path in a b dir dir/sub/c; do
+    git log --format=%H --full-history -- $path > ../log-$(echo $path | tr / -)
+  done
+)
@@ -0,0 +1,17 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q repo
+(cd repo
+  git checkout -q -b main
+  echo "*.bin -delta" > .gitattributes
+  seq 1 2000 > a.bin
+  seq 1 2000 | sed 's/^/text /' > a.txt
+  git add .
+  git commit -qm "first"
+
+  seq 2 2001 > a.bin
+  seq 2 2001 | sed 's/^/text /' > a.txt
+  git add .
+  git commit -qm "second"
+)
@@ -0,0 +1,70 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q worktree
+(cd worktree
+  git checkout -q -b main
+
+  seq 1 10 > content
+  seq 11 20 > renamed
+  seq 21 30 > modified-and-deleted
+  seq 31 40 > renamed-and-deleted
+  seq 41 50 > renamed-twice
+  mkdir dir
+  seq 51 60 > dir/unchanged
+  git add . && git commit -q -m "base"
+  git tag base
+
+  git checkout -q -b clean-ours base
+  sed -i.bak 's/^1$/one/' content && rm content.bak
+  git mv renamed renamed-by-ours
+  git commit -q -am "ours, clean"
+
+  git checkout -q -b clean-theirs base
+  sed -i.bak 's/^10$/ten/' content && rm content.bak
+  sed -i.bak 's/^20$/twenty/' renamed && rm renamed.bak
+  echo new > added-by-theirs
+  git add . && git commit -q -m "theirs, clean"
+
+  git checkout -q -b conflict-ours base
+  sed -i.bak 's/^5$/five by ours/' content && rm content.bak
+  echo ours >> modified-and-deleted
+  git mv renamed-and-deleted renamed-and-deleted-by-ours
+  git mv renamed-twice renamed-twice-by-ours
+  echo ours > added-twice
+  echo ours > file-or-dir
+  git add . && git commit -q -m "ours, conflicting"
+
+  git checkout -q -b conflict-theirs base
+  sed -i.bak 's/^5$/five by theirs/' content && rm content.bak
+  git rm -q modified-and-deleted renamed-and-deleted
+  git mv renamed-twice renamed-twice-by-theirs
+  echo theirs > added-twice
+  mkdir file-or-dir
+  echo theirs > file-or-dir/file
+  git add . && git commit -q -m "theirs, conflicting"
+
+  git checkout -q -b attributes-base base
+  seq 61 70 > union
+  seq 71 80 > binary
+  git add . && git commit -q -m "attributes base"
+
+  git checkout -q -b attributes-ours attributes-base
+  sed -i.bak 's/^65$/sixty-five by ours/' union && rm union.bak
+  sed -i.bak 's/^71$/seventy-one/' binary && rm binary.bak
+  git commit -q -am "attributes, ours"
+
+  git checkout -q -b attributes-theirs attributes-base
+  sed -i.bak 's/^65$/sixty-five by theirs/' union && rm union.bak
+  sed -i.bak 's/^80$/eighty/' binary && rm binary.bak
+  git commit -q -am "attributes, theirs"
+
+  git merge-tree --write-tree clean-ours clean-theirs > ../clean.baseline
+)
+
+git clone -q --bare worktree bare.git
+(cd bare.git
+  echo "union merge=union" > info/attributes
+  echo "binary merge=binary" >> info/attributes
+  git merge-tree --write-tree --no-messages attributes-ours attributes-theirs > ../attributes.baseline || test $? -eq 1
+)
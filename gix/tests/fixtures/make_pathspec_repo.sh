@@ -0,0 +1,11 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+mkdir -p dir/sub other
+touch a.c b.txt dir/file dir/sub/f.c dir/sub/g.txt other/a.c
+cat <<EOT > .gitattributes
+*.c text
+dir/** generated
+EOT
+git add . && git commit -q -m "init"
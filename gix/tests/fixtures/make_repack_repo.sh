@@ -0,0 +1,39 @@
+#!/bin/bash
+set -eu -o pipefail
+
+function commit() {
+  local name=${1:?file name and message}
+  echo "$name" > "$name"
+  git add "$name"
+  git commit -qm "$name"
+}
+
+git init -q repo
+(cd repo
+  git checkout -q -b main
+  commit 1
+  commit 2
+  git tag -m "annotated" annotated
+  git repack -q -d
+
+  git checkout -q -b dropped
+  commit dropped
+  git rev-parse HEAD > ../dropped.id
+  git repack -q -d
+  git checkout -q main
+  git branch -q -D dropped
+  git reflog expire --expire=all --all
+
+  commit 3
+  commit reflog-only
+  git rev-parse HEAD > ../reflog-only.id
+  git reset -q --hard HEAD~1
+
+  echo staged > staged
+  git add staged
+  git rev-parse :staged > ../staged.id
+
+  echo unreachable > unreachable
+  git hash-object -w unreachable > ../unreachable.id
+  rm unreachable
+)
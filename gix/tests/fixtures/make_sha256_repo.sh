@@ -0,0 +1,21 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q --object-format=sha256
+git checkout -q -b main
+
+mkdir dir
+echo a > a
+echo b > dir/b
+git add . && git commit -q -m c1
+echo c >> a
+git commit -q -am c2
+git tag -a -m "annotated" v1
+git branch other HEAD~1
+
+git repack -adq
+git pack-refs --all
+
+echo d > dir/c
+git add dir/c && git commit -q -m c3
+git commit-graph write --no-progress --reachable
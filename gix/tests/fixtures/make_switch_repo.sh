@@ -0,0 +1,24 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q repo
+(cd repo
+  git checkout -q -b main
+  mkdir dir
+  echo a > a
+  echo b > dir/b
+  echo c > c
+  git add .
+  git commit -qm base
+
+  git checkout -q -b other
+  echo a2 > a
+  git rm -q c
+  echo new > dir/new
+  mkdir only-other
+  echo x > only-other/x
+  git add .
+  git commit -qm other
+
+  git checkout -q main
+)
@@ -18,6 +18,8 @@ mod init;
 #[cfg(not(feature = "regex"))]
 mod object;
 #[cfg(not(feature = "regex"))]
+mod pathspec;
+#[cfg(not(feature = "regex"))]
 mod reference;
 #[cfg(not(feature = "regex"))]
 mod remote;
@@ -130,4 +130,40 @@ mod ancestors {
         }
         Ok(())
     }
+
+    #[test]
+    fn limit_to_path() -> crate::Result {
+        let dir = gix_testtools::scripted_fixture_read_only("make_changed_paths_repo.sh")?;
+        for read_changed_paths in [true, false] {
+            let repo = crate::util::named_subrepo_opts(
+                "make_changed_paths_repo.sh",
+                "repo",
+                crate::restricted().config_overrides([format!("commitGraph.readChangedPaths={read_changed_paths}")]),
+            )?;
+            assert!(
+                repo.commit_graph()?.files()[0].bloom_settings().is_some(),
+                "the fixture provides changed-path Bloom filters"
+            );
+            for path in ["a", "b", "dir", "dir/sub/c", "missing"] {
+                let mut actual = repo
+                    .head_id()?
+                    .ancestors()
+                    .limit_to_path(path)
+                    .all()?
+                    .map(|id| id.map(|id| id.detach()))
+                    .collect::<Result<Vec<_>, _>>()?;
+                actual.sort();
+                let mut expected = match std::fs::read_to_string(dir.join(format!("log-{}", path.replace('/', "-")))) {
+                    Ok(log) => log.lines().map(hex_to_id).collect(),
+                    Err(_) => Vec::new(),
+                };
+                expected.sort();
+                assert_eq!(
+                    actual, expected,
+                    "{path}: same as `git log --full-history`, read_changed_paths={read_changed_paths}"
+                );
+            }
+        }
+        Ok(())
+    }
 }
@@ -126,3 +126,62 @@ mod non_bare {
         Ok(())
     }
 }
+
+mod object_format {
+    use gix::bstr::ByteSlice;
+    use gix_testtools::tempfile;
+
+    #[test]
+    fn sha256_repositories_can_be_written_to_and_are_valid_for_git() -> crate::Result {
+        let tmp = tempfile::tempdir()?;
+        let repo: gix::Repository = gix::ThreadSafeRepository::init_opts(
+            tmp.path(),
+            gix::create::Kind::WithWorktree,
+            gix::create::Options {
+                object_hash: gix_hash::Kind::Sha256,
+                ..Default::default()
+            },
+            gix::open::Options::isolated().config_overrides(["user.name=a", "user.email=b"]),
+        )?
+        .into();
+        assert_eq!(repo.object_hash(), gix_hash::Kind::Sha256);
+        let config = std::fs::read_to_string(repo.git_dir().join("config"))?;
+        assert!(config.contains("repositoryformatversion = 1"), "{config}");
+        assert!(config.contains("[extensions]\n\tobjectformat = sha256"), "{config}");
+
+        let blob = repo.write_blob(b"content\n")?;
+        assert_eq!(blob.kind(), gix_hash::Kind::Sha256);
+        let tree = repo.write_object(&gix::objs::Tree {
+            entries: vec![gix::objs::tree::Entry {
+                mode: gix::objs::tree::EntryMode::Blob,
+                filename: "file".into(),
+                oid: blob.detach(),
+            }],
+        })?;
+        let commit = repo.commit("HEAD", "initial", tree, gix::commit::NO_PARENT_IDS)?;
+        let commit = repo.commit("HEAD", "second", tree, [commit])?;
+        let outcome = repo.write_commit_graph_for_references(
+            gix::progress::Discard,
+            &std::sync::atomic::AtomicBool::default(),
+            repo.commit_graph_options()?,
+        )?;
+        assert_eq!(outcome.num_commits, 2);
+
+        let output = std::process::Command::new("git")
+            .args(["fsck", "--strict", "--no-progress"])
+            .current_dir(tmp.path())
+            .output()?;
+        assert!(output.status.success(), "{output:?}");
+        let output = std::process::Command::new("git")
+            .args(["commit-graph", "verify", "--no-progress"])
+            .current_dir(tmp.path())
+            .output()?;
+        assert!(output.status.success(), "{output:?}");
+        let output = std::process::Command::new("git")
+            .args(["rev-parse", "HEAD"])
+            .current_dir(tmp.path())
+            .output()?;
+        assert_eq!(output.stdout.trim(), commit.to_string().as_bytes());
+        Ok(())
+    }
+}
@@ -0,0 +1,86 @@
+use gix::bstr::BStr;
+use gix::worktree::cache::state::attributes::Source;
+
+use crate::util::named_repo;
+
+fn paths<'a>(entries: impl IntoIterator<Item = &'a BStr>) -> Vec<String> {
+    entries.into_iter().map(ToString::to_string).collect()
+}
+
+#[test]
+fn index_entries_with_paths() -> crate::Result {
+    let repo = named_repo("make_pathspec_repo.sh")?;
+    let index = repo.index()?;
+    for (specs, expected) in [
+        (&["dir"][..], &["dir/file", "dir/sub/f.c", "dir/sub/g.txt"][..]),
+        (&["dir/sub/*.c", "dir/sub/*.txt"], &["dir/sub/f.c", "dir/sub/g.txt"]),
+        (&["*.c", ":!dir"], &["a.c", "other/a.c"]),
+        (&[":(attr:text)"], &["a.c", "dir/sub/f.c", "other/a.c"]),
+        (&[":(attr:text generated)"], &["dir/sub/f.c"]),
+        (&[":(attr:!generated)*.txt"], &["b.txt"]),
+    ] {
+        let mut pathspec = repo.pathspec(specs, false, &index, Source::IdMapping)?;
+        assert_eq!(
+            pathspec.attributes().is_some(),
+            specs.iter().any(|s| s.contains("attr:")),
+            "attributes are only set up if needed"
+        );
+        let actual = paths(pathspec.index_entries_with_paths(&index).map(|(path, _)| path));
+        assert_eq!(actual, expected, "{specs:?}");
+    }
+    Ok(())
+}
+
+#[test]
+fn worktree_pathspec_and_index_with_included_entries() -> crate::Result {
+    let repo = named_repo("make_pathspec_repo.sh")?;
+    let index = repo.index()?;
+    let worktree = repo.worktree().expect("non-bare");
+    let mut pathspec = worktree.pathspec([":(attr:text)dir", "b.txt"])?;
+    assert!(pathspec.attributes().is_some(), "attributes are set up if needed");
+
+    let filtered = pathspec.index_with_included_entries(&index);
+    assert_eq!(
+        paths(filtered.entries().iter().map(|e| e.path(&filtered))),
+        ["b.txt", "dir/sub/f.c"]
+    );
+    assert_eq!(filtered.timestamp(), index.timestamp());
+    assert_eq!(
+        filtered.entry_by_path_and_stage("dir/sub/f.c".into(), 0).map(|e| e.id),
+        index.entry_by_path_and_stage("dir/sub/f.c".into(), 0).map(|e| e.id),
+        "entries stay sorted and can be looked up"
+    );
+    Ok(())
+}
+
+#[test]
+fn tree_entries() -> crate::Result {
+    let repo = named_repo("make_pathspec_repo.sh")?;
+    let index = repo.index()?;
+    let tree = repo.head_commit()?.tree()?;
+    let mut pathspec = repo.pathspec(["dir/sub/*.c", "b.txt"], false, &index, Source::IdMapping)?;
+    let entries = pathspec.tree_entries(&tree)?;
+    assert_eq!(
+        paths(entries.iter().map(|e| e.filepath.as_ref())),
+        ["b.txt", "dir/sub/f.c"]
+    );
+
+    assert!(pathspec.is_included("dir/sub/other.c", None));
+    assert!(!pathspec.is_included("other/a.c", None));
+    Ok(())
+}
+
+#[test]
+fn invalid_pathspecs_are_rejected() -> crate::Result {
+    let repo = named_repo("make_pathspec_repo.sh")?;
+    let index = repo.index()?;
+    assert!(matches!(
+        repo.pathspec([":(tp)dir"], false, &index, Source::IdMapping),
+        Err(gix::pathspec::init::Error::Parse(_))
+    ));
+    assert!(matches!(
+        repo.pathspec(["../outside"], false, &index, Source::IdMapping),
+        Err(gix::pathspec::init::Error::Normalize(_))
+    ));
+    Ok(())
+}
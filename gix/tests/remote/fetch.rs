@@ -106,6 +106,56 @@ mod blocking_and_async_io {
         Ok(())
     }
 
+    #[test]
+    #[cfg(feature = "blocking-network-client")]
+    fn fetch_packs_refs_if_there_are_too_many_loose_ones() -> crate::Result {
+        let loose_refs = |repo: &gix::Repository| -> crate::Result<Vec<gix::refs::Reference>> {
+            Ok(repo
+                .refs
+                .as_file()
+                .expect("files")
+                .loose_iter()?
+                .collect::<Result<_, _>>()?)
+        };
+        for (threshold, expect_packed) in [(None, false), (Some("1000"), false), (Some("1"), true)] {
+            let (mut repo, _tmp) = repo_rw("two-origins");
+            if let Some(threshold) = threshold {
+                let mut config = repo.config_snapshot_mut();
+                config.set_raw_value("gitoxide", Some("fetch".into()), "packRefsThreshold", threshold)?;
+                config.commit()?;
+            }
+            let mut remote = repo
+                .head()?
+                .into_remote(Fetch)
+                .expect("present")?
+                .with_fetch_tags(fetch::Tags::Included);
+            remote.replace_refspecs(Some("HEAD:refs/remotes/origin/does-not-yet-exist"), Fetch)?;
+            remote
+                .connect(Fetch)?
+                .prepare_fetch(gix::progress::Discard, Default::default())?
+                .receive(gix::progress::Discard, &AtomicBool::default())?;
+
+            let new_ref_is_loose = loose_refs(&repo)?
+                .iter()
+                .any(|r| r.name.as_bstr() == "refs/remotes/origin/does-not-yet-exist");
+            assert_eq!(new_ref_is_loose, !expect_packed, "{threshold:?}");
+            if expect_packed {
+                assert!(
+                    loose_refs(&repo)?
+                        .iter()
+                        .all(|r| r.target.kind() == gix::refs::Kind::Symbolic),
+                    "all references are packed"
+                );
+            }
+            assert!(
+                repo.try_find_reference("refs/remotes/origin/does-not-yet-exist")?
+                    .is_some(),
+                "the reference can always be found"
+            );
+        }
+        Ok(())
+    }
+
     #[maybe_async::test(
         feature = "blocking-network-client",
         async(feature = "async-network-client-async-std", async_std::test)
@@ -0,0 +1,241 @@
+use gix::{branch, bstr::ByteSlice, remote};
+
+use crate::util::restricted;
+
+fn repo_rw() -> crate::Result<(gix::Repository, gix_testtools::tempfile::TempDir)> {
+    let dir = gix_testtools::scripted_fixture_writable("make_branch_repo.sh")?;
+    let repo = gix::open_opts(dir.path().join("clone"), restricted())?;
+    Ok((repo, dir))
+}
+
+fn local_config(repo: &gix::Repository) -> crate::Result<gix::config::File<'static>> {
+    Ok(gix::config::File::from_path_no_includes(
+        repo.common_dir().join("config"),
+        gix::config::Source::Local,
+    )?)
+}
+
+#[test]
+fn create_and_reset_with_force() -> crate::Result {
+    let (repo, _keep) = repo_rw()?;
+    let branch = repo.branch_create("new", "main~1", false)?;
+    assert_eq!(branch.name().as_bstr(), "refs/heads/new");
+    assert_eq!(branch.id(), repo.rev_parse_single("main~1")?);
+
+    assert!(matches!(
+        repo.branch_create("new", "feature", false),
+        Err(branch::create::Error::AlreadyExists { .. })
+    ));
+    let branch = repo.branch_create("new", "feature", true)?;
+    assert_eq!(branch.id(), repo.rev_parse_single("feature")?);
+    let branch = repo.find_reference("new")?;
+    let mut log = branch.log_iter();
+    let last = log.rev()?.expect("reflog present").next().expect("one line")?;
+    assert_eq!(last.message, "branch: Reset to feature");
+
+    assert!(
+        matches!(
+            repo.branch_create("main", "feature", true),
+            Err(branch::create::Error::CheckedOut { .. })
+        ),
+        "the branch checked out in the worktree can't be reset"
+    );
+    assert!(matches!(
+        repo.branch_create("tree", "main^{tree}", false),
+        Err(branch::create::Error::NotACommit { .. })
+    ));
+    Ok(())
+}
+
+#[test]
+fn branches_checked_out_in_the_main_worktree_are_protected_in_linked_worktrees() -> crate::Result {
+    let (repo, dir) = repo_rw()?;
+    let mut linked = repo.worktree_add(dir.path().join("linked"), "feature")?;
+    assert!(
+        matches!(
+            linked.branch_delete("main", true),
+            Err(branch::delete::Error::CheckedOut { .. })
+        ),
+        "main is checked out in the main worktree"
+    );
+    assert!(matches!(
+        linked.branch_create("main", "feature", true),
+        Err(branch::create::Error::CheckedOut { .. })
+    ));
+    assert!(matches!(
+        linked.branch_delete("feature", true),
+        Err(branch::delete::Error::CheckedOut { .. })
+    ));
+    assert_eq!(
+        linked
+            .branches()?
+            .into_iter()
+            .filter(|b| b.worktree_dir.is_some())
+            .map(|b| b.name.as_bstr().to_owned())
+            .collect::<Vec<_>>(),
+        ["refs/heads/feature", "refs/heads/main"]
+    );
+    Ok(())
+}
+
+#[test]
+fn renaming_checked_out_branches_updates_the_head_of_all_worktrees() -> crate::Result {
+    let (mut repo, dir) = repo_rw()?;
+    let mut linked = repo.worktree_add(dir.path().join("linked"), "feature")?;
+    repo.rename_reference("refs/heads/feature", "refs/heads/renamed", "renamed")?;
+    assert_eq!(
+        linked.head_name()?.expect("not detached").as_bstr(),
+        "refs/heads/renamed",
+        "the HEAD of the linked worktree follows the branch"
+    );
+    assert_eq!(repo.head_name()?.expect("not detached").as_bstr(), "refs/heads/main");
+
+    linked.rename_reference("refs/heads/main", "refs/heads/trunk", "renamed")?;
+    assert_eq!(
+        repo.head_name()?.expect("not detached").as_bstr(),
+        "refs/heads/trunk",
+        "the HEAD of the main worktree follows the branch"
+    );
+    assert_eq!(
+        linked.head_name()?.expect("not detached").as_bstr(),
+        "refs/heads/renamed"
+    );
+    Ok(())
+}
+
+#[test]
+fn delete_requires_merged_branches_unless_forced() -> crate::Result {
+    let (mut repo, _keep) = repo_rw()?;
+    assert!(
+        matches!(
+            repo.branch_delete("unmerged", false),
+            Err(branch::delete::Error::NotFullyMerged { .. })
+        ),
+        "without upstream, the branch has to be merged into HEAD"
+    );
+    let expected = repo.rev_parse_single("merged")?.detach();
+    assert_eq!(repo.branch_delete("merged", false)?, expected);
+    assert!(repo.try_find_reference("merged")?.is_none());
+
+    assert!(matches!(
+        repo.branch_delete("main", true),
+        Err(branch::delete::Error::CheckedOut { .. })
+    ));
+    assert!(
+        matches!(
+            repo.branch_delete("feature", false),
+            Err(branch::delete::Error::NotFullyMerged { .. })
+        ),
+        "with upstream, the branch has to be merged into the upstream"
+    );
+
+    repo.branch_delete("feature", true)?;
+    assert!(repo.try_find_reference("feature")?.is_none());
+    assert!(
+        !repo.refs.reflog_exists("refs/heads/feature".try_into()?)?,
+        "the reflog is deleted as well"
+    );
+    assert_eq!(repo.config_snapshot().string("branch.feature.merge"), None);
+    assert!(
+        local_config(&repo)?.section("branch", Some("feature".into())).is_err(),
+        "the branch configuration is removed from the configuration file"
+    );
+    Ok(())
+}
+
+#[test]
+fn upstream_can_be_queried_set_and_unset() -> crate::Result {
+    let (mut repo, _keep) = repo_rw()?;
+    assert_eq!(
+        repo.branch_upstream("feature")?,
+        Some(branch::Upstream {
+            remote: remote::Name::Symbol("origin".into()),
+            merge: "refs/heads/main".try_into()?,
+            tracking: Some("refs/remotes/origin/main".try_into()?),
+        })
+    );
+    assert_eq!(repo.branch_upstream("merged")?, None);
+
+    let main: gix::refs::FullName = "refs/heads/main".try_into()?;
+    repo.branch_set_upstream("merged", ".", main.as_ref())?;
+    let upstream = repo.branch_upstream("merged")?.expect("just set");
+    assert_eq!(upstream.remote.as_bstr(), ".");
+    assert_eq!(
+        upstream.tracking.as_ref(),
+        Some(&main),
+        "local branches track themselves"
+    );
+    let config = local_config(&repo)?;
+    assert_eq!(
+        config
+            .string("branch", Some("merged".into()), "merge")
+            .as_deref()
+            .map(|s| s.as_bytes()),
+        Some(b"refs/heads/main".as_ref()),
+        "the configuration is persisted"
+    );
+
+    repo.branch_unset_upstream("feature")?;
+    assert_eq!(repo.branch_upstream("feature")?, None);
+    assert_eq!(
+        local_config(&repo)?.string("branch", Some("feature".into()), "remote"),
+        None
+    );
+    assert!(matches!(
+        repo.branch_set_upstream("missing", "origin", main.as_ref()),
+        Err(branch::upstream::set::Error::NotFound { .. })
+    ));
+    Ok(())
+}
+
+#[test]
+fn ahead_behind_and_listing() -> crate::Result {
+    let (repo, _keep) = repo_rw()?;
+    assert_eq!(repo.branch_ahead_behind("feature")?, Some((2, 1)));
+    assert_eq!(repo.branch_ahead_behind("main")?, Some((0, 1)));
+    assert_eq!(repo.branch_ahead_behind("merged")?, None, "no upstream configured");
+    assert_eq!(repo.branch_ahead_behind("missing")?, None);
+
+    let branches = repo.branches()?;
+    assert_eq!(
+        branches
+            .iter()
+            .map(|b| (b.name.as_bstr().to_str_lossy().into_owned(), b.ahead_behind))
+            .collect::<Vec<_>>(),
+        vec![
+            ("refs/heads/feature".to_string(), Some((2, 1))),
+            ("refs/heads/main".into(), Some((0, 1))),
+            ("refs/heads/merged".into(), None),
+            ("refs/heads/unmerged".into(), None),
+        ]
+    );
+    assert_eq!(
+        branches
+            .iter()
+            .filter(|b| b.worktree_dir.is_some())
+            .map(|b| b.name.as_bstr())
+            .collect::<Vec<_>>(),
+        ["refs/heads/main"],
+        "only the checked out branch has a worktree"
+    );
+    Ok(())
+}
+
+#[test]
+fn ahead_behind_with_commit_graph() -> crate::Result {
+    let (repo, _keep) = repo_rw()?;
+    repo.write_commit_graph(
+        Some(repo.rev_parse_single("main")?.detach()),
+        gix::progress::Discard,
+        &std::sync::atomic::AtomicBool::default(),
+        repo.commit_graph_options()?,
+    )?;
+    assert_eq!(
+        repo.commit_graph()?.num_commits(),
+        2,
+        "commits of other branches aren't in the graph"
+    );
+    assert_eq!(repo.branch_ahead_behind("feature")?, Some((2, 1)));
+    assert_eq!(repo.branch_ahead_behind("main")?, Some((0, 1)));
+    Ok(())
+}
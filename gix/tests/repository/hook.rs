@@ -0,0 +1,95 @@
+#[cfg(unix)]
+fn write_hook(hooks_dir: &std::path::Path, name: &str, script: &str) -> crate::Result {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::create_dir_all(hooks_dir)?;
+    let path = hooks_dir.join(name);
+    std::fs::write(&path, format!("#!/bin/sh\n{script}"))?;
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn run_hook_passes_arguments_input_and_environment() -> crate::Result {
+    let (repo, _keep) = crate::repo_rw("make_basic_repo.sh")?;
+    let hooks_dir = repo.git_dir().join("hooks");
+    write_hook(
+        &hooks_dir,
+        "test-hook",
+        "{ echo \"$@\"; cat; pwd; echo \"$GIT_DIR\"; echo \"$GIT_INDEX_FILE\"; } > \"$GIT_DIR/hook.out\"\nexit 3\n",
+    )?;
+
+    let status = repo
+        .run_hook("test-hook", ["a", "b"], b"input\n")?
+        .expect("hook was run");
+    assert_eq!(status.code(), Some(3), "the exit status is passed on");
+    let git_dir = gix_path::realpath(repo.git_dir())?;
+    let work_dir = gix_path::realpath(repo.work_dir().expect("non-bare"))?;
+    let out = std::fs::read_to_string(repo.git_dir().join("hook.out"))?;
+    let lines: Vec<_> = out.lines().collect();
+    assert_eq!(&lines[..2], ["a b", "input"]);
+    assert_eq!(gix_path::realpath(lines[2])?, work_dir, "hooks run in the worktree");
+    assert_eq!(gix_path::realpath(lines[3])?, git_dir);
+    assert_eq!(gix_path::realpath(lines[4])?, git_dir.join("index"));
+
+    assert!(
+        repo.run_hook("does-not-exist", None::<&str>, &[])?.is_none(),
+        "missing hooks are ignored"
+    );
+    std::fs::write(hooks_dir.join("not-executable"), "#!/bin/sh\nexit 1\n")?;
+    assert!(
+        repo.run_hook("not-executable", None::<&str>, &[])?.is_none(),
+        "hooks that can't be executed are ignored"
+    );
+
+    let repo = gix::open_opts(repo.git_dir(), crate::restricted().with(gix_sec::Trust::Reduced))?;
+    assert!(
+        repo.run_hook("test-hook", None::<&str>, &[])?.is_none(),
+        "hooks never run in untrusted repositories"
+    );
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn run_hook_uses_core_hooks_path_relative_to_the_worktree() -> crate::Result {
+    let (repo, _keep) = crate::repo_rw("make_basic_repo.sh")?;
+    let work_dir = repo.work_dir().expect("non-bare").to_owned();
+    write_hook(&work_dir.join("custom-hooks"), "test-hook", "exit 0\n")?;
+    assert!(repo.run_hook("test-hook", None::<&str>, &[])?.is_none());
+
+    let repo = gix::open_opts(
+        repo.git_dir(),
+        crate::restricted().config_overrides(["core.hooksPath=custom-hooks"]),
+    )?;
+    assert!(repo.run_hook("test-hook", None::<&str>, &[])?.expect("found").success());
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn run_commit_hooks_may_reject_the_commit_or_edit_its_message() -> crate::Result {
+    let (repo, _keep) = crate::repo_rw("make_basic_repo.sh")?;
+    assert_eq!(
+        repo.run_commit_hooks("unchanged")?,
+        "unchanged",
+        "without hooks, the message is returned as is"
+    );
+
+    let hooks_dir = repo.git_dir().join("hooks");
+    write_hook(&hooks_dir, "commit-msg", "echo \"Signed-off-by: hook\" >> \"$1\"\n")?;
+    assert_eq!(repo.run_commit_hooks("message\n")?, "message\nSigned-off-by: hook\n");
+
+    write_hook(&hooks_dir, "commit-msg", "printf '' > \"$1\"\n")?;
+    assert!(matches!(
+        repo.run_commit_hooks("message"),
+        Err(gix::hook::commit::Error::EmptyMessage)
+    ));
+
+    write_hook(&hooks_dir, "pre-commit", "exit 1\n")?;
+    assert!(matches!(
+        repo.run_commit_hooks("message"),
+        Err(gix::hook::commit::Error::Rejected { name: "pre-commit", .. })
+    ));
+    Ok(())
+}
@@ -0,0 +1,628 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::AtomicBool,
+    time::{Duration, SystemTime},
+};
+
+use gix::{
+    commitgraph::graph::write::Split,
+    maintenance::{expire_reflogs, pack_refs, prune, repack},
+};
+
+use crate::util::{hex_to_id, restricted};
+
+fn repo_rw() -> crate::Result<(gix::Repository, gix_testtools::tempfile::TempDir)> {
+    let dir = gix_testtools::scripted_fixture_writable("make_repack_repo.sh")?;
+    let repo = gix::open_opts(dir.path().join("repo"), restricted())?;
+    Ok((repo, dir))
+}
+
+fn id_in(dir: &Path, name: &str) -> crate::Result<gix_hash::ObjectId> {
+    Ok(hex_to_id(std::fs::read_to_string(dir.join(name))?.trim()))
+}
+
+fn pack_indices(repo: &gix::Repository) -> crate::Result<Vec<PathBuf>> {
+    let mut out: Vec<_> = std::fs::read_dir(repo.objects.store_ref().path().join("pack"))?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .filter(|path| path.extension().map_or(false, |ext| ext == "idx"))
+        .collect();
+    out.sort();
+    Ok(out)
+}
+
+fn is_loose(repo: &gix::Repository, id: gix_hash::ObjectId) -> bool {
+    gix_odb::loose::Store::at(repo.objects.store_ref().path(), repo.object_hash()).contains(id)
+}
+
+fn in_the_future() -> Option<SystemTime> {
+    Some(SystemTime::now() + Duration::from_secs(60 * 60))
+}
+
+#[test]
+fn reachable_objects_include_reflogs_and_the_index() -> crate::Result {
+    let (repo, dir) = repo_rw()?;
+    let reachable = repo.reachable_objects(gix::progress::Discard, &AtomicBool::default())?;
+    assert!(reachable.contains(&repo.head_id()?.detach()));
+    assert!(reachable.contains(&repo.rev_parse_single("annotated")?.detach()));
+    assert!(reachable.contains(&id_in(dir.path(), "reflog-only.id")?));
+    assert!(reachable.contains(&id_in(dir.path(), "staged.id")?));
+    assert!(!reachable.contains(&id_in(dir.path(), "dropped.id")?));
+    assert!(!reachable.contains(&id_in(dir.path(), "unreachable.id")?));
+    assert_eq!(
+        reachable.len(),
+        14,
+        "4 commits with their trees and blobs, the tag, and the staged blob"
+    );
+    Ok(())
+}
+
+#[test]
+fn repack_with_loose_unreachable_objects() -> crate::Result {
+    let (repo, dir) = repo_rw()?;
+    let reachable = repo.reachable_objects(gix::progress::Discard, &AtomicBool::default())?;
+    let previous_packs = pack_indices(&repo)?;
+    assert_eq!(previous_packs.len(), 2);
+
+    let outcome = repo.repack(
+        gix::progress::Discard,
+        &AtomicBool::default(),
+        repack::Options {
+            unreachable: repack::Unreachable::Loose { expire: None },
+            ..Default::default()
+        },
+    )?;
+    let pack = outcome.pack.expect("there are reachable objects");
+    assert_eq!(pack.index.num_objects as usize, reachable.len());
+    assert_eq!(outcome.removed_packs, previous_packs);
+    assert_eq!(
+        pack_indices(&repo)?,
+        vec![pack.index_path.clone().expect("written to disk")]
+    );
+    assert!(
+        !pack.index_path.expect("set").with_extension("keep").exists(),
+        "the keep file is removed once done"
+    );
+    assert_eq!(outcome.cruft_pack, None);
+    assert_eq!(outcome.loosened_objects, 3, "the dropped commit, its tree and its blob");
+    assert!(
+        outcome.removed_loose_objects > 0,
+        "reachable loose objects are now packed"
+    );
+
+    let repo = gix::open_opts(repo.path(), restricted())?;
+    for id in &reachable {
+        assert!(!is_loose(&repo, *id), "reachable objects are packed");
+        repo.find_object(*id)?;
+    }
+    assert!(is_loose(&repo, id_in(dir.path(), "dropped.id")?));
+    assert!(is_loose(&repo, id_in(dir.path(), "unreachable.id")?));
+    Ok(())
+}
+
+#[test]
+fn repack_with_cruft_pack() -> crate::Result {
+    let (repo, dir) = repo_rw()?;
+    let (dropped, unreachable) = (id_in(dir.path(), "dropped.id")?, id_in(dir.path(), "unreachable.id")?);
+    let outcome = repo.repack(
+        gix::progress::Discard,
+        &AtomicBool::default(),
+        repack::Options {
+            unreachable: repack::Unreachable::Cruft { expire: None },
+            write_reverse_index: true,
+            ..Default::default()
+        },
+    )?;
+    let cruft = outcome.cruft_pack.expect("there are unreachable objects");
+    assert_eq!(
+        cruft.index.num_objects, 4,
+        "the dropped commit with its tree and blob, and the unreachable blob"
+    );
+    let cruft_index = cruft.index_path.expect("written to disk");
+    let mtimes = gix_pack::index::mtimes::File::at(cruft_index.with_extension("mtimes"), 4, repo.object_hash())?;
+    assert!(mtimes.iter().all(|mtime| mtime > 0));
+    assert!(outcome.pack.expect("reachable").reverse_index_path.is_some());
+    assert_eq!(pack_indices(&repo)?.len(), 2);
+
+    let repo = gix::open_opts(repo.path(), restricted())?;
+    for id in [dropped, unreachable] {
+        assert!(!is_loose(&repo, id), "unreachable objects are in the cruft pack");
+        repo.find_object(id)?;
+    }
+
+    let outcome = repo.repack(
+        gix::progress::Discard,
+        &AtomicBool::default(),
+        repack::Options {
+            unreachable: repack::Unreachable::Cruft {
+                expire: in_the_future(),
+            },
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(outcome.cruft_pack, None, "all unreachable objects expired");
+    assert_eq!(
+        outcome.removed_packs,
+        vec![cruft_index],
+        "the pack with reachable objects didn't change"
+    );
+    assert_eq!(pack_indices(&repo)?.len(), 1);
+
+    let repo = gix::open_opts(repo.path(), restricted())?;
+    for id in [dropped, unreachable] {
+        assert!(repo.try_find_object(id)?.is_none(), "expired objects are gone");
+    }
+    Ok(())
+}
+
+#[test]
+fn repack_with_bitmap_dropping_unreachable_objects() -> crate::Result {
+    let (repo, dir) = repo_rw()?;
+    let outcome = repo.repack(
+        gix::progress::Discard,
+        &AtomicBool::default(),
+        repack::Options {
+            unreachable: repack::Unreachable::Drop,
+            delta_compression: true,
+            write_bitmap: true,
+            ..Default::default()
+        },
+    )?;
+    let bitmap_path = outcome.bitmap_path.expect("bitmap was requested");
+    let bitmap = gix_pack::bitmap::Reachability::at(&bitmap_path, repo.object_hash())?;
+    assert!(
+        bitmap.bitmap_of_commit(repo.head_id()?).is_some(),
+        "references receive a bitmap"
+    );
+    assert_eq!(outcome.loosened_objects, 0);
+
+    let repo = gix::open_opts(repo.path(), restricted())?;
+    assert!(repo.try_find_object(id_in(dir.path(), "dropped.id")?)?.is_none());
+    assert!(
+        is_loose(&repo, id_in(dir.path(), "unreachable.id")?),
+        "loose objects are left to pruning"
+    );
+    Ok(())
+}
+
+#[test]
+fn repack_does_not_deltify_blobs_with_the_delta_attribute_unset() -> crate::Result {
+    let dir = gix_testtools::scripted_fixture_writable("make_delta_attribute_repo.sh")?;
+    let repo = gix::open_opts(dir.path().join("repo"), restricted())?;
+    let outcome = repo.repack(
+        gix::progress::Discard,
+        &AtomicBool::default(),
+        repack::Options {
+            delta_compression: true,
+            ..Default::default()
+        },
+    )?;
+    let index_path = outcome
+        .pack
+        .and_then(|pack| pack.index_path)
+        .expect("all objects are reachable");
+    let bundle = gix_pack::Bundle::at(index_path, repo.object_hash())?;
+    let is_delta = |spec: &str| -> crate::Result<bool> {
+        let id = repo.rev_parse_single(spec)?;
+        let index = bundle.index.lookup(id).expect("all objects are packed");
+        Ok(bundle
+            .pack
+            .entry(bundle.index.pack_offset_at_index(index))?
+            .header
+            .is_delta())
+    };
+    assert!(
+        is_delta("HEAD:a.txt")? || is_delta("HEAD~1:a.txt")?,
+        "similar blobs are stored as deltas"
+    );
+    assert!(
+        !is_delta("HEAD:a.bin")? && !is_delta("HEAD~1:a.bin")?,
+        "`*.bin -delta` prevents blobs from being stored as deltas even though they are just as similar"
+    );
+    Ok(())
+}
+
+#[test]
+fn prune_removes_expired_unreachable_loose_objects() -> crate::Result {
+    let (repo, dir) = repo_rw()?;
+    let unreachable = id_in(dir.path(), "unreachable.id")?;
+    let outcome = repo.prune(
+        gix::progress::Discard,
+        &AtomicBool::default(),
+        prune::Options::default(),
+    )?;
+    assert!(outcome.pruned_objects.is_empty(), "nothing expires by default");
+    assert_eq!(outcome.packed_objects, 0);
+
+    let outcome = repo.prune(
+        gix::progress::Discard,
+        &AtomicBool::default(),
+        prune::Options {
+            expire: in_the_future(),
+            dry_run: true,
+        },
+    )?;
+    assert_eq!(outcome.pruned_objects, vec![unreachable]);
+    assert!(is_loose(&repo, unreachable), "nothing is removed in a dry run");
+
+    let outcome = repo.prune(
+        gix::progress::Discard,
+        &AtomicBool::default(),
+        prune::Options {
+            expire: in_the_future(),
+            dry_run: false,
+        },
+    )?;
+    assert_eq!(outcome.pruned_objects, vec![unreachable]);
+    assert!(!is_loose(&repo, unreachable));
+    let reachable = repo.reachable_objects(gix::progress::Discard, &AtomicBool::default())?;
+    for id in reachable {
+        repo.find_object(id)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn prune_and_repack_keep_expired_objects_needed_by_recent_unreachable_objects() -> crate::Result {
+    let (repo, dir) = repo_rw()?;
+    let blob = id_in(dir.path(), "unreachable.id")?;
+    let tree = repo
+        .write_object(&gix::objs::Tree {
+            entries: vec![gix::objs::tree::Entry {
+                mode: gix::objs::tree::EntryMode::Blob,
+                filename: "unreachable".into(),
+                oid: blob,
+            }],
+        })?
+        .detach();
+    let loose = gix_odb::loose::Store::at(repo.objects.store_ref().path(), repo.object_hash());
+    let a_day_ago = filetime::FileTime::from_system_time(SystemTime::now() - Duration::from_secs(24 * 60 * 60));
+    for id in [blob, tree] {
+        filetime::set_file_mtime(loose.object_path(&id), a_day_ago)?;
+    }
+    let signature = gix::actor::Signature {
+        name: "c".into(),
+        email: "c@example.com".into(),
+        time: gix::actor::Time::new(1, 0),
+    };
+    let commit = repo
+        .write_object(&gix::objs::Commit {
+            tree,
+            parents: Default::default(),
+            author: signature.clone(),
+            committer: signature,
+            encoding: None,
+            message: "recent but unreachable".into(),
+            extra_headers: Vec::new(),
+        })?
+        .detach();
+    let an_hour_ago = Some(SystemTime::now() - Duration::from_secs(60 * 60));
+
+    let outcome = repo.prune(
+        gix::progress::Discard,
+        &AtomicBool::default(),
+        prune::Options {
+            expire: an_hour_ago,
+            dry_run: false,
+        },
+    )?;
+    assert_eq!(
+        outcome.pruned_objects,
+        Vec::<gix_hash::ObjectId>::new(),
+        "the old tree and blob are needed by the recent commit"
+    );
+
+    let outcome = repo.repack(
+        gix::progress::Discard,
+        &AtomicBool::default(),
+        repack::Options {
+            unreachable: repack::Unreachable::Cruft { expire: an_hour_ago },
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(
+        outcome
+            .cruft_pack
+            .expect("there are recent unreachable objects")
+            .index
+            .num_objects,
+        6,
+        "the dropped commit with its tree and blob, and the recent commit with its old tree and blob"
+    );
+
+    let outcome = repo.repack(
+        gix::progress::Discard,
+        &AtomicBool::default(),
+        repack::Options {
+            unreachable: repack::Unreachable::Loose { expire: an_hour_ago },
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(
+        outcome.loosened_objects, 6,
+        "the cruft pack keeps the old modification time of the tree and blob, which are loosened nonetheless"
+    );
+    let repo = gix::open_opts(repo.path(), restricted())?;
+    for id in [commit, tree, blob] {
+        assert!(is_loose(&repo, id));
+    }
+    Ok(())
+}
+
+#[test]
+fn write_commit_graph_for_references_and_turn_it_into_a_chain() -> crate::Result {
+    let (repo, dir) = repo_rw()?;
+    let info_dir = repo.objects.store_ref().path().join("info");
+    let outcome = repo.write_commit_graph_for_references(
+        gix::progress::Discard,
+        &AtomicBool::default(),
+        repo.commit_graph_options()?,
+    )?;
+    assert_eq!(outcome.path, Some(info_dir.join("commit-graph")));
+    assert_eq!(outcome.num_commits, 3, "only commits reachable from references");
+    assert_eq!(outcome.num_files, 1);
+
+    let graph = gix::commitgraph::Graph::from_info_dir(&info_dir)?;
+    assert!(graph.has_generation_data());
+    assert!(graph.lookup(repo.head_id()?).is_some());
+    assert!(graph.lookup(id_in(dir.path(), "reflog-only.id")?).is_none());
+    graph.verify_integrity(|_| Ok::<_, std::io::Error>(()))?;
+
+    let outcome = repo.write_commit_graph_for_references(
+        gix::progress::Discard,
+        &AtomicBool::default(),
+        gix::commitgraph::graph::write::Options {
+            split: Some(Split::default()),
+            ..repo.commit_graph_options()?
+        },
+    )?;
+    assert_eq!(outcome.num_commits, 3, "the single file is merged into the chain");
+    assert_eq!(outcome.num_files, 1);
+    assert_eq!(outcome.removed_files, vec![info_dir.join("commit-graph")]);
+    let graph = gix::commitgraph::Graph::from_info_dir(&info_dir)?;
+    assert_eq!(graph.num_commits(), 3);
+    graph.verify_integrity(|_| Ok::<_, std::io::Error>(()))?;
+
+    let outcome = repo.write_commit_graph_for_references(
+        gix::progress::Discard,
+        &AtomicBool::default(),
+        gix::commitgraph::graph::write::Options {
+            split: Some(Split::default()),
+            ..repo.commit_graph_options()?
+        },
+    )?;
+    assert_eq!(outcome.path, None, "nothing to do if all commits are already present");
+    Ok(())
+}
+
+#[test]
+fn write_commit_graph_with_changed_paths_like_git() -> crate::Result {
+    let dir = gix_testtools::scripted_fixture_writable("make_changed_paths_repo.sh")?;
+    let repo = gix::open_opts(dir.path().join("repo"), restricted())?;
+    let path = repo.objects.store_ref().path().join("info").join("commit-graph");
+    std::fs::remove_file(&path)?;
+
+    repo.write_commit_graph_for_references(
+        gix::progress::Discard,
+        &AtomicBool::default(),
+        gix::commitgraph::graph::write::Options {
+            changed_paths: Some(Default::default()),
+            ..repo.commit_graph_options()?
+        },
+    )?;
+    assert_eq!(
+        std::fs::read(&path)?,
+        std::fs::read(dir.path().join("commit-graph"))?,
+        "Bloom filters are computed exactly like git does"
+    );
+
+    std::fs::remove_file(&path)?;
+    std::fs::copy(dir.path().join("commit-graph"), &path)?;
+    let outcome = repo.write_commit_graph_for_references(
+        gix::progress::Discard,
+        &AtomicBool::default(),
+        gix::commitgraph::graph::write::Options {
+            split: Some(Split::NoMerge),
+            ..repo.commit_graph_options()?
+        },
+    )?;
+    assert_eq!(outcome.num_commits, 8, "the single file is turned into a chain");
+    let graph = repo.commit_graph()?;
+    assert!(
+        graph.files()[0].bloom_settings().is_some(),
+        "filters are written if the existing commit-graph has them"
+    );
+    Ok(())
+}
+
+#[test]
+fn options_from_configuration() -> crate::Result {
+    let (repo, _dir) = repo_rw()?;
+    let options = repo.repack_options()?;
+    match options.unreachable {
+        repack::Unreachable::Loose { expire: Some(expire) } => {
+            let two_weeks_ago = SystemTime::now() - Duration::from_secs(14 * 24 * 60 * 60);
+            assert!(expire <= two_weeks_ago && expire > two_weeks_ago - Duration::from_secs(60));
+        }
+        other => unreachable!("unexpected default: {other:?}"),
+    }
+    assert!(
+        !options.write_bitmap,
+        "bitmaps are only written by default in bare repositories"
+    );
+    assert_eq!(options.window, 10);
+
+    let repo = gix::open_opts(
+        repo.path(),
+        restricted().config_overrides([
+            "gc.cruftPacks=true",
+            "gc.pruneExpire=never",
+            "repack.writeBitmaps=true",
+            "pack.window=20",
+        ]),
+    )?;
+    let options = repo.repack_options()?;
+    assert_eq!(options.unreachable, repack::Unreachable::Cruft { expire: None });
+    assert!(options.write_bitmap);
+    assert_eq!(options.window, 20);
+    assert_eq!(repo.prune_options()?.expire, None);
+    assert!(repo.commit_graph_options()?.generation_data);
+
+    let repo = gix::open_opts(
+        repo.path(),
+        restricted().config_overrides(["commitGraph.generationVersion=1"]),
+    )?;
+    assert!(!repo.commit_graph_options()?.generation_data);
+    Ok(())
+}
+
+#[test]
+fn expire_reflogs_removes_entries_unreachable_from_the_tip() -> crate::Result {
+    let (repo, dir) = repo_rw()?;
+    let reflog_only = id_in(dir.path(), "reflog-only.id")?;
+    let mut options = expire_reflogs::Options {
+        expire: None,
+        expire_unreachable: in_the_future(),
+        dry_run: true,
+        ..repo.expire_reflogs_options()?
+    };
+    let outcome = repo.expire_reflogs(&AtomicBool::default(), &options)?;
+    assert_eq!(
+        outcome
+            .references
+            .iter()
+            .map(|(name, _)| name.as_bstr().to_string())
+            .collect::<Vec<_>>(),
+        ["HEAD", "refs/heads/main"]
+    );
+    for (name, outcome) in &outcome.references {
+        assert_eq!(outcome.kept, 1, "{name:?}: only the commit of '3' is reachable");
+        assert_eq!(outcome.removed.len(), 2, "{name:?}: committing and resetting");
+        assert!(outcome
+            .removed
+            .iter()
+            .all(|line| line.new_oid == reflog_only || line.previous_oid == reflog_only));
+    }
+    let reachable = |repo: &gix::Repository| -> crate::Result<bool> {
+        Ok(repo
+            .reachable_objects(gix::progress::Discard, &AtomicBool::default())?
+            .contains(&reflog_only))
+    };
+    assert!(reachable(&repo)?, "dry runs don't change anything");
+
+    options.dry_run = false;
+    let outcome = repo.expire_reflogs(&AtomicBool::default(), &options)?;
+    assert!(outcome.references.iter().all(|(_, o)| o.removed.len() == 2));
+    assert!(!reachable(&repo)?, "the commit was only reachable through the reflog");
+    let main = repo.find_reference("main")?;
+    assert_eq!(main.log_iter().all()?.expect("present").count(), 1);
+
+    let outcome = repo.expire_reflogs(&AtomicBool::default(), &options)?;
+    assert!(
+        outcome
+            .references
+            .iter()
+            .all(|(_, o)| o.removed.is_empty() && o.kept == 1),
+        "nothing else to remove"
+    );
+
+    let outcome = repo.expire_reflog(
+        "refs/heads/main".try_into()?,
+        &expire_reflogs::Options {
+            expire: in_the_future(),
+            ..options
+        },
+    )?;
+    assert_eq!(outcome.kept, 0, "old entries are removed even if they are reachable");
+    Ok(())
+}
+
+#[test]
+fn expire_reflogs_options_from_configuration() -> crate::Result {
+    let (repo, _dir) = repo_rw()?;
+    let options = repo.expire_reflogs_options()?;
+    let days_ago = |days: u64| SystemTime::now() - Duration::from_secs(days * 24 * 60 * 60);
+    let expire = options.expire.expect("set by default");
+    assert!(expire <= days_ago(90) && expire > days_ago(90) - Duration::from_secs(60));
+    let expire_unreachable = options.expire_unreachable.expect("set by default");
+    assert!(expire_unreachable <= days_ago(30) && expire_unreachable > days_ago(30) - Duration::from_secs(60));
+    let stash = options.for_reference("refs/stash".try_into()?);
+    assert_eq!(
+        (stash.expire, stash.expire_unreachable),
+        (None, None),
+        "the stash never expires by default"
+    );
+
+    let repo = gix::open_opts(
+        repo.path(),
+        restricted().config_overrides([
+            "gc.reflogExpire=never",
+            "gc.refs/remotes/*.reflogExpireUnreachable=now",
+            "gc.refs/remotes/*.reflogExpire=1.day.ago",
+            "gc.refs/remotes/origin/*.reflogExpire=false",
+        ]),
+    )?;
+    let options = repo.expire_reflogs_options()?;
+    assert_eq!(options.expire, None);
+    let main = options.for_reference("refs/heads/main".try_into()?);
+    assert_eq!(main.expire, None);
+    assert_eq!(main.expire_unreachable, options.expire_unreachable);
+
+    let remote = options.for_reference("refs/remotes/origin/main".try_into()?);
+    assert!(
+        remote.expire.expect("set") < SystemTime::now(),
+        "the first matching pattern wins"
+    );
+    assert!(remote.expire_unreachable.is_some());
+    let stash = options.for_reference("refs/stash".try_into()?);
+    assert_eq!(stash.expire, None);
+    assert_eq!(
+        stash.expire_unreachable, None,
+        "unconfigured values still don't apply to the stash"
+    );
+    Ok(())
+}
+
+#[test]
+fn pack_refs_moves_tags_by_default_and_all_references_if_configured() -> crate::Result {
+    let (repo, _dir) = repo_rw()?;
+    let loose_names = |repo: &gix::Repository| -> crate::Result<Vec<String>> {
+        Ok(repo
+            .refs
+            .as_file()
+            .expect("files")
+            .loose_iter()?
+            .map(|r| r.map(|r| r.name.as_bstr().to_string()))
+            .collect::<Result<_, _>>()?)
+    };
+    assert_eq!(loose_names(&repo)?, ["refs/heads/main", "refs/tags/annotated"]);
+    let reflog_len = |repo: &gix::Repository| -> crate::Result<usize> {
+        Ok(repo.find_reference("main")?.log_iter().all()?.expect("present").count())
+    };
+    let expected_reflog_len = reflog_len(&repo)?;
+
+    let outcome = repo.pack_refs(pack_refs::Options::default())?;
+    assert_eq!(outcome.packed.len(), 1);
+    assert_eq!(outcome.packed[0].as_bstr(), "refs/tags/annotated");
+    assert_eq!(
+        loose_names(&repo)?,
+        ["refs/heads/main"],
+        "branches stay loose by default"
+    );
+    let mut tag = repo.find_reference("annotated")?;
+    assert_eq!(
+        tag.peel_to_id_in_place()?,
+        repo.rev_parse_single("main~1")?.detach(),
+        "packed tags can still be peeled"
+    );
+
+    let outcome = repo.pack_refs(pack_refs::Options {
+        filter: pack_refs::Filter::All,
+        prune: true,
+    })?;
+    assert_eq!(outcome.packed.len(), 1);
+    assert!(loose_names(&repo)?.is_empty());
+    assert_eq!(reflog_len(&repo)?, expected_reflog_len, "reflogs are untouched");
+    Ok(())
+}
@@ -0,0 +1,167 @@
+use gix::{
+    bstr::ByteSlice,
+    merge::tree::{conflict::Kind, Side},
+};
+
+use crate::util::{hex_to_id, restricted};
+
+fn bare_repo() -> crate::Result<(gix::Repository, gix_testtools::tempfile::TempDir)> {
+    let dir = gix_testtools::scripted_fixture_writable("make_merge_trees_repo.sh")?;
+    let repo = gix::open_opts(dir.path().join("bare.git"), restricted())?;
+    assert!(repo.is_bare(), "merging works without a worktree");
+    Ok((repo, dir))
+}
+
+fn tree_of(repo: &gix::Repository, rev: &str) -> crate::Result<gix_hash::ObjectId> {
+    Ok(repo.rev_parse_single(rev)?.object()?.peel_to_tree()?.id)
+}
+
+fn blob_at(tree: gix_hash::ObjectId, repo: &gix::Repository, path: &str) -> crate::Result<Option<String>> {
+    let tree = repo.find_object(tree)?.into_tree();
+    Ok(match tree.lookup_entry_by_path(path)? {
+        Some(entry) => Some(entry.object()?.data.to_str()?.to_owned()),
+        None => None,
+    })
+}
+
+#[test]
+fn clean_merge_with_renames_matches_git() -> crate::Result {
+    let (repo, dir) = bare_repo()?;
+    let base = repo.rev_parse_single("base")?;
+    let outcome = repo.merge_trees(base, tree_of(&repo, "clean-ours")?, tree_of(&repo, "clean-theirs")?)?;
+    assert!(!outcome.has_conflicts(), "{:?}", outcome.conflicts);
+
+    let expected = std::fs::read_to_string(dir.path().join("clean.baseline"))?;
+    assert_eq!(
+        outcome.tree,
+        hex_to_id(expected.trim()),
+        "the same tree as `git merge-tree`"
+    );
+    assert_eq!(
+        blob_at(outcome.tree.detach(), &repo, "renamed-by-ours")?.expect("modification follows the rename"),
+        "11\n12\n13\n14\n15\n16\n17\n18\n19\ntwenty\n"
+    );
+    assert_eq!(blob_at(outcome.tree.detach(), &repo, "renamed")?, None);
+
+    let index = outcome.index()?;
+    assert!(index.entries().iter().all(|e| e.stage() == 0));
+    assert_eq!(index.entries().len(), 7);
+    Ok(())
+}
+
+#[test]
+fn merge_drivers_are_chosen_by_the_merge_attribute() -> crate::Result {
+    let (repo, dir) = bare_repo()?;
+    let outcome = repo.merge_trees(
+        repo.rev_parse_single("attributes-base")?,
+        tree_of(&repo, "attributes-ours")?,
+        tree_of(&repo, "attributes-theirs")?,
+    )?;
+    let expected = std::fs::read_to_string(dir.path().join("attributes.baseline"))?;
+    assert_eq!(
+        outcome.tree,
+        hex_to_id(expected.lines().next().expect("tree id")),
+        "the same tree as `git merge-tree`"
+    );
+    assert_eq!(
+        blob_at(outcome.tree.detach(), &repo, "union")?.expect("merged"),
+        "61\n62\n63\n64\nsixty-five by ours\nsixty-five by theirs\n66\n67\n68\n69\n70\n",
+        "`merge=union` resolves conflicting hunks by keeping both sides"
+    );
+    let conflicts: Vec<_> = outcome
+        .conflicts
+        .iter()
+        .map(|c| (c.kind, c.location.to_str().expect("valid UTF-8")))
+        .collect();
+    assert_eq!(
+        conflicts,
+        [(Kind::Content, "binary")],
+        "`merge=binary` keeps ours and conflicts even though the changes don't overlap"
+    );
+    Ok(())
+}
+
+#[test]
+fn conflicts_are_listed_and_staged() -> crate::Result {
+    let (repo, _dir) = bare_repo()?;
+    let outcome = repo.merge_trees(
+        repo.rev_parse_single("base")?,
+        repo.rev_parse_single("conflict-ours")?,
+        repo.rev_parse_single("conflict-theirs")?,
+    )?;
+    let conflicts: Vec<_> = outcome
+        .conflicts
+        .iter()
+        .map(|c| (c.location.to_str().expect("valid UTF-8"), c.kind))
+        .collect();
+    assert_eq!(
+        conflicts,
+        [
+            ("added-twice", Kind::AddAdd),
+            ("content", Kind::Content),
+            ("file-or-dir~ours", Kind::DirectoryFile { file_side: Side::Ours }),
+            (
+                "modified-and-deleted",
+                Kind::ModifyDelete {
+                    deleted_by: Side::Theirs
+                }
+            ),
+            (
+                "renamed-and-deleted-by-ours",
+                Kind::RenameDelete {
+                    deleted_by: Side::Theirs
+                }
+            ),
+            ("renamed-twice-by-ours", Kind::RenameRename),
+        ]
+    );
+
+    let tree = outcome.tree.detach();
+    let content = blob_at(tree, &repo, "content")?.expect("present");
+    assert!(
+        content.contains("<<<<<<<\nfive by ours\n=======\nfive by theirs\n>>>>>>>\n"),
+        "{content}"
+    );
+    assert_eq!(
+        blob_at(tree, &repo, "added-twice")?.expect("present").lines().count(),
+        5
+    );
+    assert_eq!(blob_at(tree, &repo, "file-or-dir~ours")?.as_deref(), Some("ours\n"));
+    assert_eq!(blob_at(tree, &repo, "file-or-dir/file")?.as_deref(), Some("theirs\n"));
+    assert!(blob_at(tree, &repo, "renamed-twice-by-ours")?.is_some());
+    assert!(blob_at(tree, &repo, "renamed-twice-by-theirs")?.is_some());
+
+    let index = outcome.index()?;
+    let staged: Vec<_> = index
+        .entries()
+        .iter()
+        .filter(|e| e.stage() != 0)
+        .map(|e| (e.path(&index).to_str().expect("valid UTF-8").to_owned(), e.stage()))
+        .collect();
+    assert_eq!(
+        staged,
+        [
+            ("added-twice", 2),
+            ("added-twice", 3),
+            ("content", 1),
+            ("content", 2),
+            ("content", 3),
+            ("file-or-dir~ours", 2),
+            ("modified-and-deleted", 1),
+            ("modified-and-deleted", 2),
+            ("renamed-and-deleted", 1),
+            ("renamed-and-deleted-by-ours", 2),
+            ("renamed-twice", 1),
+            ("renamed-twice-by-ours", 2),
+            ("renamed-twice-by-theirs", 3),
+        ]
+        .into_iter()
+        .map(|(path, stage)| (path.to_owned(), stage))
+        .collect::<Vec<_>>()
+    );
+    assert!(
+        index.entries().iter().any(|e| e.path(&index) == "file-or-dir/file"),
+        "non-conflicting files remain at stage 0"
+    );
+    Ok(())
+}
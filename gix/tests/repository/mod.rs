@@ -1,6 +1,10 @@
 use gix::Repository;
 
+mod branch;
 mod config;
+mod hook;
+mod maintenance;
+mod merge;
 mod object;
 mod open;
 mod reference;
@@ -162,6 +162,68 @@ mod find {
     }
 }
 
+mod object_memory {
+    use std::sync::atomic::AtomicBool;
+
+    use gix_pack::Find;
+
+    use crate::repository::object::empty_bare_repo;
+
+    #[test]
+    fn writes_stay_in_memory_until_persisted_as_pack() -> crate::Result {
+        let (tmp, repo) = empty_bare_repo()?;
+        let mut repo = repo.with_object_memory();
+        let loose = gix::odb::loose::Store::at(repo.git_dir().join("objects"), repo.object_hash());
+
+        let blob_id = repo.write_blob("hello")?.detach();
+        let tree = gix::objs::Tree {
+            entries: vec![gix::objs::tree::Entry {
+                mode: gix::objs::tree::EntryMode::Blob,
+                filename: "file".into(),
+                oid: blob_id,
+            }],
+        };
+        let tree_id = repo.write_object(&tree)?.detach();
+        assert_eq!(loose.iter().count(), 0, "nothing was written to disk");
+        assert_eq!(repo.objects.num_objects_in_memory(), 2);
+        assert_eq!(
+            repo.find_object(blob_id)?.data,
+            b"hello",
+            "objects in memory can be found"
+        );
+        assert_eq!(repo.find_object(tree_id)?.into_tree().decode()?.entries.len(), 1);
+
+        let outcome = repo
+            .objects
+            .persist_as_pack(
+                repo.objects.store_ref().path().join("pack"),
+                gix::progress::Discard,
+                &AtomicBool::default(),
+            )?
+            .expect("there were objects to write");
+        assert_eq!(outcome.index.num_objects, 2);
+        assert_eq!(repo.objects.num_objects_in_memory(), 0, "the memory is cleared");
+        assert_eq!(loose.iter().count(), 0, "objects are packed, not loose");
+
+        let mut buf = Vec::new();
+        assert!(
+            repo.objects.try_find(blob_id, &mut buf)?.is_some(),
+            "the new pack is picked up after a refresh"
+        );
+        let reopened = gix::open_opts(tmp.path(), gix::open::Options::isolated())?;
+        assert_eq!(reopened.find_object(blob_id)?.data, b"hello");
+
+        assert!(repo.objects.take_object_memory().is_some());
+        repo.write_blob("passthrough")?;
+        assert_eq!(
+            loose.iter().count(),
+            1,
+            "without object memory, writes go to the object database again"
+        );
+        Ok(())
+    }
+}
+
 mod tag {
     #[test]
     fn simple() -> crate::Result {
@@ -179,6 +179,27 @@ mod object_caches {
     }
 }
 
+mod pack_access {
+    use gix_pack::data::Access;
+
+    use crate::util::named_subrepo_opts;
+
+    #[test]
+    fn memory_map_by_default_and_read_if_configured() -> crate::Result {
+        let opts = gix::open::Options::isolated();
+        let repo = named_subrepo_opts("make_config_repos.sh", "object-caches", opts.clone())?;
+        assert_eq!(repo.objects.store_ref().pack_access(), Access::MemoryMap);
+
+        let repo = named_subrepo_opts(
+            "make_config_repos.sh",
+            "object-caches",
+            opts.config_overrides(Some("gitoxide.objects.noMmap=true")),
+        )?;
+        assert_eq!(repo.objects.store_ref().pack_access(), Access::Read);
+        Ok(())
+    }
+}
+
 mod with_overrides {
     use std::borrow::Cow;
 
@@ -209,6 +230,7 @@ mod with_overrides {
             .set("GIT_PROTOCOL_FROM_USER", "file-allowed")
             .set("GIT_REPLACE_REF_BASE", "refs/replace-mine")
             .set("GIT_NO_REPLACE_OBJECTS", "no-replace")
+            .set("GIT_QUARANTINE_PATH", "incoming-objects")
             .set("GIT_COMMITTER_NAME", "committer name")
             .set("GIT_COMMITTER_EMAIL", "committer email")
             .set("GIT_COMMITTER_DATE", default_date)
@@ -396,6 +418,7 @@ mod with_overrides {
             ("gitoxide.allow.protocolFromUser", "file-allowed"),
             ("gitoxide.objects.noReplace", "no-replace"),
             ("gitoxide.objects.replaceRefBase", "refs/replace-mine"),
+            ("gitoxide.objects.quarantinePath", "incoming-objects"),
             ("gitoxide.committer.nameFallback", "committer name"),
             ("gitoxide.committer.emailFallback", "committer email"),
             ("gitoxide.author.nameFallback", "author name"),
@@ -525,3 +548,151 @@ mod worktree {
         Ok(())
     }
 }
+
+mod object_format {
+    use gix::bstr::ByteSlice;
+
+    #[test]
+    fn sha256() -> crate::Result {
+        let repo = crate::named_repo("make_sha256_repo.sh")?;
+        assert_eq!(repo.object_hash(), gix_hash::Kind::Sha256);
+
+        let head = repo.head_commit()?;
+        assert_eq!(head.id.kind(), gix_hash::Kind::Sha256);
+        assert_eq!(head.message()?.summary().as_ref(), "c3");
+        let ancestors = head
+            .ancestors()
+            .all()?
+            .map(|id| id.map(|id| id.detach()))
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            ancestors.len(),
+            3,
+            "it can traverse with the commit-graph and objects from packs"
+        );
+        assert_eq!(repo.commit_graph()?.num_commits(), 3);
+
+        let mut paths = Vec::new();
+        for entry in head.tree()?.traverse().breadthfirst.files()? {
+            assert_eq!(entry.oid.kind(), gix_hash::Kind::Sha256);
+            paths.push(entry.filepath);
+        }
+        assert_eq!(paths, ["a", "dir", "dir/b", "dir/c"]);
+        let blob = repo.rev_parse_single("HEAD~1:dir/b")?.object()?;
+        assert_eq!(blob.data.as_bstr(), "b\n");
+
+        let tag = repo.find_reference("v1")?.peel_to_id_in_place()?;
+        assert_eq!(tag, ancestors[1], "packed refs with peeled tags are read as well");
+
+        let index = repo.index()?;
+        assert_eq!(index.entries().len(), 3);
+        assert_eq!(
+            index.entry_by_path_and_stage("dir/c".into(), 0).expect("present").id,
+            head.tree()?.lookup_entry_by_path("dir/c")?.expect("present").oid()
+        );
+        Ok(())
+    }
+}
+
+mod ref_storage {
+    use gix::refs::{
+        transaction::{Change, LogChange, PreviousValue, RefEdit},
+        Target,
+    };
+
+    fn reftable_repo(dir: &std::path::Path) -> crate::Result<std::path::PathBuf> {
+        let git_dir = gix::init_bare(dir)?.git_dir().to_owned();
+        let config = std::fs::read_to_string(git_dir.join("config"))?
+            .replace("repositoryformatversion = 0", "repositoryformatversion = 1");
+        std::fs::write(
+            git_dir.join("config"),
+            format!("{config}[extensions]\n\trefStorage = reftable\n"),
+        )?;
+        std::fs::create_dir(git_dir.join("reftable"))?;
+        std::fs::write(git_dir.join("HEAD"), "ref: refs/heads/.invalid\n")?;
+        Ok(git_dir)
+    }
+
+    #[test]
+    fn reftable_is_not_supported_in_linked_worktrees() -> crate::Result {
+        let dir = gix_testtools::tempfile::tempdir()?;
+        let git_dir = reftable_repo(dir.path())?;
+        let private_git_dir = git_dir.join("worktrees").join("wt");
+        std::fs::create_dir_all(&private_git_dir)?;
+        std::fs::write(private_git_dir.join("commondir"), "../..\n")?;
+        std::fs::write(private_git_dir.join("HEAD"), "ref: refs/heads/.invalid\n")?;
+        let work_dir = dir.path().join("wt");
+        std::fs::create_dir(&work_dir)?;
+        std::fs::write(
+            private_git_dir.join("gitdir"),
+            format!("{}\n", work_dir.join(".git").display()),
+        )?;
+        std::fs::write(
+            work_dir.join(".git"),
+            format!("gitdir: {}\n", private_git_dir.display()),
+        )?;
+
+        let err = gix::open(&work_dir).unwrap_err();
+        assert!(
+            matches!(err, gix::open::Error::UnsupportedRefStorage { .. }),
+            "the private HEAD would otherwise be read from and written to the shared stack: {err:?}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn reftable_is_detected_and_used_for_all_reference_operations() -> crate::Result {
+        let dir = gix_testtools::tempfile::tempdir()?;
+        let git_dir = reftable_repo(dir.path())?;
+
+        let mut repo = gix::open(&git_dir)?;
+        assert_eq!(repo.refs.format(), gix::refs::store::Format::Reftable);
+        assert_eq!(
+            repo.git_dir(),
+            git_dir,
+            "the git dir is still known to the reference store"
+        );
+        assert!(repo.try_find_reference("HEAD")?.is_none(), "nothing was written yet");
+
+        let id = repo.write_object(gix::objs::Tree::empty())?.detach();
+        repo.reference("refs/heads/main", id, PreviousValue::MustNotExist, "create main")?;
+        repo.edit_reference(RefEdit {
+            change: Change::Update {
+                log: LogChange::default(),
+                expected: PreviousValue::Any,
+                new: Target::Symbolic("refs/heads/main".try_into()?),
+            },
+            name: "HEAD".try_into()?,
+            deref: false,
+        })?;
+        assert!(
+            !git_dir.join("refs/heads/main").exists(),
+            "loose references aren't written"
+        );
+
+        assert_eq!(repo.head_id()?, id, "HEAD is followed to its referent and peeled");
+        assert_eq!(repo.head_name()?.expect("not detached").as_bstr(), "refs/heads/main");
+        assert_eq!(repo.find_reference("main")?.id(), id);
+        let names = repo
+            .references()?
+            .all()?
+            .map(|r| r.expect("valid").name().as_bstr().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            ["refs/heads/main"],
+            "HEAD isn't listed, just like with loose references"
+        );
+        assert_eq!(repo.references()?.local_branches()?.count(), 1);
+
+        assert!(
+            matches!(
+                repo.set_namespace("foo"),
+                Err(gix::reference::set_namespace::Error::Unsupported(_))
+            ),
+            "namespaces aren't silently ignored"
+        );
+        assert!(repo.namespace().is_none());
+        Ok(())
+    }
+}
@@ -224,3 +224,160 @@ mod head {
         Ok(())
     }
 }
+
+mod rename_and_copy {
+    #[test]
+    fn branches_move_their_reflog_symbolic_refs_and_configuration() -> crate::Result {
+        let (repo, _keep) = crate::repo_rw("make_references_repo.sh")?;
+        let config_path = repo.git_dir().join("config");
+        let mut config = std::fs::read_to_string(&config_path)?;
+        config.push_str("[branch \"main\"]\n\tremote = origin\n\tmerge = refs/heads/main\n");
+        std::fs::write(&config_path, config)?;
+        let mut repo = gix::open_opts(repo.git_dir(), crate::restricted())?;
+
+        let edits = repo.rename_reference("refs/heads/main", "refs/heads/trunk", "renamed")?;
+        assert_eq!(edits.len(), 3, "create, delete and HEAD update");
+        assert!(repo.try_find_reference("refs/heads/main")?.is_none());
+        assert_eq!(repo.head_name()?.expect("not detached").as_bstr(), "refs/heads/trunk");
+        let trunk = repo.find_reference("trunk")?;
+        let messages: Vec<_> = trunk
+            .log_iter()
+            .all()?
+            .expect("moved log")
+            .map(|line| line.map(|line| line.message.to_string()))
+            .collect::<Result<_, _>>()?;
+        assert_eq!(messages.len(), 2, "the existing entry is kept");
+        assert_eq!(messages.last().map(String::as_str), Some("renamed"));
+        let trunk_id = trunk.id().detach();
+
+        let snapshot = repo.config_snapshot();
+        assert_eq!(snapshot.string("branch.trunk.remote").as_deref(), Some("origin".into()));
+        assert_eq!(
+            snapshot.string("branch.trunk.merge").as_deref(),
+            Some("refs/heads/main".into()),
+            "values are kept as is"
+        );
+        assert!(snapshot.string("branch.main.remote").is_none());
+        let config = std::fs::read_to_string(&config_path)?;
+        assert!(config.contains("[branch \"trunk\"]") && !config.contains("[branch \"main\"]"));
+
+        repo.copy_reference("refs/heads/trunk", "refs/heads/copy", "copied")?;
+        assert_eq!(repo.find_reference("copy")?.id(), trunk_id);
+        assert_eq!(repo.head_name()?.expect("not detached").as_bstr(), "refs/heads/trunk");
+        let snapshot = repo.config_snapshot();
+        for name in ["trunk", "copy"] {
+            assert_eq!(
+                snapshot.string(format!("branch.{name}.remote").as_str()).as_deref(),
+                Some("origin".into())
+            );
+        }
+        let reopened = gix::open_opts(repo.git_dir(), crate::restricted())?;
+        assert_eq!(
+            reopened.config_snapshot().string("branch.copy.remote").as_deref(),
+            Some("origin".into()),
+            "changes are persisted"
+        );
+
+        assert!(matches!(
+            repo.rename_reference("refs/heads/copy", "refs/heads/trunk", ""),
+            Err(gix::reference::rename::Error::Rename(
+                gix::refs::store::rename::Error::DestinationExists { .. }
+            ))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn branch_configuration_keeps_keys_without_value() -> crate::Result {
+        let (repo, _keep) = crate::repo_rw("make_references_repo.sh")?;
+        let config_path = repo.git_dir().join("config");
+        let mut config = std::fs::read_to_string(&config_path)?;
+        config.push_str("[branch \"main\"]\n\trebase\n\tdescription = \"with spaces \\\" and quotes\"\n");
+        std::fs::write(&config_path, config)?;
+        let mut repo = gix::open_opts(repo.git_dir(), crate::restricted())?;
+
+        repo.copy_reference("refs/heads/main", "refs/heads/copy", "copied")?;
+        repo.rename_reference("refs/heads/copy", "refs/heads/renamed", "renamed")?;
+        let config = std::fs::read_to_string(&config_path)?;
+        assert!(
+            config.ends_with("[branch \"renamed\"]\n\trebase\n\tdescription = with spaces \\\" and quotes\n"),
+            "implicit booleans stay without value, and values are escaped as needed: {config}"
+        );
+        let snapshot = repo.config_snapshot();
+        assert_eq!(snapshot.boolean("branch.renamed.rebase"), Some(true));
+        assert_eq!(
+            snapshot.string("branch.renamed.description").as_deref(),
+            Some("with spaces \" and quotes".into())
+        );
+        Ok(())
+    }
+}
+
+mod transaction_hook {
+    use gix::refs::transaction::PreviousValue;
+
+    #[test]
+    #[cfg(unix)]
+    fn reference_transaction_hook_is_called_for_each_stage_and_may_reject_changes() -> crate::Result {
+        use std::os::unix::fs::PermissionsExt;
+
+        let (repo, _keep) = crate::repo_rw("make_references_repo.sh")?;
+        let hook = repo.git_dir().join("hooks").join("reference-transaction");
+        std::fs::create_dir_all(hook.parent().expect("parent"))?;
+        std::fs::write(
+            &hook,
+            "#!/bin/sh\necho \"$1\" >> \"$GIT_DIR/hook.log\"\ncat >> \"$GIT_DIR/hook.log\"\ntest ! -f \"$GIT_DIR/reject\"\n",
+        )?;
+        std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755))?;
+        let repo = gix::open_opts(repo.git_dir(), crate::restricted())?;
+
+        let id = gix::ObjectId::empty_tree(gix::hash::Kind::Sha1);
+        repo.tag_reference("new-tag", id, PreviousValue::MustNotExist)?;
+        let null = gix::hash::Kind::Sha1.null();
+        let log_path = repo.git_dir().join("hook.log");
+        assert_eq!(
+            std::fs::read_to_string(&log_path)?,
+            format!("prepared\n{null} {id} refs/tags/new-tag\ncommitted\n{null} {id} refs/tags/new-tag\n")
+        );
+
+        std::fs::remove_file(&log_path)?;
+        std::fs::write(repo.git_dir().join("reject"), b"")?;
+        assert!(
+            repo.tag_reference("rejected-tag", id, PreviousValue::MustNotExist)
+                .is_err(),
+            "hooks that fail in the prepared stage abort the transaction"
+        );
+        assert!(repo.try_find_reference("rejected-tag")?.is_none());
+        assert_eq!(
+            std::fs::read_to_string(&log_path)?,
+            format!("prepared\n{null} {id} refs/tags/rejected-tag\naborted\n{null} {id} refs/tags/rejected-tag\n")
+        );
+
+        let config_path = repo.git_dir().join("config");
+        let mut config = std::fs::read_to_string(&config_path)?;
+        config.push_str("[core]\n\thooksPath = does-not-exist\n");
+        std::fs::write(&config_path, config)?;
+        let repo = gix::open_opts(repo.git_dir(), crate::restricted())?;
+        repo.tag_reference("unhooked-tag", id, PreviousValue::MustNotExist)?;
+        Ok(())
+    }
+
+    #[test]
+    fn ref_updates_are_refused_in_a_quarantine_environment() -> crate::Result {
+        let (repo, _keep) = crate::repo_rw("make_references_repo.sh")?;
+        let repo = gix::open_opts(
+            repo.git_dir(),
+            crate::restricted().config_overrides(["gitoxide.objects.quarantinePath=incoming-objects"]),
+        )?;
+        let id = gix::ObjectId::empty_tree(gix::hash::Kind::Sha1);
+        let err = repo
+            .tag_reference("new-tag", id, PreviousValue::MustNotExist)
+            .unwrap_err();
+        assert_eq!(
+            std::error::Error::source(&err).map(ToString::to_string).as_deref(),
+            Some("ref updates forbidden inside quarantine environment")
+        );
+        assert!(repo.try_find_reference("new-tag")?.is_none());
+        Ok(())
+    }
+}
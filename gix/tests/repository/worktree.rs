@@ -263,6 +263,7 @@ fn run_assertions(main_repo: gix::Repository, should_be_bare: bool) {
         assert_eq!(proxy_lock_reason, expected.locked);
         let proxy_is_locked = actual.is_locked();
         assert_eq!(proxy_is_locked, proxy_lock_reason.is_some());
+        assert_eq!(actual.prune_reason(), expected.prunable);
         // TODO: check id of expected worktree, but need access to .gitdir from worktree base
         let proxy_id = actual.id().to_owned();
         assert_eq!(
@@ -301,3 +302,442 @@ fn run_assertions(main_repo: gix::Repository, should_be_bare: bool) {
         );
     }
 }
+
+mod manage {
+    use gix::bstr::ByteSlice;
+    use gix_testtools::tempfile;
+
+    fn repo() -> crate::Result<(gix::Repository, tempfile::TempDir)> {
+        let dir = gix_testtools::scripted_fixture_writable_with_args(
+            "make_worktree_repo.sh",
+            None::<String>,
+            gix_testtools::Creation::ExecuteScript,
+        )?;
+        let repo = gix::open_opts(dir.path().join("repo"), crate::restricted())?;
+        Ok((repo, dir))
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn add_runs_the_post_checkout_hook() -> crate::Result {
+        use std::os::unix::fs::PermissionsExt;
+        if gix_testtools::should_skip_as_git_version_is_smaller_than(2, 31, 0) {
+            return Ok(());
+        }
+        let (repo, dir) = repo()?;
+        let hooks_dir = repo.common_dir().join("hooks");
+        std::fs::create_dir_all(&hooks_dir)?;
+        let hook = hooks_dir.join("post-checkout");
+        std::fs::write(&hook, "#!/bin/sh\necho \"$@\" > \"$GIT_DIR/post-checkout.out\"\n")?;
+        std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755))?;
+
+        let wt_repo = repo.worktree_add(dir.path().join("wt-hook"), "HEAD~1".as_bytes().as_bstr())?;
+        let head_id = wt_repo.head_id()?;
+        assert_eq!(
+            std::fs::read_to_string(wt_repo.git_dir().join("post-checkout.out"))?,
+            format!("{} {head_id} 1\n", gix::hash::Kind::Sha1.null()),
+            "the hook runs in the new worktree with the null id as previous HEAD"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn add_and_remove() -> crate::Result {
+        if gix_testtools::should_skip_as_git_version_is_smaller_than(2, 31, 0) {
+            return Ok(());
+        }
+        let (repo, dir) = repo()?;
+        let base = dir.path().join("wt-new");
+        let wt_repo = repo.worktree_add(&base, "HEAD~1".as_bytes().as_bstr())?;
+        let expected_head = repo.rev_parse_single("HEAD~1")?;
+        assert_eq!(
+            wt_repo.head_id()?,
+            expected_head,
+            "HEAD is detached at the given revision"
+        );
+        assert!(wt_repo.head_name()?.is_none());
+        for path in ["a", "b", "dir/c"] {
+            assert!(base.join(path).is_file(), "{path} was checked out");
+        }
+        assert_eq!(wt_repo.index()?.entries().len(), 3, "the index was written");
+        assert_eq!(wt_repo.worktree().expect("linked").id(), Some("wt-new".into()));
+
+        let proxy = repo
+            .worktrees()?
+            .into_iter()
+            .find(|p| p.id() == "wt-new")
+            .expect("listed");
+        assert!(!proxy.is_locked(), "the lock is removed after initialization");
+        assert_eq!(proxy.prune_reason(), None);
+        assert!(!wt_repo.worktree().expect("linked").is_dirty()?);
+
+        let err = repo.worktree_add(dir.path().join("other"), "main").unwrap_err();
+        assert!(
+            matches!(err, gix::worktree::add::Error::BranchCheckedOut { .. }),
+            "{err:?}: cannot check out the same branch twice"
+        );
+        assert!(!dir.path().join("other").exists(), "nothing is left behind on error");
+        let err = repo.worktree_add(dir.path().join("wt-a"), "HEAD").unwrap_err();
+        assert!(
+            matches!(err, gix::worktree::add::Error::DestinationNotEmpty { .. }),
+            "{err:?}"
+        );
+        std::fs::write(dir.path().join("file"), "content")?;
+        let err = repo.worktree_add(dir.path().join("file"), "HEAD").unwrap_err();
+        assert!(
+            matches!(err, gix::worktree::add::Error::DestinationNotEmpty { .. }),
+            "{err:?}: files are in the way just like directories with content"
+        );
+        let err = repo
+            .worktree_add(dir.path().join("file").join("wt"), "HEAD")
+            .unwrap_err();
+        assert!(
+            matches!(err, gix::worktree::add::Error::Io(_)),
+            "{err:?}: other errors are passed on"
+        );
+
+        let another = repo.worktree_add(dir.path().join("prev").join("wt-new"), "HEAD")?;
+        assert_eq!(
+            another.worktree().expect("linked").id(),
+            Some("wt-new1".into()),
+            "ids are unique"
+        );
+
+        std::fs::write(base.join("untracked"), "content")?;
+        assert!(
+            wt_repo.worktree().expect("linked").is_dirty()?,
+            "untracked files make it dirty"
+        );
+        let err = proxy.clone().remove(Default::default()).unwrap_err();
+        assert!(
+            matches!(err, gix::worktree::proxy::remove::Error::Dirty { .. }),
+            "{err:?}"
+        );
+        std::fs::remove_file(base.join("untracked"))?;
+        std::fs::write(base.join("a"), "changed")?;
+        assert!(
+            wt_repo.worktree().expect("linked").is_dirty()?,
+            "modified files make it dirty"
+        );
+
+        let git_dir = proxy.git_dir().to_owned();
+        proxy.remove(gix::worktree::proxy::remove::Options {
+            allow_dirty: true,
+            ..Default::default()
+        })?;
+        assert!(!base.exists());
+        assert!(!git_dir.exists());
+        Ok(())
+    }
+
+    #[test]
+    fn lock_move_repair_and_prune() -> crate::Result {
+        if gix_testtools::should_skip_as_git_version_is_smaller_than(2, 31, 0) {
+            return Ok(());
+        }
+        let (repo, dir) = repo()?;
+        let proxy = |id: &str| -> crate::Result<gix::worktree::Proxy<'_>> {
+            Ok(repo
+                .worktrees()?
+                .into_iter()
+                .find(|p| p.id() == id)
+                .expect("worktree exists"))
+        };
+
+        let wt = proxy("wt-b")?;
+        wt.lock("on a usb stick")?;
+        assert_eq!(wt.lock_reason().expect("locked"), "on a usb stick");
+        assert!(wt.lock("again").is_err(), "cannot lock twice");
+        let err = wt.move_to(dir.path().join("moved")).unwrap_err();
+        assert!(
+            matches!(err, gix::worktree::proxy::move_to::Error::Locked { .. }),
+            "{err:?}"
+        );
+        assert!(wt.unlock()?);
+        assert!(!wt.unlock()?, "it's not locked anymore");
+
+        let new_base = wt.move_to(dir.path().join("moved"))?;
+        assert_eq!(new_base, dir.path().join("moved"));
+        assert_eq!(wt.base()?, new_base);
+        assert_eq!(wt.prune_reason(), None);
+        assert!(wt.clone().into_repo()?.worktree().is_some());
+        assert!(!wt.repair()?, "nothing to repair");
+
+        let moved_by_hand = dir.path().join("moved-by-hand");
+        std::fs::rename(&new_base, &moved_by_hand)?;
+        assert_eq!(
+            wt.prune_reason().expect("prunable"),
+            "gitdir file points to non-existent location"
+        );
+        let repaired = repo.repair_worktree(&moved_by_hand)?;
+        assert_eq!(repaired.id(), "wt-b");
+        assert_eq!(wt.prune_reason(), None, "after repairing, it's valid again");
+
+        std::fs::write(
+            moved_by_hand.join(".git"),
+            format!("gitdir: {}\n", dir.path().join("elsewhere").display()),
+        )?;
+        assert!(wt.repair()?, "the .git file was corrupted and needed to be rewritten");
+        assert_eq!(
+            gix::open(&moved_by_hand)?.worktree().expect("linked").id(),
+            Some("wt-b".into())
+        );
+
+        assert!(
+            repo.prune_worktrees(Some(std::time::SystemTime::UNIX_EPOCH))?
+                .is_empty(),
+            "the deleted worktree is younger than the expiry date"
+        );
+        assert_eq!(repo.prune_worktrees(None)?, ["wt-deleted"]);
+        assert!(repo.prune_worktrees(None)?.is_empty(), "nothing left to prune");
+        let ids: Vec<_> = repo.worktrees()?.iter().map(|p| p.id().to_owned()).collect();
+        assert_eq!(ids, ["nested-wt-b", "wt-a", "wt-a1", "wt-b", "wt-c-locked"]);
+        Ok(())
+    }
+}
+
+mod switch {
+    use std::sync::atomic::AtomicBool;
+
+    use gix::{bstr::ByteSlice, worktree::switch};
+    use gix_testtools::tempfile;
+
+    fn repo() -> crate::Result<(gix::Repository, tempfile::TempDir)> {
+        let dir = gix_testtools::scripted_fixture_writable("make_switch_repo.sh")?;
+        let repo = gix::open_opts(dir.path().join("repo"), crate::restricted())?;
+        Ok((repo, dir))
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn switch(repo: &gix::Repository, target: &str, force: bool) -> Result<switch::Outcome, switch::Error> {
+        repo.switch(
+            target,
+            gix::progress::Discard,
+            &AtomicBool::default(),
+            switch::Options { force },
+        )
+    }
+
+    fn last_head_log_message(repo: &gix::Repository) -> crate::Result<String> {
+        let head = repo.find_reference("HEAD")?;
+        let mut log = head.log_iter();
+        let line = log.rev()?.expect("reflog present").next().expect("at least one line")?;
+        Ok(line.message.to_str_lossy().into_owned())
+    }
+
+    #[test]
+    fn to_branch_and_back_only_touches_changed_files() -> crate::Result {
+        let (repo, _keep) = repo()?;
+        let workdir = repo.work_dir().expect("non-bare").to_owned();
+        let main_id = repo.head_id()?.detach();
+
+        let outcome = switch(&repo, "other", false)?;
+        assert_eq!(outcome.previous_id, Some(main_id));
+        assert_eq!(outcome.id, repo.rev_parse_single("other")?);
+        assert_eq!(
+            outcome.branch.as_ref().map(|n| n.as_bstr()),
+            Some("refs/heads/other".into())
+        );
+        assert_eq!(outcome.updated, ["a", "dir/new", "only-other/x"]);
+        assert_eq!(outcome.removed, ["c"]);
+
+        assert_eq!(std::fs::read(workdir.join("a"))?, b"a2\n");
+        assert!(!workdir.join("c").exists());
+        assert_eq!(repo.head_name()?.expect("attached").as_bstr(), "refs/heads/other");
+        assert_eq!(last_head_log_message(&repo)?, "checkout: moving from main to other");
+        let index = repo.index()?;
+        assert_eq!(
+            index
+                .entries()
+                .iter()
+                .map(|e| e.path(&index).to_owned())
+                .collect::<Vec<_>>(),
+            ["a", "dir/b", "dir/new", "only-other/x"]
+        );
+        assert_eq!(
+            index.entry_by_path_and_stage("a".into(), 0).expect("present").stat.size,
+            3,
+            "stat information of written files is updated"
+        );
+        assert!(!repo.worktree().expect("non-bare").is_dirty()?);
+
+        let outcome = switch(&repo, "main", false)?;
+        assert_eq!(outcome.updated, ["a", "c"]);
+        assert_eq!(outcome.removed, ["dir/new", "only-other/x"]);
+        assert!(
+            !workdir.join("only-other").exists(),
+            "directories which became empty are removed"
+        );
+        assert!(workdir.join("dir").join("b").is_file());
+        assert_eq!(last_head_log_message(&repo)?, "checkout: moving from other to main");
+        assert_eq!(
+            repo.rev_parse_single("@{-1}")?,
+            repo.rev_parse_single("other")?,
+            "the reflog allows to find the previous branch"
+        );
+        assert!(!repo.worktree().expect("non-bare").is_dirty()?);
+        Ok(())
+    }
+
+    #[test]
+    fn local_changes_are_kept_unless_they_would_be_overwritten() -> crate::Result {
+        let (repo, _keep) = repo()?;
+        let workdir = repo.work_dir().expect("non-bare").to_owned();
+        std::fs::write(workdir.join("dir").join("b"), "modified")?;
+
+        let outcome = switch(&repo, "other", false)?;
+        assert_eq!(outcome.updated, ["a", "dir/new", "only-other/x"]);
+        assert_eq!(
+            std::fs::read(workdir.join("dir").join("b"))?,
+            b"modified",
+            "unrelated changes are kept"
+        );
+
+        std::fs::write(workdir.join("a"), "modified")?;
+        match switch(&repo, "main", false) {
+            Err(switch::Error::WouldOverwrite { paths }) => assert_eq!(paths, ["a"]),
+            res => panic!("expected the switch to fail, got {res:?}"),
+        }
+        assert_eq!(repo.head_name()?.expect("attached").as_bstr(), "refs/heads/other");
+        assert!(workdir.join("dir").join("new").is_file(), "nothing was changed");
+
+        let outcome = switch(&repo, "main", true)?;
+        assert_eq!(outcome.updated, ["a", "c", "dir/b"], "forcing resets local changes");
+        assert_eq!(std::fs::read(workdir.join("a"))?, b"a\n");
+        assert!(!repo.worktree().expect("non-bare").is_dirty()?);
+        Ok(())
+    }
+
+    #[test]
+    fn untracked_files_in_the_way_prevent_the_switch() -> crate::Result {
+        let (repo, _keep) = repo()?;
+        let workdir = repo.work_dir().expect("non-bare").to_owned();
+        std::fs::write(workdir.join("dir").join("new"), "untracked")?;
+
+        match switch(&repo, "other", false) {
+            Err(switch::Error::WouldOverwrite { paths }) => assert_eq!(paths, ["dir/new"]),
+            res => panic!("expected the switch to fail, got {res:?}"),
+        }
+        switch(&repo, "other", true)?;
+        assert_eq!(std::fs::read(workdir.join("dir").join("new"))?, b"new\n");
+        Ok(())
+    }
+
+    #[test]
+    fn untracked_files_in_place_of_a_directory_prevent_the_switch() -> crate::Result {
+        let (repo, _keep) = repo()?;
+        let workdir = repo.work_dir().expect("non-bare").to_owned();
+        std::fs::write(workdir.join("only-other"), "untracked")?;
+
+        match switch(&repo, "other", false) {
+            Err(switch::Error::WouldOverwrite { paths }) => assert_eq!(paths, ["only-other"]),
+            res => panic!("expected the switch to fail, got {res:?}"),
+        }
+        assert_eq!(
+            std::fs::read(workdir.join("only-other"))?,
+            b"untracked",
+            "the untracked file is kept"
+        );
+        assert_eq!(repo.head_name()?.expect("attached").as_bstr(), "refs/heads/main");
+
+        switch(&repo, "other", true)?;
+        assert_eq!(std::fs::read(workdir.join("only-other").join("x"))?, b"x\n");
+        Ok(())
+    }
+
+    #[test]
+    fn files_excluded_by_sparse_checkout_are_not_reported_as_updated() -> crate::Result {
+        let (repo, _keep) = repo()?;
+        let workdir = repo.work_dir().expect("non-bare").to_owned();
+        std::fs::create_dir_all(repo.git_dir().join("info"))?;
+        std::fs::write(
+            repo.git_dir().join("info").join("sparse-checkout"),
+            "/*\n!/only-other/\n",
+        )?;
+        let repo = gix::open_opts(
+            repo.git_dir(),
+            crate::restricted().config_overrides([
+                "user.name=gitoxide",
+                "user.email=gitoxide@localhost",
+                "core.sparseCheckout=true",
+                "core.sparseCheckoutCone=false",
+            ]),
+        )?;
+
+        let outcome = switch(&repo, "other", false)?;
+        assert_eq!(outcome.updated, ["a", "dir/new"], "only-other/x isn't written");
+        assert!(!workdir.join("only-other").exists());
+        let index = repo.index()?;
+        assert!(index
+            .entry_by_path_and_stage("only-other/x".into(), 0)
+            .expect("present")
+            .flags
+            .contains(gix::index::entry::Flags::SKIP_WORKTREE));
+
+        let outcome = switch(&repo, "main", false)?;
+        assert_eq!(
+            outcome.removed,
+            ["dir/new"],
+            "only-other/x was only removed from the index"
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn the_post_checkout_hook_does_not_affect_the_switch() -> crate::Result {
+        use std::os::unix::{fs::PermissionsExt, process::ExitStatusExt};
+
+        let (repo, _keep) = repo()?;
+        let hook = repo.git_dir().join("hooks").join("post-checkout");
+        std::fs::create_dir_all(hook.parent().expect("parent"))?;
+        std::fs::write(
+            &hook,
+            "#!/bin/sh\necho \"$@\" > \"$GIT_DIR/post-checkout.out\"\nexit 3\n",
+        )?;
+        std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755))?;
+        let main_id = repo.head_id()?.detach();
+
+        let outcome = switch(&repo, "other", false)?;
+        assert_eq!(
+            outcome
+                .post_checkout_hook
+                .expect("hook ran")
+                .map(|status| status.into_raw()),
+            Some(3 << 8),
+            "the failing hook is reported, but the switch is complete"
+        );
+        assert_eq!(repo.head_name()?.expect("attached").as_bstr(), "refs/heads/other");
+        assert_eq!(
+            std::fs::read_to_string(repo.git_dir().join("post-checkout.out"))?,
+            format!("{main_id} {} 1\n", outcome.id)
+        );
+
+        std::fs::write(&hook, "#!/does/not/exist\n")?;
+        let outcome = switch(&repo, "main", false)?;
+        assert!(
+            matches!(outcome.post_checkout_hook, Err(gix::hook::run::Error::Spawn { .. })),
+            "hooks that can't be run don't fail the switch either"
+        );
+        assert_eq!(repo.head_name()?.expect("attached").as_bstr(), "refs/heads/main");
+        Ok(())
+    }
+
+    #[test]
+    fn to_revision_detaches_head() -> crate::Result {
+        let (repo, _keep) = repo()?;
+        let other_id = repo.rev_parse_single("other")?.detach();
+        let target = other_id.to_string();
+
+        let outcome = switch(&repo, &target, false)?;
+        assert_eq!(outcome.branch, None);
+        assert_eq!(outcome.id, other_id);
+        assert!(repo.head()?.is_detached());
+        assert_eq!(repo.head_id()?, other_id);
+        assert_eq!(
+            last_head_log_message(&repo)?,
+            format!("checkout: moving from main to {target}")
+        );
+        Ok(())
+    }
+}
@@ -16,7 +16,10 @@ use gix::bstr::io::BufReadExt;
 use crate::plumbing::options::attributes;
 use crate::{
     plumbing::{
-        options::{commit, config, credential, exclude, free, index, mailmap, odb, revision, tree, Args, Subcommands},
+        options::{
+            commit, config, credential, exclude, free, index, mailmap, odb, reference, revision, tree, Args,
+            Subcommands,
+        },
         show_progress,
     },
     shared::pretty::prepare_and_run,
@@ -380,6 +383,7 @@ pub fn main() -> Result<()> {
                     repository,
                     expansion,
                     thin,
+                    delta_compression,
                     statistics,
                     nondeterministic_count,
                     tips,
@@ -401,6 +405,7 @@ pub fn main() -> Result<()> {
                             let context = core::pack::create::Context {
                                 thread_limit,
                                 thin,
+                                delta_compression,
                                 nondeterministic_thread_count: nondeterministic_count.then_some(counting_threads),
                                 pack_cache_size_in_bytes: pack_cache_size_mb.unwrap_or(0) * 1_000_000,
                                 object_cache_size_in_bytes: object_cache_size_mb.unwrap_or(0) * 1_000_000,
@@ -803,6 +808,90 @@ pub fn main() -> Result<()> {
                     )
                 },
             ),
+            odb::Subcommands::Repack {
+                cruft,
+                drop_unreachable,
+                expire,
+                delta_compression,
+                write_bitmap,
+            } => prepare_and_run(
+                "odb-repack",
+                auto_verbose,
+                progress,
+                progress_keep_open,
+                core::repository::odb::repack::PROGRESS_RANGE,
+                move |progress, out, err| {
+                    core::repository::odb::repack(
+                        repository(Mode::Strict)?,
+                        progress,
+                        out,
+                        err,
+                        &should_interrupt,
+                        core::repository::odb::repack::Options {
+                            format,
+                            cruft,
+                            drop_unreachable,
+                            expire: expire.map(into_system_time),
+                            delta_compression,
+                            write_bitmap,
+                            thread_limit,
+                        },
+                    )
+                },
+            ),
+            odb::Subcommands::WriteCommitGraph {
+                split,
+                no_merge,
+                replace,
+                changed_paths,
+            } => prepare_and_run(
+                "odb-write-commit-graph",
+                auto_verbose,
+                progress,
+                progress_keep_open,
+                core::repository::odb::write_commit_graph::PROGRESS_RANGE,
+                move |progress, out, err| {
+                    core::repository::odb::write_commit_graph(
+                        repository(Mode::Strict)?,
+                        progress,
+                        out,
+                        err,
+                        &should_interrupt,
+                        core::repository::odb::write_commit_graph::Options {
+                            format,
+                            split: split.then_some(if no_merge {
+                                gix::commitgraph::graph::write::Split::NoMerge
+                            } else if replace {
+                                gix::commitgraph::graph::write::Split::Replace
+                            } else {
+                                Default::default()
+                            }),
+                            changed_paths,
+                        },
+                    )
+                },
+            ),
+            odb::Subcommands::Prune { expire, dry_run } => prepare_and_run(
+                "odb-prune",
+                auto_verbose,
+                progress,
+                progress_keep_open,
+                core::repository::odb::prune::PROGRESS_RANGE,
+                move |progress, out, err| {
+                    core::repository::odb::prune(
+                        repository(Mode::Strict)?,
+                        progress,
+                        out,
+                        err,
+                        &should_interrupt,
+                        core::repository::odb::prune::Options {
+                            format,
+                            expire: expire.map(into_system_time),
+                            dry_run,
+                        },
+                    )
+                },
+            ),
             odb::Subcommands::Entries => prepare_and_run(
                 "odb-entries",
                 verbose,
@@ -832,6 +921,27 @@ pub fn main() -> Result<()> {
                 },
             ),
         },
+        Subcommands::Reference(cmd) => match cmd {
+            reference::Subcommands::Pack { all, no_prune } => prepare_and_run(
+                "reference-pack",
+                verbose,
+                progress,
+                progress_keep_open,
+                None,
+                move |_progress, out, err| {
+                    core::repository::reference::pack(
+                        repository(Mode::Strict)?,
+                        out,
+                        err,
+                        core::repository::reference::pack::Options {
+                            format,
+                            all,
+                            prune: !no_prune,
+                        },
+                    )
+                },
+            ),
+        },
         Subcommands::Attributes(cmd) => match cmd {
             attributes::Subcommands::Query { statistics, pathspecs } => prepare_and_run(
                 "attributes-query",
@@ -930,6 +1040,7 @@ pub fn main() -> Result<()> {
                 no_attributes,
                 attributes_from_index,
                 statistics,
+                pathspecs,
             } => prepare_and_run(
                 "index-entries",
                 verbose,
@@ -939,6 +1050,7 @@ pub fn main() -> Result<()> {
                 move |_progress, out, err| {
                     core::repository::index::entries(
                         repository(Mode::LenientWithGitInstallConfig)?,
+                        pathspecs,
                         out,
                         err,
                         core::repository::index::entries::Options {
@@ -984,6 +1096,10 @@ fn stdin_or_bail() -> Result<std::io::BufReader<std::io::Stdin>> {
     Ok(BufReader::new(stdin()))
 }
 
+fn into_system_time(time: gix::date::Time) -> std::time::SystemTime {
+    std::time::UNIX_EPOCH + std::time::Duration::from_secs(time.seconds_since_unix_epoch.into())
+}
+
 fn verify_mode(decode: bool, re_encode: bool) -> verify::Mode {
     match (decode, re_encode) {
         (true, false) => verify::Mode::HashCrc32Decode,
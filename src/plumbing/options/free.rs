@@ -162,6 +162,14 @@ pub mod pack {
             /// Packs produced with this option enabled are only valid in transit, but not at rest.
             thin: bool,
 
+            #[clap(long, conflicts_with = "thin")]
+            /// if set, objects will be compressed as deltas against similar objects in the pack, which is slow but produces
+            /// the smallest packs.
+            ///
+            /// The `pack.window` and `pack.depth` configuration values control how many objects are compared and
+            /// how long delta chains can be.
+            delta_compression: bool,
+
             /// The directory into which to write the pack file.
             #[clap(long, short = 'o')]
             output_directory: Option<PathBuf>,
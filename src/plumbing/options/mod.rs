@@ -100,6 +100,9 @@ pub enum Subcommands {
     /// Interact with the mailmap.
     #[clap(subcommand)]
     Mailmap(mailmap::Subcommands),
+    /// Interact with references.
+    #[clap(subcommand, visible_alias = "ref")]
+    Reference(reference::Subcommands),
     /// Interact with the remote hosts.
     #[cfg(any(feature = "gitoxide-core-async-client", feature = "gitoxide-core-blocking-client"))]
     Remote(remote::Platform),
@@ -318,6 +321,21 @@ pub mod mailmap {
     }
 }
 
+pub mod reference {
+    #[derive(Debug, clap::Subcommand)]
+    pub enum Subcommands {
+        /// Move loose tags and references that are already packed into the packed-refs file, and remove the loose ones.
+        Pack {
+            /// Pack all references, not only tags and those that are already packed.
+            #[clap(long)]
+            all: bool,
+            /// Keep the loose references instead of removing them once they are packed.
+            #[clap(long)]
+            no_prune: bool,
+        },
+    }
+}
+
 pub mod odb {
     #[derive(Debug, clap::Subcommand)]
     pub enum Subcommands {
@@ -328,6 +346,48 @@ pub mod odb {
         /// Count and obtain information on all, possibly duplicate, objects in the database.
         #[clap(visible_alias = "statistics")]
         Stats,
+        /// Write all reachable objects into a single pack and remove the packs and loose objects that are now redundant.
+        Repack {
+            /// Store unreachable objects in a cruft pack instead of writing them as loose objects.
+            #[clap(long, conflicts_with = "drop_unreachable")]
+            cruft: bool,
+            /// Drop unreachable objects contained in packs instead of keeping them.
+            #[clap(long)]
+            drop_unreachable: bool,
+            /// Drop unreachable objects older than the given date, overriding `gc.pruneExpire`.
+            #[clap(long, value_parser = crate::shared::AsTime, value_name = "DATE")]
+            expire: Option<gix::date::Time>,
+            /// Find new deltas instead of reusing existing ones, which is slow but can lead to smaller packs.
+            #[clap(long, short = 'f')]
+            delta_compression: bool,
+            /// Write a reachability bitmap for the new pack, overriding `repack.writeBitmaps`.
+            #[clap(long, short = 'b')]
+            write_bitmap: bool,
+        },
+        /// Write all commits reachable from references into the commit-graph, along with the commits it already contains.
+        WriteCommitGraph {
+            /// Write a new file on top of a chain of commit-graph files, merging files at the top of the chain as needed.
+            #[clap(long)]
+            split: bool,
+            /// Never merge existing files of the chain when writing a split commit-graph.
+            #[clap(long, requires = "split", conflicts_with = "replace")]
+            no_merge: bool,
+            /// Replace the whole chain with a single file when writing a split commit-graph.
+            #[clap(long, requires = "split")]
+            replace: bool,
+            /// Compute changed-path Bloom filters for all new commits to accelerate path-limited history traversals.
+            #[clap(long)]
+            changed_paths: bool,
+        },
+        /// Remove loose objects which are unreachable and older than the grace period, or contained in a pack.
+        Prune {
+            /// Remove unreachable loose objects older than the given date, overriding `gc.pruneExpire`.
+            #[clap(long, value_parser = crate::shared::AsTime, value_name = "DATE")]
+            expire: Option<gix::date::Time>,
+            /// Only list the objects which would be removed.
+            #[clap(long, short = 'n')]
+            dry_run: bool,
+        },
     }
 }
 
@@ -517,6 +577,9 @@ pub mod index {
             /// Print various statistics to stderr
             #[clap(long, short = 's')]
             statistics: bool,
+            /// The git path specifications to list, or all entries if unset.
+            #[clap(value_parser = crate::shared::AsBString)]
+            pathspecs: Vec<gix::bstr::BString>,
         },
         /// Create an index from a tree-ish.
         #[clap(visible_alias = "read-tree")]